@@ -29,19 +29,21 @@ function read_socks_proxy() {
 function usage() {
     cat <<EOF
 Usage:
-  generate-btc-adapter-config [-b bitcoind_addr.conf] [-s socks_proxy.conf] -o ic-btc-adapter.json5
+  generate-btc-adapter-config [-b bitcoind_addr.conf] [-s socks_proxy.conf] [-n network] -o ic-btc-adapter.json5
 
   Generate the bitcoin adapter config.
 
   -b bitcoind_addr.conf: Optional, bitcoind address
   -s socks_proxy.conf: Optional, socks proxy url
   -m If set, we will use bitcoin mainnet dns seeds 
+  -n network: Optional, the test network to use if -m is not set (testnet, testnet4 or signet), defaults to testnet
   -o outfile: output ic-btc-adapter.json5 file
 EOF
 }
 
 MAINNET=false
-while getopts "b:mo:s:" OPT; do
+TEST_NETWORK="testnet"
+while getopts "b:mn:o:s:" OPT; do
     case "${OPT}" in
         b)
             BITCOIND_ADDR_FILE="${OPTARG}"
@@ -55,6 +57,9 @@ while getopts "b:mo:s:" OPT; do
         m)
             MAINNET=true
             ;;
+        n)
+            TEST_NETWORK="${OPTARG}"
+            ;;
         *)
             usage
             exit 1
@@ -69,11 +74,29 @@ if [ "${SOCKS_FILE}" != "" -a -e "${SOCKS_FILE}" ]; then
     read_socks_proxy "${SOCKS_FILE}"
 fi
 
-BITCOIN_NETWORK='"testnet"'
-DNS_SEEDS='"testnet-seed.bitcoin.jonasschnelli.ch",
+case "${TEST_NETWORK}" in
+    testnet)
+        BITCOIN_NETWORK='"testnet"'
+        DNS_SEEDS='"testnet-seed.bitcoin.jonasschnelli.ch",
             "seed.tbtc.petertodd.org",
             "seed.testnet.bitcoin.sprovoost.nl",
             "testnet-seed.bluematt.me"'
+        ;;
+    testnet4)
+        BITCOIN_NETWORK='"testnet4"'
+        DNS_SEEDS='"seed.testnet4.bitcoin.sprovoost.nl",
+            "seed.testnet4.wiz.biz"'
+        ;;
+    signet)
+        BITCOIN_NETWORK='"signet"'
+        DNS_SEEDS='"seed.signet.bitcoin.sprovoost.nl",
+            "seed.signet.achownodes.xyz"'
+        ;;
+    *)
+        usage
+        exit 1
+        ;;
+esac
 
 if [ "$MAINNET" = true ]; then
     BITCOIN_NETWORK='"bitcoin"'
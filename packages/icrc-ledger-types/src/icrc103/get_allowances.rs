@@ -0,0 +1,34 @@
+use candid::{CandidType, Deserialize, Nat};
+use serde::Serialize;
+
+use super::super::icrc1::account::Account;
+
+/// The arguments for the
+/// [ICRC-103 `icrc103_get_allowances`](https://github.com/dfinity/ICRC/blob/main/ICRCs/ICRC-103/ICRC-103.md)
+/// endpoint.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct GetAllowancesArgs {
+    /// The account whose approvals are listed. Defaults to the caller's default account.
+    pub from_account: Option<Account>,
+    /// If set, only allowances with a spender greater than this one are returned.
+    pub prev_spender: Option<Account>,
+    /// The maximum number of allowances to return.
+    pub take: Option<Nat>,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Allowance {
+    pub from_account: Account,
+    pub to_spender: Account,
+    pub allowance: Nat,
+    #[serde(default)]
+    pub expires_at: Option<u64>,
+}
+
+pub type Allowances = Vec<Allowance>;
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum GetAllowancesError {
+    AccessDenied { reason: String },
+    GenericError { error_code: Nat, message: String },
+}
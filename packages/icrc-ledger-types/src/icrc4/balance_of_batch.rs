@@ -0,0 +1,21 @@
+use candid::{CandidType, Deserialize, Nat};
+use serde::Serialize;
+
+use super::super::icrc1::account::Account;
+
+/// The arguments for the
+/// [ICRC-4 `icrc4_balance_of_batch`](https://github.com/dfinity/ICRC/blob/main/ICRCs/ICRC-4/ICRC-4.md)
+/// endpoint.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BalanceQueryArgs {
+    pub accounts: Vec<Account>,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct AccountBalance {
+    pub account: Account,
+    pub balance: Nat,
+}
+
+/// The balances of the queried accounts, in the order of the arguments.
+pub type BalanceQueryResult = Vec<AccountBalance>;
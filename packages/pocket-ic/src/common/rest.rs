@@ -15,6 +15,8 @@ use std::path::PathBuf;
 
 pub type InstanceId = usize;
 
+pub type SnapshotId = usize;
+
 #[derive(Debug, Clone, Eq, Hash, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct AutoProgressConfig {
     pub artificial_delay_ms: Option<u64>,
@@ -78,6 +80,11 @@ pub struct RawTime {
     pub nanos_since_epoch: u64,
 }
 
+#[derive(Clone, Serialize, Deserialize, Debug, Copy, JsonSchema)]
+pub struct RawSnapshotId {
+    pub snapshot_id: SnapshotId,
+}
+
 /// Relevant for calls to the management canister. If a subnet ID is
 /// provided, the call will be sent to the management canister of that subnet.
 /// If a canister ID is provided, the call will be sent to the management
@@ -154,6 +161,24 @@ pub struct RawSetStableMemory {
     pub blob_id: BlobId,
 }
 
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct RawStreamCanisterLogs {
+    #[serde(deserialize_with = "base64::deserialize")]
+    #[serde(serialize_with = "base64::serialize")]
+    pub canister_id: Vec<u8>,
+    /// Only log records with an index at least `start_idx` are streamed.
+    pub start_idx: Option<u64>,
+}
+
+#[derive(Clone, Serialize, Eq, PartialEq, Deserialize, Debug, JsonSchema)]
+pub struct RawCanisterLogRecord {
+    pub idx: u64,
+    pub timestamp_nanos: u64,
+    #[serde(deserialize_with = "base64::deserialize")]
+    #[serde(serialize_with = "base64::serialize")]
+    pub content: Vec<u8>,
+}
+
 #[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
 pub struct RawStableMemory {
     #[serde(deserialize_with = "base64::deserialize")]
@@ -468,6 +493,13 @@ pub struct InstanceConfig {
     pub nonmainnet_features: bool,
     pub log_level: Option<String>,
     pub bitcoind_addr: Option<SocketAddr>,
+    /// If set, the instance is created from a copy of the given snapshot
+    /// (taken by `/instances/<instance_id>/snapshot`) and `subnet_config_set` is ignored.
+    pub snapshot_id: Option<SnapshotId>,
+    /// If set, the instructions executed by each Wasm function are recorded and
+    /// one profile per message execution is written to this directory (on the
+    /// machine running the PocketIC server) in the folded stack format.
+    pub instruction_profile_dir: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone, Eq, Hash, PartialEq, Serialize, Deserialize, Default, JsonSchema)]
@@ -784,6 +816,14 @@ pub struct CanisterHttpHeader {
     pub value: String,
 }
 
+#[derive(
+    Clone, Copy, Serialize, Deserialize, Debug, Hash, Eq, PartialEq, Ord, PartialOrd, JsonSchema,
+)]
+pub struct CanisterHttpResponseRange {
+    pub offset: u64,
+    pub length: u64,
+}
+
 #[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
 pub struct RawCanisterHttpRequest {
     pub subnet_id: RawSubnetId,
@@ -795,6 +835,8 @@ pub struct RawCanisterHttpRequest {
     #[serde(serialize_with = "base64::serialize")]
     pub body: Vec<u8>,
     pub max_response_bytes: Option<u64>,
+    #[serde(default)]
+    pub response_range: Option<CanisterHttpResponseRange>,
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug, Hash, Eq, PartialEq, Ord, PartialOrd)]
@@ -808,6 +850,8 @@ pub struct CanisterHttpRequest {
     #[serde(serialize_with = "base64::serialize")]
     pub body: Vec<u8>,
     pub max_response_bytes: Option<u64>,
+    #[serde(default)]
+    pub response_range: Option<CanisterHttpResponseRange>,
 }
 
 impl From<RawCanisterHttpRequest> for CanisterHttpRequest {
@@ -822,6 +866,7 @@ impl From<RawCanisterHttpRequest> for CanisterHttpRequest {
             headers: raw_canister_http_request.headers,
             body: raw_canister_http_request.body,
             max_response_bytes: raw_canister_http_request.max_response_bytes,
+            response_range: raw_canister_http_request.response_range,
         }
     }
 }
@@ -836,6 +881,7 @@ impl From<CanisterHttpRequest> for RawCanisterHttpRequest {
             headers: canister_http_request.headers,
             body: canister_http_request.body,
             max_response_bytes: canister_http_request.max_response_bytes,
+            response_range: canister_http_request.response_range,
         }
     }
 }
@@ -35,8 +35,8 @@
 //!
 use crate::common::rest::{
     BlobCompression, BlobId, CanisterHttpRequest, DtsFlag, ExtendedSubnetConfigSet, HttpsConfig,
-    InstanceId, MockCanisterHttpResponse, RawEffectivePrincipal, RawMessageId, SubnetId,
-    SubnetKind, SubnetSpec, Topology,
+    InstanceId, MockCanisterHttpResponse, RawEffectivePrincipal, RawMessageId, SnapshotId,
+    SubnetId, SubnetKind, SubnetSpec, Topology,
 };
 pub use crate::management_canister::CanisterSettings;
 use crate::management_canister::{CanisterId, CanisterStatusResult};
@@ -86,6 +86,8 @@ pub struct PocketIcBuilder {
     nonmainnet_features: bool,
     log_level: Option<Level>,
     bitcoind_addr: Option<SocketAddr>,
+    snapshot_id: Option<SnapshotId>,
+    instruction_profile_dir: Option<PathBuf>,
 }
 
 #[allow(clippy::new_without_default)]
@@ -99,6 +101,8 @@ impl PocketIcBuilder {
             nonmainnet_features: false,
             log_level: None,
             bitcoind_addr: None,
+            snapshot_id: None,
+            instruction_profile_dir: None,
         }
     }
 
@@ -118,6 +122,8 @@ impl PocketIcBuilder {
             self.nonmainnet_features,
             self.log_level,
             self.bitcoind_addr,
+            self.snapshot_id,
+            self.instruction_profile_dir,
         )
     }
 
@@ -131,6 +137,8 @@ impl PocketIcBuilder {
             self.nonmainnet_features,
             self.log_level,
             self.bitcoind_addr,
+            self.snapshot_id,
+            self.instruction_profile_dir,
         )
         .await
     }
@@ -167,6 +175,24 @@ impl PocketIcBuilder {
         }
     }
 
+    /// Create the instance from a snapshot taken by `PocketIc::take_snapshot`
+    /// (on the same PocketIC server). The subnet configuration is then ignored.
+    pub fn with_snapshot(self, snapshot_id: SnapshotId) -> Self {
+        Self {
+            snapshot_id: Some(snapshot_id),
+            ..self
+        }
+    }
+
+    /// Record the instructions executed by each Wasm function and write one
+    /// profile per message execution in the folded stack format (as consumed
+    /// by e.g. `inferno-flamegraph`) to the given directory. Note that the
+    /// provided path must be accessible for the PocketIC server process.
+    pub fn with_instruction_profile_dir(mut self, instruction_profile_dir: PathBuf) -> Self {
+        self.instruction_profile_dir = Some(instruction_profile_dir);
+        self
+    }
+
     /// Add an empty NNS subnet
     pub fn with_nns_subnet(mut self) -> Self {
         let mut config = self.config.unwrap_or_default();
@@ -360,6 +386,8 @@ impl PocketIc {
         nonmainnet_features: bool,
         log_level: Option<Level>,
         bitcoind_addr: Option<SocketAddr>,
+        snapshot_id: Option<SnapshotId>,
+        instruction_profile_dir: Option<PathBuf>,
     ) -> Self {
         let (tx, rx) = channel();
         let thread = thread::spawn(move || {
@@ -380,6 +408,8 @@ impl PocketIc {
                 nonmainnet_features,
                 log_level,
                 bitcoind_addr,
+                snapshot_id,
+                instruction_profile_dir,
             )
             .await
         });
@@ -397,6 +427,24 @@ impl PocketIc {
         runtime.block_on(async { self.pocket_ic.topology().await })
     }
 
+    /// Takes a snapshot of the current state of this PocketIC instance.
+    /// New instances can be created from the returned snapshot
+    /// using `PocketIcBuilder::with_snapshot`.
+    #[instrument(skip(self), fields(instance_id=self.pocket_ic.instance_id))]
+    pub fn take_snapshot(&self) -> SnapshotId {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async { self.pocket_ic.take_snapshot().await })
+    }
+
+    /// Deletes a snapshot taken by `PocketIc::take_snapshot`
+    /// so that its state no longer occupies disk space on the server.
+    /// Instances created from the snapshot are not affected.
+    #[instrument(skip(self), fields(instance_id=self.pocket_ic.instance_id))]
+    pub fn delete_snapshot(&self, snapshot_id: SnapshotId) {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async { self.pocket_ic.delete_snapshot(snapshot_id).await })
+    }
+
     /// Upload and store a binary blob to the PocketIC server.
     #[instrument(ret(Display), skip(self, blob), fields(instance_id=self.pocket_ic.instance_id, blob_len = %blob.len(), compression = ?compression))]
     pub fn upload_blob(&self, blob: Vec<u8>, compression: BlobCompression) -> BlobId {
@@ -211,6 +211,7 @@ pub struct CanisterLogRecord {
 #[derive(CandidType, Deserialize, Debug, Clone)]
 pub struct FetchCanisterLogsResult {
     pub canister_log_records: Vec<CanisterLogRecord>,
+    pub next_idx: Option<u64>,
 }
 
 // canister http
@@ -3,13 +3,14 @@ use crate::common::rest::{
     CreateHttpGatewayResponse, CreateInstanceResponse, ExtendedSubnetConfigSet, HttpGatewayBackend,
     HttpGatewayConfig, HttpGatewayInfo, HttpsConfig, InstanceConfig, InstanceId,
     MockCanisterHttpResponse, RawAddCycles, RawCanisterCall, RawCanisterHttpRequest, RawCanisterId,
-    RawCanisterResult, RawCycles, RawEffectivePrincipal, RawMessageId, RawMockCanisterHttpResponse,
-    RawSetStableMemory, RawStableMemory, RawSubmitIngressResult, RawSubnetId, RawTime,
-    RawVerifyCanisterSigArg, RawWasmResult, SubnetId, Topology,
+    RawCanisterLogRecord, RawCanisterResult, RawCycles, RawEffectivePrincipal, RawMessageId,
+    RawMockCanisterHttpResponse, RawSetStableMemory, RawSnapshotId, RawStableMemory,
+    RawStreamCanisterLogs, RawSubmitIngressResult, RawSubnetId, RawTime, RawVerifyCanisterSigArg,
+    RawWasmResult, SnapshotId, SubnetId, Topology,
 };
 use crate::management_canister::{
     CanisterId, CanisterIdRecord, CanisterInstallMode, CanisterInstallModeUpgradeInner,
-    CanisterInstallModeUpgradeInnerWasmMemoryPersistenceInner, CanisterSettings,
+    CanisterInstallModeUpgradeInnerWasmMemoryPersistenceInner, CanisterLogRecord, CanisterSettings,
     CanisterStatusResult, ChunkHash, InstallChunkedCodeArgs, InstallCodeArgs,
     ProvisionalCreateCanisterWithCyclesArgs, UpdateSettingsArgs, UploadChunkArgs,
 };
@@ -25,6 +26,7 @@ use reqwest::{StatusCode, Url};
 use serde::{de::DeserializeOwned, Serialize};
 use sha2::{Digest, Sha256};
 use slog::Level;
+use std::collections::VecDeque;
 use std::fs::File;
 use std::future::Future;
 use std::net::SocketAddr;
@@ -94,10 +96,13 @@ impl PocketIc {
         nonmainnet_features: bool,
         log_level: Option<Level>,
         bitcoind_addr: Option<SocketAddr>,
+        snapshot_id: Option<SnapshotId>,
+        instruction_profile_dir: Option<PathBuf>,
     ) -> Self {
         let subnet_config_set = subnet_config_set.into();
-        if state_dir.is_none()
-            || File::open(state_dir.clone().unwrap().join("topology.json")).is_err()
+        if snapshot_id.is_none()
+            && (state_dir.is_none()
+                || File::open(state_dir.clone().unwrap().join("topology.json")).is_err())
         {
             subnet_config_set.validate().unwrap();
         }
@@ -107,6 +112,8 @@ impl PocketIc {
             nonmainnet_features,
             log_level: log_level.map(|l| l.to_string()),
             bitcoind_addr,
+            snapshot_id,
+            instruction_profile_dir,
         };
 
         let test_driver_pid = std::process::id();
@@ -144,6 +151,32 @@ impl PocketIc {
         self.get(endpoint).await
     }
 
+    /// Takes a snapshot of the current state of this PocketIC instance.
+    /// New instances can be created from the returned snapshot
+    /// using `PocketIcBuilder::with_snapshot`.
+    #[instrument(skip(self), fields(instance_id=self.instance_id))]
+    pub async fn take_snapshot(&self) -> SnapshotId {
+        let endpoint = "snapshot";
+        let RawSnapshotId { snapshot_id } = self.post(endpoint, "").await;
+        snapshot_id
+    }
+
+    /// Deletes a snapshot taken by `PocketIc::take_snapshot`
+    /// so that its state no longer occupies disk space on the server.
+    /// Instances created from the snapshot are not affected.
+    #[instrument(skip(self), fields(instance_id=self.instance_id))]
+    pub async fn delete_snapshot(&self, snapshot_id: SnapshotId) {
+        self.reqwest_client
+            .delete(
+                self.server_url
+                    .join(&format!("snapshots/{}", snapshot_id))
+                    .unwrap(),
+            )
+            .send()
+            .await
+            .expect("Failed to send delete request");
+    }
+
     /// Upload and store a binary blob to the PocketIC server.
     #[instrument(ret(Display), skip(self, blob), fields(instance_id=self.instance_id, blob_len = %blob.len(), compression = ?compression))]
     pub async fn upload_blob(&self, blob: Vec<u8>, compression: BlobCompression) -> BlobId {
@@ -202,6 +235,42 @@ impl PocketIc {
         blob
     }
 
+    /// Stream the log records of a canister starting at the log record with index `start_idx`
+    /// (or at the oldest log record in the canister log if `start_idx` is `None`).
+    /// New log records are returned as they are added to the canister log.
+    #[instrument(skip(self), fields(instance_id=self.instance_id, canister_id = %canister_id.to_string()))]
+    pub async fn stream_canister_logs(
+        &self,
+        canister_id: CanisterId,
+        start_idx: Option<u64>,
+    ) -> CanisterLogStream {
+        let url = self
+            .instance_url()
+            .join("read/stream_canister_logs")
+            .unwrap();
+        let response = self
+            .reqwest_client
+            .post(url)
+            .json(&RawStreamCanisterLogs {
+                canister_id: canister_id.as_slice().to_vec(),
+                start_idx,
+            })
+            .send()
+            .await
+            .expect("HTTP failure");
+        if !response.status().is_success() {
+            panic!(
+                "Failed to stream canister logs: {}",
+                response.text().await.unwrap_or_default()
+            );
+        }
+        CanisterLogStream {
+            response,
+            buffer: vec![],
+            records: VecDeque::new(),
+        }
+    }
+
     /// List all instances and their status.
     #[instrument(ret)]
     pub async fn list_instances() -> Vec<String> {
@@ -1232,6 +1301,41 @@ impl PocketIc {
     }
 }
 
+/// A stream of canister log records returned by `PocketIc::stream_canister_logs`.
+pub struct CanisterLogStream {
+    response: reqwest::Response,
+    buffer: Vec<u8>,
+    records: VecDeque<CanisterLogRecord>,
+}
+
+impl CanisterLogStream {
+    /// Returns the next canister log record, waiting for it to be added
+    /// to the canister log if necessary. Returns `None` if the stream
+    /// ended, e.g., because the canister or the PocketIC instance was deleted.
+    pub async fn next(&mut self) -> Option<CanisterLogRecord> {
+        loop {
+            if let Some(record) = self.records.pop_front() {
+                return Some(record);
+            }
+            let chunk = self.response.chunk().await.expect("HTTP failure")?;
+            self.buffer.extend_from_slice(&chunk);
+            while let Some(pos) = self.buffer.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = self.buffer.drain(..=pos).collect();
+                let RawCanisterLogRecord {
+                    idx,
+                    timestamp_nanos,
+                    content,
+                } = serde_json::from_slice(&line).expect("Failed to decode canister log record");
+                self.records.push_back(CanisterLogRecord {
+                    idx,
+                    timestamp_nanos,
+                    content,
+                });
+            }
+        }
+    }
+}
+
 /// Call a canister candid method, authenticated. The sender can be impersonated (i.e., the
 /// signature is not verified).
 /// PocketIC executes update calls synchronously, so there is no need to poll for the result.
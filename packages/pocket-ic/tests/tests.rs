@@ -666,6 +666,49 @@ async fn test_counter_canister_async() {
     pic.drop().await;
 }
 
+// Canister code printing a debug message on every call of the update method `log`.
+fn debug_print_wasm() -> Vec<u8> {
+    let wat = r#"
+    (module
+        (import "ic0" "debug_print" (func $debug_print (param i32 i32)))
+        (import "ic0" "msg_reply" (func $msg_reply))
+        (func $log
+            (call $debug_print (i32.const 0) (i32.const 5))
+            (call $msg_reply))
+        (memory $memory 1)
+        (data (i32.const 0) "hello")
+        (export "canister_update log" (func $log)))
+"#;
+    wat::parse_str(wat).unwrap()
+}
+
+#[tokio::test]
+async fn test_stream_canister_logs() {
+    let pic = pocket_ic::nonblocking::PocketIc::new().await;
+
+    let canister_id = pic.create_canister().await;
+    pic.add_cycles(canister_id, INIT_CYCLES).await;
+    pic.install_canister(canister_id, debug_print_wasm(), vec![], None)
+        .await;
+
+    let mut stream = pic.stream_canister_logs(canister_id, None).await;
+    for idx in 0..3 {
+        pic.update_call(canister_id, Principal::anonymous(), "log", vec![])
+            .await
+            .unwrap();
+        // The new log record is streamed as soon as it is added to the canister log.
+        let record = stream.next().await.unwrap();
+        assert_eq!(record.idx, idx);
+        assert_eq!(record.content, b"hello".to_vec());
+    }
+
+    // Streaming from a later index skips the older log records.
+    let mut stream = pic.stream_canister_logs(canister_id, Some(2)).await;
+    assert_eq!(stream.next().await.unwrap().idx, 2);
+
+    pic.drop().await;
+}
+
 // Canister code with a very large WASM.
 fn very_large_wasm(n: usize) -> Vec<u8> {
     const WASM_PAGE_SIZE: usize = 1 << 16;
@@ -15,7 +15,9 @@ use ic_logger::{warn, ReplicaLogger};
 use ic_metrics::MetricsRegistry;
 use ic_types::{
     artifact::CanisterHttpResponseId,
-    canister_http::{CanisterHttpResponse, CanisterHttpResponseShare},
+    canister_http::{
+        CanisterHttpResponse, CanisterHttpResponseArtifact, CanisterHttpResponseShare,
+    },
     crypto::CryptoHashOf,
 };
 use prometheus::IntCounter;
@@ -23,9 +25,13 @@ use prometheus::IntCounter;
 const POOL_CANISTER_HTTP: &str = "canister_http";
 const POOL_CANISTER_HTTP_CONTENT: &str = "canister_http_content";
 
-type ValidatedCanisterHttpPoolSection = PoolSection<CanisterHttpResponseShare, ()>;
+/// Shares along with the response that is gossiped with them, which is only
+/// the case for non-replicated requests.
+type ValidatedCanisterHttpPoolSection =
+    PoolSection<CanisterHttpResponseShare, Option<CanisterHttpResponse>>;
 
-type UnvalidatedCanisterHttpPoolSection = PoolSection<CanisterHttpResponseShare, ()>;
+type UnvalidatedCanisterHttpPoolSection =
+    PoolSection<CanisterHttpResponseShare, Option<CanisterHttpResponse>>;
 
 type ContentCanisterHttpPoolSection =
     PoolSection<CryptoHashOf<CanisterHttpResponse>, CanisterHttpResponse>;
@@ -87,23 +93,34 @@ impl CanisterHttpPool for CanisterHttpPoolImpl {
     fn lookup_validated(
         &self,
         msg_id: &CanisterHttpResponseId,
-    ) -> Option<CanisterHttpResponseShare> {
-        self.validated.get(msg_id).map(|()| msg_id.clone())
+    ) -> Option<CanisterHttpResponseArtifact> {
+        self.validated
+            .get(msg_id)
+            .map(|response| CanisterHttpResponseArtifact {
+                share: msg_id.clone(),
+                response: response.clone(),
+            })
     }
 
     fn lookup_unvalidated(
         &self,
         msg_id: &CanisterHttpResponseId,
-    ) -> Option<CanisterHttpResponseShare> {
-        self.unvalidated.get(msg_id).map(|()| msg_id.clone())
+    ) -> Option<CanisterHttpResponseArtifact> {
+        self.unvalidated
+            .get(msg_id)
+            .map(|response| CanisterHttpResponseArtifact {
+                share: msg_id.clone(),
+                response: response.clone(),
+            })
     }
 }
 
-impl MutablePool<CanisterHttpResponseShare> for CanisterHttpPoolImpl {
+impl MutablePool<CanisterHttpResponseArtifact> for CanisterHttpPoolImpl {
     type Mutations = CanisterHttpChangeSet;
 
-    fn insert(&mut self, artifact: UnvalidatedArtifact<CanisterHttpResponseShare>) {
-        self.unvalidated.insert(artifact.message, ());
+    fn insert(&mut self, artifact: UnvalidatedArtifact<CanisterHttpResponseArtifact>) {
+        self.unvalidated
+            .insert(artifact.message.share, artifact.message.response);
     }
 
     fn remove(&mut self, id: &CanisterHttpResponseId) {
@@ -113,23 +130,36 @@ impl MutablePool<CanisterHttpResponseShare> for CanisterHttpPoolImpl {
     fn apply(
         &mut self,
         change_set: CanisterHttpChangeSet,
-    ) -> ArtifactTransmits<CanisterHttpResponseShare> {
+    ) -> ArtifactTransmits<CanisterHttpResponseArtifact> {
         let changed = !change_set.is_empty();
         let mut transmits = vec![];
         for action in change_set {
             match action {
                 CanisterHttpChangeAction::AddToValidated(share, content) => {
                     transmits.push(ArtifactTransmit::Deliver(ArtifactWithOpt {
-                        artifact: share.clone(),
+                        artifact: CanisterHttpResponseArtifact {
+                            share: share.clone(),
+                            response: None,
+                        },
                         is_latency_sensitive: true,
                     }));
-                    self.validated.insert(share, ());
+                    self.validated.insert(share, None);
                     self.content
                         .insert(ic_types::crypto::crypto_hash(&content), content);
                 }
+                CanisterHttpChangeAction::AddToValidatedAndGossipResponse(share, response) => {
+                    transmits.push(ArtifactTransmit::Deliver(ArtifactWithOpt {
+                        artifact: CanisterHttpResponseArtifact {
+                            share: share.clone(),
+                            response: Some(response.clone()),
+                        },
+                        is_latency_sensitive: true,
+                    }));
+                    self.validated.insert(share, Some(response));
+                }
                 CanisterHttpChangeAction::MoveToValidated(share) => {
-                    if self.unvalidated.remove(&share).is_some() {
-                        self.validated.insert(share, ());
+                    if let Some(response) = self.unvalidated.remove(&share) {
+                        self.validated.insert(share, response);
                     }
                 }
                 CanisterHttpChangeAction::RemoveValidated(id) => {
@@ -160,9 +190,9 @@ impl MutablePool<CanisterHttpResponseShare> for CanisterHttpPoolImpl {
     }
 }
 
-impl ValidatedPoolReader<CanisterHttpResponseShare> for CanisterHttpPoolImpl {
-    fn get(&self, id: &CanisterHttpResponseId) -> Option<CanisterHttpResponseShare> {
-        self.validated.get(id).map(|()| id.clone())
+impl ValidatedPoolReader<CanisterHttpResponseArtifact> for CanisterHttpPoolImpl {
+    fn get(&self, id: &CanisterHttpResponseId) -> Option<CanisterHttpResponseArtifact> {
+        self.lookup_validated(id)
     }
 }
 
@@ -172,6 +202,15 @@ impl HasLabel for CanisterHttpResponse {
     }
 }
 
+impl HasLabel for Option<CanisterHttpResponse> {
+    fn label(&self) -> &str {
+        match self {
+            Some(response) => response.label(),
+            None => "",
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use ic_logger::replica_logger::no_op_logger;
@@ -190,10 +229,11 @@ mod tests {
     use super::*;
 
     fn to_unvalidated(
-        message: CanisterHttpResponseShare,
-    ) -> UnvalidatedArtifact<CanisterHttpResponseShare> {
-        UnvalidatedArtifact::<CanisterHttpResponseShare> {
-            message,
+        share: CanisterHttpResponseShare,
+        response: Option<CanisterHttpResponse>,
+    ) -> UnvalidatedArtifact<CanisterHttpResponseArtifact> {
+        UnvalidatedArtifact::<CanisterHttpResponseArtifact> {
+            message: CanisterHttpResponseArtifact { share, response },
             peer_id: node_test_id(0),
             timestamp: UNIX_EPOCH,
         }
@@ -226,10 +266,10 @@ mod tests {
         let share = fake_share(123);
         let id = share.clone();
 
-        pool.insert(to_unvalidated(share.clone()));
+        pool.insert(to_unvalidated(share.clone(), None));
         assert!(pool.get(&id).is_none());
 
-        assert_eq!(share, pool.lookup_unvalidated(&id).unwrap());
+        assert_eq!(share, pool.lookup_unvalidated(&id).unwrap().share);
 
         pool.remove(&id);
         assert!(pool.lookup_unvalidated(&id).is_none());
@@ -254,8 +294,8 @@ mod tests {
         assert!(matches!(&result.transmits[1], ArtifactTransmit::Deliver(_)));
         assert!(result.poll_immediately);
         assert_eq!(result.transmits.len(), 2);
-        assert_eq!(share, pool.lookup_validated(&id).unwrap());
-        assert_eq!(share, pool.get(&id).unwrap());
+        assert_eq!(share, pool.lookup_validated(&id).unwrap().share);
+        assert_eq!(share, pool.get(&id).unwrap().share);
         assert_eq!(
             response,
             pool.get_response_content_by_hash(&content_hash).unwrap()
@@ -283,7 +323,7 @@ mod tests {
         let share2 = fake_share(456);
         let id2 = share2.clone();
 
-        pool.insert(to_unvalidated(share1.clone()));
+        pool.insert(to_unvalidated(share1.clone(), None));
 
         let result = pool.apply(vec![
             CanisterHttpChangeAction::MoveToValidated(share2.clone()),
@@ -296,7 +336,7 @@ mod tests {
             .transmits
             .iter()
             .any(|x| matches!(x, ArtifactTransmit::Abort(_))));
-        assert_eq!(share1, pool.lookup_validated(&id1).unwrap());
+        assert_eq!(share1, pool.lookup_validated(&id1).unwrap().share);
     }
 
     #[test]
@@ -305,8 +345,8 @@ mod tests {
         let share = fake_share(123);
         let id = share.clone();
 
-        pool.insert(to_unvalidated(share.clone()));
-        assert_eq!(share, pool.lookup_unvalidated(&id).unwrap());
+        pool.insert(to_unvalidated(share.clone(), None));
+        assert_eq!(share, pool.lookup_unvalidated(&id).unwrap().share);
 
         let result = pool.apply(vec![CanisterHttpChangeAction::RemoveUnvalidated(
             id.clone(),
@@ -323,8 +363,8 @@ mod tests {
         let share = fake_share(123);
         let id = share.clone();
 
-        pool.insert(to_unvalidated(share.clone()));
-        assert_eq!(share, pool.lookup_unvalidated(&id).unwrap());
+        pool.insert(to_unvalidated(share.clone(), None));
+        assert_eq!(share, pool.lookup_unvalidated(&id).unwrap().share);
 
         let result = pool.apply(vec![CanisterHttpChangeAction::HandleInvalid(
             id.clone(),
@@ -335,4 +375,44 @@ mod tests {
         assert!(result.poll_immediately);
         assert!(result.transmits.is_empty());
     }
+
+    #[test]
+    fn test_canister_http_pool_gossip_response() {
+        let mut pool = CanisterHttpPoolImpl::new(MetricsRegistry::new(), no_op_logger());
+        let share = fake_share(123);
+        let id = share.clone();
+        let response = fake_response(123);
+
+        let result = pool.apply(vec![
+            CanisterHttpChangeAction::AddToValidatedAndGossipResponse(
+                share.clone(),
+                response.clone(),
+            ),
+        ]);
+
+        assert!(matches!(
+            &result.transmits[0],
+            ArtifactTransmit::Deliver(x) if x.artifact.response.as_ref() == Some(&response)
+        ));
+        assert_eq!(Some(response.clone()), pool.get(&id).unwrap().response);
+
+        // Received responses are kept when the share is validated.
+        let other_share = fake_share(456);
+        let other_id = other_share.clone();
+        let other_response = fake_response(456);
+        pool.insert(to_unvalidated(
+            other_share.clone(),
+            Some(other_response.clone()),
+        ));
+        assert_eq!(
+            Some(other_response.clone()),
+            pool.lookup_unvalidated(&other_id).unwrap().response
+        );
+
+        pool.apply(vec![CanisterHttpChangeAction::MoveToValidated(other_share)]);
+        assert_eq!(
+            Some(other_response),
+            pool.lookup_validated(&other_id).unwrap().response
+        );
+    }
 }
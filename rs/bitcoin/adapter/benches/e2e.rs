@@ -1,10 +1,11 @@
-use bitcoin::{Block, BlockHash, BlockHeader, Network};
+use bitcoin::{Block, BlockHash, BlockHeader};
 use criterion::{criterion_group, criterion_main, Criterion};
 use ic_btc_adapter::config::IncomingSource;
 use ic_btc_adapter::start_grpc_server;
 use ic_btc_adapter::AdapterState;
 use ic_btc_adapter::{
-    config::Config, BlockchainManagerRequest, BlockchainState, GetSuccessorsHandler,
+    config::{Config, Network},
+    BlockchainManagerRequest, BlockchainState, GetSuccessorsHandler,
 };
 use ic_btc_adapter_client::setup_bitcoin_adapter_clients;
 use ic_btc_adapter_test_utils::generate_headers;
@@ -306,8 +306,7 @@ fn format_addr(seed: &str, port: u16) -> String {
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::config::test::ConfigBuilder;
-    use bitcoin::Network;
+    use crate::config::{test::ConfigBuilder, Network};
     use ic_logger::replica_logger::no_op_logger;
     use std::str::FromStr;
 
@@ -490,6 +489,43 @@ mod test {
         ));
     }
 
+    /// This function ensures that signet seeds use the signet port and that the address
+    /// book only has enough addresses once it reaches the signet address limits.
+    #[test]
+    fn test_signet_address_limits() {
+        let config = ConfigBuilder::new()
+            .with_network(Network::Signet)
+            .with_dns_seeds(vec![String::from("127.0.0.1")])
+            .build();
+        let mut book = AddressBook::new(&config, no_op_logger());
+        let seed = book.pop_seed().expect("there should be 1 seed");
+        assert_eq!(seed.addr().port(), 38333);
+
+        let addresses: Vec<_> = (0..1000u16)
+            .map(|port| {
+                let socket = SocketAddr::from(([192, 168, 1, 1], port));
+                (
+                    0,
+                    Address::new(
+                        &socket,
+                        ServiceFlags::NETWORK | ServiceFlags::NETWORK_LIMITED,
+                    ),
+                )
+            })
+            .collect();
+        book.add_many(seed.addr(), &addresses[..99])
+            .expect("should not cause an error");
+        assert!(!book.has_enough_addresses());
+        book.add_many(seed.addr(), &addresses[99..100])
+            .expect("should not cause an error");
+        assert!(book.has_enough_addresses());
+        assert!(!book.has_max_address());
+        book.add_many(seed.addr(), &addresses)
+            .expect("should not cause an error");
+        assert!(book.has_max_address());
+        assert_eq!(book.size(), 1000);
+    }
+
     /// This function tests to ensure that when the seed queue is built and IPv6 only is enabled,
     /// IPv4 seeds are filtered out.
     #[test]
@@ -765,11 +765,10 @@ pub mod test {
     use super::*;
     use crate::{
         common::test_common::{TestChannel, TestState},
-        config::{test::ConfigBuilder, Config},
+        config::{test::ConfigBuilder, Config, Network},
     };
     use bitcoin::blockdata::constants::genesis_block;
     use bitcoin::consensus::deserialize;
-    use bitcoin::Network;
     use bitcoin::{
         network::message::NetworkMessage, network::message_blockdata::Inventory, BlockHash,
     };
@@ -1593,7 +1592,7 @@ pub mod test {
     /// Test to check that the retry queue is always used to retrieve the next block hash.
     #[test]
     fn test_get_next_block_hash_to_sync_always_retrieves_from_the_retry_queue() {
-        let genesis_block = genesis_block(Network::Regtest);
+        let genesis_block = genesis_block(bitcoin::Network::Regtest);
         let headers = generate_headers(
             genesis_block.block_hash(),
             genesis_block.header.time,
@@ -1633,7 +1632,7 @@ pub mod test {
     /// Tests if the cache is full and the retry queue is empty, then no blocks are returned.
     #[test]
     fn test_get_next_block_hash_to_sync_full_cache_and_empty_retry_queue() {
-        let genesis_block = genesis_block(Network::Regtest);
+        let genesis_block = genesis_block(bitcoin::Network::Regtest);
         let headers = generate_headers(
             genesis_block.block_hash(),
             genesis_block.header.time,
@@ -1653,7 +1652,7 @@ pub mod test {
     /// is empty.
     #[test]
     fn test_get_next_block_hash_to_sync_cache_is_not_full_and_empty_retry_queue() {
-        let genesis_block = genesis_block(Network::Regtest);
+        let genesis_block = genesis_block(bitcoin::Network::Regtest);
         let headers = generate_headers(
             genesis_block.block_hash(),
             genesis_block.header.time,
@@ -54,8 +54,7 @@ impl Cli {
 #[cfg(test)]
 pub mod test {
     use super::*;
-    use crate::config::IncomingSource;
-    use bitcoin::Network;
+    use crate::config::{IncomingSource, Network};
     use std::io::Write;
     use std::path::PathBuf;
     use std::str::FromStr;
@@ -94,6 +93,14 @@ pub mod test {
         "ipv6_only": true    
     }"#;
 
+    const TESTNET4_CONFIG: &str = r#"{
+        "network": "testnet4",
+        "dns_seeds": [
+            "seed.testnet4.bitcoin.sprovoost.nl",
+            "seed.testnet4.wiz.biz"
+        ]
+    }"#;
+
     const TESTNET_BAD_SOCKS_CONFIG: &str = r#"{
         "network": "testnet",
         "socks_proxy": "socks5.notaproxy.com"        
@@ -180,4 +187,19 @@ pub mod test {
             IncomingSource::Path(PathBuf::from("/tmp/ic-btc-adapter.socket"))
         );
     }
+
+    #[test]
+    fn test_cli_get_config_good_testnet4_json() {
+        let mut tmpfile = NamedTempFile::new().expect("Failed to create tmp file");
+        writeln!(tmpfile, "{}", TESTNET4_CONFIG).expect("Failed to write to tmp file");
+        let cli = Cli {
+            config: tmpfile.path().to_owned(),
+        };
+        let result = cli.get_config();
+        let config = result.unwrap();
+        assert_eq!(config.network, Network::Testnet4);
+        assert_eq!(config.network_port(), 48333);
+        assert_eq!(config.address_limits, (100, 1000));
+        assert_eq!(config.dns_seeds.len(), 2);
+    }
 }
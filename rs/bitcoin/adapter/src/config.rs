@@ -1,9 +1,10 @@
-use bitcoin::Network;
 use ic_config::logger::Config as LoggerConfig;
 use serde::{Deserialize, Serialize};
 use std::net::SocketAddr;
 use std::path::PathBuf;
 
+pub use ic_btc_validation::Network;
+
 #[derive(Clone, Eq, PartialEq, Debug, Default, Deserialize, Serialize)]
 /// The source of the unix domain socket to be used for inter-process
 /// communication.
@@ -18,7 +19,7 @@ pub enum IncomingSource {
 /// This struct contains configuration options for the BTC Adapter.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Config {
-    /// The type of Bitcoin network we plan to communicate to (e.g. Mainnet, Testnet, etc.).
+    /// The type of Bitcoin network we plan to communicate to (e.g. Mainnet, Testnet4, etc.).
     pub network: Network,
     /// A list of DNS seeds for address discovery.
     #[serde(default)]
@@ -61,8 +62,7 @@ fn default_idle_seconds() -> u64 {
 pub(crate) fn address_limits(network: Network) -> (usize, usize) {
     match network {
         Network::Bitcoin => (500, 2000),
-        Network::Testnet => (100, 1000),
-        Network::Signet => (1, 1),
+        Network::Testnet | Network::Testnet4 | Network::Signet => (100, 1000),
         Network::Regtest => (1, 1),
     }
 }
@@ -73,7 +73,9 @@ impl Config {
         match self.network {
             Network::Bitcoin => 8333,
             Network::Testnet => 18333,
-            _ => 8333,
+            Network::Testnet4 => 48333,
+            Network::Signet => 38333,
+            Network::Regtest => 8333,
         }
     }
 }
@@ -684,8 +684,8 @@ fn connection_limits(address_book: &AddressBook) -> (usize, usize) {
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::config::test::ConfigBuilder;
-    use bitcoin::{network::constants::ServiceFlags, Network};
+    use crate::config::{test::ConfigBuilder, Network};
+    use bitcoin::network::constants::ServiceFlags;
     use ic_logger::replica_logger::no_op_logger;
     use ic_metrics::MetricsRegistry;
     use std::str::FromStr;
@@ -803,17 +803,17 @@ mod test {
             } = config;
 
             let adapter_address = SocketAddr::from_str("0.0.0.0:8333").expect("invalid addr");
-            let address_2 = SocketAddr::from_str("192.168.1.1:8333").expect("invalid addr");
             let now = SystemTime::now();
             let since_epoch = now
                 .duration_since(UNIX_EPOCH)
                 .expect("time went backwards")
                 .as_secs() as u32;
             let services = ServiceFlags::NETWORK | ServiceFlags::NETWORK_LIMITED;
-            let addresses = vec![
-                (since_epoch, Address::new(&address, services)),
-                (since_epoch, Address::new(&address_2, services)),
-            ];
+            // Enough addresses to end the initial address discovery on every network.
+            let addresses = std::iter::once(address)
+                .chain((0..100u16).map(|port| SocketAddr::from(([192, 168, 1, 1], 8333 + port))))
+                .map(|address| (since_epoch, Address::new(&address, services)))
+                .collect::<Vec<_>>();
 
             stream_event_sender
                 .send(StreamEvent {
@@ -864,11 +864,11 @@ mod test {
         })
     }
 
-    /// This test is used to walk through the initial address discovery process.
-    #[tokio::test]
-    async fn test_initial_address_discovery_lifecycle() {
+    /// Walks through the initial address discovery process on the given network,
+    /// which ends once the address book holds `expected_addresses` addresses.
+    async fn initial_address_discovery_lifecycle(network: Network, expected_addresses: usize) {
         let config = ConfigBuilder::new()
-            .with_network(Network::Signet)
+            .with_network(network)
             .with_dns_seeds(vec![String::from("127.0.0.1")])
             .build();
         let (network_message_sender, mut network_message_receiver) =
@@ -879,7 +879,7 @@ mod test {
             network_message_sender,
             RouterMetrics::new(&MetricsRegistry::default()),
         );
-        let addr = SocketAddr::from_str("127.0.0.1:8333").expect("invalid address");
+        let addr = SocketAddr::from(([127, 0, 0, 1], config.network_port()));
         assert!(manager.initial_address_discovery);
         assert_eq!(manager.current_height, 0);
         assert_eq!(
@@ -911,8 +911,9 @@ mod test {
         assert_eq!(manager.current_height, 1);
         assert_eq!(
             manager.address_book.size(),
-            1,
-            "Expected to have found the 1 address needed"
+            expected_addresses,
+            "Expected to have found the {} addresses needed",
+            expected_addresses
         );
         assert!(manager.address_book.has_enough_addresses());
         let conn = manager
@@ -924,6 +925,62 @@ mod test {
         assert_eq!(manager.get_max_number_of_connections(), 5);
     }
 
+    /// This test is used to walk through the initial address discovery process.
+    #[tokio::test]
+    async fn test_initial_address_discovery_lifecycle() {
+        initial_address_discovery_lifecycle(Network::Signet, 100).await;
+    }
+
+    /// Regtest ends the initial address discovery after a single address.
+    #[tokio::test]
+    async fn test_initial_address_discovery_lifecycle_regtest() {
+        initial_address_discovery_lifecycle(Network::Regtest, 1).await;
+    }
+
+    /// This test checks that signet only ends the initial address discovery once
+    /// enough addresses for its address limits have been discovered.
+    #[test]
+    fn test_signet_initial_address_discovery() {
+        let config = ConfigBuilder::new()
+            .with_network(Network::Signet)
+            .with_dns_seeds(vec![String::from("127.0.0.1")])
+            .build();
+        let (network_message_sender, _network_message_receiver) =
+            channel::<(SocketAddr, NetworkMessage)>(DEFAULT_CHANNEL_BUFFER_SIZE);
+        let mut manager = ConnectionManager::new(
+            &config,
+            no_op_logger(),
+            network_message_sender,
+            RouterMetrics::new(&MetricsRegistry::default()),
+        );
+        let seed = SocketAddr::from_str("127.0.0.1:38333").expect("invalid address");
+        let services = ServiceFlags::NETWORK | ServiceFlags::NETWORK_LIMITED;
+        let addresses: Vec<_> = (0..100u16)
+            .map(|port| {
+                let address = SocketAddr::from(([192, 168, 1, 1], port));
+                (0, Address::new(&address, services))
+            })
+            .collect();
+        assert!(manager.initial_address_discovery);
+
+        // A single seed reply does not suffice to end the address discovery on signet.
+        manager
+            .process_addr_message(&seed, &addresses[..2])
+            .expect("should not cause an error");
+        assert!(manager.initial_address_discovery);
+        assert_eq!(
+            manager.get_max_number_of_connections(),
+            MAX_CONNECTIONS_DURING_ADDRESS_DISCOVERY
+        );
+
+        manager
+            .process_addr_message(&seed, &addresses)
+            .expect("should not cause an error");
+        assert_eq!(manager.address_book.size(), 100);
+        assert!(!manager.initial_address_discovery);
+        assert_eq!(manager.get_max_number_of_connections(), 5);
+    }
+
     #[test]
     fn test_flag_version_handshake_timeouts() {
         let runtime = tokio::runtime::Runtime::new().expect("runtime err");
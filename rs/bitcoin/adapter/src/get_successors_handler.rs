@@ -3,14 +3,16 @@ use std::{
     sync::{Arc, Mutex},
 };
 
-use bitcoin::{Block, BlockHash, BlockHeader, Network};
+use bitcoin::{Block, BlockHash, BlockHeader};
 use ic_metrics::MetricsRegistry;
 use tokio::sync::mpsc::Sender;
 use tonic::Status;
 
 use crate::{
-    common::BlockHeight, config::Config, metrics::GetSuccessorMetrics, BlockchainManagerRequest,
-    BlockchainState,
+    common::BlockHeight,
+    config::{Config, Network},
+    metrics::GetSuccessorMetrics,
+    BlockchainManagerRequest, BlockchainState,
 };
 
 // Max size of the `GetSuccessorsResponse` message.
@@ -239,7 +241,7 @@ fn get_next_headers(
 fn are_multiple_blocks_allowed(network: Network, anchor_height: BlockHeight) -> bool {
     match network {
         Network::Bitcoin => anchor_height <= MAINNET_MAX_MULTI_BLOCK_ANCHOR_HEIGHT,
-        Network::Testnet | Network::Signet | Network::Regtest => true,
+        Network::Testnet | Network::Testnet4 | Network::Signet | Network::Regtest => true,
     }
 }
 
@@ -249,7 +251,6 @@ mod test {
 
     use std::sync::{Arc, Mutex};
 
-    use bitcoin::Network;
     use ic_metrics::MetricsRegistry;
     use tokio::sync::mpsc::channel;
 
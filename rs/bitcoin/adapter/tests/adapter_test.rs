@@ -2,7 +2,7 @@ use bitcoin::{consensus::encode::deserialize, Address, Amount, Block, BlockHash}
 use bitcoincore_rpc::{bitcoincore_rpc_json::CreateRawTransactionInput, Auth, Client, RpcApi};
 use bitcoind::{BitcoinD, Conf, P2P};
 use ic_btc_adapter::{
-    config::{Config, IncomingSource},
+    config::{self, Config, IncomingSource},
     start_server,
 };
 use ic_btc_adapter_client::setup_bitcoin_adapter_clients;
@@ -92,7 +92,7 @@ fn start_adapter(
     rt_handle: &tokio::runtime::Handle,
     nodes: Vec<SocketAddr>,
     uds_path: &Path,
-    network: bitcoin::Network,
+    network: config::Network,
 ) {
     let config = Config {
         network,
@@ -157,7 +157,7 @@ fn start_adapter_and_client(
     rt: &Runtime,
     urls: Vec<SocketAddr>,
     logger: ReplicaLogger,
-    network: bitcoin::Network,
+    network: config::Network,
 ) -> (BitcoinAdapterClient, TempPath) {
     let metrics_registry = MetricsRegistry::new();
     let res = Builder::new()
@@ -495,7 +495,7 @@ fn test_receives_blocks() {
         &rt,
         vec![SocketAddr::V4(get_bitcoind_url(&bitcoind).unwrap())],
         logger,
-        bitcoin::Network::Regtest,
+        config::Network::Regtest,
     );
 
     let blocks = sync_until_end_block(&adapter_client, &client, 0, &mut vec![], 15);
@@ -557,7 +557,7 @@ fn test_connection_to_multiple_peers() {
         &rt,
         vec![url1, url2, url3],
         logger,
-        bitcoin::Network::Regtest,
+        config::Network::Regtest,
     );
 
     wait_for_connection(&client1, 3);
@@ -576,7 +576,7 @@ fn test_receives_new_3rd_party_txs() {
         &rt,
         vec![SocketAddr::V4(get_bitcoind_url(&bitcoind).unwrap())],
         logger,
-        bitcoin::Network::Regtest,
+        config::Network::Regtest,
     );
 
     let (alice_client, bob_client, alice_address, bob_address) =
@@ -632,7 +632,7 @@ fn test_send_tx() {
         &rt,
         vec![SocketAddr::V4(get_bitcoind_url(&bitcoind).unwrap())],
         logger,
-        bitcoin::Network::Regtest,
+        config::Network::Regtest,
     );
 
     let (alice_client, bob_client, alice_address, bob_address) =
@@ -719,7 +719,7 @@ fn test_receives_blocks_from_forks() {
         &rt,
         vec![SocketAddr::V4(url1), SocketAddr::V4(url2)],
         logger,
-        bitcoin::Network::Regtest,
+        config::Network::Regtest,
     );
 
     // Connect the nodes and mine some shared blocks
@@ -787,7 +787,7 @@ fn test_bfs_order() {
         &rt,
         vec![SocketAddr::V4(url1), SocketAddr::V4(url2)],
         logger,
-        bitcoin::Network::Regtest,
+        config::Network::Regtest,
     );
 
     // Connect the nodes and mine some shared blocks
@@ -891,7 +891,7 @@ fn test_mainnet_data() {
 
     let rt = tokio::runtime::Runtime::new().unwrap();
     let (adapter_client, _path) =
-        start_adapter_and_client(&rt, vec![bitcoind_addr], logger, bitcoin::Network::Bitcoin);
+        start_adapter_and_client(&rt, vec![bitcoind_addr], logger, config::Network::Bitcoin);
     sync_headers_until_checkpoint(&adapter_client, genesis[..].to_vec());
 
     // Block 350,989's block hash.
@@ -926,7 +926,7 @@ fn test_testnet_data() {
 
     let rt = tokio::runtime::Runtime::new().unwrap();
     let (adapter_client, _path) =
-        start_adapter_and_client(&rt, vec![bitcoind_addr], logger, bitcoin::Network::Testnet);
+        start_adapter_and_client(&rt, vec![bitcoind_addr], logger, config::Network::Testnet);
     sync_headers_until_checkpoint(&adapter_client, genesis[..].to_vec());
 
     let anchor: BlockHash = "0000000000ec75f32a0805740a6fa1364cc1683e419e915d99892db97c3e80b2"
@@ -117,6 +117,7 @@ fn test_key_recovery() {
             status: 403,
             headers: vec![],
             body: br#"{"status": 403, "message": "Access Denied"}"#.to_vec(),
+            is_replicated: None,
         }
     });
 
@@ -141,6 +142,7 @@ fn test_key_recovery() {
             status: 200,
             headers: vec![],
             body: br#"{"externalId": "12356-abcde", "updatedAt": "2023-03-02T15:23:27+00:00", "transferReference":"0000000000000000000000000000000000000000000000000000000000000000:0"}"#.to_vec(),
+            is_replicated: None,
         }
     });
 
@@ -157,6 +159,7 @@ fn test_key_recovery() {
             status: 200,
             headers: vec![],
             body: br#"{"alerts": [{"alertLevel": "HIGH", "category": "C", "service": "S", "exposureType": "DIRECT"}]}"#.to_vec(),
+            is_replicated: None,
         }
     });
 
@@ -299,6 +299,22 @@ pub fn build_metadata(s: &CkBtcMinterState) -> String {
                         <th>Total BTC managed</th>
                         <td>{}</td>
                     </tr>
+                    <tr>
+                        <th>Available UTXOs</th>
+                        <td>{}</td>
+                    </tr>
+                    <tr>
+                        <th>UTXO consolidation threshold</th>
+                        <td>{}</td>
+                    </tr>
+                    <tr>
+                        <th>UTXO consolidation fees paid</th>
+                        <td>{}</td>
+                    </tr>
+                    <tr>
+                        <th>UTXO consolidation fees burned</th>
+                        <td>{}</td>
+                    </tr>
                 </tbody>
             </table>",
         s.btc_network,
@@ -315,7 +331,11 @@ pub fn build_metadata(s: &CkBtcMinterState) -> String {
             .unwrap_or_else(|| "N/A".to_string()),
         DisplayAmount(s.kyt_fee),
         DisplayAmount(s.retrieve_btc_min_amount),
-        DisplayAmount(get_total_btc_managed(s))
+        DisplayAmount(get_total_btc_managed(s)),
+        s.available_utxos.len(),
+        crate::UTXOS_CONSOLIDATION_THRESHOLD,
+        DisplayAmount(s.consolidation_fees_paid),
+        DisplayAmount(s.consolidation_fees_burned),
     )
 }
 
@@ -372,6 +392,20 @@ pub fn build_submitted_transactions(s: &CkBtcMinterState) -> String {
                     .unwrap();
 
                     write!(buf, "<td rowspan='{}'>", rowspan).unwrap();
+                    if tx.is_consolidation() {
+                        write!(
+                            buf,
+                            "UTXO consolidation of {} into {}",
+                            DisplayAmount(tx.used_utxos.iter().map(|u| u.value).sum::<u64>()),
+                            DisplayAmount(
+                                tx.change_output
+                                    .as_ref()
+                                    .map(|out| out.value)
+                                    .unwrap_or_default()
+                            ),
+                        )
+                        .unwrap();
+                    }
                     for req in &tx.requests {
                         write!(
                             buf,
@@ -68,6 +68,39 @@ pub const CKBTC_LEDGER_MEMO_SIZE: u16 = 80;
 /// when building transactions.
 pub const UTXOS_COUNT_THRESHOLD: usize = 1_000;
 
+/// The number of available UTXOs above which the minter starts
+/// consolidating its smallest UTXOs.
+pub const UTXOS_CONSOLIDATION_THRESHOLD: usize = 2_000;
+
+/// The maximum number of inputs of a UTXO consolidation transaction.
+pub const MAX_CONSOLIDATION_INPUTS: usize = 200;
+
+/// The minter consolidates UTXOs only if the median fee (in millisatoshi
+/// per vbyte) does not exceed this value.
+pub const MAX_CONSOLIDATION_FEE_PER_VBYTE: MillisatoshiPerByte = 10_000;
+
+/// The fee percentile the minter uses for UTXO consolidation transactions.
+const CONSOLIDATION_FEE_PERCENTILE: usize = 25;
+
+/// The subaccount of the minter that the ckBTC ledger uses as its fee
+/// collector.
+pub const LEDGER_FEE_SUBACCOUNT: [u8; 32] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x0f,
+    0xee,
+];
+
+/// Having a sequence number lower than (0xffffffff - 1) signals the use of replacement by fee.
+/// It allows us to increase the fee of a transaction already sent to the mempool.
+/// The rbf option is used in `resubmit_retrieve_btc`.
+/// https://github.com/bitcoin/bips/blob/master/bip-0125.mediawiki
+const SEQUENCE_RBF_ENABLED: u32 = 0xfffffffd;
+
+// The default dustRelayFee is 3 sat/vB,
+// which translates to a dust threshold of 546 satoshi for P2PKH outputs.
+// The threshold for other types is lower,
+// so we simply use 546 satoshi as the minimum amount per output.
+const MIN_OUTPUT_AMOUNT: u64 = 546;
+
 #[derive(Clone, Debug, Deserialize, serde::Serialize)]
 pub enum Priority {
     P0,
@@ -419,6 +452,219 @@ async fn submit_pending_requests() {
     }
 }
 
+/// Consolidates the smallest UTXOs of the minter into a single output on the
+/// main address if the minter holds too many UTXOs and the fees are low.
+///
+/// The Bitcoin fee of a consolidation transaction is deducted from the value
+/// of the consolidated UTXOs. To keep the BTC reserves backing the ckBTC
+/// supply, the minter burns the same amount of ckBTC from the ledger fee
+/// collector before sending the transaction and skips the consolidation if
+/// the burn fails.
+async fn consolidate_utxos() {
+    // We have at most one consolidation transaction awaiting finalization.
+    if state::read_state(|s| {
+        s.available_utxos.len() <= UTXOS_CONSOLIDATION_THRESHOLD
+            || s.submitted_transactions
+                .iter()
+                .any(|tx| tx.is_consolidation())
+    }) {
+        return;
+    }
+
+    // Refresh the fee percentiles.
+    if estimate_fee_per_vbyte().await.is_none() {
+        return;
+    }
+
+    let fee_millisatoshi_per_vbyte =
+        match state::read_state(|s| consolidation_fee_per_vbyte(&s.last_fee_per_vbyte)) {
+            Some(fee) => fee,
+            None => {
+                log!(
+                    P1,
+                    "[consolidate_utxos]: postponing UTXO consolidation until fees go down"
+                );
+                return;
+            }
+        };
+
+    let main_account = Account {
+        owner: ic_cdk::id(),
+        subaccount: None,
+    };
+
+    let ecdsa_public_key = updates::get_btc_address::init_ecdsa_public_key().await;
+    let main_address = address::account_to_bitcoin_address(&ecdsa_public_key, &main_account);
+
+    let maybe_sign_request = state::mutate_state(|s| {
+        let utxos = select_utxos_to_consolidate(
+            &mut s.available_utxos,
+            MAX_CONSOLIDATION_INPUTS,
+            fee_millisatoshi_per_vbyte,
+        );
+
+        if utxos.is_empty() {
+            return None;
+        }
+
+        match build_consolidation_transaction(&utxos, main_address, fee_millisatoshi_per_vbyte) {
+            Ok((unsigned_tx, change_output)) => Some(SignTxRequest {
+                key_name: s.ecdsa_key_name.clone(),
+                ecdsa_public_key,
+                change_output,
+                outpoint_account: filter_output_accounts(s, &unsigned_tx),
+                network: s.btc_network,
+                unsigned_tx,
+                requests: vec![],
+                utxos,
+            }),
+            Err(err) => {
+                log!(
+                    P0,
+                    "[consolidate_utxos]: failed to build a consolidation transaction for {} UTXOs: {:?}",
+                    utxos.len(),
+                    err
+                );
+                for utxo in utxos {
+                    assert!(s.available_utxos.insert(utxo));
+                }
+                None
+            }
+        }
+    });
+
+    let req = match maybe_sign_request {
+        Some(req) => req,
+        None => return,
+    };
+
+    log!(
+        P1,
+        "[consolidate_utxos]: signing a new consolidation transaction: {}",
+        hex::encode(tx::encode_into(&req.unsigned_tx, Vec::new()))
+    );
+
+    // This guard ensures that we return UTXOs back to the state if the
+    // signing or sending a transaction fails or panics.
+    let utxos_guard = guard(req.utxos, |utxos| {
+        undo_sign_request(vec![], utxos);
+    });
+
+    let fee = utxos_guard.iter().map(|utxo| utxo.value).sum::<u64>() - req.change_output.value;
+    let fee_to_burn = state::read_state(|s| s.consolidation_fee_to_burn(fee));
+    if fee_to_burn > 0 {
+        match burn_consolidation_fee(fee_to_burn).await {
+            Ok(block_index) => {
+                log!(
+                    P1,
+                    "[consolidate_utxos]: burned {} from the ledger fee collector at block {}",
+                    tx::DisplayAmount(fee_to_burn),
+                    block_index,
+                );
+                state::mutate_state(|s| {
+                    state::audit::burned_consolidation_fee(s, fee_to_burn, block_index)
+                });
+            }
+            Err(err) => {
+                log!(
+                    P0,
+                    "[consolidate_utxos]: failed to burn {} from the ledger fee collector: {}",
+                    tx::DisplayAmount(fee_to_burn),
+                    err
+                );
+                return;
+            }
+        }
+    }
+
+    let txid = req.unsigned_tx.txid();
+
+    let signed_tx = match sign_transaction(
+        req.key_name,
+        &req.ecdsa_public_key,
+        &req.outpoint_account,
+        req.unsigned_tx,
+    )
+    .await
+    {
+        Ok(signed_tx) => signed_tx,
+        Err(err) => {
+            log!(
+                P0,
+                "[consolidate_utxos]: failed to sign a BTC transaction: {}",
+                err
+            );
+            return;
+        }
+    };
+
+    match management::send_transaction(&signed_tx, req.network).await {
+        Ok(()) => {
+            log!(
+                P0,
+                "[consolidate_utxos]: sent transaction {} consolidating {} UTXOs into {}",
+                &txid,
+                utxos_guard.len(),
+                tx::DisplayAmount(req.change_output.value),
+            );
+
+            // Defuse the guard because we sent the transaction successfully.
+            let used_utxos = ScopeGuard::into_inner(utxos_guard);
+
+            state::mutate_state(|s| {
+                state::audit::sent_consolidation_transaction(
+                    s,
+                    state::SubmittedBtcTransaction {
+                        requests: vec![],
+                        txid,
+                        used_utxos,
+                        change_output: Some(req.change_output),
+                        submitted_at: ic_cdk::api::time(),
+                        fee_per_vbyte: Some(fee_millisatoshi_per_vbyte),
+                    },
+                );
+            });
+        }
+        Err(err) => {
+            log!(
+                P0,
+                "[consolidate_utxos]: failed to send a bitcoin transaction: {}",
+                err
+            );
+        }
+    }
+}
+
+/// Burns the given amount of ckBTC from the ledger fee collector and returns
+/// the index of the burn block.
+async fn burn_consolidation_fee(amount: u64) -> Result<u64, String> {
+    use icrc_ledger_client_cdk::CdkRuntime;
+    use icrc_ledger_client_cdk::ICRC1Client;
+    use icrc_ledger_types::icrc1::transfer::TransferArg;
+
+    let client = ICRC1Client {
+        runtime: CdkRuntime,
+        ledger_canister_id: state::read_state(|s| s.ledger_id.get().into()),
+    };
+    let memo = crate::memo::BurnMemo::Consolidation;
+    client
+        .transfer(TransferArg {
+            from_subaccount: Some(LEDGER_FEE_SUBACCOUNT),
+            to: Account {
+                owner: ic_cdk::id(),
+                subaccount: None,
+            },
+            fee: None,
+            created_at_time: None,
+            memo: Some(crate::memo::encode(&memo).into()),
+            amount: candid::Nat::from(amount),
+        })
+        .await
+        .map_err(|(code, msg)| format!("ledger call failed with code {}: {}", code, msg))?
+        .map_err(|err| format!("{:?}", err))
+        .map(|n| n.0.to_u64().expect("nat does not fit into u64"))
+}
+
 fn finalization_time_estimate(min_confirmations: u32, network: Network) -> Duration {
     Duration::from_nanos(
         min_confirmations as u64
@@ -620,27 +866,47 @@ async fn finalize_requests() {
     for (old_txid, submitted_tx) in maybe_finalized_transactions {
         let mut utxos: BTreeSet<_> = submitted_tx.used_utxos.iter().cloned().collect();
 
+        let current_fee_per_vbyte = if submitted_tx.is_consolidation() {
+            // Consolidation transactions are not urgent, we replace them only when fees are low.
+            match state::read_state(|s| consolidation_fee_per_vbyte(&s.last_fee_per_vbyte)) {
+                Some(fee) => fee,
+                None => {
+                    log!(
+                        P1,
+                        "[finalize_requests]: postponing the replacement of consolidation transaction {} until fees go down",
+                        &submitted_tx.txid
+                    );
+                    continue;
+                }
+            }
+        } else {
+            fee_per_vbyte
+        };
+
         let tx_fee_per_vbyte = match submitted_tx.fee_per_vbyte {
             Some(prev_fee) => {
                 // Ensure that the fee is at least min relay fee higher than the previous
                 // transaction fee to comply with BIP-125 (https://en.bitcoin.it/wiki/BIP_0125).
-                fee_per_vbyte.max(prev_fee + MIN_RELAY_FEE_PER_VBYTE)
+                current_fee_per_vbyte.max(prev_fee + MIN_RELAY_FEE_PER_VBYTE)
             }
-            None => fee_per_vbyte,
+            None => current_fee_per_vbyte,
         };
 
-        let outputs = submitted_tx
-            .requests
-            .iter()
-            .map(|req| (req.address.clone(), req.amount))
-            .collect();
+        let build_result = if submitted_tx.is_consolidation() {
+            let used_utxos: Vec<_> = std::mem::take(&mut utxos).into_iter().collect();
+            build_consolidation_transaction(&used_utxos, main_address.clone(), tx_fee_per_vbyte)
+                .map(|(unsigned_tx, change_output)| (unsigned_tx, change_output, used_utxos))
+        } else {
+            let outputs = submitted_tx
+                .requests
+                .iter()
+                .map(|req| (req.address.clone(), req.amount))
+                .collect();
 
-        let (unsigned_tx, change_output, used_utxos) = match build_unsigned_transaction(
-            &mut utxos,
-            outputs,
-            main_address.clone(),
-            tx_fee_per_vbyte,
-        ) {
+            build_unsigned_transaction(&mut utxos, outputs, main_address.clone(), tx_fee_per_vbyte)
+        };
+
+        let (unsigned_tx, change_output, used_utxos) = match build_result {
             Ok(tx) => tx,
             // If it's impossible to build a new transaction, the fees probably became too high.
             // Let's ignore this transaction and wait for fees to go down.
@@ -963,12 +1229,6 @@ pub fn build_unsigned_transaction(
 ) -> Result<(tx::UnsignedTransaction, state::ChangeOutput, Vec<Utxo>), BuildTxError> {
     assert!(!outputs.is_empty());
 
-    /// Having a sequence number lower than (0xffffffff - 1) signals the use of replacement by fee.
-    /// It allows us to increase the fee of a transaction already sent to the mempool.
-    /// The rbf option is used in `resubmit_retrieve_btc`.
-    /// https://github.com/bitcoin/bips/blob/master/bip-0125.mediawiki
-    const SEQUENCE_RBF_ENABLED: u32 = 0xfffffffd;
-
     let amount = outputs.iter().map(|(_, amount)| amount).sum::<u64>();
 
     let input_utxos = utxos_selection(amount, minter_utxos, outputs.len());
@@ -1037,11 +1297,6 @@ pub fn build_unsigned_transaction(
     }
 
     let fee_shares = distribute(fee + minter_fee, outputs.len() as u64);
-    // The default dustRelayFee is 3 sat/vB,
-    // which translates to a dust threshold of 546 satoshi for P2PKH outputs.
-    // The threshold for other types is lower,
-    // so we simply use 546 satoshi as the minimum amount per output.
-    const MIN_OUTPUT_AMOUNT: u64 = 546;
 
     for (output, fee_share) in unsigned_tx.outputs.iter_mut().zip(fee_shares.iter()) {
         if output.address != main_address {
@@ -1067,6 +1322,117 @@ pub fn build_unsigned_transaction(
     ))
 }
 
+/// Returns the fee (in millisatoshi per vbyte) for a UTXO consolidation
+/// transaction given the latest fee percentiles, or `None` if the fees are too
+/// high to consolidate UTXOs.
+fn consolidation_fee_per_vbyte(
+    fee_percentiles: &[MillisatoshiPerByte],
+) -> Option<MillisatoshiPerByte> {
+    let median_fee = *fee_percentiles.get(50)?;
+    if median_fee > MAX_CONSOLIDATION_FEE_PER_VBYTE {
+        return None;
+    }
+    let fee = fee_percentiles
+        .get(CONSOLIDATION_FEE_PERCENTILE)
+        .copied()
+        .unwrap_or(median_fee);
+    Some(fee.max(MIN_RELAY_FEE_PER_VBYTE))
+}
+
+/// Selects at most `max_inputs` of the smallest UTXOs worth consolidating and
+/// removes the selected UTXOs from the available set. UTXOs whose value does
+/// not cover the fee for spending them are never selected.
+///
+/// Returns an empty vector if there are less than two UTXOs to consolidate or
+/// if their total value does not cover the estimated transaction fee plus the
+/// minimum output amount, as building the transaction would fail.
+///
+/// POSTCONDITION: solution.is_empty() ⇒ available_utxos did not change.
+fn select_utxos_to_consolidate(
+    available_utxos: &mut BTreeSet<Utxo>,
+    max_inputs: usize,
+    fee_per_vbyte: u64,
+) -> Vec<Utxo> {
+    let input_fee = (tx_vsize_estimate(1, 0) - tx_vsize_estimate(0, 0)) * fee_per_vbyte / 1000;
+
+    let mut candidates: Vec<&Utxo> = available_utxos
+        .iter()
+        .filter(|u| u.value > input_fee)
+        .collect();
+
+    if candidates.len() < 2 {
+        return vec![];
+    }
+
+    candidates.sort_unstable_by_key(|u| u.value);
+    let solution: Vec<Utxo> = candidates.into_iter().take(max_inputs).cloned().collect();
+
+    let inputs_value = solution.iter().map(|u| u.value).sum::<u64>();
+    let fee = tx_vsize_estimate(solution.len() as u64, 1) * fee_per_vbyte / 1000;
+    if inputs_value <= fee + MIN_OUTPUT_AMOUNT {
+        return vec![];
+    }
+
+    for utxo in solution.iter() {
+        assert!(available_utxos.remove(utxo));
+    }
+
+    solution
+}
+
+/// Builds a transaction that moves the value of the given minter UTXOs to a
+/// single output on the minter main address. The minter pays the fee.
+///
+/// # Panics
+///
+/// This function panics if the `input_utxos` slice is empty as it indicates a
+/// bug in the caller's code.
+pub fn build_consolidation_transaction(
+    input_utxos: &[Utxo],
+    main_address: BitcoinAddress,
+    fee_per_vbyte: u64,
+) -> Result<(tx::UnsignedTransaction, state::ChangeOutput), BuildTxError> {
+    assert!(!input_utxos.is_empty());
+
+    let inputs_value = input_utxos.iter().map(|u| u.value).sum::<u64>();
+
+    let mut unsigned_tx = tx::UnsignedTransaction {
+        inputs: input_utxos
+            .iter()
+            .map(|utxo| tx::UnsignedInput {
+                previous_output: utxo.outpoint.clone(),
+                value: utxo.value,
+                sequence: SEQUENCE_RBF_ENABLED,
+            })
+            .collect(),
+        outputs: vec![tx::TxOut {
+            address: main_address,
+            value: inputs_value,
+        }],
+        lock_time: 0,
+    };
+
+    let tx_vsize = fake_sign(&unsigned_tx).vsize();
+    let fee = (tx_vsize as u64 * fee_per_vbyte) / 1000;
+
+    if inputs_value <= fee + MIN_OUTPUT_AMOUNT {
+        return Err(BuildTxError::AmountTooLow);
+    }
+
+    let change_output = state::ChangeOutput {
+        vout: 0,
+        value: inputs_value - fee,
+    };
+    unsigned_tx.outputs[0].value = change_output.value;
+
+    debug_assert_eq!(
+        inputs_value,
+        fee + unsigned_tx.outputs.iter().map(|u| u.value).sum::<u64>()
+    );
+
+    Ok((unsigned_tx, change_output))
+}
+
 /// Distributes an amount across the specified number of shares as fairly as
 /// possible.
 ///
@@ -1208,6 +1574,17 @@ pub fn timer() {
                 schedule_after(FEE_ESTIMATE_DELAY, TaskType::RefreshFeePercentiles);
             });
         }
+        TaskType::ConsolidateUtxos => {
+            ic_cdk::spawn(async {
+                const CONSOLIDATION_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+                let _enqueue_followup_guard = guard((), |_| {
+                    schedule_after(CONSOLIDATION_INTERVAL, TaskType::ConsolidateUtxos)
+                });
+
+                consolidate_utxos().await;
+            });
+        }
         TaskType::DistributeKytFee => {
             ic_cdk::spawn(async {
                 let _guard = match crate::guard::DistributeKytFeeGuard::new() {
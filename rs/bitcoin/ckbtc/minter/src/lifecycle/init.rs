@@ -25,13 +25,17 @@ pub enum BtcNetwork {
     Mainnet,
     Testnet,
     Regtest,
+    Testnet4,
+    Signet,
 }
 
+/// The Bitcoin canister serves all public test networks as `testnet`, so the
+/// minter talks to it in the same way for testnet, testnet4 and signet.
 impl From<BtcNetwork> for Network {
     fn from(network: BtcNetwork) -> Self {
         match network {
             BtcNetwork::Mainnet => Network::Mainnet,
-            BtcNetwork::Testnet => Network::Testnet,
+            BtcNetwork::Testnet | BtcNetwork::Testnet4 | BtcNetwork::Signet => Network::Testnet,
             BtcNetwork::Regtest => Network::Regtest,
         }
     }
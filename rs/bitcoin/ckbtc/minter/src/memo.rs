@@ -69,4 +69,8 @@ pub enum BurnMemo<'a> {
         /// The status of the KYT check.
         status: Option<Status>,
     },
+    #[n(1)]
+    /// The minter burned ledger fees to cover the Bitcoin fee of a UTXO
+    /// consolidation transaction.
+    Consolidation,
 }
@@ -207,6 +207,18 @@ pub fn encode_metrics(
         "The total amount of ckBTC that minter owes to the KYT canister.",
     )?;
 
+    metrics.encode_counter(
+        "ckbtc_minter_consolidation_fees_paid",
+        state::read_state(|s| s.consolidation_fees_paid) as f64,
+        "The total Bitcoin fee (in satoshi) paid for UTXO consolidation transactions.",
+    )?;
+
+    metrics.encode_counter(
+        "ckbtc_minter_consolidation_fees_burned",
+        state::read_state(|s| s.consolidation_fees_burned) as f64,
+        "The total amount of ckBTC burned from the ledger fee collector to cover UTXO consolidation fees.",
+    )?;
+
     Ok(())
 }
 
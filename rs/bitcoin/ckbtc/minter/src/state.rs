@@ -86,6 +86,23 @@ pub struct SubmittedBtcTransaction {
     pub fee_per_vbyte: Option<u64>,
 }
 
+impl SubmittedBtcTransaction {
+    /// Returns true if the transaction consolidates the minter's UTXOs rather
+    /// than serving retrieve_btc requests.
+    pub fn is_consolidation(&self) -> bool {
+        self.requests.is_empty()
+    }
+
+    /// Returns the Bitcoin fee of a consolidation transaction, i.e., the
+    /// difference between the value of its inputs and its only output.
+    fn consolidation_fee(&self) -> u64 {
+        debug_assert!(self.is_consolidation());
+        let inputs: u64 = self.used_utxos.iter().map(|utxo| utxo.value).sum();
+        let output = self.change_output.as_ref().map_or(0, |out| out.value);
+        inputs.saturating_sub(output)
+    }
+}
+
 /// Pairs a retrieve_btc request with its outcome.
 #[derive(Clone, Eq, PartialEq, Debug, Deserialize, Serialize)]
 pub struct FinalizedBtcRetrieval {
@@ -388,6 +405,14 @@ pub struct CkBtcMinterState {
 
     /// Map from burn block index to the the reimbursed request.
     pub reimbursed_transactions: BTreeMap<u64, ReimbursedDeposit>,
+
+    /// The total Bitcoin fee (in satoshi) paid for UTXO consolidation
+    /// transactions, including the fee increases of their replacements.
+    pub consolidation_fees_paid: u64,
+
+    /// The total amount of ckBTC (in satoshi) burned from the ledger fee
+    /// collector to cover the fees of UTXO consolidation transactions.
+    pub consolidation_fees_burned: u64,
 }
 
 #[derive(Clone, Eq, PartialEq, Debug, CandidType, Serialize, serde::Deserialize)]
@@ -876,6 +901,11 @@ impl CkBtcMinterState {
         // tx points to the old transaction now.
         debug_assert_eq!(&tx.txid, old_txid);
 
+        if tx.is_consolidation() {
+            let new_fee = self.submitted_transactions[pos].consolidation_fee();
+            self.consolidation_fees_paid += new_fee.saturating_sub(tx.consolidation_fee());
+        }
+
         self.stuck_transactions.push(tx);
         self.replacement_txid.insert(*old_txid, new_txid);
         self.rev_replacement_txid.insert(new_txid, *old_txid);
@@ -963,9 +993,28 @@ impl CkBtcMinterState {
             assert!(!self.has_pending_request(req.block_index));
             self.requests_in_flight.remove(&req.block_index);
         }
+        if tx.is_consolidation() {
+            self.consolidation_fees_paid += tx.consolidation_fee();
+        }
         self.submitted_transactions.push(tx);
     }
 
+    /// Returns the amount of ckBTC the minter has to burn from the ledger fee
+    /// collector before sending a consolidation transaction with the given fee.
+    ///
+    /// The amount covers the fee increases of replaced consolidation
+    /// transactions and is reduced by burns for transactions that the minter
+    /// failed to send.
+    pub fn consolidation_fee_to_burn(&self, fee: u64) -> u64 {
+        (self.consolidation_fees_paid + fee).saturating_sub(self.consolidation_fees_burned)
+    }
+
+    /// Records that the minter burned the given amount of ckBTC to cover the
+    /// fees of consolidation transactions.
+    pub fn burn_consolidation_fee(&mut self, amount: u64) {
+        self.consolidation_fees_burned += amount;
+    }
+
     /// Marks the specified retrieve_btc request as finalized.
     ///
     /// # Panics
@@ -1172,6 +1221,18 @@ impl CkBtcMinterState {
             "kyt_principal does not match"
         );
 
+        ensure_eq!(
+            self.consolidation_fees_paid,
+            other.consolidation_fees_paid,
+            "consolidation_fees_paid does not match"
+        );
+
+        ensure_eq!(
+            self.consolidation_fees_burned,
+            other.consolidation_fees_burned,
+            "consolidation_fees_burned does not match"
+        );
+
         ensure_eq!(
             self.retrieve_btc_account_to_block_indices,
             other.retrieve_btc_account_to_block_indices,
@@ -1267,6 +1328,8 @@ impl From<InitArgs> for CkBtcMinterState {
             quarantined_utxos: Default::default(),
             pending_reimbursements: Default::default(),
             reimbursed_transactions: Default::default(),
+            consolidation_fees_paid: 0,
+            consolidation_fees_burned: 0,
         }
     }
 }
@@ -83,6 +83,39 @@ pub enum Event {
         fee_per_vbyte: Option<u64>,
     },
 
+    /// Indicates that the minter sent out a new transaction consolidating
+    /// some of its UTXOs into a single output on the main address.
+    #[serde(rename = "sent_consolidation_transaction")]
+    SentConsolidationTransaction {
+        /// The Txid of the Bitcoin transaction.
+        #[serde(rename = "txid")]
+        txid: Txid,
+        /// UTXOs consolidated by the transaction.
+        #[serde(rename = "utxos")]
+        utxos: Vec<Utxo>,
+        /// The output with the consolidated value.
+        #[serde(rename = "change_output")]
+        change_output: ChangeOutput,
+        /// The IC time at which the minter submitted the transaction.
+        #[serde(rename = "submitted_at")]
+        submitted_at: u64,
+        /// The fee per vbyte (in millisatoshi) that we used for the transaction.
+        #[serde(rename = "fee")]
+        fee_per_vbyte: u64,
+    },
+
+    /// Indicates that the minter burned ckBTC from the ledger fee collector to
+    /// cover the Bitcoin fee of UTXO consolidation transactions.
+    #[serde(rename = "burned_consolidation_fee")]
+    BurnedConsolidationFee {
+        /// The token amount burned.
+        #[serde(rename = "amount")]
+        amount: u64,
+        /// The burn block on the ledger.
+        #[serde(rename = "block_index")]
+        block_index: u64,
+    },
+
     /// Indicates that the minter sent out a new transaction to replace an older transaction
     /// because the old transaction did not appear on the Bitcoin blockchain.
     #[serde(rename = "replaced_transaction")]
@@ -262,6 +295,30 @@ pub fn replay(mut events: impl Iterator<Item = Event>) -> Result<CkBtcMinterStat
                     submitted_at,
                 });
             }
+            Event::SentConsolidationTransaction {
+                txid,
+                utxos,
+                change_output,
+                submitted_at,
+                fee_per_vbyte,
+            } => {
+                for utxo in utxos.iter() {
+                    if !state.available_utxos.remove(utxo) {
+                        return Err(ReplayLogError::InconsistentLog(format!(
+                            "Attempted to consolidate an unavailable UTXO {:?}",
+                            utxo
+                        )));
+                    }
+                }
+                state.push_submitted_transaction(SubmittedBtcTransaction {
+                    requests: vec![],
+                    txid,
+                    used_utxos: utxos,
+                    fee_per_vbyte: Some(fee_per_vbyte),
+                    change_output: Some(change_output),
+                    submitted_at,
+                });
+            }
             Event::ReplacedBtcTransaction {
                 old_txid,
                 new_txid,
@@ -333,6 +390,9 @@ pub fn replay(mut events: impl Iterator<Item = Event>) -> Result<CkBtcMinterStat
                     return Err(ReplayLogError::InconsistentLog(format!("Attempted to distribute {amount} to {kyt_provider}, causing an overdraft of {overdraft}")));
                 }
             }
+            Event::BurnedConsolidationFee { amount, .. } => {
+                state.burn_consolidation_fee(amount);
+            }
             Event::RetrieveBtcKytFailed { kyt_provider, .. } => {
                 *state.owed_kyt_amount.entry(kyt_provider).or_insert(0) += state.kyt_fee;
             }
@@ -13,6 +13,7 @@ pub enum TaskType {
     ProcessLogic,
     RefreshFeePercentiles,
     DistributeKytFee,
+    ConsolidateUtxos,
 }
 
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Debug)]
@@ -1,6 +1,7 @@
 use crate::MINTER_FEE_CONSTANT;
 use crate::{
-    address::BitcoinAddress, build_unsigned_transaction, estimate_fee, fake_sign, greedy,
+    address::BitcoinAddress, build_consolidation_transaction, build_unsigned_transaction,
+    consolidation_fee_per_vbyte, estimate_fee, fake_sign, greedy, select_utxos_to_consolidate,
     signature::EncodedSignature, tx, BuildTxError,
 };
 use crate::{
@@ -373,6 +374,171 @@ fn test_no_dust_outputs() {
     assert_eq!(available_utxos.len(), 1);
 }
 
+#[test]
+fn test_build_consolidation_transaction() {
+    let utxos: Vec<_> = (1..=5).map(|i| dummy_utxo_from_value(i * 10_000)).collect();
+    let minter_addr = BitcoinAddress::P2wpkhV0([0; 20]);
+    let fee_per_vbyte = 2000;
+
+    let (tx, change_output) =
+        build_consolidation_transaction(&utxos, minter_addr.clone(), fee_per_vbyte)
+            .expect("failed to build a consolidation transaction");
+
+    let fee = fake_sign(&tx).vsize() as u64 * fee_per_vbyte / 1000;
+
+    assert_eq!(
+        tx.inputs
+            .iter()
+            .map(|input| input.previous_output.clone())
+            .collect::<Vec<_>>(),
+        utxos.iter().map(|u| u.outpoint.clone()).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        &tx.outputs,
+        &[tx::TxOut {
+            address: minter_addr.clone(),
+            value: 150_000 - fee,
+        }]
+    );
+    assert_eq!(
+        change_output,
+        ChangeOutput {
+            vout: 0,
+            value: 150_000 - fee,
+        }
+    );
+
+    assert_eq!(
+        build_consolidation_transaction(
+            &[dummy_utxo_from_value(300), dummy_utxo_from_value(301)],
+            minter_addr,
+            fee_per_vbyte
+        ),
+        Err(BuildTxError::AmountTooLow)
+    );
+}
+
+#[test]
+fn test_select_utxos_to_consolidate() {
+    let fee_per_vbyte = 10_000;
+    // Spending an input costs 68 vbytes * 10 sat/vbyte = 680 satoshi.
+    let mut available_utxos: BTreeSet<_> = [500, 680, 681, 1_000, 50_000, 2_000, 100_000]
+        .into_iter()
+        .map(dummy_utxo_from_value)
+        .collect();
+
+    let selected = select_utxos_to_consolidate(&mut available_utxos, 3, fee_per_vbyte);
+    assert_eq!(
+        selected.iter().map(|u| u.value).collect::<Vec<_>>(),
+        vec![681, 1_000, 2_000]
+    );
+    assert_eq!(
+        available_utxos
+            .iter()
+            .map(|u| u.value)
+            .collect::<BTreeSet<_>>(),
+        BTreeSet::from([500, 680, 50_000, 100_000])
+    );
+
+    // The selected UTXOs always make a valid consolidation transaction.
+    assert!(build_consolidation_transaction(
+        &selected,
+        BitcoinAddress::P2wpkhV0([0; 20]),
+        fee_per_vbyte
+    )
+    .is_ok());
+
+    // Not enough UTXOs worth consolidating.
+    let mut available_utxos: BTreeSet<_> = [500, 680, 50_000]
+        .into_iter()
+        .map(dummy_utxo_from_value)
+        .collect();
+    let before = available_utxos.clone();
+    assert_eq!(
+        select_utxos_to_consolidate(&mut available_utxos, 3, fee_per_vbyte),
+        vec![]
+    );
+    assert_eq!(available_utxos, before);
+
+    // The smallest UTXOs do not cover the transaction fee and the minimum output amount.
+    let mut available_utxos: BTreeSet<_> = [681, 690, 50_000]
+        .into_iter()
+        .map(dummy_utxo_from_value)
+        .collect();
+    let before = available_utxos.clone();
+    assert_eq!(
+        select_utxos_to_consolidate(&mut available_utxos, 2, fee_per_vbyte),
+        vec![]
+    );
+    assert_eq!(available_utxos, before);
+}
+
+#[test]
+fn test_consolidation_fee_per_vbyte() {
+    let percentiles: Vec<u64> = (0..100).map(|i| 1_000 + i * 100).collect();
+    assert_eq!(consolidation_fee_per_vbyte(&percentiles), Some(3_500));
+
+    // The fee is never below the minimum relay fee.
+    assert_eq!(
+        consolidation_fee_per_vbyte(&[1; 100]),
+        Some(crate::MIN_RELAY_FEE_PER_VBYTE)
+    );
+
+    // No consolidation if the median fee is too high.
+    let percentiles: Vec<u64> = (0..100)
+        .map(|i| crate::MAX_CONSOLIDATION_FEE_PER_VBYTE - 40 + i)
+        .collect();
+    assert_eq!(consolidation_fee_per_vbyte(&percentiles), None);
+
+    // No consolidation without fee percentiles.
+    assert_eq!(consolidation_fee_per_vbyte(&[]), None);
+}
+
+#[test]
+fn test_consolidation_fee_accounting() {
+    let mut state = CkBtcMinterState::from(InitArgs {
+        btc_network: Network::Regtest.into(),
+        ecdsa_key_name: "".to_string(),
+        retrieve_btc_min_amount: 0,
+        ledger_id: CanisterId::from_u64(42),
+        max_time_in_queue_nanos: 0,
+        min_confirmations: None,
+        mode: Mode::GeneralAvailability,
+        kyt_fee: None,
+        kyt_principal: None,
+    });
+    let used_utxos = vec![dummy_utxo_from_value(10_000), dummy_utxo_from_value(20_000)];
+    let consolidation_tx = |txid: [u8; 32], change_value: u64| SubmittedBtcTransaction {
+        requests: vec![],
+        txid: txid.into(),
+        used_utxos: used_utxos.clone(),
+        submitted_at: 0,
+        change_output: Some(ChangeOutput {
+            vout: 0,
+            value: change_value,
+        }),
+        fee_per_vbyte: Some(1_000),
+    };
+
+    // The minter burns the full fee before sending the first transaction.
+    assert_eq!(state.consolidation_fee_to_burn(1_000), 1_000);
+    state.burn_consolidation_fee(1_000);
+    state.push_submitted_transaction(consolidation_tx([1; 32], 29_000));
+    assert_eq!(state.consolidation_fees_paid, 1_000);
+    assert_eq!(state.consolidation_fee_to_burn(0), 0);
+
+    // The fee increase of a replacement is burned with the next consolidation.
+    state.replace_transaction(&[1; 32].into(), consolidation_tx([2; 32], 28_500));
+    assert_eq!(state.consolidation_fees_paid, 1_500);
+    assert_eq!(state.consolidation_fee_to_burn(1_000), 1_500);
+
+    // Burns for transactions that the minter failed to send are credited.
+    state.burn_consolidation_fee(1_500);
+    assert_eq!(state.consolidation_fees_burned, 2_500);
+    assert_eq!(state.consolidation_fee_to_burn(600), 0);
+    assert_eq!(state.consolidation_fee_to_burn(1_600), 600);
+}
+
 #[test]
 fn blocklist_is_sorted() {
     use crate::blocklist::BTC_ADDRESS_BLOCKLIST;
@@ -1240,3 +1406,18 @@ fn test_build_account_to_utxos_table_pagination() {
         assert!(!no_utxo_page.contains(&format!("{}", utxo.outpoint.txid)));
     }
 }
+
+#[test]
+fn test_public_test_networks_use_testnet() {
+    use crate::lifecycle::init::BtcNetwork as MinterNetwork;
+
+    for network in [
+        MinterNetwork::Testnet,
+        MinterNetwork::Testnet4,
+        MinterNetwork::Signet,
+    ] {
+        assert_eq!(Network::from(network), Network::Testnet);
+    }
+    assert_eq!(Network::from(MinterNetwork::Mainnet), Network::Mainnet);
+    assert_eq!(Network::from(MinterNetwork::Regtest), Network::Regtest);
+}
@@ -1,8 +1,8 @@
 use std::collections::HashMap;
 
-use bitcoin::{hashes::hex::FromHex, util::uint::Uint256, BlockHash, Network};
+use bitcoin::{hashes::hex::FromHex, util::uint::Uint256, BlockHash};
 
-use crate::BlockHeight;
+use crate::{BlockHeight, Network};
 
 /// Expected number of blocks for 2 weeks (2_016).
 pub const DIFFICULTY_ADJUSTMENT_INTERVAL: BlockHeight = 6 * 24 * 14;
@@ -10,6 +10,10 @@ pub const DIFFICULTY_ADJUSTMENT_INTERVAL: BlockHeight = 6 * 24 * 14;
 /// Needed to help test check for the 20 minute testnet/regtest rule
 pub const TEN_MINUTES: u32 = 60 * 10;
 
+/// The number of seconds that the first block of a difficulty adjustment period
+/// may be timestamped before its predecessor on networks enforcing BIP94.
+pub const MAX_TIMEWARP: u32 = 600;
+
 /// Represents approximately the number of blocks that will be created within one year.
 ///
 /// This number is determine by the following formula. A year approximately has 356.25 days. Assuming the
@@ -57,6 +61,18 @@ const TESTNET: &[(BlockHeight, &str)] = &[
     (546, "000000002a936ca763904c3c35fce2f3556c559c0214345d31b1bcebf76acb70")
 ];
 
+/// Bitcoin testnet4 checkpoints
+#[rustfmt::skip]
+const TESTNET4: &[(BlockHeight, &str)] = &[
+    (0, "00000000da84f2bafbbc53dee25a72ae507ff4914b867c565be350b0da8bf043"),
+];
+
+/// Bitcoin signet checkpoints
+#[rustfmt::skip]
+const SIGNET: &[(BlockHeight, &str)] = &[
+    (0, "00000008819873e925422c1ff0f99f7cc9bbb232af63a077a480a3633bee1ef6"),
+];
+
 /// Bitcoin mainnet maximum target value
 const BITCOIN_MAX_TARGET: Uint256 = Uint256([
     0x0000000000000000,
@@ -93,7 +109,7 @@ const SIGNET_MAX_TARGET: Uint256 = Uint256([
 pub fn max_target(network: &Network) -> Uint256 {
     match network {
         Network::Bitcoin => BITCOIN_MAX_TARGET,
-        Network::Testnet => TESTNET_MAX_TARGET,
+        Network::Testnet | Network::Testnet4 => TESTNET_MAX_TARGET,
         Network::Regtest => REGTEST_MAX_TARGET,
         Network::Signet => SIGNET_MAX_TARGET,
     }
@@ -103,16 +119,25 @@ pub fn max_target(network: &Network) -> Uint256 {
 /// readjusted in the network after a fixed time interval.
 pub fn no_pow_retargeting(network: &Network) -> bool {
     match network {
-        Network::Bitcoin | Network::Testnet | Network::Signet => false,
+        Network::Bitcoin | Network::Testnet | Network::Testnet4 | Network::Signet => false,
         Network::Regtest => true,
     }
 }
 
+/// Returns true iff the network enforces the BIP94 rules, i.e., the timewarp
+/// fix and difficulty adjustments based on the first block of the period.
+pub fn enforces_bip94(network: &Network) -> bool {
+    match network {
+        Network::Testnet4 => true,
+        Network::Bitcoin | Network::Testnet | Network::Signet | Network::Regtest => false,
+    }
+}
+
 /// Returns the PoW limit bits of the bitcoin network
 pub fn pow_limit_bits(network: &Network) -> u32 {
     match network {
         Network::Bitcoin => 0x1d00ffff,
-        Network::Testnet => 0x1d00ffff,
+        Network::Testnet | Network::Testnet4 => 0x1d00ffff,
         Network::Regtest => 0x207fffff,
         Network::Signet => 0x1e0377ae,
     }
@@ -123,7 +148,8 @@ pub fn checkpoints(network: &Network) -> HashMap<BlockHeight, BlockHash> {
     let points = match network {
         Network::Bitcoin => BITCOIN,
         Network::Testnet => TESTNET,
-        Network::Signet => &[],
+        Network::Testnet4 => TESTNET4,
+        Network::Signet => SIGNET,
         Network::Regtest => &[],
     };
     points
@@ -140,7 +166,8 @@ pub fn latest_checkpoint_height(network: &Network, current_height: BlockHeight)
     let points = match network {
         Network::Bitcoin => BITCOIN,
         Network::Testnet => TESTNET,
-        Network::Signet => &[],
+        Network::Testnet4 => TESTNET4,
+        Network::Signet => SIGNET,
         Network::Regtest => &[],
     };
 
@@ -155,7 +182,8 @@ pub fn last_checkpoint(network: &Network) -> Option<BlockHeight> {
     let points = match network {
         Network::Bitcoin => BITCOIN,
         Network::Testnet => TESTNET,
-        Network::Signet => &[],
+        Network::Testnet4 => TESTNET4,
+        Network::Signet => SIGNET,
         Network::Regtest => &[],
     };
 
@@ -203,6 +231,20 @@ pub mod test {
     #[test]
     fn test_last_checkpoint() {
         assert_eq!(last_checkpoint(&Network::Bitcoin), Some(704_256));
+        assert_eq!(last_checkpoint(&Network::Testnet4), Some(0));
+        assert_eq!(last_checkpoint(&Network::Signet), Some(0));
         assert_eq!(last_checkpoint(&Network::Regtest), None);
     }
+
+    #[test]
+    fn test_checkpoints_start_at_genesis() {
+        for network in [Network::Testnet4, Network::Signet] {
+            assert_eq!(
+                checkpoints(&network).get(&0),
+                Some(&network.genesis_block_header().block_hash()),
+                "{:?}",
+                network
+            );
+        }
+    }
 }
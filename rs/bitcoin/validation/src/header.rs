@@ -1,11 +1,12 @@
-use bitcoin::{util::uint::Uint256, BlockHash, BlockHeader, Network};
+use bitcoin::{util::uint::Uint256, BlockHash, BlockHeader};
 
 use crate::{
     constants::{
-        checkpoints, last_checkpoint, latest_checkpoint_height, max_target, no_pow_retargeting,
-        pow_limit_bits, BLOCKS_IN_ONE_YEAR, DIFFICULTY_ADJUSTMENT_INTERVAL, TEN_MINUTES,
+        checkpoints, enforces_bip94, last_checkpoint, latest_checkpoint_height, max_target,
+        no_pow_retargeting, pow_limit_bits, BLOCKS_IN_ONE_YEAR, DIFFICULTY_ADJUSTMENT_INTERVAL,
+        MAX_TIMEWARP, TEN_MINUTES,
     },
-    BlockHeight,
+    BlockHeight, Network,
 };
 
 /// An error thrown when trying to validate a header.
@@ -14,6 +15,9 @@ pub enum ValidateHeaderError {
     /// Used when the timestamp in the header is lower than
     /// the median of timestamps of past 11 headers.
     HeaderIsOld,
+    /// Used when the header is the first of a difficulty adjustment period and
+    /// its timestamp is too far before the previous header's timestamp (BIP94).
+    TimewarpAttack,
     /// Used when the header doesn't match with a checkpoint.
     DoesNotMatchCheckpoint,
     /// Used when the PoW in the header is invalid as per the target mentioned
@@ -64,6 +68,10 @@ pub fn validate_header(
         return Err(ValidateHeaderError::HeaderIsOld);
     }
 
+    if !is_timewarp_valid(network, &prev_header, prev_height, header) {
+        return Err(ValidateHeaderError::TimewarpAttack);
+    }
+
     if !is_checkpoint_valid(network, prev_height, header, chain_height) {
         return Err(ValidateHeaderError::DoesNotMatchCheckpoint);
     }
@@ -154,6 +162,21 @@ fn is_timestamp_valid(store: &impl HeaderStore, header: &BlockHeader) -> bool {
     header.time > median
 }
 
+/// Validates that the first header of a difficulty adjustment period is not
+/// timestamped more than [MAX_TIMEWARP] seconds before the previous header,
+/// if the network enforces BIP94.
+fn is_timewarp_valid(
+    network: &Network,
+    prev_header: &BlockHeader,
+    prev_height: BlockHeight,
+    header: &BlockHeader,
+) -> bool {
+    if !enforces_bip94(network) || (prev_height + 1) % DIFFICULTY_ADJUSTMENT_INTERVAL != 0 {
+        return true;
+    }
+    header.time >= prev_header.time.saturating_sub(MAX_TIMEWARP)
+}
+
 /// Gets the next target by doing the following:
 /// * If the network allows blocks to have the max target (testnet & regtest),
 ///   the next difficulty is searched for unless the header's timestamp is
@@ -168,7 +191,7 @@ fn get_next_target(
     header: &BlockHeader,
 ) -> Uint256 {
     match network {
-        Network::Testnet | Network::Regtest => {
+        Network::Testnet | Network::Testnet4 | Network::Regtest => {
             if (prev_height + 1) % DIFFICULTY_ADJUSTMENT_INTERVAL != 0 {
                 // This if statements is reached only for Regtest and Testnet networks
                 // Here is the quote from "https://en.bitcoin.it/wiki/Testnet"
@@ -220,7 +243,7 @@ fn find_next_difficulty_in_chain(
     // This is the maximum difficulty target for the network
     let pow_limit_bits = pow_limit_bits(network);
     match network {
-        Network::Testnet | Network::Regtest => {
+        Network::Testnet | Network::Testnet4 | Network::Regtest => {
             let mut current_header = *prev_header;
             let mut current_height = prev_height;
             let mut current_hash = prev_header.block_hash();
@@ -297,7 +320,13 @@ fn compute_next_difficulty(
     // Computing new difficulty target.
     // new difficulty target = old difficult target * (adjusted_interval /
     // 2_weeks);
-    let mut target = prev_header.target();
+    // With BIP94, the old difficulty target is taken from the first header of the
+    // period, so that a minimum difficulty block at its end doesn't affect the result.
+    let mut target = if enforces_bip94(network) {
+        last_adjustment_header.target()
+    } else {
+        prev_header.target()
+    };
     target = target.mul_u32(adjusted_interval);
     target = target / Uint256::from_u64(target_adjustment_interval_time as u64).unwrap();
 
@@ -649,4 +678,31 @@ mod test {
             "chain height difference is one year + 1 block"
         );
     }
+
+    #[test]
+    fn test_is_timewarp_valid() {
+        let prev_header = deserialize_header(TESTNET_HEADER_2132555);
+        let mut header = deserialize_header(TESTNET_HEADER_2132556);
+        header.time = prev_header.time - MAX_TIMEWARP - 1;
+        let period_end = 2 * DIFFICULTY_ADJUSTMENT_INTERVAL - 1;
+
+        assert!(
+            !is_timewarp_valid(&Network::Testnet4, &prev_header, period_end, &header),
+            "first header of a period is timewarped on testnet4"
+        );
+        assert!(
+            is_timewarp_valid(&Network::Testnet4, &prev_header, period_end - 1, &header),
+            "header is not the first of a period"
+        );
+        assert!(
+            is_timewarp_valid(&Network::Testnet, &prev_header, period_end, &header),
+            "testnet does not enforce BIP94"
+        );
+
+        header.time = prev_header.time - MAX_TIMEWARP;
+        assert!(
+            is_timewarp_valid(&Network::Testnet4, &prev_header, period_end, &header),
+            "header is within the allowed timewarp"
+        );
+    }
 }
@@ -1,8 +1,10 @@
 mod constants;
 mod header;
+mod network;
 
 pub use crate::header::{
     is_beyond_last_checkpoint, validate_header, HeaderStore, ValidateHeaderError,
 };
+pub use crate::network::Network;
 
 type BlockHeight = u32;
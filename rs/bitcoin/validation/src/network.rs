@@ -0,0 +1,151 @@
+use bitcoin::{
+    blockdata::constants::genesis_block, hashes::hex::FromHex, BlockHeader, TxMerkleNode,
+};
+use serde::{Deserialize, Serialize};
+
+/// The magic value of testnet4 (the message start bytes `1c 16 3f 28`).
+const TESTNET4_MAGIC: u32 = 0x283f161c;
+
+/// The merkle root of the testnet4 genesis block.
+const TESTNET4_GENESIS_MERKLE_ROOT: &str =
+    "7aa0a7ae1e223414cb807e40cd57e667b718e42aaf9306db9102fe28912b7b4e";
+
+/// The Bitcoin networks that can be validated.
+///
+/// This mirrors [bitcoin::Network], but additionally contains testnet4 (BIP94),
+/// which the `bitcoin` crate does not know about.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Network {
+    /// Bitcoin mainnet.
+    Bitcoin,
+    /// Bitcoin testnet3.
+    Testnet,
+    /// Bitcoin testnet4.
+    Testnet4,
+    /// Bitcoin's default signet.
+    Signet,
+    /// Bitcoin regtest.
+    Regtest,
+}
+
+impl Network {
+    /// Returns the magic value used to identify the network in P2P messages.
+    pub fn magic(&self) -> u32 {
+        match self {
+            Network::Bitcoin => bitcoin::Network::Bitcoin.magic(),
+            Network::Testnet => bitcoin::Network::Testnet.magic(),
+            Network::Testnet4 => TESTNET4_MAGIC,
+            Network::Signet => bitcoin::Network::Signet.magic(),
+            Network::Regtest => bitcoin::Network::Regtest.magic(),
+        }
+    }
+
+    /// Returns the header of the network's genesis block.
+    pub fn genesis_block_header(&self) -> BlockHeader {
+        match self {
+            Network::Bitcoin => genesis_block(bitcoin::Network::Bitcoin).header,
+            Network::Testnet => genesis_block(bitcoin::Network::Testnet).header,
+            Network::Testnet4 => BlockHeader {
+                version: 1,
+                prev_blockhash: Default::default(),
+                merkle_root: TxMerkleNode::from_hex(TESTNET4_GENESIS_MERKLE_ROOT)
+                    .expect("Programmer error: invalid merkle root"),
+                time: 1_714_777_860,
+                bits: 0x1d00ffff,
+                nonce: 393_743_547,
+            },
+            Network::Signet => genesis_block(bitcoin::Network::Signet).header,
+            Network::Regtest => genesis_block(bitcoin::Network::Regtest).header,
+        }
+    }
+}
+
+impl From<bitcoin::Network> for Network {
+    fn from(network: bitcoin::Network) -> Self {
+        match network {
+            bitcoin::Network::Bitcoin => Network::Bitcoin,
+            bitcoin::Network::Testnet => Network::Testnet,
+            bitcoin::Network::Signet => Network::Signet,
+            bitcoin::Network::Regtest => Network::Regtest,
+        }
+    }
+}
+
+/// The IC only distinguishes mainnet, a public test network and regtest, so
+/// all public test networks map to [ic_btc_interface::Network::Testnet].
+impl From<Network> for ic_btc_interface::Network {
+    fn from(network: Network) -> Self {
+        match network {
+            Network::Bitcoin => ic_btc_interface::Network::Mainnet,
+            Network::Testnet | Network::Testnet4 | Network::Signet => {
+                ic_btc_interface::Network::Testnet
+            }
+            Network::Regtest => ic_btc_interface::Network::Regtest,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bitcoin::BlockHash;
+
+    #[test]
+    fn test_genesis_block_headers() {
+        for (network, hash) in [
+            (
+                Network::Bitcoin,
+                "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f",
+            ),
+            (
+                Network::Testnet,
+                "000000000933ea01ad0ee984209779baaec3ced90fa3f408719526f8d77f4943",
+            ),
+            (
+                Network::Testnet4,
+                "00000000da84f2bafbbc53dee25a72ae507ff4914b867c565be350b0da8bf043",
+            ),
+            (
+                Network::Signet,
+                "00000008819873e925422c1ff0f99f7cc9bbb232af63a077a480a3633bee1ef6",
+            ),
+        ] {
+            assert_eq!(
+                network.genesis_block_header().block_hash(),
+                BlockHash::from_hex(hash).unwrap(),
+                "{:?}",
+                network
+            );
+        }
+    }
+
+    #[test]
+    fn test_ic_btc_interface_network() {
+        for (network, expected) in [
+            (Network::Bitcoin, ic_btc_interface::Network::Mainnet),
+            (Network::Testnet, ic_btc_interface::Network::Testnet),
+            (Network::Testnet4, ic_btc_interface::Network::Testnet),
+            (Network::Signet, ic_btc_interface::Network::Testnet),
+            (Network::Regtest, ic_btc_interface::Network::Regtest),
+        ] {
+            assert_eq!(ic_btc_interface::Network::from(network), expected);
+        }
+    }
+
+    #[test]
+    fn test_magic_matches_bitcoin_network() {
+        for network in [
+            bitcoin::Network::Bitcoin,
+            bitcoin::Network::Testnet,
+            bitcoin::Network::Signet,
+            bitcoin::Network::Regtest,
+        ] {
+            assert_eq!(Network::from(network).magic(), network.magic());
+        }
+        assert_eq!(
+            Network::Testnet4.magic().to_le_bytes(),
+            [0x1c, 0x16, 0x3f, 0x28]
+        );
+    }
+}
@@ -1,5 +1,6 @@
 use std::{net::SocketAddr, path::PathBuf};
 
+use candid::Principal;
 use clap::{Args, Parser};
 use url::Url;
 
@@ -39,6 +40,9 @@ pub struct Cli {
 
     #[command(flatten, next_help_heading = "bouncer")]
     pub bouncer: BouncerConfig,
+
+    #[command(flatten, next_help_heading = "mirror")]
+    pub mirror: MirrorConfig,
 }
 
 #[derive(Args)]
@@ -398,3 +402,35 @@ pub struct BouncerConfig {
     #[clap(long, default_value = "blackhole6")]
     pub bouncer_v6_set: String,
 }
+
+#[derive(Args)]
+pub struct MirrorConfig {
+    /// Fraction of query & read_state requests to mirror to another replica, in range [0.0..1.0].
+    /// Specify a value to enable mirroring. Mirrored responses are only compared, never returned.
+    #[clap(long)]
+    pub mirror_sample_rate: Option<f64>,
+
+    /// Comma separated list of node IDs to mirror the requests to, they can belong to any subnet.
+    /// If empty - any other healthy node of the same subnet (or of --mirror-target-subnet) is used.
+    #[clap(long, value_delimiter = ',')]
+    pub mirror_nodes: Vec<Principal>,
+
+    /// Subnet ID to mirror the requests to instead of the subnet that serves them,
+    /// e.g. a separate replica set. If --mirror-nodes is given - only those nodes of it are used.
+    #[clap(long)]
+    pub mirror_target_subnet: Option<Principal>,
+
+    /// Comma separated list of subnet IDs whose requests are mirrored.
+    /// If empty - requests to all subnets are mirrored.
+    #[clap(long, value_delimiter = ',')]
+    pub mirror_subnets: Vec<Principal>,
+
+    /// Timeout for the mirrored requests in seconds
+    #[clap(long, default_value = "10")]
+    pub mirror_timeout: u64,
+
+    /// Maximum number of mirrored requests in flight.
+    /// Requests sampled while the limit is reached are not mirrored.
+    #[clap(long, default_value = "100")]
+    pub mirror_max_concurrency: usize,
+}
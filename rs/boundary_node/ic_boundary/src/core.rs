@@ -46,6 +46,7 @@ use crate::{
         MetricParamsPersist, MetricParamsSnapshot, MetricsCache, MetricsRunner, WithMetrics,
         WithMetricsCheck, WithMetricsPersist, WithMetricsSnapshot, HTTP_DURATION_BUCKETS,
     },
+    mirror::{self, Mirror},
     persist::{Persist, Persister, Routes},
     rate_limiting::{generic, RateLimit},
     retry::{retry_request, RetryParams},
@@ -779,6 +780,24 @@ pub fn setup_router(
             ))
     }));
 
+    let middleware_mirror = option_layer(
+        Mirror::new(
+            &cli.mirror,
+            http_client.clone(),
+            routing_table.clone(),
+            metrics_registry,
+        )
+        .expect("unable to setup request mirroring")
+        .map(|x| {
+            warn!(
+                "Request mirroring enabled: sample rate {}",
+                cli.mirror.mirror_sample_rate.unwrap_or_default()
+            );
+
+            middleware::from_fn_with_state(Arc::new(x), mirror::middleware)
+        }),
+    );
+
     let middlware_bouncer =
         option_layer(bouncer.map(|x| middleware::from_fn_with_state(x, bouncer::middleware)));
     let middleware_subnet_lookup = middleware::from_fn_with_state(lookup, routes::lookup_subnet);
@@ -804,6 +823,7 @@ pub fn setup_router(
         .layer(common_service_layers.clone())
         .layer(middleware_subnet_lookup.clone())
         .layer(middleware_generic_limiter.clone())
+        .layer(middleware_mirror.clone())
         .layer(middleware_retry.clone());
 
     let service_subnet_read = ServiceBuilder::new()
@@ -812,6 +832,7 @@ pub fn setup_router(
         .layer(common_service_layers)
         .layer(middleware_subnet_lookup)
         .layer(middleware_generic_limiter)
+        .layer(middleware_mirror)
         .layer(middleware_retry);
 
     let canister_read_state_route = Router::new().route(routes::PATH_READ_STATE, {
@@ -8,6 +8,7 @@ mod firewall;
 mod geoip;
 mod http;
 mod metrics;
+mod mirror;
 mod persist;
 mod rate_limiting;
 mod retry;
@@ -0,0 +1,337 @@
+use std::{
+    collections::HashSet,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Error};
+use arc_swap::ArcSwapOption;
+use axum::{
+    body::{to_bytes, Body},
+    extract::{Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Extension,
+};
+use bytes::Bytes;
+use candid::Principal;
+use http::{header::CONTENT_TYPE, HeaderValue, Method, StatusCode};
+use ic_bn_lib::http::Client;
+use ic_types::{messages::HttpQueryResponse, CanisterId, SubnetId};
+use prometheus::{
+    register_histogram_vec_with_registry, register_int_counter_vec_with_registry, HistogramVec,
+    IntCounterVec, Registry,
+};
+use rand::{seq::IteratorRandom, Rng};
+use strum::IntoStaticStr;
+use tokio::sync::Semaphore;
+use tracing::debug;
+
+use crate::{
+    cli::MirrorConfig,
+    metrics::HTTP_DURATION_BUCKETS,
+    persist::{RouteSubnet, Routes},
+    routes::{ErrorCause, RequestContext, RequestType},
+    snapshot::Node,
+};
+
+const CONTENT_TYPE_CBOR: HeaderValue = HeaderValue::from_static("application/cbor");
+
+// Outcome of comparing the mirrored response with the primary one
+#[derive(Copy, Clone, Debug, PartialEq, Eq, IntoStaticStr)]
+#[strum(serialize_all = "snake_case")]
+pub enum MirrorResult {
+    Match,
+    Mismatch,
+    Error,
+    // The request was sampled, but too many mirrored requests were in flight
+    Dropped,
+}
+
+struct Metrics {
+    requests: IntCounterVec,
+    duration: HistogramVec,
+}
+
+// Mirrors a sampled fraction of query & read_state requests to another replica in the background
+// and records the latency of both replicas and whether their responses match
+pub struct Mirror {
+    http_client: Arc<dyn Client>,
+    routing_table: Arc<ArcSwapOption<Routes>>,
+    sample_rate: f64,
+    // Nodes to mirror to, if empty then any node of the target subnet other than the primary one is used
+    nodes: HashSet<Principal>,
+    // Subnet to mirror to, if not set then the subnet serving the request is used
+    target_subnet: Option<Principal>,
+    // Subnets whose traffic is mirrored, if empty then all subnets are mirrored
+    subnets: HashSet<Principal>,
+    timeout: Duration,
+    // Limits the number of mirrored requests in flight
+    semaphore: Arc<Semaphore>,
+    metrics: Metrics,
+}
+
+impl Mirror {
+    pub fn new(
+        cli: &MirrorConfig,
+        http_client: Arc<dyn Client>,
+        routing_table: Arc<ArcSwapOption<Routes>>,
+        registry: &Registry,
+    ) -> Result<Option<Self>, Error> {
+        let Some(sample_rate) = cli.mirror_sample_rate else {
+            return Ok(None);
+        };
+
+        if !(0.0..=1.0).contains(&sample_rate) {
+            return Err(anyhow!("mirror_sample_rate should be in range 0.0..1.0"));
+        }
+
+        if cli.mirror_max_concurrency == 0 {
+            return Err(anyhow!("mirror_max_concurrency should be > 0"));
+        }
+
+        let metrics = Metrics {
+            requests: register_int_counter_vec_with_registry!(
+                "mirror_requests",
+                "Number of mirrored requests partitioned by request type and comparison result",
+                &["request_type", "result"],
+                registry
+            )?,
+
+            duration: register_histogram_vec_with_registry!(
+                "mirror_duration_sec",
+                "Duration of mirrored requests in seconds on the primary and the mirror replica",
+                &["request_type", "target"],
+                HTTP_DURATION_BUCKETS.to_vec(),
+                registry
+            )?,
+        };
+
+        Ok(Some(Self {
+            http_client,
+            routing_table,
+            sample_rate,
+            nodes: HashSet::from_iter(cli.mirror_nodes.iter().copied()),
+            target_subnet: cli.mirror_target_subnet,
+            subnets: HashSet::from_iter(cli.mirror_subnets.iter().copied()),
+            timeout: Duration::from_secs(cli.mirror_timeout),
+            semaphore: Arc::new(Semaphore::new(cli.mirror_max_concurrency)),
+            metrics,
+        }))
+    }
+
+    // Decides if the given request should be mirrored
+    fn should_mirror(&self, request_type: RequestType, subnet: &RouteSubnet) -> bool {
+        if !matches!(
+            request_type,
+            RequestType::Query | RequestType::ReadState | RequestType::ReadStateSubnet
+        ) {
+            return false;
+        }
+
+        if !self.subnets.is_empty() && !self.subnets.contains(&subnet.id) {
+            return false;
+        }
+
+        rand::thread_rng().gen_bool(self.sample_rate)
+    }
+
+    // Picks a node to mirror the request to, excluding the one that served the primary request.
+    // The node is picked from the target subnet if one is configured, otherwise configured nodes
+    // are looked up in all subnets and, if there are none, the subnet serving the request is used.
+    fn pick_node(&self, subnet: &RouteSubnet, primary: &Node) -> Option<Arc<Node>> {
+        let routes = self.routing_table.load_full();
+
+        let candidates: Box<dyn Iterator<Item = &Arc<Node>>> =
+            match (self.target_subnet, routes.as_ref()) {
+                (Some(id), Some(routes)) => Box::new(routes.subnet_map.get(&id)?.nodes.iter()),
+                (Some(_), None) => return None,
+                (None, Some(routes)) if !self.nodes.is_empty() => Box::new(
+                    routes
+                        .subnet_map
+                        .values()
+                        .flat_map(|subnet| subnet.nodes.iter()),
+                ),
+                (None, _) => Box::new(subnet.nodes.iter()),
+            };
+
+        candidates
+            .filter(|x| x.id != primary.id)
+            .filter(|x| self.nodes.is_empty() || self.nodes.contains(&x.id))
+            .choose(&mut rand::thread_rng())
+            .cloned()
+    }
+
+    // Sends the request to the mirror node and returns the status & body of the response
+    async fn send(
+        &self,
+        node: &Node,
+        request_type: RequestType,
+        principal: Principal,
+        body: Bytes,
+    ) -> Result<(StatusCode, Bytes), Error> {
+        let url = node.build_url(request_type, principal)?;
+
+        let mut request = reqwest::Request::new(Method::POST, url);
+        *request.timeout_mut() = Some(self.timeout);
+        request
+            .headers_mut()
+            .insert(CONTENT_TYPE, CONTENT_TYPE_CBOR);
+        *request.body_mut() = Some(body.into());
+
+        let response = self.http_client.execute(request).await?;
+        let status = response.status();
+        let body = response.bytes().await?;
+
+        Ok((status, body))
+    }
+
+    async fn mirror(
+        &self,
+        request_type: RequestType,
+        principal: Principal,
+        node: Arc<Node>,
+        request_body: Bytes,
+        primary: (StatusCode, Bytes),
+    ) {
+        let start = Instant::now();
+        let result = self
+            .send(&node, request_type, principal, request_body)
+            .await;
+        let duration = start.elapsed().as_secs_f64();
+
+        let request_type_label: &'static str = request_type.into();
+
+        let result = match result {
+            Ok(mirror) => {
+                self.metrics
+                    .duration
+                    .with_label_values(&[request_type_label, "mirror"])
+                    .observe(duration);
+
+                if responses_match(request_type, &primary, &mirror) {
+                    MirrorResult::Match
+                } else {
+                    MirrorResult::Mismatch
+                }
+            }
+
+            Err(e) => {
+                debug!("Mirror: request to node {} failed: {e:#}", node.id);
+                MirrorResult::Error
+            }
+        };
+
+        self.metrics
+            .requests
+            .with_label_values(&[request_type_label, result.into()])
+            .inc();
+    }
+}
+
+// Compares the responses of the primary and the mirror replica.
+// The status codes must be the same and, for successful queries, also the replies.
+// Certificates of read_state responses are produced at different heights and are not compared.
+pub fn responses_match(
+    request_type: RequestType,
+    (primary_status, primary_body): &(StatusCode, Bytes),
+    (mirror_status, mirror_body): &(StatusCode, Bytes),
+) -> bool {
+    if primary_status != mirror_status {
+        return false;
+    }
+
+    if request_type != RequestType::Query || !primary_status.is_success() {
+        return true;
+    }
+
+    // Signatures differ between the nodes, but they're not part of the decoded response
+    match (
+        serde_cbor::from_slice::<HttpQueryResponse>(primary_body),
+        serde_cbor::from_slice::<HttpQueryResponse>(mirror_body),
+    ) {
+        (Ok(primary), Ok(mirror)) => primary == mirror,
+        _ => primary_body == mirror_body,
+    }
+}
+
+// Middleware that mirrors the sampled requests after the primary response is obtained
+pub async fn middleware(
+    State(mirror): State<Arc<Mirror>>,
+    Extension(ctx): Extension<Arc<RequestContext>>,
+    Extension(subnet): Extension<Arc<RouteSubnet>>,
+    request: Request,
+    next: Next,
+) -> Result<impl IntoResponse, ErrorCause> {
+    if !mirror.should_mirror(ctx.request_type, &subnet) {
+        return Ok(next.run(request).await);
+    }
+
+    // Principal that the request URL is built with
+    let principal = if let Some(v) = request.extensions().get::<CanisterId>() {
+        v.get().0
+    } else if let Some(v) = request.extensions().get::<SubnetId>() {
+        v.get().0
+    } else {
+        return Ok(next.run(request).await);
+    };
+
+    let request_type_label: &'static str = ctx.request_type.into();
+
+    // Sampled requests are not mirrored while too many mirrored requests are in flight
+    let Ok(permit) = mirror.semaphore.clone().try_acquire_owned() else {
+        mirror
+            .metrics
+            .requests
+            .with_label_values(&[request_type_label, MirrorResult::Dropped.into()])
+            .inc();
+
+        return Ok(next.run(request).await);
+    };
+
+    // The body was already buffered by the preprocessing middleware
+    let (parts, body) = request.into_parts();
+    let request_body = to_bytes(body, usize::MAX)
+        .await
+        .map_err(|e| ErrorCause::UnableToReadBody(e.to_string()))?;
+    let request = Request::from_parts(parts, Body::from(request_body.clone()));
+
+    let start = Instant::now();
+    let response = next.run(request).await;
+    let duration = start.elapsed().as_secs_f64();
+
+    // Node that has served the request is set by the retry middleware
+    let Some(node) = response
+        .extensions()
+        .get::<Arc<Node>>()
+        .and_then(|x| mirror.pick_node(&subnet, x))
+    else {
+        return Ok(response);
+    };
+
+    mirror
+        .metrics
+        .duration
+        .with_label_values(&[request_type_label, "primary"])
+        .observe(duration);
+
+    let (parts, body) = response.into_parts();
+    let response_body = to_bytes(body, usize::MAX)
+        .await
+        .map_err(|e| ErrorCause::ReplicaErrorOther(e.to_string()))?;
+    let primary = (parts.status, response_body.clone());
+
+    let request_type = ctx.request_type;
+    tokio::spawn(async move {
+        mirror
+            .mirror(request_type, principal, node, request_body, primary)
+            .await;
+
+        drop(permit);
+    });
+
+    Ok(Response::from_parts(parts, Body::from(response_body)))
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,369 @@
+use super::*;
+
+use std::{
+    str::FromStr,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use async_trait::async_trait;
+use axum::{middleware, routing::method_routing::post, Router};
+use ic_types::messages::{Blob, HttpQueryResponseReply};
+use tower::Service;
+
+use crate::{
+    persist::test::generate_test_routes,
+    routes::test::{test_node, test_route_subnet},
+};
+
+#[derive(Debug)]
+struct TestHttpClient {
+    body: Vec<u8>,
+    calls: AtomicUsize,
+}
+
+#[async_trait]
+impl Client for TestHttpClient {
+    async fn execute(&self, _req: reqwest::Request) -> Result<reqwest::Response, reqwest::Error> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        Ok(reqwest::Response::from(http::Response::new(
+            self.body.clone(),
+        )))
+    }
+}
+
+fn query_response(arg: &[u8]) -> Bytes {
+    serde_cbor::to_vec(&HttpQueryResponse::Replied {
+        reply: HttpQueryResponseReply {
+            arg: Blob(arg.to_vec()),
+        },
+    })
+    .unwrap()
+    .into()
+}
+
+fn mirror_config(sample_rate: f64) -> MirrorConfig {
+    MirrorConfig {
+        mirror_sample_rate: Some(sample_rate),
+        mirror_nodes: vec![],
+        mirror_target_subnet: None,
+        mirror_subnets: vec![],
+        mirror_timeout: 1,
+        mirror_max_concurrency: 100,
+    }
+}
+
+// Routing table with the subnet of `test_route_subnet` and some other subnets
+fn test_routing_table() -> Arc<ArcSwapOption<Routes>> {
+    let mut routes = generate_test_routes(100);
+    let subnet = Arc::new(test_route_subnet(4));
+    routes.subnet_map.insert(subnet.id, subnet.clone());
+    routes.subnets.push(subnet);
+
+    Arc::new(ArcSwapOption::from_pointee(routes))
+}
+
+fn test_mirror(cli: &MirrorConfig, http_client: Arc<dyn Client>) -> Mirror {
+    Mirror::new(cli, http_client, test_routing_table(), &Registry::new())
+        .unwrap()
+        .unwrap()
+}
+
+fn gen_request(request_type: RequestType) -> Request {
+    let ctx = RequestContext {
+        request_type,
+        ..Default::default()
+    };
+
+    let mut req = Request::post("/").body(Body::from("foobar")).unwrap();
+    req.extensions_mut().insert(Arc::new(ctx));
+    req.extensions_mut()
+        .insert(CanisterId::from_str("f7crg-kabae").unwrap());
+    req.extensions_mut().insert(Arc::new(test_route_subnet(4)));
+
+    req
+}
+
+// Returns a query reply as if it was served by the first node of the subnet
+async fn handler() -> impl IntoResponse {
+    let mut resp = Body::from(query_response(b"foo")).into_response();
+    resp.extensions_mut().insert(test_node(0));
+    resp
+}
+
+#[test]
+fn test_new() {
+    let http_client = Arc::new(TestHttpClient {
+        body: vec![],
+        calls: AtomicUsize::new(0),
+    });
+
+    let mut cli = mirror_config(0.5);
+    cli.mirror_sample_rate = None;
+    assert!(Mirror::new(
+        &cli,
+        http_client.clone(),
+        test_routing_table(),
+        &Registry::new()
+    )
+    .unwrap()
+    .is_none());
+
+    cli.mirror_sample_rate = Some(1.5);
+    assert!(Mirror::new(
+        &cli,
+        http_client.clone(),
+        test_routing_table(),
+        &Registry::new()
+    )
+    .is_err());
+
+    cli.mirror_sample_rate = Some(0.5);
+    cli.mirror_max_concurrency = 0;
+    assert!(Mirror::new(&cli, http_client, test_routing_table(), &Registry::new()).is_err());
+}
+
+#[test]
+fn test_should_mirror() {
+    let http_client = Arc::new(TestHttpClient {
+        body: vec![],
+        calls: AtomicUsize::new(0),
+    });
+    let subnet = test_route_subnet(4);
+
+    let mirror = test_mirror(&mirror_config(1.0), http_client.clone());
+    assert!(mirror.should_mirror(RequestType::Query, &subnet));
+    assert!(mirror.should_mirror(RequestType::ReadState, &subnet));
+    assert!(mirror.should_mirror(RequestType::ReadStateSubnet, &subnet));
+    assert!(!mirror.should_mirror(RequestType::Call, &subnet));
+    assert!(!mirror.should_mirror(RequestType::SyncCall, &subnet));
+    assert!(!mirror.should_mirror(RequestType::Status, &subnet));
+
+    let mirror = test_mirror(&mirror_config(0.0), http_client.clone());
+    assert!(!mirror.should_mirror(RequestType::Query, &subnet));
+
+    // Subnet is not in the list
+    let mut cli = mirror_config(1.0);
+    cli.mirror_subnets = vec![Principal::from_text("aaaaa-aa").unwrap()];
+    let mirror = test_mirror(&cli, http_client.clone());
+    assert!(!mirror.should_mirror(RequestType::Query, &subnet));
+
+    cli.mirror_subnets.push(subnet.id);
+    let mirror = test_mirror(&cli, http_client);
+    assert!(mirror.should_mirror(RequestType::Query, &subnet));
+}
+
+#[test]
+fn test_pick_node() {
+    let http_client = Arc::new(TestHttpClient {
+        body: vec![],
+        calls: AtomicUsize::new(0),
+    });
+    let subnet = test_route_subnet(4);
+    let primary = subnet.nodes[0].clone();
+
+    // Primary node is never picked
+    let mirror = test_mirror(&mirror_config(1.0), http_client.clone());
+    for _ in 0..100 {
+        assert_ne!(mirror.pick_node(&subnet, &primary).unwrap().id, primary.id);
+    }
+
+    // Only the configured nodes are picked
+    let mut cli = mirror_config(1.0);
+    cli.mirror_nodes = vec![subnet.nodes[2].id];
+    let mirror = test_mirror(&cli, http_client.clone());
+    for _ in 0..100 {
+        assert_eq!(
+            mirror.pick_node(&subnet, &primary).unwrap().id,
+            subnet.nodes[2].id
+        );
+    }
+
+    // No node to pick if the only configured one served the primary request
+    cli.mirror_nodes = vec![primary.id];
+    let mirror = test_mirror(&cli, http_client.clone());
+    assert!(mirror.pick_node(&subnet, &primary).is_none());
+
+    // Configured nodes can belong to another subnet
+    let other_subnet = generate_test_routes(100).subnets[1].clone();
+    cli.mirror_nodes = vec![other_subnet.nodes[0].id];
+    let mirror = test_mirror(&cli, http_client.clone());
+    assert_eq!(
+        mirror.pick_node(&subnet, &primary).unwrap().id,
+        other_subnet.nodes[0].id
+    );
+
+    // Nodes of the target subnet are picked
+    let mut cli = mirror_config(1.0);
+    cli.mirror_target_subnet = Some(other_subnet.id);
+    let mirror = test_mirror(&cli, http_client.clone());
+    assert_eq!(
+        mirror.pick_node(&subnet, &primary).unwrap().id,
+        other_subnet.nodes[0].id
+    );
+
+    // Configured nodes outside of the target subnet are not picked
+    cli.mirror_nodes = vec![subnet.nodes[2].id];
+    let mirror = test_mirror(&cli, http_client.clone());
+    assert!(mirror.pick_node(&subnet, &primary).is_none());
+
+    // No node to pick if the target subnet is unknown
+    cli.mirror_nodes = vec![];
+    cli.mirror_target_subnet = Some(Principal::from_text("aaaaa-aa").unwrap());
+    let mirror = test_mirror(&cli, http_client);
+    assert!(mirror.pick_node(&subnet, &primary).is_none());
+}
+
+#[test]
+fn test_responses_match() {
+    let ok = StatusCode::OK;
+    let foo = query_response(b"foo");
+    let bar = query_response(b"bar");
+
+    assert!(responses_match(
+        RequestType::Query,
+        &(ok, foo.clone()),
+        &(ok, foo.clone())
+    ));
+    assert!(!responses_match(
+        RequestType::Query,
+        &(ok, foo.clone()),
+        &(ok, bar.clone())
+    ));
+    assert!(!responses_match(
+        RequestType::Query,
+        &(ok, foo.clone()),
+        &(StatusCode::SERVICE_UNAVAILABLE, foo.clone())
+    ));
+
+    // Bodies of failed requests are not compared
+    assert!(responses_match(
+        RequestType::Query,
+        &(StatusCode::BAD_REQUEST, Bytes::from("foo")),
+        &(StatusCode::BAD_REQUEST, Bytes::from("bar"))
+    ));
+
+    // Certificates in read_state responses are not compared
+    assert!(responses_match(
+        RequestType::ReadState,
+        &(ok, foo.clone()),
+        &(ok, bar)
+    ));
+
+    // Undecodable bodies are compared as is
+    assert!(responses_match(
+        RequestType::Query,
+        &(ok, Bytes::from("foo")),
+        &(ok, Bytes::from("foo"))
+    ));
+    assert!(!responses_match(
+        RequestType::Query,
+        &(ok, Bytes::from("foo")),
+        &(ok, foo)
+    ));
+}
+
+#[tokio::test]
+async fn test_middleware() {
+    for (mirror_body, request_type, result) in [
+        (b"foo", RequestType::Query, "match"),
+        (b"bar", RequestType::Query, "mismatch"),
+        (b"bar", RequestType::Call, ""),
+    ] {
+        let http_client = Arc::new(TestHttpClient {
+            body: query_response(mirror_body).to_vec(),
+            calls: AtomicUsize::new(0),
+        });
+        let mirror = Arc::new(test_mirror(&mirror_config(1.0), http_client.clone()));
+
+        let mut app =
+            Router::new()
+                .route("/", post(handler))
+                .layer(middleware::from_fn_with_state(
+                    mirror.clone(),
+                    super::middleware,
+                ));
+
+        // Primary response is passed through unchanged
+        let resp = app.call(gen_request(request_type)).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(body, query_response(b"foo"));
+
+        if request_type == RequestType::Call {
+            tokio::task::yield_now().await;
+            assert_eq!(http_client.calls.load(Ordering::SeqCst), 0);
+            continue;
+        }
+
+        // Wait for the mirrored request to complete in the background
+        let counter = mirror
+            .metrics
+            .requests
+            .with_label_values(&["query", result]);
+        for _ in 0..100 {
+            if counter.get() > 0 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        assert_eq!(counter.get(), 1);
+        assert_eq!(http_client.calls.load(Ordering::SeqCst), 1);
+    }
+}
+
+#[tokio::test]
+async fn test_middleware_concurrency_limit() {
+    let http_client = Arc::new(TestHttpClient {
+        body: query_response(b"foo").to_vec(),
+        calls: AtomicUsize::new(0),
+    });
+    let mut cli = mirror_config(1.0);
+    cli.mirror_max_concurrency = 1;
+    let mirror = Arc::new(test_mirror(&cli, http_client.clone()));
+
+    let mut app = Router::new()
+        .route("/", post(handler))
+        .layer(middleware::from_fn_with_state(
+            mirror.clone(),
+            super::middleware,
+        ));
+
+    // Occupy the only slot as if a mirrored request was in flight
+    let permit = mirror.semaphore.clone().try_acquire_owned().unwrap();
+
+    let resp = app.call(gen_request(RequestType::Query)).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body = to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+    assert_eq!(body, query_response(b"foo"));
+
+    tokio::task::yield_now().await;
+    assert_eq!(http_client.calls.load(Ordering::SeqCst), 0);
+    assert_eq!(
+        mirror
+            .metrics
+            .requests
+            .with_label_values(&["query", "dropped"])
+            .get(),
+        1
+    );
+
+    // Requests are mirrored again once the slot is freed
+    drop(permit);
+    app.call(gen_request(RequestType::Query)).await.unwrap();
+
+    let counter = mirror
+        .metrics
+        .requests
+        .with_label_values(&["query", "match"]);
+    for _ in 0..100 {
+        if counter.get() > 0 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    assert_eq!(counter.get(), 1);
+    assert_eq!(http_client.calls.load(Ordering::SeqCst), 1);
+    assert_eq!(mirror.semaphore.available_permits(), 1);
+}
@@ -104,6 +104,7 @@ mod tests {
                 instance_stats: InstanceStats::default(),
                 system_api_call_counters: SystemApiCallCounters::default(),
                 canister_log: CanisterLog::default(),
+                instruction_profile: None,
             },
             state: Some(StateModifications {
                 globals: vec![
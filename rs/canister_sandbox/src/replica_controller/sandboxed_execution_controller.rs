@@ -13,7 +13,8 @@ use ic_embedders::wasm_executor::{
     SliceExecutionOutput, WasmExecutionResult, WasmExecutor,
 };
 use ic_embedders::{
-    wasm_utils::WasmImportsDetails, CompilationCache, CompilationResult, WasmExecutionInput,
+    wasm_utils::{instruction_profile::InstructionProfileExporter, WasmImportsDetails},
+    CompilationCache, CompilationResult, WasmExecutionInput,
 };
 use ic_interfaces::execution_environment::{HypervisorError, HypervisorResult};
 #[cfg(target_os = "linux")]
@@ -641,6 +642,9 @@ pub struct SandboxedExecutionController {
     max_sandbox_count: usize,
     max_sandbox_idle_time: Duration,
     trace_execution: FlagStatus,
+    /// If set, the instruction profiles of all executions are written to the
+    /// directory of the exporter.
+    instruction_profile_exporter: Option<InstructionProfileExporter>,
     logger: ReplicaLogger,
     /// Executable and arguments to be passed to `canister_sandbox` which are
     /// the same for all canisters.
@@ -1064,6 +1068,10 @@ impl SandboxedExecutionController {
         let max_sandbox_count = embedder_config.max_sandbox_count;
         let max_sandbox_idle_time = embedder_config.max_sandbox_idle_time;
         let trace_execution = embedder_config.trace_execution;
+        let instruction_profile_exporter = embedder_config
+            .instruction_profile_dir
+            .clone()
+            .map(InstructionProfileExporter::new);
         let sandbox_exec_argv =
             create_sandbox_argv(embedder_config).expect("No canister_sandbox binary found");
         let backends = Arc::new(Mutex::new(HashMap::new()));
@@ -1112,6 +1120,7 @@ impl SandboxedExecutionController {
             max_sandbox_count,
             max_sandbox_idle_time,
             trace_execution,
+            instruction_profile_exporter,
             logger,
             sandbox_exec_argv,
             metrics,
@@ -1392,9 +1401,33 @@ impl SandboxedExecutionController {
 
         execution_tracing.trace(&self.logger, &exec_output, execution_start.elapsed());
 
+        self.export_instruction_profile(canister_id, execution_state, &mut exec_output);
+
         WasmExecutionResult::Finished(exec_output.slice, exec_output.wasm, canister_state_changes)
     }
 
+    // Writes the instruction profile of the execution (if any) to the
+    // configured directory. Failures are only logged because profiling is a
+    // development aid that must not affect execution.
+    fn export_instruction_profile(
+        &self,
+        canister_id: CanisterId,
+        execution_state: &ExecutionState,
+        exec_output: &mut SandboxExecOutput,
+    ) {
+        let Some(profile) = exec_output.wasm.instruction_profile.take() else {
+            return;
+        };
+        let Some(exporter) = &self.instruction_profile_exporter else {
+            return;
+        };
+        if let Err(err) =
+            exporter.export(canister_id, &execution_state.wasm_binary.binary, &profile)
+        {
+            warn!(self.logger, "Canister {}: {}", canister_id, err);
+        }
+    }
+
     // Unless execution trapped, commit state (applying execution state
     // changes, returning system state changes to caller).
     #[allow(clippy::too_many_arguments)]
@@ -1,3 +1,4 @@
+use std::path::PathBuf;
 use std::time::Duration;
 
 use ic_base_types::NumBytes;
@@ -110,6 +111,10 @@ pub struct FeatureFlags {
     pub best_effort_responses: FlagStatus,
     /// Collect a backtrace from the canister when it panics.
     pub canister_backtrace: FlagStatus,
+    /// Instrument canister code to record the instructions executed by each
+    /// Wasm function. Only meant for local development tools like drun and
+    /// PocketIC.
+    pub instruction_profiling: FlagStatus,
 }
 
 impl FeatureFlags {
@@ -121,6 +126,7 @@ impl FeatureFlags {
             wasm64: FlagStatus::Disabled,
             best_effort_responses: FlagStatus::Disabled,
             canister_backtrace: FlagStatus::Disabled,
+            instruction_profiling: FlagStatus::Disabled,
         }
     }
 }
@@ -223,6 +229,11 @@ pub struct Config {
     /// entry with the number of executed instructions and the duration.
     pub trace_execution: FlagStatus,
 
+    /// If set and `feature_flags.instruction_profiling` is enabled, then the
+    /// instruction profile of every executed message is written to this
+    /// directory as a flamegraph-compatible folded-stack file.
+    pub instruction_profile_dir: Option<PathBuf>,
+
     /// The maximum number of pages that a message dirties without optimizing dirty
     /// page copying by triggering a new execution slice for copying and using prefaulting.
     pub max_dirty_pages_without_optimization: usize,
@@ -271,6 +282,7 @@ impl Config {
             subnet_type: SubnetType::Application,
             dirty_page_overhead: NumInstructions::new(0),
             trace_execution: FlagStatus::Disabled,
+            instruction_profile_dir: None,
             max_dirty_pages_without_optimization: DEFAULT_MAX_DIRTY_PAGES_WITHOUT_OPTIMIZATION,
             dirty_page_copy_overhead: DIRTY_PAGE_COPY_OVERHEAD,
             wasm_max_size: WASM_MAX_SIZE,
@@ -5,7 +5,7 @@ use ic_types::{
     Cycles, NumBytes, NumInstructions, MAX_STABLE_MEMORY_IN_BYTES, MAX_WASM_MEMORY_IN_BYTES,
 };
 use serde::{Deserialize, Serialize};
-use std::{str::FromStr, time::Duration};
+use std::{path::PathBuf, str::FromStr, time::Duration};
 
 const MIB: u64 = 1024 * 1024;
 const GIB: u64 = MIB * 1024;
@@ -288,6 +288,11 @@ pub struct Config {
     // TODO(EXC-1678): remove after release.
     /// Feature flag to enable/disable allowed viewers for canister log visibility.
     pub allowed_viewers_feature: FlagStatus,
+
+    /// If set, then the scheduler appends a JSON record for every message
+    /// executed on a canister or by the management canister to this file.
+    /// Meant for offline analysis of replayed rounds, e.g. by `ic-replay`.
+    pub message_trace_path: Option<PathBuf>,
 }
 
 impl Default for Config {
@@ -361,6 +366,7 @@ impl Default for Config {
             max_canister_http_requests_in_flight: MAX_CANISTER_HTTP_REQUESTS_IN_FLIGHT,
             default_wasm_memory_limit: DEFAULT_WASM_MEMORY_LIMIT,
             allowed_viewers_feature: FlagStatus::Disabled,
+            message_trace_path: None,
         }
     }
 }
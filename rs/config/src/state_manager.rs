@@ -19,6 +19,12 @@ pub struct Config {
     /// A config for LSMT storage.
     #[serde(default = "lsmt_config_default")]
     pub lsmt_config: LsmtConfig,
+    /// A feature flag that enables/disables storing the `PageMap` files of checkpoints in a
+    /// compressed format. The latest checkpoint, which the state is loaded from, is kept
+    /// uncompressed; older checkpoints are compressed once a newer one is created. Has no effect
+    /// if LSMT is disabled.
+    #[serde(default = "checkpoint_compression_default")]
+    pub checkpoint_compression: FlagStatus,
 }
 
 impl Config {
@@ -27,6 +33,7 @@ impl Config {
             state_root,
             file_backed_memory_allocator: file_backed_memory_allocator_default(),
             lsmt_config: lsmt_config_default(),
+            checkpoint_compression: checkpoint_compression_default(),
         }
     }
 
@@ -45,6 +52,10 @@ fn file_backed_memory_allocator_default() -> FlagStatus {
     FlagStatus::Disabled
 }
 
+fn checkpoint_compression_default() -> FlagStatus {
+    FlagStatus::Disabled
+}
+
 pub fn lsmt_config_default() -> LsmtConfig {
     LsmtConfig {
         lsmt_status: FlagStatus::Enabled,
@@ -752,11 +752,9 @@ impl CyclesAccountManager {
         //   - the fee to send the request (by size)
         //   - the fee for the largest possible response
         //   - the fee for executing the largest allowed response when it eventually arrives.
-        let transmission_fee = self.scale_cost(
-            self.config.xnet_call_fee
-                + self.config.xnet_byte_transmission_fee * request.payload_size_bytes().get(),
-            subnet_size,
-        ) + prepayment_for_response_transmission;
+        let transmission_fee = self
+            .request_transmission_fee(request.payload_size_bytes(), subnet_size)
+            + prepayment_for_response_transmission;
 
         let fee = transmission_fee + prepayment_for_response_execution;
 
@@ -803,6 +801,24 @@ impl CyclesAccountManager {
         )
     }
 
+    /// Returns the amount of cycles withdrawn when sending a request with the
+    /// given payload size (method name and argument): the transmission fee plus
+    /// the prepayments for transmitting and executing the largest response.
+    pub fn xnet_call_total_fee(&self, payload_size: NumBytes, subnet_size: usize) -> Cycles {
+        self.request_transmission_fee(payload_size, subnet_size)
+            + self.prepayment_for_response_transmission(subnet_size)
+            + self.prepayment_for_response_execution(subnet_size)
+    }
+
+    /// Returns the fee for doing the xnet call and for transmitting a request
+    /// with the given payload size, without the prepayments for the response.
+    fn request_transmission_fee(&self, payload_size: NumBytes, subnet_size: usize) -> Cycles {
+        self.scale_cost(
+            self.config.xnet_call_fee + self.config.xnet_byte_transmission_fee * payload_size.get(),
+            subnet_size,
+        )
+    }
+
     /// Returns the refund cycles for the response transmission bytes reserved at
     /// the initial call time.
     pub fn refund_for_response_transmission(
@@ -1123,6 +1139,26 @@ impl CyclesAccountManager {
             * (subnet_size as u64)
     }
 
+    /// Returns the fee for a non-replicated http request. Such a request is
+    /// made by a single node and does not go through consensus, so it is
+    /// charged like the share of a single node of a replicated request,
+    /// without the component that grows with the subnet size.
+    pub fn non_replicated_http_request_fee(
+        &self,
+        request_size: NumBytes,
+        response_size_limit: Option<NumBytes>,
+    ) -> Cycles {
+        let response_size = match response_size_limit {
+            Some(response_size) => response_size.get(),
+            // Defaults to maximum response size.
+            None => MAX_CANISTER_HTTP_RESPONSE_BYTES,
+        };
+
+        self.config.http_request_linear_baseline_fee
+            + self.config.http_request_per_byte_fee * request_size.get()
+            + self.config.http_response_per_byte_fee * response_size
+    }
+
     /// Returns the default value of the reserved balance limit for the case
     /// when the canister doesn't have it set in the settings.
     pub fn default_reserved_balance_limit(&self) -> Cycles {
@@ -1262,6 +1298,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn non_replicated_http_requests_fee_does_not_scale() {
+        let request_size = NumBytes::from(17);
+        let cycles_account_manager = create_cycles_account_manager(13);
+
+        assert_eq!(
+            cycles_account_manager.non_replicated_http_request_fee(request_size, None),
+            Cycles::from(1_603_006_800u64)
+        );
+        assert!(
+            cycles_account_manager.non_replicated_http_request_fee(request_size, None)
+                < cycles_account_manager.http_request_fee(request_size, None, 1)
+        );
+    }
+
     #[test]
     fn test_cycles_burn() {
         let subnet_size = 13;
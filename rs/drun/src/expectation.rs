@@ -0,0 +1,392 @@
+//! Expectations on the outcome of the messages in a message file.
+//!
+//! An expectation line (`expect ...`) refers to the closest preceding message
+//! and is checked as soon as that message has been processed.
+
+use candid::IDLArgs;
+use hex::encode;
+use ic_error_types::{RejectCode, UserError};
+use ic_types::ingress::WasmResult;
+use std::fmt;
+
+use crate::message::parse_octet_string;
+
+/// The observable outcome of processing a single message.
+pub(crate) struct Outcome {
+    pub result: Result<WasmResult, UserError>,
+    pub cycles_consumed: u128,
+    pub instructions_executed: u64,
+}
+
+/// An inclusive range of values; a missing bound is unconstrained.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) struct ValueRange {
+    min: Option<u128>,
+    max: Option<u128>,
+}
+
+impl ValueRange {
+    fn contains(&self, value: u128) -> bool {
+        self.min.map_or(true, |min| min <= value) && self.max.map_or(true, |max| value <= max)
+    }
+}
+
+impl fmt::Display for ValueRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.min, self.max) {
+            (Some(min), Some(max)) if min == max => write!(f, "{}", min),
+            (min, max) => {
+                if let Some(min) = min {
+                    write!(f, "{}", min)?;
+                }
+                write!(f, "..")?;
+                if let Some(max) = max {
+                    write!(f, "{}", max)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) enum Expectation {
+    /// The message is replied to with exactly these bytes.
+    Reply(Vec<u8>),
+    /// The message is replied to with these Candid values
+    /// (given as Candid text and stored in their binary encoding).
+    CandidReply { text: String, encoded: Vec<u8> },
+    /// The message is rejected with this reject code.
+    Reject(RejectCode),
+    /// The number of cycles consumed by all canisters while processing the message.
+    Cycles(ValueRange),
+    /// The number of instructions executed by canisters while processing the message.
+    Instructions(ValueRange),
+}
+
+/// A failed expectation, rendered as expected vs. actual values.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) struct Mismatch {
+    pub expected: String,
+    pub actual: String,
+}
+
+impl Expectation {
+    pub(crate) fn check(&self, outcome: &Outcome) -> Result<(), Mismatch> {
+        match self {
+            Expectation::Reply(expected) => match &outcome.result {
+                Ok(WasmResult::Reply(actual)) if actual == expected => Ok(()),
+                result => Err(Mismatch {
+                    expected: format!("reply 0x{}", encode(expected)),
+                    actual: describe_result(result),
+                }),
+            },
+            Expectation::CandidReply { text, encoded } => match &outcome.result {
+                Ok(WasmResult::Reply(actual)) if candid_eq(encoded, actual) => Ok(()),
+                Ok(WasmResult::Reply(actual)) => Err(Mismatch {
+                    expected: format!("reply-candid {}", text),
+                    actual: match IDLArgs::from_bytes(actual) {
+                        Ok(args) => format!("reply-candid {}", args),
+                        Err(_) => format!("reply 0x{} (not Candid)", encode(actual)),
+                    },
+                }),
+                result => Err(Mismatch {
+                    expected: format!("reply-candid {}", text),
+                    actual: describe_result(result),
+                }),
+            },
+            Expectation::Reject(expected) => {
+                let actual = match &outcome.result {
+                    Ok(WasmResult::Reply(_)) => None,
+                    Ok(WasmResult::Reject(_)) => Some(RejectCode::CanisterReject),
+                    Err(err) => Some(err.reject_code()),
+                };
+                if actual == Some(*expected) {
+                    Ok(())
+                } else {
+                    Err(Mismatch {
+                        expected: format!("reject {}", expected),
+                        actual: describe_result(&outcome.result),
+                    })
+                }
+            }
+            Expectation::Cycles(range) => {
+                if range.contains(outcome.cycles_consumed) {
+                    Ok(())
+                } else {
+                    Err(Mismatch {
+                        expected: format!("cycles {}", range),
+                        actual: format!("cycles {}", outcome.cycles_consumed),
+                    })
+                }
+            }
+            Expectation::Instructions(range) => {
+                if range.contains(outcome.instructions_executed as u128) {
+                    Ok(())
+                } else {
+                    Err(Mismatch {
+                        expected: format!("instructions {}", range),
+                        actual: format!("instructions {}", outcome.instructions_executed),
+                    })
+                }
+            }
+        }
+    }
+}
+
+fn describe_result(result: &Result<WasmResult, UserError>) -> String {
+    match result {
+        Ok(WasmResult::Reply(bytes)) => format!("reply 0x{}", encode(bytes)),
+        Ok(WasmResult::Reject(msg)) => {
+            format!("reject {}: {}", RejectCode::CanisterReject, msg)
+        }
+        Err(err) => format!("reject {}: {}", err.reject_code(), err),
+    }
+}
+
+/// Compares two Candid messages by value, i.e., independently of how
+/// their type tables are laid out.
+fn candid_eq(expected: &[u8], actual: &[u8]) -> bool {
+    match (IDLArgs::from_bytes(expected), IDLArgs::from_bytes(actual)) {
+        (Ok(expected), Ok(actual)) => expected.args == actual.args,
+        _ => expected == actual,
+    }
+}
+
+/// Parses the part of an expectation line following the `expect` keyword.
+pub(crate) fn parse_expectation(s: &str) -> Result<Expectation, String> {
+    let (kind, arg) = s
+        .trim()
+        .split_once(char::is_whitespace)
+        .ok_or_else(|| format!("Missing argument of expectation {}.", s.trim()))?;
+    let arg = arg.trim();
+
+    match kind {
+        "reply" => Ok(Expectation::Reply(parse_octet_string(arg)?)),
+        "reply-candid" => {
+            let args = candid_parser::parse_idl_args(arg)
+                .map_err(|e| format!("Invalid Candid value {}: {}", arg, e))?;
+            let encoded = args
+                .to_bytes()
+                .map_err(|e| format!("Failed to encode Candid value {}: {}", arg, e))?;
+            Ok(Expectation::CandidReply {
+                text: arg.to_string(),
+                encoded,
+            })
+        }
+        "reject" => Ok(Expectation::Reject(parse_reject_code(arg)?)),
+        "cycles" => Ok(Expectation::Cycles(parse_range(arg)?)),
+        "instructions" => Ok(Expectation::Instructions(parse_range(arg)?)),
+        _ => Err(format!("Unknown expectation {}.", kind)),
+    }
+}
+
+/// Parses a reject code given either as a number (e.g. `4`)
+/// or by its name (e.g. `CANISTER_REJECT`).
+fn parse_reject_code(s: &str) -> Result<RejectCode, String> {
+    if let Ok(code) = s.parse::<u64>() {
+        return RejectCode::try_from(code).map_err(|_| format!("Invalid reject code {}.", s));
+    }
+    (1..=6)
+        .filter_map(|code| RejectCode::try_from(code).ok())
+        .find(|code| code.to_string().eq_ignore_ascii_case(s))
+        .ok_or_else(|| format!("Invalid reject code {}.", s))
+}
+
+/// Parses a value range `<min>..<max>`, `<min>..`, `..<max>`, or an exact value `<n>`.
+/// Both bounds are inclusive and may contain `_` separators.
+fn parse_range(s: &str) -> Result<ValueRange, String> {
+    fn parse_bound(bound: &str) -> Result<Option<u128>, String> {
+        let bound = bound.trim();
+        if bound.is_empty() {
+            return Ok(None);
+        }
+        bound
+            .replace('_', "")
+            .parse::<u128>()
+            .map(Some)
+            .map_err(|e| format!("Invalid range bound {}: {}", bound, e))
+    }
+
+    let range = match s.split_once("..") {
+        Some((min, max)) => ValueRange {
+            min: parse_bound(min)?,
+            max: parse_bound(max)?,
+        },
+        None => {
+            let value = parse_bound(s)?;
+            ValueRange {
+                min: value,
+                max: value,
+            }
+        }
+    };
+    match range {
+        ValueRange {
+            min: Some(min),
+            max: Some(max),
+        } if min > max => Err(format!("Empty range {}.", s)),
+        ValueRange {
+            min: None,
+            max: None,
+        } => Err(format!("Unbounded range {}.", s)),
+        range => Ok(range),
+    }
+}
+
+/// A failed expectation together with where it was stated.
+pub(crate) struct ExpectationFailure {
+    /// The line of the expectation in the message file.
+    pub line: usize,
+    /// The message the expectation refers to.
+    pub message: String,
+    pub mismatch: Mismatch,
+}
+
+/// Renders the failed expectations as a diff of expected (`-`) vs. actual (`+`) outcomes.
+pub(crate) fn format_report(failures: &[ExpectationFailure]) -> String {
+    let mut report = format!("{} expectation(s) failed:\n", failures.len());
+    for failure in failures {
+        report.push_str(&format!(
+            "\nLine {}: {}\n- {}\n+ {}\n",
+            failure.line, failure.message, failure.mismatch.expected, failure.mismatch.actual
+        ));
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use candid::Encode;
+    use ic_error_types::ErrorCode;
+
+    fn outcome(result: Result<WasmResult, UserError>) -> Outcome {
+        Outcome {
+            result,
+            cycles_consumed: 1_000,
+            instructions_executed: 500,
+        }
+    }
+
+    #[test]
+    fn test_parse_expectation() {
+        assert_eq!(
+            parse_expectation("reply 0x0102"),
+            Ok(Expectation::Reply(vec![1, 2]))
+        );
+        assert_eq!(
+            parse_expectation("reply \"ok\""),
+            Ok(Expectation::Reply(b"ok".to_vec()))
+        );
+        assert_eq!(
+            parse_expectation("reject CANISTER_ERROR"),
+            Ok(Expectation::Reject(RejectCode::CanisterError))
+        );
+        assert_eq!(
+            parse_expectation("reject 4"),
+            Ok(Expectation::Reject(RejectCode::CanisterReject))
+        );
+        assert_eq!(
+            parse_expectation("cycles 1_000..2_000"),
+            Ok(Expectation::Cycles(ValueRange {
+                min: Some(1_000),
+                max: Some(2_000)
+            }))
+        );
+        assert_eq!(
+            parse_expectation("instructions ..10"),
+            Ok(Expectation::Instructions(ValueRange {
+                min: None,
+                max: Some(10)
+            }))
+        );
+        assert!(parse_expectation("reject 7").is_err());
+        assert!(parse_expectation("cycles 2..1").is_err());
+        assert!(parse_expectation("cycles ..").is_err());
+        assert!(parse_expectation("reply").is_err());
+        assert!(parse_expectation("output 0x00").is_err());
+    }
+
+    #[test]
+    fn test_check_reply() {
+        let expectation = parse_expectation("reply 0x0102").unwrap();
+        assert!(expectation
+            .check(&outcome(Ok(WasmResult::Reply(vec![1, 2]))))
+            .is_ok());
+        assert_eq!(
+            expectation.check(&outcome(Ok(WasmResult::Reply(vec![1, 3])))),
+            Err(Mismatch {
+                expected: "reply 0x0102".to_string(),
+                actual: "reply 0x0103".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_check_candid_reply() {
+        let expectation = parse_expectation("reply-candid (42 : nat32, \"foo\")").unwrap();
+        let reply = Encode!(&42_u32, &"foo").unwrap();
+        assert!(expectation
+            .check(&outcome(Ok(WasmResult::Reply(reply))))
+            .is_ok());
+        let reply = Encode!(&43_u32, &"foo").unwrap();
+        let mismatch = expectation
+            .check(&outcome(Ok(WasmResult::Reply(reply))))
+            .unwrap_err();
+        assert_eq!(mismatch.expected, "reply-candid (42 : nat32, \"foo\")");
+        assert!(mismatch.actual.contains("43"));
+    }
+
+    #[test]
+    fn test_check_reject() {
+        let expectation = parse_expectation("reject CANISTER_ERROR").unwrap();
+        let error = UserError::new(ErrorCode::CanisterTrapped, "trapped");
+        assert!(expectation.check(&outcome(Err(error))).is_ok());
+        assert!(expectation
+            .check(&outcome(Ok(WasmResult::Reject("no".to_string()))))
+            .is_err());
+
+        let expectation = parse_expectation("reject CANISTER_REJECT").unwrap();
+        assert!(expectation
+            .check(&outcome(Ok(WasmResult::Reject("no".to_string()))))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_check_ranges() {
+        let ok = outcome(Ok(WasmResult::Reply(vec![])));
+        assert!(parse_expectation("cycles 1000").unwrap().check(&ok).is_ok());
+        assert!(parse_expectation("cycles 1001..")
+            .unwrap()
+            .check(&ok)
+            .is_err());
+        assert!(parse_expectation("instructions 0..500")
+            .unwrap()
+            .check(&ok)
+            .is_ok());
+        assert_eq!(
+            parse_expectation("instructions ..499").unwrap().check(&ok),
+            Err(Mismatch {
+                expected: "instructions ..499".to_string(),
+                actual: "instructions 500".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_format_report() {
+        let report = format_report(&[ExpectationFailure {
+            line: 3,
+            message: "query rwlgt-iiaaa-aaaaa-aaaaa-cai read 0x".to_string(),
+            mismatch: Mismatch {
+                expected: "reply 0x01".to_string(),
+                actual: "reply 0x02".to_string(),
+            },
+        }]);
+        assert_eq!(
+            report,
+            "1 expectation(s) failed:\n\nLine 3: query rwlgt-iiaaa-aaaaa-aaaaa-cai read 0x\n- reply 0x01\n+ reply 0x02\n"
+        );
+    }
+}
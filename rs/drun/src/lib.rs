@@ -1,6 +1,7 @@
 //! Standalone interface for testing application canisters.
 
-use crate::message::{msg_stream_from_file, Message};
+use crate::expectation::{format_report, ExpectationFailure, Outcome};
+use crate::message::{msg_stream_from_file, Message, MessageLine};
 use hex::encode;
 use ic_config::{subnet_config::SubnetConfig, Config};
 use ic_crypto_test_utils_ni_dkg::dummy_initial_dkg_transcript_with_master_key;
@@ -12,6 +13,7 @@ use ic_interfaces::{
     execution_environment::{IngressHistoryReader, QueryExecutionError},
     messaging::MessageRouting,
 };
+use ic_interfaces_state_manager::StateReader;
 use ic_messaging::MessageRoutingImpl;
 use ic_metrics::MetricsRegistry;
 use ic_protobuf::registry::{
@@ -54,6 +56,7 @@ use std::sync::{Arc, Mutex};
 use std::{thread::sleep, time::Duration};
 use tower::util::ServiceExt;
 
+mod expectation;
 mod message;
 
 // drun will panic if it takes more than this many batches
@@ -61,6 +64,9 @@ mod message;
 const MAX_BATCHES_UNTIL_RESPONSE: u64 = 100_000;
 // how long to wait between batches
 const WAIT_PER_BATCH: Duration = Duration::from_millis(5);
+// metrics used to determine the number of instructions executed per message
+const INGRESS_INSTRUCTIONS_METRIC: &str = "scheduler_instructions_consumed_per_message";
+const QUERY_INSTRUCTIONS_METRIC: &str = "execution_query_instructions";
 
 pub struct DrunOptions {
     pub msg_filename: String,
@@ -77,14 +83,58 @@ fn deliver_message(
     message_routing: &dyn MessageRouting,
     ingress_hist_reader: &dyn IngressHistoryReader,
     extra_batches: u64,
-) {
+) -> Result<WasmResult, UserError> {
     let message_id = msg.id();
 
-    let _ = execute_ingress_message(message_routing, msg, &message_id, ingress_hist_reader);
+    let result = execute_ingress_message(message_routing, msg, &message_id, ingress_hist_reader);
     // print result after waiting, to not interleave the result
     // with debug.print messages from subsequent calls. revise after DFN-1269.
     wait_extra_batches(message_routing, extra_batches);
     print_ingress_result(&message_id, ingress_hist_reader);
+    result
+}
+
+/// Resource usage counters that are compared before and after
+/// processing a message to check expectations on cycles and instructions.
+struct Usage {
+    cycles_consumed: u128,
+    instructions_executed: u64,
+}
+
+impl Usage {
+    fn observe(
+        state_manager: &StateManagerImpl,
+        metrics_registry: &MetricsRegistry,
+        instructions_metric: &str,
+    ) -> Self {
+        let state = state_manager.get_latest_state().take();
+        let cycles_consumed = state
+            .canisters_iter()
+            .map(|canister| canister.system_state.canister_metrics.consumed_cycles.get())
+            .sum();
+        let instructions_executed = metrics_registry
+            .prometheus_registry()
+            .gather()
+            .iter()
+            .filter(|family| family.get_name() == instructions_metric)
+            .flat_map(|family| family.get_metric())
+            .map(|metric| metric.get_histogram().get_sample_sum())
+            .sum::<f64>() as u64;
+        Self {
+            cycles_consumed,
+            instructions_executed,
+        }
+    }
+
+    fn outcome_since(self, before: Usage, result: Result<WasmResult, UserError>) -> Outcome {
+        Outcome {
+            result,
+            cycles_consumed: self.cycles_consumed.saturating_sub(before.cycles_consumed),
+            instructions_executed: self
+                .instructions_executed
+                .saturating_sub(before.instructions_executed),
+        }
+    }
 }
 
 fn setup_logger(log_file: PathBuf) -> Logger {
@@ -268,15 +318,42 @@ pub async fn run_drun(uo: DrunOptions) -> Result<(), String> {
         MaliciousFlags::default(),
     );
 
+    // the outcome of the last processed message and its line in the message file
+    let mut last_outcome: Option<(MessageLine, Outcome)> = None;
+    let mut failures = vec![];
+
     for parse_result in msg_stream {
-        match parse_result? {
-            Message::Install(msg) => {
+        let message_line = parse_result?;
+        // Expectations are not executed, so they are checked without observing the usage.
+        if let Message::Expect(expectation) = &message_line.message {
+            let Some((message, outcome)) = &last_outcome else {
+                return Err(format!(
+                    "Line {}: Expectation does not follow a message.",
+                    message_line.line
+                ));
+            };
+            if let Err(mismatch) = expectation.check(outcome) {
+                failures.push(ExpectationFailure {
+                    line: message_line.line,
+                    message: format!("{} (line {})", message.text, message.line),
+                    mismatch,
+                });
+            }
+            continue;
+        }
+        let instructions_metric = match &message_line.message {
+            Message::Query(_) => QUERY_INSTRUCTIONS_METRIC,
+            _ => INGRESS_INSTRUCTIONS_METRIC,
+        };
+        let before = Usage::observe(&state_manager, &metrics_registry, instructions_metric);
+        let result = match &message_line.message {
+            Message::Install(msg) | Message::Ingress(msg) | Message::Create(msg) => {
                 deliver_message(
-                    msg,
+                    msg.clone(),
                     &message_routing,
                     ingress_hist_reader.as_ref(),
                     extra_batches,
-                );
+                )
             }
 
             Message::Query(q) => {
@@ -287,35 +364,33 @@ pub async fn run_drun(uo: DrunOptions) -> Result<(), String> {
                     &secret_key,
                     replica_config.subnet_id,
                 );
-                let query_result = match query_handler.clone().oneshot((q, None)).await.unwrap() {
+                let query_result = match query_handler
+                    .clone()
+                    .oneshot((q.clone(), None))
+                    .await
+                    .unwrap()
+                {
                     Ok((result, _)) => result,
                     Err(QueryExecutionError::CertifiedStateUnavailable) => {
                         panic!("Certified state unavailable for query call.")
                     }
                 };
-                print_query_result(query_result);
+                print_query_result(query_result.clone());
+                query_result
             }
 
-            Message::Ingress(msg) => {
-                deliver_message(
-                    msg,
-                    &message_routing,
-                    ingress_hist_reader.as_ref(),
-                    extra_batches,
-                );
-            }
+            Message::Expect(_) => unreachable!("Expectations are checked above."),
+        };
+        let after = Usage::observe(&state_manager, &metrics_registry, instructions_metric);
+        last_outcome = Some((message_line, after.outcome_since(before, result)));
+    }
 
-            Message::Create(msg) => {
-                deliver_message(
-                    msg,
-                    &message_routing,
-                    ingress_hist_reader.as_ref(),
-                    extra_batches,
-                );
-            }
-        }
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        eprint!("{}", format_report(&failures));
+        Err(format!("{} expectation(s) failed", failures.len()))
     }
-    Ok(())
 }
 
 fn disable_dts(subnet_config: &mut SubnetConfig) {
@@ -18,6 +18,7 @@ const ARG_MESSAGES: &str = "messages";
 const ARG_EXTRA_BATCHES: &str = "extra-batches";
 const ARG_INSTRUCTION_LIMIT: &str = "instruction-limit";
 const ARG_SUBNET_TYPE: &str = "subnet-type";
+const ARG_INSTRUCTION_PROFILE_DIR: &str = "instruction-profile-dir";
 
 const GB: u64 = 1024 * 1024 * 1024;
 const MAIN_MEMORY_CAPACITY: NumBytes = NumBytes::new(16 * GB);
@@ -68,6 +69,13 @@ async fn drun_main() -> Result<(), String> {
         hypervisor_config.max_canister_memory_size =
             hypervisor_config.embedders_config.max_wasm_memory_size
                 + hypervisor_config.embedders_config.max_stable_memory_size;
+        if let Some(dir) = matches.get_one::<String>(ARG_INSTRUCTION_PROFILE_DIR) {
+            hypervisor_config
+                .embedders_config
+                .feature_flags
+                .instruction_profiling = FlagStatus::Enabled;
+            hypervisor_config.embedders_config.instruction_profile_dir = Some(PathBuf::from(dir));
+        }
 
         let cfg = Config::load_with_default(&source, default_config).unwrap_or_else(|err| {
             eprintln!("Failed to load config:\n  {}", err);
@@ -167,5 +175,15 @@ fn get_arg_matches() -> ArgMatches {
                 .value_name("Subnet Type")
                 .num_args(1),
         )
+        .arg(
+            Arg::new(ARG_INSTRUCTION_PROFILE_DIR)
+                .long(ARG_INSTRUCTION_PROFILE_DIR)
+                .value_name("dir")
+                .help(
+                    "Record the instructions executed by each Wasm function and write \
+                    one profile per message in the folded stack format to this directory.",
+                )
+                .num_args(1),
+        )
         .get_matches()
 }
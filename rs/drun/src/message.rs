@@ -1,5 +1,6 @@
 use super::CanisterId;
 
+use crate::expectation::{parse_expectation, Expectation};
 use hex::decode;
 use ic_execution_environment::execution::upgrade::ENHANCED_ORTHOGONAL_PERSISTENCE_SECTION;
 use ic_management_canister_types::{
@@ -25,6 +26,15 @@ pub(crate) enum Message {
     Query(Query),
     Install(SignedIngress),
     Create(SignedIngress),
+    Expect(Expectation),
+}
+
+/// A message together with the line of the message file it was parsed from.
+pub(crate) struct MessageLine {
+    /// The (1-based) line number.
+    pub line: usize,
+    pub text: String,
+    pub message: Message,
 }
 
 #[derive(Debug)]
@@ -126,7 +136,7 @@ impl<R: Read> Iterator for LineIterator<R> {
 
 pub(crate) fn msg_stream_from_file(
     filename: &str,
-) -> Result<impl Iterator<Item = Result<Message, String>>, String> {
+) -> Result<impl Iterator<Item = Result<MessageLine, String>>, String> {
     let f = File::open(filename).map_err(|e| e.to_string())?;
     let line_iterator = LineIterator::new(f);
 
@@ -138,15 +148,24 @@ pub(crate) fn msg_stream_from_file(
             _ => true,
         })
         .map(|(i, line)| match line {
-            Ok(line) => {
-                parse_message(&line, i as u64).map_err(|e| format!("Line {}: {}", i + 1, e))
-            }
+            Ok(line) => match parse_message(&line, i as u64) {
+                Ok(message) => Ok(MessageLine {
+                    line: i + 1,
+                    text: line.trim_end().to_string(),
+                    message,
+                }),
+                Err(e) => Err(format!("Line {}: {}", i + 1, e)),
+            },
             Err(e) => Err(format!("Error while reading line {}: {}", i, e)),
         }))
 }
 
 fn parse_message(s: &str, nonce: u64) -> Result<Message, String> {
     let s = s.trim_end();
+    // Expectations may contain arbitrary whitespace (e.g. in Candid values).
+    if let Some(("expect", expectation)) = s.split_once(char::is_whitespace) {
+        return parse_expectation(expectation).map(Message::Expect);
+    }
     let tokens: Vec<&str> = s.splitn(4, char::is_whitespace).collect();
 
     match &tokens[..] {
@@ -309,7 +328,7 @@ fn validate_method_name(method_name: &str) -> Result<String, String> {
     }
 }
 
-fn parse_octet_string(input_str: &str) -> Result<Vec<u8>, String> {
+pub(crate) fn parse_octet_string(input_str: &str) -> Result<Vec<u8>, String> {
     if input_str.starts_with('"') {
         parse_quoted(input_str)
     } else {
@@ -501,6 +520,16 @@ mod tests {
         assert!(parse_message(s, 0).is_err());
     }
 
+    #[test]
+    fn test_parse_message_expectation() {
+        assert_eq!(
+            parse_message("expect reply 0x010203", 0).unwrap(),
+            Message::Expect(Expectation::Reply(vec![1, 2, 3]))
+        );
+        assert!(parse_message("expect reply-candid (1 : nat, \"a b\")", 0).is_ok());
+        assert!(parse_message("expect nothing", 0).is_err());
+    }
+
     #[test]
     fn test_line_iterator() {
         let text = Cursor::new(
@@ -15,7 +15,10 @@ use wasmtime::Module;
 
 use crate::wasmtime_embedder::CanisterMemoryType;
 use crate::{
-    wasm_utils::{compile, decoding::decode_wasm, Segments, WasmImportsDetails},
+    wasm_utils::{
+        compile, decoding::decode_wasm, instruction_profile::InstructionProfileExporter, Segments,
+        WasmImportsDetails,
+    },
     wasmtime_embedder::WasmtimeInstance,
     CompilationCache, CompilationResult, SerializedModule, WasmExecutionInput, WasmtimeEmbedder,
 };
@@ -165,6 +168,9 @@ pub struct WasmExecutorImpl {
     metrics: WasmExecutorMetrics,
     log: ReplicaLogger,
     fd_factory: Arc<dyn PageAllocatorFileDescriptor>,
+    /// If set, the instruction profiles of all executions are written to the
+    /// directory of the exporter.
+    instruction_profile_exporter: Option<InstructionProfileExporter>,
 }
 
 impl WasmExecutor for WasmExecutorImpl {
@@ -212,10 +218,11 @@ impl WasmExecutor for WasmExecutorImpl {
         let wasm_reserved_pages = get_wasm_reserved_pages(execution_state);
         let mut wasm_memory = execution_state.wasm_memory.clone();
         let mut stable_memory = execution_state.stable_memory.clone();
+        let canister_id = sandbox_safe_system_state.canister_id();
 
         let (
             slice_execution_output,
-            wasm_execution_output,
+            mut wasm_execution_output,
             wasm_state_changes,
             instance_or_system_api,
         ) = process(
@@ -236,6 +243,19 @@ impl WasmExecutor for WasmExecutorImpl {
             Rc::new(DefaultOutOfInstructionsHandler::default()),
         );
 
+        if let (Some(exporter), Some(profile)) = (
+            &self.instruction_profile_exporter,
+            wasm_execution_output.instruction_profile.take(),
+        ) {
+            // Failures are only logged because profiling is a development aid
+            // that must not affect execution.
+            if let Err(err) =
+                exporter.export(canister_id, &execution_state.wasm_binary.binary, &profile)
+            {
+                warn!(self.log, "Canister {}: {}", canister_id, err);
+            }
+        }
+
         // Collect logs only when the flag is enabled to avoid producing too much data.
         if EMIT_STATE_HASHES_FOR_DEBUGGING == FlagStatus::Enabled {
             self.emit_state_hashes_for_debugging(&wasm_state_changes, &wasm_execution_output);
@@ -342,11 +362,17 @@ impl WasmExecutorImpl {
         log: ReplicaLogger,
         fd_factory: Arc<dyn PageAllocatorFileDescriptor>,
     ) -> Self {
+        let instruction_profile_exporter = wasm_embedder
+            .config()
+            .instruction_profile_dir
+            .clone()
+            .map(InstructionProfileExporter::new);
         Self {
             wasm_embedder,
             metrics: WasmExecutorMetrics::new(metrics_registry),
             log,
             fd_factory: Arc::clone(&fd_factory),
+            instruction_profile_exporter,
         }
     }
 
@@ -466,6 +492,7 @@ pub fn wasm_execution_error(
             instance_stats: InstanceStats::default(),
             system_api_call_counters: SystemApiCallCounters::default(),
             canister_log: Default::default(),
+            instruction_profile: None,
         },
         None,
     )
@@ -634,6 +661,7 @@ pub fn process(
                     instance_stats: InstanceStats::default(),
                     system_api_call_counters: SystemApiCallCounters::default(),
                     canister_log: Default::default(),
+                    instruction_profile: None,
                 },
                 None,
                 Err(system_api.unwrap()), // should be safe because we've passed Some(api) to new_instance
@@ -652,6 +680,7 @@ pub fn process(
     // Get the executed/remaining instructions for the message and the slice.
     let instruction_counter = instance.instruction_counter();
     let instance_stats = instance.get_stats();
+    let instruction_profiler = instance.store_data_mut().instruction_profiler.take();
     //unwrap should not fail, because we have passed Some(system_api) to the instance above
     let system_api = instance.store_data_mut().system_api_mut().unwrap();
     let system_api_call_counters = system_api.call_counters();
@@ -669,6 +698,8 @@ pub fn process(
         .message_instructions_executed(instruction_counter)
         .min(message_instruction_limit);
     let message_instructions_left = message_instruction_limit - message_instructions_executed;
+    let instruction_profile =
+        instruction_profiler.map(|profiler| profiler.finish(message_instructions_executed.get()));
 
     // In case the message dirtied too many pages, as a performance optimization we will
     // yield the control to the replica and then resume copying dirty pages in a new execution slice.
@@ -693,6 +724,7 @@ pub fn process(
                         instance_stats,
                         system_api_call_counters,
                         canister_log,
+                        instruction_profile,
                     },
                     None,
                     Ok(instance),
@@ -800,7 +832,7 @@ pub fn process(
                 }
                 _ => None,
             } {
-                canister_log.add_record(timestamp_nanos, log_message.into_bytes());
+                canister_log.add_trap_record(timestamp_nanos, log_message.into_bytes());
             }
             None
         }
@@ -828,6 +860,7 @@ pub fn process(
             instance_stats,
             system_api_call_counters,
             canister_log,
+            instruction_profile,
         },
         wasm_state_changes,
         Ok(instance),
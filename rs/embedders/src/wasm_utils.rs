@@ -20,6 +20,7 @@ use crate::{serialized_module::SerializedModule, CompilationResult, WasmtimeEmbe
 use wasmtime::InstancePre;
 
 pub mod decoding;
+pub mod instruction_profile;
 pub mod instrumentation;
 mod system_api_replacements;
 pub mod validation;
@@ -206,6 +207,7 @@ fn validate_and_instrument(
         config.cost_to_compile_wasm_instruction,
         config.feature_flags.write_barrier,
         config.feature_flags.wasm_native_stable_memory,
+        config.feature_flags.instruction_profiling,
         config.metering_type,
         config.subnet_type,
         config.dirty_page_overhead,
@@ -0,0 +1,197 @@
+//! Export of instruction profiles recorded by the probes that the
+//! instrumentation injects if instruction profiling is enabled.
+//!
+//! Profiles are written in the folded stack format understood by tools like
+//! `inferno` and `flamegraph.pl`: every line contains the `;`-separated names of
+//! the functions on a call stack, outermost function first, followed by the
+//! number of instructions executed by the innermost function.
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use ic_interfaces::execution_environment::InstructionProfile;
+use ic_types::{CanisterId, NumBytes};
+use ic_wasm_types::CanisterModule;
+use wasmparser::{KnownCustom, Name, Parser, Payload};
+
+use super::decoding::decode_wasm;
+
+/// Writes the instruction profiles of the executions to a directory. Shared
+/// by the sandboxed and the in-process Wasm executors.
+pub struct InstructionProfileExporter {
+    dir: PathBuf,
+    /// Number of instruction profiles written so far. Used to order the files.
+    count: AtomicU64,
+}
+
+impl InstructionProfileExporter {
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            count: AtomicU64::new(0),
+        }
+    }
+
+    /// Writes the profile of an execution of the given canister module, see
+    /// [`write_folded_profile`].
+    pub fn export(
+        &self,
+        canister_id: CanisterId,
+        canister_module: &CanisterModule,
+        profile: &InstructionProfile,
+    ) -> Result<Option<PathBuf>, String> {
+        // The name section has to be read from the decoded module because
+        // canister modules may be gzip-compressed.
+        let wasm = decode_wasm(NumBytes::new(u64::MAX), canister_module.to_shared_vec())
+            .map_err(|err| format!("Failed to decode the Wasm module: {}", err))?;
+        let sequence = self.count.fetch_add(1, Ordering::Relaxed);
+        write_folded_profile(&self.dir, canister_id, sequence, wasm.as_slice(), profile)
+            .map_err(|err| format!("Failed to write the instruction profile: {}", err))
+    }
+}
+
+/// Returns the names of the functions found in the name section of the given
+/// Wasm module, indexed by the function index. Returns an empty map if the
+/// module cannot be parsed or has no name section.
+pub fn function_names(wasm: &[u8]) -> BTreeMap<u32, String> {
+    let mut names = BTreeMap::new();
+    for payload in Parser::new(0).parse_all(wasm) {
+        let Ok(Payload::CustomSection(reader)) = payload else {
+            continue;
+        };
+        let KnownCustom::Name(name_section) = reader.as_known() else {
+            continue;
+        };
+        for subsection in name_section.into_iter().flatten() {
+            if let Name::Function(name_map) = subsection {
+                for naming in name_map.into_iter().flatten() {
+                    names.insert(naming.index, naming.name.to_string());
+                }
+            }
+        }
+    }
+    names
+}
+
+fn function_name(names: &BTreeMap<u32, String>, index: u32) -> String {
+    match names.get(&index) {
+        // Semicolons and whitespace are separators in the folded format.
+        Some(name) => name.replace(|c: char| c == ';' || c.is_whitespace(), "_"),
+        None => format!("func{}", index),
+    }
+}
+
+/// Formats the profile in the folded stack format.
+pub fn fold(profile: &InstructionProfile, names: &BTreeMap<u32, String>) -> String {
+    let mut folded = String::new();
+    for (stack, instructions) in profile.stacks.iter() {
+        let stack: Vec<_> = stack
+            .iter()
+            .map(|index| function_name(names, *index))
+            .collect();
+        folded.push_str(&format!("{} {}\n", stack.join(";"), instructions));
+    }
+    folded
+}
+
+/// Writes the profile of a single message execution to
+/// `<dir>/<canister_id>/<sequence>_<entry function>.folded` and returns the
+/// path of the written file. Empty profiles are not written.
+///
+/// The given Wasm module must be the uninstrumented (but decoded) module that
+/// was executed.
+pub fn write_folded_profile(
+    dir: &Path,
+    canister_id: CanisterId,
+    sequence: u64,
+    wasm: &[u8],
+    profile: &InstructionProfile,
+) -> std::io::Result<Option<PathBuf>> {
+    let Some((entry, _)) = profile.stacks.first_key_value() else {
+        return Ok(None);
+    };
+    let names = function_names(wasm);
+    let entry = entry
+        .first()
+        .map(|index| function_name(&names, *index))
+        .unwrap_or_default()
+        .replace(std::path::is_separator, "_");
+    let dir = dir.join(canister_id.to_string());
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("{:06}_{}.folded", sequence, entry));
+    std::fs::write(&path, fold(profile, &names))?;
+    Ok(Some(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ic_test_utilities_types::ids::canister_test_id;
+
+    const WAT: &str = r#"
+        (module
+            (func $inc)
+            (func $test (export "canister_update test")
+                (call $inc)
+                (call 2)
+            )
+            (func)
+        )"#;
+
+    fn profile() -> InstructionProfile {
+        InstructionProfile {
+            stacks: BTreeMap::from([(vec![1], 5), (vec![1, 0], 3), (vec![1, 2], 7)]),
+        }
+    }
+
+    #[test]
+    fn fold_uses_function_names() {
+        let wasm = wat::parse_str(WAT).unwrap();
+        let names = function_names(&wasm);
+        assert_eq!(
+            names,
+            BTreeMap::from([(0, "inc".to_string()), (1, "test".to_string())])
+        );
+        assert_eq!(
+            fold(&profile(), &names),
+            "test 5\ntest;inc 3\ntest;func2 7\n"
+        );
+    }
+
+    #[test]
+    fn write_folded_profile_writes_one_file_per_execution() {
+        let wasm = wat::parse_str(WAT).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let canister_id = canister_test_id(1);
+
+        let path = write_folded_profile(dir.path(), canister_id, 7, &wasm, &profile())
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            path,
+            dir.path()
+                .join(canister_id.to_string())
+                .join("000007_test.folded")
+        );
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "test 5\ntest;inc 3\ntest;func2 7\n"
+        );
+
+        // Empty profiles are not written.
+        assert_eq!(
+            write_folded_profile(
+                dir.path(),
+                canister_id,
+                8,
+                &wasm,
+                &InstructionProfile::default()
+            )
+            .unwrap(),
+            None
+        );
+    }
+}
@@ -109,6 +109,25 @@
 //! (memory (export "stable_memory_bytemap") i32 (i64.const STABLE_BYTEMAP_SIZE) (i64.const STABLE_BYTEMAP_SIZE))
 //! ```
 //!
+//! # Instruction profiling
+//!
+//! If instruction profiling is enabled, two more System API functions are
+//! inserted after all other injected imports:
+//!
+//! ```wasm
+//! (import "__" "profile_enter" (func ((param i32))))
+//! (import "__" "profile_exit" (func ((param i32))))
+//! ```
+//!
+//! The body of every function defined in the module is then wrapped into a
+//! block with the result type of the function, preceded by a call to
+//! `profile_enter` and followed by a call to `profile_exit`. Additional calls
+//! to `profile_exit` are inserted before every `return`, `return_call` and
+//! `return_call_indirect`. Both functions receive the index of the function in
+//! the original module, so that the name section of the original binary can be
+//! used to resolve function names. The probes are inserted after metering, so
+//! they don't consume any instructions.
+//!
 
 use super::system_api_replacements::replacement_functions;
 use super::validation::API_VERSION_IC0;
@@ -164,12 +183,24 @@ pub(crate) enum InjectedImports {
 }
 
 impl InjectedImports {
-    fn count(wasm_native_stable_memory: FlagStatus) -> usize {
-        if wasm_native_stable_memory == FlagStatus::Enabled {
+    fn count(wasm_native_stable_memory: FlagStatus, instruction_profiling: FlagStatus) -> usize {
+        let base = if wasm_native_stable_memory == FlagStatus::Enabled {
             5
         } else {
             2
-        }
+        };
+        let profiling = if instruction_profiling == FlagStatus::Enabled {
+            2
+        } else {
+            0
+        };
+        base + profiling
+    }
+
+    /// Returns the index of the injected `profile_enter` import. The
+    /// `profile_exit` import immediately follows it.
+    fn profile_enter_index(wasm_native_stable_memory: FlagStatus) -> u32 {
+        Self::count(wasm_native_stable_memory, FlagStatus::Disabled) as u32
     }
 }
 
@@ -712,6 +743,8 @@ const TRY_GROW_WASM_MEMORY_FUN_NAME: &str = "try_grow_wasm_memory";
 const TRY_GROW_STABLE_MEMORY_FUN_NAME: &str = "try_grow_stable_memory";
 const INTERNAL_TRAP_FUN_NAME: &str = "internal_trap";
 const STABLE_READ_FIRST_ACCESS_NAME: &str = "stable_read_first_access";
+const PROFILE_ENTER_FUN_NAME: &str = "profile_enter";
+const PROFILE_EXIT_FUN_NAME: &str = "profile_exit";
 const TABLE_STR: &str = "table";
 pub(crate) const INSTRUCTIONS_COUNTER_GLOBAL_NAME: &str = "canister counter_instructions";
 pub(crate) const DIRTY_PAGES_COUNTER_GLOBAL_NAME: &str = "canister counter_dirty_pages";
@@ -828,6 +861,7 @@ fn mutate_function_indices(module: &mut Module, f: impl Fn(u32) -> u32) {
 fn inject_helper_functions(
     mut module: Module,
     wasm_native_stable_memory: FlagStatus,
+    instruction_profiling: FlagStatus,
     mem_type: WasmMemoryType,
 ) -> Module {
     // insert types
@@ -854,8 +888,9 @@ fn inject_helper_functions(
     };
 
     let mut old_imports = module.imports;
-    module.imports =
-        Vec::with_capacity(old_imports.len() + InjectedImports::count(wasm_native_stable_memory));
+    let injected_imports_count =
+        InjectedImports::count(wasm_native_stable_memory, instruction_profiling);
+    module.imports = Vec::with_capacity(old_imports.len() + injected_imports_count);
     module.imports.push(ooi_imp);
     module.imports.push(tgwm_imp);
 
@@ -888,10 +923,25 @@ fn inject_helper_functions(
         module.imports.push(fr_imp);
     }
 
+    if instruction_profiling == FlagStatus::Enabled {
+        let profile_type = FuncType::new([ValType::I32], []);
+        let profile_type_idx = add_func_type(&mut module, profile_type);
+        module.imports.push(Import {
+            module: INSTRUMENTED_FUN_MODULE,
+            name: PROFILE_ENTER_FUN_NAME,
+            ty: TypeRef::Func(profile_type_idx),
+        });
+        module.imports.push(Import {
+            module: INSTRUMENTED_FUN_MODULE,
+            name: PROFILE_EXIT_FUN_NAME,
+            ty: TypeRef::Func(profile_type_idx),
+        });
+    }
+
     module.imports.append(&mut old_imports);
 
     // now increment all function references by InjectedImports::Count
-    let cnt = InjectedImports::count(wasm_native_stable_memory) as u32;
+    let cnt = injected_imports_count as u32;
     mutate_function_indices(&mut module, |i| i + cnt);
 
     debug_assert!(
@@ -913,6 +963,11 @@ fn inject_helper_functions(
                 == "stable_read_first_access"
         );
     }
+    if instruction_profiling == FlagStatus::Enabled {
+        let profile_enter = InjectedImports::profile_enter_index(wasm_native_stable_memory);
+        debug_assert!(module.imports[profile_enter as usize].name == "profile_enter");
+        debug_assert!(module.imports[profile_enter as usize + 1].name == "profile_exit");
+    }
 
     module
 }
@@ -940,6 +995,7 @@ pub(super) fn instrument(
     cost_to_compile_wasm_instruction: NumInstructions,
     write_barrier: FlagStatus,
     wasm_native_stable_memory: FlagStatus,
+    instruction_profiling: FlagStatus,
     metering_type: MeteringType,
     subnet_type: SubnetType,
     dirty_page_overhead: NumInstructions,
@@ -948,7 +1004,12 @@ pub(super) fn instrument(
 ) -> Result<InstrumentationOutput, WasmInstrumentationError> {
     let main_memory_type = main_memory_type(&module);
     let stable_memory_index;
-    let mut module = inject_helper_functions(module, wasm_native_stable_memory, main_memory_type);
+    let mut module = inject_helper_functions(
+        module,
+        wasm_native_stable_memory,
+        instruction_profiling,
+        main_memory_type,
+    );
     module = export_table(module);
     (module, stable_memory_index) = update_memories(
         module,
@@ -1050,6 +1111,19 @@ pub(super) fn instrument(
         }
     }
 
+    // Inject the profiling probes. This has to happen after all other
+    // injections into the bodies of the original functions and before any
+    // helper functions are added.
+    if instruction_profiling == FlagStatus::Enabled {
+        let num_original_imported_functions = num_imported_functions as u32
+            - InjectedImports::count(wasm_native_stable_memory, instruction_profiling) as u32;
+        inject_profiling(
+            &mut module,
+            InjectedImports::profile_enter_index(wasm_native_stable_memory),
+            num_original_imported_functions,
+        )?;
+    }
+
     module = export_additional_symbols(module, &special_indices, wasm_native_stable_memory);
 
     if wasm_native_stable_memory == FlagStatus::Enabled {
@@ -1563,6 +1637,87 @@ fn inject_metering(
     *orig_elems = elems;
 }
 
+// Wraps the body of every function defined in the module into a block which
+// is surrounded by calls to `profile_enter` and `profile_exit`. Calls to
+// `profile_exit` are also inserted before every instruction that returns from
+// the function. A `br` to the outermost label now targets the injected block,
+// which falls through to the `profile_exit` call.
+fn inject_profiling(
+    module: &mut Module,
+    profile_enter_fn: u32,
+    num_original_imported_functions: u32,
+) -> Result<(), WasmInstrumentationError> {
+    let profile_exit_fn = profile_enter_fn + 1;
+
+    let mut block_types = Vec::with_capacity(module.code_sections.len());
+    for i in 0..module.code_sections.len() {
+        let results = match &module.types[module.functions[i] as usize]
+            .composite_type
+            .inner
+        {
+            CompositeInnerType::Func(t) => t.results().to_vec(),
+            other => {
+                return Err(WasmInstrumentationError::InvalidFunctionType(format!(
+                    "Function has type which is not a function type. Found type: {:?}",
+                    other
+                )))
+            }
+        };
+        let block_type = match results.as_slice() {
+            [] => BlockType::Empty,
+            [ty] => BlockType::Type(*ty),
+            _ => BlockType::FuncType(add_func_type(
+                module,
+                FuncType::new([], results.iter().copied()),
+            )),
+        };
+        block_types.push(block_type);
+    }
+
+    use Operator::*;
+
+    for (i, (func_body, block_type)) in module.code_sections.iter_mut().zip(block_types).enumerate()
+    {
+        let original_index = num_original_imported_functions + i as u32;
+        let exit = [
+            I32Const {
+                value: original_index as i32,
+            },
+            Call {
+                function_index: profile_exit_fn,
+            },
+        ];
+        let orig_elems = std::mem::take(&mut func_body.instructions);
+        let mut elems = Vec::with_capacity(orig_elems.len() + 8);
+        elems.extend_from_slice(&[
+            I32Const {
+                value: original_index as i32,
+            },
+            Call {
+                function_index: profile_enter_fn,
+            },
+            Block {
+                blockty: block_type,
+            },
+        ]);
+        // The last instruction is the `End` of the function body.
+        let (body_end, body) = orig_elems
+            .split_last()
+            .expect("Function body must end with an `End` instruction");
+        for op in body {
+            if let Return | ReturnCall { .. } | ReturnCallIndirect { .. } = op {
+                elems.extend_from_slice(&exit);
+            }
+            elems.push(op.clone());
+        }
+        elems.push(End);
+        elems.extend_from_slice(&exit);
+        elems.push(body_end.clone());
+        func_body.instructions = elems;
+    }
+    Ok(())
+}
+
 // This function adds mem barrier writes, assuming that arguments
 // of the original store operation are on the stack
 fn write_barrier_instructions<'a>(
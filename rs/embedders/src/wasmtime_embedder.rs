@@ -5,7 +5,7 @@ pub mod system_api_complexity;
 
 use std::{
     cell::Ref,
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     convert::TryFrom,
     mem::size_of,
     sync::{atomic::Ordering, Arc, Mutex},
@@ -20,7 +20,8 @@ use wasmtime::{
 pub use host_memory::WasmtimeMemoryCreator;
 use ic_config::{embedders::Config as EmbeddersConfig, flag_status::FlagStatus};
 use ic_interfaces::execution_environment::{
-    CanisterBacktrace, HypervisorError, HypervisorResult, InstanceStats, SystemApi, TrapCode,
+    CanisterBacktrace, HypervisorError, HypervisorResult, InstanceStats, InstructionProfile,
+    SystemApi, TrapCode,
 };
 use ic_logger::{debug, error, fatal, ReplicaLogger};
 use ic_replicated_state::{
@@ -420,6 +421,9 @@ impl WasmtimeEmbedder {
                     .table_elements(MAX_STORE_TABLE_ELEMENTS)
                     .build(),
                 canister_backtrace: self.config.feature_flags.canister_backtrace,
+                instruction_profiler: (self.config.feature_flags.instruction_profiling
+                    == FlagStatus::Enabled)
+                    .then(InstructionProfiler::default),
             },
         );
         store.limiter(|state| &mut state.limits);
@@ -730,6 +734,8 @@ pub struct StoreData {
     pub num_stable_dirty_pages_from_non_native_writes: NumOsPages,
     pub limits: StoreLimits,
     pub canister_backtrace: FlagStatus,
+    /// Only present if instruction profiling is enabled.
+    pub instruction_profiler: Option<InstructionProfiler>,
 }
 
 impl StoreData {
@@ -759,6 +765,50 @@ impl StoreData {
     }
 }
 
+/// Aggregates the instructions executed by each function from the calls to the
+/// `profile_enter` and `profile_exit` probes injected by the instrumentation.
+///
+/// All instruction counts are relative to the start of the message execution,
+/// so a profile spanning multiple slices is accumulated correctly.
+#[derive(Default)]
+pub struct InstructionProfiler {
+    stack: Vec<u32>,
+    last_instructions: u64,
+    stacks: BTreeMap<Vec<u32>, u64>,
+}
+
+impl InstructionProfiler {
+    /// Attributes the instructions executed since the last probe to the
+    /// current call stack.
+    fn record(&mut self, instructions: u64) {
+        let delta = instructions.saturating_sub(self.last_instructions);
+        self.last_instructions = instructions;
+        if delta > 0 && !self.stack.is_empty() {
+            *self.stacks.entry(self.stack.clone()).or_default() += delta;
+        }
+    }
+
+    pub fn enter(&mut self, function_index: u32, instructions: u64) {
+        self.record(instructions);
+        self.stack.push(function_index);
+    }
+
+    pub fn exit(&mut self, function_index: u32, instructions: u64) {
+        self.record(instructions);
+        debug_assert_eq!(self.stack.last(), Some(&function_index));
+        self.stack.pop();
+    }
+
+    /// Returns the profile. Functions that are still on the stack (e.g.
+    /// because the execution trapped) are closed at `instructions`.
+    pub fn finish(mut self, instructions: u64) -> InstructionProfile {
+        self.record(instructions);
+        InstructionProfile {
+            stacks: self.stacks,
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct PageAccessResults {
     pub wasm_dirty_pages: Vec<PageIndex>,
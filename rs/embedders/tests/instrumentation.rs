@@ -429,6 +429,58 @@ fn metering_plain() {
     assert_eq!(instructions_used, 1 + cost_a(10) + ctrap);
 }
 
+#[test]
+#[allow(clippy::field_reassign_with_default)]
+fn instruction_profiling_attributes_all_instructions() {
+    let wat = r#"
+        (module
+            (global $g1 (export "g1") (mut i64) (i64.const 0))
+            (func $inc (param $early i32) (result i64)
+                (if (local.get $early) (then (return (i64.const 1))))
+                global.get $g1
+                i64.const 2
+                i64.add
+            )
+            (func $test (export "canister_update test")
+                (global.set $g1 (call $inc (i32.const 0)))
+                (global.set $g1 (call $inc (i32.const 1)))
+            )
+        )"#;
+
+    let mut instance = new_instance(wat, 1000);
+    instance.run(func_ref("test")).unwrap();
+    let instructions_without_profiling = instr_used(&mut instance);
+    assert!(instance.store_data().instruction_profiler.is_none());
+
+    let mut config = EmbeddersConfig::default();
+    config.dirty_page_overhead = SchedulerConfig::application_subnet().dirty_page_overhead;
+    config.feature_flags.instruction_profiling = FlagStatus::Enabled;
+    let mut instance = WasmtimeInstanceBuilder::new()
+        .with_config(config)
+        .with_wat(wat)
+        .with_num_instructions(NumInstructions::new(1000))
+        .build();
+    let res = instance.run(func_ref("test")).unwrap();
+    assert_eq!(res.exported_globals[0], Global::I64(1));
+
+    // The probes must not be metered.
+    let instructions_used = instr_used(&mut instance);
+    assert_eq!(instructions_used, instructions_without_profiling);
+
+    let profile = instance
+        .store_data_mut()
+        .instruction_profiler
+        .take()
+        .unwrap()
+        .finish(instructions_used);
+    // `$inc` has index 0 and `$test` has index 1 in the original module.
+    assert_eq!(
+        profile.stacks.keys().cloned().collect::<Vec<_>>(),
+        vec![vec![1], vec![1, 0]]
+    );
+    assert_eq!(profile.stacks.values().sum::<u64>(), instructions_used);
+}
+
 #[test]
 fn metering_block() {
     let wat = format!(
@@ -0,0 +1,79 @@
+// SPDX-License-Identifier: Apache-2.0
+
+pragma solidity 0.8.20;
+
+/**
+ * @dev Subset of the ERC-20 interface used by the helper contract.
+ */
+interface IERC20 {
+    function transferFrom(address from, address to, uint256 value) external returns (bool);
+}
+
+/**
+ * @title A helper smart contract for ETH <-> ckETH and ERC20 <-> ckERC20 conversion.
+ * @notice This smart contract deposits incoming ETH or ERC-20 to the ckETH minter account
+ * and emits deposit events that specify the beneficiary ICRC account (principal and subaccount).
+ */
+contract CkDeposit {
+    address payable private immutable cketh_minter_main_address;
+
+    /**
+     * @dev `erc20_contract_address` is the zero address for ETH deposits.
+     * A zero `subaccount` designates the default subaccount of `principal`.
+     */
+    event ReceivedEthOrErc20(
+        address indexed erc20_contract_address,
+        address indexed owner,
+        uint256 amount,
+        bytes32 indexed principal,
+        bytes32 subaccount
+    );
+
+    error TransferFailed();
+
+    /**
+     * @dev Set cketh_minter_main_address.
+     */
+    constructor(address _cketh_minter_main_address) {
+        cketh_minter_main_address = payable(_cketh_minter_main_address);
+    }
+
+    /**
+     * @dev Return ckETH minter main address.
+     * @return address of ckETH minter main address.
+     */
+    function getMinterAddress() public view returns (address) {
+        return cketh_minter_main_address;
+    }
+
+    /**
+     * @dev Emits the `ReceivedEthOrErc20` event if the transfer succeeds.
+     */
+    function depositEth(bytes32 principal, bytes32 subaccount) public payable {
+        (bool success, ) = cketh_minter_main_address.call{value: msg.value}("");
+        if (!success) {
+            revert TransferFailed();
+        }
+
+        emit ReceivedEthOrErc20(address(0), msg.sender, msg.value, principal, subaccount);
+    }
+
+    /**
+     * @dev Emits the `ReceivedEthOrErc20` event if the transfer succeeds.
+     * Tokens that return no value are supported, non-reverting calls are assumed to be successful.
+     */
+    function depositErc20(address erc20_address, uint256 amount, bytes32 principal, bytes32 subaccount) public {
+        if (erc20_address == address(0) || erc20_address.code.length == 0) {
+            revert TransferFailed();
+        }
+
+        (bool success, bytes memory returndata) = erc20_address.call(
+            abi.encodeCall(IERC20.transferFrom, (msg.sender, cketh_minter_main_address, amount))
+        );
+        if (!success || (returndata.length != 0 && !abi.decode(returndata, (bool)))) {
+            revert TransferFailed();
+        }
+
+        emit ReceivedEthOrErc20(erc20_address, msg.sender, amount, principal, subaccount);
+    }
+}
@@ -145,11 +145,13 @@ pub struct DashboardTemplate {
     pub minter_address: String,
     pub eth_helper_contract_address: String,
     pub erc20_helper_contract_address: String,
+    pub deposit_with_subaccount_helper_contract_address: String,
     pub next_transaction_nonce: TransactionNonce,
     pub minimum_withdrawal_amount: Wei,
     pub first_synced_block: BlockNumber,
     pub last_eth_synced_block: BlockNumber,
     pub last_erc20_synced_block: Option<BlockNumber>,
+    pub last_deposit_with_subaccount_synced_block: Option<BlockNumber>,
     pub last_observed_block: Option<BlockNumber>,
     pub cketh_ledger_id: Principal,
     pub minted_events: Vec<MintedEvent>,
@@ -328,6 +330,9 @@ impl DashboardTemplate {
             erc20_helper_contract_address: state
                 .erc20_helper_contract_address
                 .map_or("N/A".to_string(), |address| address.to_string()),
+            deposit_with_subaccount_helper_contract_address: state
+                .deposit_with_subaccount_helper_contract_address
+                .map_or("N/A".to_string(), |address| address.to_string()),
             cketh_ledger_id: state.cketh_ledger_id,
             next_transaction_nonce: state.eth_transactions.next_transaction_nonce(),
             minimum_withdrawal_amount: state.cketh_minimum_withdrawal_amount,
@@ -336,6 +341,9 @@ impl DashboardTemplate {
             last_erc20_synced_block: state
                 .erc20_helper_contract_address
                 .map(|_| state.last_erc20_scraped_block_number),
+            last_deposit_with_subaccount_synced_block: state
+                .deposit_with_subaccount_helper_contract_address
+                .map(|_| state.last_deposit_with_subaccount_scraped_block_number),
             last_observed_block: state.last_observed_block_number,
             minted_events,
             pending_deposits,
@@ -41,6 +41,7 @@ fn should_display_metadata() {
         .has_minter_address("0x1789F79e95324A47c5Fd6693071188e82E9a3558")
         .has_eth_helper_contract_address("0xb44B5e756A894775FC32EDdf3314Bb1B1944dC34")
         .has_erc20_helper_contract_address("N/A")
+        .has_deposit_with_subaccount_helper_contract_address("N/A")
         .has_cketh_ledger_canister_id("apia6-jaaaa-aaaar-qabma-cai")
         .has_tecdsa_key_name("key_1")
         .has_next_transaction_nonce("42")
@@ -53,6 +54,13 @@ fn should_display_metadata() {
         "0xE1788E4834c896F1932188645cc36c54d1b80AC1".to_string();
     DashboardAssert::assert_that(dashboard.clone())
         .has_erc20_helper_contract_address("0xE1788E4834c896F1932188645cc36c54d1b80AC1");
+
+    dashboard.deposit_with_subaccount_helper_contract_address =
+        "0x2D39863d30716aaf2B7fFFd85Dd03Dda2BFC2E38".to_string();
+    DashboardAssert::assert_that(dashboard.clone())
+        .has_deposit_with_subaccount_helper_contract_address(
+            "0x2D39863d30716aaf2B7fFFd85Dd03Dda2BFC2E38",
+        );
 }
 
 #[test]
@@ -65,6 +73,7 @@ fn should_display_block_sync() {
     DashboardAssert::assert_that(dashboard)
         .has_no_elements_matching("#last-observed-block-number")
         .has_no_elements_matching("#last-erc20-synced-block-number")
+        .has_no_elements_matching("#last-deposit-with-subaccount-synced-block-number")
         .has_last_eth_synced_block_href("https://sepolia.etherscan.io/block/4552270")
         .has_first_synced_block_href("https://sepolia.etherscan.io/block/3956207")
         .has_no_elements_matching("#skipped-blocks");
@@ -73,6 +82,7 @@ fn should_display_block_sync() {
         last_observed_block: Some(BlockNumber::from(4552271_u32)),
         last_eth_synced_block: BlockNumber::from(4552270_u32),
         last_erc20_synced_block: Some(BlockNumber::from(4552269_u32)),
+        last_deposit_with_subaccount_synced_block: Some(BlockNumber::from(4552268_u32)),
         skipped_blocks: btreemap! {
             "0xb44B5e756A894775FC32EDdf3314Bb1B1944dC34".to_string() => btreeset! {BlockNumber::from(3552270_u32), BlockNumber::from(2552270_u32)},
             "0xE1788E4834c896F1932188645cc36c54d1b80AC1".to_string() => btreeset! {BlockNumber::from(3552370_u32), BlockNumber::from(2552370_u32)},
@@ -83,6 +93,9 @@ fn should_display_block_sync() {
         .has_last_observed_block_href("https://sepolia.etherscan.io/block/4552271")
         .has_last_eth_synced_block_href("https://sepolia.etherscan.io/block/4552270")
         .has_last_erc20_synced_block_href("https://sepolia.etherscan.io/block/4552269")
+        .has_last_deposit_with_subaccount_synced_block_href(
+            "https://sepolia.etherscan.io/block/4552268",
+        )
         .has_first_synced_block_href("https://sepolia.etherscan.io/block/3956207")
         .has_skipped_blocks(
             "0xb44B5e756A894775FC32EDdf3314Bb1B1944dC34",
@@ -980,6 +993,7 @@ fn received_eth_event() -> ReceivedEthEvent {
         principal: "k2t6j-2nvnp-4zjm3-25dtz-6xhaa-c7boj-5gayf-oj3xs-i43lp-teztq-6ae"
             .parse()
             .unwrap(),
+        subaccount: None,
     }
 }
 
@@ -1000,6 +1014,7 @@ fn received_erc20_event() -> ReceivedErc20Event {
         erc20_contract_address: "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"
             .parse()
             .unwrap(),
+        subaccount: None,
     }
 }
 
@@ -1238,6 +1253,17 @@ mod assertions {
             )
         }
 
+        pub fn has_last_deposit_with_subaccount_synced_block_href(
+            &self,
+            expected_href: &str,
+        ) -> &Self {
+            self.has_href_value(
+                "#last-deposit-with-subaccount-synced-block-number > td > a",
+                expected_href,
+                "wrong last deposit with subaccount synced block href",
+            )
+        }
+
         pub fn has_skipped_blocks(&self, contract_address: &str, expected_blocks: &[u64]) -> &Self {
             let expected_links = expected_blocks
                 .iter()
@@ -1307,6 +1333,17 @@ mod assertions {
             )
         }
 
+        pub fn has_deposit_with_subaccount_helper_contract_address(
+            &self,
+            expected_address: &str,
+        ) -> &Self {
+            self.has_string_value(
+                "#deposit-with-subaccount-helper-contract-address > td",
+                expected_address,
+                "wrong deposit with subaccount helper contract address",
+            )
+        }
+
         pub fn has_cketh_ledger_canister_id(&self, expected_id: &str) -> &Self {
             self.has_string_value(
                 "#cketh-ledger-canister-id > td",
@@ -21,6 +21,9 @@ pub(crate) const RECEIVED_ETH_EVENT_TOPIC: [u8; 32] =
 pub(crate) const RECEIVED_ERC20_EVENT_TOPIC: [u8; 32] =
     hex!("4d69d0bd4287b7f66c548f90154dc81bc98f65a1b362775df5ae171a2ccd262b");
 
+pub(crate) const RECEIVED_ETH_OR_ERC20_WITH_SUBACCOUNT_EVENT_TOPIC: [u8; 32] =
+    hex!("918adbebdb8f3b36fc337ab76df10b147b2def5c9dd62cb3456d9aeca40e0b07");
+
 async fn mint() {
     use icrc_ledger_client_cdk::{CdkRuntime, ICRC1Client};
     use icrc_ledger_types::icrc1::transfer::TransferArg;
@@ -69,7 +72,7 @@ async fn mint() {
         let block_index = match client
             .transfer(TransferArg {
                 from_subaccount: None,
-                to: (event.principal()).into(),
+                to: event.beneficiary(),
                 fee: None,
                 created_at_time: None,
                 memo: Some((&event).into()),
@@ -118,7 +121,7 @@ async fn mint() {
             INFO,
             "Minted {} {token_symbol} to {} in block {block_index}",
             event.value(),
-            event.principal()
+            event.beneficiary()
         );
         // minting succeeded, defuse guard
         ScopeGuard::into_inner(prevent_double_minting_guard);
@@ -216,7 +219,7 @@ where
                     INFO,
                     "Received event {event:?}; will mint {} {topic_name} to {}",
                     event.value(),
-                    event.principal()
+                    event.beneficiary()
                 );
                 if crate::blocklist::is_blocked(&event.from_address()) {
                     log!(
@@ -353,6 +356,33 @@ async fn scrape_erc20_logs(last_block_number: BlockNumber, max_block_spread: u16
     .await
 }
 
+async fn scrape_deposit_with_subaccount_logs(
+    last_block_number: BlockNumber,
+    max_block_spread: u16,
+) {
+    // ETH deposits are emitted with the zero address as ERC-20 contract address.
+    let token_contract_addresses = std::iter::once(Address::ZERO)
+        .chain(read_state(|s| {
+            s.ckerc20_tokens.alt_keys().cloned().collect::<Vec<_>>()
+        }))
+        .collect::<Vec<_>>();
+    scrape_contract_logs(
+        &RECEIVED_ETH_OR_ERC20_WITH_SUBACCOUNT_EVENT_TOPIC,
+        "ETH or ERC-20 with subaccount",
+        read_state(|s| s.deposit_with_subaccount_helper_contract_address),
+        &token_contract_addresses,
+        last_block_number,
+        read_state(|s| s.last_deposit_with_subaccount_scraped_block_number),
+        max_block_spread,
+        &|last_block_number| {
+            mutate_state(|s| {
+                s.last_deposit_with_subaccount_scraped_block_number = last_block_number
+            })
+        },
+    )
+    .await
+}
+
 pub async fn scrape_logs() {
     let _guard = match TimerGuard::new(TaskType::ScrapEthLogs) {
         Ok(guard) => guard,
@@ -371,6 +401,7 @@ pub async fn scrape_logs() {
     let max_block_spread = read_state(|s| s.max_block_spread_for_logs_scraping());
     scrape_eth_logs(last_block_number, max_block_spread).await;
     scrape_erc20_logs(last_block_number, max_block_spread).await;
+    scrape_deposit_with_subaccount_logs(last_block_number, max_block_spread).await;
 }
 
 pub async fn update_last_observed_block_number() -> Option<BlockNumber> {
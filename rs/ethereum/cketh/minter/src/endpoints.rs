@@ -67,6 +67,7 @@ pub struct MinterInfo {
     pub smart_contract_address: Option<String>,
     pub eth_helper_contract_address: Option<String>,
     pub erc20_helper_contract_address: Option<String>,
+    pub deposit_with_subaccount_helper_contract_address: Option<String>,
     pub supported_ckerc20_tokens: Option<Vec<CkErc20Token>>,
     pub minimum_withdrawal_amount: Option<Nat>,
     pub ethereum_block_height: Option<CandidBlockTag>,
@@ -76,6 +77,7 @@ pub struct MinterInfo {
     pub erc20_balances: Option<Vec<Erc20Balance>>,
     pub last_eth_scraped_block_number: Option<Nat>,
     pub last_erc20_scraped_block_number: Option<Nat>,
+    pub last_deposit_with_subaccount_scraped_block_number: Option<Nat>,
     pub cketh_ledger_id: Option<Principal>,
 }
 
@@ -366,6 +368,7 @@ pub mod events {
             from_address: String,
             value: Nat,
             principal: Principal,
+            subaccount: Option<[u8; 32]>,
         },
         AcceptedErc20Deposit {
             transaction_hash: String,
@@ -375,6 +378,7 @@ pub mod events {
             value: Nat,
             principal: Principal,
             erc20_contract_address: String,
+            subaccount: Option<[u8; 32]>,
         },
         InvalidDeposit {
             event_source: EventSource,
@@ -468,5 +472,8 @@ pub mod events {
         QuarantinedReimbursement {
             index: ReimbursementIndex,
         },
+        SyncedDepositWithSubaccountToBlock {
+            block_number: Nat,
+        },
     }
 }
@@ -9,6 +9,7 @@ use crate::state::read_state;
 use candid::Principal;
 use ic_canister_log::log;
 use ic_ethereum_types::Address;
+use icrc_ledger_types::icrc1::account::Account;
 use minicbor::{Decode, Encode};
 use std::fmt;
 use thiserror::Error;
@@ -27,6 +28,8 @@ pub struct ReceivedEthEvent {
     pub value: Wei,
     #[cbor(n(5), with = "crate::cbor::principal")]
     pub principal: Principal,
+    #[n(6)]
+    pub subaccount: Option<LedgerSubaccount>,
 }
 
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Decode, Encode)]
@@ -45,6 +48,38 @@ pub struct ReceivedErc20Event {
     pub principal: Principal,
     #[n(6)]
     pub erc20_contract_address: Address,
+    #[n(7)]
+    pub subaccount: Option<LedgerSubaccount>,
+}
+
+/// A subaccount of the ledger account the minted tokens are sent to.
+///
+/// The default subaccount (all zeroes) is never represented by this type,
+/// so that deposits to the default subaccount are recorded in the same way
+/// regardless of the helper contract they came from.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Decode, Encode)]
+#[cbor(transparent)]
+pub struct LedgerSubaccount(#[cbor(n(0), with = "minicbor::bytes")] [u8; 32]);
+
+impl LedgerSubaccount {
+    /// Returns `None` for the default subaccount.
+    pub fn from_bytes(bytes: [u8; 32]) -> Option<Self> {
+        const DEFAULT_SUBACCOUNT: [u8; 32] = [0; 32];
+        if bytes == DEFAULT_SUBACCOUNT {
+            return None;
+        }
+        Some(Self(bytes))
+    }
+
+    pub fn to_bytes(self) -> [u8; 32] {
+        self.0
+    }
+}
+
+impl fmt::Debug for LedgerSubaccount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", hex::encode(self.0))
+    }
 }
 
 #[derive(Clone, Eq, PartialEq, Debug)]
@@ -74,6 +109,7 @@ impl fmt::Debug for ReceivedEthEvent {
             .field("from_address", &self.from_address)
             .field("value", &self.value)
             .field("principal", &format_args!("{}", self.principal))
+            .field("subaccount", &self.subaccount)
             .finish()
     }
 }
@@ -88,6 +124,7 @@ impl fmt::Debug for ReceivedErc20Event {
             .field("value", &self.value)
             .field("principal", &format_args!("{}", self.principal))
             .field("contract_address", &self.erc20_contract_address)
+            .field("subaccount", &self.subaccount)
             .finish()
     }
 }
@@ -149,6 +186,19 @@ impl ReceivedEvent {
             ReceivedEvent::Erc20(evt) => evt.principal,
         }
     }
+    pub fn subaccount(&self) -> Option<LedgerSubaccount> {
+        match self {
+            ReceivedEvent::Eth(evt) => evt.subaccount,
+            ReceivedEvent::Erc20(evt) => evt.subaccount,
+        }
+    }
+    /// Return the ledger account the minted tokens are sent to.
+    pub fn beneficiary(&self) -> Account {
+        Account {
+            owner: self.principal(),
+            subaccount: self.subaccount().map(LedgerSubaccount::to_bytes),
+        }
+    }
     pub fn block_number(&self) -> BlockNumber {
         match self {
             ReceivedEvent::Eth(evt) => evt.block_number,
@@ -307,30 +357,49 @@ impl TryFrom<LogEntry> for ReceivedEvent {
             })
         };
 
-        // We have only one non-indexed data field for both ETH and ERC20 events.
-        let value_bytes: [u8; 32] = entry.data.0.clone().try_into().map_err(|data| {
-            ReceivedEventError::InvalidEventSource {
-                source: event_source,
-                error: EventSourceError::InvalidEvent(format!(
-                    "Invalid data length; expected 32-byte value, got {}",
-                    hex::encode(data)
-                )),
+        let parse_data = |expected_len: usize| -> Result<&[u8], ReceivedEventError> {
+            if entry.data.0.len() != expected_len {
+                return Err(ReceivedEventError::InvalidEventSource {
+                    source: event_source,
+                    error: EventSourceError::InvalidEvent(format!(
+                        "Invalid data length; expected {}-byte value, got {}",
+                        expected_len,
+                        hex::encode(&entry.data.0)
+                    )),
+                });
             }
-        })?;
+            Ok(&entry.data.0)
+        };
 
+        let ensure_topics = |expected: usize, event_name: &str| -> Result<(), ReceivedEventError> {
+            if entry.topics.len() != expected {
+                return Err(ReceivedEventError::InvalidEventSource {
+                    source: event_source,
+                    error: EventSourceError::InvalidEvent(format!(
+                        "Expected {} topics for {} event, got {}",
+                        expected,
+                        event_name,
+                        entry.topics.len()
+                    )),
+                });
+            }
+            Ok(())
+        };
+
+        let to_word = |bytes: &[u8]| -> [u8; 32] {
+            bytes.try_into().expect("BUG: data word must be 32 bytes")
+        };
+
+        // ETH and ERC20 events have only one non-indexed data field: the deposited value.
         // We either have 3 indexed topics for ETH events: (hash, from_address, principal),
-        // or 4 indexed topics for ERC20 events: (hash, erc20_contract_address, from_address, principal)
+        // or 4 indexed topics for ERC20 events: (hash, erc20_contract_address, from_address, principal).
+        // Events with a subaccount always have 4 indexed topics
+        // (hash, erc20_contract_address, from_address, principal), where the zero ERC20 contract
+        // address denotes an ETH deposit, and two non-indexed data fields: (value, subaccount).
         match entry.topics[0] {
             FixedSizeData(crate::deposit::RECEIVED_ETH_EVENT_TOPIC) => {
-                if entry.topics.len() != 3 {
-                    return Err(ReceivedEventError::InvalidEventSource {
-                        source: event_source,
-                        error: EventSourceError::InvalidEvent(format!(
-                            "Expected 3 topics for ReceivedEth event, got {}",
-                            entry.topics.len()
-                        )),
-                    });
-                };
+                ensure_topics(3, "ReceivedEth")?;
+                let value_bytes = to_word(parse_data(32)?);
                 let from_address = parse_address(&entry.topics[1])?;
                 let principal = parse_principal(&entry.topics[2])?;
                 Ok(ReceivedEthEvent {
@@ -340,19 +409,13 @@ impl TryFrom<LogEntry> for ReceivedEvent {
                     from_address,
                     value: Wei::from_be_bytes(value_bytes),
                     principal,
+                    subaccount: None,
                 }
                 .into())
             }
             FixedSizeData(crate::deposit::RECEIVED_ERC20_EVENT_TOPIC) => {
-                if entry.topics.len() != 4 {
-                    return Err(ReceivedEventError::InvalidEventSource {
-                        source: event_source,
-                        error: EventSourceError::InvalidEvent(format!(
-                            "Expected 4 topics for ReceivedERC20 event, got {}",
-                            entry.topics.len()
-                        )),
-                    });
-                };
+                ensure_topics(4, "ReceivedERC20")?;
+                let value_bytes = to_word(parse_data(32)?);
                 let erc20_contract_address = parse_address(&entry.topics[1])?;
                 let from_address = parse_address(&entry.topics[2])?;
                 let principal = parse_principal(&entry.topics[3])?;
@@ -364,13 +427,48 @@ impl TryFrom<LogEntry> for ReceivedEvent {
                     value: Erc20Value::from_be_bytes(value_bytes),
                     principal,
                     erc20_contract_address,
+                    subaccount: None,
                 }
                 .into())
             }
+            FixedSizeData(crate::deposit::RECEIVED_ETH_OR_ERC20_WITH_SUBACCOUNT_EVENT_TOPIC) => {
+                ensure_topics(4, "ReceivedEthOrErc20")?;
+                let (value_bytes, subaccount_bytes) = parse_data(64)?.split_at(32);
+                let (value_bytes, subaccount_bytes) =
+                    (to_word(value_bytes), to_word(subaccount_bytes));
+                let erc20_contract_address = parse_address(&entry.topics[1])?;
+                let from_address = parse_address(&entry.topics[2])?;
+                let principal = parse_principal(&entry.topics[3])?;
+                let subaccount = LedgerSubaccount::from_bytes(subaccount_bytes);
+                if erc20_contract_address == Address::ZERO {
+                    Ok(ReceivedEthEvent {
+                        transaction_hash,
+                        block_number,
+                        log_index,
+                        from_address,
+                        value: Wei::from_be_bytes(value_bytes),
+                        principal,
+                        subaccount,
+                    }
+                    .into())
+                } else {
+                    Ok(ReceivedErc20Event {
+                        transaction_hash,
+                        block_number,
+                        log_index,
+                        from_address,
+                        value: Erc20Value::from_be_bytes(value_bytes),
+                        principal,
+                        erc20_contract_address,
+                        subaccount,
+                    }
+                    .into())
+                }
+            }
             _ => Err(ReceivedEventError::InvalidEventSource {
                 source: event_source,
                 error: EventSourceError::InvalidEvent(format!(
-                    "Expected either ReceivedEth or ReceivedERC20 topics, got {}",
+                    "Expected either ReceivedEth, ReceivedERC20 or ReceivedEthOrErc20 topics, got {}",
                     entry.topics[0]
                 )),
             }),
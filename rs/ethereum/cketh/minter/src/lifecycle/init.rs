@@ -74,6 +74,7 @@ impl TryFrom<InitArg> for State {
             ecdsa_key_name,
             eth_helper_contract_address,
             erc20_helper_contract_address: None,
+            deposit_with_subaccount_helper_contract_address: None,
             pending_withdrawal_principals: Default::default(),
             eth_transactions: EthTransactions::new(initial_nonce),
             cketh_ledger_id: ledger_id,
@@ -82,6 +83,7 @@ impl TryFrom<InitArg> for State {
             first_scraped_block_number,
             last_scraped_block_number,
             last_erc20_scraped_block_number: last_scraped_block_number,
+            last_deposit_with_subaccount_scraped_block_number: last_scraped_block_number,
             last_observed_block_number: None,
             events_to_mint: Default::default(),
             minted_events: Default::default(),
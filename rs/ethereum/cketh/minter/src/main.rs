@@ -110,6 +110,9 @@ fn emit_preupgrade_events() {
         storage::record_event(EventType::SyncedErc20ToBlock {
             block_number: s.last_erc20_scraped_block_number,
         });
+        storage::record_event(EventType::SyncedDepositWithSubaccountToBlock {
+            block_number: s.last_deposit_with_subaccount_scraped_block_number,
+        });
     });
 }
 
@@ -210,6 +213,9 @@ async fn get_minter_info() -> MinterInfo {
             smart_contract_address: s.eth_helper_contract_address.map(|a| a.to_string()),
             eth_helper_contract_address: s.eth_helper_contract_address.map(|a| a.to_string()),
             erc20_helper_contract_address: s.erc20_helper_contract_address.map(|a| a.to_string()),
+            deposit_with_subaccount_helper_contract_address: s
+                .deposit_with_subaccount_helper_contract_address
+                .map(|a| a.to_string()),
             supported_ckerc20_tokens,
             minimum_withdrawal_amount: Some(s.cketh_minimum_withdrawal_amount.into()),
             ethereum_block_height: Some(s.ethereum_block_height.into()),
@@ -225,6 +231,9 @@ async fn get_minter_info() -> MinterInfo {
             erc20_balances,
             last_eth_scraped_block_number: Some(s.last_scraped_block_number.into()),
             last_erc20_scraped_block_number: Some(s.last_erc20_scraped_block_number.into()),
+            last_deposit_with_subaccount_scraped_block_number: Some(
+                s.last_deposit_with_subaccount_scraped_block_number.into(),
+            ),
             cketh_ledger_id: Some(s.cketh_ledger_id),
         }
     })
@@ -627,6 +636,7 @@ fn get_events(arg: GetEventsArg) -> GetEventsResult {
                     from_address,
                     value,
                     principal,
+                    subaccount,
                 }) => EP::AcceptedDeposit {
                     transaction_hash: transaction_hash.to_string(),
                     block_number: block_number.into(),
@@ -634,6 +644,7 @@ fn get_events(arg: GetEventsArg) -> GetEventsResult {
                     from_address: from_address.to_string(),
                     value: value.into(),
                     principal,
+                    subaccount: subaccount.map(|s| s.to_bytes()),
                 },
                 EventType::AcceptedErc20Deposit(ReceivedErc20Event {
                     transaction_hash,
@@ -643,6 +654,7 @@ fn get_events(arg: GetEventsArg) -> GetEventsResult {
                     value,
                     principal,
                     erc20_contract_address,
+                    subaccount,
                 }) => EP::AcceptedErc20Deposit {
                     transaction_hash: transaction_hash.to_string(),
                     block_number: block_number.into(),
@@ -651,6 +663,7 @@ fn get_events(arg: GetEventsArg) -> GetEventsResult {
                     value: value.into(),
                     principal,
                     erc20_contract_address: erc20_contract_address.to_string(),
+                    subaccount: subaccount.map(|s| s.to_bytes()),
                 },
                 EventType::InvalidDeposit {
                     event_source,
@@ -672,6 +685,11 @@ fn get_events(arg: GetEventsArg) -> GetEventsResult {
                 EventType::SyncedErc20ToBlock { block_number } => EP::SyncedErc20ToBlock {
                     block_number: block_number.into(),
                 },
+                EventType::SyncedDepositWithSubaccountToBlock { block_number } => {
+                    EP::SyncedDepositWithSubaccountToBlock {
+                        block_number: block_number.into(),
+                    }
+                }
                 EventType::AcceptedEthWithdrawalRequest(EthWithdrawalRequest {
                     withdrawal_amount,
                     destination,
@@ -873,6 +891,12 @@ fn http_request(req: HttpRequest) -> HttpResponse {
                     "The last Ethereum block the ckETH minter checked for ckERC20 deposits.",
                 )?;
 
+                w.encode_gauge(
+                    "cketh_minter_last_deposit_with_subaccount_processed_block",
+                    s.last_deposit_with_subaccount_scraped_block_number.as_f64(),
+                    "The last Ethereum block the ckETH minter checked for ckETH and ckERC20 deposits with a subaccount.",
+                )?;
+
                 w.encode_counter(
                     "cketh_minter_skipped_blocks",
                     s.skipped_blocks
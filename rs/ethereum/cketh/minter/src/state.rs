@@ -55,12 +55,14 @@ pub struct State {
     pub cketh_ledger_id: Principal,
     pub eth_helper_contract_address: Option<Address>,
     pub erc20_helper_contract_address: Option<Address>,
+    pub deposit_with_subaccount_helper_contract_address: Option<Address>,
     pub ecdsa_public_key: Option<EcdsaPublicKeyResponse>,
     pub cketh_minimum_withdrawal_amount: Wei,
     pub ethereum_block_height: BlockTag,
     pub first_scraped_block_number: BlockNumber,
     pub last_scraped_block_number: BlockNumber,
     pub last_erc20_scraped_block_number: BlockNumber,
+    pub last_deposit_with_subaccount_scraped_block_number: BlockNumber,
     pub last_observed_block_number: Option<BlockNumber>,
     pub events_to_mint: BTreeMap<EventSource, ReceivedEvent>,
     pub minted_events: BTreeMap<EventSource, MintedEvent>,
@@ -110,9 +112,11 @@ pub enum InvalidStateError {
     InvalidLedgerId(String),
     InvalidEthereumContractAddress(String),
     InvalidErc20HelperContractAddress(String),
+    InvalidDepositWithSubaccountHelperContractAddress(String),
     InvalidMinimumWithdrawalAmount(String),
     InvalidLastScrapedBlockNumber(String),
     InvalidLastErc20ScrapedBlockNumber(String),
+    InvalidLastDepositWithSubaccountScrapedBlockNumber(String),
 }
 
 #[derive(Clone, Eq, PartialEq, Debug)]
@@ -478,6 +482,8 @@ impl State {
             erc20_helper_contract_address,
             last_erc20_scraped_block_number,
             evm_rpc_id,
+            deposit_with_subaccount_helper_contract_address,
+            last_deposit_with_subaccount_scraped_block_number,
         } = upgrade_args;
         if let Some(nonce) = next_transaction_nonce {
             let nonce = TransactionNonce::try_from(nonce)
@@ -508,6 +514,26 @@ impl State {
                     InvalidStateError::InvalidLastErc20ScrapedBlockNumber(format!("ERROR: {}", e))
                 })?;
         }
+        if let Some(address) = deposit_with_subaccount_helper_contract_address {
+            let deposit_with_subaccount_helper_contract_address = Address::from_str(&address)
+                .map_err(|e| {
+                    InvalidStateError::InvalidDepositWithSubaccountHelperContractAddress(format!(
+                        "ERROR: {}",
+                        e
+                    ))
+                })?;
+            self.deposit_with_subaccount_helper_contract_address =
+                Some(deposit_with_subaccount_helper_contract_address);
+        }
+        if let Some(block_number) = last_deposit_with_subaccount_scraped_block_number {
+            self.last_deposit_with_subaccount_scraped_block_number =
+                BlockNumber::try_from(block_number).map_err(|e| {
+                    InvalidStateError::InvalidLastDepositWithSubaccountScrapedBlockNumber(format!(
+                        "ERROR: {}",
+                        e
+                    ))
+                })?;
+        }
         if let Some(block_height) = ethereum_block_height {
             self.ethereum_block_height = block_height.into();
         }
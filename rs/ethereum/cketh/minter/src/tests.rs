@@ -48,12 +48,13 @@ fn deserialize_json_reply() {
 }
 
 mod eth_get_logs {
-    use crate::eth_logs::{ReceivedErc20Event, ReceivedEthEvent, ReceivedEvent};
+    use crate::eth_logs::{LedgerSubaccount, ReceivedErc20Event, ReceivedEthEvent, ReceivedEvent};
     use crate::eth_rpc::LogEntry;
     use crate::numeric::{BlockNumber, Erc20Value, LogIndex, Wei};
     use candid::Principal;
     use ic_ethereum_types::Address;
     use ic_sha3::Keccak256;
+    use icrc_ledger_types::icrc1::account::Account;
     use std::str::FromStr;
 
     #[test]
@@ -136,6 +137,7 @@ mod eth_get_logs {
                 .unwrap(),
             value: Wei::from(10_000_000_000_000_000_u128),
             principal: Principal::from_str("2chl6-4hpzw-vqaaa-aaaaa-c").unwrap(),
+            subaccount: None,
         }
         .into();
 
@@ -179,12 +181,146 @@ mod eth_get_logs {
             erc20_contract_address: "0x7439e9bb6d8a84dd3a23fe621a30f95403f87fb9"
                 .parse()
                 .unwrap(),
+            subaccount: None,
         }
         .into();
 
         assert_eq!(parsed_event, expected_event);
     }
 
+    #[test]
+    fn should_have_correct_topic_for_deposit_with_subaccount() {
+        use crate::deposit::RECEIVED_ETH_OR_ERC20_WITH_SUBACCOUNT_EVENT_TOPIC;
+
+        //must match event signature in DepositHelperWithSubaccount.sol
+        let event_signature = "ReceivedEthOrErc20(address,address,uint256,bytes32,bytes32)";
+        let topic = Keccak256::hash(event_signature);
+        assert_eq!(topic, RECEIVED_ETH_OR_ERC20_WITH_SUBACCOUNT_EVENT_TOPIC)
+    }
+
+    #[test]
+    fn should_parse_received_eth_event_with_subaccount() {
+        let event = r#"{
+            "address": "0x2d39863d30716aaf2b7fffd85dd03dda2bfc2e38",
+            "topics": [
+                "0x918adbebdb8f3b36fc337ab76df10b147b2def5c9dd62cb3456d9aeca40e0b07",
+                "0x0000000000000000000000000000000000000000000000000000000000000000",
+                "0x000000000000000000000000dd2851cdd40ae6536831558dd46db62fac7a844d",
+                "0x09efcdab00000000000100000000000000000000000000000000000000000000"
+            ],
+            "data": "0x000000000000000000000000000000000000000000000000002386f26fc10000ff00000000000000000000000000000000000000000000000000000000000001",
+            "blockNumber": "0x3ca487",
+            "transactionHash": "0x705f826861c802b407843e99af986cfde8749b669e5e0a5a150f4350bcaa9bc3",
+            "transactionIndex": "0x22",
+            "blockHash": "0x8436209a391f7bc076123616ecb229602124eb6c1007f5eae84df8e098885d3c",
+            "logIndex": "0x27",
+            "removed": false
+        }"#;
+        let parsed_event =
+            ReceivedEvent::try_from(serde_json::from_str::<LogEntry>(event).unwrap()).unwrap();
+        let mut subaccount = [0_u8; 32];
+        subaccount[0] = 0xff;
+        subaccount[31] = 0x01;
+        let expected_event = ReceivedEthEvent {
+            transaction_hash: "0x705f826861c802b407843e99af986cfde8749b669e5e0a5a150f4350bcaa9bc3"
+                .parse()
+                .unwrap(),
+            block_number: BlockNumber::new(3974279),
+            log_index: LogIndex::from(39_u8),
+            from_address: "0xdd2851cdd40ae6536831558dd46db62fac7a844d"
+                .parse()
+                .unwrap(),
+            value: Wei::from(10_000_000_000_000_000_u128),
+            principal: Principal::from_str("2chl6-4hpzw-vqaaa-aaaaa-c").unwrap(),
+            subaccount: LedgerSubaccount::from_bytes(subaccount),
+        }
+        .into();
+
+        assert_eq!(parsed_event, expected_event);
+        assert_eq!(
+            parsed_event.beneficiary(),
+            Account {
+                owner: Principal::from_str("2chl6-4hpzw-vqaaa-aaaaa-c").unwrap(),
+                subaccount: Some(subaccount),
+            }
+        );
+    }
+
+    #[test]
+    fn should_parse_received_erc20_event_with_default_subaccount() {
+        let event = r#"{
+            "address": "0x2d39863d30716aaf2b7fffd85dd03dda2bfc2e38",
+            "topics": [
+                "0x918adbebdb8f3b36fc337ab76df10b147b2def5c9dd62cb3456d9aeca40e0b07",
+                "0x0000000000000000000000007439e9bb6d8a84dd3a23fe621a30f95403f87fb9",
+                "0x000000000000000000000000dd2851cdd40ae6536831558dd46db62fac7a844d",
+                "0x1d9facb184cbe453de4841b6b9d9cc95bfc065344e485789b550544529020000"
+            ],
+            "data": "0x0000000000000000000000000000000000000000000000008ac7230489e800000000000000000000000000000000000000000000000000000000000000000000",
+            "blockNumber": "0x5146a4",
+            "transactionHash": "0x44d8e93a8f4bbc89ad35fc4fbbdb12cb597b4832da09c0b2300777be180fde87",
+            "transactionIndex": "0x22",
+            "blockHash": "0x0cbfb260e2e589ef110e63314279eb3ef2e307e46fa5409f08c101976858f80a",
+            "logIndex": "0x27",
+            "removed": false
+        }"#;
+        let parsed_event =
+            ReceivedEvent::try_from(serde_json::from_str::<LogEntry>(event).unwrap()).unwrap();
+        let expected_event = ReceivedErc20Event {
+            transaction_hash: "0x44d8e93a8f4bbc89ad35fc4fbbdb12cb597b4832da09c0b2300777be180fde87"
+                .parse()
+                .unwrap(),
+            block_number: BlockNumber::new(5326500),
+            log_index: LogIndex::from(39_u8),
+            from_address: "0xdd2851Cdd40aE6536831558DD46db62fAc7A844d"
+                .parse()
+                .unwrap(),
+            value: Erc20Value::from(10_000_000_000_000_000_000_u128),
+            principal: Principal::from_str(
+                "hkroy-sm7vs-yyjs7-ekppe-qqnwx-hm4zf-n7ybs-titsi-k6e3k-ucuiu-uqe",
+            )
+            .unwrap(),
+            erc20_contract_address: "0x7439e9bb6d8a84dd3a23fe621a30f95403f87fb9"
+                .parse()
+                .unwrap(),
+            subaccount: None,
+        }
+        .into();
+
+        assert_eq!(parsed_event, expected_event);
+    }
+
+    #[test]
+    fn should_not_parse_event_with_subaccount_with_invalid_data_length() {
+        use crate::eth_logs::{EventSourceError, ReceivedEventError};
+        let event = r#"{
+            "address": "0x2d39863d30716aaf2b7fffd85dd03dda2bfc2e38",
+            "topics": [
+                "0x918adbebdb8f3b36fc337ab76df10b147b2def5c9dd62cb3456d9aeca40e0b07",
+                "0x0000000000000000000000000000000000000000000000000000000000000000",
+                "0x000000000000000000000000dd2851cdd40ae6536831558dd46db62fac7a844d",
+                "0x09efcdab00000000000100000000000000000000000000000000000000000000"
+            ],
+            "data": "0x000000000000000000000000000000000000000000000000002386f26fc10000",
+            "blockNumber": "0x3ca487",
+            "transactionHash": "0x705f826861c802b407843e99af986cfde8749b669e5e0a5a150f4350bcaa9bc3",
+            "transactionIndex": "0x22",
+            "blockHash": "0x8436209a391f7bc076123616ecb229602124eb6c1007f5eae84df8e098885d3c",
+            "logIndex": "0x27",
+            "removed": false
+        }"#;
+        let parsed_event =
+            ReceivedEvent::try_from(serde_json::from_str::<LogEntry>(event).unwrap());
+
+        match parsed_event {
+            Err(ReceivedEventError::InvalidEventSource {
+                error: EventSourceError::InvalidEvent(message),
+                ..
+            }) => assert!(message.contains("expected 64-byte value"), "{message}"),
+            _ => panic!("unexpected result: {parsed_event:?}"),
+        }
+    }
+
     #[test]
     fn should_not_parse_removed_event() {
         use crate::eth_logs::{EventSource, EventSourceError, ReceivedEventError};
@@ -517,6 +517,7 @@ impl CkEthSetup {
             status: 500_u128,
             headers: vec![],
             body: vec![],
+            is_replicated: None,
         };
         let ongoing_https_outcalls: Vec<_> = self
             .env
@@ -282,6 +282,7 @@ impl StubOnce {
             status: 200_u128,
             headers: vec![],
             body: clean_up_response.body,
+            is_replicated: None,
         };
         let mut payload = PayloadBuilder::new();
         payload = payload.http_response(id, &http_response);
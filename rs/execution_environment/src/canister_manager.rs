@@ -22,27 +22,34 @@ use ic_interfaces::execution_environment::{
 };
 use ic_logger::{error, fatal, info, ReplicaLogger};
 use ic_management_canister_types::{
-    CanisterChangeDetails, CanisterChangeOrigin, CanisterInstallModeV2, CanisterSnapshotResponse,
-    CanisterStatusResultV2, CanisterStatusType, ChunkHash, InstallChunkedCodeArgs,
-    InstallCodeArgsV2, Method as Ic00Method, StoredChunksReply, UploadChunkReply,
+    CanisterChangeDetails, CanisterChangeOrigin, CanisterInstallModeV2, CanisterSnapshotDataKind,
+    CanisterSnapshotDataOffset, CanisterSnapshotResponse, CanisterStatusResultV2,
+    CanisterStatusType, ChunkHash, EnvironmentVariable, InstallChunkedCodeArgs, InstallCodeArgsV2,
+    Method as Ic00Method, ReadCanisterSnapshotDataResponse, ReadCanisterSnapshotMetadataResponse,
+    SnapshotGlobal, StoredChunksReply, UploadCanisterSnapshotDataArgs,
+    UploadCanisterSnapshotMetadataArgs, UploadCanisterSnapshotMetadataResponse, UploadChunkReply,
+    MAX_CANISTER_SNAPSHOT_DATA_CHUNK_SIZE,
 };
 use ic_registry_provisional_whitelist::ProvisionalWhitelist;
 use ic_registry_subnet_type::SubnetType;
 use ic_replicated_state::{
-    canister_snapshots::{CanisterSnapshot, CanisterSnapshotError},
+    canister_snapshots::{
+        CanisterSnapshot, CanisterSnapshotError, ExecutionStateSnapshot, PageMemory, SnapshotSource,
+    },
     canister_state::{
-        execution_state::Memory,
+        execution_state::{Global, Memory},
         system_state::{
-            wasm_chunk_store::{self, WasmChunkStore},
+            wasm_chunk_store::{self, WasmChunkHash, WasmChunkStore},
             CyclesUseCase, ReservationError,
         },
-        NextExecution,
+        NextExecution, WASM_PAGE_SIZE_IN_BYTES,
     },
     metadata_state::subnet_call_context_manager::InstallCodeCallId,
-    page_map::PageAllocatorFileDescriptor,
-    CallOrigin, CanisterState, NetworkTopology, ReplicatedState, SchedulerState, SystemState,
+    page_map::{Buffer, PageAllocatorFileDescriptor},
+    CallOrigin, CanisterState, NetworkTopology, NumWasmPages, PageMap, ReplicatedState,
+    SchedulerState, SystemState,
 };
-use ic_system_api::ExecutionParameters;
+use ic_system_api::{ExecutionParameters, CERTIFIED_DATA_MAX_LENGTH};
 use ic_types::{
     ingress::{IngressState, IngressStatus},
     messages::{
@@ -52,7 +59,7 @@ use ic_types::{
     nominal_cycles::NominalCycles,
     CanisterId, CanisterTimer, ComputeAllocation, Cycles, InvalidComputeAllocationError,
     InvalidMemoryAllocationError, MemoryAllocation, NumBytes, NumInstructions, PrincipalId,
-    SnapshotId, SubnetId, Time,
+    SnapshotId, SubnetId, Time, MAX_STABLE_MEMORY_IN_BYTES, MAX_WASM_MEMORY_IN_BYTES,
 };
 use ic_wasm_types::{doc_ref, AsErrorHelp, CanisterModule, ErrorHelp, WasmHash};
 use num_traits::cast::ToPrimitive;
@@ -60,7 +67,7 @@ use num_traits::SaturatingAdd;
 use prometheus::IntCounter;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
-use std::{collections::BTreeSet, convert::TryFrom, str::FromStr, sync::Arc};
+use std::{collections::BTreeSet, convert::TryFrom, ops::Range, str::FromStr, sync::Arc};
 
 #[derive(Eq, PartialEq, Debug)]
 pub(crate) struct InstallCodeResult {
@@ -498,7 +505,11 @@ impl CanisterManager {
             | Ok(Ic00Method::TakeCanisterSnapshot)
             | Ok(Ic00Method::LoadCanisterSnapshot)
             | Ok(Ic00Method::ListCanisterSnapshots)
-            | Ok(Ic00Method::DeleteCanisterSnapshot) => {
+            | Ok(Ic00Method::DeleteCanisterSnapshot)
+            | Ok(Ic00Method::ReadCanisterSnapshotMetadata)
+            | Ok(Ic00Method::ReadCanisterSnapshotData)
+            | Ok(Ic00Method::UploadCanisterSnapshotMetadata)
+            | Ok(Ic00Method::UploadCanisterSnapshotData) => {
                 match effective_canister_id {
                     Some(canister_id) => {
                         let canister = state.canister_state(&canister_id).ok_or_else(|| UserError::new(
@@ -632,6 +643,9 @@ impl CanisterManager {
         if let Some(wasm_memory_limit) = settings.wasm_memory_limit() {
             canister.system_state.wasm_memory_limit = Some(wasm_memory_limit);
         }
+        if let Some(environment_variables) = settings.environment_variables() {
+            canister.system_state.environment_variables = environment_variables.clone();
+        }
     }
 
     /// Tries to apply the requested settings on the canister identified by
@@ -1130,6 +1144,12 @@ impl CanisterManager {
         let reserved_cycles_limit = canister.system_state.reserved_balance_limit();
         let log_visibility = canister.system_state.log_visibility.clone();
         let wasm_memory_limit = canister.system_state.wasm_memory_limit;
+        let environment_variables = canister
+            .system_state
+            .environment_variables
+            .iter()
+            .map(|(name, value)| EnvironmentVariable::new(name.clone(), value.clone()))
+            .collect();
 
         Ok(CanisterStatusResultV2::new(
             canister.status(),
@@ -1167,6 +1187,7 @@ impl CanisterManager {
                 .total_query_stats
                 .egress_payload_size,
             wasm_memory_limit.map(|x| x.get()),
+            environment_variables,
         ))
     }
 
@@ -1830,85 +1851,15 @@ impl CanisterManager {
                 .saturating_sub(replace_snapshot_size.get()),
         );
 
-        {
-            // Run the following checks on memory usage and return an error
-            // if any fails:
-            // 1. Check new usage will not freeze canister
-            // 2. Check subnet has available memory
-            // 3. Reserve cycles on canister
-            // 4. Actually deduct memory from subnet (asserting it won't fail)
-
-            // Calculate if any cycles will need to be reserved.
-            let reservation_cycles = self.cycles_account_manager.storage_reservation_cycles(
-                new_snapshot_increase,
-                resource_saturation,
-                subnet_size,
-            );
-
-            // Memory usage will increase by the snapshot size.
-            // Check that it doesn't bump the canister over the freezing threshold.
-            let threshold = self.cycles_account_manager.freeze_threshold_cycles(
-                canister.system_state.freeze_threshold,
-                canister.memory_allocation(),
-                new_memory_usage,
-                canister.message_memory_usage(),
-                canister.compute_allocation(),
-                subnet_size,
-                canister.system_state.reserved_balance(),
-            );
-
-            if canister.system_state.balance() < threshold + reservation_cycles {
-                return (
-                    Err(CanisterManagerError::InsufficientCyclesInMemoryGrow {
-                        bytes: new_snapshot_increase,
-                        available: canister.system_state.balance(),
-                        threshold,
-                    }),
-                    NumInstructions::new(0),
-                );
-            }
-            // Verify that the subnet has enough memory for a new snapshot.
-            if let Err(err) = round_limits
-                .subnet_available_memory
-                .check_available_memory(new_snapshot_increase, NumBytes::from(0), NumBytes::from(0))
-                .map_err(
-                    |_| CanisterManagerError::SubnetMemoryCapacityOverSubscribed {
-                        requested: new_snapshot_increase,
-                        available: NumBytes::from(
-                            round_limits
-                                .subnet_available_memory
-                                .get_execution_memory()
-                                .max(0) as u64,
-                        ),
-                    },
-                )
-            {
-                return (Err(err), NumInstructions::new(0));
-            };
-            // Reserve needed cycles if the subnet is becoming saturated.
-            if let Err(err) = canister
-                .system_state
-                .reserve_cycles(reservation_cycles)
-                .map_err(|err| match err {
-                    ReservationError::InsufficientCycles {
-                        requested,
-                        available,
-                    } => CanisterManagerError::InsufficientCyclesInMemoryGrow {
-                        bytes: new_snapshot_increase,
-                        available,
-                        threshold: requested,
-                    },
-                    ReservationError::ReservedLimitExceed { requested, limit } => {
-                        CanisterManagerError::ReservedCyclesLimitExceededInMemoryGrow {
-                            bytes: new_snapshot_increase,
-                            requested,
-                            limit,
-                        }
-                    }
-                })
-            {
-                return (Err(err), NumInstructions::new(0));
-            };
+        if let Err(err) = self.reserve_snapshot_memory(
+            canister,
+            new_snapshot_increase,
+            new_memory_usage,
+            subnet_size,
+            round_limits,
+            resource_saturation,
+        ) {
+            return (Err(err), NumInstructions::new(0));
         }
 
         // Charge for taking a snapshot of the canister.
@@ -1995,6 +1946,89 @@ impl CanisterManager {
         )
     }
 
+    /// Checks that `memory_increase` additional bytes of snapshot memory
+    /// can be allocated for the canister and reserves cycles for them.
+    ///
+    /// Run the following checks on memory usage and return an error
+    /// if any fails:
+    /// 1. Check new usage will not freeze canister
+    /// 2. Check subnet has available memory
+    /// 3. Reserve cycles on canister
+    ///
+    /// The caller is responsible for actually deducting the memory from the subnet.
+    fn reserve_snapshot_memory(
+        &self,
+        canister: &mut CanisterState,
+        memory_increase: NumBytes,
+        new_memory_usage: NumBytes,
+        subnet_size: usize,
+        round_limits: &RoundLimits,
+        resource_saturation: &ResourceSaturation,
+    ) -> Result<(), CanisterManagerError> {
+        // Calculate if any cycles will need to be reserved.
+        let reservation_cycles = self.cycles_account_manager.storage_reservation_cycles(
+            memory_increase,
+            resource_saturation,
+            subnet_size,
+        );
+
+        // Memory usage will increase by the snapshot size.
+        // Check that it doesn't bump the canister over the freezing threshold.
+        let threshold = self.cycles_account_manager.freeze_threshold_cycles(
+            canister.system_state.freeze_threshold,
+            canister.memory_allocation(),
+            new_memory_usage,
+            canister.message_memory_usage(),
+            canister.compute_allocation(),
+            subnet_size,
+            canister.system_state.reserved_balance(),
+        );
+
+        if canister.system_state.balance() < threshold + reservation_cycles {
+            return Err(CanisterManagerError::InsufficientCyclesInMemoryGrow {
+                bytes: memory_increase,
+                available: canister.system_state.balance(),
+                threshold,
+            });
+        }
+        // Verify that the subnet has enough memory for the snapshot.
+        round_limits
+            .subnet_available_memory
+            .check_available_memory(memory_increase, NumBytes::from(0), NumBytes::from(0))
+            .map_err(
+                |_| CanisterManagerError::SubnetMemoryCapacityOverSubscribed {
+                    requested: memory_increase,
+                    available: NumBytes::from(
+                        round_limits
+                            .subnet_available_memory
+                            .get_execution_memory()
+                            .max(0) as u64,
+                    ),
+                },
+            )?;
+        // Reserve needed cycles if the subnet is becoming saturated.
+        canister
+            .system_state
+            .reserve_cycles(reservation_cycles)
+            .map_err(|err| match err {
+                ReservationError::InsufficientCycles {
+                    requested,
+                    available,
+                } => CanisterManagerError::InsufficientCyclesInMemoryGrow {
+                    bytes: memory_increase,
+                    available,
+                    threshold: requested,
+                },
+                ReservationError::ReservedLimitExceed { requested, limit } => {
+                    CanisterManagerError::ReservedCyclesLimitExceededInMemoryGrow {
+                        bytes: memory_increase,
+                        requested,
+                        limit,
+                    }
+                }
+            })
+    }
+
     pub(crate) fn load_canister_snapshot(
         &self,
         subnet_size: usize,
@@ -2267,6 +2301,631 @@ impl CanisterManager {
         );
         Ok(())
     }
+    /// Returns the canister snapshot identified by `snapshot_id` if it exists
+    /// and belongs to the given canister.
+    fn get_canister_snapshot(
+        state: &ReplicatedState,
+        canister_id: CanisterId,
+        snapshot_id: SnapshotId,
+    ) -> Result<Arc<CanisterSnapshot>, CanisterManagerError> {
+        match state.canister_snapshots.get(snapshot_id) {
+            None => Err(CanisterManagerError::CanisterSnapshotNotFound {
+                canister_id,
+                snapshot_id,
+            }),
+            Some(snapshot) => {
+                // Verify the provided snapshot id belongs to this canister.
+                if snapshot.canister_id() != canister_id {
+                    return Err(CanisterManagerError::CanisterSnapshotInvalidOwnership {
+                        canister_id,
+                        snapshot_id,
+                    });
+                }
+                Ok(Arc::clone(snapshot))
+            }
+        }
+    }
+
+    /// Returns the metadata of the specified canister snapshot, which
+    /// describes the sizes of the data that can be read with
+    /// `read_canister_snapshot_data`.
+    ///
+    /// Reading the snapshot metadata can only be initiated by the controllers.
+    pub(crate) fn read_canister_snapshot_metadata(
+        &self,
+        sender: PrincipalId,
+        canister: &CanisterState,
+        snapshot_id: SnapshotId,
+        state: &ReplicatedState,
+    ) -> Result<ReadCanisterSnapshotMetadataResponse, CanisterManagerError> {
+        // Check sender is a controller.
+        validate_controller(canister, &sender)?;
+
+        let snapshot = Self::get_canister_snapshot(state, canister.canister_id(), snapshot_id)?;
+        Ok(ReadCanisterSnapshotMetadataResponse {
+            taken_at_timestamp: snapshot.taken_at_timestamp().as_nanos_since_unix_epoch(),
+            canister_version: snapshot.canister_version(),
+            wasm_module_size: snapshot.canister_module().len() as u64,
+            exported_globals: snapshot
+                .exported_globals()
+                .iter()
+                .map(global_to_snapshot_global)
+                .collect(),
+            wasm_memory_size: memory_size_in_bytes(snapshot.wasm_memory()),
+            stable_memory_size: memory_size_in_bytes(snapshot.stable_memory()),
+            wasm_chunk_store: snapshot
+                .chunk_store()
+                .keys()
+                .map(|k| ChunkHash { hash: k.to_vec() })
+                .collect(),
+            certified_data: snapshot.certified_data().clone(),
+        })
+    }
+
+    /// Reads a chunk of the Wasm module, the main memory, the stable memory
+    /// or the Wasm chunk store of the specified canister snapshot.
+    ///
+    /// Reading the snapshot data can only be initiated by the controllers.
+    pub(crate) fn read_canister_snapshot_data(
+        &self,
+        subnet_size: usize,
+        sender: PrincipalId,
+        canister: &mut CanisterState,
+        snapshot_id: SnapshotId,
+        kind: &CanisterSnapshotDataKind,
+        state: &ReplicatedState,
+    ) -> (
+        Result<ReadCanisterSnapshotDataResponse, CanisterManagerError>,
+        NumInstructions,
+    ) {
+        // Check sender is a controller.
+        if let Err(err) = validate_controller(canister, &sender) {
+            return (Err(err), NumInstructions::new(0));
+        }
+
+        let snapshot = match Self::get_canister_snapshot(state, canister.canister_id(), snapshot_id)
+        {
+            Ok(snapshot) => snapshot,
+            Err(err) => return (Err(err), NumInstructions::new(0)),
+        };
+
+        let chunk = match kind {
+            CanisterSnapshotDataKind::WasmModule { offset, size } => {
+                let module = snapshot.canister_module().as_slice();
+                validate_snapshot_data_range(*offset, *size, module.len() as u64)
+                    .map(|range| module[range].to_vec())
+            }
+            CanisterSnapshotDataKind::MainMemory { offset, size } => {
+                read_snapshot_memory(snapshot.wasm_memory(), *offset, *size)
+            }
+            CanisterSnapshotDataKind::StableMemory { offset, size } => {
+                read_snapshot_memory(snapshot.stable_memory(), *offset, *size)
+            }
+            CanisterSnapshotDataKind::WasmChunk { hash } => {
+                let chunk_hash = match WasmChunkHash::try_from(hash.as_slice()) {
+                    Ok(chunk_hash) => chunk_hash,
+                    Err(_) => {
+                        return (
+                            Err(CanisterManagerError::CanisterSnapshotInvalidData {
+                                message: format!("Invalid chunk hash of length {}", hash.len()),
+                            }),
+                            NumInstructions::new(0),
+                        )
+                    }
+                };
+                snapshot
+                    .chunk_store()
+                    .get_chunk_data(&chunk_hash)
+                    .map(|pages| pages.flatten().copied().collect())
+                    .ok_or_else(|| CanisterManagerError::CanisterSnapshotInvalidData {
+                        message: format!("Chunk with hash {} not found", hex::encode(hash)),
+                    })
+            }
+        };
+        let chunk = match chunk {
+            Ok(chunk) => chunk,
+            Err(err) => return (Err(err), NumInstructions::new(0)),
+        };
+
+        // Charge for reading the snapshot data.
+        let instructions = self
+            .config
+            .canister_snapshot_baseline_instructions
+            .saturating_add(&(chunk.len() as u64).into());
+        if let Err(err) = self.cycles_account_manager.consume_cycles_for_instructions(
+            &sender,
+            canister,
+            instructions,
+            subnet_size,
+        ) {
+            return (
+                Err(CanisterManagerError::CanisterSnapshotNotEnoughCycles(err)),
+                0.into(),
+            );
+        };
+
+        (Ok(ReadCanisterSnapshotDataResponse { chunk }), instructions)
+    }
+
+    /// Creates a new canister snapshot from the uploaded metadata. The
+    /// Wasm module and the memories of the new snapshot are zero-initialized
+    /// and its Wasm chunk store is empty. They can be filled afterwards with
+    /// `upload_canister_snapshot_data`.
+    ///
+    /// Like for `take_canister_snapshot`, if the `replace_snapshot` parameter
+    /// is `Some`, the specified snapshot is deleted before creating a new one.
+    ///
+    /// Uploading a snapshot can only be initiated by the controllers.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn upload_canister_snapshot_metadata(
+        &self,
+        subnet_size: usize,
+        sender: PrincipalId,
+        canister: &mut CanisterState,
+        args: &UploadCanisterSnapshotMetadataArgs,
+        state: &mut ReplicatedState,
+        round_limits: &mut RoundLimits,
+        resource_saturation: &ResourceSaturation,
+    ) -> (
+        Result<UploadCanisterSnapshotMetadataResponse, CanisterManagerError>,
+        NumInstructions,
+    ) {
+        // Check sender is a controller.
+        if let Err(err) = validate_controller(canister, &sender) {
+            return (Err(err), NumInstructions::new(0));
+        };
+
+        let replace_snapshot = args.replace_snapshot();
+        let replace_snapshot_size = match replace_snapshot {
+            Some(replace_snapshot) => {
+                match Self::get_canister_snapshot(state, canister.canister_id(), replace_snapshot) {
+                    Ok(snapshot) => snapshot.size(),
+                    Err(err) => return (Err(err), NumInstructions::new(0)),
+                }
+            }
+            // No replace snapshot ID provided, check whether the maximum number of snapshots
+            // has been reached.
+            None => {
+                if state
+                    .canister_snapshots
+                    .count_by_canister(&canister.canister_id())
+                    >= MAX_NUMBER_OF_SNAPSHOTS_PER_CANISTER
+                {
+                    return (
+                        Err(CanisterManagerError::CanisterSnapshotLimitExceeded {
+                            canister_id: canister.canister_id(),
+                            limit: MAX_NUMBER_OF_SNAPSHOTS_PER_CANISTER,
+                        }),
+                        NumInstructions::new(0),
+                    );
+                }
+                0.into()
+            }
+        };
+
+        if let Err(err) = self.validate_snapshot_metadata(args) {
+            return (Err(err), NumInstructions::new(0));
+        }
+
+        // We use 8 bytes per global, like for the execution state.
+        let new_snapshot_size = NumBytes::from(
+            args.wasm_module_size
+                + args.wasm_memory_size
+                + args.stable_memory_size
+                + 8 * args.exported_globals.len() as u64
+                + args.certified_data.len() as u64,
+        );
+        let new_snapshot_increase = NumBytes::from(
+            new_snapshot_size
+                .get()
+                .saturating_sub(replace_snapshot_size.get()),
+        );
+        let new_memory_usage = NumBytes::from(
+            canister
+                .memory_usage()
+                .get()
+                .saturating_add(new_snapshot_size.get())
+                .saturating_sub(replace_snapshot_size.get()),
+        );
+
+        if let Err(err) = self.reserve_snapshot_memory(
+            canister,
+            new_snapshot_increase,
+            new_memory_usage,
+            subnet_size,
+            round_limits,
+            resource_saturation,
+        ) {
+            return (Err(err), NumInstructions::new(0));
+        }
+
+        // Charge for creating the snapshot.
+        let instructions = self.config.canister_snapshot_baseline_instructions;
+        if let Err(err) = self.cycles_account_manager.consume_cycles_for_instructions(
+            &sender,
+            canister,
+            instructions,
+            subnet_size,
+        ) {
+            return (
+                Err(CanisterManagerError::CanisterSnapshotNotEnoughCycles(err)),
+                0.into(),
+            );
+        };
+
+        let execution_snapshot = ExecutionStateSnapshot {
+            wasm_binary: CanisterModule::new(vec![0; args.wasm_module_size as usize]),
+            exported_globals: args
+                .exported_globals
+                .iter()
+                .map(snapshot_global_to_global)
+                .collect(),
+            stable_memory: PageMemory {
+                page_map: PageMap::new(Arc::clone(&self.fd_factory)),
+                size: NumWasmPages::new(args.stable_memory_size as usize / WASM_PAGE_SIZE_IN_BYTES),
+            },
+            wasm_memory: PageMemory {
+                page_map: PageMap::new(Arc::clone(&self.fd_factory)),
+                size: NumWasmPages::new(args.wasm_memory_size as usize / WASM_PAGE_SIZE_IN_BYTES),
+            },
+        };
+        let new_snapshot = CanisterSnapshot::new(
+            canister.canister_id(),
+            SnapshotSource::MetadataUpload,
+            state.time(),
+            canister.system_state.canister_version,
+            args.certified_data.clone(),
+            WasmChunkStore::new(Arc::clone(&self.fd_factory)),
+            execution_snapshot,
+            new_snapshot_size,
+        );
+
+        // Delete old snapshot identified by `replace_snapshot` ID.
+        if let Some(replace_snapshot) = replace_snapshot {
+            state.canister_snapshots.remove(replace_snapshot);
+            canister.system_state.snapshots_memory_usage = canister
+                .system_state
+                .snapshots_memory_usage
+                .get()
+                .saturating_sub(replace_snapshot_size.get())
+                .into();
+            round_limits.subnet_available_memory.increment(
+                replace_snapshot_size,
+                NumBytes::from(0),
+                NumBytes::from(0),
+            );
+        }
+
+        // Actually deduct memory from the subnet. It's safe to unwrap
+        // here because we already checked the available memory above.
+        round_limits.subnet_available_memory
+            .try_decrement(new_snapshot_size, NumBytes::from(0), NumBytes::from(0))
+            .expect("Error: Cannot fail to decrement SubnetAvailableMemory after checking for availability");
+
+        let snapshot_id =
+            SnapshotId::from((canister.canister_id(), canister.new_local_snapshot_id()));
+        state
+            .canister_snapshots
+            .insert_uploaded(snapshot_id, Arc::new(new_snapshot));
+        canister.system_state.snapshots_memory_usage = canister
+            .system_state
+            .snapshots_memory_usage
+            .saturating_add(&new_snapshot_size);
+        // Confirm that `snapshots_memory_usage` is updated correctly.
+        debug_assert_eq!(
+            canister.system_state.snapshots_memory_usage,
+            state
+                .canister_snapshots
+                .compute_memory_usage_by_canister(canister.canister_id()),
+        );
+
+        (
+            Ok(UploadCanisterSnapshotMetadataResponse::new(&snapshot_id)),
+            instructions,
+        )
+    }
+
+    /// Writes a chunk of the Wasm module, the main memory or the stable memory
+    /// of the specified canister snapshot at the given offset, or inserts a
+    /// chunk into its Wasm chunk store.
+    ///
+    /// Uploading snapshot data can only be initiated by the controllers.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn upload_canister_snapshot_data(
+        &self,
+        subnet_size: usize,
+        sender: PrincipalId,
+        canister: &mut CanisterState,
+        args: &UploadCanisterSnapshotDataArgs,
+        state: &mut ReplicatedState,
+        round_limits: &mut RoundLimits,
+        resource_saturation: &ResourceSaturation,
+    ) -> (Result<(), CanisterManagerError>, NumInstructions) {
+        // Check sender is a controller.
+        if let Err(err) = validate_controller(canister, &sender) {
+            return (Err(err), NumInstructions::new(0));
+        };
+
+        let snapshot_id = args.get_snapshot_id();
+        let snapshot = match Self::get_canister_snapshot(state, canister.canister_id(), snapshot_id)
+        {
+            Ok(snapshot) => snapshot,
+            Err(err) => return (Err(err), NumInstructions::new(0)),
+        };
+        // Snapshots taken from a canister are immutable, only the contents of
+        // snapshots created by uploading metadata can be uploaded.
+        if snapshot.source() != SnapshotSource::MetadataUpload {
+            return (
+                Err(CanisterManagerError::CanisterSnapshotImmutable {
+                    canister_id: canister.canister_id(),
+                    snapshot_id,
+                }),
+                NumInstructions::new(0),
+            );
+        }
+
+        if self.config.rate_limiting_of_heap_delta == FlagStatus::Enabled
+            && canister.scheduler_state.heap_delta_debit >= self.config.heap_delta_rate_limit
+        {
+            return (
+                Err(CanisterManagerError::CanisterHeapDeltaRateLimited {
+                    canister_id: canister.canister_id(),
+                    value: canister.scheduler_state.heap_delta_debit,
+                    limit: self.config.heap_delta_rate_limit,
+                }),
+                NumInstructions::new(0),
+            );
+        }
+
+        let chunk = &args.chunk;
+        let mut new_snapshot = snapshot.as_ref().clone();
+        let result = match args.kind {
+            // The module is copied on write once the upload is charged below.
+            CanisterSnapshotDataOffset::WasmModule { offset } => validate_snapshot_data_range(
+                offset,
+                chunk.len() as u64,
+                snapshot.canister_module().len() as u64,
+            )
+            .map(|_| NumBytes::from(0)),
+            CanisterSnapshotDataOffset::MainMemory { offset } => write_snapshot_memory(
+                &mut new_snapshot.execution_snapshot_mut().wasm_memory,
+                offset,
+                chunk,
+            )
+            .map(|()| NumBytes::from(0)),
+            CanisterSnapshotDataOffset::StableMemory { offset } => write_snapshot_memory(
+                &mut new_snapshot.execution_snapshot_mut().stable_memory,
+                offset,
+                chunk,
+            )
+            .map(|()| NumBytes::from(0)),
+            CanisterSnapshotDataOffset::WasmChunk => snapshot
+                .chunk_store()
+                .can_insert_chunk(self.config.wasm_chunk_store_max_size, chunk)
+                .map(|()| wasm_chunk_store::chunk_size())
+                .map_err(|err| CanisterManagerError::WasmChunkStoreError { message: err }),
+        };
+        // The Wasm chunk store is the only part of a snapshot that can grow.
+        let memory_increase = match result {
+            Ok(memory_increase) => memory_increase,
+            Err(err) => return (Err(err), NumInstructions::new(0)),
+        };
+
+        if memory_increase.get() > 0 {
+            let new_memory_usage = canister.memory_usage() + memory_increase;
+            if let Err(err) = self.reserve_snapshot_memory(
+                canister,
+                memory_increase,
+                new_memory_usage,
+                subnet_size,
+                round_limits,
+                resource_saturation,
+            ) {
+                return (Err(err), NumInstructions::new(0));
+            }
+        }
+
+        // Charge for uploading the snapshot data.
+        let instructions = self
+            .config
+            .canister_snapshot_baseline_instructions
+            .saturating_add(&(chunk.len() as u64).into());
+        if let Err(err) = self.cycles_account_manager.consume_cycles_for_instructions(
+            &sender,
+            canister,
+            instructions,
+            subnet_size,
+        ) {
+            return (
+                Err(CanisterManagerError::CanisterSnapshotNotEnoughCycles(err)),
+                0.into(),
+            );
+        };
+
+        if let CanisterSnapshotDataOffset::WasmModule { offset } = args.kind {
+            // Drop the copies of the snapshot so that the module, which can
+            // be as large as the Wasm chunk store, is not copied for every
+            // chunk. Writing the module changes neither the size nor the
+            // heap delta of the snapshot.
+            drop(new_snapshot);
+            drop(snapshot);
+            state
+                .canister_snapshots
+                .get_uploaded_mut(snapshot_id)
+                .expect("Error: The uploaded snapshot was found above")
+                .execution_snapshot_mut()
+                .wasm_binary
+                .write(offset as usize, chunk);
+            return (Ok(()), instructions);
+        }
+
+        if let CanisterSnapshotDataOffset::WasmChunk = args.kind {
+            // It's safe to unwrap here because we already checked that the
+            // chunk can be inserted above.
+            new_snapshot
+                .insert_wasm_chunk(self.config.wasm_chunk_store_max_size, chunk)
+                .expect("Error: Cannot fail to insert a chunk after checking for it");
+            // Actually deduct memory from the subnet. It's safe to unwrap
+            // here because we already checked the available memory above.
+            round_limits.subnet_available_memory
+                .try_decrement(memory_increase, NumBytes::from(0), NumBytes::from(0))
+                .expect("Error: Cannot fail to decrement SubnetAvailableMemory after checking for availability");
+            canister.system_state.snapshots_memory_usage = canister
+                .system_state
+                .snapshots_memory_usage
+                .saturating_add(&memory_increase);
+        }
+
+        let heap_delta = NumBytes::from(
+            new_snapshot
+                .heap_delta()
+                .get()
+                .saturating_sub(snapshot.heap_delta().get()),
+        );
+        if self.config.rate_limiting_of_heap_delta == FlagStatus::Enabled {
+            canister.scheduler_state.heap_delta_debit = canister
+                .scheduler_state
+                .heap_delta_debit
+                .saturating_add(&heap_delta);
+        }
+        state.metadata.heap_delta_estimate = state
+            .metadata
+            .heap_delta_estimate
+            .saturating_add(&heap_delta);
+
+        state
+            .canister_snapshots
+            .insert_uploaded(snapshot_id, Arc::new(new_snapshot));
+        // Confirm that `snapshots_memory_usage` is updated correctly.
+        debug_assert_eq!(
+            canister.system_state.snapshots_memory_usage,
+            state
+                .canister_snapshots
+                .compute_memory_usage_by_canister(canister.canister_id()),
+        );
+
+        (Ok(()), instructions)
+    }
+
+    /// Validates the sizes declared in the metadata of an uploaded snapshot.
+    fn validate_snapshot_metadata(
+        &self,
+        args: &UploadCanisterSnapshotMetadataArgs,
+    ) -> Result<(), CanisterManagerError> {
+        // A Wasm module cannot be larger than what can be installed from the chunk store.
+        if args.wasm_module_size > self.config.wasm_chunk_store_max_size.get() {
+            return Err(CanisterManagerError::CanisterSnapshotInvalidData {
+                message: format!(
+                    "Wasm module size {} exceeds the maximum of {}",
+                    args.wasm_module_size, self.config.wasm_chunk_store_max_size
+                ),
+            });
+        }
+        for (name, size, max_size) in [
+            ("Wasm", args.wasm_memory_size, MAX_WASM_MEMORY_IN_BYTES),
+            (
+                "stable",
+                args.stable_memory_size,
+                MAX_STABLE_MEMORY_IN_BYTES,
+            ),
+        ] {
+            if size % WASM_PAGE_SIZE_IN_BYTES as u64 != 0 || size > max_size {
+                return Err(CanisterManagerError::CanisterSnapshotInvalidData {
+                    message: format!(
+                        "{} memory size {} must be a multiple of the Wasm page size {} and at most {}",
+                        name, size, WASM_PAGE_SIZE_IN_BYTES, max_size
+                    ),
+                });
+            }
+        }
+        if args.certified_data.len() > CERTIFIED_DATA_MAX_LENGTH {
+            return Err(CanisterManagerError::CanisterSnapshotInvalidData {
+                message: format!(
+                    "Certified data of length {} exceeds the maximum of {}",
+                    args.certified_data.len(),
+                    CERTIFIED_DATA_MAX_LENGTH
+                ),
+            });
+        }
+        Ok(())
+    }
+}
+
+fn global_to_snapshot_global(global: &Global) -> SnapshotGlobal {
+    match global {
+        Global::I32(value) => SnapshotGlobal::I32(*value),
+        Global::I64(value) => SnapshotGlobal::I64(*value),
+        Global::F32(value) => SnapshotGlobal::F32(*value),
+        Global::F64(value) => SnapshotGlobal::F64(*value),
+        Global::V128(value) => SnapshotGlobal::V128(*value),
+    }
+}
+
+fn snapshot_global_to_global(global: &SnapshotGlobal) -> Global {
+    match global {
+        SnapshotGlobal::I32(value) => Global::I32(*value),
+        SnapshotGlobal::I64(value) => Global::I64(*value),
+        SnapshotGlobal::F32(value) => Global::F32(*value),
+        SnapshotGlobal::F64(value) => Global::F64(*value),
+        SnapshotGlobal::V128(value) => Global::V128(*value),
+    }
+}
+
+fn memory_size_in_bytes(memory: &PageMemory) -> u64 {
+    (memory.size.get() * WASM_PAGE_SIZE_IN_BYTES) as u64
+}
+
+/// Returns the byte range of a chunk of snapshot data of `size` bytes at
+/// `offset`, or an error if the chunk is too large or does not fit into
+/// the `total_size` bytes of data.
+fn validate_snapshot_data_range(
+    offset: u64,
+    size: u64,
+    total_size: u64,
+) -> Result<Range<usize>, CanisterManagerError> {
+    if size > MAX_CANISTER_SNAPSHOT_DATA_CHUNK_SIZE {
+        return Err(CanisterManagerError::CanisterSnapshotInvalidData {
+            message: format!(
+                "Chunk size {} exceeds the maximum of {}",
+                size, MAX_CANISTER_SNAPSHOT_DATA_CHUNK_SIZE
+            ),
+        });
+    }
+    match offset.checked_add(size) {
+        Some(end) if end <= total_size => Ok(offset as usize..end as usize),
+        _ => Err(CanisterManagerError::CanisterSnapshotInvalidData {
+            message: format!(
+                "Range of {} bytes at offset {} is out of bounds of {} bytes",
+                size, offset, total_size
+            ),
+        }),
+    }
+}
+
+fn read_snapshot_memory(
+    memory: &PageMemory,
+    offset: u64,
+    size: u64,
+) -> Result<Vec<u8>, CanisterManagerError> {
+    let range = validate_snapshot_data_range(offset, size, memory_size_in_bytes(memory))?;
+    let mut chunk = vec![0; range.len()];
+    Buffer::new(memory.page_map.clone()).read(&mut chunk, range.start);
+    Ok(chunk)
+}
+
+fn write_snapshot_memory(
+    memory: &mut PageMemory,
+    offset: u64,
+    chunk: &[u8],
+) -> Result<(), CanisterManagerError> {
+    let range =
+        validate_snapshot_data_range(offset, chunk.len() as u64, memory_size_in_bytes(memory))?;
+    let mut buffer = Buffer::new(memory.page_map.clone());
+    buffer.write(chunk, range.start);
+    memory
+        .page_map
+        .update(&buffer.dirty_pages().collect::<Vec<_>>());
+    Ok(())
 }
 
 #[derive(Eq, PartialEq, Debug)]
@@ -2372,6 +3031,13 @@ pub(crate) enum CanisterManagerError {
         limit: usize,
     },
     CanisterSnapshotNotEnoughCycles(CanisterOutOfCyclesError),
+    CanisterSnapshotInvalidData {
+        message: String,
+    },
+    CanisterSnapshotImmutable {
+        canister_id: CanisterId,
+        snapshot_id: SnapshotId,
+    },
     LongExecutionAlreadyInProgress {
         canister_id: CanisterId,
     },
@@ -2542,6 +3208,18 @@ impl AsErrorHelp for CanisterManagerError {
                 suggestion: "".to_string(),
                 doc_link: "".to_string(),
             },
+            CanisterManagerError::CanisterSnapshotInvalidData { .. } => ErrorHelp::UserError {
+                suggestion: "Use the `read_canister_snapshot_metadata` API to check the sizes \
+                of the snapshot contents."
+                    .to_string(),
+                doc_link: "".to_string(),
+            },
+            CanisterManagerError::CanisterSnapshotImmutable { .. } => ErrorHelp::UserError {
+                suggestion: "Use the `upload_canister_snapshot_metadata` API to create a \
+                snapshot whose contents can be uploaded."
+                    .to_string(),
+                doc_link: "".to_string(),
+            },
             CanisterManagerError::LongExecutionAlreadyInProgress { .. } => ErrorHelp::UserError {
                 suggestion: "Try waiting for the long execution to complete.".to_string(),
                 doc_link: doc_ref("long-execution-already-in-progress"),
@@ -2856,6 +3534,20 @@ impl From<CanisterManagerError> for UserError {
                     format!("Canister snapshotting failed with `{}`{additional_help}", err),
                 )
             }
+            CanisterSnapshotInvalidData { message } => {
+                Self::new(
+                    ErrorCode::InvalidManagementPayload,
+                    format!("Invalid snapshot data: {}.{additional_help}", message),
+                )
+            }
+            CanisterSnapshotImmutable { canister_id, snapshot_id } => {
+                Self::new(
+                    ErrorCode::CanisterRejectedMessage,
+                    format!(
+                        "The snapshot {} of canister {} was taken from the canister and cannot be modified.{additional_help}", snapshot_id, canister_id,
+                    )
+                )
+            }
             LongExecutionAlreadyInProgress { canister_id } => {
                 Self::new(
                     ErrorCode::CanisterRejectedMessage,
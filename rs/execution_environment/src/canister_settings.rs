@@ -2,12 +2,13 @@ use ic_base_types::{NumBytes, NumSeconds};
 use ic_cycles_account_manager::{CyclesAccountManager, ResourceSaturation};
 use ic_error_types::{ErrorCode, UserError};
 use ic_interfaces::execution_environment::SubnetAvailableMemory;
-use ic_management_canister_types::{CanisterSettingsArgs, LogVisibilityV2};
+use ic_management_canister_types::{CanisterSettingsArgs, EnvironmentVariable, LogVisibilityV2};
 use ic_types::{
     ComputeAllocation, Cycles, InvalidComputeAllocationError, InvalidMemoryAllocationError,
     MemoryAllocation, PrincipalId,
 };
 use num_traits::cast::ToPrimitive;
+use std::collections::BTreeMap;
 use std::convert::TryFrom;
 
 use crate::canister_manager::CanisterManagerError;
@@ -15,6 +16,13 @@ use crate::canister_manager::CanisterManagerError;
 /// These limit comes from the spec and is not expected to change,
 /// which is why it is not part of the replica config.
 const MAX_WASM_MEMORY_LIMIT: u64 = 1 << 48;
+/// Maximum number of environment variables a canister can have.
+pub(crate) const MAX_ENVIRONMENT_VARIABLES: usize = 20;
+/// Maximum length in bytes of an environment variable name.
+pub(crate) const MAX_ENVIRONMENT_VARIABLE_NAME_LENGTH: usize = 128;
+/// Maximum length in bytes of an environment variable value.
+pub(crate) const MAX_ENVIRONMENT_VARIABLE_VALUE_LENGTH: usize = 128;
+
 /// Struct used for decoding CanisterSettingsArgs
 #[derive(Default)]
 pub(crate) struct CanisterSettings {
@@ -27,9 +35,11 @@ pub(crate) struct CanisterSettings {
     pub(crate) reserved_cycles_limit: Option<Cycles>,
     pub(crate) log_visibility: Option<LogVisibilityV2>,
     pub(crate) wasm_memory_limit: Option<NumBytes>,
+    pub(crate) environment_variables: Option<Vec<EnvironmentVariable>>,
 }
 
 impl CanisterSettings {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         controllers: Option<Vec<PrincipalId>>,
         compute_allocation: Option<ComputeAllocation>,
@@ -39,6 +49,7 @@ impl CanisterSettings {
         reserved_cycles_limit: Option<Cycles>,
         log_visibility: Option<LogVisibilityV2>,
         wasm_memory_limit: Option<NumBytes>,
+        environment_variables: Option<Vec<EnvironmentVariable>>,
     ) -> Self {
         Self {
             controllers,
@@ -49,6 +60,7 @@ impl CanisterSettings {
             reserved_cycles_limit,
             log_visibility,
             wasm_memory_limit,
+            environment_variables,
         }
     }
 
@@ -83,6 +95,10 @@ impl CanisterSettings {
     pub fn wasm_memory_limit(&self) -> Option<NumBytes> {
         self.wasm_memory_limit
     }
+
+    pub fn environment_variables(&self) -> Option<&Vec<EnvironmentVariable>> {
+        self.environment_variables.as_ref()
+    }
 }
 
 impl TryFrom<CanisterSettingsArgs> for CanisterSettings {
@@ -157,6 +173,7 @@ impl TryFrom<CanisterSettingsArgs> for CanisterSettings {
             reserved_cycles_limit,
             input.log_visibility,
             wasm_memory_limit,
+            input.environment_variables,
         ))
     }
 }
@@ -181,6 +198,7 @@ pub(crate) struct CanisterSettingsBuilder {
     reserved_cycles_limit: Option<Cycles>,
     log_visibility: Option<LogVisibilityV2>,
     wasm_memory_limit: Option<NumBytes>,
+    environment_variables: Option<Vec<EnvironmentVariable>>,
 }
 
 #[allow(dead_code)]
@@ -195,6 +213,7 @@ impl CanisterSettingsBuilder {
             reserved_cycles_limit: None,
             log_visibility: None,
             wasm_memory_limit: None,
+            environment_variables: None,
         }
     }
 
@@ -208,6 +227,7 @@ impl CanisterSettingsBuilder {
             reserved_cycles_limit: self.reserved_cycles_limit,
             log_visibility: self.log_visibility,
             wasm_memory_limit: self.wasm_memory_limit,
+            environment_variables: self.environment_variables,
         }
     }
 
@@ -266,6 +286,16 @@ impl CanisterSettingsBuilder {
             ..self
         }
     }
+
+    pub fn with_environment_variables(
+        self,
+        environment_variables: Vec<EnvironmentVariable>,
+    ) -> Self {
+        Self {
+            environment_variables: Some(environment_variables),
+            ..self
+        }
+    }
 }
 
 pub enum UpdateSettingsError {
@@ -350,6 +380,7 @@ pub(crate) struct ValidatedCanisterSettings {
     reservation_cycles: Cycles,
     log_visibility: Option<LogVisibilityV2>,
     wasm_memory_limit: Option<NumBytes>,
+    environment_variables: Option<BTreeMap<String, String>>,
 }
 
 impl ValidatedCanisterSettings {
@@ -388,6 +419,10 @@ impl ValidatedCanisterSettings {
     pub fn wasm_memory_limit(&self) -> Option<NumBytes> {
         self.wasm_memory_limit
     }
+
+    pub fn environment_variables(&self) -> Option<&BTreeMap<String, String>> {
+        self.environment_variables.as_ref()
+    }
 }
 
 /// Validates the new canisters settings:
@@ -401,6 +436,10 @@ impl ValidatedCanisterSettings {
 ///     - there must be enough cycles to avoid freezing the canister.
 /// - controllers:
 ///     - the number of controllers cannot exceed the given maximum.
+/// - environment variables:
+///     - the number of variables and the lengths of names and values cannot
+///       exceed the fixed maximums.
+///     - names must be non-empty and unique.
 ///
 /// Keep this function in sync with `do_update_settings()`.
 #[allow(clippy::too_many_arguments)]
@@ -494,6 +533,11 @@ pub(crate) fn validate_canister_settings(
         }
     }
 
+    let environment_variables = settings
+        .environment_variables()
+        .map(|variables| validate_environment_variables(variables))
+        .transpose()?;
+
     let new_memory_allocation = settings
         .memory_allocation
         .unwrap_or(canister_memory_allocation);
@@ -595,5 +639,54 @@ pub(crate) fn validate_canister_settings(
         reservation_cycles,
         log_visibility: settings.log_visibility().cloned(),
         wasm_memory_limit: settings.wasm_memory_limit(),
+        environment_variables,
     })
 }
+
+/// Checks the given environment variables against the limits and converts
+/// them into a map keyed by name.
+fn validate_environment_variables(
+    variables: &[EnvironmentVariable],
+) -> Result<BTreeMap<String, String>, CanisterManagerError> {
+    if variables.len() > MAX_ENVIRONMENT_VARIABLES {
+        return Err(CanisterManagerError::InvalidSettings {
+            message: format!(
+                "Invalid settings: 'environment_variables' length exceeds maximum size allowed of {}.",
+                MAX_ENVIRONMENT_VARIABLES
+            ),
+        });
+    }
+    let mut result = BTreeMap::new();
+    for EnvironmentVariable { name, value } in variables {
+        if name.is_empty() {
+            return Err(CanisterManagerError::InvalidSettings {
+                message: "Invalid settings: environment variable name cannot be empty.".to_string(),
+            });
+        }
+        if name.len() > MAX_ENVIRONMENT_VARIABLE_NAME_LENGTH {
+            return Err(CanisterManagerError::InvalidSettings {
+                message: format!(
+                    "Invalid settings: environment variable name exceeds maximum length allowed of {} bytes.",
+                    MAX_ENVIRONMENT_VARIABLE_NAME_LENGTH
+                ),
+            });
+        }
+        if value.len() > MAX_ENVIRONMENT_VARIABLE_VALUE_LENGTH {
+            return Err(CanisterManagerError::InvalidSettings {
+                message: format!(
+                    "Invalid settings: value of environment variable '{}' exceeds maximum length allowed of {} bytes.",
+                    name, MAX_ENVIRONMENT_VARIABLE_VALUE_LENGTH
+                ),
+            });
+        }
+        if result.insert(name.clone(), value.clone()).is_some() {
+            return Err(CanisterManagerError::InvalidSettings {
+                message: format!(
+                    "Invalid settings: duplicate environment variable name '{}'.",
+                    name
+                ),
+            });
+        }
+    }
+    Ok(result)
+}
@@ -38,9 +38,10 @@ use ic_management_canister_types::{
     EmptyBlob, InstallChunkedCodeArgs, InstallCodeArgsV2, ListCanisterSnapshotArgs,
     LoadCanisterSnapshotArgs, MasterPublicKeyId, Method as Ic00Method, NodeMetricsHistoryArgs,
     Payload as Ic00Payload, ProvisionalCreateCanisterWithCyclesArgs, ProvisionalTopUpCanisterArgs,
-    SchnorrPublicKeyArgs, SchnorrPublicKeyResponse, SetupInitialDKGArgs, SignWithECDSAArgs,
-    SignWithSchnorrArgs, StoredChunksArgs, TakeCanisterSnapshotArgs, UninstallCodeArgs,
-    UpdateSettingsArgs, UploadChunkArgs, IC_00,
+    ReadCanisterSnapshotDataArgs, ReadCanisterSnapshotMetadataArgs, SchnorrPublicKeyArgs,
+    SchnorrPublicKeyResponse, SetupInitialDKGArgs, SignWithECDSAArgs, SignWithSchnorrArgs,
+    StoredChunksArgs, TakeCanisterSnapshotArgs, UninstallCodeArgs, UpdateSettingsArgs,
+    UploadCanisterSnapshotDataArgs, UploadCanisterSnapshotMetadataArgs, UploadChunkArgs, IC_00,
 };
 use ic_metrics::MetricsRegistry;
 use ic_registry_provisional_whitelist::ProvisionalWhitelist;
@@ -58,7 +59,7 @@ use ic_replicated_state::{
 };
 use ic_system_api::{ExecutionParameters, InstructionLimits};
 use ic_types::{
-    canister_http::CanisterHttpRequestContext,
+    canister_http::{CanisterHttpRequestContext, Replication},
     crypto::{
         canister_threshold_sig::{ExtendedDerivationPath, MasterPublicKey, PublicKey},
         threshold_sig::ni_dkg::NiDkgTargetId,
@@ -79,6 +80,7 @@ use ic_wasm_types::WasmHash;
 use phantom_newtype::AmountOf;
 use prometheus::IntCounter;
 use rand::RngCore;
+use serde::Serialize;
 use std::{
     collections::{BTreeMap, HashMap},
     convert::{Into, TryFrom},
@@ -909,22 +911,36 @@ impl ExecutionEnvironment {
                                 response: Err(err),
                                 refund: msg.take_cycles(),
                             },
-                            Ok(args) => match CanisterHttpRequestContext::try_from((
-                                state.time(),
+                            Ok(args) => match self.canister_http_request_context(
+                                &state,
                                 request.as_ref(),
                                 args,
-                            )) {
+                                rng,
+                            ) {
                                 Err(err) => ExecuteSubnetMessageResult::Finished {
-                                    response: Err(err.into()),
+                                    response: Err(err),
                                     refund: msg.take_cycles(),
                                 },
                                 Ok(mut canister_http_request_context) => {
-                                    let http_request_fee =
-                                        self.cycles_account_manager.http_request_fee(
-                                            canister_http_request_context.variable_parts_size(),
-                                            canister_http_request_context.max_response_bytes,
-                                            registry_settings.subnet_size,
-                                        );
+                                    let http_request_fee = match canister_http_request_context
+                                        .replication
+                                    {
+                                        Replication::FullyReplicated => {
+                                            self.cycles_account_manager.http_request_fee(
+                                                canister_http_request_context.variable_parts_size(),
+                                                canister_http_request_context
+                                                    .charged_response_bytes(),
+                                                registry_settings.subnet_size,
+                                            )
+                                        }
+                                        Replication::NonReplicated(_) => self
+                                            .cycles_account_manager
+                                            .non_replicated_http_request_fee(
+                                                canister_http_request_context.variable_parts_size(),
+                                                canister_http_request_context
+                                                    .charged_response_bytes(),
+                                            ),
+                                    };
                                     // Here we make sure that we do not let upper layers open new
                                     // http calls while the maximum number of calls is in-flight.
                                     // Later, in the http adapter we also have a bounded queue of
@@ -1507,6 +1523,140 @@ impl ExecutionEnvironment {
                 }
             },
 
+            Ok(Ic00Method::ReadCanisterSnapshotMetadata) => match self.config.canister_snapshots {
+                FlagStatus::Enabled => {
+                    let res = ReadCanisterSnapshotMetadataArgs::decode(payload).and_then(|args| {
+                        self.read_canister_snapshot_metadata(*msg.sender(), &state, args)
+                    });
+                    ExecuteSubnetMessageResult::Finished {
+                        response: res,
+                        refund: msg.take_cycles(),
+                    }
+                }
+                FlagStatus::Disabled => {
+                    let err = Err(UserError::new(
+                        ErrorCode::CanisterContractViolation,
+                        "This API is not enabled on this subnet".to_string(),
+                    ));
+                    ExecuteSubnetMessageResult::Finished {
+                        response: err,
+                        refund: msg.take_cycles(),
+                    }
+                }
+            },
+
+            Ok(Ic00Method::ReadCanisterSnapshotData) => match self.config.canister_snapshots {
+                FlagStatus::Enabled => match ReadCanisterSnapshotDataArgs::decode(payload) {
+                    Err(err) => ExecuteSubnetMessageResult::Finished {
+                        response: Err(err),
+                        refund: msg.take_cycles(),
+                    },
+                    Ok(args) => {
+                        let (result, instructions_used) = self.read_canister_snapshot_data(
+                            *msg.sender(),
+                            &mut state,
+                            args,
+                            registry_settings.subnet_size,
+                        );
+                        let msg_result = ExecuteSubnetMessageResult::Finished {
+                            response: result,
+                            refund: msg.take_cycles(),
+                        };
+
+                        let state =
+                            self.finish_subnet_message_execution(state, msg, msg_result, since);
+                        return (state, Some(instructions_used));
+                    }
+                },
+                FlagStatus::Disabled => {
+                    let err = Err(UserError::new(
+                        ErrorCode::CanisterContractViolation,
+                        "This API is not enabled on this subnet".to_string(),
+                    ));
+                    ExecuteSubnetMessageResult::Finished {
+                        response: err,
+                        refund: msg.take_cycles(),
+                    }
+                }
+            },
+
+            Ok(Ic00Method::UploadCanisterSnapshotMetadata) => {
+                match self.config.canister_snapshots {
+                    FlagStatus::Enabled => {
+                        match UploadCanisterSnapshotMetadataArgs::decode(payload) {
+                            Err(err) => ExecuteSubnetMessageResult::Finished {
+                                response: Err(err),
+                                refund: msg.take_cycles(),
+                            },
+                            Ok(args) => {
+                                let (result, instructions_used) = self
+                                    .upload_canister_snapshot_metadata(
+                                        *msg.sender(),
+                                        &mut state,
+                                        args,
+                                        registry_settings.subnet_size,
+                                        round_limits,
+                                    );
+                                let msg_result = ExecuteSubnetMessageResult::Finished {
+                                    response: result,
+                                    refund: msg.take_cycles(),
+                                };
+
+                                let state = self
+                                    .finish_subnet_message_execution(state, msg, msg_result, since);
+                                return (state, Some(instructions_used));
+                            }
+                        }
+                    }
+                    FlagStatus::Disabled => {
+                        let err = Err(UserError::new(
+                            ErrorCode::CanisterContractViolation,
+                            "This API is not enabled on this subnet".to_string(),
+                        ));
+                        ExecuteSubnetMessageResult::Finished {
+                            response: err,
+                            refund: msg.take_cycles(),
+                        }
+                    }
+                }
+            }
+
+            Ok(Ic00Method::UploadCanisterSnapshotData) => match self.config.canister_snapshots {
+                FlagStatus::Enabled => match UploadCanisterSnapshotDataArgs::decode(payload) {
+                    Err(err) => ExecuteSubnetMessageResult::Finished {
+                        response: Err(err),
+                        refund: msg.take_cycles(),
+                    },
+                    Ok(args) => {
+                        let (result, instructions_used) = self.upload_canister_snapshot_data(
+                            *msg.sender(),
+                            &mut state,
+                            args,
+                            registry_settings.subnet_size,
+                            round_limits,
+                        );
+                        let msg_result = ExecuteSubnetMessageResult::Finished {
+                            response: result,
+                            refund: msg.take_cycles(),
+                        };
+
+                        let state =
+                            self.finish_subnet_message_execution(state, msg, msg_result, since);
+                        return (state, Some(instructions_used));
+                    }
+                },
+                FlagStatus::Disabled => {
+                    let err = Err(UserError::new(
+                        ErrorCode::CanisterContractViolation,
+                        "This API is not enabled on this subnet".to_string(),
+                    ));
+                    ExecuteSubnetMessageResult::Finished {
+                        response: err,
+                        refund: msg.take_cycles(),
+                    }
+                }
+            },
+
             Err(ParseError::VariantNotFound) => {
                 let res = Err(UserError::new(
                     ErrorCode::CanisterMethodNotFound,
@@ -1524,6 +1674,9 @@ impl ExecutionEnvironment {
         //   - `InstallChunkedCode`
         //   - `TakeCanisterSnapshot`
         //   - `LoadCanisterSnapshot`
+        //   - `ReadCanisterSnapshotData`
+        //   - `UploadCanisterSnapshotMetadata`
+        //   - `UploadCanisterSnapshotData`
         //   - `SignWithECDSA`
         // If you modify code below, please also update
         // these cases.
@@ -1864,6 +2017,37 @@ impl ExecutionEnvironment {
         }
     }
 
+    /// Creates the context of a canister http request. Non-replicated requests
+    /// are assigned to a node of this subnet chosen at random.
+    fn canister_http_request_context(
+        &self,
+        state: &ReplicatedState,
+        request: &Request,
+        args: CanisterHttpRequestArgs,
+        rng: &mut dyn RngCore,
+    ) -> Result<CanisterHttpRequestContext, UserError> {
+        let is_replicated = args.is_replicated.unwrap_or(true);
+        let mut context = CanisterHttpRequestContext::try_from((state.time(), request, args))?;
+        if !is_replicated {
+            let nodes: Vec<_> = state
+                .metadata
+                .network_topology
+                .subnets
+                .get(&state.metadata.own_subnet_id)
+                .map(|subnet| subnet.nodes.iter().copied().collect())
+                .unwrap_or_default();
+            if nodes.is_empty() {
+                return Err(UserError::new(
+                    ErrorCode::CanisterRejectedMessage,
+                    "Non-replicated http requests are not supported: the nodes of this subnet are unknown.",
+                ));
+            }
+            let node_id = nodes[(rng.next_u64() % nodes.len() as u64) as usize];
+            context.replication = Replication::NonReplicated(node_id);
+        }
+        Ok(context)
+    }
+
     fn deposit_cycles(
         &self,
         canister_id: CanisterId,
@@ -2201,6 +2385,149 @@ impl ExecutionEnvironment {
         result
     }
 
+    /// Returns the metadata of the specified canister snapshot.
+    fn read_canister_snapshot_metadata(
+        &self,
+        sender: PrincipalId,
+        state: &ReplicatedState,
+        args: ReadCanisterSnapshotMetadataArgs,
+    ) -> Result<Vec<u8>, UserError> {
+        let canister = get_canister(args.get_canister_id(), state)?;
+        self.canister_manager
+            .read_canister_snapshot_metadata(sender, canister, args.get_snapshot_id(), state)
+            .map(|response| response.encode())
+            .map_err(|err| err.into())
+    }
+
+    /// Reads a chunk of data of the specified canister snapshot.
+    fn read_canister_snapshot_data(
+        &self,
+        sender: PrincipalId,
+        state: &mut ReplicatedState,
+        args: ReadCanisterSnapshotDataArgs,
+        subnet_size: usize,
+    ) -> (Result<Vec<u8>, UserError>, NumInstructions) {
+        let canister_id = args.get_canister_id();
+        // Take canister out.
+        let mut canister = match state.take_canister_state(&canister_id) {
+            None => {
+                return (
+                    Err(UserError::new(
+                        ErrorCode::CanisterNotFound,
+                        format!("Canister {} not found.", &canister_id),
+                    )),
+                    NumInstructions::new(0),
+                )
+            }
+            Some(canister) => canister,
+        };
+
+        let (result, instructions_used) = self.canister_manager.read_canister_snapshot_data(
+            subnet_size,
+            sender,
+            &mut canister,
+            args.get_snapshot_id(),
+            &args.kind,
+            state,
+        );
+        // Put canister back.
+        state.put_canister_state(canister);
+
+        match result {
+            Ok(response) => (Ok(response.encode()), instructions_used),
+            Err(err) => (Err(err.into()), instructions_used),
+        }
+    }
+
+    /// Creates a new canister snapshot from uploaded metadata and inserts it
+    /// into `ReplicatedState`.
+    fn upload_canister_snapshot_metadata(
+        &self,
+        sender: PrincipalId,
+        state: &mut ReplicatedState,
+        args: UploadCanisterSnapshotMetadataArgs,
+        subnet_size: usize,
+        round_limits: &mut RoundLimits,
+    ) -> (Result<Vec<u8>, UserError>, NumInstructions) {
+        let canister_id = args.get_canister_id();
+        // Take canister out.
+        let mut canister = match state.take_canister_state(&canister_id) {
+            None => {
+                return (
+                    Err(UserError::new(
+                        ErrorCode::CanisterNotFound,
+                        format!("Canister {} not found.", &canister_id),
+                    )),
+                    NumInstructions::new(0),
+                )
+            }
+            Some(canister) => canister,
+        };
+
+        let resource_saturation =
+            self.subnet_memory_saturation(&round_limits.subnet_available_memory);
+        let (result, instructions_used) = self.canister_manager.upload_canister_snapshot_metadata(
+            subnet_size,
+            sender,
+            &mut canister,
+            &args,
+            state,
+            round_limits,
+            &resource_saturation,
+        );
+        // Put canister back.
+        state.put_canister_state(canister);
+
+        match result {
+            Ok(response) => (Ok(response.encode()), instructions_used),
+            Err(err) => (Err(err.into()), instructions_used),
+        }
+    }
+
+    /// Uploads a chunk of data to the specified canister snapshot.
+    fn upload_canister_snapshot_data(
+        &self,
+        sender: PrincipalId,
+        state: &mut ReplicatedState,
+        args: UploadCanisterSnapshotDataArgs,
+        subnet_size: usize,
+        round_limits: &mut RoundLimits,
+    ) -> (Result<Vec<u8>, UserError>, NumInstructions) {
+        let canister_id = args.get_canister_id();
+        // Take canister out.
+        let mut canister = match state.take_canister_state(&canister_id) {
+            None => {
+                return (
+                    Err(UserError::new(
+                        ErrorCode::CanisterNotFound,
+                        format!("Canister {} not found.", &canister_id),
+                    )),
+                    NumInstructions::new(0),
+                )
+            }
+            Some(canister) => canister,
+        };
+
+        let resource_saturation =
+            self.subnet_memory_saturation(&round_limits.subnet_available_memory);
+        let (result, instructions_used) = self.canister_manager.upload_canister_snapshot_data(
+            subnet_size,
+            sender,
+            &mut canister,
+            &args,
+            state,
+            round_limits,
+            &resource_saturation,
+        );
+        // Put canister back.
+        state.put_canister_state(canister);
+
+        match result {
+            Ok(()) => (Ok(EmptyBlob.encode()), instructions_used),
+            Err(err) => (Err(err.into()), instructions_used),
+        }
+    }
+
     fn node_metrics_history(
         &self,
         state: &ReplicatedState,
@@ -3598,6 +3925,41 @@ fn get_canister_mut(
     }
 }
 
+/// Whether an executed message was replied to or rejected.
+#[derive(Clone, Eq, PartialEq, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MessageOutcome {
+    Reply,
+    Reject(String),
+}
+
+impl ExecuteMessageResult {
+    /// Returns the outcome of the execution if it finished with a reply or a
+    /// reject, based on the ingress status or the response to the request.
+    fn outcome(&self) -> Option<MessageOutcome> {
+        let response = match self {
+            ExecuteMessageResult::Finished { response, .. } => response,
+            ExecuteMessageResult::Paused { .. } => return None,
+        };
+        match response {
+            ExecutionResponse::Ingress((_, IngressStatus::Known { state, .. })) => match state {
+                IngressState::Completed(WasmResult::Reply(_)) => Some(MessageOutcome::Reply),
+                IngressState::Completed(WasmResult::Reject(message)) => {
+                    Some(MessageOutcome::Reject(message.clone()))
+                }
+                IngressState::Failed(err) => Some(MessageOutcome::Reject(err.to_string())),
+                IngressState::Received | IngressState::Processing | IngressState::Done => None,
+            },
+            ExecutionResponse::Ingress((_, IngressStatus::Unknown)) => None,
+            ExecutionResponse::Request(response) => match &response.response_payload {
+                Payload::Data(_) => Some(MessageOutcome::Reply),
+                Payload::Reject(context) => Some(MessageOutcome::Reject(context.message().clone())),
+            },
+            ExecutionResponse::Empty => None,
+        }
+    }
+}
+
 /// The result of `execute_canister()`.
 pub struct ExecuteCanisterResult {
     pub canister: CanisterState,
@@ -3606,6 +3968,11 @@ pub struct ExecuteCanisterResult {
     pub ingress_status: Option<(MessageId, IngressStatus)>,
     // The description of the executed task or message.
     pub description: Option<String>,
+    // The executed task or message, only set if messages are traced.
+    pub input: Option<CanisterMessageOrTask>,
+    // Whether the executed message was replied to or rejected, only set if
+    // messages are traced.
+    pub outcome: Option<MessageOutcome>,
 }
 
 /// Executes the given input message or task.
@@ -3621,8 +3988,10 @@ fn execute_canister_input(
     time: Time,
     round_limits: &mut RoundLimits,
     subnet_size: usize,
+    trace_messages: bool,
 ) -> ExecuteCanisterResult {
     let info = input.to_string();
+    let traced_input = trace_messages.then(|| input.clone());
     let result = exec_env.execute_canister_input(
         canister,
         instruction_limits,
@@ -3634,6 +4003,11 @@ fn execute_canister_input(
         round_limits,
         subnet_size,
     );
+    let outcome = if trace_messages {
+        result.outcome()
+    } else {
+        None
+    };
     let (canister, instructions_used, heap_delta, ingress_status) = exec_env.process_result(result);
     ExecuteCanisterResult {
         canister,
@@ -3641,11 +4015,14 @@ fn execute_canister_input(
         heap_delta,
         ingress_status,
         description: Some(info),
+        input: traced_input,
+        outcome,
     }
 }
 
 /// Executes either a single task from the task queue of the canister or a
-/// single input message if there is no task.
+/// single input message if there is no task. The executed input and its
+/// outcome are only returned if `trace_messages` is set.
 pub fn execute_canister(
     exec_env: &ExecutionEnvironment,
     mut canister: CanisterState,
@@ -3655,6 +4032,7 @@ pub fn execute_canister(
     time: Time,
     round_limits: &mut RoundLimits,
     subnet_size: usize,
+    trace_messages: bool,
 ) -> ExecuteCanisterResult {
     match canister.next_execution() {
         NextExecution::None | NextExecution::ContinueInstallCode => {
@@ -3664,6 +4042,8 @@ pub fn execute_canister(
                 heap_delta: NumBytes::from(0),
                 ingress_status: None,
                 description: None,
+                input: None,
+                outcome: None,
             };
         }
         NextExecution::StartNew | NextExecution::ContinueLong => {}
@@ -3673,6 +4053,7 @@ pub fn execute_canister(
         Some(task) => match task {
             ExecutionTask::PausedExecution { id, .. } => {
                 let paused = exec_env.take_paused_execution(id).unwrap();
+                let input = trace_messages.then(|| paused.input());
                 let round_counters = RoundCounters {
                     execution_refund_error: &exec_env.metrics.execution_cycles_refund_error,
                     state_changes_error: &exec_env.metrics.state_changes_error,
@@ -3698,6 +4079,11 @@ pub fn execute_canister(
                     subnet_size,
                     &exec_env.call_tree_metrics,
                 );
+                let outcome = if trace_messages {
+                    result.outcome()
+                } else {
+                    None
+                };
                 let (canister, instructions_used, heap_delta, ingress_status) =
                     exec_env.process_result(result);
                 return ExecuteCanisterResult {
@@ -3706,6 +4092,8 @@ pub fn execute_canister(
                     heap_delta,
                     ingress_status,
                     description: Some("paused execution".to_string()),
+                    input,
+                    outcome,
                 };
             }
             ExecutionTask::Heartbeat => {
@@ -3749,6 +4137,7 @@ pub fn execute_canister(
         time,
         round_limits,
         subnet_size,
+        trace_messages,
     )
 }
 
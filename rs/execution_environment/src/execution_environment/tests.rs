@@ -5,10 +5,11 @@ use ic_error_types::{ErrorCode, RejectCode, UserError};
 use ic_management_canister_types::{
     self as ic00, BitcoinGetUtxosArgs, BitcoinNetwork, BoundedHttpHeaders, CanisterChange,
     CanisterHttpRequestArgs, CanisterIdRecord, CanisterStatusResultV2, CanisterStatusType,
-    DerivationPath, EcdsaCurve, EcdsaKeyId, EmptyBlob, FetchCanisterLogsRequest, HttpMethod,
-    LogVisibilityV2, MasterPublicKeyId, Method, Payload as Ic00Payload,
-    ProvisionalCreateCanisterWithCyclesArgs, ProvisionalTopUpCanisterArgs, SchnorrAlgorithm,
-    SchnorrKeyId, TakeCanisterSnapshotArgs, TransformContext, TransformFunc, IC_00,
+    DerivationPath, EcdsaCurve, EcdsaKeyId, EmptyBlob, EnvironmentVariable,
+    FetchCanisterLogsRequest, HttpMethod, LogVisibilityV2, MasterPublicKeyId, Method,
+    Payload as Ic00Payload, ProvisionalCreateCanisterWithCyclesArgs, ProvisionalTopUpCanisterArgs,
+    SchnorrAlgorithm, SchnorrKeyId, TakeCanisterSnapshotArgs, TransformContext, TransformFunc,
+    IC_00,
 };
 use ic_registry_routing_table::{canister_id_into_u64, CanisterIdRange, RoutingTable};
 use ic_registry_subnet_type::SubnetType;
@@ -1619,6 +1620,8 @@ fn http_request_bound_holds() {
             }),
             context: transform_context.clone(),
         }),
+        response_range: None,
+        is_replicated: None,
     };
 
     // Create request to HTTP_REQUEST method.
@@ -2210,6 +2213,8 @@ fn execute_canister_http_request() {
             }),
             context: transform_context.clone(),
         }),
+        response_range: None,
+        is_replicated: None,
     };
 
     // Create request to HTTP_REQUEST method.
@@ -2289,6 +2294,8 @@ fn execute_canister_http_request_disabled() {
             }),
             context: vec![0, 1, 2],
         }),
+        response_range: None,
+        is_replicated: None,
     };
 
     // Create request to HTTP_REQUEST method.
@@ -3618,6 +3625,118 @@ fn test_canister_settings_log_visibility_set_to_public() {
     );
 }
 
+#[test]
+fn test_canister_settings_environment_variables_set_and_read_back() {
+    // Arrange.
+    let mut test = ExecutionTestBuilder::new().build();
+    let canister_id = test.create_canister(Cycles::new(1_000_000_000));
+    let version_before = test
+        .canister_state(canister_id)
+        .system_state
+        .canister_version;
+    // Act.
+    test.canister_update_environment_variables(
+        canister_id,
+        vec![
+            EnvironmentVariable::new("NETWORK".to_string(), "staging".to_string()),
+            EnvironmentVariable::new("API_URL".to_string(), "https://example.com".to_string()),
+        ],
+    )
+    .unwrap();
+    let result = test.canister_status(canister_id);
+    let canister_status = CanisterStatusResultV2::decode(&get_reply(result)).unwrap();
+    // Assert.
+    // Variables are returned ordered by name.
+    assert_eq!(
+        canister_status.settings().environment_variables(),
+        &[
+            EnvironmentVariable::new("API_URL".to_string(), "https://example.com".to_string()),
+            EnvironmentVariable::new("NETWORK".to_string(), "staging".to_string()),
+        ]
+    );
+    assert!(
+        test.canister_state(canister_id)
+            .system_state
+            .canister_version
+            > version_before
+    );
+}
+
+#[test]
+fn test_canister_settings_environment_variables_rejects_invalid_variables() {
+    let mut test = ExecutionTestBuilder::new().build();
+    let canister_id = test.create_canister(Cycles::new(1_000_000_000));
+    let invalid_variables = vec![
+        vec![
+            EnvironmentVariable::new("NETWORK".to_string(), "staging".to_string()),
+            EnvironmentVariable::new("NETWORK".to_string(), "production".to_string()),
+        ],
+        vec![EnvironmentVariable::new(String::new(), "value".to_string())],
+        vec![EnvironmentVariable::new(
+            "N".repeat(129),
+            "value".to_string(),
+        )],
+        vec![EnvironmentVariable::new(
+            "NETWORK".to_string(),
+            "v".repeat(129),
+        )],
+        (0..21)
+            .map(|i| EnvironmentVariable::new(format!("VAR_{}", i), "value".to_string()))
+            .collect(),
+    ];
+    for variables in invalid_variables {
+        let err = test
+            .canister_update_environment_variables(canister_id, variables)
+            .unwrap_err();
+        assert_eq!(err.code(), ErrorCode::CanisterContractViolation);
+    }
+    assert!(test
+        .canister_state(canister_id)
+        .system_state
+        .environment_variables
+        .is_empty());
+}
+
+#[test]
+fn test_canister_can_read_environment_variables() {
+    let mut test = ExecutionTestBuilder::new().build();
+    let canister_id = test
+        .canister_from_wat(
+            r#"(module
+                (import "ic0" "env_var_count" (func $env_var_count (result i32)))
+                (import "ic0" "env_var_value_size"
+                    (func $env_var_value_size (param i32 i32) (result i32)))
+                (import "ic0" "env_var_value_copy"
+                    (func $env_var_value_copy (param i32 i32 i32 i32 i32)))
+                (import "ic0" "msg_reply_data_append"
+                    (func $msg_reply_data_append (param i32 i32)))
+                (import "ic0" "msg_reply" (func $msg_reply))
+                (func (export "canister_query read_network")
+                    (if (i32.ne (call $env_var_count) (i32.const 1)) (then unreachable))
+                    (call $env_var_value_copy
+                        (i32.const 0) (i32.const 7) (i32.const 100) (i32.const 0)
+                        (call $env_var_value_size (i32.const 0) (i32.const 7)))
+                    (call $msg_reply_data_append (i32.const 100)
+                        (call $env_var_value_size (i32.const 0) (i32.const 7)))
+                    (call $msg_reply)
+                )
+                (memory 1)
+                (data (i32.const 0) "NETWORK")
+            )"#,
+        )
+        .unwrap();
+    test.canister_update_environment_variables(
+        canister_id,
+        vec![EnvironmentVariable::new(
+            "NETWORK".to_string(),
+            "staging".to_string(),
+        )],
+    )
+    .unwrap();
+    let result = test.ingress(canister_id, "read_network", vec![]).unwrap();
+    assert_eq!(result, WasmResult::Reply(b"staging".to_vec()));
+}
+
 #[test]
 fn test_fetch_canister_logs_should_accept_ingress_message() {
     // Arrange.
@@ -4,24 +4,29 @@ use ic_base_types::NumBytes;
 use ic_config::flag_status::FlagStatus;
 use ic_config::subnet_config::SubnetConfig;
 use ic_cycles_account_manager::ResourceSaturation;
-use ic_error_types::{ErrorCode, RejectCode};
+use ic_error_types::{ErrorCode, RejectCode, UserError};
 use ic_management_canister_types::{
-    self as ic00, CanisterChange, CanisterChangeDetails, CanisterSnapshotResponse,
-    ClearChunkStoreArgs, DeleteCanisterSnapshotArgs, ListCanisterSnapshotArgs,
-    LoadCanisterSnapshotArgs, Method, Payload as Ic00Payload, TakeCanisterSnapshotArgs,
-    UploadChunkArgs,
+    self as ic00, CanisterChange, CanisterChangeDetails, CanisterSnapshotDataKind,
+    CanisterSnapshotDataOffset, CanisterSnapshotResponse, ClearChunkStoreArgs,
+    DeleteCanisterSnapshotArgs, ListCanisterSnapshotArgs, LoadCanisterSnapshotArgs, Method,
+    Payload as Ic00Payload, ReadCanisterSnapshotDataArgs, ReadCanisterSnapshotDataResponse,
+    ReadCanisterSnapshotMetadataArgs, ReadCanisterSnapshotMetadataResponse, SnapshotGlobal,
+    TakeCanisterSnapshotArgs, UploadCanisterSnapshotDataArgs, UploadCanisterSnapshotMetadataArgs,
+    UploadCanisterSnapshotMetadataResponse, UploadChunkArgs, MAX_CANISTER_SNAPSHOT_DATA_CHUNK_SIZE,
 };
 use ic_registry_subnet_type::SubnetType;
 use ic_replicated_state::{
     canister_snapshots::SnapshotOperation,
-    canister_state::{execution_state::WasmBinary, system_state::CyclesUseCase},
+    canister_state::{
+        execution_state::WasmBinary, system_state::CyclesUseCase, WASM_PAGE_SIZE_IN_BYTES,
+    },
     CanisterState, ExecutionState, SchedulerState,
 };
 use ic_test_utilities_execution_environment::{
     cycles_reserved_for_app_and_verified_app_subnets, get_output_messages, ExecutionTest,
     ExecutionTestBuilder,
 };
-use ic_test_utilities_types::ids::{canister_test_id, subnet_test_id};
+use ic_test_utilities_types::ids::{canister_test_id, subnet_test_id, user_test_id};
 use ic_types::{
     ingress::WasmResult,
     messages::{Payload, RejectContext, RequestOrResponse},
@@ -1919,6 +1924,497 @@ fn snapshot_must_include_globals() {
     assert_eq!(result, WasmResult::Reply(vec![1, 0, 0, 0]));
 }
 
+#[test]
+fn read_and_upload_canister_snapshot_data_decode_round_trip() {
+    let canister_id = canister_test_id(4);
+    let snapshot_id = SnapshotId::from((canister_id, 6));
+
+    let args = ReadCanisterSnapshotMetadataArgs::new(canister_id, snapshot_id);
+    assert_eq!(
+        args,
+        ReadCanisterSnapshotMetadataArgs::decode(args.encode().as_slice()).unwrap()
+    );
+
+    let args = ReadCanisterSnapshotDataArgs::new(
+        canister_id,
+        snapshot_id,
+        CanisterSnapshotDataKind::MainMemory {
+            offset: 10,
+            size: 20,
+        },
+    );
+    assert_eq!(
+        args,
+        ReadCanisterSnapshotDataArgs::decode(args.encode().as_slice()).unwrap()
+    );
+
+    let args = UploadCanisterSnapshotMetadataArgs::new(
+        canister_id,
+        Some(snapshot_id),
+        3,
+        vec![SnapshotGlobal::I32(1), SnapshotGlobal::V128(u128::MAX)],
+        65536,
+        0,
+        vec![1, 2, 3],
+    );
+    assert_eq!(
+        args,
+        UploadCanisterSnapshotMetadataArgs::decode(args.encode().as_slice()).unwrap()
+    );
+
+    let args = UploadCanisterSnapshotDataArgs::new(
+        canister_id,
+        snapshot_id,
+        CanisterSnapshotDataOffset::WasmChunk,
+        vec![4, 5, 6],
+    );
+    assert_eq!(
+        args,
+        UploadCanisterSnapshotDataArgs::decode(args.encode().as_slice()).unwrap()
+    );
+}
+
+#[test]
+fn upload_canister_snapshot_metadata_decode_fails() {
+    let args = ic00::UploadCanisterSnapshotMetadataArgs {
+        canister_id: canister_test_id(4).get(),
+        replace_snapshot: Some(ByteBuf::from(vec![4, 5, 6, 6])), // Invalid snapshot ID.
+        ..Default::default()
+    };
+    let err = UploadCanisterSnapshotMetadataArgs::decode(args.encode().as_slice()).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::InvalidManagementPayload);
+}
+
+fn read_canister_snapshot_metadata(
+    test: &mut ExecutionTest,
+    canister_id: CanisterId,
+    snapshot_id: SnapshotId,
+) -> ReadCanisterSnapshotMetadataResponse {
+    let args = ReadCanisterSnapshotMetadataArgs::new(canister_id, snapshot_id);
+    let result = test
+        .subnet_message("read_canister_snapshot_metadata", args.encode())
+        .unwrap();
+    ReadCanisterSnapshotMetadataResponse::decode(&result.bytes()).unwrap()
+}
+
+fn read_canister_snapshot_data(
+    test: &mut ExecutionTest,
+    canister_id: CanisterId,
+    snapshot_id: SnapshotId,
+    kind: CanisterSnapshotDataKind,
+) -> Result<Vec<u8>, UserError> {
+    let args = ReadCanisterSnapshotDataArgs::new(canister_id, snapshot_id, kind);
+    let result = test.subnet_message("read_canister_snapshot_data", args.encode())?;
+    Ok(ReadCanisterSnapshotDataResponse::decode(&result.bytes())
+        .unwrap()
+        .chunk)
+}
+
+fn upload_canister_snapshot_data(
+    test: &mut ExecutionTest,
+    canister_id: CanisterId,
+    snapshot_id: SnapshotId,
+    kind: CanisterSnapshotDataOffset,
+    chunk: Vec<u8>,
+) -> Result<WasmResult, UserError> {
+    let args = UploadCanisterSnapshotDataArgs::new(canister_id, snapshot_id, kind, chunk);
+    test.subnet_message("upload_canister_snapshot_data", args.encode())
+}
+
+#[test]
+fn read_canister_snapshot_data_fails_invalid_controller() {
+    let own_subnet = subnet_test_id(1);
+    let caller_canister = canister_test_id(1);
+    let mut test = ExecutionTestBuilder::new()
+        .with_own_subnet_id(own_subnet)
+        .with_snapshots(FlagStatus::Enabled)
+        .with_caller(own_subnet, caller_canister)
+        .build();
+
+    let canister_id = test
+        .canister_from_cycles_and_binary(
+            Cycles::new(1_000_000_000_000_000),
+            UNIVERSAL_CANISTER_WASM.into(),
+        )
+        .unwrap();
+    let args = TakeCanisterSnapshotArgs::new(canister_id, None);
+    let result = test.subnet_message("take_canister_snapshot", args.encode());
+    let snapshot_id = CanisterSnapshotResponse::decode(&result.unwrap().bytes())
+        .unwrap()
+        .snapshot_id();
+
+    // Only controllers can read the snapshot data.
+    test.set_user_id(user_test_id(42));
+    let err = read_canister_snapshot_data(
+        &mut test,
+        canister_id,
+        snapshot_id,
+        CanisterSnapshotDataKind::WasmModule { offset: 0, size: 1 },
+    )
+    .unwrap_err();
+    assert_eq!(err.code(), ErrorCode::CanisterInvalidController);
+}
+
+#[test]
+fn read_canister_snapshot_data_fails_out_of_bounds() {
+    let mut test = ExecutionTestBuilder::new()
+        .with_snapshots(FlagStatus::Enabled)
+        .build();
+
+    let canister_id = test
+        .canister_from_binary(UNIVERSAL_CANISTER_WASM.into())
+        .unwrap();
+    let args = TakeCanisterSnapshotArgs::new(canister_id, None);
+    let result = test.subnet_message("take_canister_snapshot", args.encode());
+    let snapshot_id = CanisterSnapshotResponse::decode(&result.unwrap().bytes())
+        .unwrap()
+        .snapshot_id();
+    let metadata = read_canister_snapshot_metadata(&mut test, canister_id, snapshot_id);
+    assert_eq!(
+        metadata.wasm_module_size,
+        UNIVERSAL_CANISTER_WASM.len() as u64
+    );
+
+    for kind in [
+        CanisterSnapshotDataKind::WasmModule {
+            offset: metadata.wasm_module_size,
+            size: 1,
+        },
+        CanisterSnapshotDataKind::MainMemory {
+            offset: u64::MAX,
+            size: 1,
+        },
+        CanisterSnapshotDataKind::StableMemory {
+            offset: 0,
+            size: metadata.stable_memory_size + 1,
+        },
+        CanisterSnapshotDataKind::WasmModule {
+            offset: 0,
+            size: MAX_CANISTER_SNAPSHOT_DATA_CHUNK_SIZE + 1,
+        },
+        CanisterSnapshotDataKind::WasmChunk { hash: vec![0; 32] },
+    ] {
+        let err =
+            read_canister_snapshot_data(&mut test, canister_id, snapshot_id, kind).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::InvalidManagementPayload);
+    }
+}
+
+#[test]
+fn upload_canister_snapshot_metadata_fails_invalid_sizes() {
+    let mut test = ExecutionTestBuilder::new()
+        .with_snapshots(FlagStatus::Enabled)
+        .build();
+
+    let canister_id = test
+        .canister_from_binary(UNIVERSAL_CANISTER_WASM.into())
+        .unwrap();
+
+    // The Wasm memory size is not a multiple of the Wasm page size.
+    let args =
+        UploadCanisterSnapshotMetadataArgs::new(canister_id, None, 10, vec![], 1000, 0, vec![]);
+    let err = test
+        .subnet_message("upload_canister_snapshot_metadata", args.encode())
+        .unwrap_err();
+    assert_eq!(err.code(), ErrorCode::InvalidManagementPayload);
+
+    // The certified data is too long.
+    let args =
+        UploadCanisterSnapshotMetadataArgs::new(canister_id, None, 10, vec![], 0, 0, vec![0; 33]);
+    let err = test
+        .subnet_message("upload_canister_snapshot_metadata", args.encode())
+        .unwrap_err();
+    assert_eq!(err.code(), ErrorCode::InvalidManagementPayload);
+    assert_eq!(test.state().canister_snapshots.count(), 0);
+}
+
+#[test]
+fn upload_canister_snapshot_data_fails_out_of_bounds() {
+    let mut test = ExecutionTestBuilder::new()
+        .with_snapshots(FlagStatus::Enabled)
+        .build();
+
+    let canister_id = test
+        .canister_from_binary(UNIVERSAL_CANISTER_WASM.into())
+        .unwrap();
+    let args = UploadCanisterSnapshotMetadataArgs::new(
+        canister_id,
+        None,
+        10,
+        vec![],
+        WASM_PAGE_SIZE_IN_BYTES as u64,
+        0,
+        vec![],
+    );
+    let result = test
+        .subnet_message("upload_canister_snapshot_metadata", args.encode())
+        .unwrap();
+    let snapshot_id = UploadCanisterSnapshotMetadataResponse::decode(&result.bytes())
+        .unwrap()
+        .get_snapshot_id();
+
+    for kind in [
+        CanisterSnapshotDataOffset::WasmModule { offset: 8 },
+        CanisterSnapshotDataOffset::MainMemory {
+            offset: WASM_PAGE_SIZE_IN_BYTES as u64 - 2,
+        },
+        CanisterSnapshotDataOffset::StableMemory { offset: 0 },
+    ] {
+        let err =
+            upload_canister_snapshot_data(&mut test, canister_id, snapshot_id, kind, vec![1, 2, 3])
+                .unwrap_err();
+        assert_eq!(err.code(), ErrorCode::InvalidManagementPayload);
+    }
+}
+
+#[test]
+fn upload_canister_snapshot_data_fails_for_taken_snapshot() {
+    let mut test = ExecutionTestBuilder::new()
+        .with_snapshots(FlagStatus::Enabled)
+        .build();
+
+    let canister_id = test
+        .canister_from_binary(UNIVERSAL_CANISTER_WASM.into())
+        .unwrap();
+    let args = TakeCanisterSnapshotArgs::new(canister_id, None);
+    let result = test.subnet_message("take_canister_snapshot", args.encode());
+    let snapshot_id = CanisterSnapshotResponse::decode(&result.unwrap().bytes())
+        .unwrap()
+        .snapshot_id();
+    let module_before = read_canister_snapshot_data(
+        &mut test,
+        canister_id,
+        snapshot_id,
+        CanisterSnapshotDataKind::WasmModule { offset: 0, size: 4 },
+    )
+    .unwrap();
+
+    // Snapshots taken from a canister cannot be modified.
+    for kind in [
+        CanisterSnapshotDataOffset::WasmModule { offset: 0 },
+        CanisterSnapshotDataOffset::MainMemory { offset: 0 },
+        CanisterSnapshotDataOffset::StableMemory { offset: 0 },
+        CanisterSnapshotDataOffset::WasmChunk,
+    ] {
+        let err =
+            upload_canister_snapshot_data(&mut test, canister_id, snapshot_id, kind, vec![1, 2, 3])
+                .unwrap_err();
+        assert_eq!(err.code(), ErrorCode::CanisterRejectedMessage);
+    }
+    let module_after = read_canister_snapshot_data(
+        &mut test,
+        canister_id,
+        snapshot_id,
+        CanisterSnapshotDataKind::WasmModule { offset: 0, size: 4 },
+    )
+    .unwrap();
+    assert_eq!(module_before, module_after);
+}
+
+#[test]
+fn canister_snapshot_can_be_downloaded_and_uploaded_to_another_canister() {
+    let wat = r#"
+    (module
+        (import "ic0" "msg_reply" (func $msg_reply))
+        (import "ic0" "msg_reply_data_append"
+          (func $msg_reply_data_append (param i32 i32)))
+
+        (func $read
+          (i32.store
+            (i32.const 0)
+            (global.get 0)
+          )
+          (call $msg_reply_data_append
+            (i32.const 0)
+            (i32.const 8))
+          (call $msg_reply)
+        )
+
+        (func $increase
+          (global.set 0
+            (i32.add
+              (global.get 0)
+              (i32.const 1)
+            )
+          )
+          (i32.store
+            (i32.const 4)
+            (i32.add
+              (i32.load (i32.const 4))
+              (i32.const 2)
+            )
+          )
+          (call $msg_reply)
+        )
+
+        (memory $memory 1)
+        (export "memory" (memory $memory))
+        (global (export "counter") (mut i32) (i32.const 0))
+        (export "canister_query read" (func $read))
+        (export "canister_update increase" (func $increase))
+      )"#;
+    let wasm = wat::parse_str(wat).unwrap();
+
+    let mut test = ExecutionTestBuilder::new()
+        .with_snapshots(FlagStatus::Enabled)
+        .build();
+
+    let source_canister_id = test.canister_from_binary(wasm.clone()).unwrap();
+    let target_canister_id = test.canister_from_binary(wasm).unwrap();
+
+    // Update the global and the memory of the source canister.
+    test.ingress(source_canister_id, "increase", vec![])
+        .unwrap();
+    let result = test
+        .non_replicated_query(source_canister_id, "read", vec![])
+        .unwrap();
+    assert_eq!(result, WasmResult::Reply(vec![1, 0, 0, 0, 2, 0, 0, 0]));
+
+    // Add a chunk to the chunk store and take a snapshot.
+    let chunk = vec![1, 2, 3, 4, 5];
+    let upload_args = UploadChunkArgs {
+        canister_id: source_canister_id.into(),
+        chunk: chunk.clone(),
+    };
+    test.subnet_message("upload_chunk", upload_args.encode())
+        .unwrap();
+    let args = TakeCanisterSnapshotArgs::new(source_canister_id, None);
+    let result = test
+        .subnet_message("take_canister_snapshot", args.encode())
+        .unwrap();
+    let source_snapshot_id = CanisterSnapshotResponse::decode(&result.bytes())
+        .unwrap()
+        .snapshot_id();
+
+    // Download the snapshot.
+    let metadata =
+        read_canister_snapshot_metadata(&mut test, source_canister_id, source_snapshot_id);
+    assert!(metadata.exported_globals.contains(&SnapshotGlobal::I32(1)));
+    assert_eq!(metadata.wasm_memory_size, WASM_PAGE_SIZE_IN_BYTES as u64);
+    assert_eq!(metadata.stable_memory_size, 0);
+    assert_eq!(metadata.wasm_chunk_store.len(), 1);
+    let wasm_module = read_canister_snapshot_data(
+        &mut test,
+        source_canister_id,
+        source_snapshot_id,
+        CanisterSnapshotDataKind::WasmModule {
+            offset: 0,
+            size: metadata.wasm_module_size,
+        },
+    )
+    .unwrap();
+    let main_memory = read_canister_snapshot_data(
+        &mut test,
+        source_canister_id,
+        source_snapshot_id,
+        CanisterSnapshotDataKind::MainMemory {
+            offset: 0,
+            size: metadata.wasm_memory_size,
+        },
+    )
+    .unwrap();
+    assert_eq!(&main_memory[4..8], &[2, 0, 0, 0]);
+    let downloaded_chunk = read_canister_snapshot_data(
+        &mut test,
+        source_canister_id,
+        source_snapshot_id,
+        CanisterSnapshotDataKind::WasmChunk {
+            hash: metadata.wasm_chunk_store[0].hash.clone(),
+        },
+    )
+    .unwrap();
+    assert_eq!(downloaded_chunk, chunk);
+
+    // Upload the snapshot to the target canister.
+    let args = UploadCanisterSnapshotMetadataArgs::new(
+        target_canister_id,
+        None,
+        metadata.wasm_module_size,
+        metadata.exported_globals.clone(),
+        metadata.wasm_memory_size,
+        metadata.stable_memory_size,
+        metadata.certified_data.clone(),
+    );
+    let result = test
+        .subnet_message("upload_canister_snapshot_metadata", args.encode())
+        .unwrap();
+    let target_snapshot_id = UploadCanisterSnapshotMetadataResponse::decode(&result.bytes())
+        .unwrap()
+        .get_snapshot_id();
+    // Upload the Wasm module in two parts.
+    let (first_part, second_part) = wasm_module.split_at(wasm_module.len() / 2);
+    for (offset, part) in [(0, first_part), (first_part.len(), second_part)] {
+        upload_canister_snapshot_data(
+            &mut test,
+            target_canister_id,
+            target_snapshot_id,
+            CanisterSnapshotDataOffset::WasmModule {
+                offset: offset as u64,
+            },
+            part.to_vec(),
+        )
+        .unwrap();
+    }
+    upload_canister_snapshot_data(
+        &mut test,
+        target_canister_id,
+        target_snapshot_id,
+        CanisterSnapshotDataOffset::MainMemory { offset: 0 },
+        main_memory,
+    )
+    .unwrap();
+    upload_canister_snapshot_data(
+        &mut test,
+        target_canister_id,
+        target_snapshot_id,
+        CanisterSnapshotDataOffset::WasmChunk,
+        downloaded_chunk,
+    )
+    .unwrap();
+
+    // The uploaded snapshot is accounted for and its page maps will be flushed.
+    let target_snapshot = test
+        .state()
+        .canister_snapshots
+        .get(target_snapshot_id)
+        .unwrap()
+        .clone();
+    assert_eq!(target_snapshot.canister_id(), target_canister_id);
+    assert_eq!(
+        test.canister_state(target_canister_id)
+            .system_state
+            .snapshots_memory_usage,
+        target_snapshot.size()
+    );
+    let unflushed_changes = test.state_mut().canister_snapshots.take_unflushed_changes();
+    assert_eq!(
+        unflushed_changes.last(),
+        Some(&SnapshotOperation::Upload(target_snapshot_id))
+    );
+    assert_eq!(
+        read_canister_snapshot_metadata(&mut test, target_canister_id, target_snapshot_id)
+            .wasm_chunk_store,
+        metadata.wasm_chunk_store
+    );
+
+    // Load the uploaded snapshot onto the target canister.
+    let args = LoadCanisterSnapshotArgs::new(target_canister_id, target_snapshot_id, None);
+    test.subnet_message("load_canister_snapshot", args.encode())
+        .unwrap();
+    let result = test
+        .non_replicated_query(target_canister_id, "read", vec![])
+        .unwrap();
+    assert_eq!(result, WasmResult::Reply(vec![1, 0, 0, 0, 2, 0, 0, 0]));
+    assert_eq!(
+        test.canister_state(target_canister_id)
+            .system_state
+            .wasm_chunk_store
+            .keys()
+            .count(),
+        1
+    );
+}
+
 /// Early warning system / stumbling block forcing the authors of changes adding
 /// or removing canister state fields to think about and/or ask the Execution
 /// team to think about any repercussions to the canister snapshot logic.
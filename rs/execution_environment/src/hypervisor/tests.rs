@@ -3654,6 +3654,7 @@ fn canister_anonymous_query_transform_http_response() {
         status: 200,
         headers: vec![],
         body: vec![0, 1, 2],
+        is_replicated: None,
     };
     let payload = Encode!(&canister_http_response).unwrap();
     let result = test.anonymous_query(canister_id, "http_transform", payload, vec![]);
@@ -192,6 +192,7 @@ impl ExecutionServices {
             config.rate_limiting_of_instructions,
             config.deterministic_time_slicing,
             Arc::clone(&fd_factory),
+            config.message_trace_path.clone(),
         ));
 
         Self {
@@ -51,8 +51,18 @@ use tower::{util::BoxCloneService, Service};
 
 pub(crate) use self::query_scheduler::{QueryScheduler, QuerySchedulerFlag};
 use ic_management_canister_types::{
-    FetchCanisterLogsRequest, FetchCanisterLogsResponse, LogVisibilityV2, Payload, QueryMethod,
+    CanisterLogFilter, CanisterLogRecord, FetchCanisterLogsRequest, FetchCanisterLogsResponse,
+    LogVisibilityV2, Payload, QueryMethod,
 };
+use regex::bytes::{Regex, RegexBuilder};
+
+/// Maximum length in bytes of a `content_regex` or `content_substring` in a
+/// `fetch_canister_logs` filter.
+const MAX_LOG_FILTER_PATTERN_LEN: usize = 1024;
+
+/// Maximum size in bytes of the compiled `content_regex` of a
+/// `fetch_canister_logs` filter.
+const MAX_LOG_FILTER_REGEX_SIZE: usize = 1 << 20;
 
 /// Convert an object into CBOR binary.
 fn into_cbor<R: Serialize>(r: &R) -> Vec<u8> {
@@ -327,18 +337,118 @@ fn fetch_canister_logs(
         )),
     }?;
 
+    let filter = CompiledLogFilter::new(args.filter.unwrap_or_default())?;
+    let max_records = args.max_records.map_or(usize::MAX, |n| n as usize);
+    let start_idx = args.start_idx.unwrap_or(0);
+
+    let canister_log = &canister.system_state.canister_log;
+    let mut canister_log_records = vec![];
+    // Points past the last record that was inspected, i.e., to the next
+    // record the canister logs if all the records were inspected.
+    let mut next_idx = canister_log.next_idx();
+    for record in canister_log
+        .records()
+        .iter()
+        .filter(|record| record.idx >= start_idx)
+    {
+        if canister_log_records.len() >= max_records {
+            next_idx = record.idx;
+            break;
+        }
+        if filter.matches(record) {
+            canister_log_records.push(record.clone());
+        }
+    }
+
     let response = FetchCanisterLogsResponse {
-        canister_log_records: canister
-            .system_state
-            .canister_log
-            .records()
-            .iter()
-            .cloned()
-            .collect(),
+        canister_log_records,
+        next_idx: Some(next_idx),
     };
     Ok(WasmResult::Reply(Encode!(&response).unwrap()))
 }
 
+/// A `CanisterLogFilter` with its content regex compiled.
+struct CompiledLogFilter {
+    filter: CanisterLogFilter,
+    content_regex: Option<Regex>,
+}
+
+impl CompiledLogFilter {
+    fn new(filter: CanisterLogFilter) -> Result<Self, UserError> {
+        let invalid_filter = |message: String| {
+            UserError::new(
+                ErrorCode::InvalidManagementPayload,
+                format!(
+                    "Invalid filter for {}: {}",
+                    QueryMethod::FetchCanisterLogs,
+                    message
+                ),
+            )
+        };
+        for pattern in [&filter.content_substring, &filter.content_regex]
+            .into_iter()
+            .flatten()
+        {
+            if pattern.len() > MAX_LOG_FILTER_PATTERN_LEN {
+                return Err(invalid_filter(format!(
+                    "pattern of {} bytes exceeds the limit of {} bytes.",
+                    pattern.len(),
+                    MAX_LOG_FILTER_PATTERN_LEN
+                )));
+            }
+        }
+        let content_regex = match &filter.content_regex {
+            Some(pattern) => Some(
+                RegexBuilder::new(pattern)
+                    .size_limit(MAX_LOG_FILTER_REGEX_SIZE)
+                    .build()
+                    .map_err(|err| invalid_filter(format!("bad content_regex: {}", err)))?,
+            ),
+            None => None,
+        };
+        Ok(Self {
+            filter,
+            content_regex,
+        })
+    }
+
+    fn matches(&self, record: &CanisterLogRecord) -> bool {
+        let filter = &self.filter;
+        let in_range = |value: u64, start: Option<u64>, end: Option<u64>| {
+            start.map_or(true, |start| value >= start) && end.map_or(true, |end| value <= end)
+        };
+        if !in_range(record.idx, filter.start_idx, filter.end_idx)
+            || !in_range(
+                record.timestamp_nanos,
+                filter.start_timestamp_nanos,
+                filter.end_timestamp_nanos,
+            )
+        {
+            return false;
+        }
+        if filter.traps_only == Some(true) && record.is_trap != Some(true) {
+            return false;
+        }
+        if let Some(substring) = &filter.content_substring {
+            let needle = substring.as_bytes();
+            if !needle.is_empty()
+                && !record
+                    .content
+                    .windows(needle.len())
+                    .any(|window| window == needle)
+            {
+                return false;
+            }
+        }
+        if let Some(regex) = &self.content_regex {
+            if !regex.is_match(&record.content) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
 impl HttpQueryHandler {
     pub(crate) fn new_service(
         internal: Arc<InternalHttpQueryHandler>,
@@ -35,7 +35,7 @@ use ic_types::{
     consensus::idkg::PreSigId,
     crypto::canister_threshold_sig::MasterPublicKey,
     ingress::{IngressState, IngressStatus},
-    messages::{CanisterMessage, Ingress, MessageId, Response, NO_DEADLINE},
+    messages::{CanisterMessage, CanisterMessageOrTask, Ingress, MessageId, Response, NO_DEADLINE},
     AccumulatedPriority, CanisterId, ComputeAllocation, Cycles, ExecutionRound, LongExecutionMode,
     MemoryAllocation, NumBytes, NumInstructions, NumSlices, Randomness, SubnetId, Time,
     MAX_WASM_MEMORY_IN_BYTES,
@@ -46,6 +46,7 @@ use std::{
     cell::RefCell,
     cmp::Reverse,
     collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
     str::FromStr,
     sync::Arc,
 };
@@ -57,6 +58,8 @@ mod round_schedule;
 use crate::util::debug_assert_or_critical_error;
 pub use round_schedule::RoundSchedule;
 use round_schedule::*;
+mod message_trace;
+use message_trace::{ExecutedSlice, MessageTracer};
 mod threshold_signatures;
 use threshold_signatures::*;
 
@@ -144,6 +147,7 @@ pub(crate) struct SchedulerImpl {
     rate_limiting_of_instructions: FlagStatus,
     deterministic_time_slicing: FlagStatus,
     fd_factory: Arc<dyn PageAllocatorFileDescriptor>,
+    message_tracer: Option<MessageTracer>,
 }
 
 impl SchedulerImpl {
@@ -401,8 +405,18 @@ impl SchedulerImpl {
         rate_limiting_of_instructions: FlagStatus,
         deterministic_time_slicing: FlagStatus,
         fd_factory: Arc<dyn PageAllocatorFileDescriptor>,
+        message_trace_path: Option<PathBuf>,
     ) -> Self {
         let scheduler_cores = config.scheduler_cores as u32;
+        let message_tracer = message_trace_path.map(|path| {
+            MessageTracer::new(&path).unwrap_or_else(|err| {
+                panic!(
+                    "Failed to open the message trace file {}: {}",
+                    path.display(),
+                    err
+                )
+            })
+        });
         Self {
             config,
             thread_pool: RefCell::new(scoped_threadpool::Pool::new(scheduler_cores)),
@@ -416,6 +430,7 @@ impl SchedulerImpl {
             rate_limiting_of_instructions,
             deterministic_time_slicing,
             fd_factory,
+            message_tracer,
         }
     }
 
@@ -473,10 +488,12 @@ impl SchedulerImpl {
     }
 
     /// Drains the subnet queues, executing all messages not blocked by long executions.
+    #[allow(clippy::too_many_arguments)]
     fn drain_subnet_queues(
         &self,
         mut state: ReplicatedState,
         csprng: &mut Csprng,
+        current_round: ExecutionRound,
         round_limits: &mut RoundLimits,
         measurement_scope: &MeasurementScope,
         registry_settings: &RegistryExecutionSettings,
@@ -514,6 +531,7 @@ impl SchedulerImpl {
                     msg,
                     state,
                     csprng,
+                    current_round,
                     round_limits,
                     registry_settings,
                     measurement_scope,
@@ -540,11 +558,13 @@ impl SchedulerImpl {
     }
 
     /// Invokes `ExecutionEnvironment` to execute a subnet message.
+    #[allow(clippy::too_many_arguments)]
     fn execute_subnet_message(
         &self,
         msg: CanisterMessage,
         state: ReplicatedState,
         csprng: &mut Csprng,
+        current_round: ExecutionRound,
         round_limits: &mut RoundLimits,
         registry_settings: &RegistryExecutionSettings,
         measurement_scope: &MeasurementScope,
@@ -556,6 +576,8 @@ impl SchedulerImpl {
             &msg,
         );
 
+        let traced_msg = self.message_tracer.as_ref().map(|_| msg.clone());
+        let heap_delta_before = state.metadata.heap_delta_estimate;
         let instructions_before = round_limits.instructions;
         let (new_state, message_instructions) = self.exec_env.execute_subnet_message(
             msg,
@@ -570,6 +592,34 @@ impl SchedulerImpl {
             as_num_instructions(instructions_before - round_limits.instructions);
         let messages = NumMessages::from(message_instructions.map(|_| 1).unwrap_or(0));
         measurement_scope.add(round_instructions_executed, NumSlices::from(1), messages);
+
+        // Subnet messages are traced as executed by the management canister once they finish.
+        // Their outcome is delivered to the caller asynchronously and is not traced.
+        if let (Some(message_tracer), Some(msg), Some(instructions_used)) =
+            (&self.message_tracer, traced_msg, message_instructions)
+        {
+            let slice = ExecutedSlice {
+                canister_id: CanisterId::ic_00(),
+                input: CanisterMessageOrTask::Message(msg),
+                instructions_used: Some(instructions_used),
+                heap_delta: NumBytes::from(
+                    new_state
+                        .metadata
+                        .heap_delta_estimate
+                        .get()
+                        .saturating_sub(heap_delta_before.get()),
+                ),
+                outcome: None,
+                consumed_cycles_before: NominalCycles::default(),
+                consumed_cycles_after: NominalCycles::default(),
+            };
+            if let Err(err) = message_tracer.trace(current_round, vec![slice]) {
+                warn!(
+                    self.log,
+                    "Failed to write the message trace of round {}: {}", current_round, err
+                );
+            }
+        }
         (new_state, message_instructions)
     }
 
@@ -676,6 +726,7 @@ impl SchedulerImpl {
                     state = self.drain_subnet_queues(
                         state,
                         csprng,
+                        current_round,
                         &mut subnet_round_limits,
                         &subnet_measurement_scope,
                         registry_settings,
@@ -920,6 +971,7 @@ impl SchedulerImpl {
                 let logger = new_logger!(self.log; messaging.round => round_id.get());
                 let rate_limiting_of_heap_delta = self.rate_limiting_of_heap_delta;
                 let deterministic_time_slicing = self.deterministic_time_slicing;
+                let trace_messages = self.message_tracer.is_some();
                 let round_limits = RoundLimits {
                     instructions: round_limits.instructions,
                     subnet_available_memory: round_limits_per_thread.subnet_available_memory,
@@ -941,6 +993,7 @@ impl SchedulerImpl {
                         round_limits,
                         subnet_size,
                         is_first_iteration,
+                        trace_messages,
                     );
                 });
             }
@@ -954,10 +1007,12 @@ impl SchedulerImpl {
         let mut total_instructions_executed = NumInstructions::from(0);
         let mut max_instructions_executed_per_thread = NumInstructions::from(0);
         let mut heap_delta = NumBytes::from(0);
+        let mut executed_slices = Vec::new();
         for mut result in results_by_thread.into_iter() {
             canisters.append(&mut result.canisters);
             executed_canister_ids.extend(result.executed_canister_ids);
             ingress_results.append(&mut result.ingress_results);
+            executed_slices.append(&mut result.executed_slices);
             let instructions_executed = as_num_instructions(
                 round_limits_per_thread.instructions - result.round_limits.instructions,
             );
@@ -987,6 +1042,15 @@ impl SchedulerImpl {
         self.metrics
             .instructions_consumed_per_round
             .observe(total_instructions_executed.get() as f64);
+
+        if let Some(message_tracer) = &self.message_tracer {
+            if let Err(err) = message_tracer.trace(round_id, executed_slices) {
+                warn!(
+                    self.log,
+                    "Failed to write the message trace of round {}: {}", round_id, err
+                );
+            }
+        }
         (
             canisters,
             executed_canister_ids,
@@ -1539,6 +1603,7 @@ impl Scheduler for SchedulerImpl {
                     ),
                     state,
                     &mut csprng,
+                    current_round,
                     &mut subnet_round_limits,
                     registry_settings,
                     &measurement_scope,
@@ -1597,6 +1662,7 @@ impl Scheduler for SchedulerImpl {
                     CanisterMessage::Request(raw_rand_context.request.into()),
                     state,
                     &mut csprng,
+                    current_round,
                     &mut subnet_round_limits,
                     registry_settings,
                     &measurement_scope,
@@ -1892,6 +1958,7 @@ struct ExecutionThreadResult {
     messages_executed: NumMessages,
     heap_delta: NumBytes,
     round_limits: RoundLimits,
+    executed_slices: Vec<ExecutedSlice>,
 }
 
 /// Executes the given canisters one by one. For each canister it
@@ -1915,6 +1982,7 @@ fn execute_canisters_on_thread(
     mut round_limits: RoundLimits,
     subnet_size: usize,
     is_first_iteration: bool,
+    trace_messages: bool,
 ) -> ExecutionThreadResult {
     // Since this function runs on a helper thread, we cannot use a nested scope
     // here. Instead, we propagate metrics to the outer scope manually via
@@ -1928,6 +1996,7 @@ fn execute_canisters_on_thread(
     let mut total_slices_executed = NumSlices::from(0);
     let mut total_messages_executed = NumMessages::from(0);
     let mut total_heap_delta = NumBytes::from(0);
+    let mut executed_slices = vec![];
 
     let instruction_limits = InstructionLimits::new(
         deterministic_time_slicing,
@@ -1972,12 +2041,15 @@ fn execute_canisters_on_thread(
 
             let instructions_before = round_limits.instructions;
             let canister_had_paused_execution = canister.has_paused_execution();
+            let consumed_cycles_before = canister.system_state.canister_metrics.consumed_cycles;
             let ExecuteCanisterResult {
                 canister: new_canister,
                 instructions_used,
                 heap_delta,
                 ingress_status,
                 description,
+                input,
+                outcome,
             } = execute_canister(
                 exec_env,
                 canister,
@@ -1987,12 +2059,27 @@ fn execute_canisters_on_thread(
                 time,
                 &mut round_limits,
                 subnet_size,
+                trace_messages,
             );
             if instructions_used.map_or(false, |instructions| instructions.get() > 0) {
                 // We only want to count the canister as executed if it used instructions.
                 executed_canister_ids.insert(new_canister.canister_id());
             }
             ingress_results.extend(ingress_status);
+            if let Some(input) = input {
+                executed_slices.push(ExecutedSlice {
+                    canister_id: new_canister.canister_id(),
+                    input,
+                    instructions_used,
+                    heap_delta,
+                    outcome,
+                    consumed_cycles_before,
+                    consumed_cycles_after: new_canister
+                        .system_state
+                        .canister_metrics
+                        .consumed_cycles,
+                });
+            }
             let round_instructions_executed =
                 as_num_instructions(instructions_before - round_limits.instructions);
             let messages = NumMessages::from(
@@ -2076,6 +2163,7 @@ fn execute_canisters_on_thread(
         messages_executed: total_messages_executed,
         heap_delta: total_heap_delta,
         round_limits,
+        executed_slices,
     }
 }
 
@@ -2387,7 +2475,11 @@ fn can_execute_subnet_msg(
         | Ic00Method::ClearChunkStore
         | Ic00Method::TakeCanisterSnapshot
         | Ic00Method::ListCanisterSnapshots
-        | Ic00Method::DeleteCanisterSnapshot => true,
+        | Ic00Method::DeleteCanisterSnapshot
+        | Ic00Method::ReadCanisterSnapshotMetadata
+        | Ic00Method::ReadCanisterSnapshotData
+        | Ic00Method::UploadCanisterSnapshotMetadata
+        | Ic00Method::UploadCanisterSnapshotData => true,
     }
 }
 
@@ -2451,7 +2543,11 @@ fn get_instructions_limits_for_subnet_message(
             | TakeCanisterSnapshot
             | LoadCanisterSnapshot
             | ListCanisterSnapshots
-            | DeleteCanisterSnapshot => default_limits,
+            | DeleteCanisterSnapshot
+            | ReadCanisterSnapshotMetadata
+            | ReadCanisterSnapshotData
+            | UploadCanisterSnapshotMetadata
+            | UploadCanisterSnapshotData => default_limits,
             InstallCode | InstallChunkedCode => InstructionLimits::new(
                 dts,
                 config.max_instructions_per_install_code,
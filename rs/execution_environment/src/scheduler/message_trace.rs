@@ -0,0 +1,287 @@
+//! Export of a trace of the messages executed on canisters and of the subnet
+//! messages executed by the management canister, one JSON record per line, for
+//! the offline analysis of replayed rounds.
+
+use crate::execution_environment::MessageOutcome;
+use ic_types::{
+    messages::{CanisterMessage, CanisterMessageOrTask},
+    methods::SystemMethod,
+    nominal_cycles::NominalCycles,
+    CanisterId, ExecutionRound, NumBytes, NumInstructions,
+};
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    fs::{File, OpenOptions},
+    io::{self, BufWriter, Write},
+    path::Path,
+    sync::Mutex,
+};
+
+/// A slice of a message execution, as observed by an execution thread or, for
+/// subnet messages, by the scheduler.
+pub(super) struct ExecutedSlice {
+    pub canister_id: CanisterId,
+    pub input: CanisterMessageOrTask,
+    /// The number of instructions used by the message, if its execution
+    /// finished in this slice.
+    pub instructions_used: Option<NumInstructions>,
+    pub heap_delta: NumBytes,
+    pub outcome: Option<MessageOutcome>,
+    /// The cycles consumed by the canister before and after the slice.
+    pub consumed_cycles_before: NominalCycles,
+    pub consumed_cycles_after: NominalCycles,
+}
+
+/// A single line of the message trace.
+#[derive(Clone, Eq, PartialEq, Debug, Serialize)]
+struct MessageTraceRecord {
+    height: u64,
+    /// The executing canister, i.e. the management canister for subnet
+    /// messages.
+    canister_id: String,
+    /// One of `ingress`, `request`, `response` or `task`.
+    kind: &'static str,
+    /// The called method. Not set for responses.
+    method: Option<String>,
+    /// The sender of an ingress message or request, or the respondent of a
+    /// response. Not set for tasks.
+    caller: Option<String>,
+    /// The message ID of an ingress message.
+    message_id: Option<String>,
+    instructions_used: u64,
+    cycles_charged: u128,
+    outcome: Option<MessageOutcome>,
+    heap_delta: u64,
+}
+
+impl MessageTraceRecord {
+    fn new(
+        height: ExecutionRound,
+        slice: ExecutedSlice,
+        instructions_used: NumInstructions,
+        cycles_charged: NominalCycles,
+    ) -> Self {
+        let (kind, method, caller, message_id) = match &slice.input {
+            CanisterMessageOrTask::Message(CanisterMessage::Ingress(ingress)) => (
+                "ingress",
+                Some(ingress.method_name.clone()),
+                Some(ingress.source.to_string()),
+                Some(ingress.message_id.to_string()),
+            ),
+            CanisterMessageOrTask::Message(CanisterMessage::Request(request)) => (
+                "request",
+                Some(request.method_name.clone()),
+                Some(request.sender.to_string()),
+                None,
+            ),
+            CanisterMessageOrTask::Message(CanisterMessage::Response(response)) => (
+                "response",
+                None,
+                Some(response.respondent.to_string()),
+                None,
+            ),
+            CanisterMessageOrTask::Task(task) => (
+                "task",
+                Some(SystemMethod::from(task.clone()).to_string()),
+                None,
+                None,
+            ),
+        };
+        Self {
+            height: height.get(),
+            canister_id: slice.canister_id.to_string(),
+            kind,
+            method,
+            caller,
+            message_id,
+            instructions_used: instructions_used.get(),
+            cycles_charged: cycles_charged.get(),
+            outcome: slice.outcome,
+            heap_delta: slice.heap_delta.get(),
+        }
+    }
+}
+
+/// Appends a record for every executed message to a file.
+pub(super) struct MessageTracer {
+    writer: Mutex<BufWriter<File>>,
+    /// The cycles consumed by canisters with a paused execution before the
+    /// first slice of the execution.
+    paused_executions: Mutex<BTreeMap<CanisterId, NominalCycles>>,
+}
+
+impl MessageTracer {
+    pub(super) fn new(path: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            writer: Mutex::new(BufWriter::new(file)),
+            paused_executions: Mutex::new(BTreeMap::new()),
+        })
+    }
+
+    /// Writes a record for every message whose execution finished in one of
+    /// the given slices, in order. The cycles charged for a message include
+    /// the slices of previous rounds if the execution was paused.
+    pub(super) fn trace(
+        &self,
+        height: ExecutionRound,
+        slices: Vec<ExecutedSlice>,
+    ) -> io::Result<()> {
+        let records = self.records(height, slices);
+        let mut writer = self.writer.lock().unwrap();
+        for record in &records {
+            serde_json::to_writer(&mut *writer, record)?;
+            writeln!(writer)?;
+        }
+        writer.flush()
+    }
+
+    fn records(
+        &self,
+        height: ExecutionRound,
+        slices: Vec<ExecutedSlice>,
+    ) -> Vec<MessageTraceRecord> {
+        let mut paused_executions = self.paused_executions.lock().unwrap();
+        let mut records = Vec::new();
+        for slice in slices {
+            let consumed_cycles_before = paused_executions
+                .remove(&slice.canister_id)
+                .unwrap_or(slice.consumed_cycles_before);
+            match slice.instructions_used {
+                None => {
+                    paused_executions.insert(slice.canister_id, consumed_cycles_before);
+                }
+                Some(instructions_used) => {
+                    let cycles_charged = slice.consumed_cycles_after - consumed_cycles_before;
+                    records.push(MessageTraceRecord::new(
+                        height,
+                        slice,
+                        instructions_used,
+                        cycles_charged,
+                    ));
+                }
+            }
+        }
+        records
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ic_test_utilities_types::messages::{IngressBuilder, RequestBuilder};
+    use ic_types::messages::CanisterTask;
+    use ic_types_test_utils::ids::{canister_test_id, user_test_id};
+    use std::sync::Arc;
+
+    fn slice(
+        input: CanisterMessageOrTask,
+        instructions_used: Option<u64>,
+        consumed_cycles: (u128, u128),
+        outcome: Option<MessageOutcome>,
+    ) -> ExecutedSlice {
+        ExecutedSlice {
+            canister_id: canister_test_id(1),
+            input,
+            instructions_used: instructions_used.map(NumInstructions::from),
+            heap_delta: NumBytes::from(4096),
+            outcome,
+            consumed_cycles_before: NominalCycles::from(consumed_cycles.0),
+            consumed_cycles_after: NominalCycles::from(consumed_cycles.1),
+        }
+    }
+
+    #[test]
+    fn cycles_of_paused_executions_are_accumulated() {
+        let tmpdir = tempfile::Builder::new().tempdir().unwrap();
+        let tracer = MessageTracer::new(&tmpdir.path().join("trace.jsonl")).unwrap();
+        let request = CanisterMessageOrTask::Message(CanisterMessage::Request(Arc::new(
+            RequestBuilder::new()
+                .sender(canister_test_id(2))
+                .method_name("update")
+                .build(),
+        )));
+
+        let records = tracer.records(
+            ExecutionRound::from(10),
+            vec![slice(request.clone(), None, (100, 1_000), None)],
+        );
+        assert!(records.is_empty());
+
+        let records = tracer.records(
+            ExecutionRound::from(11),
+            vec![slice(
+                request,
+                Some(5_000),
+                (1_000, 700),
+                Some(MessageOutcome::Reply),
+            )],
+        );
+        assert_eq!(
+            records,
+            vec![MessageTraceRecord {
+                height: 11,
+                canister_id: canister_test_id(1).to_string(),
+                kind: "request",
+                method: Some("update".to_string()),
+                caller: Some(canister_test_id(2).to_string()),
+                message_id: None,
+                instructions_used: 5_000,
+                cycles_charged: 600,
+                outcome: Some(MessageOutcome::Reply),
+                heap_delta: 4096,
+            }]
+        );
+    }
+
+    #[test]
+    fn trace_appends_json_lines() {
+        let tmpdir = tempfile::Builder::new().tempdir().unwrap();
+        let path = tmpdir.path().join("trace.jsonl");
+        let tracer = MessageTracer::new(&path).unwrap();
+        let ingress = IngressBuilder::new()
+            .source(user_test_id(3))
+            .method_name("transfer")
+            .build();
+        let message_id = ingress.message_id.to_string();
+
+        tracer
+            .trace(
+                ExecutionRound::from(7),
+                vec![
+                    slice(
+                        CanisterMessageOrTask::Message(CanisterMessage::Ingress(Arc::new(ingress))),
+                        Some(1_000),
+                        (0, 10),
+                        Some(MessageOutcome::Reject("out of funds".to_string())),
+                    ),
+                    slice(
+                        CanisterMessageOrTask::Task(CanisterTask::Heartbeat),
+                        Some(0),
+                        (10, 10),
+                        None,
+                    ),
+                ],
+            )
+            .unwrap();
+
+        let lines: Vec<serde_json::Value> = std::fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["height"], 7);
+        assert_eq!(lines[0]["kind"], "ingress");
+        assert_eq!(lines[0]["method"], "transfer");
+        assert_eq!(lines[0]["caller"], user_test_id(3).to_string());
+        assert_eq!(lines[0]["message_id"], message_id);
+        assert_eq!(lines[0]["cycles_charged"], 10);
+        assert_eq!(lines[0]["outcome"]["reject"], "out of funds");
+        assert_eq!(lines[1]["kind"], "task");
+        assert_eq!(lines[1]["method"], "canister_heartbeat");
+        assert_eq!(lines[1]["caller"], serde_json::Value::Null);
+        assert_eq!(lines[1]["outcome"], serde_json::Value::Null);
+    }
+}
@@ -659,6 +659,7 @@ pub(crate) struct SchedulerTestBuilder {
     metrics_registry: MetricsRegistry,
     round_summary: Option<ExecutionRoundSummary>,
     canister_snapshot_flag: bool,
+    message_trace_path: Option<PathBuf>,
 }
 
 impl Default for SchedulerTestBuilder {
@@ -684,6 +685,7 @@ impl Default for SchedulerTestBuilder {
             metrics_registry: MetricsRegistry::new(),
             round_summary: None,
             canister_snapshot_flag: true,
+            message_trace_path: None,
         }
     }
 }
@@ -760,6 +762,13 @@ impl SchedulerTestBuilder {
         }
     }
 
+    pub fn with_message_trace(self, message_trace_path: PathBuf) -> Self {
+        Self {
+            message_trace_path: Some(message_trace_path),
+            ..self
+        }
+    }
+
     pub fn build(self) -> SchedulerTest {
         let first_xnet_canister = u64::MAX / 2;
         let routing_table = Arc::new(
@@ -913,6 +922,7 @@ impl SchedulerTestBuilder {
             rate_limiting_of_instructions,
             deterministic_time_slicing,
             Arc::new(TestPageAllocatorFileDescriptorImpl::new()),
+            self.message_trace_path,
         );
         SchedulerTest {
             state: Some(state),
@@ -1167,6 +1177,7 @@ impl TestWasmExecutorCore {
                 instance_stats: InstanceStats::default(),
                 system_api_call_counters: SystemApiCallCounters::default(),
                 canister_log: Default::default(),
+                instruction_profile: None,
             };
             self.schedule
                 .push((self.round, canister_id, instructions_to_execute));
@@ -1209,6 +1220,7 @@ impl TestWasmExecutorCore {
             instance_stats,
             system_api_call_counters: SystemApiCallCounters::default(),
             canister_log: Default::default(),
+            instruction_profile: None,
         };
         self.schedule
             .push((self.round, canister_id, instructions_to_execute));
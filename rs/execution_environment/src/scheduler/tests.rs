@@ -2812,6 +2812,8 @@ fn canister_is_stopped_if_timeout_occurs_and_ready_to_stop() {
             body: None,
             transform: None,
             max_response_bytes: None,
+            response_range: None,
+            is_replicated: None,
         })
         .unwrap();
 
@@ -2857,6 +2859,7 @@ fn canister_is_stopped_if_timeout_occurs_and_ready_to_stop() {
             status: 200,
             headers: vec![],
             body: vec![],
+            is_replicated: None,
         };
 
         let payload = PayloadBuilder::new().http_response(CallbackId::from(0), &response);
@@ -3837,6 +3840,8 @@ fn consumed_cycles_http_outcalls_are_added_to_consumed_cycles_total() {
             }),
             context: transform_context,
         }),
+        response_range: None,
+        is_replicated: None,
     };
 
     // Create request to `HttpRequest` method.
@@ -5971,3 +5976,36 @@ fn inner_round_long_execution_is_a_full_execution() {
     // The accumulated priority invariant should be respected.
     assert_eq!(total_accumulated_priority - total_priority_credit, 0);
 }
+
+#[test]
+fn subnet_messages_are_traced() {
+    let tmpdir = tempfile::Builder::new().tempdir().unwrap();
+    let path = tmpdir.path().join("trace.jsonl");
+    let mut test = SchedulerTestBuilder::new()
+        .with_message_trace(path.clone())
+        .build();
+    let canister_id = test.create_canister();
+
+    test.inject_call_to_ic00(
+        Method::CanisterStatus,
+        CanisterIdRecord::from(canister_id).encode(),
+        Cycles::zero(),
+        canister_id,
+        InputQueueType::LocalSubnet,
+    );
+    test.execute_round(ExecutionRoundType::OrdinaryRound);
+    assert_eq!(test.state().subnet_queues().input_queues_message_count(), 0);
+
+    let lines: Vec<serde_json::Value> = std::fs::read_to_string(&path)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 1);
+    assert_eq!(lines[0]["canister_id"], CanisterId::ic_00().to_string());
+    assert_eq!(lines[0]["kind"], "request");
+    assert_eq!(lines[0]["method"], "canister_status");
+    assert_eq!(lines[0]["caller"], canister_id.to_string());
+    assert_eq!(lines[0]["cycles_charged"], 0);
+    assert_eq!(lines[0]["outcome"], serde_json::Value::Null);
+}
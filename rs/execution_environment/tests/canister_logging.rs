@@ -1,8 +1,8 @@
 use ic_config::execution_environment::Config as ExecutionConfig;
 use ic_config::subnet_config::SubnetConfig;
 use ic_management_canister_types::{
-    self as ic00, BoundedAllowedViewers, CanisterIdRecord, CanisterInstallMode, CanisterLogRecord,
-    CanisterSettingsArgs, CanisterSettingsArgsBuilder, DataSize, EmptyBlob,
+    self as ic00, BoundedAllowedViewers, CanisterIdRecord, CanisterInstallMode, CanisterLogFilter,
+    CanisterLogRecord, CanisterSettingsArgs, CanisterSettingsArgsBuilder, DataSize, EmptyBlob,
     FetchCanisterLogsRequest, FetchCanisterLogsResponse, LogVisibilityV2, Payload,
 };
 use ic_registry_subnet_type::SubnetType;
@@ -34,15 +34,25 @@ fn system_time_to_nanos(t: SystemTime) -> u64 {
 }
 
 fn canister_log_response(data: Vec<(u64, u64, Vec<u8>)>) -> FetchCanisterLogsResponse {
+    let next_idx = data.last().map_or(0, |(idx, _, _)| idx + 1);
+    canister_log_response_with_next_idx(data, Some(next_idx))
+}
+
+fn canister_log_response_with_next_idx(
+    data: Vec<(u64, u64, Vec<u8>)>,
+    next_idx: Option<u64>,
+) -> FetchCanisterLogsResponse {
     FetchCanisterLogsResponse {
         canister_log_records: data
             .into_iter()
             .map(|(idx, timestamp_nanos, content)| CanisterLogRecord {
                 idx,
                 timestamp_nanos,
+                is_trap: Some(content.starts_with(b"[TRAP]:")),
                 content,
             })
             .collect(),
+        next_idx,
     }
 }
 
@@ -107,6 +117,19 @@ fn fetch_canister_logs(
     )
 }
 
+fn fetch_canister_logs_with_request(
+    env: &StateMachine,
+    sender: PrincipalId,
+    request: FetchCanisterLogsRequest,
+) -> Result<WasmResult, UserError> {
+    env.query_as(
+        sender,
+        CanisterId::ic_00(),
+        "fetch_canister_logs",
+        request.encode(),
+    )
+}
+
 #[test]
 fn test_fetch_canister_logs_via_submit_ingress() {
     let (env, canister_id) = setup_and_install_wasm(
@@ -174,6 +197,7 @@ fn test_fetch_canister_logs_via_query_call() {
         Ok(WasmResult::Reply(
             FetchCanisterLogsResponse {
                 canister_log_records: vec![],
+                next_idx: Some(0),
             }
             .encode(),
         ))
@@ -258,6 +282,7 @@ fn test_log_visibility_of_fetch_canister_logs() {
     let ok = Ok(WasmResult::Reply(
         FetchCanisterLogsResponse {
             canister_log_records: vec![],
+            next_idx: Some(0),
         }
         .encode(),
     ));
@@ -422,6 +447,191 @@ fn test_canister_log_record_index_increment_for_different_calls() {
     );
 }
 
+#[test]
+fn test_fetch_canister_logs_with_filter() {
+    let (env, canister_id, controller) = setup_with_controller(
+        wat_canister()
+            .update(
+                "test1",
+                wat_fn().debug_print(b"alpha 0").debug_print(b"beta 1"),
+            )
+            .update("test2", wat_fn().debug_print(b"alpha 2").trap())
+            .build_wasm(),
+    );
+    let timestamp_01 = system_time_to_nanos(env.time_of_next_round());
+    let _ = env.execute_ingress(canister_id, "test1", vec![]);
+    env.advance_time(Duration::from_nanos(123_456));
+    let timestamp_23 = system_time_to_nanos(env.time_of_next_round());
+    let _ = env.execute_ingress(canister_id, "test2", vec![]);
+
+    let test_cases = vec![
+        (
+            CanisterLogFilter {
+                start_idx: Some(1),
+                end_idx: Some(2),
+                ..Default::default()
+            },
+            vec![
+                (1, timestamp_01, b"beta 1".to_vec()),
+                (2, timestamp_23, b"alpha 2".to_vec()),
+            ],
+        ),
+        (
+            CanisterLogFilter {
+                start_timestamp_nanos: Some(timestamp_23),
+                ..Default::default()
+            },
+            vec![
+                (2, timestamp_23, b"alpha 2".to_vec()),
+                (3, timestamp_23, b"[TRAP]: (no message)".to_vec()),
+            ],
+        ),
+        (
+            CanisterLogFilter {
+                end_timestamp_nanos: Some(timestamp_01),
+                ..Default::default()
+            },
+            vec![
+                (0, timestamp_01, b"alpha 0".to_vec()),
+                (1, timestamp_01, b"beta 1".to_vec()),
+            ],
+        ),
+        (
+            CanisterLogFilter {
+                content_substring: Some("alpha".to_string()),
+                ..Default::default()
+            },
+            vec![
+                (0, timestamp_01, b"alpha 0".to_vec()),
+                (2, timestamp_23, b"alpha 2".to_vec()),
+            ],
+        ),
+        (
+            CanisterLogFilter {
+                content_regex: Some("^(beta|alpha) [12]$".to_string()),
+                ..Default::default()
+            },
+            vec![
+                (1, timestamp_01, b"beta 1".to_vec()),
+                (2, timestamp_23, b"alpha 2".to_vec()),
+            ],
+        ),
+        (
+            CanisterLogFilter {
+                traps_only: Some(true),
+                ..Default::default()
+            },
+            vec![(3, timestamp_23, b"[TRAP]: (no message)".to_vec())],
+        ),
+    ];
+    for (filter, expected) in test_cases {
+        let result = fetch_canister_logs_with_request(
+            &env,
+            controller,
+            FetchCanisterLogsRequest::new(canister_id).with_filter(filter.clone()),
+        );
+        assert_eq!(
+            FetchCanisterLogsResponse::decode(&get_reply(result)).unwrap(),
+            // All records are inspected, so the cursor points past the last one.
+            canister_log_response_with_next_idx(expected, Some(4)),
+            "filter: {:?}",
+            filter
+        );
+    }
+}
+
+#[test]
+fn test_fetch_canister_logs_with_invalid_regex_filter() {
+    let (env, canister_id, controller) = setup_with_controller(wat_canister().build_wasm());
+    let result = fetch_canister_logs_with_request(
+        &env,
+        controller,
+        FetchCanisterLogsRequest::new(canister_id).with_filter(CanisterLogFilter {
+            content_regex: Some("(unclosed".to_string()),
+            ..Default::default()
+        }),
+    );
+    let error = result.unwrap_err();
+    assert_eq!(error.code(), ErrorCode::InvalidManagementPayload);
+    assert!(
+        error.description().contains("bad content_regex"),
+        "Unexpected error: {}",
+        error.description()
+    );
+}
+
+#[test]
+fn test_fetch_canister_logs_with_cursor() {
+    let (env, canister_id, controller) = setup_with_controller(
+        wat_canister()
+            .update(
+                "test",
+                wat_fn()
+                    .debug_print(b"message 0")
+                    .debug_print(b"message 1")
+                    .debug_print(b"message 2"),
+            )
+            .build_wasm(),
+    );
+    let timestamp = system_time_to_nanos(env.time_of_next_round());
+    let _ = env.execute_ingress(canister_id, "test", vec![]);
+
+    // Page through the log two records at a time.
+    let result = fetch_canister_logs_with_request(
+        &env,
+        controller,
+        FetchCanisterLogsRequest::new(canister_id).with_max_records(2),
+    );
+    let page = FetchCanisterLogsResponse::decode(&get_reply(result)).unwrap();
+    assert_eq!(
+        page,
+        canister_log_response(vec![
+            (0, timestamp, b"message 0".to_vec()),
+            (1, timestamp, b"message 1".to_vec()),
+        ])
+    );
+    let result = fetch_canister_logs_with_request(
+        &env,
+        controller,
+        FetchCanisterLogsRequest::new(canister_id)
+            .with_max_records(2)
+            .with_start_idx(page.next_idx.unwrap()),
+    );
+    let page = FetchCanisterLogsResponse::decode(&get_reply(result)).unwrap();
+    assert_eq!(
+        page,
+        canister_log_response(vec![(2, timestamp, b"message 2".to_vec())])
+    );
+
+    // Nothing new yet: the cursor is kept.
+    let result = fetch_canister_logs_with_request(
+        &env,
+        controller,
+        FetchCanisterLogsRequest::new(canister_id).with_start_idx(page.next_idx.unwrap()),
+    );
+    assert_eq!(
+        FetchCanisterLogsResponse::decode(&get_reply(result)).unwrap(),
+        canister_log_response_with_next_idx(vec![], Some(3))
+    );
+
+    // New records are picked up from the cursor.
+    let timestamp = system_time_to_nanos(env.time_of_next_round());
+    let _ = env.execute_ingress(canister_id, "test", vec![]);
+    let result = fetch_canister_logs_with_request(
+        &env,
+        controller,
+        FetchCanisterLogsRequest::new(canister_id).with_start_idx(3),
+    );
+    assert_eq!(
+        FetchCanisterLogsResponse::decode(&get_reply(result)).unwrap(),
+        canister_log_response(vec![
+            (3, timestamp, b"message 0".to_vec()),
+            (4, timestamp, b"message 1".to_vec()),
+            (5, timestamp, b"message 2".to_vec()),
+        ])
+    );
+}
+
 #[test]
 fn test_canister_log_record_index_increment_after_node_restart() {
     // Test that the index of the log records is incremented for each log message
@@ -710,11 +920,11 @@ fn test_deleting_logs_on_uninstall() {
     )
     .unwrap();
 
-    // Expect logs to be deleted.
+    // Expect logs to be deleted, while the cursor keeps pointing past them.
     let result = fetch_canister_logs(&env, controller, canister_id);
     assert_eq!(
         FetchCanisterLogsResponse::decode(&get_reply(result)).unwrap(),
-        canister_log_response(vec![])
+        canister_log_response_with_next_idx(vec![], Some(3))
     );
 }
 
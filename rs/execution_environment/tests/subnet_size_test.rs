@@ -511,6 +511,8 @@ fn simulate_http_request_cost(subnet_type: SubnetType, subnet_size: usize) -> Cy
                         }),
                         context: vec![],
                     }),
+                    response_range: None,
+                    is_replicated: None,
                 })
                 .unwrap(),
             ),
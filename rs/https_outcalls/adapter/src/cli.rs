@@ -295,7 +295,10 @@ pub mod test {
                 "level": "info",
                 "format": "json"
             },
-            "socks_proxy": "socks5://notaproxy.com:1080" 
+            "socks_proxy": "socks5://notaproxy.com:1080",
+            "max_ranged_response_size_bytes": 1048576,
+            "response_cache_size_bytes": 4194304,
+            "response_cache_ttl_secs": 60
         }       
         "#;
         let mut tmpfile = NamedTempFile::new().expect("Failed to create tmp file");
@@ -318,6 +321,9 @@ pub mod test {
                 ..Default::default()
             },
             socks_proxy: "socks5://notaproxy.com:1080".to_string(),
+            max_ranged_response_size_bytes: 1_048_576,
+            response_cache_size_bytes: 4_194_304,
+            response_cache_ttl_secs: 60,
         };
         assert_eq!(config, expected_config);
     }
@@ -1,9 +1,13 @@
 use ic_config::logger::Config as LoggerConfig;
+use ic_limits::MAX_CANISTER_HTTP_RANGED_DOWNLOAD_BYTES;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
 const DEFAULT_HTTP_CONNECT_TIMEOUT_SECS: u64 = 2;
 const DEFAULT_HTTP_REQUEST_TIMEOUT_SECS: u64 = 30;
+const DEFAULT_MAX_RANGED_RESPONSE_SIZE_BYTES: u64 = MAX_CANISTER_HTTP_RANGED_DOWNLOAD_BYTES;
+const DEFAULT_RESPONSE_CACHE_SIZE_BYTES: u64 = 256 * 1024 * 1024;
+const DEFAULT_RESPONSE_CACHE_TTL_SECS: u64 = 300;
 
 #[derive(Clone, Eq, PartialEq, Debug, Default, Deserialize, Serialize)]
 /// The source of the unix domain socket to be used for inter-process
@@ -30,6 +34,16 @@ pub struct Config {
     /// is not present at adapter startup. So to enable/disable the proxy there exists a `socks_proxy_allowed` field in
     /// the adapter request.
     pub socks_proxy: String,
+    /// Maximum size of a response body that is downloaded for a ranged request.
+    /// Ranged requests download the full body into the response cache, so this
+    /// limit replaces the per-request `max_response_size_bytes` for the download.
+    /// Values above `MAX_CANISTER_HTTP_RANGED_DOWNLOAD_BYTES` are capped to it.
+    pub max_ranged_response_size_bytes: u64,
+    /// Total size of the response bodies kept in the response cache. The oldest
+    /// responses are evicted once the limit is reached.
+    pub response_cache_size_bytes: u64,
+    /// Time after which a cached response is no longer served and downloaded again.
+    pub response_cache_ttl_secs: u64,
 }
 
 impl Default for Config {
@@ -40,6 +54,9 @@ impl Default for Config {
             incoming_source: IncomingSource::default(),
             logger: LoggerConfig::default(),
             socks_proxy: "socks5://notaproxy:1080".to_string(),
+            max_ranged_response_size_bytes: DEFAULT_MAX_RANGED_RESPONSE_SIZE_BYTES,
+            response_cache_size_bytes: DEFAULT_RESPONSE_CACHE_SIZE_BYTES,
+            response_cache_ttl_secs: DEFAULT_RESPONSE_CACHE_TTL_SECS,
         }
     }
 }
@@ -11,6 +11,9 @@ mod config;
 /// Adapter metrics
 mod metrics;
 
+/// Cache of the responses that ranged requests are served from.
+mod response_cache;
+
 pub use cli::Cli;
 pub use config::{Config, IncomingSource};
 pub use rpc_server::CanisterHttp;
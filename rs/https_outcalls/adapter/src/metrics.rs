@@ -8,6 +8,7 @@ pub(crate) const LABEL_HEADER_RECEIVE_SIZE: &str = "header_receive_size";
 pub(crate) const LABEL_HTTP_SCHEME: &str = "http_scheme";
 pub(crate) const LABEL_HTTP_METHOD: &str = "http_method";
 pub(crate) const LABEL_RESPONSE_HEADERS: &str = "response_headers";
+pub(crate) const LABEL_RESPONSE_CHANGED: &str = "response_changed";
 pub(crate) const LABEL_REQUEST_HEADERS: &str = "request_headers";
 pub(crate) const LABEL_CONNECT: &str = "connect";
 pub(crate) const LABEL_URL_PARSE: &str = "url_parse";
@@ -24,6 +25,8 @@ pub struct AdapterMetrics {
     pub network_traffic: IntCounterVec,
     /// Request failure types.
     pub request_errors: IntCounterVec,
+    /// The number of ranged requests served from the response cache.
+    pub response_cache_hits: IntCounter,
 }
 
 impl AdapterMetrics {
@@ -48,6 +51,10 @@ impl AdapterMetrics {
                 "Error types encountered in the adapter.",
                 &["cause"],
             ),
+            response_cache_hits: metrics_registry.int_counter(
+                "response_cache_hits_total",
+                "Total number of ranged requests served from the response cache",
+            ),
         }
     }
 }
@@ -0,0 +1,415 @@
+use hyper::body::Bytes;
+use ic_https_outcalls_service::{HttpHeader, HttpResponseRange, HttpsOutcallRequest};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::OwnedMutexGuard;
+
+/// Maximum number of responses whose validators are pinned. The validators of
+/// the responses that were downloaded first are unpinned once it is reached.
+const MAX_PINNED_VALIDATORS: usize = 100_000;
+
+/// Identifies the upstream request a cached response belongs to. Two outcalls
+/// share a cached response iff they send the same request to the same URL.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub(crate) struct ResponseCacheKey {
+    url: String,
+    method: i32,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl ResponseCacheKey {
+    pub(crate) fn new(request: &HttpsOutcallRequest) -> Self {
+        Self {
+            url: request.url.clone(),
+            method: request.method,
+            headers: request
+                .headers
+                .iter()
+                .map(|h| (h.name.to_lowercase(), h.value.clone()))
+                .collect(),
+            body: request.body.clone(),
+        }
+    }
+}
+
+/// A fully downloaded response that ranges are served from.
+#[derive(Clone, Debug)]
+pub(crate) struct CachedResponse {
+    pub status: u32,
+    pub headers: Vec<HttpHeader>,
+    pub body: Bytes,
+}
+
+impl CachedResponse {
+    /// Returns the headers and the content for the given range of the body.
+    ///
+    /// Upstream `content-length` and `content-range` headers describe the full
+    /// body and are replaced by a `content-range` header describing the returned
+    /// part: `bytes <first>-<last>/<total>`, or `bytes */<total>` if the range
+    /// starts at or past the end of the body.
+    pub(crate) fn range(&self, range: &HttpResponseRange) -> (Vec<HttpHeader>, Vec<u8>) {
+        let total = self.body.len();
+        let start = usize::try_from(range.offset)
+            .unwrap_or(usize::MAX)
+            .min(total);
+        let end = usize::try_from(range.offset.saturating_add(range.length))
+            .unwrap_or(usize::MAX)
+            .min(total);
+
+        let mut headers: Vec<_> = self
+            .headers
+            .iter()
+            .filter(|h| {
+                !h.name
+                    .eq_ignore_ascii_case(http::header::CONTENT_LENGTH.as_str())
+                    && !h
+                        .name
+                        .eq_ignore_ascii_case(http::header::CONTENT_RANGE.as_str())
+            })
+            .cloned()
+            .collect();
+        headers.push(HttpHeader {
+            name: http::header::CONTENT_RANGE.to_string(),
+            value: if start < end {
+                format!("bytes {}-{}/{}", start, end - 1, total)
+            } else {
+                format!("bytes */{}", total)
+            },
+        });
+
+        (headers, self.body.slice(start..end).to_vec())
+    }
+
+    fn size(&self) -> u64 {
+        self.body.len() as u64
+    }
+}
+
+/// The `etag` and `last-modified` headers of a response, which identify the
+/// version of the downloaded resource.
+#[derive(Clone, Eq, PartialEq, Debug)]
+struct ResponseValidator {
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+impl ResponseValidator {
+    fn new(headers: &[HttpHeader]) -> Self {
+        let header = |name: &http::header::HeaderName| {
+            headers
+                .iter()
+                .find(|h| h.name.eq_ignore_ascii_case(name.as_str()))
+                .map(|h| h.value.clone())
+        };
+        Self {
+            etag: header(&http::header::ETAG),
+            last_modified: header(&http::header::LAST_MODIFIED),
+        }
+    }
+}
+
+/// Size and age bounded cache of the responses downloaded for ranged requests.
+pub(crate) struct ResponseCache {
+    entries: HashMap<ResponseCacheKey, (Instant, CachedResponse)>,
+    size_bytes: u64,
+    max_size_bytes: u64,
+    ttl: Duration,
+    /// Validators of the first response downloaded for each key. They outlive
+    /// the cached responses, so that a response that is downloaded again
+    /// after it was evicted can be checked against the first download.
+    validators: HashMap<ResponseCacheKey, ResponseValidator>,
+    /// Keys of the pinned validators, in the order they were pinned.
+    pinned: VecDeque<ResponseCacheKey>,
+}
+
+impl ResponseCache {
+    pub(crate) fn new(max_size_bytes: u64, ttl: Duration) -> Self {
+        Self {
+            entries: HashMap::new(),
+            size_bytes: 0,
+            max_size_bytes,
+            ttl,
+            validators: HashMap::new(),
+            pinned: VecDeque::new(),
+        }
+    }
+
+    /// Pins the validator of the first response downloaded for the given key
+    /// and returns whether the given response has the same validator, i.e.,
+    /// whether its ranges belong to the same version of the resource as the
+    /// ranges that were served so far.
+    pub(crate) fn check_validator(
+        &mut self,
+        key: &ResponseCacheKey,
+        response: &CachedResponse,
+    ) -> bool {
+        let validator = ResponseValidator::new(&response.headers);
+        if let Some(pinned) = self.validators.get(key) {
+            return *pinned == validator;
+        }
+        if self.pinned.len() >= MAX_PINNED_VALIDATORS {
+            if let Some(oldest) = self.pinned.pop_front() {
+                self.validators.remove(&oldest);
+            }
+        }
+        self.pinned.push_back(key.clone());
+        self.validators.insert(key.clone(), validator);
+        true
+    }
+
+    /// Returns the cached response for the given key, unless it has expired.
+    pub(crate) fn get(&mut self, key: &ResponseCacheKey, now: Instant) -> Option<CachedResponse> {
+        self.evict_expired(now);
+        self.entries.get(key).map(|(_, response)| response.clone())
+    }
+
+    /// Caches the given response, evicting the oldest responses if the cache
+    /// would exceed its size limit. Responses larger than the cache are not
+    /// cached.
+    pub(crate) fn insert(&mut self, key: ResponseCacheKey, response: CachedResponse, now: Instant) {
+        if response.size() > self.max_size_bytes {
+            return;
+        }
+        self.remove(&key);
+        self.evict_expired(now);
+        while self.size_bytes + response.size() > self.max_size_bytes {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (inserted_at, _))| *inserted_at)
+                .map(|(key, _)| key.clone());
+            match oldest {
+                Some(oldest) => self.remove(&oldest),
+                None => break,
+            }
+        }
+        self.size_bytes += response.size();
+        self.entries.insert(key, (now, response));
+    }
+
+    fn remove(&mut self, key: &ResponseCacheKey) {
+        if let Some((_, response)) = self.entries.remove(key) {
+            self.size_bytes -= response.size();
+        }
+    }
+
+    fn evict_expired(&mut self, now: Instant) {
+        let ttl = self.ttl;
+        let mut evicted_bytes = 0;
+        self.entries.retain(|_, (inserted_at, response)| {
+            let keep = now.saturating_duration_since(*inserted_at) < ttl;
+            if !keep {
+                evicted_bytes += response.size();
+            }
+            keep
+        });
+        self.size_bytes -= evicted_bytes;
+    }
+}
+
+/// Tracks the responses that are being downloaded, so that concurrent ranged
+/// requests for the same response wait for a single download instead of each
+/// downloading the full body.
+#[derive(Default)]
+pub(crate) struct InFlightDownloads {
+    downloads: Mutex<HashMap<ResponseCacheKey, Arc<tokio::sync::Mutex<()>>>>,
+}
+
+impl InFlightDownloads {
+    /// Waits until no other request downloads the response for the given key.
+    /// The returned guard has to be held until the downloaded response is
+    /// cached, so that the waiting requests find it in the cache.
+    pub(crate) async fn acquire(&self, key: &ResponseCacheKey) -> DownloadGuard<'_> {
+        let download = self
+            .downloads
+            .lock()
+            .unwrap()
+            .entry(key.clone())
+            .or_default()
+            .clone();
+        let guard = download.lock_owned().await;
+        DownloadGuard {
+            in_flight: self,
+            key: key.clone(),
+            guard: Some(guard),
+        }
+    }
+
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.downloads.lock().unwrap().len()
+    }
+}
+
+/// Held by the single request that downloads a response. Waiting requests
+/// are released once it is dropped.
+pub(crate) struct DownloadGuard<'a> {
+    in_flight: &'a InFlightDownloads,
+    key: ResponseCacheKey,
+    guard: Option<OwnedMutexGuard<()>>,
+}
+
+impl Drop for DownloadGuard<'_> {
+    fn drop(&mut self) {
+        // Release the lock before checking whether anyone else still waits.
+        self.guard.take();
+        let mut downloads = self.in_flight.downloads.lock().unwrap();
+        if downloads
+            .get(&self.key)
+            .is_some_and(|download| Arc::strong_count(download) == 1)
+        {
+            downloads.remove(&self.key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(url: &str) -> ResponseCacheKey {
+        ResponseCacheKey::new(&HttpsOutcallRequest {
+            url: url.to_string(),
+            ..Default::default()
+        })
+    }
+
+    fn response(body: &[u8]) -> CachedResponse {
+        CachedResponse {
+            status: 200,
+            headers: vec![
+                HttpHeader {
+                    name: "Content-Length".to_string(),
+                    value: body.len().to_string(),
+                },
+                HttpHeader {
+                    name: "content-type".to_string(),
+                    value: "text/plain".to_string(),
+                },
+            ],
+            body: Bytes::copy_from_slice(body),
+        }
+    }
+
+    fn content_range(headers: &[HttpHeader]) -> &str {
+        headers
+            .iter()
+            .find(|h| h.name == "content-range")
+            .map(|h| h.value.as_str())
+            .unwrap()
+    }
+
+    #[test]
+    fn range_returns_slice_and_replaces_length_headers() {
+        let (headers, content) = response(b"0123456789").range(&HttpResponseRange {
+            offset: 2,
+            length: 3,
+        });
+        assert_eq!(content, b"234");
+        assert_eq!(content_range(&headers), "bytes 2-4/10");
+        assert!(!headers
+            .iter()
+            .any(|h| h.name.eq_ignore_ascii_case("content-length")));
+        assert!(headers.iter().any(|h| h.name == "content-type"));
+    }
+
+    #[test]
+    fn range_is_truncated_at_end_of_body() {
+        let (headers, content) = response(b"0123456789").range(&HttpResponseRange {
+            offset: 8,
+            length: u64::MAX,
+        });
+        assert_eq!(content, b"89");
+        assert_eq!(content_range(&headers), "bytes 8-9/10");
+
+        let (headers, content) = response(b"0123456789").range(&HttpResponseRange {
+            offset: 10,
+            length: 5,
+        });
+        assert!(content.is_empty());
+        assert_eq!(content_range(&headers), "bytes */10");
+    }
+
+    #[test]
+    fn cache_evicts_expired_responses() {
+        let mut cache = ResponseCache::new(100, Duration::from_secs(10));
+        let now = Instant::now();
+        cache.insert(key("https://a"), response(b"a"), now);
+        assert!(cache.get(&key("https://a"), now).is_some());
+        assert!(cache.get(&key("https://b"), now).is_none());
+        assert!(cache
+            .get(&key("https://a"), now + Duration::from_secs(10))
+            .is_none());
+        assert_eq!(cache.size_bytes, 0);
+    }
+
+    #[test]
+    fn cache_evicts_oldest_responses_when_full() {
+        let mut cache = ResponseCache::new(10, Duration::from_secs(10));
+        let now = Instant::now();
+        cache.insert(key("https://a"), response(&[0; 4]), now);
+        cache.insert(
+            key("https://b"),
+            response(&[0; 4]),
+            now + Duration::from_secs(1),
+        );
+        cache.insert(
+            key("https://c"),
+            response(&[0; 4]),
+            now + Duration::from_secs(2),
+        );
+        let later = now + Duration::from_secs(3);
+        assert!(cache.get(&key("https://a"), later).is_none());
+        assert!(cache.get(&key("https://b"), later).is_some());
+        assert!(cache.get(&key("https://c"), later).is_some());
+        assert_eq!(cache.size_bytes, 8);
+
+        // Responses that do not fit into the cache are not cached.
+        cache.insert(key("https://d"), response(&[0; 11]), later);
+        assert!(cache.get(&key("https://d"), later).is_none());
+        assert_eq!(cache.size_bytes, 8);
+    }
+
+    #[test]
+    fn cache_rejects_responses_with_a_different_validator() {
+        let with_etag = |etag: &str| {
+            let mut response = response(b"0123456789");
+            response.headers.push(HttpHeader {
+                name: "ETag".to_string(),
+                value: etag.to_string(),
+            });
+            response
+        };
+        let mut cache = ResponseCache::new(100, Duration::from_secs(10));
+        assert!(cache.check_validator(&key("https://a"), &with_etag("\"v1\"")));
+        assert!(cache.check_validator(&key("https://a"), &with_etag("\"v1\"")));
+        assert!(!cache.check_validator(&key("https://a"), &with_etag("\"v2\"")));
+        assert!(!cache.check_validator(&key("https://a"), &response(b"0123456789")));
+
+        // Validators are pinned per response.
+        assert!(cache.check_validator(&key("https://b"), &with_etag("\"v2\"")));
+        assert!(cache.check_validator(&key("https://c"), &response(b"0123456789")));
+        assert!(cache.check_validator(&key("https://c"), &response(b"0123456789")));
+    }
+
+    #[tokio::test]
+    async fn concurrent_downloads_of_the_same_response_are_serialized() {
+        let in_flight = InFlightDownloads::default();
+        let guard = in_flight.acquire(&key("https://a")).await;
+
+        // Downloads of other responses are not blocked.
+        drop(in_flight.acquire(&key("https://b")).await);
+
+        let mut waiting = Box::pin(in_flight.acquire(&key("https://a")));
+        assert!(futures::poll!(&mut waiting).is_pending());
+
+        drop(guard);
+        let guard = waiting.await;
+        assert_eq!(in_flight.len(), 1);
+
+        drop(guard);
+        assert_eq!(in_flight.len(), 0);
+    }
+}
@@ -1,8 +1,9 @@
 use crate::metrics::{
     AdapterMetrics, LABEL_BODY_RECEIVE_SIZE, LABEL_CONNECT, LABEL_DOWNLOAD,
-    LABEL_HEADER_RECEIVE_SIZE, LABEL_HTTP_METHOD, LABEL_REQUEST_HEADERS, LABEL_RESPONSE_HEADERS,
-    LABEL_UPLOAD, LABEL_URL_PARSE,
+    LABEL_HEADER_RECEIVE_SIZE, LABEL_HTTP_METHOD, LABEL_REQUEST_HEADERS, LABEL_RESPONSE_CHANGED,
+    LABEL_RESPONSE_HEADERS, LABEL_UPLOAD, LABEL_URL_PARSE,
 };
+use crate::response_cache::{CachedResponse, InFlightDownloads, ResponseCache, ResponseCacheKey};
 use crate::Config;
 use core::convert::TryFrom;
 use http::{header::USER_AGENT, HeaderName, HeaderValue, Uri};
@@ -18,13 +19,15 @@ use hyper_socks2::SocksConnector;
 use hyper_util::client::legacy::{connect::HttpConnector, Client};
 use hyper_util::rt::TokioExecutor;
 use ic_https_outcalls_service::{
-    https_outcalls_service_server::HttpsOutcallsService, HttpHeader, HttpMethod,
+    https_outcalls_service_server::HttpsOutcallsService, HttpHeader, HttpMethod, HttpResponseRange,
     HttpsOutcallRequest, HttpsOutcallResponse,
 };
+use ic_limits::MAX_CANISTER_HTTP_RANGED_DOWNLOAD_BYTES;
 use ic_logger::{debug, ReplicaLogger};
 use ic_metrics::MetricsRegistry;
 use std::str::FromStr;
-use std::time::Duration;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 use tonic::{Request, Response, Status};
 
 /// Hyper only supports a maximum of 32768 headers https://docs.rs/hyper/0.14.23/hyper/header/index.html#limitations-1
@@ -46,6 +49,9 @@ pub struct CanisterHttp {
     socks_client: Client<HttpsConnector<SocksConnector<HttpConnector>>, OutboundRequestBody>,
     logger: ReplicaLogger,
     metrics: AdapterMetrics,
+    max_ranged_response_size_bytes: u64,
+    response_cache: Mutex<ResponseCache>,
+    in_flight_downloads: InFlightDownloads,
 }
 
 impl CanisterHttp {
@@ -95,8 +101,50 @@ impl CanisterHttp {
             socks_client,
             logger,
             metrics: AdapterMetrics::new(metrics),
+            max_ranged_response_size_bytes: config
+                .max_ranged_response_size_bytes
+                .min(MAX_CANISTER_HTTP_RANGED_DOWNLOAD_BYTES),
+            response_cache: Mutex::new(ResponseCache::new(
+                config.response_cache_size_bytes,
+                Duration::from_secs(config.response_cache_ttl_secs),
+            )),
+            in_flight_downloads: InFlightDownloads::default(),
         }
     }
+
+    /// Returns the requested range of a cached response, as long as it fits
+    /// into the response size limit of the request.
+    fn ranged_response(
+        &self,
+        response: &CachedResponse,
+        range: &HttpResponseRange,
+        max_response_size_bytes: u64,
+    ) -> Result<Response<HttpsOutcallResponse>, Status> {
+        let (headers, content) = response.range(range);
+        let response_size_bytes = content.len()
+            + headers
+                .iter()
+                .map(|h| h.name.len() + h.value.len())
+                .sum::<usize>();
+        if response_size_bytes as u64 > max_response_size_bytes {
+            self.metrics
+                .request_errors
+                .with_label_values(&[LABEL_BODY_RECEIVE_SIZE])
+                .inc();
+            return Err(Status::new(
+                tonic::Code::OutOfRange,
+                format!(
+                    "Http body exceeds size limit of {} bytes.",
+                    max_response_size_bytes
+                ),
+            ));
+        }
+        Ok(Response::new(HttpsOutcallResponse {
+            status: response.status,
+            headers,
+            content,
+        }))
+    }
 }
 
 #[tonic::async_trait]
@@ -108,6 +156,8 @@ impl HttpsOutcallsService for CanisterHttp {
         self.metrics.requests.inc();
 
         let req = request.into_inner();
+        let response_range = req.response_range;
+        let cache_key = response_range.as_ref().map(|_| ResponseCacheKey::new(&req));
 
         let uri = req.url.parse::<Uri>().map_err(|err| {
             debug!(self.logger, "Failed to parse URL: {}", err);
@@ -172,6 +222,22 @@ impl HttpsOutcallsService for CanisterHttp {
         // Add user-agent header if not present.
         add_fallback_user_agent_header(&mut headers);
 
+        // Ranges of a response that was already downloaded are served from the cache.
+        // Concurrent requests for the same response wait for a single download and
+        // are then served from the cache as well.
+        let _download_guard = match (&response_range, &cache_key) {
+            (Some(range), Some(key)) => {
+                let download_guard = self.in_flight_downloads.acquire(key).await;
+                let cached = self.response_cache.lock().unwrap().get(key, Instant::now());
+                if let Some(cached) = cached {
+                    self.metrics.response_cache_hits.inc();
+                    return self.ranged_response(&cached, range, req.max_response_size_bytes);
+                }
+                Some(download_guard)
+            }
+            _ => None,
+        };
+
         let mut request_size = req.body.len();
         request_size += headers
             .iter()
@@ -254,47 +320,85 @@ impl HttpsOutcallsService for CanisterHttp {
                 )
             })?;
 
+        // Ranged requests download the full body, which is checked against the
+        // response size limit only after the requested range is cut out of it.
+        let (body_size_limit, response_size_limit) = match response_range {
+            Some(_) => (
+                self.max_ranged_response_size_bytes,
+                self.max_ranged_response_size_bytes,
+            ),
+            None => (
+                req.max_response_size_bytes
+                    .checked_sub(headers_size_bytes as u64)
+                    .ok_or_else(|| {
+                        self.metrics
+                            .request_errors
+                            .with_label_values(&[LABEL_HEADER_RECEIVE_SIZE])
+                            .inc();
+                        Status::new(
+                            tonic::Code::OutOfRange,
+                            format!(
+                                "Header size exceeds specified response size limit {}",
+                                req.max_response_size_bytes
+                            ),
+                        )
+                    })?,
+                req.max_response_size_bytes,
+            ),
+        };
+
         // We don't need a timeout here because there is a global timeout on the entire request.
-        let body_bytes = http_body_util::Limited::new(
-            http_resp.into_body(),
-            req.max_response_size_bytes
-                .checked_sub(headers_size_bytes as u64)
-                .ok_or_else(|| {
+        let body_bytes =
+            http_body_util::Limited::new(http_resp.into_body(), body_size_limit as usize)
+                .collect()
+                .await
+                .map(|col| col.to_bytes())
+                .map_err(|err| {
+                    debug!(self.logger, "Failed to fetch body: {}", err);
                     self.metrics
                         .request_errors
-                        .with_label_values(&[LABEL_HEADER_RECEIVE_SIZE])
+                        .with_label_values(&[LABEL_BODY_RECEIVE_SIZE])
                         .inc();
                     Status::new(
                         tonic::Code::OutOfRange,
                         format!(
-                            "Header size exceeds specified response size limit {}",
-                            req.max_response_size_bytes
+                            "Http body exceeds size limit of {} bytes.",
+                            response_size_limit
                         ),
                     )
-                })? as usize,
-        )
-        .collect()
-        .await
-        .map(|col| col.to_bytes())
-        .map_err(|err| {
-            debug!(self.logger, "Failed to fetch body: {}", err);
-            self.metrics
-                .request_errors
-                .with_label_values(&[LABEL_BODY_RECEIVE_SIZE])
-                .inc();
-            Status::new(
-                tonic::Code::OutOfRange,
-                format!(
-                    "Http body exceeds size limit of {} bytes.",
-                    req.max_response_size_bytes
-                ),
-            )
-        })?;
+                })?;
 
         self.metrics
             .network_traffic
             .with_label_values(&[LABEL_DOWNLOAD])
             .inc_by(body_bytes.len() as u64 + headers_size_bytes as u64);
+
+        if let (Some(range), Some(key)) = (response_range, cache_key) {
+            let response = CachedResponse {
+                status,
+                headers,
+                body: body_bytes,
+            };
+            let mut response_cache = self.response_cache.lock().unwrap();
+            // Ranges of different versions of the resource must not be mixed,
+            // e.g., if the resource changed after its first download expired.
+            if !response_cache.check_validator(&key, &response) {
+                self.metrics
+                    .request_errors
+                    .with_label_values(&[LABEL_RESPONSE_CHANGED])
+                    .inc();
+                return Err(Status::new(
+                    tonic::Code::FailedPrecondition,
+                    "The ETag or Last-Modified header of the response differs from the first \
+                    download of the response."
+                        .to_string(),
+                ));
+            }
+            response_cache.insert(key, response.clone(), Instant::now());
+            drop(response_cache);
+            return self.ranged_response(&response, &range, req.max_response_size_bytes);
+        }
+
         Ok(Response::new(HttpsOutcallResponse {
             status,
             headers,
@@ -4,7 +4,8 @@
 mod test {
     use ic_https_outcalls_adapter::{Config, IncomingSource};
     use ic_https_outcalls_service::{
-        https_outcalls_service_client::HttpsOutcallsServiceClient, HttpMethod, HttpsOutcallRequest,
+        https_outcalls_service_client::HttpsOutcallsServiceClient, HttpMethod, HttpResponseRange,
+        HttpsOutcallRequest, HttpsOutcallResponse,
     };
     use ic_logger::replica_logger::no_op_logger;
     use ic_metrics::MetricsRegistry;
@@ -12,6 +13,7 @@ mod test {
     use std::convert::TryFrom;
     use std::env;
     use std::io::Write;
+    use std::sync::atomic::{AtomicU64, Ordering};
     use tempfile::TempDir;
     use tokio::net::UnixStream;
     use tonic::transport::{Channel, Endpoint, Uri};
@@ -108,12 +110,20 @@ MIGHAgEAMBMGByqGSM49AgEGCCqGSM49AwEHBG0wawIBAQQgob29X4H4m2XOkSZE
 
         let basic_head = warp::head().and(warp::path("head")).map(warp::reply::reply);
 
+        // Returns a different 4 KiB body on every request.
+        static DOCUMENT_VERSION: AtomicU64 = AtomicU64::new(0);
+        let get_document = warp::get().and(warp::path("document")).map(|| {
+            let version = DOCUMENT_VERSION.fetch_add(1, Ordering::SeqCst);
+            Response::builder().body(version.to_be_bytes().repeat(512))
+        });
+
         basic_post
             .or(basic_get)
             .or(basic_head)
             .or(get_response_size)
             .or(get_delay)
             .or(invalid_header)
+            .or(get_document)
             .boxed()
     }
 
@@ -153,6 +163,7 @@ MIGHAgEAMBMGByqGSM49AgEGCCqGSM49AwEHBG0wawIBAQQgob29X4H4m2XOkSZE
             body: "hello".to_string().as_bytes().to_vec(),
             max_response_size_bytes: 512,
             socks_proxy_allowed: false,
+            response_range: None,
         });
         let response = client.https_outcall(request).await;
         let http_response = response.unwrap().into_inner();
@@ -179,6 +190,7 @@ MIGHAgEAMBMGByqGSM49AgEGCCqGSM49AwEHBG0wawIBAQQgob29X4H4m2XOkSZE
             body: "hello".to_string().as_bytes().to_vec(),
             max_response_size_bytes: 512,
             socks_proxy_allowed: false,
+            response_range: None,
         });
         let response = client.https_outcall(request).await;
         assert_eq!(
@@ -211,6 +223,7 @@ MIGHAgEAMBMGByqGSM49AgEGCCqGSM49AwEHBG0wawIBAQQgob29X4H4m2XOkSZE
             body: "hello".to_string().as_bytes().to_vec(),
             max_response_size_bytes: 512,
             socks_proxy_allowed: false,
+            response_range: None,
         });
         let response = client.https_outcall(request).await;
         let http_response = response.unwrap().into_inner();
@@ -235,6 +248,7 @@ MIGHAgEAMBMGByqGSM49AgEGCCqGSM49AwEHBG0wawIBAQQgob29X4H4m2XOkSZE
             body: "420".to_string().as_bytes().to_vec(),
             max_response_size_bytes: 512,
             socks_proxy_allowed: false,
+            response_range: None,
         });
 
         let response = client.https_outcall(request).await;
@@ -261,6 +275,7 @@ MIGHAgEAMBMGByqGSM49AgEGCCqGSM49AwEHBG0wawIBAQQgob29X4H4m2XOkSZE
             body: "".to_string().as_bytes().to_vec(),
             max_response_size_bytes: 512,
             socks_proxy_allowed: false,
+            response_range: None,
         });
 
         let response = client.https_outcall(request).await;
@@ -288,6 +303,7 @@ MIGHAgEAMBMGByqGSM49AgEGCCqGSM49AwEHBG0wawIBAQQgob29X4H4m2XOkSZE
             body: format!("{}", response_limit + 1).as_bytes().to_vec(),
             max_response_size_bytes: response_limit,
             socks_proxy_allowed: false,
+            response_range: None,
         });
 
         let response = client.https_outcall(request).await;
@@ -320,6 +336,7 @@ MIGHAgEAMBMGByqGSM49AgEGCCqGSM49AwEHBG0wawIBAQQgob29X4H4m2XOkSZE
             body: format!("{}", response_size).as_bytes().to_vec(),
             max_response_size_bytes: response_size * 2,
             socks_proxy_allowed: false,
+            response_range: None,
         });
 
         let response = client.https_outcall(request).await;
@@ -327,6 +344,69 @@ MIGHAgEAMBMGByqGSM49AgEGCCqGSM49AwEHBG0wawIBAQQgob29X4H4m2XOkSZE
         assert_eq!(http_response.status, StatusCode::OK.as_u16() as u32);
     }
 
+    #[tokio::test]
+    async fn test_ranged_response_served_from_cache() {
+        let path = "/tmp/canister-http-test-".to_string() + &Uuid::new_v4().to_string();
+        let server_config = Config {
+            incoming_source: IncomingSource::Path(path.into()),
+            ..Default::default()
+        };
+
+        let url = start_server(CERT_INIT.get_or_init(generate_certs));
+        let mut client = spawn_grpc_server(server_config);
+
+        let request = |offset, length| {
+            tonic::Request::new(HttpsOutcallRequest {
+                url: format!("https://{}/document", &url),
+                headers: Vec::new(),
+                method: HttpMethod::Get as i32,
+                body: vec![],
+                max_response_size_bytes: 1024,
+                socks_proxy_allowed: false,
+                response_range: Some(HttpResponseRange { offset, length }),
+            })
+        };
+        let content_range = |response: &HttpsOutcallResponse| {
+            response
+                .headers
+                .iter()
+                .find(|h| h.name == "content-range")
+                .map(|h| h.value.clone())
+                .unwrap()
+        };
+
+        // The full document exceeds the response limit, but each range fits.
+        let first = client
+            .https_outcall(request(0, 512))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(first.status, StatusCode::OK.as_u16() as u32);
+        assert_eq!(first.content.len(), 512);
+        assert_eq!(content_range(&first), "bytes 0-511/4096");
+
+        // The last range is served from the same cached document.
+        let last = client
+            .https_outcall(request(3584, 1024))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(last.content, first.content);
+        assert_eq!(content_range(&last), "bytes 3584-4095/4096");
+
+        let past_end = client
+            .https_outcall(request(4096, 512))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(past_end.content.is_empty());
+        assert_eq!(content_range(&past_end), "bytes */4096");
+
+        // Ranges that do not fit into the response limit are rejected.
+        let response = client.https_outcall(request(0, 2048)).await;
+        assert_eq!(response.unwrap_err().code(), tonic::Code::OutOfRange);
+    }
+
     #[tokio::test]
     async fn test_request_timeout() {
         // Check if response with higher than allowed response limit is rejected.
@@ -347,6 +427,7 @@ MIGHAgEAMBMGByqGSM49AgEGCCqGSM49AwEHBG0wawIBAQQgob29X4H4m2XOkSZE
             body: format!("{}", delay).as_bytes().to_vec(),
             max_response_size_bytes: 512,
             socks_proxy_allowed: false,
+            response_range: None,
         });
 
         let response = client.https_outcall(request).await;
@@ -383,6 +464,7 @@ MIGHAgEAMBMGByqGSM49AgEGCCqGSM49AwEHBG0wawIBAQQgob29X4H4m2XOkSZE
             body: "hello".to_string().as_bytes().to_vec(),
             max_response_size_bytes: 64,
             socks_proxy_allowed: false,
+            response_range: None,
         });
         let response = client.https_outcall(request).await;
         assert_eq!(
@@ -414,6 +496,7 @@ MIGHAgEAMBMGByqGSM49AgEGCCqGSM49AwEHBG0wawIBAQQgob29X4H4m2XOkSZE
             body: "hello".as_bytes().to_vec(),
             max_response_size_bytes: response_limit,
             socks_proxy_allowed: false,
+            response_range: None,
         });
 
         let response = client.https_outcall(request).await;
@@ -439,6 +522,7 @@ MIGHAgEAMBMGByqGSM49AgEGCCqGSM49AwEHBG0wawIBAQQgob29X4H4m2XOkSZE
             body: "hello".to_string().as_bytes().to_vec(),
             max_response_size_bytes: 512,
             socks_proxy_allowed: false,
+            response_range: None,
         });
         let response = client.https_outcall(request).await;
         let _ = response.unwrap_err();
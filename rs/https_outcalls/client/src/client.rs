@@ -4,7 +4,7 @@ use futures::future::TryFutureExt;
 use ic_error_types::{RejectCode, UserError};
 use ic_https_outcalls_service::{
     https_outcalls_service_client::HttpsOutcallsServiceClient, HttpHeader, HttpMethod,
-    HttpsOutcallRequest, HttpsOutcallResponse,
+    HttpResponseRange, HttpsOutcallRequest, HttpsOutcallResponse,
 };
 use ic_interfaces::execution_environment::QueryExecutionService;
 use ic_interfaces_adapter_client::{NonBlockingChannel, SendError, TryReceiveError};
@@ -13,9 +13,9 @@ use ic_metrics::MetricsRegistry;
 use ic_registry_subnet_type::SubnetType;
 use ic_types::{
     canister_http::{
-        validate_http_headers_and_body, CanisterHttpMethod, CanisterHttpReject,
+        mark_unattested, validate_http_headers_and_body, CanisterHttpMethod, CanisterHttpReject,
         CanisterHttpRequest, CanisterHttpRequestContext, CanisterHttpResponse,
-        CanisterHttpResponseContent, Transform, MAX_CANISTER_HTTP_RESPONSE_BYTES,
+        CanisterHttpResponseContent, Replication, Transform, MAX_CANISTER_HTTP_RESPONSE_BYTES,
     },
     ingress::WasmResult,
     messages::{CertificateDelegation, Query, QuerySource, Request},
@@ -145,6 +145,8 @@ impl NonBlockingChannel<CanisterHttpRequest> for CanisterHttpAdapterClientImpl {
                         http_method: request_http_method,
                         max_response_bytes: request_max_response_bytes,
                         transform: request_transform,
+                        response_range: request_response_range,
+                        replication: request_replication,
                         ..
                     },
             } = canister_http_request;
@@ -169,7 +171,11 @@ impl NonBlockingChannel<CanisterHttpRequest> for CanisterHttpAdapterClientImpl {
                         .collect(),
                     body: request_body.unwrap_or_default(),
                     // Socks proxy is only enabled on system subnets.
-                    socks_proxy_allowed: matches!(subnet_type, SubnetType::System)
+                    socks_proxy_allowed: matches!(subnet_type, SubnetType::System),
+                    response_range: request_response_range.map(|range| HttpResponseRange {
+                        offset: range.offset,
+                        length: range.length,
+                    }),
                 })
                 .map_err(|grpc_status| {
                     (
@@ -187,6 +193,7 @@ impl NonBlockingChannel<CanisterHttpRequest> for CanisterHttpAdapterClientImpl {
                                     ic_management_canister_types::HttpHeader { name, value }
                                 }).collect(),
                         body,
+                        is_replicated: None,
                     };
 
                     metrics.http_request_duration
@@ -221,6 +228,20 @@ impl NonBlockingChannel<CanisterHttpRequest> for CanisterHttpAdapterClientImpl {
                     };
 
                     transform_timer.observe_duration();
+
+                    // Responses to non-replicated requests are marked as not attested by the
+                    // other nodes. This happens after the transform, so that it can't be undone.
+                    let transform_response = match request_replication {
+                        Replication::FullyReplicated => transform_response,
+                        Replication::NonReplicated(_) => mark_unattested(&transform_response)
+                            .map_err(|err| {
+                                (
+                                    RejectCode::SysFatal,
+                                    format!("Failed to mark the http response as unattested: {}", err),
+                                )
+                            })?,
+                    };
+
                     if transform_response.len() > (MAX_CANISTER_HTTP_RESPONSE_BYTES as usize) {
                         let err_msg = match request_transform {
                             Some(_) => format!(
@@ -349,8 +370,8 @@ mod tests {
         HttpsOutcallRequest, HttpsOutcallResponse,
     };
     use ic_interfaces::execution_environment::{QueryExecutionError, QueryExecutionResponse};
-    use ic_test_utilities_types::messages::RequestBuilder;
-    use ic_types::canister_http::Transform;
+    use ic_test_utilities_types::{ids::node_test_id, messages::RequestBuilder};
+    use ic_types::canister_http::{Replication, Transform};
     use ic_types::{
         canister_http::CanisterHttpMethod,
         messages::{CallbackId, CertificateDelegation},
@@ -447,6 +468,8 @@ mod tests {
                     context: vec![],
                 }),
                 time: UNIX_EPOCH,
+                response_range: None,
+                replication: Replication::FullyReplicated,
             },
         }
     }
@@ -489,6 +512,7 @@ mod tests {
                         })
                         .collect(),
                     body,
+                    is_replicated: None,
                 })
                 .unwrap(),
             ),
@@ -595,6 +619,48 @@ mod tests {
         assert_eq!(client.try_receive(), Err(TryReceiveError::Empty));
     }
 
+    /// Test that the responses to non-replicated requests are marked as unattested.
+    #[tokio::test]
+    async fn test_client_marks_non_replicated_responses() {
+        let mock_grpc_channel = setup_adapter_mock(Ok(HttpsOutcallResponse {
+            status: 200,
+            headers: vec![],
+            content: b"body".to_vec(),
+        }))
+        .await;
+
+        // Asynchronous query handler mock setup. Does not serve any purpose in this test case.
+        let (svc, mut handle) = setup_anonymous_query_mock();
+
+        tokio::spawn(async move {
+            let (_, rsp) = handle.next_request().await.unwrap();
+            rsp.send_response(Err(QueryExecutionError::CertifiedStateUnavailable));
+        });
+
+        let mut client = CanisterHttpAdapterClientImpl::new(
+            tokio::runtime::Handle::current(),
+            mock_grpc_channel,
+            svc,
+            100,
+            MetricsRegistry::default(),
+            SubnetType::Application,
+            Arc::new(OnceCell::new()),
+        );
+
+        let mut request = build_mock_canister_http_request(420, UNIX_EPOCH, None);
+        request.context.replication = Replication::NonReplicated(node_test_id(1));
+        assert_eq!(client.send(request), Ok(()));
+        loop {
+            match client.try_receive() {
+                Err(_) => tokio::time::sleep(Duration::from_millis(10)).await,
+                Ok(r) => {
+                    assert!(r.content.is_marked_unattested());
+                    break;
+                }
+            }
+        }
+    }
+
     /// Test case where adapter encounters an UNAVAILABLE  error in executing the http request.
     /// This should be reported as a transient error.
     #[tokio::test]
@@ -807,6 +873,7 @@ mod tests {
                             })
                             .collect(),
                         body: adapter_b.clone(),
+                        is_replicated: None,
                     })
                     .unwrap(),
                 )),
@@ -4,7 +4,10 @@ use crate::{
     metrics::CanisterHttpPayloadBuilderMetrics,
     payload_builder::{
         parse::bytes_to_payload,
-        utils::{group_shares_by_callback_id, grouped_shares_meet_divergence_criteria},
+        utils::{
+            designated_nodes_of_non_replicated_requests, group_shares_by_callback_id,
+            grouped_shares_meet_divergence_criteria,
+        },
     },
 };
 use ic_consensus_utils::{
@@ -208,6 +211,8 @@ impl CanisterHttpPayloadBuilderImpl {
         let mut active_shares = 0;
         let mut unique_responses_count = 0;
 
+        let mut designated_nodes = BTreeMap::new();
+
         // Check the state for timeouts NOTE: We can not use the existing
         // timed out artifacts for this task, since we don't have consensus
         // on them. For example a malicious node might publish a single
@@ -218,14 +223,15 @@ impl CanisterHttpPayloadBuilderImpl {
             .state_reader
             .get_state_at(validation_context.certified_height)
         {
-            // Iterate over all outstanding canister http requests
-            for (callback_id, request) in state
+            let http_contexts = &state
                 .get_ref()
                 .metadata
                 .subnet_call_context_manager
-                .canister_http_request_contexts
-                .iter()
-            {
+                .canister_http_request_contexts;
+            designated_nodes = designated_nodes_of_non_replicated_requests(http_contexts);
+
+            // Iterate over all outstanding canister http requests
+            for (callback_id, request) in http_contexts.iter() {
                 unique_includable_responses += 1;
                 let candidate_size = callback_id.count_bytes();
                 let size = NumBytes::new((accumulated_size + candidate_size) as u64);
@@ -279,7 +285,29 @@ impl CanisterHttpPayloadBuilderImpl {
 
             let candidates_and_divergences = response_candidates_by_callback_id
                 .into_iter()
-                .filter_map(|(_, grouped_shares)| {
+                .filter_map(|(callback_id, grouped_shares)| {
+                    // A response to a non-replicated request only needs the
+                    // share of the node the request was assigned to. That node
+                    // gossips the response along with its share, so any block
+                    // maker can include the response in a block.
+                    if let Some(designated_node) = designated_nodes.get(&callback_id) {
+                        return grouped_shares.iter().find_map(|(metadata, shares)| {
+                            let share = shares
+                                .iter()
+                                .find(|share| share.signature.signer == *designated_node)?;
+                            pool_access
+                                .lookup_validated(share)
+                                .and_then(|artifact| artifact.response)
+                                .map(|content| {
+                                    CandidateOrDivergence::Candidate((
+                                        metadata.clone(),
+                                        BTreeSet::from([share.signature.clone()]),
+                                        content,
+                                    ))
+                                })
+                        });
+                    }
+
                     if let Some((metadata, shares)) = grouped_shares.iter().find(|(_, shares)| {
                         unique_responses_count += 1;
                         let signers: BTreeSet<_> =
@@ -415,6 +443,7 @@ impl CanisterHttpPayloadBuilderImpl {
             .metadata
             .subnet_call_context_manager
             .canister_http_request_contexts;
+        let designated_nodes = designated_nodes_of_non_replicated_requests(http_contexts);
 
         for timeout_id in &payload.timeouts {
             // Get requests
@@ -499,7 +528,28 @@ impl CanisterHttpPayloadBuilderImpl {
                     valid_signers,
                 });
             }
-            if valid_signers.len() < threshold {
+            if let Some(designated_node) = designated_nodes.get(&response.content.id) {
+                if valid_signers != [*designated_node] {
+                    return invalid_artifact(
+                        InvalidCanisterHttpPayloadReason::NotSignedByDesignatedNode {
+                            callback_id: response.content.id,
+                            designated_node: *designated_node,
+                            signers: valid_signers,
+                        },
+                    );
+                }
+                // The canister must be able to tell that the response is unattested.
+                let content = &response.content.content;
+                if matches!(content, CanisterHttpResponseContent::Success(_))
+                    && !content.is_marked_unattested()
+                {
+                    return invalid_artifact(
+                        InvalidCanisterHttpPayloadReason::NonReplicatedResponseNotMarked(
+                            response.content.id,
+                        ),
+                    );
+                }
+            } else if valid_signers.len() < threshold {
                 return invalid_artifact(InvalidCanisterHttpPayloadReason::NotEnoughSigners {
                     committee,
                     signers: valid_signers,
@@ -554,7 +604,15 @@ impl CanisterHttpPayloadBuilderImpl {
                     InvalidCanisterHttpPayloadReason::DivergenceProofContainsMultipleCallbackIds,
                 );
             }
-            for (_, grouped_shares) in grouped_shares {
+            for (callback_id, grouped_shares) in grouped_shares {
+                // A single node makes non-replicated requests, so their responses can't diverge.
+                if designated_nodes.contains_key(&callback_id) {
+                    return invalid_artifact(
+                        InvalidCanisterHttpPayloadReason::DivergenceProofForNonReplicatedRequest(
+                            callback_id,
+                        ),
+                    );
+                }
                 if !grouped_shares_meet_divergence_criteria(&grouped_shares, faults_tolerated) {
                     return invalid_artifact(
                         InvalidCanisterHttpPayloadReason::DivergenceProofDoesNotMeetDivergenceCriteria,
@@ -8,6 +8,7 @@ use crate::payload_builder::{
     divergence_response_into_reject,
     parse::{bytes_to_payload, payload_to_bytes},
 };
+use candid::Encode;
 use ic_artifact_pool::canister_http_pool::CanisterHttpPoolImpl;
 use ic_consensus_mocks::{dependencies_with_subnet_params, Dependencies};
 use ic_error_types::RejectCode;
@@ -22,6 +23,7 @@ use ic_interfaces::{
     validation::ValidationError,
 };
 use ic_logger::replica_logger::no_op_logger;
+use ic_management_canister_types::CanisterHttpResponsePayload;
 use ic_metrics::MetricsRegistry;
 use ic_registry_subnet_features::SubnetFeatures;
 use ic_test_utilities::state_manager::RefMockStateManager;
@@ -33,10 +35,10 @@ use ic_test_utilities_types::{
 use ic_types::{
     batch::{CanisterHttpPayload, ValidationContext, MAX_CANISTER_HTTP_PAYLOAD_SIZE},
     canister_http::{
-        CanisterHttpMethod, CanisterHttpRequestContext, CanisterHttpResponse,
-        CanisterHttpResponseContent, CanisterHttpResponseDivergence, CanisterHttpResponseMetadata,
-        CanisterHttpResponseShare, CanisterHttpResponseWithConsensus,
-        CANISTER_HTTP_MAX_RESPONSES_PER_BLOCK, CANISTER_HTTP_TIMEOUT_INTERVAL,
+        mark_unattested, CanisterHttpMethod, CanisterHttpRequestContext, CanisterHttpResponse,
+        CanisterHttpResponseArtifact, CanisterHttpResponseContent, CanisterHttpResponseDivergence,
+        CanisterHttpResponseMetadata, CanisterHttpResponseShare, CanisterHttpResponseWithConsensus,
+        Replication, CANISTER_HTTP_MAX_RESPONSES_PER_BLOCK, CANISTER_HTTP_TIMEOUT_INTERVAL,
     },
     consensus::get_faults_tolerated,
     crypto::{crypto_hash, BasicSig, BasicSigOf, CryptoHash, CryptoHashOf, Signed},
@@ -115,6 +117,129 @@ fn single_request_test() {
     }
 }
 
+/// Check that a response to a non-replicated request is included with only the share
+/// of the designated node, and that it is rejected if signed by any other node or if
+/// it is not marked as unattested
+#[test]
+fn non_replicated_request_test() {
+    let context = default_validation_context();
+
+    for (designated_node, marked, expect_valid) in
+        [(1, true, true), (2, true, false), (1, false, false)]
+    {
+        test_config_with_http_feature(true, 4, |mut payload_builder, canister_http_pool| {
+            let payload = Encode!(&CanisterHttpResponsePayload {
+                status: 200,
+                headers: vec![],
+                body: b"abc".to_vec(),
+                is_replicated: None,
+            })
+            .unwrap();
+            let payload = if marked {
+                mark_unattested(&payload).unwrap()
+            } else {
+                payload
+            };
+            let (response, metadata) = test_response_and_metadata_with_content(
+                0,
+                CanisterHttpResponseContent::Success(payload),
+            );
+            let share = metadata_to_share(1, &metadata);
+
+            {
+                // The share and the response are gossiped by node 1, so the
+                // block maker (node 0) never computed the response itself
+                let mut pool_access = canister_http_pool.write().unwrap();
+                add_gossiped_share_to_pool(pool_access.deref_mut(), &share, &response);
+
+                let mut init_state = ic_test_utilities_state::get_initial_state(0, 0);
+                init_state
+                    .metadata
+                    .subnet_call_context_manager
+                    .canister_http_request_contexts
+                    .insert(
+                        response.id,
+                        CanisterHttpRequestContext {
+                            request: RequestBuilder::default().build(),
+                            url: String::new(),
+                            max_response_bytes: None,
+                            headers: vec![],
+                            body: None,
+                            http_method: CanisterHttpMethod::GET,
+                            transform: None,
+                            time: UNIX_EPOCH,
+                            response_range: None,
+                            replication: Replication::NonReplicated(node_test_id(designated_node)),
+                        },
+                    );
+                let state_manager = Arc::new(RefMockStateManager::default());
+                state_manager
+                    .get_mut()
+                    .expect_get_state_at()
+                    .return_const(Ok(ic_interfaces_state_manager::Labeled::new(
+                        Height::new(0),
+                        Arc::new(init_state),
+                    )));
+                payload_builder.state_reader = state_manager;
+            }
+
+            let payload = CanisterHttpPayload {
+                responses: vec![CanisterHttpResponseWithConsensus {
+                    content: response.clone(),
+                    proof: Signed {
+                        content: metadata.clone(),
+                        signature: BasicSignatureBatch {
+                            signatures_map: BTreeMap::from([(
+                                share.signature.signer,
+                                share.signature.signature.clone(),
+                            )]),
+                        },
+                    },
+                }],
+                timeouts: vec![],
+                divergence_responses: vec![],
+            };
+            let payload = payload_to_bytes(&payload, NumBytes::new(4 * 1024 * 1024));
+            let validation_result = payload_builder.validate_payload(
+                Height::new(1),
+                &test_proposal_context(&context),
+                &payload,
+                &[],
+            );
+
+            if expect_valid {
+                assert!(validation_result.is_ok());
+
+                // The single share of the designated node suffices to build a payload
+                let payload = payload_builder.build_payload(
+                    Height::new(1),
+                    NumBytes::new(4 * 1024 * 1024),
+                    &[],
+                    &context,
+                );
+                let parsed_payload =
+                    bytes_to_payload(&payload).expect("Failed to parse the payload");
+                assert_eq!(parsed_payload.num_responses(), 1);
+                assert_eq!(parsed_payload.responses[0].content, response);
+            } else {
+                match validation_result {
+                    Err(ValidationError::InvalidArtifact(
+                        InvalidPayloadReason::InvalidCanisterHttpPayload(
+                            InvalidCanisterHttpPayloadReason::NotSignedByDesignatedNode { .. },
+                        ),
+                    )) if marked => (),
+                    Err(ValidationError::InvalidArtifact(
+                        InvalidPayloadReason::InvalidCanisterHttpPayload(
+                            InvalidCanisterHttpPayloadReason::NonReplicatedResponseNotMarked(_),
+                        ),
+                    )) if !marked => (),
+                    x => panic!("Expected an invalid payload, got {:?}", x),
+                }
+            }
+        });
+    }
+}
+
 /// Submit a number of requests to the payload builder:
 ///
 /// - One has insufficient support
@@ -338,6 +463,8 @@ fn timeout_priority() {
                     transform: None,
                     // this is the important one
                     time: UNIX_EPOCH,
+                    response_range: None,
+                    replication: Replication::FullyReplicated,
                 };
                 init_state
                     .metadata
@@ -881,12 +1008,15 @@ fn test_response_and_metadata_full(
 }
 /// Replicates the behaviour of receiving and successfully validating a share over the network
 pub(crate) fn add_received_shares_to_pool(
-    pool: &mut dyn MutablePool<CanisterHttpResponseShare, Mutations = CanisterHttpChangeSet>,
+    pool: &mut dyn MutablePool<CanisterHttpResponseArtifact, Mutations = CanisterHttpChangeSet>,
     shares: Vec<CanisterHttpResponseShare>,
 ) {
     for share in shares {
         pool.insert(UnvalidatedArtifact {
-            message: share.clone(),
+            message: CanisterHttpResponseArtifact {
+                share: share.clone(),
+                response: None,
+            },
             peer_id: node_test_id(0),
             timestamp: UNIX_EPOCH,
         });
@@ -895,9 +1025,30 @@ pub(crate) fn add_received_shares_to_pool(
     }
 }
 
+/// Replicates the behaviour of receiving and successfully validating a share
+/// of a non-replicated request, which is gossiped together with its response
+fn add_gossiped_share_to_pool(
+    pool: &mut dyn MutablePool<CanisterHttpResponseArtifact, Mutations = CanisterHttpChangeSet>,
+    share: &CanisterHttpResponseShare,
+    response: &CanisterHttpResponse,
+) {
+    pool.insert(UnvalidatedArtifact {
+        message: CanisterHttpResponseArtifact {
+            share: share.clone(),
+            response: Some(response.clone()),
+        },
+        peer_id: share.signature.signer,
+        timestamp: UNIX_EPOCH,
+    });
+
+    pool.apply(vec![CanisterHttpChangeAction::MoveToValidated(
+        share.clone(),
+    )]);
+}
+
 /// Replicates the behaviour of adding your own share (and content) to the pool
 pub(crate) fn add_own_share_to_pool(
-    pool: &mut dyn MutablePool<CanisterHttpResponseShare, Mutations = CanisterHttpChangeSet>,
+    pool: &mut dyn MutablePool<CanisterHttpResponseArtifact, Mutations = CanisterHttpChangeSet>,
     share: &CanisterHttpResponseShare,
     content: &CanisterHttpResponse,
 ) {
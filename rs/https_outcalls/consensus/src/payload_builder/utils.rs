@@ -2,7 +2,8 @@ use ic_interfaces::canister_http::InvalidCanisterHttpPayloadReason;
 use ic_types::{
     batch::ValidationContext,
     canister_http::{
-        CanisterHttpResponseMetadata, CanisterHttpResponseShare, CanisterHttpResponseWithConsensus,
+        CanisterHttpRequestContext, CanisterHttpResponseMetadata, CanisterHttpResponseShare,
+        CanisterHttpResponseWithConsensus, Replication,
     },
     crypto::crypto_hash,
     messages::CallbackId,
@@ -146,3 +147,16 @@ pub(crate) fn group_shares_by_callback_id<
     }
     map
 }
+
+/// Returns the node that each non-replicated request is assigned to.
+pub(crate) fn designated_nodes_of_non_replicated_requests(
+    contexts: &BTreeMap<CallbackId, CanisterHttpRequestContext>,
+) -> BTreeMap<CallbackId, NodeId> {
+    contexts
+        .iter()
+        .filter_map(|(callback_id, context)| match context.replication {
+            Replication::FullyReplicated => None,
+            Replication::NonReplicated(node_id) => Some((*callback_id, node_id)),
+        })
+        .collect()
+}
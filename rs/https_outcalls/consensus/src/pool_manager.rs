@@ -182,6 +182,12 @@ impl CanisterHttpPoolManagerImpl {
             .collect();
 
         for (id, context) in http_requests {
+            // Non-replicated requests are only made by the designated node.
+            if let Replication::NonReplicated(node_id) = context.replication {
+                if node_id != self.replica_config.node_id {
+                    continue;
+                }
+            }
             if !request_ids_already_made.contains(&id) {
                 let timeout = context.time + Duration::from_secs(5 * 60);
                 if let Err(err) = self
@@ -224,6 +230,12 @@ impl CanisterHttpPoolManagerImpl {
             );
             return Vec::new();
         };
+        let state = self.state_reader.get_latest_state();
+        let http_requests = &state
+            .get_ref()
+            .metadata
+            .subnet_call_context_manager
+            .canister_http_request_contexts;
         let mut change_set = Vec::new();
         loop {
             match self.http_adapter_shim.lock().unwrap().try_receive() {
@@ -254,7 +266,19 @@ impl CanisterHttpPoolManagerImpl {
                     };
                     self.requested_id_cache.borrow_mut().remove(&response.id);
                     self.metrics.shares_signed.inc();
-                    change_set.push(CanisterHttpChangeAction::AddToValidated(share, response));
+                    // The responses to non-replicated requests are gossiped with the
+                    // share, so that not only this node can include them in a block.
+                    let is_non_replicated = matches!(
+                        http_requests
+                            .get(&response.id)
+                            .map(|context| context.replication),
+                        Some(Replication::NonReplicated(_))
+                    );
+                    change_set.push(if is_non_replicated {
+                        CanisterHttpChangeAction::AddToValidatedAndGossipResponse(share, response)
+                    } else {
+                        CanisterHttpChangeAction::AddToValidated(share, response)
+                    });
                 }
             }
         }
@@ -287,6 +311,12 @@ impl CanisterHttpPoolManagerImpl {
 
         let active_callback_ids = self.active_callback_ids();
         let next_callback_id = self.next_callback_id();
+        let state = self.state_reader.get_latest_state();
+        let http_requests = &state
+            .get_ref()
+            .metadata
+            .subnet_call_context_manager
+            .canister_http_request_contexts;
 
         let key_from_share =
             |share: &CanisterHttpResponseShare| (share.signature.signer, share.content.id);
@@ -335,6 +365,60 @@ impl CanisterHttpPoolManagerImpl {
                             .to_string(),
                     ));
                 }
+                let response = canister_http_pool
+                    .lookup_unvalidated(share)
+                    .and_then(|artifact| artifact.response);
+                match http_requests
+                    .get(&share.content.id)
+                    .map(|context| context.replication)
+                {
+                    Some(Replication::NonReplicated(node_id)) => {
+                        if share.signature.signer != node_id {
+                            return Some(CanisterHttpChangeAction::HandleInvalid(
+                                share.clone(),
+                                "Share for a non-replicated request signed by a node other than the designated node"
+                                    .to_string(),
+                            ));
+                        }
+                        // The response is gossiped with the share and must be the one
+                        // that was signed.
+                        let Some(response) = response else {
+                            return Some(CanisterHttpChangeAction::HandleInvalid(
+                                share.clone(),
+                                "Share for a non-replicated request without a response"
+                                    .to_string(),
+                            ));
+                        };
+                        if response.id != share.content.id
+                            || ic_types::crypto::crypto_hash(&response)
+                                != share.content.content_hash
+                        {
+                            return Some(CanisterHttpChangeAction::HandleInvalid(
+                                share.clone(),
+                                "Response does not match the share of a non-replicated request"
+                                    .to_string(),
+                            ));
+                        }
+                        // Otherwise the response could never be included in a block.
+                        if matches!(response.content, CanisterHttpResponseContent::Success(_))
+                            && !response.content.is_marked_unattested()
+                        {
+                            return Some(CanisterHttpChangeAction::HandleInvalid(
+                                share.clone(),
+                                "Response to a non-replicated request is not marked as unattested"
+                                    .to_string(),
+                            ));
+                        }
+                    }
+                    _ => {
+                        if response.is_some() {
+                            return Some(CanisterHttpChangeAction::HandleInvalid(
+                                share.clone(),
+                                "Share for a replicated request with a response".to_string(),
+                            ));
+                        }
+                    }
+                }
                 // TODO: more precise error handling
                 if let Err(err) = self.crypto.verify(share, registry_version) {
                     error!(self.log, "Unable to verify signature of share, {}", err);
@@ -439,17 +523,19 @@ impl<T: CanisterHttpPool> PoolMutationsProducer<T> for CanisterHttpPoolManagerIm
 #[cfg(test)]
 pub mod test {
     use super::*;
+    use candid::Encode;
     use ic_artifact_pool::canister_http_pool::CanisterHttpPoolImpl;
     use ic_consensus_mocks::{dependencies, Dependencies};
     use ic_consensus_utils::crypto::SignVerify;
     use ic_interfaces::p2p::consensus::{MutablePool, UnvalidatedArtifact};
     use ic_interfaces_state_manager::Labeled;
     use ic_logger::replica_logger::no_op_logger;
+    use ic_management_canister_types::CanisterHttpResponsePayload;
     use ic_metrics::MetricsRegistry;
     use ic_registry_subnet_type::SubnetType;
     use ic_replicated_state::metadata_state::subnet_call_context_manager::SubnetCallContext;
     use ic_test_utilities_logger::with_test_replica_logger;
-    use ic_test_utilities_types::ids::subnet_test_id;
+    use ic_test_utilities_types::ids::{node_test_id, subnet_test_id};
     use ic_types::{
         crypto::{CryptoHash, CryptoHashOf},
         messages::CallbackId,
@@ -526,6 +612,8 @@ pub mod test {
                     http_method: CanisterHttpMethod::GET,
                     transform: None,
                     time: ic_types::Time::from_nanos_since_unix_epoch(10),
+                    response_range: None,
+                    replication: Replication::FullyReplicated,
                 };
 
                 state_manager
@@ -565,7 +653,10 @@ pub mod test {
                         signature,
                     };
                     canister_http_pool.insert(UnvalidatedArtifact {
-                        message: share,
+                        message: CanisterHttpResponseArtifact {
+                            share,
+                            response: None,
+                        },
                         peer_id: replica_config.node_id,
                         timestamp: UNIX_EPOCH,
                     });
@@ -623,6 +714,8 @@ pub mod test {
                     http_method: CanisterHttpMethod::GET,
                     transform: None,
                     time: ic_types::Time::from_nanos_since_unix_epoch(10),
+                    response_range: None,
+                    replication: Replication::FullyReplicated,
                 };
 
                 state_manager
@@ -682,7 +775,10 @@ pub mod test {
                         signature,
                     };
                     canister_http_pool.insert(UnvalidatedArtifact {
-                        message: share,
+                        message: CanisterHttpResponseArtifact {
+                            share,
+                            response: None,
+                        },
                         peer_id: replica_config.node_id,
                         timestamp: UNIX_EPOCH,
                     });
@@ -747,6 +843,8 @@ pub mod test {
                     http_method: CanisterHttpMethod::GET,
                     transform: None,
                     time: ic_types::Time::from_nanos_since_unix_epoch(10),
+                    response_range: None,
+                    replication: Replication::FullyReplicated,
                 };
 
                 state_manager
@@ -891,6 +989,8 @@ pub mod test {
                     http_method: CanisterHttpMethod::GET,
                     transform: None,
                     time: ic_types::Time::from_nanos_since_unix_epoch(10),
+                    response_range: None,
+                    replication: Replication::FullyReplicated,
                 };
 
                 // Expect times to be called exactly once to check that already
@@ -966,4 +1066,204 @@ pub mod test {
             });
         });
     }
+
+    #[test]
+    pub fn test_non_replicated_requests_only_submitted_by_designated_node() {
+        ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
+            with_test_replica_logger(|log| {
+                let Dependencies {
+                    pool,
+                    replica_config,
+                    crypto,
+                    state_manager,
+                    registry,
+                    ..
+                } = dependencies(pool_config.clone(), 4);
+                let mut shim_mock = MockNonBlockingChannel::<CanisterHttpRequest>::new();
+                shim_mock
+                    .expect_try_receive()
+                    .return_const(Err(TryReceiveError::Empty));
+
+                let request = |replication| CanisterHttpRequestContext {
+                    request: ic_test_utilities_types::messages::RequestBuilder::new().build(),
+                    url: "".to_string(),
+                    max_response_bytes: None,
+                    headers: vec![],
+                    body: None,
+                    http_method: CanisterHttpMethod::POST,
+                    transform: None,
+                    time: ic_types::Time::from_nanos_since_unix_epoch(10),
+                    response_range: None,
+                    replication,
+                };
+                let own_request = request(Replication::NonReplicated(replica_config.node_id));
+                let other_request = request(Replication::NonReplicated(node_test_id(100)));
+
+                // Only the request assigned to this node is sent to the adapter.
+                shim_mock
+                    .expect_send()
+                    .with(eq(CanisterHttpRequest {
+                        id: CallbackId::from(7),
+                        timeout: ic_types::Time::from_nanos_since_unix_epoch(10)
+                            + Duration::from_secs(60 * 5),
+                        context: own_request.clone(),
+                    }))
+                    .times(1)
+                    .return_const(Ok(()));
+
+                let shim: Arc<Mutex<CanisterHttpAdapterClient>> =
+                    Arc::new(Mutex::new(Box::new(shim_mock)));
+
+                state_manager
+                    .get_mut()
+                    .expect_get_latest_state()
+                    .return_const(Labeled::new(
+                        Height::from(1),
+                        Arc::new(state_with_pending_http_calls(BTreeMap::from([
+                            (CallbackId::from(7), own_request),
+                            (CallbackId::from(8), other_request),
+                        ]))),
+                    ));
+
+                let pool_manager = CanisterHttpPoolManagerImpl::new(
+                    state_manager,
+                    shim,
+                    crypto,
+                    pool.get_cache(),
+                    replica_config,
+                    Arc::clone(&registry) as Arc<_>,
+                    MetricsRegistry::new(),
+                    log,
+                );
+                let canister_http_pool =
+                    CanisterHttpPoolImpl::new(MetricsRegistry::new(), no_op_logger());
+                let change_set = pool_manager.generate_change_set(&canister_http_pool);
+                assert_eq!(change_set.len(), 0);
+            });
+        });
+    }
+
+    #[test]
+    pub fn test_validation_of_non_replicated_shares() {
+        ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
+            with_test_replica_logger(|log| {
+                let Dependencies {
+                    pool,
+                    replica_config,
+                    crypto,
+                    state_manager,
+                    registry,
+                    ..
+                } = dependencies(pool_config.clone(), 4);
+                let shim_mock = MockNonBlockingChannel::<CanisterHttpRequest>::new();
+
+                let request = CanisterHttpRequestContext {
+                    request: ic_test_utilities_types::messages::RequestBuilder::new().build(),
+                    url: "".to_string(),
+                    max_response_bytes: None,
+                    headers: vec![],
+                    body: None,
+                    http_method: CanisterHttpMethod::POST,
+                    transform: None,
+                    time: ic_types::Time::from_nanos_since_unix_epoch(10),
+                    response_range: None,
+                    replication: Replication::NonReplicated(replica_config.node_id),
+                };
+
+                state_manager
+                    .get_mut()
+                    .expect_get_latest_state()
+                    .return_const(Labeled::new(
+                        Height::from(1),
+                        Arc::new(state_with_pending_http_calls(BTreeMap::from([(
+                            CallbackId::from(0),
+                            request,
+                        )]))),
+                    ));
+
+                let payload = Encode!(&CanisterHttpResponsePayload {
+                    status: 200,
+                    headers: vec![],
+                    body: vec![],
+                    is_replicated: None,
+                })
+                .unwrap();
+                let response_with_payload = |payload| CanisterHttpResponse {
+                    content: CanisterHttpResponseContent::Success(payload),
+                    ..empty_canister_http_response(0)
+                };
+                let response = response_with_payload(mark_unattested(&payload).unwrap());
+                let unmarked_response = response_with_payload(payload);
+                let share_of = |response: &CanisterHttpResponse| {
+                    let response_metadata = CanisterHttpResponseMetadata {
+                        id: CallbackId::from(0),
+                        timeout: response.timeout,
+                        registry_version: RegistryVersion::from(1),
+                        content_hash: ic_types::crypto::crypto_hash(response),
+                    };
+                    let signature = crypto
+                        .sign(
+                            &response_metadata,
+                            replica_config.node_id,
+                            RegistryVersion::from(1),
+                        )
+                        .unwrap();
+                    Signed {
+                        content: response_metadata,
+                        signature,
+                    }
+                };
+                let share = share_of(&response);
+                let unmarked_share = share_of(&unmarked_response);
+
+                let shim: Arc<Mutex<CanisterHttpAdapterClient>> =
+                    Arc::new(Mutex::new(Box::new(shim_mock)));
+                let pool_manager = CanisterHttpPoolManagerImpl::new(
+                    state_manager as Arc<_>,
+                    shim,
+                    crypto,
+                    pool.get_cache(),
+                    replica_config.clone(),
+                    Arc::clone(&registry) as Arc<_>,
+                    MetricsRegistry::new(),
+                    log,
+                );
+
+                let validate = |share: &CanisterHttpResponseShare, response| {
+                    let mut canister_http_pool =
+                        CanisterHttpPoolImpl::new(MetricsRegistry::new(), no_op_logger());
+                    canister_http_pool.insert(UnvalidatedArtifact {
+                        message: CanisterHttpResponseArtifact {
+                            share: share.clone(),
+                            response,
+                        },
+                        peer_id: replica_config.node_id,
+                        timestamp: UNIX_EPOCH,
+                    });
+                    pool_manager.validate_shares(
+                        pool.get_cache().as_ref(),
+                        &canister_http_pool,
+                        Height::from(0),
+                    )
+                };
+
+                // The share is only valid if it comes with the response it signs.
+                assert!(matches!(
+                    validate(&share, Some(response))[..],
+                    [CanisterHttpChangeAction::MoveToValidated(_)]
+                ));
+                for response in [None, Some(empty_canister_http_response(1))] {
+                    assert!(matches!(
+                        validate(&share, response)[..],
+                        [CanisterHttpChangeAction::HandleInvalid(..)]
+                    ));
+                }
+                // The response must be marked as unattested.
+                assert!(matches!(
+                    validate(&unmarked_share, Some(unmarked_response))[..],
+                    [CanisterHttpChangeAction::HandleInvalid(..)]
+                ));
+            });
+        });
+    }
 }
@@ -4,7 +4,9 @@ use ic_base_types::RegistryVersion;
 use ic_protobuf::proxy::ProxyDecodeError;
 use ic_types::{
     artifact::CanisterHttpResponseId,
-    canister_http::{CanisterHttpResponse, CanisterHttpResponseShare},
+    canister_http::{
+        CanisterHttpResponse, CanisterHttpResponseArtifact, CanisterHttpResponseShare,
+    },
     consensus::Threshold,
     crypto::{CryptoError, CryptoHashOf},
     messages::CallbackId,
@@ -68,6 +70,17 @@ pub enum InvalidCanisterHttpPayloadReason {
     DuplicateResponse(CallbackId),
     DivergenceProofContainsMultipleCallbackIds,
     DivergenceProofDoesNotMeetDivergenceCriteria,
+    /// The response to a non-replicated request is not signed by exactly the
+    /// node that the request was assigned to
+    NotSignedByDesignatedNode {
+        callback_id: CallbackId,
+        designated_node: NodeId,
+        signers: Vec<NodeId>,
+    },
+    /// A divergence proof was included for a non-replicated request
+    DivergenceProofForNonReplicatedRequest(CallbackId),
+    /// The response to a non-replicated request is not marked as unattested
+    NonReplicatedResponseNotMarked(CallbackId),
     /// The payload could not be deserialized
     DecodeError(ProxyDecodeError),
 }
@@ -90,6 +103,10 @@ pub type CanisterHttpPayloadValidationError =
 
 pub enum CanisterHttpChangeAction {
     AddToValidated(CanisterHttpResponseShare, CanisterHttpResponse),
+    /// Like `AddToValidated`, but the response is gossiped along with the
+    /// share. Used for non-replicated requests, whose response only the node
+    /// that made the request has.
+    AddToValidatedAndGossipResponse(CanisterHttpResponseShare, CanisterHttpResponse),
     MoveToValidated(CanisterHttpResponseShare),
     RemoveValidated(CanisterHttpResponseId),
     RemoveUnvalidated(CanisterHttpResponseId),
@@ -116,10 +133,10 @@ pub trait CanisterHttpPool: Send + Sync {
     fn lookup_validated(
         &self,
         msg_id: &CanisterHttpResponseId,
-    ) -> Option<CanisterHttpResponseShare>;
+    ) -> Option<CanisterHttpResponseArtifact>;
 
     fn lookup_unvalidated(
         &self,
         msg_id: &CanisterHttpResponseId,
-    ) -> Option<CanisterHttpResponseShare>;
+    ) -> Option<CanisterHttpResponseArtifact>;
 }
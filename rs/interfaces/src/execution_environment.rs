@@ -155,6 +155,16 @@ pub enum SystemApiCallId {
     CanisterVersion,
     /// Tracker for `ic0.certified_data_set()`
     CertifiedDataSet,
+    /// Tracker for `ic0.cost_call()`
+    CostCall,
+    /// Tracker for `ic0.cost_create_canister()`
+    CostCreateCanister,
+    /// Tracker for `ic0.cost_http_request()`
+    CostHttpRequest,
+    /// Tracker for `ic0.cost_sign_with_ecdsa()`
+    CostSignWithEcdsa,
+    /// Tracker for `ic0.cost_sign_with_schnorr()`
+    CostSignWithSchnorr,
     /// Tracker for `ic0.cycles_burn128()`
     CyclesBurn128,
     /// Tracker for `ic0.data_certificate_copy()`
@@ -165,6 +175,18 @@ pub enum SystemApiCallId {
     DataCertificateSize,
     /// Tracker for `ic0.debug_print()`
     DebugPrint,
+    /// Tracker for `ic0.env_var_count()`
+    EnvVarCount,
+    /// Tracker for `ic0.env_var_name_copy()`
+    EnvVarNameCopy,
+    /// Tracker for `ic0.env_var_name_exists()`
+    EnvVarNameExists,
+    /// Tracker for `ic0.env_var_name_size()`
+    EnvVarNameSize,
+    /// Tracker for `ic0.env_var_value_copy()`
+    EnvVarValueCopy,
+    /// Tracker for `ic0.env_var_value_size()`
+    EnvVarValueSize,
     /// Tracker for `ic0.global_timer_set()`
     GlobalTimerSet,
     /// Tracker for `ic0.in_replicated_execution()`
@@ -1151,6 +1173,138 @@ pub trait SystemApi {
         dst: usize,
         heap: &mut [u8],
     ) -> HypervisorResult<()>;
+
+    /// Copies to `dst` the amount of cycles (as a 128-bit value) that
+    /// performing a call to a method with a name of `method_name_size` bytes
+    /// and a payload of `payload_size` bytes costs, excluding any cycles
+    /// attached to the call.
+    ///
+    /// This system call traps if dst+16 exceeds the size of the WebAssembly memory.
+    fn ic0_cost_call(
+        &self,
+        method_name_size: u64,
+        payload_size: u64,
+        dst: usize,
+        heap: &mut [u8],
+    ) -> HypervisorResult<()>;
+
+    /// Copies to `dst` the amount of cycles (as a 128-bit value) that
+    /// creating a canister on this subnet costs.
+    ///
+    /// This system call traps if dst+16 exceeds the size of the WebAssembly memory.
+    fn ic0_cost_create_canister(&self, dst: usize, heap: &mut [u8]) -> HypervisorResult<()>;
+
+    /// Copies to `dst` the amount of cycles (as a 128-bit value) that an
+    /// HTTPS outcall with a request of `request_size` bytes and a response
+    /// limit of `max_res_bytes` bytes costs.
+    ///
+    /// This system call traps if dst+16 exceeds the size of the WebAssembly memory.
+    fn ic0_cost_http_request(
+        &self,
+        request_size: u64,
+        max_res_bytes: u64,
+        dst: usize,
+        heap: &mut [u8],
+    ) -> HypervisorResult<()>;
+
+    /// Copies to `dst` the amount of cycles (as a 128-bit value) that a
+    /// `sign_with_ecdsa` call with the key named by src/size on the given
+    /// curve costs.
+    ///
+    /// Returns 0 on success, 1 if the curve is unknown and 2 if no subnet
+    /// is enabled to sign with the key. Nothing is copied in the error cases.
+    ///
+    /// This system call traps if src+size or dst+16 exceed the size of the
+    /// WebAssembly memory.
+    fn ic0_cost_sign_with_ecdsa(
+        &self,
+        src: usize,
+        size: usize,
+        curve: u32,
+        dst: usize,
+        heap: &mut [u8],
+    ) -> HypervisorResult<u32>;
+
+    /// Copies to `dst` the amount of cycles (as a 128-bit value) that a
+    /// `sign_with_schnorr` call with the key named by src/size for the given
+    /// algorithm costs.
+    ///
+    /// Returns 0 on success, 1 if the algorithm is unknown and 2 if no subnet
+    /// is enabled to sign with the key. Nothing is copied in the error cases.
+    ///
+    /// This system call traps if src+size or dst+16 exceed the size of the
+    /// WebAssembly memory.
+    fn ic0_cost_sign_with_schnorr(
+        &self,
+        src: usize,
+        size: usize,
+        algorithm: u32,
+        dst: usize,
+        heap: &mut [u8],
+    ) -> HypervisorResult<u32>;
+
+    /// Returns the number of environment variables set for the canister.
+    fn ic0_env_var_count(&self) -> HypervisorResult<usize>;
+
+    /// Returns the size of the name of the environment variable at `index`.
+    /// Variables are ordered by name.
+    ///
+    /// Traps if `index` is not smaller than the number of variables.
+    fn ic0_env_var_name_size(&self, index: usize) -> HypervisorResult<usize>;
+
+    /// Copies `size` bytes of the name of the environment variable at
+    /// `index`, starting at `offset`, to `dst` in the heap.
+    ///
+    /// Traps if `index` is not smaller than the number of variables or if
+    /// the source or destination ranges are out of bounds.
+    fn ic0_env_var_name_copy(
+        &self,
+        index: usize,
+        dst: usize,
+        offset: usize,
+        size: usize,
+        heap: &mut [u8],
+    ) -> HypervisorResult<()>;
+
+    /// Returns 1 if an environment variable with the name given by
+    /// `name_src`/`name_size` exists and 0 otherwise.
+    ///
+    /// Traps if the name is out of bounds or is not valid UTF-8.
+    fn ic0_env_var_name_exists(
+        &self,
+        name_src: usize,
+        name_size: usize,
+        heap: &[u8],
+    ) -> HypervisorResult<i32>;
+
+    /// Returns the size of the value of the environment variable with the
+    /// name given by `name_src`/`name_size`.
+    ///
+    /// Traps if the name is out of bounds, is not valid UTF-8 or no such
+    /// variable exists.
+    fn ic0_env_var_value_size(
+        &self,
+        name_src: usize,
+        name_size: usize,
+        heap: &[u8],
+    ) -> HypervisorResult<usize>;
+
+    /// Copies `size` bytes of the value of the environment variable with the
+    /// name given by `name_src`/`name_size`, starting at `offset`, to `dst`
+    /// in the heap.
+    ///
+    /// Traps if the name is out of bounds, is not valid UTF-8 or no such
+    /// variable exists, or if the source or destination ranges are out of
+    /// bounds.
+    fn ic0_env_var_value_copy(
+        &self,
+        name_src: usize,
+        name_size: usize,
+        dst: usize,
+        offset: usize,
+        size: usize,
+        heap: &mut [u8],
+    ) -> HypervisorResult<()>;
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
@@ -1266,6 +1420,19 @@ pub struct WasmExecutionOutput {
     /// How many times each tracked System API call was invoked.
     pub system_api_call_counters: SystemApiCallCounters,
     pub canister_log: CanisterLog,
+    /// Instructions executed by each Wasm function. Only present if
+    /// instruction profiling is enabled.
+    pub instruction_profile: Option<InstructionProfile>,
+}
+
+/// Instructions executed by the Wasm functions of a canister during a single
+/// message execution.
+#[derive(Clone, PartialEq, Eq, Debug, Default, Deserialize, Serialize)]
+pub struct InstructionProfile {
+    /// Maps a call stack of function indices (outermost function first) to the
+    /// number of instructions executed by the innermost function of that stack,
+    /// excluding the instructions executed by its callees.
+    pub stacks: BTreeMap<Vec<u32>, u64>,
 }
 
 impl fmt::Display for WasmExecutionOutput {
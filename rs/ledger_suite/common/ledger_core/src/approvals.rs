@@ -7,6 +7,7 @@ use std::collections::{BTreeMap, BTreeSet};
 use std::{
     borrow::Cow,
     io::{Cursor, Read},
+    ops::Bound,
 };
 
 #[cfg(test)]
@@ -76,6 +77,14 @@ pub trait AllowancesData {
 
     fn oldest_arrivals(&self, n: usize) -> Vec<(Self::AccountId, Self::AccountId)>;
 
+    /// Returns an iterator over the allowances whose (account, spender) key
+    /// is in the range `(start, unbounded)`, in key order.
+    #[allow(clippy::type_complexity)]
+    fn allowances_from(
+        &self,
+        start: Bound<(Self::AccountId, Self::AccountId)>,
+    ) -> Box<dyn Iterator<Item = ((Self::AccountId, Self::AccountId), Allowance<Self::Tokens>)> + '_>;
+
     fn len_allowances(&self) -> usize;
 
     fn len_expirations(&self) -> usize;
@@ -184,6 +193,18 @@ where
         result
     }
 
+    fn allowances_from(
+        &self,
+        start: Bound<(Self::AccountId, Self::AccountId)>,
+    ) -> Box<dyn Iterator<Item = ((Self::AccountId, Self::AccountId), Allowance<Self::Tokens>)> + '_>
+    {
+        Box::new(
+            self.allowances
+                .range((start, Bound::Unbounded))
+                .map(|(key, allowance)| (key.clone(), allowance.clone())),
+        )
+    }
+
     fn len_allowances(&self) -> usize {
         self.allowances.len()
     }
@@ -436,6 +457,26 @@ where
         self.allowances_data.oldest_arrivals(n)
     }
 
+    /// Returns at most `limit` allowances that have not expired at `now`,
+    /// ordered by (account, spender) and starting at the `start` key.
+    /// The iteration stops at the first approving account for which
+    /// `take_while` returns false.
+    #[allow(clippy::type_complexity)]
+    pub fn allowances_from(
+        &self,
+        start: Bound<(AD::AccountId, AD::AccountId)>,
+        now: TimeStamp,
+        limit: usize,
+        take_while: impl Fn(&AD::AccountId) -> bool,
+    ) -> Vec<((AD::AccountId, AD::AccountId), Allowance<AD::Tokens>)> {
+        self.allowances_data
+            .allowances_from(start)
+            .take_while(|((account, _spender), _allowance)| take_while(account))
+            .filter(|(_key, allowance)| allowance.expires_at.unwrap_or_else(remote_future) > now)
+            .take(limit)
+            .collect()
+    }
+
     /// Prunes allowances that are expired, removes at most `limit` allowances.
     pub fn prune(&mut self, now: TimeStamp, limit: usize) -> usize {
         self.with_postconditions_check(|table| {
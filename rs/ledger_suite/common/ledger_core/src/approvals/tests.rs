@@ -529,6 +529,56 @@ fn allowance_table_select_approvals_for_trimming() {
     );
 }
 
+#[test]
+fn allowance_table_allowances_from() {
+    let mut table = TestAllowanceTable::default();
+
+    for account in 1..4 {
+        for spender in 10..15 {
+            let expiration = if spender == 12 { Some(ts(5)) } else { None };
+            table
+                .approve(
+                    &Account(account),
+                    &Account(spender),
+                    tokens(spender),
+                    expiration,
+                    ts(1),
+                    None,
+                )
+                .unwrap();
+        }
+    }
+
+    fn keys(allowances: Vec<((Account, Account), Allowance<Tokens>)>) -> Vec<(u64, u64)> {
+        allowances
+            .into_iter()
+            .map(|((account, spender), _)| (account.0, spender.0))
+            .collect()
+    }
+
+    // Expired allowances are skipped and iteration stops when take_while fails.
+    let result = table.allowances_from(
+        Bound::Included((Account(2), Account(0))),
+        ts(10),
+        100,
+        |account| account.0 == 2,
+    );
+    assert_eq!(keys(result), vec![(2, 10), (2, 11), (2, 13), (2, 14)]);
+
+    // The start key is excluded and the limit is respected.
+    let result =
+        table.allowances_from(Bound::Excluded((Account(1), Account(10))), ts(1), 3, |_| {
+            true
+        });
+    assert_eq!(keys(result), vec![(1, 11), (1, 12), (1, 13)]);
+
+    let result =
+        table.allowances_from(Bound::Excluded((Account(3), Account(14))), ts(1), 3, |_| {
+            true
+        });
+    assert!(result.is_empty());
+}
+
 #[test]
 fn arrival_table_updated_correctly() {
     let mut table = TestAllowanceTable::default();
@@ -68,6 +68,42 @@ pub struct GetAccountTransactionsError {
 pub type GetAccountTransactionsResult =
     Result<GetAccountTransactionsResponse, GetAccountTransactionsError>;
 
+#[derive(Clone, Eq, PartialEq, Debug, CandidType, Deserialize)]
+pub enum BalanceAt {
+    // The balance after the block with the given index has been applied.
+    BlockIndex(BlockIndex),
+    // The balance after all the blocks with a timestamp not after the given
+    // one, in nanoseconds since the UNIX epoch, have been applied.
+    Timestamp(u64),
+}
+
+#[derive(Clone, Eq, PartialEq, Debug, CandidType, Deserialize)]
+pub struct GetAccountBalanceAtArgs {
+    pub account: Account,
+    pub at: BalanceAt,
+}
+
+#[derive(Eq, PartialEq, Debug, CandidType, Deserialize)]
+pub struct GetAccountBalanceAtResponse {
+    pub balance: Nat,
+    // The index of the last block taken into account, i.e., the
+    // requested block index or the last block at the requested timestamp.
+    // None if there are no blocks at the requested timestamp.
+    pub block_index: Option<BlockIndex>,
+}
+
+#[derive(Eq, PartialEq, Debug, CandidType, Deserialize)]
+pub enum GetAccountBalanceAtError {
+    // The requested block index hasn't been indexed yet.
+    BlockNotIndexed { num_blocks_synced: BlockIndex },
+    // The index is still recording the balances after the blocks it indexed
+    // before an upgrade. The balances are available once all the blocks
+    // have been recorded.
+    BalancesNotRecorded { num_blocks_recorded: BlockIndex },
+}
+
+pub type GetAccountBalanceAtResult = Result<GetAccountBalanceAtResponse, GetAccountBalanceAtError>;
+
 #[derive(Eq, PartialEq, Debug, CandidType, Deserialize)]
 pub struct ListSubaccountsArgs {
     pub owner: Principal,
@@ -110,3 +146,116 @@ pub enum GetBlocksMethod {
     // ICRC-3 compatible Ledgers.
     ICRC3GetBlocks,
 }
+
+/// The format of an account history export served by `http_request`.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, CandidType, Deserialize)]
+pub enum AccountHistoryFormat {
+    Csv,
+    Jsonl,
+}
+
+impl AccountHistoryFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv; charset=utf-8",
+            Self::Jsonl => "application/jsonl; charset=utf-8",
+        }
+    }
+}
+
+/// A single line of an account history export.
+///
+/// Amounts and balances are encoded as decimal strings because
+/// they may not fit into a JSON number.
+#[derive(Clone, Eq, PartialEq, Debug, Deserialize, serde::Serialize)]
+pub struct AccountHistoryEntry {
+    pub block_index: u64,
+    pub timestamp: u64,
+    pub kind: String,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub spender: Option<String>,
+    pub amount: String,
+    pub fee: Option<String>,
+    // The memo of the transaction, hex encoded.
+    pub memo: Option<String>,
+    // The balance of the exported account after the transaction.
+    pub balance: String,
+}
+
+/// The header line of an account history export in CSV format.
+pub const ACCOUNT_HISTORY_CSV_HEADER: &str =
+    "block_index,timestamp,kind,from,to,spender,amount,fee,memo,balance\n";
+
+impl AccountHistoryEntry {
+    /// Returns the entry as a CSV line matching [ACCOUNT_HISTORY_CSV_HEADER].
+    /// Missing optional fields are left empty. None of the fields can
+    /// contain a comma so no quoting is needed.
+    pub fn to_csv_line(&self) -> String {
+        let opt = |field: &Option<String>| field.clone().unwrap_or_default();
+        format!(
+            "{},{},{},{},{},{},{},{},{},{}\n",
+            self.block_index,
+            self.timestamp,
+            self.kind,
+            opt(&self.from),
+            opt(&self.to),
+            opt(&self.spender),
+            self.amount,
+            opt(&self.fee),
+            opt(&self.memo),
+            self.balance,
+        )
+    }
+}
+
+/// The token used to resume an account history export
+/// in `http_request_streaming_callback`.
+#[derive(Clone, Eq, PartialEq, Debug, CandidType, Deserialize)]
+pub struct AccountHistoryStreamingToken {
+    pub account: Account,
+    pub format: AccountHistoryFormat,
+    // The export continues with the transactions older than
+    // this block index (start won't be included).
+    pub start: BlockIndex,
+    // The balance of the account right after the next
+    // transaction to export.
+    pub balance: Nat,
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct StreamingCallback {
+    pub callback: candid::Func,
+    pub token: AccountHistoryStreamingToken,
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub enum StreamingStrategy {
+    Callback(StreamingCallback),
+}
+
+/// An HTTP response that can be continued via a streaming callback.
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct StreamingHttpResponse {
+    pub status_code: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: serde_bytes::ByteBuf,
+    pub streaming_strategy: Option<StreamingStrategy>,
+}
+
+impl From<ic_canisters_http_types::HttpResponse> for StreamingHttpResponse {
+    fn from(response: ic_canisters_http_types::HttpResponse) -> Self {
+        Self {
+            status_code: response.status_code,
+            headers: response.headers,
+            body: response.body,
+            streaming_strategy: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct StreamingCallbackHttpResponse {
+    pub body: serde_bytes::ByteBuf,
+    pub token: Option<AccountHistoryStreamingToken>,
+}
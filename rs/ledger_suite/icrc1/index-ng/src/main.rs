@@ -10,9 +10,13 @@ use ic_icrc1::blocks::{encoded_block_to_generic_block, generic_block_to_encoded_
 use ic_icrc1::endpoints::StandardRecord;
 use ic_icrc1::{Block, Operation};
 use ic_icrc1_index_ng::{
-    FeeCollectorRanges, GetAccountTransactionsArgs, GetAccountTransactionsResponse,
-    GetAccountTransactionsResult, GetBlocksMethod, IndexArg, InitArg, ListSubaccountsArgs, Log,
-    LogEntry, Status, TransactionWithId, UpgradeArg, DEFAULT_MAX_BLOCKS_PER_RESPONSE,
+    AccountHistoryEntry, AccountHistoryFormat, AccountHistoryStreamingToken, BalanceAt,
+    FeeCollectorRanges, GetAccountBalanceAtArgs, GetAccountBalanceAtError,
+    GetAccountBalanceAtResponse, GetAccountBalanceAtResult, GetAccountTransactionsArgs,
+    GetAccountTransactionsResponse, GetAccountTransactionsResult, GetBlocksMethod, IndexArg,
+    InitArg, ListSubaccountsArgs, Log, LogEntry, Status, StreamingCallback,
+    StreamingCallbackHttpResponse, StreamingHttpResponse, StreamingStrategy, TransactionWithId,
+    UpgradeArg, ACCOUNT_HISTORY_CSV_HEADER, DEFAULT_MAX_BLOCKS_PER_RESPONSE,
 };
 use ic_ledger_canister_core::runtime::total_memory_size_bytes;
 use ic_ledger_core::block::{BlockIndex as BlockIndex64, BlockType, EncodedBlock};
@@ -34,16 +38,18 @@ use icrc_ledger_types::icrc3::transactions::Transaction;
 use num_traits::ToPrimitive;
 use scopeguard::guard;
 use serde::{Deserialize, Serialize};
+use serde_bytes::ByteBuf;
 use std::borrow::Cow;
 use std::cell::RefCell;
 use std::cmp::Reverse;
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::convert::TryFrom;
 use std::fmt::Debug;
 use std::hash::Hash;
 use std::io::Read;
 use std::ops::Bound::{Excluded, Included};
 use std::ops::Range;
+use std::str::FromStr;
 use std::time::Duration;
 
 pub mod logs;
@@ -55,9 +61,18 @@ const BLOCK_LOG_INDEX_MEMORY_ID: MemoryId = MemoryId::new(1);
 const BLOCK_LOG_DATA_MEMORY_ID: MemoryId = MemoryId::new(2);
 const ACCOUNT_BLOCK_IDS_MEMORY_ID: MemoryId = MemoryId::new(3);
 const ACCOUNT_DATA_MEMORY_ID: MemoryId = MemoryId::new(4);
+const ACCOUNT_BALANCES_MEMORY_ID: MemoryId = MemoryId::new(5);
 
 const DEFAULT_RETRIEVE_BLOCKS_FROM_LEDGER_INTERVAL: Duration = Duration::from_secs(1);
 
+/// The maximum number of blocks whose balance changes are recorded in
+/// [ACCOUNT_BALANCES] by a single call to [backfill_account_balances].
+const ACCOUNT_BALANCES_BACKFILL_BATCH_SIZE: u64 = 2_000;
+
+/// The maximum number of blocks in the fee collector ranges of an account
+/// that are decoded to produce a single chunk of its history.
+const MAX_FEE_COLLECTOR_BLOCKS_PER_CHUNK: u64 = 10_000;
+
 #[cfg(not(feature = "u256-tokens"))]
 type Tokens = ic_icrc1_tokens_u64::U64;
 
@@ -77,6 +92,10 @@ type AccountBlockIdsMap = StableBTreeMap<AccountBlockIdsMapKey, (), VM>;
 type AccountDataMapKey = (AccountDataType, (Blob<29>, [u8; 32]));
 type AccountDataMap = StableBTreeMap<AccountDataMapKey, Tokens, VM>;
 
+// The balances of an account after the blocks that changed it, keyed like
+// the account block ids so that the last balance up to a block comes first.
+type AccountBalancesMap = StableBTreeMap<AccountBlockIdsMapKey, Tokens, VM>;
+
 thread_local! {
     /// Static memory manager to manage the memory available for stable structures.
     static MEMORY_MANAGER: RefCell<MemoryManager<DefaultMemoryImpl>> = RefCell::new(MemoryManager::init(DefaultMemoryImpl::default()));
@@ -104,6 +123,12 @@ thread_local! {
         RefCell::new(AccountDataMap::init(memory_manager.get(ACCOUNT_DATA_MEMORY_ID)))
     });
 
+    /// Map that contains the balance of an account after each block that
+    /// changed it. The account is hashed to save space.
+    static ACCOUNT_BALANCES: RefCell<AccountBalancesMap> = with_memory_manager(|memory_manager| {
+        RefCell::new(AccountBalancesMap::init(memory_manager.get(ACCOUNT_BALANCES_MEMORY_ID)))
+    });
+
     /// Profiling data to understand cycles usage
     static PROFILING_DATA: RefCell<SpanStats> = RefCell::new(SpanStats::default());
 
@@ -132,10 +157,20 @@ struct State {
     /// This fee is used if no fee nor effetive_fee is found in Approve blocks.
     pub last_fee: Option<Tokens>,
 
+    /// The transfer fees by the index of the block where they changed, i.e.
+    /// the block whose fee differs from the fee of the transfer before it.
+    #[serde(default)]
+    transfer_fee_changes: BTreeMap<BlockIndex64, Tokens>,
+
     /// The interval for retrieving blocks from the ledger and archive(s) for (re)building the
     /// index. Lower values will result in a more responsive UI, but higher costs due to increased
     /// cycle burn for the index, ledger and archive(s).
     retrieve_blocks_from_ledger_interval: Option<Duration>,
+
+    /// The blocks whose balance changes are not recorded in [ACCOUNT_BALANCES]
+    /// yet because they were indexed before the map existed. They are
+    /// recorded in batches by [build_index].
+    account_balances_backfill: Option<Range<BlockIndex64>>,
 }
 
 impl State {
@@ -156,7 +191,9 @@ impl Default for State {
             last_wait_time: Duration::from_secs(0),
             fee_collectors: Default::default(),
             last_fee: None,
+            transfer_fee_changes: BTreeMap::new(),
             retrieve_blocks_from_ledger_interval: None,
+            account_balances_backfill: None,
         }
     }
 }
@@ -259,6 +296,11 @@ fn with_account_data<R>(f: impl FnOnce(&mut AccountDataMap) -> R) -> R {
     ACCOUNT_DATA.with(|cell| f(&mut cell.borrow_mut()))
 }
 
+/// A helper function to access the account balances after each block.
+fn with_account_balances<R>(f: impl FnOnce(&mut AccountBalancesMap) -> R) -> R {
+    ACCOUNT_BALANCES.with(|cell| f(&mut cell.borrow_mut()))
+}
+
 /// A helper function that returns a decoded block stored in the
 /// block log at the given index or None if there is no block at that index.
 /// This function can trap if the index at the given block cannot be decoded
@@ -371,6 +413,23 @@ fn post_upgrade(index_arg: Option<IndexArg>) {
         _ => (),
     };
 
+    // The balances after each block are recorded since the blocks are
+    // appended, so an index upgraded from a version that didn't record them
+    // has to backfill the ones of the blocks it already indexed.
+    let num_blocks = with_blocks(|blocks| blocks.len());
+    let has_account_balances = with_account_balances(|balances| !balances.is_empty());
+    if num_blocks > 0
+        && !has_account_balances
+        && with_state(|state| state.account_balances_backfill.is_none())
+    {
+        log!(
+            P1,
+            "Recording the account balances of the {} blocks already indexed",
+            num_blocks
+        );
+        mutate_state(|state| state.account_balances_backfill = Some(0..num_blocks));
+    }
+
     // set the first build_index to be called after init
     set_build_index_timer(with_state(|state| {
         state.retrieve_blocks_from_ledger_interval()
@@ -560,6 +619,7 @@ pub async fn build_index() -> Option<()> {
             state.is_build_index_running = false;
         });
     });
+    backfill_account_balances();
     let num_indexed = match find_get_blocks_method().await {
         GetBlocksMethod::GetBlocks => fetch_blocks_via_get_blocks().await?,
         GetBlocksMethod::ICRC3GetBlocks => fetch_blocks_via_icrc3().await?,
@@ -788,6 +848,7 @@ fn process_balance_changes(block_index: BlockIndex64, block: &Block<Tokens>) {
                     ))
                 });
                 mutate_state(|s| s.last_fee = Some(fee));
+                record_transfer_fee(block_index, fee);
                 debit(
                     block_index,
                     from,
@@ -841,7 +902,8 @@ fn debit(block_index: BlockIndex64, account: Account, amount: Tokens) {
             ic_cdk::trap(&format!("Block {} caused an underflow for account {} when calculating balance {} - amount {}",
                 block_index, account, balance, amount));
         })
-    })
+    });
+    record_account_balance(block_index, account, get_balance(account));
 }
 
 fn credit(block_index: BlockIndex64, account: Account, amount: Tokens) {
@@ -851,6 +913,89 @@ fn credit(block_index: BlockIndex64, account: Account, amount: Tokens) {
                 block_index, account, balance, amount))
         })
     });
+    record_account_balance(block_index, account, get_balance(account));
+}
+
+/// Records the balance of `account` after the block with the given index.
+/// A block that changes the balance of the account more than once, e.g., a
+/// transfer to self, overwrites the record with the final balance.
+fn record_account_balance(block_index: BlockIndex64, account: Account, balance: Tokens) {
+    with_account_balances(|balances| {
+        balances.insert(account_block_ids_key(account, block_index), balance)
+    });
+}
+
+/// Returns the last balance of `account` recorded up to the block with the
+/// given index, i.e., its balance after that block, or zero if no block up
+/// to it changed the balance of the account.
+fn recorded_account_balance(account: Account, block_index: BlockIndex64) -> Tokens {
+    let key = account_block_ids_key(account, block_index);
+    with_account_balances(|balances| {
+        balances
+            .range(key..)
+            .next()
+            .filter(|(k, _)| k.0 == key.0)
+            .map(|(_, balance)| balance)
+    })
+    .unwrap_or_else(Tokens::zero)
+}
+
+/// Records the balances after a batch of the blocks that were indexed
+/// before [ACCOUNT_BALANCES] existed. The blocks are processed in order, so
+/// the balance before each block is the last one recorded for the account.
+fn backfill_account_balances() {
+    let Some(backfill) = with_state(|state| state.account_balances_backfill.clone()) else {
+        return;
+    };
+    let end = backfill
+        .end
+        .min(backfill.start + ACCOUNT_BALANCES_BACKFILL_BATCH_SIZE);
+    for block_index in backfill.start..end {
+        let block = get_decoded_block(block_index).unwrap_or_else(|| {
+            trap(&format!(
+                "Block {} not found in the block log while recording the account balances",
+                block_index
+            ))
+        });
+        if let Operation::Transfer { fee, .. } = block.transaction.operation {
+            if let Some(fee) = block.effective_fee.or(fee) {
+                record_transfer_fee(block_index, fee);
+            }
+        }
+        let mut accounts: BTreeSet<Account> = get_accounts(&block).into_iter().collect();
+        accounts.extend(get_fee_collector(block_index, &block));
+        for account in accounts {
+            let (credited, debited) = account_balance_change(account, block_index, &block);
+            if credited.is_zero() && debited.is_zero() {
+                continue;
+            }
+            let balance_before = match block_index.checked_sub(1) {
+                Some(previous_block_index) => {
+                    recorded_account_balance(account, previous_block_index)
+                }
+                None => Tokens::zero(),
+            };
+            let balance = balance_before
+                .checked_add(&credited)
+                .and_then(|balance| balance.checked_sub(&debited))
+                .unwrap_or_else(|| {
+                    trap(&format!(
+                        "Block {} caused an inconsistency when recording the balance of account {}",
+                        block_index, account
+                    ))
+                });
+            record_account_balance(block_index, account, balance);
+        }
+    }
+    mutate_state(|state| {
+        state.account_balances_backfill = (end < backfill.end).then_some(end..backfill.end)
+    });
+    log!(
+        P1,
+        "Recorded the account balances up to block {} of {}",
+        end,
+        backfill.end
+    );
 }
 
 fn generic_block_to_encoded_block_or_trap(
@@ -1034,6 +1179,64 @@ fn icrc1_balance_of(account: Account) -> Nat {
     get_balance(account).into()
 }
 
+#[query]
+#[candid_method(query)]
+fn get_account_balance_at(args: GetAccountBalanceAtArgs) -> GetAccountBalanceAtResult {
+    let num_blocks_synced = with_blocks(|blocks| blocks.len());
+    if let Some(backfill) = with_state(|state| state.account_balances_backfill.clone()) {
+        return Err(GetAccountBalanceAtError::BalancesNotRecorded {
+            num_blocks_recorded: backfill.start.into(),
+        });
+    }
+    let block_index = match args.at {
+        BalanceAt::BlockIndex(block_index) => match block_index.0.to_u64() {
+            Some(block_index) if block_index < num_blocks_synced => block_index,
+            _ => {
+                return Err(GetAccountBalanceAtError::BlockNotIndexed {
+                    num_blocks_synced: num_blocks_synced.into(),
+                })
+            }
+        },
+        BalanceAt::Timestamp(timestamp) => match get_last_block_index_at(timestamp) {
+            Some(block_index) => block_index,
+            None => {
+                return Ok(GetAccountBalanceAtResponse {
+                    balance: Nat::from(0u8),
+                    block_index: None,
+                })
+            }
+        },
+    };
+    Ok(GetAccountBalanceAtResponse {
+        balance: get_balance_at(args.account, block_index).into(),
+        block_index: Some(block_index.into()),
+    })
+}
+
+/// Returns the index of the last block with a timestamp not after `timestamp`.
+fn get_last_block_index_at(timestamp: u64) -> Option<BlockIndex64> {
+    // Block timestamps are non-decreasing so the blocks at `timestamp`
+    // are a prefix of the block log and can be found via binary search.
+    let mut low = 0;
+    let mut high = with_blocks(|blocks| blocks.len());
+    while low < high {
+        let mid = low + (high - low) / 2;
+        let block = get_decoded_block(mid)
+            .unwrap_or_else(|| trap(&format!("Block {} not found in the block log", mid)));
+        if block.timestamp <= timestamp {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    low.checked_sub(1)
+}
+
+/// Returns the balance of `account` right after the block with the given index.
+fn get_balance_at(account: Account, block_index: BlockIndex64) -> Tokens {
+    recorded_account_balance(account, block_index)
+}
+
 #[query]
 #[candid_method(query)]
 fn status() -> Status {
@@ -1069,8 +1272,11 @@ fn list_subaccounts(args: ListSubaccountsArgs) -> Vec<Subaccount> {
 }
 
 #[query(hidden = true, decoding_quota = 10000)]
-fn http_request(req: HttpRequest) -> HttpResponse {
-    if req.path() == "/metrics" {
+fn http_request(req: HttpRequest) -> StreamingHttpResponse {
+    if req.path() == "/account_history" {
+        return account_history_http_response(&req);
+    }
+    let response: HttpResponse = if req.path() == "/metrics" {
         let mut writer =
             ic_metrics_encoder::MetricsEncoder::new(vec![], ic_cdk::api::time() as i64 / 1_000_000);
 
@@ -1101,7 +1307,398 @@ fn http_request(req: HttpRequest) -> HttpResponse {
             .build()
     } else {
         HttpResponseBuilder::not_found().build()
+    };
+    response.into()
+}
+
+#[query(hidden = true)]
+fn http_request_streaming_callback(
+    token: AccountHistoryStreamingToken,
+) -> StreamingCallbackHttpResponse {
+    let (body, token) = account_history_chunk(token);
+    StreamingCallbackHttpResponse {
+        body: ByteBuf::from(body),
+        token,
+    }
+}
+
+fn account_history_http_response(req: &HttpRequest) -> StreamingHttpResponse {
+    let bad_request = |message: String| -> StreamingHttpResponse {
+        HttpResponseBuilder::bad_request()
+            .with_body_and_content_length(message)
+            .build()
+            .into()
+    };
+    let account = match req.raw_query_param("account").map(Account::from_str) {
+        Some(Ok(account)) => account,
+        Some(Err(err)) => return bad_request(format!("invalid account: {}", err)),
+        None => return bad_request("missing account parameter".to_string()),
+    };
+    let format = match req.raw_query_param("format").unwrap_or("csv") {
+        "csv" => AccountHistoryFormat::Csv,
+        "jsonl" => AccountHistoryFormat::Jsonl,
+        format => {
+            return bad_request(format!(
+                "unsupported format {}, expected csv or jsonl",
+                format
+            ))
+        }
+    };
+
+    // The transfer fees of the blocks indexed before the upgrade are
+    // recorded together with their account balances.
+    if let Some(backfill) = with_state(|state| state.account_balances_backfill.clone()) {
+        return HttpResponseBuilder::server_error(format!(
+            "the history is not available until blocks {}..{} are recorded, try again later",
+            backfill.start, backfill.end
+        ))
+        .build()
+        .into();
+    }
+
+    // The export starts from the most recent block and walks back
+    // the history of the account, so the current balance is the
+    // balance after the first exported transaction.
+    let token = AccountHistoryStreamingToken {
+        account,
+        format,
+        start: with_blocks(|blocks| blocks.len()).into(),
+        balance: get_balance(account).into(),
+    };
+    let (chunk, token) = account_history_chunk(token);
+    let mut body = match format {
+        AccountHistoryFormat::Csv => ACCOUNT_HISTORY_CSV_HEADER.as_bytes().to_vec(),
+        AccountHistoryFormat::Jsonl => vec![],
+    };
+    body.extend(chunk);
+    StreamingHttpResponse {
+        status_code: 200,
+        headers: vec![(
+            "Content-Type".to_string(),
+            format.content_type().to_string(),
+        )],
+        body: ByteBuf::from(body),
+        streaming_strategy: token.map(|token| {
+            StreamingStrategy::Callback(StreamingCallback {
+                callback: candid::Func {
+                    principal: ic_cdk::api::id(),
+                    method: "http_request_streaming_callback".to_string(),
+                },
+                token,
+            })
+        }),
+    }
+}
+
+/// Encodes the next chunk of the history of the account in the token
+/// and returns it together with the token to fetch the chunk after it,
+/// if any.
+fn account_history_chunk(
+    token: AccountHistoryStreamingToken,
+) -> (Vec<u8>, Option<AccountHistoryStreamingToken>) {
+    let start = token
+        .start
+        .0
+        .to_u64()
+        .unwrap_or_else(|| trap("start must be a u64!"));
+    let mut balance = Tokens::try_from(token.balance)
+        .unwrap_or_else(|err| trap(&format!("invalid balance in token: {}", err)));
+    let length = with_state(|state| state.max_blocks_per_response).min(usize::MAX as u64) as usize;
+    let (ids, lowest_block_index) = get_account_history_block_ids(token.account, start, length);
+    let mut body = vec![];
+    for &id in &ids {
+        let block = get_decoded_block(id).unwrap_or_else(|| {
+            trap(&format!(
+                "Block {} not found in the block log, account blocks map is corrupted!",
+                id
+            ))
+        });
+        let entry = account_history_entry(id, &block, balance);
+        match token.format {
+            AccountHistoryFormat::Csv => body.extend(entry.to_csv_line().into_bytes()),
+            AccountHistoryFormat::Jsonl => {
+                body.extend(serde_json::to_vec(&entry).unwrap_or_else(|err| {
+                    trap(&format!("failed to encode block {} to JSON: {}", id, err))
+                }));
+                body.push(b'\n');
+            }
+        }
+        balance = balance_before_block(token.account, id, &block, balance);
+    }
+    let next_start = match ids.last() {
+        Some(&last_id) if ids.len() == length => Some(last_id),
+        // Not all the blocks older than `start` were scanned.
+        _ => (lowest_block_index > 0).then_some(lowest_block_index),
+    };
+    let next_token = next_start.map(|start| AccountHistoryStreamingToken {
+        start: start.into(),
+        balance: balance.into(),
+        ..token
+    });
+    (body, next_token)
+}
+
+/// Returns up to `length` indices of the blocks older than `start` that
+/// changed the balance of `account`, from the most recent to the oldest.
+/// These are the blocks involving the account and the blocks where the
+/// account collected a fee.
+///
+/// Finding the blocks where the account collected a fee requires decoding
+/// the blocks in its fee collector ranges, so at most
+/// [MAX_FEE_COLLECTOR_BLOCKS_PER_CHUNK] of them are scanned. The second
+/// element of the result is the lowest block index that was scanned: the
+/// blocks older than it are left for the next chunk.
+fn get_account_history_block_ids(
+    account: Account,
+    start: BlockIndex64,
+    length: usize,
+) -> (Vec<BlockIndex64>, BlockIndex64) {
+    let fee_collector_ranges: Vec<Range<BlockIndex64>> =
+        with_state(|state| state.fee_collectors.get(&account).cloned())
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|range| {
+                let range = range.start..range.end.min(start);
+                (!range.is_empty()).then_some(range)
+            })
+            .collect();
+    let mut lowest_block_index = 0;
+    let mut budget = MAX_FEE_COLLECTOR_BLOCKS_PER_CHUNK;
+    for range in fee_collector_ranges.iter().rev() {
+        let range_len = range.end - range.start;
+        if range_len > budget {
+            lowest_block_index = range.end - budget;
+            break;
+        }
+        budget -= range_len;
+    }
+
+    let key = account_block_ids_key(account, start);
+    let mut ids = with_account_block_ids(|account_block_ids| {
+        account_block_ids
+            .range(key..)
+            .take_while(|(k, _)| k.0 == key.0)
+            .filter(|(k, _)| k.1 .0 < start)
+            .take_while(|(k, _)| k.1 .0 >= lowest_block_index)
+            .take(length)
+            .map(|(k, _)| k.1 .0)
+            .collect::<BTreeSet<BlockIndex64>>()
+    });
+    ids.extend(
+        fee_collector_ranges
+            .iter()
+            .rev()
+            .flat_map(|range| (range.start.max(lowest_block_index)..range.end).rev())
+            .filter(|&block_index| credits_fee_collector(account, block_index))
+            .take(length),
+    );
+    (
+        ids.into_iter().rev().take(length).collect(),
+        lowest_block_index,
+    )
+}
+
+/// Returns whether the block credited a non-zero fee to `fee_collector`.
+/// Blocks in the fee collector ranges that are not transfers, or whose fee
+/// is zero, do not change the balance of the fee collector.
+fn credits_fee_collector(fee_collector: Account, block_index: BlockIndex64) -> bool {
+    let block = get_decoded_block(block_index).unwrap_or_else(|| {
+        trap(&format!(
+            "Block {} not found in the block log, fee collector ranges are corrupted!",
+            block_index
+        ))
+    });
+    match block.transaction.operation {
+        Operation::Transfer { fee, .. } => {
+            block
+                .effective_fee
+                .or(fee)
+                .map_or(false, |fee| !Tokens::is_zero(&fee))
+                && get_fee_collector(block_index, &block) == Some(fee_collector)
+        }
+        Operation::Burn { .. } | Operation::Mint { .. } | Operation::Approve { .. } => false,
+    }
+}
+
+/// Returns the fee of the last transfer before the block. This is the fee
+/// that [process_balance_changes] charged for the approve blocks without
+/// fee fields, as it was the last recorded fee when the block was indexed.
+fn transfer_fee_before(block_index: BlockIndex64) -> Option<Tokens> {
+    with_state(|state| {
+        state
+            .transfer_fee_changes
+            .range(..block_index)
+            .next_back()
+            .map(|(_, fee)| *fee)
+    })
+}
+
+/// Records the fee of the transfer in the block if it differs from the fee
+/// of the transfer before it. The transfers must be recorded in order.
+fn record_transfer_fee(block_index: BlockIndex64, fee: Tokens) {
+    if transfer_fee_before(block_index) != Some(fee) {
+        mutate_state(|state| {
+            state.transfer_fee_changes.insert(block_index, fee);
+        });
+    }
+}
+
+fn account_history_entry(
+    block_index: BlockIndex64,
+    block: &Block<Tokens>,
+    balance: Tokens,
+) -> AccountHistoryEntry {
+    let text = |account: Account| Some(account.to_string());
+    let (kind, from, to, spender, amount, fee) = match block.transaction.operation {
+        Operation::Mint { to, amount } => ("mint", None, text(to), None, amount, None),
+        Operation::Burn {
+            from,
+            spender,
+            amount,
+        } => (
+            "burn",
+            text(from),
+            None,
+            spender.and_then(text),
+            amount,
+            None,
+        ),
+        Operation::Transfer {
+            from,
+            to,
+            spender,
+            amount,
+            fee,
+        } => (
+            "transfer",
+            text(from),
+            text(to),
+            spender.and_then(text),
+            amount,
+            block.effective_fee.or(fee),
+        ),
+        Operation::Approve {
+            from,
+            spender,
+            amount,
+            fee,
+            ..
+        } => (
+            "approve",
+            text(from),
+            None,
+            text(spender),
+            amount,
+            fee.or(block.effective_fee),
+        ),
+    };
+    AccountHistoryEntry {
+        block_index,
+        timestamp: block.timestamp,
+        kind: kind.to_string(),
+        from,
+        to,
+        spender,
+        amount: amount.to_string(),
+        fee: fee.map(|fee| fee.to_string()),
+        memo: block
+            .transaction
+            .memo
+            .as_ref()
+            .map(|memo| hex::encode(memo.0.as_slice())),
+        balance: balance.to_string(),
+    }
+}
+
+/// Returns the balance of `account` before the block given the balance
+/// after it.
+fn balance_before_block(
+    account: Account,
+    block_index: BlockIndex64,
+    block: &Block<Tokens>,
+    balance_after: Tokens,
+) -> Tokens {
+    let (credited, debited) = account_balance_change(account, block_index, block);
+    balance_after
+        .checked_sub(&credited)
+        .and_then(|balance| balance.checked_add(&debited))
+        .unwrap_or_else(|| {
+            trap(&format!(
+                "Block {} caused an inconsistency when computing the balance of account {} before it",
+                block_index, account
+            ))
+        })
+}
+
+/// Returns the amounts credited to and debited from `account` by the block.
+/// The changes are the same applied by [process_balance_changes].
+fn account_balance_change(
+    account: Account,
+    block_index: BlockIndex64,
+    block: &Block<Tokens>,
+) -> (Tokens, Tokens) {
+    let mut credited = Tokens::zero();
+    let mut debited = Tokens::zero();
+    let add = |total: &mut Tokens, amount: Tokens| {
+        *total = total.checked_add(&amount).unwrap_or_else(|| {
+            trap(&format!(
+                "token amount overflow while computing the balance of {} at block {}",
+                account, block_index
+            ))
+        })
+    };
+    match block.transaction.operation {
+        Operation::Burn { from, amount, .. } => {
+            if from == account {
+                add(&mut debited, amount);
+            }
+        }
+        Operation::Mint { to, amount } => {
+            if to == account {
+                add(&mut credited, amount);
+            }
+        }
+        Operation::Transfer {
+            from,
+            to,
+            amount,
+            fee,
+            ..
+        } => {
+            let fee = block.effective_fee.or(fee).unwrap_or_else(|| {
+                trap(&format!(
+                    "Block {} is of type Transfer but has no fee or effective fee!",
+                    block_index
+                ))
+            });
+            if from == account {
+                add(&mut debited, amount);
+                add(&mut debited, fee);
+            }
+            if to == account {
+                add(&mut credited, amount);
+            }
+            if get_fee_collector(block_index, block) == Some(account) {
+                add(&mut credited, fee);
+            }
+        }
+        Operation::Approve { from, fee, .. } => {
+            if from == account {
+                // NB. see process_balance_changes for the blocks
+                // without fee fields.
+                let fee = fee
+                    .or(block.effective_fee)
+                    .or_else(|| transfer_fee_before(block_index))
+                    .unwrap_or_else(|| {
+                        trap(&format!(
+                            "block with index {block_index} doesn't contain a fee"
+                        ))
+                    });
+                add(&mut debited, fee);
+            }
+        }
     }
+    (credited, debited)
 }
 
 pub fn encode_metrics(w: &mut ic_metrics_encoder::MetricsEncoder<Vec<u8>>) -> std::io::Result<()> {
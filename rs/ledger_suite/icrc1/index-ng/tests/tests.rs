@@ -6,10 +6,15 @@ use crate::common::{
 use candid::{Decode, Encode, Nat, Principal};
 use ic_agent::identity::Identity;
 use ic_base_types::{CanisterId, PrincipalId};
+use ic_canisters_http_types::HttpRequest;
 use ic_icrc1_index_ng::{
-    FeeCollectorRanges, GetAccountTransactionsArgs, GetAccountTransactionsResponse,
-    GetAccountTransactionsResult, GetBlocksResponse, IndexArg, InitArg as IndexInitArg,
-    ListSubaccountsArgs, TransactionWithId, DEFAULT_MAX_BLOCKS_PER_RESPONSE,
+    AccountHistoryEntry, AccountHistoryFormat, AccountHistoryStreamingToken, BalanceAt,
+    FeeCollectorRanges, GetAccountBalanceAtArgs, GetAccountBalanceAtError,
+    GetAccountBalanceAtResponse, GetAccountBalanceAtResult, GetAccountTransactionsArgs,
+    GetAccountTransactionsResponse, GetAccountTransactionsResult, GetBlocksResponse, IndexArg,
+    InitArg as IndexInitArg, ListSubaccountsArgs, StreamingCallbackHttpResponse,
+    StreamingHttpResponse, TransactionWithId, ACCOUNT_HISTORY_CSV_HEADER,
+    DEFAULT_MAX_BLOCKS_PER_RESPONSE,
 };
 use ic_icrc1_ledger::{ChangeFeeCollector, LedgerArgument, UpgradeArgs as LedgerUpgradeArgs};
 use ic_icrc1_test_utils::{
@@ -1140,6 +1145,287 @@ fn test_fee_collector() {
     );
 }
 
+fn account_history(
+    env: &StateMachine,
+    index_id: CanisterId,
+    account: Account,
+    format: &str,
+) -> StreamingHttpResponse {
+    let req = HttpRequest {
+        method: "GET".to_string(),
+        url: format!("/account_history?account={}&format={}", account, format),
+        headers: vec![],
+        body: Default::default(),
+    };
+    let res = env
+        .execute_ingress(index_id, "http_request", Encode!(&req).unwrap())
+        .expect("Failed to send http_request")
+        .bytes();
+    Decode!(&res, StreamingHttpResponse).expect("Failed to decode StreamingHttpResponse")
+}
+
+fn account_history_streaming_callback(
+    env: &StateMachine,
+    index_id: CanisterId,
+    token: AccountHistoryStreamingToken,
+) -> StreamingCallbackHttpResponse {
+    let res = env
+        .execute_ingress(
+            index_id,
+            "http_request_streaming_callback",
+            Encode!(&token).unwrap(),
+        )
+        .expect("Failed to send http_request_streaming_callback")
+        .bytes();
+    Decode!(&res, StreamingCallbackHttpResponse)
+        .expect("Failed to decode StreamingCallbackHttpResponse")
+}
+
+fn parse_jsonl_account_history(body: &[u8]) -> Vec<(u64, String, String)> {
+    std::str::from_utf8(body)
+        .unwrap()
+        .lines()
+        .map(|line| {
+            let entry: AccountHistoryEntry = serde_json::from_str(line).unwrap();
+            (entry.block_index, entry.kind, entry.balance)
+        })
+        .collect()
+}
+
+#[test]
+fn test_account_history_export() {
+    let env = &StateMachine::new();
+    let fee_collector = account(42, 0);
+    let minter = minter_identity().sender().unwrap();
+    let ledger_id = install_ledger(
+        env,
+        vec![(account(1, 0), 10_000_000)], // txid: 0
+        default_archive_options(),
+        Some(fee_collector),
+        minter,
+    );
+    let index_id = install_index_ng(env, index_init_arg_without_interval(ledger_id));
+
+    transfer(env, ledger_id, account(1, 0), account(2, 0), 100_000); // txid: 1
+    transfer(env, ledger_id, account(1, 0), account(3, 0), 200_000); // txid: 2
+    approve(env, ledger_id, account(2, 0), account(3, 0), 50_000); // txid: 3
+
+    wait_until_sync_is_completed(env, index_id, ledger_id);
+
+    // The history is exported from the most recent transaction to the oldest
+    // one, together with the balance of the account after each transaction.
+    let res = account_history(env, index_id, account(1, 0), "jsonl");
+    assert_eq!(res.status_code, 200);
+    assert!(res.streaming_strategy.is_none());
+    assert_eq!(
+        parse_jsonl_account_history(&res.body),
+        vec![
+            (2, "transfer".to_string(), (9_700_000 - 2 * FEE).to_string()),
+            (1, "transfer".to_string(), (9_900_000 - FEE).to_string()),
+            (0, "mint".to_string(), 10_000_000.to_string()),
+        ]
+    );
+
+    let res = account_history(env, index_id, account(2, 0), "csv");
+    assert_eq!(res.status_code, 200);
+    let body = String::from_utf8(res.body.into_vec()).unwrap();
+    let mut lines = body.lines();
+    assert_eq!(
+        lines.next().map(|line| format!("{}\n", line)),
+        Some(ACCOUNT_HISTORY_CSV_HEADER.to_string())
+    );
+    let rows: Vec<Vec<&str>> = lines.map(|line| line.split(',').collect()).collect();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0][0], "3");
+    assert_eq!(rows[0][2], "approve");
+    assert_eq!(rows[0][3], account(2, 0).to_string());
+    assert_eq!(rows[0][5], account(3, 0).to_string());
+    assert_eq!(rows[0][9], (100_000 - FEE).to_string());
+    assert_eq!(rows[1][0], "1");
+    assert_eq!(rows[1][2], "transfer");
+    assert_eq!(rows[1][4], account(2, 0).to_string());
+    assert_eq!(rows[1][9], 100_000.to_string());
+
+    // The fee collector history contains only the blocks where it collected
+    // the fees, and neither the mint nor the approve whose fee is burned.
+    let res = account_history(env, index_id, fee_collector, "jsonl");
+    assert_eq!(
+        parse_jsonl_account_history(&res.body),
+        vec![
+            (2, "transfer".to_string(), (2 * FEE).to_string()),
+            (1, "transfer".to_string(), FEE.to_string()),
+        ]
+    );
+    assert_eq!(icrc1_balance_of(env, index_id, fee_collector), 2 * FEE);
+
+    // The export can be resumed from any point via the streaming callback.
+    let res = account_history_streaming_callback(
+        env,
+        index_id,
+        AccountHistoryStreamingToken {
+            account: account(1, 0),
+            format: AccountHistoryFormat::Jsonl,
+            start: 2u8.into(),
+            balance: (9_900_000 - FEE).into(),
+        },
+    );
+    assert_eq!(res.token, None);
+    assert_eq!(
+        parse_jsonl_account_history(&res.body),
+        vec![
+            (1, "transfer".to_string(), (9_900_000 - FEE).to_string()),
+            (0, "mint".to_string(), 10_000_000.to_string()),
+        ]
+    );
+
+    let res = account_history(env, index_id, account(1, 0), "xml");
+    assert_eq!(res.status_code, 400);
+}
+
+fn get_account_balance_at(
+    env: &StateMachine,
+    index_id: CanisterId,
+    account: Account,
+    at: BalanceAt,
+) -> GetAccountBalanceAtResult {
+    let req = GetAccountBalanceAtArgs { account, at };
+    let req = Encode!(&req).expect("Failed to encode GetAccountBalanceAtArgs");
+    let res = env
+        .execute_ingress(index_id, "get_account_balance_at", req)
+        .expect("Failed to get_account_balance_at")
+        .bytes();
+    Decode!(&res, GetAccountBalanceAtResult).expect("Failed to decode GetAccountBalanceAtResult")
+}
+
+#[test]
+fn test_get_account_balance_at() {
+    let env = &StateMachine::new();
+    let fee_collector = account(42, 0);
+    let minter = minter_identity().sender().unwrap();
+    let ledger_id = install_ledger(
+        env,
+        vec![(account(1, 0), 10_000_000)], // txid: 0
+        default_archive_options(),
+        Some(fee_collector),
+        minter,
+    );
+    let index_id = install_index_ng(env, index_init_arg_without_interval(ledger_id));
+
+    env.advance_time(Duration::from_secs(1));
+    transfer(env, ledger_id, account(1, 0), account(2, 0), 100_000); // txid: 1
+    env.advance_time(Duration::from_secs(1));
+    transfer(env, ledger_id, account(1, 0), account(3, 0), 200_000); // txid: 2
+    env.advance_time(Duration::from_secs(1));
+    transfer(env, ledger_id, account(2, 0), account(1, 0), 50_000); // txid: 3
+
+    wait_until_sync_is_completed(env, index_id, ledger_id);
+
+    // The expected balances after each block.
+    let expected_balances = vec![
+        (
+            account(1, 0),
+            vec![
+                10_000_000,
+                9_900_000 - FEE,
+                9_700_000 - 2 * FEE,
+                9_750_000 - 2 * FEE,
+            ],
+        ),
+        (account(2, 0), vec![0, 100_000, 100_000, 50_000 - FEE]),
+        (fee_collector, vec![0, FEE, 2 * FEE, 3 * FEE]),
+    ];
+    for (account, balances) in expected_balances {
+        for (block_index, balance) in balances.into_iter().enumerate() {
+            assert_eq!(
+                get_account_balance_at(
+                    env,
+                    index_id,
+                    account,
+                    BalanceAt::BlockIndex(block_index.into())
+                ),
+                Ok(GetAccountBalanceAtResponse {
+                    balance: balance.into(),
+                    block_index: Some(block_index.into()),
+                }),
+                "account: {} block_index: {}",
+                account,
+                block_index
+            );
+        }
+    }
+
+    assert_eq!(
+        get_account_balance_at(
+            env,
+            index_id,
+            account(1, 0),
+            BalanceAt::BlockIndex(4u8.into())
+        ),
+        Err(GetAccountBalanceAtError::BlockNotIndexed {
+            num_blocks_synced: 4u8.into()
+        })
+    );
+
+    // Query by timestamp using the timestamps of the blocks.
+    let mut transactions = get_account_transactions(env, index_id, account(1, 0), None, 10)
+        .transactions
+        .into_iter()
+        .map(|tx| (tx.id.0.to_u64().unwrap(), tx.transaction.timestamp))
+        .collect::<Vec<_>>();
+    transactions.sort();
+    let timestamp_of = |block_index: u64| -> u64 {
+        transactions
+            .iter()
+            .find(|(id, _)| *id == block_index)
+            .map(|(_, timestamp)| *timestamp)
+            .unwrap()
+    };
+
+    assert_eq!(
+        get_account_balance_at(
+            env,
+            index_id,
+            account(1, 0),
+            BalanceAt::Timestamp(timestamp_of(0) - 1)
+        ),
+        Ok(GetAccountBalanceAtResponse {
+            balance: 0u8.into(),
+            block_index: None,
+        })
+    );
+    assert_eq!(
+        get_account_balance_at(
+            env,
+            index_id,
+            account(1, 0),
+            BalanceAt::Timestamp(timestamp_of(2))
+        ),
+        Ok(GetAccountBalanceAtResponse {
+            balance: (9_700_000 - 2 * FEE).into(),
+            block_index: Some(2u8.into()),
+        })
+    );
+    assert_eq!(
+        get_account_balance_at(
+            env,
+            index_id,
+            account(1, 0),
+            BalanceAt::Timestamp(timestamp_of(3) - 1)
+        ),
+        Ok(GetAccountBalanceAtResponse {
+            balance: (9_700_000 - 2 * FEE).into(),
+            block_index: Some(2u8.into()),
+        })
+    );
+    assert_eq!(
+        get_account_balance_at(env, index_id, account(1, 0), BalanceAt::Timestamp(u64::MAX)),
+        Ok(GetAccountBalanceAtResponse {
+            balance: icrc1_balance_of(env, index_id, account(1, 0)).into(),
+            block_index: Some(3u8.into()),
+        })
+    );
+}
+
 #[test]
 fn test_get_account_transactions_vs_old_index() {
     let mut runner = TestRunner::new(TestRunnerConfig::with_cases(1));
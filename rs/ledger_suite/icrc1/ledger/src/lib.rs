@@ -32,6 +32,7 @@ use ic_ledger_core::{
 use ic_ledger_hash_of::HashOf;
 use ic_stable_structures::memory_manager::{MemoryId, MemoryManager, VirtualMemory};
 use ic_stable_structures::DefaultMemoryImpl;
+use icrc_ledger_types::icrc103::get_allowances::{Allowance as Allowance103, Allowances};
 use icrc_ledger_types::icrc3::transactions::Transaction as Tx;
 use icrc_ledger_types::icrc3::{blocks::GetBlocksResponse, transactions::GetTransactionsResponse};
 use icrc_ledger_types::{
@@ -51,7 +52,7 @@ use serde_bytes::ByteBuf;
 use std::borrow::Cow;
 use std::cell::RefCell;
 use std::collections::{BTreeMap, VecDeque};
-use std::ops::DerefMut;
+use std::ops::{Bound, DerefMut};
 use std::time::Duration;
 
 const TRANSACTION_WINDOW: Duration = Duration::from_secs(24 * 60 * 60);
@@ -62,6 +63,15 @@ const MAX_TRANSACTIONS_PER_REQUEST: usize = 2_000;
 const ACCOUNTS_OVERFLOW_TRIM_QUANTITY: usize = 100_000;
 const MAX_TRANSACTIONS_IN_WINDOW: usize = 3_000_000;
 const MAX_TRANSACTIONS_TO_PURGE: usize = 100_000;
+/// The maximum number of allowances the ledger should return for a single
+/// icrc103_get_allowances request.
+pub const MAX_TAKE_ALLOWANCES: u64 = 500;
+/// The maximum number of transfers the ledger accepts in a single
+/// icrc4_transfer_batch request.
+pub const MAX_TRANSFER_BATCH_SIZE: u64 = 500;
+/// The maximum number of accounts the ledger accepts in a single
+/// icrc4_balance_of_batch request.
+pub const MAX_BALANCE_QUERY_BATCH_SIZE: u64 = 500;
 
 const DEFAULT_MAX_MEMO_LENGTH: u16 = 32;
 
@@ -758,6 +768,43 @@ impl<Tokens: TokensType> Ledger<Tokens> {
         (locations.local_blocks.start, local_blocks, archived_blocks)
     }
 
+    /// Returns at most `max_results` unexpired allowances granted by accounts
+    /// of `from.owner`, starting at `from` and, if `prev_spender` is set,
+    /// right after the (`from`, `prev_spender`) pair.
+    pub fn get_allowances(
+        &self,
+        from: Account,
+        prev_spender: Option<Account>,
+        max_results: u64,
+        now: u64,
+    ) -> Allowances {
+        let start = match prev_spender {
+            Some(spender) => Bound::Excluded((from, spender)),
+            None => Bound::Included((
+                from,
+                Account {
+                    owner: Principal::from_slice(&[]),
+                    subaccount: None,
+                },
+            )),
+        };
+        self.approvals
+            .allowances_from(
+                start,
+                TimeStamp::from_nanos_since_unix_epoch(now),
+                max_results as usize,
+                |account| account.owner == from.owner,
+            )
+            .into_iter()
+            .map(|((from_account, to_spender), allowance)| Allowance103 {
+                from_account,
+                to_spender,
+                allowance: allowance.amount.into(),
+                expires_at: allowance.expires_at.map(|t| t.as_nanos_since_unix_epoch()),
+            })
+            .collect()
+    }
+
     /// Returns transactions in the specified range.
     pub fn get_transactions(&self, start: BlockIndex, length: usize) -> GetTransactionsResponse {
         let (first_index, local_transactions, archived_transactions) = self.query_blocks(
@@ -15,7 +15,10 @@ use ic_icrc1::{
     Operation, Transaction,
 };
 use ic_icrc1_ledger::{InitArgs, Ledger, LedgerArgument};
-use ic_icrc1_ledger::{LEDGER_VERSION, UPGRADES_MEMORY};
+use ic_icrc1_ledger::{
+    LEDGER_VERSION, MAX_BALANCE_QUERY_BATCH_SIZE, MAX_TAKE_ALLOWANCES, MAX_TRANSFER_BATCH_SIZE,
+    UPGRADES_MEMORY,
+};
 use ic_ledger_canister_core::ledger::{
     apply_transaction, archive_blocks, LedgerAccess, LedgerContext, LedgerData,
     TransferError as CoreTransferError,
@@ -26,6 +29,9 @@ use ic_ledger_core::timestamp::TimeStamp;
 use ic_ledger_core::tokens::Zero;
 use ic_stable_structures::reader::{BufferedReader, Reader};
 use ic_stable_structures::writer::{BufferedWriter, Writer};
+use icrc_ledger_types::icrc103::get_allowances::{
+    Allowances, GetAllowancesArgs, GetAllowancesError,
+};
 use icrc_ledger_types::icrc2::approve::{ApproveArgs, ApproveError};
 use icrc_ledger_types::icrc21::{
     errors::Icrc21Error, lib::build_icrc21_consent_info_for_icrc1_and_icrc2_endpoints,
@@ -34,6 +40,12 @@ use icrc_ledger_types::icrc21::{
 use icrc_ledger_types::icrc3::blocks::DataCertificate;
 #[cfg(not(feature = "get-blocks-disabled"))]
 use icrc_ledger_types::icrc3::blocks::GetBlocksResponse;
+use icrc_ledger_types::icrc4::balance_of_batch::{
+    AccountBalance, BalanceQueryArgs, BalanceQueryResult,
+};
+use icrc_ledger_types::icrc4::batch_transfer::{
+    TransferBatchArgs, TransferBatchError, TransferBatchResults,
+};
 use icrc_ledger_types::{
     icrc::generic_metadata_value::MetadataValue as Value,
     icrc3::{
@@ -64,6 +76,18 @@ use std::io::{Read, Write};
 
 const MAX_MESSAGE_SIZE: u64 = 1024 * 1024;
 
+/// The error code of the `GenericError` returned for a transfer of a batch
+/// whose memo is too long.
+const MEMO_TOO_LONG_ERROR_CODE: u64 = 1;
+
+/// The error code of the `GenericError` returned for a transfer of a batch
+/// whose error cannot be represented as an ICRC-1 transfer error.
+const UNEXPECTED_TRANSFER_ERROR_CODE: u64 = 2;
+
+/// The error code of the `GenericError` returned by `icrc103_get_allowances`
+/// for a `take` argument that does not fit into 64 bits.
+const INVALID_TAKE_ERROR_CODE: u64 = 1;
+
 #[cfg(not(feature = "u256-tokens"))]
 pub type Tokens = ic_icrc1_tokens_u64::U64;
 
@@ -464,6 +488,18 @@ async fn execute_transfer(
     Ok(Nat::from(block_idx))
 }
 
+/// Returns an error if the memo is longer than the ledger allows.
+fn check_memo_length(memo: Option<&Memo>, max_memo_length: u16) -> Result<(), String> {
+    match memo {
+        Some(memo) if memo.0.len() > max_memo_length as usize => Err(format!(
+            "the memo field size of {} bytes is above the allowed limit of {} bytes",
+            memo.0.len(),
+            max_memo_length
+        )),
+        _ => Ok(()),
+    }
+}
+
 fn execute_transfer_not_async(
     from_account: Account,
     to: Account,
@@ -477,16 +513,9 @@ fn execute_transfer_not_async(
         let now = TimeStamp::from_nanos_since_unix_epoch(ic_cdk::api::time());
         let created_at_time = created_at_time.map(TimeStamp::from_nanos_since_unix_epoch);
 
-        match memo.as_ref() {
-            Some(memo) if memo.0.len() > ledger.max_memo_length() as usize => {
-                ic_cdk::trap(&format!(
-                    "the memo field size of {} bytes is above the allowed limit of {} bytes",
-                    memo.0.len(),
-                    ledger.max_memo_length()
-                ))
-            }
-            _ => {}
-        };
+        if let Err(err) = check_memo_length(memo.as_ref(), ledger.max_memo_length()) {
+            ic_cdk::trap(&err)
+        }
         let amount = match Tokens::try_from(amount.clone()) {
             Ok(n) => n,
             Err(_) => {
@@ -621,6 +650,93 @@ async fn icrc2_transfer_from(arg: TransferFromArgs) -> Result<Nat, TransferFromE
     })
 }
 
+#[update]
+#[candid_method(update)]
+async fn icrc4_transfer_batch(args: TransferBatchArgs) -> TransferBatchResults {
+    if args.len() as u64 > MAX_TRANSFER_BATCH_SIZE {
+        return vec![Some(Err(TransferBatchError::TooManyRequests {
+            limit: Nat::from(MAX_TRANSFER_BATCH_SIZE),
+        }))];
+    }
+    let caller = ic_cdk::api::caller();
+    let max_memo_length = Access::with_ledger(|ledger| ledger.max_memo_length());
+    let results: TransferBatchResults = args
+        .into_iter()
+        .map(|arg| {
+            // Unlike icrc1_transfer, a batch must not trap on a single invalid transfer.
+            if let Err(message) = check_memo_length(arg.memo.as_ref(), max_memo_length) {
+                return Some(Err(TransferBatchError::GenericError {
+                    error_code: Nat::from(MEMO_TOO_LONG_ERROR_CODE),
+                    message,
+                }));
+            }
+            let from_account = Account {
+                owner: caller,
+                subaccount: arg.from_subaccount,
+            };
+            let result = execute_transfer_not_async(
+                from_account,
+                arg.to,
+                None,
+                arg.fee,
+                arg.amount,
+                arg.memo,
+                arg.created_at_time,
+            )
+            .map(Nat::from)
+            .map_err(convert_transfer_error)
+            .map_err(|err| match TransferError::try_from(err) {
+                Ok(err) => TransferBatchError::from(err),
+                Err(message) => TransferBatchError::GenericError {
+                    error_code: Nat::from(UNEXPECTED_TRANSFER_ERROR_CODE),
+                    message,
+                },
+            });
+            Some(result)
+        })
+        .collect();
+
+    // NB. we need to set the certified data before the first async call to make sure that the
+    // blockchain state agrees with the certificate while archiving is in progress.
+    ic_cdk::api::set_certified_data(&Access::with_ledger(Ledger::root_hash));
+
+    archive_blocks::<Access>(&LOG, MAX_MESSAGE_SIZE).await;
+    results
+}
+
+#[query]
+#[candid_method(query)]
+fn icrc4_maximum_update_batch_size() -> Option<Nat> {
+    Some(Nat::from(MAX_TRANSFER_BATCH_SIZE))
+}
+
+#[query]
+#[candid_method(query)]
+fn icrc4_balance_of_batch(args: BalanceQueryArgs) -> BalanceQueryResult {
+    if args.accounts.len() as u64 > MAX_BALANCE_QUERY_BATCH_SIZE {
+        ic_cdk::trap(&format!(
+            "the number of accounts {} exceeds the maximum batch size {}",
+            args.accounts.len(),
+            MAX_BALANCE_QUERY_BATCH_SIZE
+        ));
+    }
+    Access::with_ledger(|ledger| {
+        args.accounts
+            .into_iter()
+            .map(|account| AccountBalance {
+                balance: ledger.balances().account_balance(&account).into(),
+                account,
+            })
+            .collect()
+    })
+}
+
+#[query]
+#[candid_method(query)]
+fn icrc4_maximum_query_batch_size() -> Option<Nat> {
+    Some(Nat::from(MAX_BALANCE_QUERY_BATCH_SIZE))
+}
+
 #[query]
 fn archives() -> Vec<ArchiveInfo> {
     Access::with_ledger(|ledger| {
@@ -661,10 +777,18 @@ fn supported_standards() -> Vec<StandardRecord> {
             name: "ICRC-3".to_string(),
             url: "https://github.com/dfinity/ICRC-1/tree/main/standards/ICRC-3".to_string(),
         },
+        StandardRecord {
+            name: "ICRC-4".to_string(),
+            url: "https://github.com/dfinity/ICRC/tree/main/ICRCs/ICRC-4".to_string(),
+        },
         StandardRecord {
             name: "ICRC-21".to_string(),
             url: "https://github.com/dfinity/wg-identity-authentication/blob/main/topics/ICRC-21/icrc_21_consent_msg.md".to_string(),
         },
+        StandardRecord {
+            name: "ICRC-103".to_string(),
+            url: "https://github.com/dfinity/ICRC/tree/main/ICRCs/ICRC-103".to_string(),
+        },
     ];
     standards
 }
@@ -793,6 +917,38 @@ fn icrc2_allowance(arg: AllowanceArgs) -> Allowance {
     })
 }
 
+#[query]
+#[candid_method(query)]
+fn icrc103_get_allowances(arg: GetAllowancesArgs) -> Result<Allowances, GetAllowancesError> {
+    let from_account = match arg.from_account {
+        Some(from_account) => from_account,
+        None => Account {
+            owner: ic_cdk::api::caller(),
+            subaccount: None,
+        },
+    };
+    let max_results = match arg.take {
+        Some(take) => match take.0.to_u64() {
+            Some(take) => std::cmp::min(take, MAX_TAKE_ALLOWANCES),
+            None => {
+                return Err(GetAllowancesError::GenericError {
+                    error_code: Nat::from(INVALID_TAKE_ERROR_CODE),
+                    message: format!("take {} does not fit into 64 bits", take),
+                })
+            }
+        },
+        None => MAX_TAKE_ALLOWANCES,
+    };
+    Ok(Access::with_ledger(|ledger| {
+        ledger.get_allowances(
+            from_account,
+            arg.prev_spender,
+            max_results,
+            ic_cdk::api::time(),
+        )
+    }))
+}
+
 #[query]
 #[candid_method(query)]
 fn icrc3_get_archives(args: GetArchivesArgs) -> GetArchivesResult {
@@ -200,6 +200,19 @@ fn test_tx_deduplication() {
     ic_ledger_suite_state_machine_tests::test_tx_deduplication(ledger_wasm(), encode_init_args);
 }
 
+#[test]
+fn test_icrc4_transfer_batch() {
+    ic_ledger_suite_state_machine_tests::test_icrc4_transfer_batch(ledger_wasm(), encode_init_args);
+}
+
+#[test]
+fn test_icrc4_balance_of_batch() {
+    ic_ledger_suite_state_machine_tests::test_icrc4_balance_of_batch(
+        ledger_wasm(),
+        encode_init_args,
+    );
+}
+
 #[test]
 fn test_mint_burn() {
     ic_ledger_suite_state_machine_tests::test_mint_burn(ledger_wasm(), encode_init_args);
@@ -365,6 +378,14 @@ fn test_balances_overflow() {
     ic_ledger_suite_state_machine_tests::test_balances_overflow(ledger_wasm(), encode_init_args);
 }
 
+#[test]
+fn test_icrc103_get_allowances() {
+    ic_ledger_suite_state_machine_tests::test_icrc103_get_allowances(
+        ledger_wasm(),
+        encode_init_args,
+    );
+}
+
 #[test]
 fn test_approval_trimming() {
     ic_ledger_suite_state_machine_tests::test_approval_trimming(ledger_wasm(), encode_init_args);
@@ -22,6 +22,9 @@ use icrc_ledger_types::icrc::generic_metadata_value::MetadataValue as Value;
 use icrc_ledger_types::icrc::generic_value::Value as GenericValue;
 use icrc_ledger_types::icrc1::account::{Account, Subaccount};
 use icrc_ledger_types::icrc1::transfer::{Memo, TransferArg, TransferError};
+use icrc_ledger_types::icrc103::get_allowances::{
+    Allowances, GetAllowancesArgs, GetAllowancesError,
+};
 use icrc_ledger_types::icrc2::allowance::{Allowance, AllowanceArgs};
 use icrc_ledger_types::icrc2::approve::{ApproveArgs, ApproveError};
 use icrc_ledger_types::icrc2::transfer_from::{TransferFromArgs, TransferFromError};
@@ -42,6 +45,10 @@ use icrc_ledger_types::icrc3::transactions::GetTransactionsResponse;
 use icrc_ledger_types::icrc3::transactions::Transaction as Tx;
 use icrc_ledger_types::icrc3::transactions::TransactionRange;
 use icrc_ledger_types::icrc3::transactions::Transfer;
+use icrc_ledger_types::icrc4::balance_of_batch::{
+    AccountBalance, BalanceQueryArgs, BalanceQueryResult,
+};
+use icrc_ledger_types::icrc4::batch_transfer::{TransferBatchError, TransferBatchResults};
 use num_traits::ToPrimitive;
 use proptest::prelude::*;
 use proptest::test_runner::{Config as TestRunnerConfig, TestCaseResult, TestRunner};
@@ -233,6 +240,26 @@ pub fn send_transfer(
     .map(|n| n.0.to_u64().unwrap())
 }
 
+pub fn send_transfer_batch(
+    env: &StateMachine,
+    ledger: CanisterId,
+    from: Principal,
+    args: &[TransferArg],
+) -> TransferBatchResults {
+    Decode!(
+        &env.execute_ingress_as(
+            PrincipalId(from),
+            ledger,
+            "icrc4_transfer_batch",
+            Encode!(&args).unwrap()
+        )
+        .expect("failed to transfer funds in batch")
+        .bytes(),
+        TransferBatchResults
+    )
+    .expect("failed to decode icrc4_transfer_batch response")
+}
+
 pub fn system_time_to_nanos(t: SystemTime) -> u64 {
     t.duration_since(SystemTime::UNIX_EPOCH).unwrap().as_nanos() as u64
 }
@@ -632,6 +659,26 @@ pub fn get_allowance(
     .expect("failed to decode allowance response")
 }
 
+pub fn get_allowances(
+    env: &StateMachine,
+    ledger: CanisterId,
+    caller: Principal,
+    arg: GetAllowancesArgs,
+) -> Result<Allowances, GetAllowancesError> {
+    Decode!(
+        &env.query_as(
+            PrincipalId(caller),
+            ledger,
+            "icrc103_get_allowances",
+            Encode!(&arg).unwrap()
+        )
+        .expect("failed to query the allowances")
+        .bytes(),
+        Result<Allowances, GetAllowancesError>
+    )
+    .expect("failed to decode icrc103_get_allowances response")
+}
+
 fn arb_amount() -> impl Strategy<Value = Tokens> {
     any::<u64>().prop_map(|n| Tokens::try_from(Nat::from(n)).unwrap())
 }
@@ -967,7 +1014,10 @@ where
         standards.push(standard.name);
     }
     standards.sort();
-    assert_eq!(standards, vec!["ICRC-1", "ICRC-2", "ICRC-21", "ICRC-3"]);
+    assert_eq!(
+        standards,
+        vec!["ICRC-1", "ICRC-103", "ICRC-2", "ICRC-21", "ICRC-3", "ICRC-4"]
+    );
 }
 
 pub fn test_total_supply<T>(ledger_wasm: Vec<u8>, encode_init_args: fn(InitArgs) -> T)
@@ -1106,6 +1156,165 @@ where
     assert_eq!(6_000_000u64, balance_of(&env, canister_id, p2.0));
 }
 
+pub fn test_icrc4_transfer_batch<T>(ledger_wasm: Vec<u8>, encode_init_args: fn(InitArgs) -> T)
+where
+    T: CandidType,
+{
+    let p1 = PrincipalId::new_user_test_id(1);
+    let p2 = PrincipalId::new_user_test_id(2);
+    let p3 = PrincipalId::new_user_test_id(3);
+    let (env, canister_id) = setup(
+        ledger_wasm,
+        encode_init_args,
+        vec![(Account::from(p1.0), 10_000_000)],
+    );
+
+    let now = system_time_to_nanos(env.time());
+    let transfer_to = |to: PrincipalId, amount: u64| TransferArg {
+        from_subaccount: None,
+        to: to.0.into(),
+        fee: None,
+        amount: Nat::from(amount),
+        created_at_time: Some(now),
+        memo: Some(Memo::from(amount)),
+    };
+
+    let results = send_transfer_batch(
+        &env,
+        canister_id,
+        p1.0,
+        &[
+            transfer_to(p2, 1_000_000),
+            transfer_to(p3, 2_000_000),
+            // Deduplicated against the first transfer of the batch.
+            transfer_to(p2, 1_000_000),
+            // Only fails on its own, the other transfers are still applied.
+            TransferArg {
+                fee: Some(Nat::from(FEE + 1)),
+                ..transfer_to(p3, 1)
+            },
+            transfer_to(p3, 100_000_000),
+            // A too long memo fails the transfer instead of the whole batch.
+            TransferArg {
+                memo: Some(Memo::from(vec![0_u8; 33])),
+                ..transfer_to(p3, 3)
+            },
+        ],
+    );
+    assert_eq!(
+        results,
+        vec![
+            Some(Ok(Nat::from(1_u64))),
+            Some(Ok(Nat::from(2_u64))),
+            Some(Err(TransferBatchError::Duplicate {
+                duplicate_of: Nat::from(1_u64)
+            })),
+            Some(Err(TransferBatchError::BadFee {
+                expected_fee: Nat::from(FEE)
+            })),
+            Some(Err(TransferBatchError::InsufficientFunds {
+                balance: Nat::from(7_000_000 - 2 * FEE)
+            })),
+            Some(Err(TransferBatchError::GenericError {
+                error_code: Nat::from(1_u64),
+                message: "the memo field size of 33 bytes is above the allowed limit of 32 bytes"
+                    .to_string(),
+            })),
+        ]
+    );
+    assert_eq!(balance_of(&env, canister_id, p1.0), 7_000_000 - 2 * FEE);
+    assert_eq!(balance_of(&env, canister_id, p2.0), 1_000_000);
+    assert_eq!(balance_of(&env, canister_id, p3.0), 2_000_000);
+
+    // Transfers from a previous batch are deduplicated as well.
+    assert_eq!(
+        send_transfer_batch(&env, canister_id, p1.0, &[transfer_to(p3, 2_000_000)]),
+        vec![Some(Err(TransferBatchError::Duplicate {
+            duplicate_of: Nat::from(2_u64)
+        }))]
+    );
+
+    let max_batch_size = Decode!(
+        &env.query(
+            canister_id,
+            "icrc4_maximum_update_batch_size",
+            Encode!().unwrap()
+        )
+        .expect("failed to query the maximum batch size")
+        .bytes(),
+        Option<Nat>
+    )
+    .expect("failed to decode icrc4_maximum_update_batch_size response")
+    .expect("the ledger should advertise a maximum batch size");
+    let too_many_transfers = vec![transfer_to(p2, 1); max_batch_size.0.to_usize().unwrap() + 1];
+    assert_eq!(
+        send_transfer_batch(&env, canister_id, p1.0, &too_many_transfers),
+        vec![Some(Err(TransferBatchError::TooManyRequests {
+            limit: max_batch_size
+        }))]
+    );
+    assert_eq!(balance_of(&env, canister_id, p2.0), 1_000_000);
+}
+
+pub fn test_icrc4_balance_of_batch<T>(ledger_wasm: Vec<u8>, encode_init_args: fn(InitArgs) -> T)
+where
+    T: CandidType,
+{
+    let p1 = PrincipalId::new_user_test_id(1);
+    let p2 = PrincipalId::new_user_test_id(2);
+    let p3 = PrincipalId::new_user_test_id(3);
+    let (env, canister_id) = setup(
+        ledger_wasm,
+        encode_init_args,
+        vec![
+            (Account::from(p1.0), 10_000_000),
+            (Account::from(p2.0), 5_000_000),
+        ],
+    );
+    let balance_of_batch = |accounts: Vec<Account>| {
+        env.query(
+            canister_id,
+            "icrc4_balance_of_batch",
+            Encode!(&BalanceQueryArgs { accounts }).unwrap(),
+        )
+        .map(|res| Decode!(&res.bytes(), BalanceQueryResult).unwrap())
+    };
+
+    let accounts = vec![p2.0.into(), p3.0.into(), p1.0.into()];
+    assert_eq!(
+        balance_of_batch(accounts.clone()).expect("failed to query the balances"),
+        vec![
+            AccountBalance {
+                account: accounts[0],
+                balance: Nat::from(5_000_000_u64)
+            },
+            AccountBalance {
+                account: accounts[1],
+                balance: Nat::from(0_u64)
+            },
+            AccountBalance {
+                account: accounts[2],
+                balance: Nat::from(10_000_000_u64)
+            },
+        ]
+    );
+
+    let max_batch_size = Decode!(
+        &env.query(
+            canister_id,
+            "icrc4_maximum_query_batch_size",
+            Encode!().unwrap()
+        )
+        .expect("failed to query the maximum batch size")
+        .bytes(),
+        Option<Nat>
+    )
+    .expect("failed to decode icrc4_maximum_query_batch_size response")
+    .expect("the ledger should advertise a maximum batch size");
+    let too_many_accounts = vec![Account::from(p1.0); max_batch_size.0.to_usize().unwrap() + 1];
+    assert!(balance_of_batch(too_many_accounts).is_err());
+}
+
 pub fn test_tx_deduplication<T>(ledger_wasm: Vec<u8>, encode_init_args: fn(InitArgs) -> T)
 where
     T: CandidType,
@@ -3708,6 +3917,147 @@ where
     }
 }
 
+pub fn test_icrc103_get_allowances<T>(ledger_wasm: Vec<u8>, encode_init_args: fn(InitArgs) -> T)
+where
+    T: CandidType,
+{
+    let from = PrincipalId::new_user_test_id(1);
+    let other = PrincipalId::new_user_test_id(2);
+    let from_sub_1 = Account {
+        owner: from.0,
+        subaccount: Some([1; 32]),
+    };
+
+    let (env, canister_id) = setup(
+        ledger_wasm,
+        encode_init_args,
+        vec![
+            (Account::from(from.0), 1_000_000),
+            (from_sub_1, 1_000_000),
+            (Account::from(other.0), 1_000_000),
+        ],
+    );
+
+    let spenders: Vec<Account> = (10..14)
+        .map(|i| Account::from(PrincipalId::new_user_test_id(i).0))
+        .collect();
+    for (i, spender) in spenders.iter().enumerate() {
+        let approve_args = default_approve_args(*spender, 10_000 + i as u64);
+        send_approval(&env, canister_id, from.0, &approve_args).expect("approval failed");
+        let approve_args = ApproveArgs {
+            from_subaccount: from_sub_1.subaccount,
+            ..default_approve_args(*spender, 20_000 + i as u64)
+        };
+        send_approval(&env, canister_id, from.0, &approve_args).expect("approval failed");
+        send_approval(
+            &env,
+            canister_id,
+            other.0,
+            &default_approve_args(*spender, 30_000),
+        )
+        .expect("approval failed");
+    }
+    let mut sorted_spenders = spenders.clone();
+    sorted_spenders.sort();
+
+    // Without from_account, the caller's default account is used and the listing
+    // continues with the caller's other subaccounts, but never with other principals.
+    let allowances = get_allowances(
+        &env,
+        canister_id,
+        from.0,
+        GetAllowancesArgs {
+            from_account: None,
+            prev_spender: None,
+            take: None,
+        },
+    )
+    .expect("failed to list allowances");
+    assert_eq!(allowances.len(), 2 * spenders.len());
+    for (i, allowance) in allowances.iter().enumerate() {
+        let from_account = if i < spenders.len() {
+            Account::from(from.0)
+        } else {
+            from_sub_1
+        };
+        assert_eq!(allowance.from_account, from_account);
+        assert_eq!(allowance.to_spender, sorted_spenders[i % spenders.len()]);
+        assert_eq!(
+            allowance.allowance,
+            get_allowance(&env, canister_id, from_account, allowance.to_spender).allowance
+        );
+        assert_eq!(allowance.expires_at, None);
+    }
+
+    // Paginate through the subaccount allowances using prev_spender.
+    let mut prev_spender = None;
+    let mut listed = vec![];
+    loop {
+        let page = get_allowances(
+            &env,
+            canister_id,
+            other.0,
+            GetAllowancesArgs {
+                from_account: Some(from_sub_1),
+                prev_spender,
+                take: Some(Nat::from(3_u64)),
+            },
+        )
+        .expect("failed to list allowances");
+        assert!(page.len() <= 3);
+        match page.last() {
+            Some(last) => prev_spender = Some(last.to_spender),
+            None => break,
+        }
+        listed.extend(page.into_iter().map(|allowance| allowance.to_spender));
+    }
+    assert_eq!(listed, sorted_spenders);
+
+    // Expired allowances are not listed.
+    let expiring_spender = Account::from(PrincipalId::new_user_test_id(20).0);
+    let approve_args = ApproveArgs {
+        expires_at: Some(
+            system_time_to_nanos(env.time()) + Duration::from_secs(60).as_nanos() as u64,
+        ),
+        ..default_approve_args(expiring_spender, 10_000)
+    };
+    send_approval(&env, canister_id, other.0, &approve_args).expect("approval failed");
+    let list_other = |env: &StateMachine| {
+        get_allowances(
+            env,
+            canister_id,
+            other.0,
+            GetAllowancesArgs {
+                from_account: None,
+                prev_spender: None,
+                take: None,
+            },
+        )
+        .expect("failed to list allowances")
+    };
+    assert_eq!(list_other(&env).len(), spenders.len() + 1);
+    env.advance_time(Duration::from_secs(120));
+    assert_eq!(list_other(&env).len(), spenders.len());
+
+    // A `take` that doesn't fit into 64 bits is rejected.
+    let result = get_allowances(
+        &env,
+        canister_id,
+        other.0,
+        GetAllowancesArgs {
+            from_account: None,
+            prev_spender: None,
+            take: Some(Nat::from(u128::MAX)),
+        },
+    );
+    match result {
+        Err(GetAllowancesError::GenericError { error_code, .. }) => {
+            assert_eq!(error_code, Nat::from(1_u64))
+        }
+        other => panic!("expected a GenericError, got {:?}", other),
+    }
+}
+
 pub fn test_icrc1_test_suite<T: candid::CandidType>(
     ledger_wasm: Vec<u8>,
     encode_init_args: fn(InitArgs) -> T,
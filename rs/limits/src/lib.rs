@@ -71,3 +71,7 @@ pub const DKG_INTERVAL_HEIGHT: u64 = 499;
 /// The default upper bound for the number of allowed dkg dealings in a
 /// block.
 pub const DKG_DEALINGS_PER_BLOCK: usize = 1;
+/// Maximum number of response body bytes that the HTTPS outcalls adapter
+/// downloads for a ranged canister http request. The full body is downloaded
+/// once and cached, and the requested ranges are cut out of it.
+pub const MAX_CANISTER_HTTP_RANGED_DOWNLOAD_BYTES: u64 = 64 * MEGABYTE;
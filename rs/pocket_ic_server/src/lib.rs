@@ -92,6 +92,32 @@ pub fn copy_dir(
     Ok(())
 }
 
+/// Like `copy_dir`, but files are cloned (copy-on-write) if the underlying
+/// filesystem supports it and only copied otherwise.
+pub fn clone_dir(
+    src: impl AsRef<std::path::Path>,
+    dst: impl AsRef<std::path::Path>,
+) -> std::io::Result<()> {
+    std::fs::create_dir_all(&dst)?;
+    for entry in std::fs::read_dir(src)? {
+        let entry = entry?;
+        let ty = entry.file_type()?;
+        let dst_path = dst.as_ref().join(entry.file_name());
+        if ty.is_dir() {
+            clone_dir(entry.path(), dst_path)?;
+        } else {
+            match ic_sys::fs::clone_file(&entry.path(), &dst_path) {
+                Ok(()) => {}
+                Err(ic_sys::fs::FileCloneError::IoError(e)) => return Err(e),
+                Err(_) => {
+                    std::fs::copy(entry.path(), dst_path)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
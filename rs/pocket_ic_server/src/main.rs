@@ -1,6 +1,6 @@
 use aide::{
     axum::{
-        routing::{get, post},
+        routing::{delete, get, post},
         ApiRouter, IntoApiResponse,
     },
     openapi::{Info, OpenApi},
@@ -27,7 +27,7 @@ use ic_crypto_utils_threshold_sig_der::parse_threshold_sig_key_from_der;
 use pocket_ic::common::rest::{BinaryBlob, BlobCompression, BlobId, RawVerifyCanisterSigArg};
 use pocket_ic_server::state_api::routes::{handler_read_graph, timeout_or_default};
 use pocket_ic_server::state_api::{
-    routes::{http_gateway_routes, instances_routes, status, AppState, RouterExt},
+    routes::{delete_snapshot, http_gateway_routes, instances_routes, status, AppState, RouterExt},
     state::{ApiState, PocketIcApiStateBuilder},
 };
 use pocket_ic_server::BlobStore;
@@ -156,6 +156,9 @@ async fn start(runtime: Arc<Runtime>) {
         // Verify signature.
         .directory_route("/verify_signature", post(verify_signature))
         //
+        // Delete a snapshot of an IC instance.
+        .directory_route("/snapshots/:id", delete(delete_snapshot))
+        //
         // Read state: Poll a result based on a received Started{} reply.
         .directory_route("/read_graph/:state_label/:op_id", get(handler_read_graph))
         //
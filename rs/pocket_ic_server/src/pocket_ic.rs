@@ -1,11 +1,10 @@
-use crate::state_api::state::{HasStateLabel, OpOut, PocketIcError, StateLabel};
-use crate::{async_trait, copy_dir, BlobStore, OpId, Operation};
+use crate::state_api::state::{HasStateLabel, OpOut, PocketIcError, SnapshotDir, StateLabel};
+use crate::{async_trait, clone_dir, copy_dir, BlobStore, OpId, Operation};
 use askama::Template;
 use axum::{
     extract::State,
     response::{Html, IntoResponse, Response as AxumResponse},
 };
-use bitcoin::Network;
 use candid::{Decode, Principal};
 use futures::future::BoxFuture;
 use futures::FutureExt;
@@ -13,7 +12,7 @@ use hyper::body::Bytes;
 use hyper::header::{HeaderValue, CONTENT_TYPE};
 use hyper::{Method, StatusCode};
 use ic_boundary::{Health, RootKey};
-use ic_btc_adapter::config::{Config as BitcoinAdapterConfig, IncomingSource};
+use ic_btc_adapter::config::{Config as BitcoinAdapterConfig, IncomingSource, Network};
 use ic_btc_adapter::start_server;
 use ic_config::{
     execution_environment, flag_status::FlagStatus, http_handler, logger::Config as LoggerConfig,
@@ -65,9 +64,10 @@ use ic_validator_ingress_message::StandaloneIngressSigVerifier;
 use itertools::Itertools;
 use pocket_ic::common::rest::{
     self, BinaryBlob, BlobCompression, CanisterHttpHeader, CanisterHttpMethod, CanisterHttpRequest,
-    CanisterHttpResponse, DtsFlag, ExtendedSubnetConfigSet, MockCanisterHttpResponse, RawAddCycles,
-    RawCanisterCall, RawCanisterId, RawEffectivePrincipal, RawMessageId, RawSetStableMemory,
-    SubnetInstructionConfig, SubnetKind, SubnetSpec, Topology,
+    CanisterHttpResponse, CanisterHttpResponseRange, DtsFlag, ExtendedSubnetConfigSet,
+    MockCanisterHttpResponse, RawAddCycles, RawCanisterCall, RawCanisterId, RawEffectivePrincipal,
+    RawMessageId, RawSetStableMemory, SnapshotId, SubnetInstructionConfig, SubnetKind, SubnetSpec,
+    Topology,
 };
 use serde::{Deserialize, Serialize};
 use std::hash::Hash;
@@ -78,7 +78,7 @@ use std::{
     fs::{remove_file, File},
     io::{BufReader, Write},
     net::SocketAddr,
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::{Arc, Mutex, RwLock},
     time::{Duration, SystemTime},
 };
@@ -238,43 +238,18 @@ pub struct PocketIc {
     runtime: Arc<Runtime>,
     nonmainnet_features: bool,
     log_level: Option<Level>,
+    instruction_profile_dir: Option<PathBuf>,
     bitcoind_addr: Option<SocketAddr>,
     _bitcoin_adapter_parts: Option<BitcoinAdapterParts>,
 }
 
 impl Drop for PocketIc {
     fn drop(&mut self) {
-        let subnets = self.subnets.read().unwrap();
         if let Some(ref state_dir) = self.state_dir {
-            for subnet in subnets.values() {
-                subnet.checkpointed_tick();
-            }
-            for subnet in subnets.values() {
-                subnet.await_state_hash();
-            }
-            let mut topology_file = File::create(state_dir.join("topology.json")).unwrap();
-            let subnet_configs = self
-                .topology
-                .subnet_configs
-                .iter()
-                .map(|(seed, config)| {
-                    let time = subnets.get(&config.subnet_id).unwrap().time();
-                    (
-                        hex::encode(seed),
-                        RawSubnetConfigInternal {
-                            subnet_config: config.clone(),
-                            time,
-                        },
-                    )
-                })
-                .collect();
-            let raw_topology: RawTopologyInternal = RawTopologyInternal {
-                subnet_configs,
-                default_effective_canister_id: self.topology.default_effective_canister_id.into(),
-            };
-            let topology_json = serde_json::to_string(&raw_topology).unwrap();
-            topology_file.write_all(topology_json.as_bytes()).unwrap();
+            self.checkpoint_subnets();
+            self.write_topology(state_dir).unwrap();
         }
+        let subnets = self.subnets.read().unwrap();
         for subnet in subnets.values() {
             subnet.drop_payload_builder();
         }
@@ -286,6 +261,63 @@ impl PocketIc {
         self.canister_http_adapters.clone()
     }
 
+    /// Writes a checkpoint of the latest state on every subnet (without executing
+    /// a round) and waits until the corresponding state hashes have been computed.
+    fn checkpoint_subnets(&self) {
+        let subnets = self.subnets.read().unwrap();
+        for subnet in subnets.values() {
+            subnet.checkpoint_latest_state();
+        }
+        for subnet in subnets.values() {
+            subnet.await_state_hash();
+        }
+    }
+
+    /// Writes the topology of this instance into `topology.json` in the given directory
+    /// so that the instance can be restored from that directory.
+    fn write_topology(&self, dir: &Path) -> std::io::Result<()> {
+        let subnets = self.subnets.read().unwrap();
+        let mut topology_file = File::create(dir.join("topology.json"))?;
+        let subnet_configs = self
+            .topology
+            .subnet_configs
+            .iter()
+            .map(|(seed, config)| {
+                let time = subnets.get(&config.subnet_id).unwrap().time();
+                (
+                    hex::encode(seed),
+                    RawSubnetConfigInternal {
+                        subnet_config: config.clone(),
+                        time,
+                    },
+                )
+            })
+            .collect();
+        let raw_topology: RawTopologyInternal = RawTopologyInternal {
+            subnet_configs,
+            default_effective_canister_id: self.topology.default_effective_canister_id.into(),
+        };
+        let topology_json = serde_json::to_string(&raw_topology).unwrap();
+        topology_file.write_all(topology_json.as_bytes())
+    }
+
+    /// Stores a checkpoint of every subnet and the topology of this instance
+    /// in the given directory. The directory has the same layout as a `state_dir`
+    /// and can be used to create new instances via `PocketIc::new`.
+    fn snapshot(&self, snapshot_dir: &Path) -> std::io::Result<()> {
+        self.checkpoint_subnets();
+        let subnets = self.subnets.read().unwrap();
+        for (subnet_seed, config) in self.topology.subnet_configs.iter() {
+            let subnet = subnets.get(&config.subnet_id).unwrap();
+            clone_dir(
+                subnet.state_dir_path(),
+                snapshot_dir.join(hex::encode(subnet_seed)),
+            )?;
+        }
+        drop(subnets);
+        self.write_topology(snapshot_dir)
+    }
+
     pub(crate) fn topology(&self) -> Topology {
         let mut subnet_configs = BTreeMap::new();
         let subnets = self.subnets.read().unwrap();
@@ -333,6 +365,7 @@ impl PocketIc {
         time: SystemTime,
         nonmainnet_features: bool,
         log_level: Option<Level>,
+        instruction_profile_dir: Option<PathBuf>,
         bitcoin_adapter_uds_path: Option<PathBuf>,
     ) -> StateMachineBuilder {
         let subnet_type = conv_type(subnet_kind);
@@ -365,6 +398,15 @@ impl PocketIc {
             .embedders_config
             .feature_flags
             .rate_limiting_of_debug_prints = FlagStatus::Disabled;
+        // enable instruction profiling if requested
+        if let Some(instruction_profile_dir) = instruction_profile_dir {
+            hypervisor_config
+                .embedders_config
+                .feature_flags
+                .instruction_profiling = FlagStatus::Enabled;
+            hypervisor_config.embedders_config.instruction_profile_dir =
+                Some(instruction_profile_dir);
+        }
         let state_machine_config = StateMachineConfig::new(subnet_config, hypervisor_config);
         let t = time
             .duration_since(SystemTime::UNIX_EPOCH)
@@ -391,6 +433,8 @@ impl PocketIc {
         nonmainnet_features: bool,
         log_level: Option<Level>,
         bitcoind_addr: Option<SocketAddr>,
+        snapshot_dir: Option<PathBuf>,
+        instruction_profile_dir: Option<PathBuf>,
     ) -> Self {
         let mut range_gen = RangeGen::new();
         let mut routing_table = RoutingTable::new();
@@ -399,32 +443,45 @@ impl PocketIc {
                 .map(|y| SubnetId::new(PrincipalId(y.into())))
         });
 
-        let topology: Option<RawTopologyInternal> = if let Some(ref state_dir) = state_dir {
-            let topology_file_path = state_dir.join("topology.json");
-            File::open(topology_file_path).ok().map(|file| {
-                let reader = BufReader::new(file);
-                serde_json::from_reader(reader).unwrap()
-            })
-        } else {
-            None
-        };
+        // A snapshot takes precedence over a topology persisted in the state directory.
+        let topology: Option<RawTopologyInternal> =
+            if let Some(topology_dir) = snapshot_dir.as_ref().or(state_dir.as_ref()) {
+                let topology_file_path = topology_dir.join("topology.json");
+                File::open(topology_file_path).ok().map(|file| {
+                    let reader = BufReader::new(file);
+                    serde_json::from_reader(reader).unwrap()
+                })
+            } else {
+                None
+            };
 
         let subnet_config_info: Vec<SubnetConfigInfo> = if let Some(topology) = topology {
             topology
                 .subnet_configs
                 .into_iter()
-                .map(|(subnet_seed, config)| SubnetConfigInfo {
-                    state_machine_state_dir: Box::new(
-                        state_dir.as_ref().unwrap().join(subnet_seed.clone()),
-                    ),
-                    subnet_id: Some(config.subnet_config.subnet_id),
-                    ranges: config.subnet_config.ranges,
-                    alloc_range: config.subnet_config.alloc_range,
-                    subnet_kind: config.subnet_config.subnet_kind,
-                    subnet_seed: hex::decode(subnet_seed).unwrap().try_into().unwrap(),
-                    instruction_config: config.subnet_config.instruction_config,
-                    dts_flag: config.subnet_config.dts_flag,
-                    time: config.time,
+                .map(|(subnet_seed, config)| {
+                    let subnet_seed: [u8; 32] =
+                        hex::decode(subnet_seed).unwrap().try_into().unwrap();
+                    let state_machine_state_dir =
+                        Self::create_state_machine_state_dir(&state_dir, &subnet_seed);
+                    if let Some(ref snapshot_dir) = snapshot_dir {
+                        clone_dir(
+                            snapshot_dir.join(hex::encode(subnet_seed)),
+                            state_machine_state_dir.path(),
+                        )
+                        .expect("Failed to copy snapshot");
+                    }
+                    SubnetConfigInfo {
+                        state_machine_state_dir,
+                        subnet_id: Some(config.subnet_config.subnet_id),
+                        ranges: config.subnet_config.ranges,
+                        alloc_range: config.subnet_config.alloc_range,
+                        subnet_kind: config.subnet_config.subnet_kind,
+                        subnet_seed,
+                        instruction_config: config.subnet_config.instruction_config,
+                        dts_flag: config.subnet_config.dts_flag,
+                        time: config.time,
+                    }
                 })
                 .collect()
         } else {
@@ -535,6 +592,7 @@ impl PocketIc {
                 time,
                 nonmainnet_features,
                 log_level,
+                instruction_profile_dir.clone(),
                 bitcoin_adapter_uds_path.clone(),
             );
 
@@ -708,6 +766,7 @@ impl PocketIc {
             runtime,
             nonmainnet_features,
             log_level,
+            instruction_profile_dir,
             bitcoind_addr,
             _bitcoin_adapter_parts,
         }
@@ -978,6 +1037,34 @@ impl Operation for SetTime {
     }
 }
 
+#[derive(Clone, Debug)]
+pub struct TakeSnapshot {
+    pub snapshot_id: SnapshotId,
+    pub snapshot_dir: SnapshotDir,
+}
+
+impl Operation for TakeSnapshot {
+    fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        // Holding the write lock prevents the snapshot from being deleted
+        // or used to create an instance while it is being taken.
+        let snapshot_dir = self.snapshot_dir.blocking_write();
+        let Some(snapshot_dir) = snapshot_dir.as_ref() else {
+            return OpOut::Error(PocketIcError::SnapshotFailed(format!(
+                "Snapshot {} has been deleted.",
+                self.snapshot_id
+            )));
+        };
+        match pic.snapshot(snapshot_dir.path()) {
+            Ok(()) => OpOut::SnapshotId(self.snapshot_id),
+            Err(e) => OpOut::Error(PocketIcError::SnapshotFailed(e.to_string())),
+        }
+    }
+
+    fn id(&self) -> OpId {
+        OpId(format!("take_snapshot_{}", self.snapshot_id))
+    }
+}
+
 #[derive(Copy, Clone, Debug)]
 pub struct GetTopology;
 
@@ -1046,6 +1133,10 @@ fn get_canister_http_requests(pic: &PocketIc) -> Vec<CanisterHttpRequest> {
                 headers: c.headers.iter().map(http_header_from).collect(),
                 body: c.body.unwrap_or_default(),
                 max_response_bytes: c.max_response_bytes.map(|b| b.get()),
+                response_range: c.response_range.map(|range| CanisterHttpResponseRange {
+                    offset: range.offset,
+                    length: range.length,
+                }),
             })
             .collect();
         canister_http.append(&mut cur);
@@ -2205,6 +2296,35 @@ impl Operation for GetStableMemory {
     }
 }
 
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct GetCanisterLogs {
+    pub canister_id: CanisterId,
+    pub start_idx: u64,
+}
+
+impl Operation for GetCanisterLogs {
+    fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        match pic.try_route_canister(self.canister_id) {
+            Some(sm) if sm.canister_exists(self.canister_id) => OpOut::CanisterLogs(
+                sm.canister_log(self.canister_id)
+                    .records()
+                    .iter()
+                    .filter(|record| record.idx >= self.start_idx)
+                    .cloned()
+                    .collect(),
+            ),
+            _ => OpOut::Error(PocketIcError::CanisterNotFound(self.canister_id)),
+        }
+    }
+
+    fn id(&self) -> OpId {
+        OpId(format!(
+            "get_canister_logs({},{})",
+            self.canister_id, self.start_idx
+        ))
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct GetCyclesBalance {
     pub canister_id: CanisterId,
@@ -2415,6 +2535,7 @@ fn route(
                         time,
                         pic.nonmainnet_features,
                         pic.log_level,
+                        pic.instruction_profile_dir.clone(),
                         bitcoin_adapter_uds_path.clone(),
                     );
                     let sm = builder.build_with_subnets(pic.subnets.clone());
@@ -2759,6 +2880,8 @@ mod tests {
             false,
             None,
             None,
+            None,
+            None,
         );
         let canister_id = pic.any_subnet().create_canister(None);
 
@@ -7,9 +7,10 @@
 use super::state::{ApiState, OpOut, PocketIcError, StateLabel, UpdateReply};
 use crate::pocket_ic::{
     AddCycles, AwaitIngressMessage, CallRequest, CallRequestVersion, CanisterReadStateRequest,
-    DashboardRequest, ExecuteIngressMessage, GetCanisterHttp, GetCyclesBalance, GetStableMemory,
-    GetSubnet, GetTime, GetTopology, MockCanisterHttp, PubKey, Query, QueryRequest,
-    SetStableMemory, SetTime, StatusRequest, SubmitIngressMessage, SubnetReadStateRequest, Tick,
+    DashboardRequest, ExecuteIngressMessage, GetCanisterHttp, GetCanisterLogs, GetCyclesBalance,
+    GetStableMemory, GetSubnet, GetTime, GetTopology, MockCanisterHttp, PubKey, Query,
+    QueryRequest, SetStableMemory, SetTime, StatusRequest, SubmitIngressMessage,
+    SubnetReadStateRequest, TakeSnapshot, Tick,
 };
 use crate::{async_trait, pocket_ic::PocketIc, BlobStore, InstanceId, OpId, Operation};
 use aide::{
@@ -37,9 +38,10 @@ use ic_types::{CanisterId, SubnetId};
 use pocket_ic::common::rest::{
     self, ApiResponse, AutoProgressConfig, ExtendedSubnetConfigSet, HttpGatewayConfig,
     HttpGatewayDetails, InstanceConfig, MockCanisterHttpResponse, RawAddCycles, RawCanisterCall,
-    RawCanisterHttpRequest, RawCanisterId, RawCanisterResult, RawCycles, RawMessageId,
-    RawMockCanisterHttpResponse, RawSetStableMemory, RawStableMemory, RawSubmitIngressResult,
-    RawSubnetId, RawTime, RawWasmResult, Topology,
+    RawCanisterHttpRequest, RawCanisterId, RawCanisterLogRecord, RawCanisterResult, RawCycles,
+    RawMessageId, RawMockCanisterHttpResponse, RawSetStableMemory, RawSnapshotId, RawStableMemory,
+    RawStreamCanisterLogs, RawSubmitIngressResult, RawSubnetId, RawTime, RawWasmResult, SnapshotId,
+    Topology,
 };
 use pocket_ic::WasmResult;
 use serde::Serialize;
@@ -56,6 +58,9 @@ type PocketHttpResponse = (BTreeMap<String, Vec<u8>>, Vec<u8>);
 pub static TIMEOUT_HEADER_NAME: HeaderName = HeaderName::from_static("processing-timeout-ms");
 const RETRY_TIMEOUT_S: u64 = 300;
 
+/// Interval at which a canister log stream checks for new log records.
+const CANISTER_LOGS_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
 #[derive(Clone)]
 pub struct AppState {
     pub api_state: Arc<ApiState>,
@@ -76,6 +81,7 @@ where
         .directory_route("/get_canister_http", get(handler_get_canister_http))
         .directory_route("/get_cycles", post(handler_get_cycles))
         .directory_route("/get_stable_memory", post(handler_get_stable_memory))
+        .directory_route("/stream_canister_logs", post(handler_stream_canister_logs))
         .directory_route("/get_subnet", post(handler_get_subnet))
         .directory_route("/pub_key", post(handler_pub_key))
 }
@@ -202,6 +208,10 @@ where
         // Stop automatic progress (see endpoint `auto_progress`)
         // on an IC instance.
         .api_route("/:id/stop_progress", post(stop_progress))
+        //
+        // Takes a snapshot of an IC instance. Returns a SnapshotId
+        // from which new IC instances can be created.
+        .api_route("/:id/snapshot", post(handler_take_snapshot))
         .layer(cors_layer())
 }
 
@@ -352,6 +362,16 @@ impl TryFrom<OpOut> for Topology {
     }
 }
 
+impl TryFrom<OpOut> for RawSnapshotId {
+    type Error = OpConversionError;
+    fn try_from(value: OpOut) -> Result<Self, Self::Error> {
+        match value {
+            OpOut::SnapshotId(snapshot_id) => Ok(RawSnapshotId { snapshot_id }),
+            _ => Err(OpConversionError),
+        }
+    }
+}
+
 impl TryFrom<OpOut> for () {
     type Error = OpConversionError;
     fn try_from(value: OpOut) -> Result<Self, Self::Error> {
@@ -384,6 +404,23 @@ impl TryFrom<OpOut> for RawStableMemory {
     }
 }
 
+impl TryFrom<OpOut> for Vec<RawCanisterLogRecord> {
+    type Error = OpConversionError;
+    fn try_from(value: OpOut) -> Result<Self, Self::Error> {
+        match value {
+            OpOut::CanisterLogs(records) => Ok(records
+                .into_iter()
+                .map(|record| RawCanisterLogRecord {
+                    idx: record.idx,
+                    timestamp_nanos: record.timestamp_nanos,
+                    content: record.content,
+                })
+                .collect()),
+            _ => Err(OpConversionError),
+        }
+    }
+}
+
 impl TryFrom<OpOut> for RawCanisterResult {
     type Error = OpConversionError;
     fn try_from(value: OpOut) -> Result<Self, Self::Error> {
@@ -626,6 +663,82 @@ pub async fn handler_get_stable_memory(
     }
 }
 
+/// Streams the log records of a canister as newline-delimited JSON, one
+/// `RawCanisterLogRecord` per line. The log is polled for new records until
+/// the client disconnects or the canister (or instance) no longer exists.
+pub async fn handler_stream_canister_logs(
+    State(AppState { api_state, .. }): State<AppState>,
+    Path(instance_id): Path<InstanceId>,
+    axum::extract::Json(raw_stream_canister_logs): axum::extract::Json<RawStreamCanisterLogs>,
+) -> (StatusCode, NoApi<Response<Body>>) {
+    let canister_id = match CanisterId::try_from(raw_stream_canister_logs.canister_id) {
+        Ok(canister_id) => canister_id,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                NoApi(
+                    Json(ApiResponse::<()>::Error {
+                        message: format!("{:?}", e),
+                    })
+                    .into_response(),
+                ),
+            )
+        }
+    };
+    let start_idx = raw_stream_canister_logs.start_idx.unwrap_or_default();
+    let stream = futures::stream::unfold(
+        (api_state, start_idx, true),
+        move |(api_state, start_idx, first)| async move {
+            if !first {
+                tokio::time::sleep(CANISTER_LOGS_POLL_INTERVAL).await;
+            }
+            let op = GetCanisterLogs {
+                canister_id,
+                start_idx,
+            };
+            let (_, response) = run_operation::<Vec<RawCanisterLogRecord>>(
+                api_state.clone(),
+                instance_id,
+                None,
+                op,
+            )
+            .await;
+            match response {
+                ApiResponse::Success(records) => {
+                    let next_idx = records.last().map_or(start_idx, |record| record.idx + 1);
+                    let mut chunk = vec![];
+                    for record in records {
+                        serde_json::to_writer(&mut chunk, &record).unwrap();
+                        chunk.push(b'\n');
+                    }
+                    Some((
+                        Ok::<_, std::convert::Infallible>(Bytes::from(chunk)),
+                        (api_state, next_idx, false),
+                    ))
+                }
+                // The instance is busy: try again after the poll interval.
+                ApiResponse::Busy { .. } | ApiResponse::Started { .. } => {
+                    Some((Ok(Bytes::new()), (api_state, start_idx, false)))
+                }
+                ApiResponse::Error { message } => {
+                    trace!(
+                        "stream_canister_logs::end instance_id={} canister_id={} error={}",
+                        instance_id,
+                        canister_id,
+                        message
+                    );
+                    None
+                }
+            }
+        },
+    );
+    let response = Response::builder()
+        .header(header::CONTENT_TYPE, "application/x-ndjson")
+        .body(Body::from_stream(stream))
+        .unwrap();
+    (StatusCode::OK, NoApi(response))
+}
+
 pub async fn handler_get_subnet(
     State(AppState { api_state, .. }): State<AppState>,
     Path(instance_id): Path<InstanceId>,
@@ -840,6 +953,20 @@ async fn op_out_to_response(op_out: OpOut) -> Response {
             )),
         )
             .into_response(),
+        opout @ OpOut::CanisterLogs(_) => (
+            StatusCode::OK,
+            Json(ApiResponse::Success(
+                Vec::<RawCanisterLogRecord>::try_from(opout).unwrap(),
+            )),
+        )
+            .into_response(),
+        opout @ OpOut::SnapshotId(_) => (
+            StatusCode::OK,
+            Json(ApiResponse::Success(
+                RawSnapshotId::try_from(opout).unwrap(),
+            )),
+        )
+            .into_response(),
         OpOut::RawResponse(fut) => {
             let (status, headers, bytes) = fut.await;
             let code = StatusCode::from_u16(status).unwrap();
@@ -1034,6 +1161,25 @@ pub async fn handler_tick(
     (code, Json(res))
 }
 
+pub async fn handler_take_snapshot(
+    State(AppState { api_state, .. }): State<AppState>,
+    Path(instance_id): Path<InstanceId>,
+    headers: HeaderMap,
+) -> (StatusCode, Json<ApiResponse<RawSnapshotId>>) {
+    let timeout = timeout_or_default(headers);
+    let (snapshot_id, snapshot_dir) = api_state.new_snapshot_dir().await;
+    let op = TakeSnapshot {
+        snapshot_id,
+        snapshot_dir,
+    };
+    let (code, res) = run_operation(api_state.clone(), instance_id, timeout, op).await;
+    // The snapshot directory is only kept if the snapshot was (or is being) taken.
+    if !matches!(res, ApiResponse::Success(_) | ApiResponse::Started { .. }) {
+        api_state.delete_snapshot(snapshot_id).await;
+    }
+    (code, Json(res))
+}
+
 // ----------------------------------------------------------------------------------------------------------------- //
 // Other handlers
 
@@ -1059,7 +1205,8 @@ fn contains_unimplemented(config: ExtendedSubnetConfigSet) -> bool {
     )
 }
 
-/// Create a new empty IC instance from a given subnet configuration.
+/// Create a new empty IC instance from a given subnet configuration
+/// or from a snapshot of another IC instance.
 /// The new InstanceId will be returned.
 pub async fn create_instance(
     State(AppState {
@@ -1072,11 +1219,49 @@ pub async fn create_instance(
 ) -> (StatusCode, Json<rest::CreateInstanceResponse>) {
     let subnet_configs = instance_config.subnet_config_set;
 
-    let skip_validate_subnet_configs = instance_config
+    let state_dir_has_topology = instance_config
         .state_dir
         .as_ref()
         .map(|state_dir| File::open(state_dir.clone().join("topology.json")).is_ok())
         .unwrap_or_default();
+
+    let snapshot_dir = if let Some(snapshot_id) = instance_config.snapshot_id {
+        if state_dir_has_topology {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(rest::CreateInstanceResponse::Error {
+                    message: "Cannot create an instance from a snapshot in a state directory that already contains a topology.".to_owned(),
+                }),
+            );
+        }
+        // The read lock is held until the instance has been created
+        // so that the snapshot cannot be deleted in the meantime.
+        let snapshot_dir = match api_state.get_snapshot_dir(snapshot_id).await {
+            Some(snapshot_dir) => Some(snapshot_dir.read_owned().await),
+            None => None,
+        };
+        match snapshot_dir {
+            Some(snapshot_dir)
+                if snapshot_dir.as_ref().is_some_and(|snapshot_dir| {
+                    File::open(snapshot_dir.path().join("topology.json")).is_ok()
+                }) =>
+            {
+                Some(snapshot_dir)
+            }
+            _ => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(rest::CreateInstanceResponse::Error {
+                        message: format!("Snapshot {} is not available.", snapshot_id),
+                    }),
+                )
+            }
+        }
+    } else {
+        None
+    };
+
+    let skip_validate_subnet_configs = state_dir_has_topology || snapshot_dir.is_some();
     if !skip_validate_subnet_configs {
         if let Err(e) = subnet_configs.validate() {
             return (
@@ -1114,14 +1299,22 @@ pub async fn create_instance(
     };
 
     let pocket_ic = tokio::task::spawn_blocking(move || {
-        PocketIc::new(
+        let snapshot_path = snapshot_dir
+            .as_ref()
+            .and_then(|snapshot_dir| snapshot_dir.as_ref())
+            .map(|snapshot_dir| snapshot_dir.path().to_path_buf());
+        let pocket_ic = PocketIc::new(
             runtime,
             subnet_configs,
             instance_config.state_dir,
             instance_config.nonmainnet_features,
             log_level,
             instance_config.bitcoind_addr,
-        )
+            snapshot_path,
+            instance_config.instruction_profile_dir,
+        );
+        drop(snapshot_dir);
+        pocket_ic
     })
     .await
     .expect("Failed to launch PocketIC");
@@ -1152,6 +1345,14 @@ pub async fn delete_instance(
     StatusCode::OK
 }
 
+pub async fn delete_snapshot(
+    State(AppState { api_state, .. }): State<AppState>,
+    Path(id): Path<SnapshotId>,
+) -> StatusCode {
+    api_state.delete_snapshot(id).await;
+    StatusCode::OK
+}
+
 pub async fn list_http_gateways(
     State(AppState { api_state, .. }): State<AppState>,
 ) -> Json<Vec<HttpGatewayDetails>> {
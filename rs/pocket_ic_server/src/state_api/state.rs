@@ -36,9 +36,10 @@ use ic_http_gateway::{CanisterRequest, HttpGatewayClient, HttpGatewayRequestArgs
 use ic_https_outcalls_adapter::CanisterHttp;
 use ic_https_outcalls_adapter_client::grpc_status_code_to_reject;
 use ic_https_outcalls_service::{
-    https_outcalls_service_server::HttpsOutcallsService, HttpHeader, HttpMethod,
+    https_outcalls_service_server::HttpsOutcallsService, HttpHeader, HttpMethod, HttpResponseRange,
     HttpsOutcallRequest, HttpsOutcallResponse,
 };
+use ic_management_canister_types::CanisterLogRecord;
 use ic_state_machine_tests::RejectCode;
 use ic_types::{
     canister_http::{CanisterHttpRequestId, MAX_CANISTER_HTTP_RESPONSE_BYTES},
@@ -47,11 +48,12 @@ use ic_types::{
 use pocket_ic::common::rest::{
     CanisterHttpHeader, CanisterHttpMethod, CanisterHttpReject, CanisterHttpReply,
     CanisterHttpRequest, CanisterHttpResponse, HttpGatewayBackend, HttpGatewayConfig,
-    HttpGatewayDetails, HttpGatewayInfo, MockCanisterHttpResponse, Topology,
+    HttpGatewayDetails, HttpGatewayInfo, MockCanisterHttpResponse, SnapshotId, Topology,
 };
 use pocket_ic::{ErrorCode, UserError, WasmResult};
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, fmt, path::PathBuf, str::FromStr, sync::Arc, time::Duration};
+use tempfile::TempDir;
 use tokio::{
     sync::mpsc::error::TryRecvError,
     sync::mpsc::Receiver,
@@ -123,8 +125,15 @@ pub struct ApiState {
     port: Option<u16>,
     // HTTP gateway infos (`None` = stopped)
     http_gateways: Arc<RwLock<Vec<Option<HttpGatewayDetails>>>>,
+    // snapshots of IC instances
+    snapshots: Arc<RwLock<Vec<SnapshotDir>>>,
 }
 
+/// The directory of a snapshot (`None` = deleted). Taking and deleting the snapshot
+/// hold the write lock and creating an instance from the snapshot holds the read lock
+/// so that a snapshot is never deleted while it is being taken or used.
+pub type SnapshotDir = Arc<RwLock<Option<TempDir>>>;
+
 #[derive(Default)]
 pub struct PocketIcApiStateBuilder {
     initial_instances: Vec<PocketIc>,
@@ -195,6 +204,7 @@ impl PocketIcApiStateBuilder {
             sync_wait_time,
             port: self.port,
             http_gateways: Arc::new(RwLock::new(Vec::new())),
+            snapshots: Arc::new(RwLock::new(Vec::new())),
         })
     }
 }
@@ -214,6 +224,8 @@ pub enum OpOut {
     MessageId((EffectivePrincipal, Vec<u8>)),
     Topology(Topology),
     CanisterHttp(Vec<CanisterHttpRequest>),
+    SnapshotId(SnapshotId),
+    CanisterLogs(Vec<CanisterLogRecord>),
 }
 
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Debug, Deserialize, Serialize)]
@@ -224,6 +236,7 @@ pub enum PocketIcError {
     RequestRoutingError(String),
     InvalidCanisterHttpRequestId((SubnetId, CanisterHttpRequestId)),
     InvalidMockCanisterHttpResponses((usize, usize)),
+    SnapshotFailed(String),
 }
 
 impl From<Result<ic_state_machine_tests::WasmResult, ic_state_machine_tests::UserError>> for OpOut {
@@ -299,6 +312,9 @@ impl std::fmt::Debug for OpOut {
                     actual, expected
                 )
             }
+            OpOut::Error(PocketIcError::SnapshotFailed(msg)) => {
+                write!(f, "SnapshotFailed({})", msg)
+            }
             OpOut::Bytes(bytes) => write!(f, "Bytes({})", base64::encode(bytes)),
             OpOut::StableMemBytes(bytes) => write!(f, "StableMemory({})", base64::encode(bytes)),
             OpOut::MaybeSubnetId(Some(subnet_id)) => write!(f, "SubnetId({})", subnet_id),
@@ -326,6 +342,8 @@ impl std::fmt::Debug for OpOut {
             OpOut::CanisterHttp(canister_http_reqeusts) => {
                 write!(f, "CanisterHttp({:?})", canister_http_reqeusts)
             }
+            OpOut::SnapshotId(snapshot_id) => write!(f, "SnapshotId({})", snapshot_id),
+            OpOut::CanisterLogs(records) => write!(f, "CanisterLogs({:?})", records),
         }
     }
 }
@@ -1089,6 +1107,12 @@ impl ApiState {
                 .collect(),
             body: canister_http_request.body,
             socks_proxy_allowed: false,
+            response_range: canister_http_request
+                .response_range
+                .map(|range| HttpResponseRange {
+                    offset: range.offset,
+                    length: range.length,
+                }),
         };
         let request = TonicRequest::new(canister_http_request);
         canister_http_adapter
@@ -1271,6 +1295,29 @@ impl ApiState {
         res
     }
 
+    /// Allocates a fresh directory for a new snapshot and returns its id and directory.
+    pub async fn new_snapshot_dir(&self) -> (SnapshotId, SnapshotDir) {
+        let mut snapshots = self.snapshots.write().await;
+        let snapshot_dir = TempDir::new().expect("Failed to create snapshot directory");
+        let snapshot_id = snapshots.len();
+        let snapshot_dir = Arc::new(RwLock::new(Some(snapshot_dir)));
+        snapshots.push(snapshot_dir.clone());
+        (snapshot_id, snapshot_dir)
+    }
+
+    /// Returns the directory of a snapshot if the snapshot id is known.
+    pub async fn get_snapshot_dir(&self, snapshot_id: SnapshotId) -> Option<SnapshotDir> {
+        let snapshots = self.snapshots.read().await;
+        snapshots.get(snapshot_id).cloned()
+    }
+
+    /// Deletes a snapshot, waiting until it is no longer being taken or used.
+    pub async fn delete_snapshot(&self, snapshot_id: SnapshotId) {
+        if let Some(snapshot_dir) = self.get_snapshot_dir(snapshot_id).await {
+            *snapshot_dir.write().await = None;
+        }
+    }
+
     pub async fn list_http_gateways(&self) -> Vec<HttpGatewayDetails> {
         self.http_gateways
             .read()
@@ -100,6 +100,8 @@ fn test_creation_of_instance_extended() {
         nonmainnet_features: false,
         log_level: None,
         bitcoind_addr: None,
+        snapshot_id: None,
+        instruction_profile_dir: None,
     };
     let response = client
         .post(url.join("instances").unwrap())
@@ -852,6 +854,114 @@ fn canister_state_dir(shutdown_signal: Option<Signal>) {
     check_counter(&pic, app_canister_id, 3);
 }
 
+/// Tests that new PocketIC instances can be created from a snapshot
+/// of another PocketIC instance and that all these instances evolve independently.
+#[test]
+fn snapshot_and_fork_instances() {
+    const INIT_CYCLES: u128 = 2_000_000_000_000;
+
+    let server_url = start_server();
+    let pic = PocketIcBuilder::new()
+        .with_server_url(server_url.clone())
+        .with_nns_subnet()
+        .with_application_subnet()
+        .build();
+
+    // We create a counter canister on the application subnet and bump the counter once.
+    let canister_id = pic.create_canister();
+    pic.add_cycles(canister_id, INIT_CYCLES);
+    let counter_wasm = wat::parse_str(COUNTER_WAT).unwrap();
+    pic.install_canister(canister_id, counter_wasm, vec![], None);
+    pic.update_call(canister_id, Principal::anonymous(), "write", vec![])
+        .unwrap();
+    check_counter(&pic, canister_id, 1);
+
+    // Taking a snapshot does not execute a round on the original instance.
+    let time = pic.get_time();
+    let snapshot_id = pic.take_snapshot();
+    assert_eq!(pic.get_time(), time);
+
+    // Bumping the counter after the snapshot does not affect the snapshot.
+    pic.update_call(canister_id, Principal::anonymous(), "write", vec![])
+        .unwrap();
+    check_counter(&pic, canister_id, 2);
+
+    let fork_1 = PocketIcBuilder::new()
+        .with_server_url(server_url.clone())
+        .with_snapshot(snapshot_id)
+        .build();
+    let fork_2 = PocketIcBuilder::new()
+        .with_server_url(server_url.clone())
+        .with_snapshot(snapshot_id)
+        .build();
+
+    // The forks have the same subnets as the original instance.
+    let topology = pic.topology();
+    for fork in [&fork_1, &fork_2] {
+        let fork_topology = fork.topology();
+        assert_eq!(fork_topology.get_nns(), topology.get_nns());
+        assert_eq!(fork_topology.get_app_subnets(), topology.get_app_subnets());
+    }
+
+    // The forks start from the state at the time of the snapshot.
+    check_counter(&fork_1, canister_id, 1);
+    check_counter(&fork_2, canister_id, 1);
+
+    // The forks evolve independently.
+    fork_1
+        .update_call(canister_id, Principal::anonymous(), "write", vec![])
+        .unwrap();
+    check_counter(&fork_1, canister_id, 2);
+    check_counter(&fork_2, canister_id, 1);
+    check_counter(&pic, canister_id, 2);
+
+    // A deleted snapshot cannot be used to create new instances,
+    // but the instances created from it are not affected.
+    pic.delete_snapshot(snapshot_id);
+    let instance_config = InstanceConfig {
+        subnet_config_set: SubnetConfigSet::default().into(),
+        state_dir: None,
+        nonmainnet_features: false,
+        log_level: None,
+        bitcoind_addr: None,
+        snapshot_id: Some(snapshot_id),
+        instruction_profile_dir: None,
+    };
+    let response = Client::new()
+        .post(server_url.join("instances").unwrap())
+        .json(&instance_config)
+        .send()
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    check_counter(&fork_1, canister_id, 2);
+}
+
+#[test]
+fn create_instance_from_unknown_snapshot() {
+    let url = start_server();
+    let client = Client::new();
+    let instance_config = InstanceConfig {
+        subnet_config_set: SubnetConfigSet::default().into(),
+        state_dir: None,
+        nonmainnet_features: false,
+        log_level: None,
+        bitcoind_addr: None,
+        snapshot_id: Some(42),
+        instruction_profile_dir: None,
+    };
+    let response = client
+        .post(url.join("instances").unwrap())
+        .json(&instance_config)
+        .send()
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert!(response
+        .text()
+        .unwrap()
+        .contains("Snapshot 42 is not available"));
+}
+
 /// Test that PocketIC can handle synchronous update calls, i.e. `/api/v3/.../call`.
 #[test]
 fn test_specified_id_call_v3() {
@@ -24,4 +24,35 @@ pub struct CanisterSnapshotBits {
     pub total_size: u64,
     #[prost(message, repeated, tag = "11")]
     pub exported_globals: ::prost::alloc::vec::Vec<super::super::canister_state_bits::v1::Global>,
+    #[prost(enumeration = "SnapshotSource", tag = "12")]
+    pub source: i32,
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum SnapshotSource {
+    Unspecified = 0,
+    TakenFromCanister = 1,
+    MetadataUpload = 2,
+}
+impl SnapshotSource {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Self::Unspecified => "SNAPSHOT_SOURCE_UNSPECIFIED",
+            Self::TakenFromCanister => "SNAPSHOT_SOURCE_TAKEN_FROM_CANISTER",
+            Self::MetadataUpload => "SNAPSHOT_SOURCE_METADATA_UPLOAD",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "SNAPSHOT_SOURCE_UNSPECIFIED" => Some(Self::Unspecified),
+            "SNAPSHOT_SOURCE_TAKEN_FROM_CANISTER" => Some(Self::TakenFromCanister),
+            "SNAPSHOT_SOURCE_METADATA_UPLOAD" => Some(Self::MetadataUpload),
+            _ => None,
+        }
+    }
 }
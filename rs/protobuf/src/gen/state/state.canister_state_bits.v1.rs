@@ -539,6 +539,9 @@ pub struct CanisterLogRecord {
     pub timestamp_nanos: u64,
     #[prost(bytes = "vec", tag = "3")]
     pub content: ::prost::alloc::vec::Vec<u8>,
+    /// Whether the record was added because the execution trapped.
+    #[prost(bool, tag = "4")]
+    pub is_trap: bool,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SnapshotId {
@@ -546,6 +549,13 @@ pub struct SnapshotId {
     pub content: ::prost::alloc::vec::Vec<u8>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EnvironmentVariable {
+    #[prost(string, tag = "1")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub value: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CanisterStateBits {
     #[prost(uint64, tag = "2")]
     pub last_full_execution_round: u64,
@@ -650,6 +660,9 @@ pub struct CanisterStateBits {
     pub wasm_memory_threshold: ::core::option::Option<u64>,
     #[prost(enumeration = "OnLowWasmMemoryHookStatus", optional, tag = "53")]
     pub on_low_wasm_memory_hook_status: ::core::option::Option<i32>,
+    /// Environment variables of the canister, sorted by name.
+    #[prost(message, repeated, tag = "54")]
+    pub environment_variables: ::prost::alloc::vec::Vec<EnvironmentVariable>,
     #[prost(oneof = "canister_state_bits::CanisterStatus", tags = "11, 12, 13")]
     pub canister_status: ::core::option::Option<canister_state_bits::CanisterStatus>,
 }
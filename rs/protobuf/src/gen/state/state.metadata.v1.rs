@@ -148,6 +148,13 @@ pub struct HttpHeader {
     #[prost(string, tag = "2")]
     pub value: ::prost::alloc::string::String,
 }
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct HttpResponseRange {
+    #[prost(uint64, tag = "1")]
+    pub offset: u64,
+    #[prost(uint64, tag = "2")]
+    pub length: u64,
+}
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CanisterHttpRequestContext {
     #[prost(message, optional, tag = "1")]
@@ -168,6 +175,11 @@ pub struct CanisterHttpRequestContext {
     pub max_response_bytes: ::core::option::Option<u64>,
     #[prost(message, optional, tag = "10")]
     pub transform_context: ::core::option::Option<::prost::alloc::vec::Vec<u8>>,
+    #[prost(message, optional, tag = "11")]
+    pub response_range: ::core::option::Option<HttpResponseRange>,
+    /// Set iff the request is only made by the given node.
+    #[prost(message, optional, tag = "12")]
+    pub non_replicated_node_id: ::core::option::Option<super::super::super::types::v1::NodeId>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CanisterHttpRequestContextTree {
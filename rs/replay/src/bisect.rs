@@ -0,0 +1,372 @@
+//! Bisection of the heights above the latest checkpoint, to find the first height at which the
+//! replayed state diverges from the certified state.
+//!
+//! Every probe replays the finalized blocks from the latest checkpoint up to the probed height,
+//! where a new checkpoint is created, and compares its state hash with the certification found in
+//! the certification pool. If the hashes match, the new checkpoint becomes the starting point of
+//! the following probes. Otherwise, all checkpoints above the last matching height are marked as
+//! diverged, including the ones the replay created at summary heights below the probed height, so
+//! that the following probes start from the last matching checkpoint again.
+
+use crate::player::{Player, ReplayError, ReplayResult};
+use ic_canonical_state::lazy_tree_conversion::replicated_state_as_lazy_tree;
+use ic_canonical_state_tree_hash::{hash_tree::hash_lazy_tree, lazy_tree::LazyTree};
+use ic_config::Config;
+use ic_crypto_tree_hash::{Digest, Label};
+use ic_replicated_state::ReplicatedState;
+use ic_types::{CryptoHashOfPartialState, Height, PrincipalId, SubnetId};
+use std::{collections::BTreeMap, fmt};
+
+const CANISTER_LABEL: &[u8] = b"canister";
+
+/// The state hash computed by replaying up to `height`, and the certified state hash at `height`.
+#[derive(Clone, Debug)]
+pub(crate) struct CertifiedHashComparison {
+    pub height: Height,
+    pub local_hash: CryptoHashOfPartialState,
+    pub certified_hash: CryptoHashOfPartialState,
+}
+
+impl CertifiedHashComparison {
+    fn matches(&self) -> bool {
+        self.local_hash == self.certified_hash
+    }
+}
+
+/// Binary-searches the certified heights between the latest checkpoint and `to_height` (or the
+/// finalized height, if not specified) for the first height whose replayed state hash does not
+/// match its certification.
+///
+/// Returns `ReplayError::StateDivergence` with the first diverging height, or the latest state
+/// parameters if all probed heights match.
+pub(crate) fn bisect(cfg: &Config, subnet_id: SubnetId, to_height: Option<Height>) -> ReplayResult {
+    let player = Player::new(cfg.clone(), subnet_id);
+    let start_height = player.latest_state_height();
+    let finalized_height = player.get_finalized_height();
+    let to_height = to_height.unwrap_or(finalized_height).min(finalized_height);
+
+    // The start height is known to match, the certified heights above it are the candidates.
+    let heights: Vec<Height> = std::iter::once(start_height)
+        .chain(
+            player
+                .get_certified_heights()
+                .into_iter()
+                .filter(|h| start_height < *h && *h <= to_height),
+        )
+        .collect();
+    if heights.len() < 2 {
+        println!(
+            "No certifications found between the checkpoint at height {} and height {}.",
+            start_height, to_height
+        );
+        return Ok(player.get_latest_state_params(None, Vec::new()));
+    }
+    println!(
+        "Bisecting {} certified heights between the checkpoint at height {} and height {}.",
+        heights.len() - 1,
+        start_height,
+        to_height
+    );
+
+    // Invariant: the state at `heights[lo]` matches its certification, while the state at
+    // `heights[hi]` diverges, once the latter has been probed.
+    let (mut lo, mut hi) = (0, heights.len() - 1);
+    let mut divergence = match probe(player, heights[lo], heights[hi])? {
+        Ok(player) => {
+            println!("No divergence found up to height {}.", heights[hi]);
+            return Ok(player.get_latest_state_params(None, Vec::new()));
+        }
+        Err(divergence) => divergence,
+    };
+    while hi - lo > 1 {
+        let mid = lo + (hi - lo) / 2;
+        match probe(
+            Player::new(cfg.clone(), subnet_id),
+            heights[lo],
+            heights[mid],
+        )? {
+            Ok(_) => lo = mid,
+            Err(mid_divergence) => {
+                hi = mid;
+                divergence = mid_divergence;
+            }
+        }
+    }
+
+    // The divergence at `heights[hi]` was found by replaying from the matching height at the
+    // time, which later probes may have moved up. Replay once more from `heights[lo]`, so that
+    // the reported last matching height and breakdown cover only the first diverging height.
+    if divergence.last_matching_height != heights[lo] {
+        divergence = match probe(
+            Player::new(cfg.clone(), subnet_id),
+            heights[lo],
+            heights[hi],
+        )? {
+            Ok(_) => panic!(
+                "State at height {} diverged when replayed from height {}, but matches its \
+                 certification when replayed from height {}",
+                heights[hi], divergence.last_matching_height, heights[lo]
+            ),
+            Err(divergence) => divergence,
+        };
+    }
+
+    println!("{}", divergence);
+    Err(ReplayError::StateDivergence(divergence.comparison.height))
+}
+
+/// Replays from the latest checkpoint at `last_matching_height` up to `height` and compares the
+/// state hash with the certification. Returns the player if the hashes match, and the
+/// divergence otherwise.
+fn probe(
+    player: Player,
+    last_matching_height: Height,
+    height: Height,
+) -> Result<Result<Player, Divergence>, ReplayError> {
+    assert_eq!(
+        player.latest_state_height(),
+        last_matching_height,
+        "The replay must start from the last matching checkpoint"
+    );
+    println!("Probing height {}...", height);
+    let comparison = player.replay_and_compare_with_certification(height)?;
+    if comparison.matches() {
+        println!("State hash at height {} matches the certification.", height);
+        return Ok(Ok(player));
+    }
+    println!(
+        "State hash at height {} differs from the certification.",
+        height
+    );
+
+    // States between the checkpoint and the probed height are still in memory.
+    let breakdown = match (
+        player.get_state_at(last_matching_height),
+        player.get_state_at(height),
+    ) {
+        (Some(before), Some(after)) => Some(StateTreeChanges::new(&before, &after)),
+        _ => None,
+    };
+    player.mark_checkpoints_diverged_above(last_matching_height);
+    Ok(Err(Divergence {
+        last_matching_height,
+        comparison,
+        breakdown,
+    }))
+}
+
+/// The first diverging height found by the bisection.
+struct Divergence {
+    last_matching_height: Height,
+    comparison: CertifiedHashComparison,
+    /// The changes of the state tree between the last matching and the first diverging height.
+    breakdown: Option<StateTreeChanges>,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "First diverging height: {} (last matching height: {})",
+            self.comparison.height, self.last_matching_height
+        )?;
+        writeln!(
+            f,
+            "Local state hash: {}",
+            hex::encode(&self.comparison.local_hash.get_ref().0)
+        )?;
+        writeln!(
+            f,
+            "Certified state hash: {}",
+            hex::encode(&self.comparison.certified_hash.get_ref().0)
+        )?;
+        match &self.breakdown {
+            Some(breakdown) => write!(
+                f,
+                "Subtrees of the state tree that changed between heights {} and {}:\n{}",
+                self.last_matching_height, self.comparison.height, breakdown
+            ),
+            None => write!(f, "The states are not available for a breakdown."),
+        }
+    }
+}
+
+/// How a subtree of the state tree changed between two states.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum SubtreeChange {
+    Added,
+    Removed,
+    Modified,
+}
+
+/// The changed subtrees of the state tree between two states, with a per-canister breakdown of
+/// the `canister` subtree.
+#[derive(Debug, PartialEq, Eq)]
+struct StateTreeChanges {
+    /// The changed top-level subtrees.
+    subtrees: BTreeMap<Label, SubtreeChange>,
+    /// The changed canisters, with the labels of the changed entries of modified canisters.
+    canisters: BTreeMap<Label, (SubtreeChange, Vec<Label>)>,
+}
+
+impl StateTreeChanges {
+    fn new(before: &ReplicatedState, after: &ReplicatedState) -> Self {
+        let before = replicated_state_as_lazy_tree(before);
+        let after = replicated_state_as_lazy_tree(after);
+        let subtrees = changed_children(&before, &after);
+
+        let mut canisters = BTreeMap::new();
+        if subtrees.get(&Label::from(CANISTER_LABEL)) == Some(&SubtreeChange::Modified) {
+            let before = children(&before).remove(&Label::from(CANISTER_LABEL));
+            let after = children(&after).remove(&Label::from(CANISTER_LABEL));
+            if let (Some(before), Some(after)) = (before, after) {
+                let (before, after) = (children(&before), children(&after));
+                for (canister, change) in changed_children_of(&before, &after) {
+                    let entries = match change {
+                        SubtreeChange::Modified => {
+                            changed_children(&before[&canister], &after[&canister])
+                                .into_keys()
+                                .collect()
+                        }
+                        SubtreeChange::Added | SubtreeChange::Removed => Vec::new(),
+                    };
+                    canisters.insert(canister, (change, entries));
+                }
+            }
+        }
+
+        Self {
+            subtrees,
+            canisters,
+        }
+    }
+}
+
+impl fmt::Display for StateTreeChanges {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (label, change) in &self.subtrees {
+            writeln!(f, "  /{} {:?}", label, change)?;
+        }
+        for (canister, (change, entries)) in &self.canisters {
+            let canister = PrincipalId::try_from(canister.as_bytes())
+                .map(|id| id.to_string())
+                .unwrap_or_else(|_| canister.to_string());
+            write!(f, "  /canister/{} {:?}", canister, change)?;
+            if !entries.is_empty() {
+                let entries: Vec<_> = entries.iter().map(|label| label.to_string()).collect();
+                write!(f, ": {}", entries.join(", "))?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the labeled children of a fork, or nothing if `tree` is a leaf.
+fn children<'a>(tree: &LazyTree<'a>) -> BTreeMap<Label, LazyTree<'a>> {
+    match tree {
+        LazyTree::LazyFork(fork) => fork.children().collect(),
+        LazyTree::Blob(..) | LazyTree::LazyBlob(_) => BTreeMap::new(),
+    }
+}
+
+fn digest(tree: &LazyTree<'_>) -> Digest {
+    hash_lazy_tree(tree)
+        .expect("Failed to hash the state tree")
+        .root_hash()
+        .clone()
+}
+
+/// Returns the changes of the children of `before` and `after` whose subtrees differ.
+fn changed_children(before: &LazyTree<'_>, after: &LazyTree<'_>) -> BTreeMap<Label, SubtreeChange> {
+    changed_children_of(&children(before), &children(after))
+}
+
+fn changed_children_of(
+    before: &BTreeMap<Label, LazyTree<'_>>,
+    after: &BTreeMap<Label, LazyTree<'_>>,
+) -> BTreeMap<Label, SubtreeChange> {
+    let mut changes = BTreeMap::new();
+    for (label, subtree) in before {
+        match after.get(label) {
+            None => {
+                changes.insert(label.clone(), SubtreeChange::Removed);
+            }
+            Some(other) if digest(subtree) != digest(other) => {
+                changes.insert(label.clone(), SubtreeChange::Modified);
+            }
+            Some(_) => {}
+        }
+    }
+    for label in after.keys() {
+        if !before.contains_key(label) {
+            changes.insert(label.clone(), SubtreeChange::Added);
+        }
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ic_test_utilities_state::{CanisterStateBuilder, ReplicatedStateBuilder};
+    use ic_test_utilities_types::ids::canister_test_id;
+
+    fn canister_label(i: u64) -> Label {
+        Label::from(canister_test_id(i).get().as_slice())
+    }
+
+    #[test]
+    fn state_tree_changes_break_down_canisters() {
+        let canister = |i: u64, certified_data: Vec<u8>| {
+            CanisterStateBuilder::new()
+                .with_canister_id(canister_test_id(i))
+                .with_certified_data(certified_data)
+                .build()
+        };
+        let before = ReplicatedStateBuilder::new()
+            .with_canister(canister(1, vec![1]))
+            .with_canister(canister(2, vec![2]))
+            .with_canister(canister(3, vec![3]))
+            .build();
+        let after = ReplicatedStateBuilder::new()
+            .with_canister(canister(2, vec![2]))
+            .with_canister(canister(3, vec![4]))
+            .with_canister(canister(4, vec![4]))
+            .build();
+
+        let changes = StateTreeChanges::new(&before, &after);
+
+        assert_eq!(
+            changes.subtrees,
+            BTreeMap::from([(Label::from(CANISTER_LABEL), SubtreeChange::Modified)])
+        );
+        assert_eq!(
+            changes.canisters,
+            BTreeMap::from([
+                (canister_label(1), (SubtreeChange::Removed, vec![])),
+                (
+                    canister_label(3),
+                    (SubtreeChange::Modified, vec![Label::from("certified_data")])
+                ),
+                (canister_label(4), (SubtreeChange::Added, vec![])),
+            ])
+        );
+    }
+
+    #[test]
+    fn state_tree_changes_of_equal_states_are_empty() {
+        let state = ReplicatedStateBuilder::new()
+            .with_canister(
+                CanisterStateBuilder::new()
+                    .with_canister_id(canister_test_id(1))
+                    .build(),
+            )
+            .build();
+
+        let changes = StateTreeChanges::new(&state, &state);
+
+        assert!(changes.subtrees.is_empty());
+        assert!(changes.canisters.is_empty());
+    }
+}
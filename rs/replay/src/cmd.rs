@@ -40,6 +40,12 @@ pub struct ReplayToolArgs {
     #[clap(long)]
     /// The replay will stop at this height and make a checkpoint.
     pub replay_until_height: Option<u64>,
+
+    /// Append a JSON record for every message executed during the replay to
+    /// this file. Heights that are replayed more than once, e.g. by `bisect`,
+    /// are traced every time.
+    #[clap(long)]
+    pub message_trace: Option<PathBuf>,
 }
 
 #[derive(Clone, Subcommand)]
@@ -64,6 +70,11 @@ pub enum SubCommand {
     /// Restore from the backup.
     RestoreFromBackup(RestoreFromBackupCmd),
 
+    /// Binary-search the heights above the latest checkpoint for the first height whose state
+    /// hash does not match the certification, and print a per-canister breakdown of the state
+    /// changes at that height.
+    Bisect(BisectCmd),
+
     /// The replay will add a test Neuron to the Governance canister
     /// and the corresponding account in the ledger.
     WithNeuronForTests(WithNeuronCmd),
@@ -117,6 +128,13 @@ pub struct RestoreFromBackupCmd {
     pub start_height: u64,
 }
 
+#[derive(Clone, Parser)]
+pub struct BisectCmd {
+    /// The highest height to consider; defaults to the finalized height.
+    #[clap(long)]
+    pub to_height: Option<u64>,
+}
+
 #[derive(Clone, Parser)]
 pub struct RestoreFromBackup2Cmd {
     /// Registry local store path
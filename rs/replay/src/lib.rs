@@ -23,11 +23,12 @@ use ic_canister_client::{Agent, Sender};
 use ic_config::{Config, ConfigSource};
 use ic_nns_constants::GOVERNANCE_CANISTER_ID;
 use ic_protobuf::{registry::subnet::v1::InitialNiDkgTranscriptRecord, types::v1 as pb};
-use ic_types::ReplicaVersion;
+use ic_types::{Height, ReplicaVersion};
 use prost::Message;
 use std::{cell::RefCell, convert::TryFrom, rc::Rc};
 
 mod backup;
+mod bisect;
 pub mod cmd;
 pub mod ingress;
 mod mocks;
@@ -54,6 +55,7 @@ mod validator;
 ///     canister_caller_id: None,
 ///     replay_until_height: None,
 ///     data_root: None,
+///     message_trace: None,
 ///     subcmd: Some(SubCommand::RestoreFromBackup(RestoreFromBackupCmd {
 ///         registry_local_store_path: PathBuf::from("/path/to/ic_registry_local_store"),
 ///         backup_spool_path: PathBuf::from("/path/to/spool"),
@@ -88,6 +90,9 @@ pub fn replay(args: ReplayToolArgs) -> ReplayResult {
             cfg.state_manager = ic_config::state_manager::Config::new(path.join("ic_state"));
             cfg.artifact_pool.consensus_pool_path = path.join("ic_consensus_pool");
         }
+        if let Some(path) = args.message_trace {
+            cfg.hypervisor.message_trace_path = Some(path);
+        }
 
         let canister_caller_id = args.canister_caller_id.unwrap_or(GOVERNANCE_CANISTER_ID);
         let subnet_id = args
@@ -125,6 +130,16 @@ pub fn replay(args: ReplayToolArgs) -> ReplayResult {
             return;
         }
 
+        if let Some(SubCommand::Bisect(cmd)) = subcmd {
+            if target_height.is_some() {
+                panic!("Target height cannot be used with bisect, use --to-height instead.");
+            }
+            let _enter_guard = rt.enter();
+            *res_clone.borrow_mut() =
+                bisect::bisect(&cfg, subnet_id, cmd.to_height.map(Height::from));
+            return;
+        }
+
         {
             let _enter_guard = rt.enter();
             let player = match (subcmd.as_ref(), target_height) {
@@ -1,6 +1,7 @@
 use crate::{
     backup,
     backup::{cup_file_name, rename_file},
+    bisect::CertifiedHashComparison,
     ingress::IngressWithPrinter,
     validator::{InvalidArtifact, ReplayValidator},
 };
@@ -48,6 +49,8 @@ use ic_registry_transport::{
     deserialize_get_value_response, serialize_get_changes_since_request,
     serialize_get_value_request,
 };
+use ic_replicated_state::ReplicatedState;
+use ic_state_layout::StateLayout;
 use ic_state_manager::StateManagerImpl;
 use ic_types::{
     batch::{Batch, BatchMessages, BlockmakerMetrics},
@@ -1167,6 +1170,109 @@ impl Player {
         pool.cup_as_protobuf()
     }
 
+    /// Return the height of the latest state known to the state manager.
+    pub(crate) fn latest_state_height(&self) -> Height {
+        self.state_manager.latest_state_height()
+    }
+
+    /// Return the finalized height of the consensus pool.
+    pub(crate) fn get_finalized_height(&self) -> Height {
+        PoolReader::new(self.consensus_pool.as_ref().expect("no consensus_pool"))
+            .get_finalized_height()
+    }
+
+    /// Return the sorted heights of all full certifications in the certification pool.
+    pub(crate) fn get_certified_heights(&self) -> Vec<Height> {
+        let mut heights = Vec::from_iter(
+            self.certification_pool
+                .as_ref()
+                .expect("no certification_pool")
+                .certified_heights(),
+        );
+        heights.sort();
+        heights
+    }
+
+    /// Return the state at the given height, if it is still held by the state manager.
+    pub(crate) fn get_state_at(&self, height: Height) -> Option<Arc<ReplicatedState>> {
+        self.state_manager
+            .get_state_at(height)
+            .ok()
+            .map(|state| state.take())
+    }
+
+    /// Replays past finalized blocks up to the given height, where a checkpoint is created, and
+    /// compares the resulting state hash to the one of the certification at that height.
+    ///
+    /// Unlike [`Player::replay`], the certification is not delivered to the state manager, so
+    /// a mismatch is returned to the caller instead of causing a panic.
+    pub(crate) fn replay_and_compare_with_certification(
+        &self,
+        height: Height,
+    ) -> Result<CertifiedHashComparison, ReplayError> {
+        let (Some(consensus_pool), Some(certification_pool), Some(validator), Some(membership)) = (
+            &self.consensus_pool,
+            &self.certification_pool,
+            &self.validator,
+            &self.membership,
+        ) else {
+            panic!("Comparing with certifications requires a consensus pool");
+        };
+        match self.verify_latest_cup() {
+            Err(ReplayError::UpgradeDetected(_)) | Ok(_) => {}
+            other => other?,
+        };
+
+        let certification = certification_pool
+            .certification_at_height(height)
+            .unwrap_or_else(|| panic!("Missing certification at height {:?}", height));
+        validator
+            .verify_certification(&certification)
+            .unwrap_or_else(|e| panic!("Failed to verify certification at height {height}: {e}"));
+
+        let invalid_artifacts =
+            validator.validate_in_tmp_pool(consensus_pool, self.get_latest_cup_proto(), height)?;
+        if !invalid_artifacts.is_empty() {
+            println!("Invalid artifacts:");
+            invalid_artifacts.iter().for_each(|a| println!("{:?}", a));
+        }
+
+        let last_batch_height = self.deliver_batches(
+            self.message_routing.as_ref(),
+            &PoolReader::new(consensus_pool),
+            membership,
+            Some(height),
+        );
+        self.wait_for_state(last_batch_height);
+        if last_batch_height != height {
+            return Err(ReplayError::ValidationIncomplete(
+                last_batch_height,
+                invalid_artifacts,
+            ));
+        }
+
+        let local_hash = self
+            .state_manager
+            .list_state_hashes_to_certify()
+            .into_iter()
+            .find_map(|(h, hash)| (h == height).then_some(hash))
+            .unwrap_or_else(|| panic!("No state hash found at height {:?}", height));
+        Ok(CertifiedHashComparison {
+            height,
+            local_hash,
+            certified_hash: certification.signed.content.hash,
+        })
+    }
+
+    /// Moves all checkpoints above the given height to the diverged checkpoints, so that the
+    /// next replay starts from the checkpoint at that height. Besides the checkpoint at the
+    /// replayed height, a replay leaves checkpoints at the summary heights it passes.
+    pub(crate) fn mark_checkpoints_diverged_above(&self, height: Height) {
+        // Make sure the checkpoints are not used by the tip thread anymore.
+        self.state_manager.flush_tip_channel();
+        mark_checkpoints_diverged_above(self.state_manager.state_layout(), height);
+    }
+
     /// Checks that the catch-up package inside the consensus pool contains the same state hash as
     /// the one computed by the state manager. Additionally, it verifies the CUP's signature.
     pub fn verify_latest_cup(&self) -> Result<(), ReplayError> {
@@ -1428,6 +1534,33 @@ fn get_state_hash<T>(
     None
 }
 
+/// Moves all checkpoints above `height` to the diverged checkpoints. Diverged checkpoints at the
+/// same heights left over from a previous run are replaced.
+fn mark_checkpoints_diverged_above(state_layout: &StateLayout, height: Height) {
+    let diverged_heights = state_layout
+        .diverged_checkpoint_heights()
+        .unwrap_or_default();
+    for checkpoint_height in state_layout
+        .unfiltered_checkpoint_heights()
+        .expect("Failed to gather checkpoint heights")
+        .into_iter()
+        .filter(|h| *h > height)
+    {
+        if diverged_heights.contains(&checkpoint_height) {
+            state_layout
+                .remove_diverged_checkpoint(checkpoint_height)
+                .expect("Failed to remove diverged checkpoint");
+        }
+        state_layout
+            .mark_checkpoint_diverged(checkpoint_height)
+            .expect("Failed to mark checkpoint diverged");
+        println!(
+            "Marked checkpoint at height {} as diverged",
+            checkpoint_height
+        );
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use ic_logger::replica_logger::no_op_logger;
@@ -1559,4 +1692,50 @@ mod tests {
             f
         ));
     }
+
+    #[test]
+    fn test_mark_checkpoints_diverged_above() {
+        use ic_state_layout::{CheckpointLayout, RwPolicy};
+
+        let tmp = tempfile::tempdir().expect("Could not create a temp dir");
+        let state_layout = StateLayout::try_new(
+            no_op_logger(),
+            tmp.path().to_path_buf(),
+            &MetricsRegistry::new(),
+        )
+        .unwrap();
+        let scratchpads = tempfile::tempdir().expect("Could not create a temp dir");
+        let create_checkpoint = |height: u64| {
+            let height = Height::new(height);
+            let scratchpad = CheckpointLayout::<RwPolicy<()>>::new_untracked(
+                scratchpads.path().join(height.get().to_string()),
+                height,
+            )
+            .unwrap();
+            state_layout
+                .scratchpad_to_checkpoint(scratchpad, height, None)
+                .unwrap();
+        };
+
+        // A probe from the checkpoint at height 10 to height 25 also creates a checkpoint at the
+        // summary height 20. A diverged checkpoint at height 25 is left over from a previous run.
+        create_checkpoint(10);
+        create_checkpoint(25);
+        state_layout
+            .mark_checkpoint_diverged(Height::new(25))
+            .unwrap();
+        create_checkpoint(20);
+        create_checkpoint(25);
+
+        mark_checkpoints_diverged_above(&state_layout, Height::new(10));
+
+        assert_eq!(
+            state_layout.checkpoint_heights().unwrap(),
+            vec![Height::new(10)]
+        );
+        assert_eq!(
+            state_layout.diverged_checkpoint_heights().unwrap(),
+            vec![Height::new(20), Height::new(25)]
+        );
+    }
 }
@@ -1,8 +1,9 @@
 use crate::{
     canister_state::execution_state::{Global, Memory},
-    canister_state::system_state::wasm_chunk_store::WasmChunkStore,
+    canister_state::system_state::wasm_chunk_store::{WasmChunkHash, WasmChunkStore},
     CanisterState, NumWasmPages, PageMap,
 };
+use ic_protobuf::state::canister_snapshot_bits::v1 as pb_canister_snapshot_bits;
 use ic_sys::PAGE_SIZE;
 use ic_types::{CanisterId, NumBytes, SnapshotId, Time};
 use ic_validate_eq::ValidateEq;
@@ -69,6 +70,42 @@ impl CanisterSnapshots {
         snapshot_id
     }
 
+    /// Inserts a snapshot whose contents were uploaded by a controller, replacing
+    /// the previous version of the snapshot with the same `snapshot_id`, if any.
+    ///
+    /// Unlike `push`, the contents of the snapshot do not come from the canister,
+    /// so the new item added to the `unflushed_changes` only requests its
+    /// `PageMap`s to be flushed.
+    pub fn insert_uploaded(&mut self, snapshot_id: SnapshotId, snapshot: Arc<CanisterSnapshot>) {
+        let canister_id = snapshot.canister_id();
+        self.unflushed_changes
+            .push(SnapshotOperation::Upload(snapshot_id));
+        self.memory_usage += snapshot.size();
+        if let Some(old_snapshot) = self.snapshots.insert(snapshot_id, snapshot) {
+            self.memory_usage -= old_snapshot.size();
+        }
+        let snapshot_ids = self.snapshot_ids.entry(canister_id).or_default();
+        snapshot_ids.insert(snapshot_id);
+    }
+
+    /// Returns a mutable reference to the uploaded snapshot identified by
+    /// `snapshot_id` to modify it without changing its size. The snapshot is
+    /// only copied if it is shared, e.g., with a checkpoint. Returns `None`
+    /// if the snapshot does not exist or was taken from a canister, because
+    /// taken snapshots are immutable.
+    ///
+    /// Like `insert_uploaded`, adds a new item to the `unflushed_changes`
+    /// that requests the snapshot to be flushed.
+    pub fn get_uploaded_mut(&mut self, snapshot_id: SnapshotId) -> Option<&mut CanisterSnapshot> {
+        let snapshot = self.snapshots.get_mut(&snapshot_id)?;
+        if snapshot.source() != SnapshotSource::MetadataUpload {
+            return None;
+        }
+        self.unflushed_changes
+            .push(SnapshotOperation::Upload(snapshot_id));
+        Some(Arc::make_mut(snapshot))
+    }
+
     /// Returns a reference of the canister snapshot identified by `snapshot_id`.
     pub fn get(&self, snapshot_id: SnapshotId) -> Option<&Arc<CanisterSnapshot>> {
         self.snapshots.get(&snapshot_id)
@@ -307,6 +344,8 @@ pub struct ExecutionStateSnapshot {
 pub struct CanisterSnapshot {
     /// Identifies the canister to which this snapshot belongs.
     canister_id: CanisterId,
+    /// Whether the snapshot was taken from the canister or uploaded.
+    source: SnapshotSource,
     /// The timestamp indicating the moment the snapshot was captured.
     taken_at_timestamp: Time,
     /// The canister version at the time of taking the snapshot.
@@ -323,8 +362,10 @@ pub struct CanisterSnapshot {
 }
 
 impl CanisterSnapshot {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         canister_id: CanisterId,
+        source: SnapshotSource,
         taken_at_timestamp: Time,
         canister_version: u64,
         certified_data: Vec<u8>,
@@ -334,6 +375,7 @@ impl CanisterSnapshot {
     ) -> CanisterSnapshot {
         Self {
             canister_id,
+            source,
             taken_at_timestamp,
             canister_version,
             certified_data,
@@ -362,6 +404,7 @@ impl CanisterSnapshot {
 
         Ok(CanisterSnapshot {
             canister_id,
+            source: SnapshotSource::TakenFromCanister,
             taken_at_timestamp,
             canister_version: canister.system_state.canister_version,
             certified_data: canister.system_state.certified_data.clone(),
@@ -375,6 +418,10 @@ impl CanisterSnapshot {
         self.canister_id
     }
 
+    pub fn source(&self) -> SnapshotSource {
+        self.source
+    }
+
     pub fn canister_version(&self) -> u64 {
         self.canister_version
     }
@@ -423,6 +470,19 @@ impl CanisterSnapshot {
         &mut self.execution_snapshot
     }
 
+    /// Inserts a chunk into the Wasm chunk store of the snapshot and accounts
+    /// for the memory it takes in the size of the snapshot.
+    pub fn insert_wasm_chunk(
+        &mut self,
+        max_size: NumBytes,
+        chunk: &[u8],
+    ) -> Result<WasmChunkHash, String> {
+        let old_usage = self.chunk_store.memory_usage();
+        let hash = self.chunk_store.insert_chunk(max_size, chunk)?;
+        self.size += self.chunk_store.memory_usage() - old_usage;
+        Ok(hash)
+    }
+
     /// Returns the heap delta produced by this snapshot.
     ///
     /// The heap delta includes the delta of the wasm memory, stable memory and
@@ -449,12 +509,46 @@ pub enum CanisterSnapshotError {
     EmptyExecutionState(CanisterId),
 }
 
+/// Describes how a canister snapshot was created.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SnapshotSource {
+    /// The snapshot was taken from the canister via `take_canister_snapshot`.
+    TakenFromCanister,
+    /// The snapshot was created via `upload_canister_snapshot_metadata` and its
+    /// contents are uploaded by the controllers of the canister.
+    MetadataUpload,
+}
+
+impl From<SnapshotSource> for pb_canister_snapshot_bits::SnapshotSource {
+    fn from(item: SnapshotSource) -> Self {
+        match item {
+            SnapshotSource::TakenFromCanister => Self::TakenFromCanister,
+            SnapshotSource::MetadataUpload => Self::MetadataUpload,
+        }
+    }
+}
+
+impl From<pb_canister_snapshot_bits::SnapshotSource> for SnapshotSource {
+    fn from(item: pb_canister_snapshot_bits::SnapshotSource) -> Self {
+        match item {
+            // Snapshots persisted before the source was recorded could only
+            // be taken from canisters.
+            pb_canister_snapshot_bits::SnapshotSource::Unspecified
+            | pb_canister_snapshot_bits::SnapshotSource::TakenFromCanister => {
+                Self::TakenFromCanister
+            }
+            pb_canister_snapshot_bits::SnapshotSource::MetadataUpload => Self::MetadataUpload,
+        }
+    }
+}
+
 /// Describes the types of unflushed changes that can be stored by the `SnapshotManager`.
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub enum SnapshotOperation {
     Delete(SnapshotId),
     Backup(CanisterId, SnapshotId),
     Restore(CanisterId, SnapshotId),
+    Upload(SnapshotId),
 }
 
 #[cfg(test)]
@@ -484,6 +578,7 @@ mod tests {
         };
         let snapshot = CanisterSnapshot::new(
             canister_id,
+            SnapshotSource::TakenFromCanister,
             UNIX_EPOCH,
             0,
             vec![],
@@ -536,6 +631,39 @@ mod tests {
         assert_eq!(snapshot_manager.snapshot_ids.get(&canister_id), None);
     }
 
+    #[test]
+    fn test_insert_uploaded_snapshot() {
+        let canister_id = canister_test_id(0);
+        let (snapshot_id, snapshot) = fake_canister_snapshot(canister_id, 1);
+        let mut snapshot_manager = CanisterSnapshots::default();
+
+        snapshot_manager.insert_uploaded(snapshot_id, Arc::new(snapshot.clone()));
+        assert_eq!(snapshot_manager.snapshots.len(), 1);
+        assert_eq!(snapshot_manager.memory_taken(), snapshot.size());
+        assert_eq!(
+            snapshot_manager.take_unflushed_changes(),
+            vec![SnapshotOperation::Upload(snapshot_id)]
+        );
+
+        // Uploading a chunk replaces the snapshot and updates the `memory_usage`.
+        let mut updated_snapshot = snapshot.clone();
+        updated_snapshot
+            .insert_wasm_chunk(NumBytes::from(u64::MAX), &[1, 2, 3])
+            .unwrap();
+        assert!(updated_snapshot.size() > snapshot.size());
+        snapshot_manager.insert_uploaded(snapshot_id, Arc::new(updated_snapshot.clone()));
+        assert_eq!(snapshot_manager.snapshots.len(), 1);
+        assert_eq!(snapshot_manager.memory_taken(), updated_snapshot.size());
+        assert_eq!(
+            snapshot_manager.take_unflushed_changes(),
+            vec![SnapshotOperation::Upload(snapshot_id)]
+        );
+        assert_eq!(
+            snapshot_manager.compute_memory_usage_by_canister(canister_id),
+            updated_snapshot.size()
+        );
+    }
+
     #[test]
     fn test_construct_canister_snapshot_ids() {
         let snapshots: BTreeMap<_, _> = [
@@ -651,6 +651,10 @@ pub struct SystemState {
     /// See the interface specification for more information.
     pub wasm_memory_limit: Option<NumBytes>,
 
+    /// Environment variables of the canister, set through canister settings
+    /// and readable by the canister through the `ic0.env_var_*` system calls.
+    pub environment_variables: BTreeMap<String, String>,
+
     /// Next local snapshot id.
     pub next_snapshot_id: u64,
 
@@ -1081,6 +1085,7 @@ impl SystemState {
             log_visibility: Default::default(),
             canister_log: Default::default(),
             wasm_memory_limit: None,
+            environment_variables: Default::default(),
             next_snapshot_id: 0,
             snapshots_memory_usage: NumBytes::from(0),
         }
@@ -1110,6 +1115,7 @@ impl SystemState {
         log_visibility: LogVisibilityV2,
         canister_log: CanisterLog,
         wasm_memory_limit: Option<NumBytes>,
+        environment_variables: BTreeMap<String, String>,
         next_snapshot_id: u64,
         snapshots_memory_usage: NumBytes,
         metrics: &dyn CheckpointLoadingMetrics,
@@ -1144,6 +1150,7 @@ impl SystemState {
             log_visibility,
             canister_log,
             wasm_memory_limit,
+            environment_variables,
             next_snapshot_id,
             snapshots_memory_usage,
         };
@@ -2538,6 +2545,7 @@ pub mod testing {
             log_visibility: Default::default(),
             canister_log: Default::default(),
             wasm_memory_limit: Default::default(),
+            environment_variables: Default::default(),
             next_snapshot_id: Default::default(),
             snapshots_memory_usage: Default::default(),
         };
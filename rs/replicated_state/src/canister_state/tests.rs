@@ -983,6 +983,7 @@ fn canister_state_canister_log_record_round_trip() {
         idx: 42,
         timestamp_nanos: 27,
         content: vec![1, 2, 3],
+        is_trap: Some(true),
     };
     let encoded = pb::CanisterLogRecord::from(&initial);
     let round_trip = CanisterLogRecord::from(encoded);
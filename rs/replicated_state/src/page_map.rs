@@ -31,7 +31,7 @@ use libc::off_t;
 use page_allocator::Page;
 use prometheus::{Histogram, HistogramVec, IntCounter, IntCounterVec};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::fs::{File, OpenOptions};
 use std::ops::Range;
 use std::os::unix::io::RawFd;
@@ -844,6 +844,17 @@ impl PageMap {
         std::mem::take(&mut self.unflushed_delta);
     }
 
+    /// Returns the indices of all pages that may differ between `self` and
+    /// `other`: the pages in the deltas of either page map and the pages in
+    /// the storage files that are not shared (hardlinked) between the two.
+    /// All other pages are guaranteed to be equal.
+    pub fn maybe_differing_pages(&self, other: &PageMap) -> BTreeSet<PageIndex> {
+        let mut pages = self.storage.maybe_differing_pages(&other.storage);
+        pages.extend(self.page_delta.iter().map(|(index, _)| index));
+        pages.extend(other.page_delta.iter().map(|(index, _)| index));
+        pages
+    }
+
     pub fn get_page_delta_indices(&self) -> Vec<PageIndex> {
         self.page_delta.iter().map(|(index, _)| index).collect()
     }
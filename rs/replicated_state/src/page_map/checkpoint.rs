@@ -1,11 +1,13 @@
 use crate::page_map::{
-    FileDescriptor, FileOffset, MemoryInstructions, MemoryMapOrData, PageIndex, PersistenceError,
+    storage::compression, FileDescriptor, FileOffset, MemoryInstructions, MemoryMapOrData,
+    PageIndex, PersistenceError,
 };
 use ic_sys::{mmap::ScopedMmap, PAGE_SIZE};
 use ic_sys::{page_bytes_from_ptr, PageBytes};
 use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
-use std::fs::{File, OpenOptions};
+use std::fs::File;
+use std::os::unix::fs::MetadataExt;
 use std::os::unix::io::AsRawFd;
 use std::os::unix::prelude::FromRawFd;
 use std::path::Path;
@@ -25,11 +27,19 @@ pub(crate) struct Checkpoint {
     mapping: Option<Arc<Mapping>>,
 }
 
+/// Identifies a file by its device and inode number. Hardlinks of the same
+/// file have the same `FileId`.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub(crate) struct FileId {
+    dev: u64,
+    ino: u64,
+}
+
 /// A memory map of a (section of a) file containing pages of size `PAGE_SIZE`.
 /// The memory map is read-only and most functions are helper functions to read pages.
 pub(crate) struct Mapping {
     mmap: ScopedMmap,
-    _file: File, // It is not used but it keeps the `file_descriptor` alive.
+    file: File, // It keeps the `file_descriptor` alive.
     file_descriptor: FileDescriptor,
 }
 
@@ -57,7 +67,7 @@ impl Mapping {
             })?;
             let fd = file.as_raw_fd();
             Ok(Some(Mapping {
-                _file: file,
+                file,
                 file_descriptor: FileDescriptor { fd },
                 mmap,
             }))
@@ -65,12 +75,10 @@ impl Mapping {
     }
 
     fn open(path: &Path) -> Result<Option<Mapping>, PersistenceError> {
-        let file = OpenOptions::new().read(true).open(path).map_err(|err| {
-            PersistenceError::FileSystemError {
-                path: path.display().to_string(),
-                context: "Failed to open file".to_string(),
-                internal_error: err.to_string(),
-            }
+        let file = compression::open(path).map_err(|err| PersistenceError::FileSystemError {
+            path: path.display().to_string(),
+            context: "Failed to open file".to_string(),
+            internal_error: err.to_string(),
         })?;
         let metadata = file
             .metadata()
@@ -91,6 +99,16 @@ impl Mapping {
         Self::new(file, len, Some(path))
     }
 
+    /// Returns the `FileId` of the mapped file, or `None` if it cannot be
+    /// retrieved.
+    pub(crate) fn file_id(&self) -> Option<FileId> {
+        let metadata = self.file.metadata().ok()?;
+        Some(FileId {
+            dev: metadata.dev(),
+            ino: metadata.ino(),
+        })
+    }
+
     /// Returns a serialization-friendly representation of `Mapping`.
     pub(crate) fn serialize(&self) -> MappingSerialization {
         MappingSerialization {
@@ -208,6 +226,11 @@ impl Checkpoint {
             None => 0,
         }
     }
+
+    /// Returns the `FileId` of the checkpoint file, if any.
+    pub(crate) fn file_id(&self) -> Option<FileId> {
+        self.mapping.as_ref().and_then(|mapping| mapping.file_id())
+    }
 }
 
 impl Default for Checkpoint {
@@ -4,14 +4,14 @@
 use std::{
     collections::{BTreeMap, BTreeSet},
     fs::{File, OpenOptions},
-    io::{Read, Seek, SeekFrom, Write},
+    io::Write,
     ops::Range,
     path::{Path, PathBuf},
     sync::Arc,
 };
 
 use crate::page_map::{
-    checkpoint::{Checkpoint, Mapping, ZEROED_PAGE},
+    checkpoint::{Checkpoint, FileId, Mapping, ZEROED_PAGE},
     CheckpointSerialization, MappingSerialization, MemoryInstruction, MemoryInstructions,
     MemoryMapOrData, PageDelta, PersistenceError, StorageMetrics, LABEL_OP_FLUSH, LABEL_OP_MERGE,
     LABEL_TYPE_INDEX, LABEL_TYPE_PAGE_DATA,
@@ -27,6 +27,8 @@ use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 use strum_macros::{EnumCount, EnumIter};
 
+pub mod compression;
+
 /// The (soft) maximum of the number of overlay files.
 /// There is no limit on the number of overlays while reading,
 /// but we target this number with merges.
@@ -305,6 +307,52 @@ impl Storage {
         base.max(overlays)
     }
 
+    /// Returns the files of this `Storage`, oldest first, each with the
+    /// ranges of page indices it contains. A file without a `FileId` is
+    /// represented by `None`.
+    fn layers(&self) -> Vec<(Option<FileId>, Vec<Range<PageIndex>>)> {
+        let overlay_layer = |overlay: &OverlayFile| {
+            (
+                overlay.mapping.file_id(),
+                overlay
+                    .index_iter()
+                    .map(|range| range.start_page..range.end_page)
+                    .collect(),
+            )
+        };
+        let mut layers: Vec<_> = match &self.base {
+            BaseFile::Base(checkpoint) => vec![(
+                checkpoint.file_id(),
+                vec![PageIndex::new(0)..PageIndex::new(checkpoint.num_pages() as u64)],
+            )],
+            BaseFile::Overlay(overlays) => overlays.iter().map(overlay_layer).collect(),
+        };
+        layers.extend(self.overlays.iter().map(overlay_layer));
+        layers
+    }
+
+    /// Returns the indices of all pages that may differ between `self` and
+    /// `other`, without reading any page contents.
+    ///
+    /// Both storages resolve pages through the same files for as long as
+    /// their stacks of files start with the same (hardlinked) files. Only
+    /// pages contained in the remaining files can differ.
+    pub(crate) fn maybe_differing_pages(&self, other: &Storage) -> BTreeSet<PageIndex> {
+        let layers = self.layers();
+        let other_layers = other.layers();
+        let num_shared = layers
+            .iter()
+            .zip(other_layers.iter())
+            .take_while(|((id, _), (other_id, _))| id.is_some() && id == other_id)
+            .count();
+        layers[num_shared..]
+            .iter()
+            .chain(other_layers[num_shared..].iter())
+            .flat_map(|(_, ranges)| ranges.iter())
+            .flat_map(|range| (range.start.get()..range.end.get()).map(PageIndex::new))
+            .collect()
+    }
+
     pub fn serialize(&self) -> StorageSerialization {
         StorageSerialization {
             base: self.base.serialize(),
@@ -408,12 +456,10 @@ impl OverlayFile {
     /// Returns an error if disk operations fail or the file does not have the format of an
     /// overlay file.
     pub fn load(path: &Path) -> Result<Self, PersistenceError> {
-        let file = OpenOptions::new().read(true).open(path).map_err(|err| {
-            PersistenceError::FileSystemError {
-                path: path.display().to_string(),
-                context: "Failed to open file".to_string(),
-                internal_error: err.to_string(),
-            }
+        let file = compression::open(path).map_err(|err| PersistenceError::FileSystemError {
+            path: path.display().to_string(),
+            context: "Failed to open file".to_string(),
+            internal_error: err.to_string(),
         })?;
         let metadata = file
             .metadata()
@@ -864,15 +910,13 @@ impl dyn StorageLayout + '_ {
     pub fn memory_size_pages(&self) -> StorageResult<usize> {
         let mut result = 0;
         if let Some(base) = self.existing_base() {
-            result = (std::fs::metadata(&base)
-                .map_err(|err: _| {
-                    Box::new(PersistenceError::FileSystemError {
-                        path: base.display().to_string(),
-                        context: format!("Failed get existing file length: {}", base.display()),
-                        internal_error: err.to_string(),
-                    }) as Box<dyn std::error::Error + Send>
-                })?
-                .len() as usize)
+            result = (compression::uncompressed_len(&base).map_err(|err: _| {
+                Box::new(PersistenceError::FileSystemError {
+                    path: base.display().to_string(),
+                    context: format!("Failed get existing file length: {}", base.display()),
+                    internal_error: err.to_string(),
+                }) as Box<dyn std::error::Error + Send>
+            })? as usize)
                 / PAGE_SIZE;
         }
         for overlay in self.existing_overlays()? {
@@ -921,15 +965,23 @@ impl dyn StorageLayout + '_ {
             }) as Box<dyn std::error::Error + Send>
         };
 
-        let mut file = OpenOptions::new()
-            .read(true)
-            .open(overlay)
-            .map_err(to_storage_err)?;
+        // The overlay may be compressed, so offsets from the end are relative to its uncompressed
+        // length.
+        let reader = compression::Reader::open(overlay).map_err(to_storage_err)?;
+        let len = reader.uncompressed_len().map_err(to_storage_err)?;
+        let offset_from_end = |num_bytes: usize| {
+            len.checked_sub(num_bytes as u64).ok_or_else(|| {
+                to_storage_err(std::io::Error::new(
+                    std::io::ErrorKind::UnexpectedEof,
+                    "Overlay file is too short",
+                ))
+            })
+        };
 
         let mut version_buf = [0u8; VERSION_NUM_BYTES];
-        file.seek(SeekFrom::End(-(VERSION_NUM_BYTES as i64)))
+        reader
+            .read_exact_at(&mut version_buf, offset_from_end(VERSION_NUM_BYTES)?)
             .map_err(to_storage_err)?;
-        file.read_exact(&mut version_buf).map_err(to_storage_err)?;
         static_assertions::const_assert_eq!(MAX_SUPPORTED_OVERLAY_VERSION as u32, 0);
         let version = u32::from_le_bytes(version_buf);
         if version > MAX_SUPPORTED_OVERLAY_VERSION as u32 {
@@ -941,11 +993,11 @@ impl dyn StorageLayout + '_ {
         }
 
         let mut last_page_index_range_buf = [[0u8; 8]; 3];
-        file.seek(SeekFrom::End(
-            -((VERSION_NUM_BYTES + SIZE_NUM_BYTES + PAGE_INDEX_RANGE_NUM_BYTES) as i64),
-        ))
-        .map_err(to_storage_err)?;
-        file.read_exact(last_page_index_range_buf.as_flattened_mut())
+        reader
+            .read_exact_at(
+                last_page_index_range_buf.as_flattened_mut(),
+                offset_from_end(VERSION_NUM_BYTES + SIZE_NUM_BYTES + PAGE_INDEX_RANGE_NUM_BYTES)?,
+            )
             .map_err(to_storage_err)?;
         let last_page_index_range = PageIndexRange::from(&last_page_index_range_buf);
         Ok(last_page_index_range.end_page.get() as usize)
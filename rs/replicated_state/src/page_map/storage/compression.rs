@@ -0,0 +1,348 @@
+//! An optional compressed on-disk format for `PageMap` files, i.e. base and overlay files in
+//! checkpoints.
+//!
+//! A compressed file replaces the uncompressed one under the same path, so the file names seen by
+//! `StorageLayout`, the manifest computation and state sync don't change. All readers see the
+//! uncompressed content, hence compression doesn't affect the manifest or the state hash.
+//!
+//! The content is split into blocks of `BLOCK_SIZE` bytes and each block is compressed as an
+//! independent zstd frame, so a range of bytes can be read without decompressing the whole file.
+//! Blocks consisting of zeroes only are stored as empty frames. The file layout is
+//!
+//! ```text
+//! [frame 0] .. [frame n - 1] [padding] [frame end offsets: n x u64] [n: u64] [uncompressed length: u64] [magic]
+//! ```
+//!
+//! with all integers encoded in little endian.
+//!
+//! Uncompressed base files have a length that is a multiple of `PAGE_SIZE` and uncompressed
+//! overlay files end with their `OverlayVersion`, so neither can be mistaken for a compressed file:
+//! the optional padding byte ensures that the length of a compressed file is never a multiple of
+//! `PAGE_SIZE`, and the last four bytes of the magic are larger than any overlay version.
+
+use ic_sys::PAGE_SIZE;
+use std::ffi::OsStr;
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Error, ErrorKind, Result, Write};
+use std::ops::Range;
+use std::os::unix::fs::FileExt;
+use std::path::Path;
+
+/// Number of uncompressed bytes per compressed frame. It matches the default chunk size of the
+/// manifest, so reading a single chunk decompresses at most one frame.
+pub const BLOCK_SIZE: usize = 1 << 20;
+
+/// The magic number ending every compressed file.
+const MAGIC: [u8; 8] = *b"ICPMZST1";
+
+const U64_NUM_BYTES: usize = std::mem::size_of::<u64>();
+
+/// Number of frames, uncompressed length and magic.
+const TRAILER_NUM_BYTES: usize = 2 * U64_NUM_BYTES + MAGIC.len();
+
+const COMPRESSION_LEVEL: i32 = zstd::DEFAULT_COMPRESSION_LEVEL;
+
+/// Whether `path` names a `PageMap` file in a checkpoint, i.e. a base (`.bin`) or an overlay
+/// (`.overlay`) file. Only these files may be stored compressed. All other files, including the
+/// ones whose content is controlled by canisters such as Wasm modules, are always read as is.
+pub fn is_page_map_file(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(OsStr::to_str),
+        Some("bin") | Some("overlay")
+    )
+}
+
+/// Whether the file at `path` is a `PageMap` file stored in the compressed format.
+pub fn is_compressed(path: &Path) -> Result<bool> {
+    Ok(Reader::open(path)?.is_compressed())
+}
+
+/// The length of the uncompressed content of the file at `path`.
+pub fn uncompressed_len(path: &Path) -> Result<u64> {
+    Reader::open(path)?.uncompressed_len()
+}
+
+/// Opens the file at `path` for reading its uncompressed content.
+///
+/// An uncompressed file is returned as is. A compressed file is decompressed into an unlinked
+/// temporary file in the directory of `path`, which can be memory mapped and shared with sandboxes
+/// like the original file. Decompressing to disk next to the checkpoint keeps loaded states from
+/// using memory for their decompressed files. Zero blocks are not written, so the temporary file
+/// is sparse.
+///
+/// The state manager never compresses the latest checkpoint, so this only decompresses files when
+/// loading older checkpoints, e.g. on startup. Use [`Reader`] to read parts of a file lazily.
+pub fn open(path: &Path) -> Result<File> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    open_in(path, dir)
+}
+
+/// Like `open`, but decompresses into an unlinked temporary file in `dir`. This allows using
+/// `copy_file_range` to copy from the returned file into files in `dir`, which fails across file
+/// systems.
+pub fn open_in(path: &Path, dir: &Path) -> Result<File> {
+    open_with(path, || tempfile::tempfile_in(dir))
+}
+
+fn open_with(path: &Path, create_file: impl FnOnce() -> Result<File>) -> Result<File> {
+    let reader = Reader::open(path)?;
+    match &reader.index {
+        None => Ok(reader.file),
+        Some(index) => {
+            let file = create_file()?;
+            index.decompress_into(&reader.file, &file)?;
+            Ok(file)
+        }
+    }
+}
+
+/// Writes the content of the uncompressed file `src` in the compressed format into the new file
+/// `dst` and syncs it.
+pub fn compress_file(src: &Path, dst: &Path) -> Result<()> {
+    let reader = Reader::open(src)?;
+    if reader.index.is_some() {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("{} is already compressed", src.display()),
+        ));
+    }
+    let len = reader.uncompressed_len()?;
+
+    let file = OpenOptions::new().write(true).create_new(true).open(dst)?;
+    let mut writer = BufWriter::new(file);
+    let mut frame_ends = Vec::with_capacity(len.div_ceil(BLOCK_SIZE as u64) as usize);
+    let mut position = 0_u64;
+    let mut buf = vec![0; BLOCK_SIZE];
+    let mut offset = 0_u64;
+    while offset < len {
+        let block = &mut buf[..(len - offset).min(BLOCK_SIZE as u64) as usize];
+        reader.file.read_exact_at(block, offset)?;
+        if block.iter().any(|b| *b != 0) {
+            let frame = zstd::bulk::compress(block, COMPRESSION_LEVEL)?;
+            writer.write_all(&frame)?;
+            position += frame.len() as u64;
+        }
+        frame_ends.push(position);
+        offset += block.len() as u64;
+    }
+
+    let index_len = (frame_ends.len() * U64_NUM_BYTES + TRAILER_NUM_BYTES) as u64;
+    if (position + index_len) % PAGE_SIZE as u64 == 0 {
+        writer.write_all(&[0])?;
+    }
+    for end in frame_ends.iter() {
+        writer.write_all(&end.to_le_bytes())?;
+    }
+    writer.write_all(&(frame_ends.len() as u64).to_le_bytes())?;
+    writer.write_all(&len.to_le_bytes())?;
+    writer.write_all(&MAGIC)?;
+    writer
+        .into_inner()
+        .map_err(|err| err.into_error())?
+        .sync_all()
+}
+
+/// Writes the uncompressed content of the compressed file `src` into the new file `dst` and
+/// syncs it. Zero blocks are skipped, so `dst` is sparse.
+pub fn decompress_file(src: &Path, dst: &Path) -> Result<()> {
+    let reader = Reader::open(src)?;
+    let index = reader.index.as_ref().ok_or_else(|| {
+        Error::new(
+            ErrorKind::InvalidInput,
+            format!("{} is not compressed", src.display()),
+        )
+    })?;
+    let file = OpenOptions::new().write(true).create_new(true).open(dst)?;
+    index.decompress_into(&reader.file, &file)?;
+    file.sync_all()
+}
+
+/// Reads the uncompressed content of a file, regardless of whether it is stored compressed.
+pub struct Reader {
+    file: File,
+    /// `None` for uncompressed files.
+    index: Option<BlockIndex>,
+}
+
+impl Reader {
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = File::open(path)?;
+        let index = if is_page_map_file(path) {
+            BlockIndex::read(&file)
+                .map_err(|err| Error::new(err.kind(), format!("{}: {}", path.display(), err)))?
+        } else {
+            None
+        };
+        Ok(Self { file, index })
+    }
+
+    /// Whether the file is stored in the compressed format.
+    pub fn is_compressed(&self) -> bool {
+        self.index.is_some()
+    }
+
+    /// The length of the uncompressed content.
+    pub fn uncompressed_len(&self) -> Result<u64> {
+        match &self.index {
+            None => Ok(self.file.metadata()?.len()),
+            Some(index) => Ok(index.uncompressed_len),
+        }
+    }
+
+    /// Reads the exact number of bytes required to fill `buf` from the uncompressed content
+    /// starting at `offset`, see `FileExt::read_exact_at`.
+    pub fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> Result<()> {
+        match &self.index {
+            None => self.file.read_exact_at(buf, offset),
+            Some(index) => index.read_exact_at(&self.file, buf, offset),
+        }
+    }
+}
+
+/// Location of the frames in a compressed file.
+struct BlockIndex {
+    /// End offset of each frame; frame `i` starts where frame `i - 1` ends.
+    frame_ends: Vec<u64>,
+    uncompressed_len: u64,
+}
+
+impl BlockIndex {
+    /// Reads the index of `file`; returns `None` if `file` is not compressed.
+    fn read(file: &File) -> Result<Option<Self>> {
+        let invalid = |message: &str| Error::new(ErrorKind::InvalidData, message.to_string());
+
+        let len = file.metadata()?.len();
+        if len % PAGE_SIZE as u64 == 0 || len < TRAILER_NUM_BYTES as u64 {
+            return Ok(None);
+        }
+        let mut trailer = [0; TRAILER_NUM_BYTES];
+        file.read_exact_at(&mut trailer, len - TRAILER_NUM_BYTES as u64)?;
+        if trailer[2 * U64_NUM_BYTES..] != MAGIC {
+            return Ok(None);
+        }
+
+        let num_frames = u64::from_le_bytes(trailer[..U64_NUM_BYTES].try_into().unwrap());
+        let uncompressed_len = u64::from_le_bytes(
+            trailer[U64_NUM_BYTES..2 * U64_NUM_BYTES]
+                .try_into()
+                .unwrap(),
+        );
+        if num_frames != uncompressed_len.div_ceil(BLOCK_SIZE as u64)
+            || num_frames > (len - TRAILER_NUM_BYTES as u64) / U64_NUM_BYTES as u64
+        {
+            return Err(invalid("Inconsistent number of frames in compressed file"));
+        }
+
+        let index_start = len - TRAILER_NUM_BYTES as u64 - num_frames * U64_NUM_BYTES as u64;
+        let mut buf = vec![0; num_frames as usize * U64_NUM_BYTES];
+        file.read_exact_at(&mut buf, index_start)?;
+        let frame_ends: Vec<u64> = buf
+            .chunks_exact(U64_NUM_BYTES)
+            .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+            .collect();
+        if frame_ends.windows(2).any(|w| w[0] > w[1])
+            || frame_ends.last().is_some_and(|end| *end > index_start)
+        {
+            return Err(invalid("Invalid frame offsets in compressed file"));
+        }
+
+        Ok(Some(Self {
+            frame_ends,
+            uncompressed_len,
+        }))
+    }
+
+    fn frame_range(&self, block: usize) -> Range<u64> {
+        let start = match block {
+            0 => 0,
+            _ => self.frame_ends[block - 1],
+        };
+        start..self.frame_ends[block]
+    }
+
+    /// Uncompressed length of `block`; only the last block may be shorter than `BLOCK_SIZE`.
+    fn block_len(&self, block: usize) -> usize {
+        (self.uncompressed_len - (block * BLOCK_SIZE) as u64).min(BLOCK_SIZE as u64) as usize
+    }
+
+    /// Decompresses `block` into `buf`, which must have the length of the block.
+    fn read_block(&self, file: &File, block: usize, buf: &mut [u8]) -> Result<()> {
+        debug_assert_eq!(buf.len(), self.block_len(block));
+        let range = self.frame_range(block);
+        if range.is_empty() {
+            buf.fill(0);
+            return Ok(());
+        }
+        let mut frame = vec![0; (range.end - range.start) as usize];
+        file.read_exact_at(&mut frame, range.start)?;
+        let decompressed_len = zstd::bulk::decompress_to_buffer(&frame, buf)?;
+        if decompressed_len != buf.len() {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "Frame {} decompressed to {} bytes instead of {}",
+                    block,
+                    decompressed_len,
+                    buf.len()
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    fn read_exact_at(&self, file: &File, buf: &mut [u8], offset: u64) -> Result<()> {
+        if offset + buf.len() as u64 > self.uncompressed_len {
+            return Err(Error::new(
+                ErrorKind::UnexpectedEof,
+                "failed to fill whole buffer",
+            ));
+        }
+        let mut block_buf = vec![0; BLOCK_SIZE];
+        let mut done = 0;
+        while done < buf.len() {
+            let position = offset + done as u64;
+            let block = (position / BLOCK_SIZE as u64) as usize;
+            let block_buf = &mut block_buf[..self.block_len(block)];
+            self.read_block(file, block, block_buf)?;
+            let start = (position % BLOCK_SIZE as u64) as usize;
+            let len = (block_buf.len() - start).min(buf.len() - done);
+            buf[done..done + len].copy_from_slice(&block_buf[start..start + len]);
+            done += len;
+        }
+        Ok(())
+    }
+
+    /// Writes the uncompressed content of `src` into `dst`, skipping zero pages.
+    fn decompress_into(&self, src: &File, dst: &File) -> Result<()> {
+        dst.set_len(self.uncompressed_len)?;
+        let mut buf = vec![0; BLOCK_SIZE];
+        for block in 0..self.frame_ends.len() {
+            if self.frame_range(block).is_empty() {
+                continue;
+            }
+            let buf = &mut buf[..self.block_len(block)];
+            self.read_block(src, block, buf)?;
+            let block_offset = (block * BLOCK_SIZE) as u64;
+            // Write runs of consecutive non-zero pages at once.
+            let mut run_start = None;
+            for (i, page) in buf.chunks(PAGE_SIZE).enumerate() {
+                let page_start = i * PAGE_SIZE;
+                match (page.iter().any(|b| *b != 0), run_start) {
+                    (true, None) => run_start = Some(page_start),
+                    (false, Some(start)) => {
+                        dst.write_all_at(&buf[start..page_start], block_offset + start as u64)?;
+                        run_start = None;
+                    }
+                    _ => {}
+                }
+            }
+            if let Some(start) = run_start {
+                dst.write_all_at(&buf[start..], block_offset + start as u64)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -9,9 +9,9 @@ use std::{
 
 use crate::page_map::{
     storage::{
-        Checkpoint, FileIndex, MergeCandidate, MergeDestination, OverlayFile, PageIndexRange,
-        Shard, Storage, StorageLayout, CURRENT_OVERLAY_VERSION, PAGE_INDEX_RANGE_NUM_BYTES,
-        SIZE_NUM_BYTES, VERSION_NUM_BYTES,
+        compression, Checkpoint, FileIndex, MergeCandidate, MergeDestination, OverlayFile,
+        PageIndexRange, Shard, Storage, StorageLayout, CURRENT_OVERLAY_VERSION,
+        PAGE_INDEX_RANGE_NUM_BYTES, SIZE_NUM_BYTES, VERSION_NUM_BYTES,
     },
     test_utils::{ShardedTestStorageLayout, TestStorageLayout},
     FileDescriptor, MemoryInstructions, MemoryMapOrData, PageAllocator, PageDelta, PageMap,
@@ -1490,6 +1490,161 @@ fn overlapping_page_ranges() {
     );
 }
 
+/// Compresses `path` in place, as done for checkpoint files.
+fn compress_in_place(path: &Path) {
+    let compressed = path.with_extension("compressed");
+    compression::compress_file(path, &compressed).unwrap();
+    std::fs::rename(&compressed, path).unwrap();
+}
+
+/// A buffer spanning several compression blocks, with a zero block in the middle and a
+/// partial block at the end.
+fn compressible_buffer() -> Vec<u8> {
+    let mut buf = vec![0; 3 * compression::BLOCK_SIZE + 5 * PAGE_SIZE];
+    for (i, byte) in buf[..compression::BLOCK_SIZE].iter_mut().enumerate() {
+        *byte = (i % 251) as u8;
+    }
+    buf[2 * compression::BLOCK_SIZE + 17] = 42;
+    buf[3 * compression::BLOCK_SIZE + 4 * PAGE_SIZE] = 13;
+    buf
+}
+
+#[test]
+fn compress_and_decompress_roundtrip() {
+    let tempdir = tempdir().unwrap();
+    let original = tempdir.path().join("vmemory_0.bin");
+    let compressed = tempdir.path().join("compressed.bin");
+    let decompressed = tempdir.path().join("decompressed.bin");
+    let buf = compressible_buffer();
+    std::fs::write(&original, &buf).unwrap();
+
+    compression::compress_file(&original, &compressed).unwrap();
+    assert!(!compression::is_compressed(&original).unwrap());
+    assert!(compression::is_compressed(&compressed).unwrap());
+    assert!(std::fs::metadata(&compressed).unwrap().len() < buf.len() as u64);
+    assert_ne!(
+        std::fs::metadata(&compressed).unwrap().len() % PAGE_SIZE as u64,
+        0
+    );
+    assert_eq!(
+        compression::uncompressed_len(&compressed).unwrap(),
+        buf.len() as u64
+    );
+
+    compression::decompress_file(&compressed, &decompressed).unwrap();
+    assert_eq!(std::fs::read(&decompressed).unwrap(), buf);
+
+    let mut opened = Vec::new();
+    std::io::Read::read_to_end(&mut compression::open(&compressed).unwrap(), &mut opened).unwrap();
+    assert_eq!(opened, buf);
+    // The decompressed file is unlinked, so it doesn't show up next to the compressed one.
+    assert_eq!(std::fs::read_dir(tempdir.path()).unwrap().count(), 3);
+
+    let mut opened_in = Vec::new();
+    std::io::Read::read_to_end(
+        &mut compression::open_in(&compressed, tempdir.path()).unwrap(),
+        &mut opened_in,
+    )
+    .unwrap();
+    assert_eq!(opened_in, buf);
+
+    // Compressing a compressed file is an error.
+    assert!(compression::compress_file(&compressed, &tempdir.path().join("twice.bin")).is_err());
+}
+
+#[test]
+fn compressed_reader_reads_across_blocks() {
+    let tempdir = tempdir().unwrap();
+    let original = tempdir.path().join("vmemory_0.bin");
+    let buf = compressible_buffer();
+    std::fs::write(&original, &buf).unwrap();
+    compress_in_place(&original);
+
+    let reader = compression::Reader::open(&original).unwrap();
+    assert_eq!(reader.uncompressed_len().unwrap(), buf.len() as u64);
+    for (offset, len) in [
+        (0, 10),
+        (compression::BLOCK_SIZE - 7, 20),
+        (
+            compression::BLOCK_SIZE - 7,
+            2 * compression::BLOCK_SIZE + 30,
+        ),
+        (buf.len() - PAGE_SIZE, PAGE_SIZE),
+        (buf.len(), 0),
+    ] {
+        let mut actual = vec![0xff; len];
+        reader.read_exact_at(&mut actual, offset as u64).unwrap();
+        assert_eq!(actual, &buf[offset..offset + len], "offset: {}", offset);
+    }
+    // Reading beyond the end is an error.
+    let mut actual = vec![0; 2];
+    assert!(reader
+        .read_exact_at(&mut actual, buf.len() as u64 - 1)
+        .is_err());
+}
+
+#[test]
+fn compression_is_only_detected_for_page_map_files() {
+    let tempdir = tempdir().unwrap();
+    let original = tempdir.path().join("vmemory_0.bin");
+    std::fs::write(&original, compressible_buffer()).unwrap();
+    let compressed = tempdir.path().join("canister.pbuf");
+    compression::compress_file(&original, &compressed).unwrap();
+
+    // A file of any other type that happens to look compressed is read as is.
+    assert!(!compression::is_compressed(&compressed).unwrap());
+    let reader = compression::Reader::open(&compressed).unwrap();
+    assert_eq!(
+        reader.uncompressed_len().unwrap(),
+        std::fs::metadata(&compressed).unwrap().len()
+    );
+}
+
+#[test]
+fn can_load_compressed_storage() {
+    let tempdir = tempdir().unwrap();
+    write_overlays_and_verify_with_tempdir(
+        vec![
+            WriteOverlay((0..300).collect()),
+            Merge {
+                assert_files_merged: None,
+                is_downgrade: true,
+            },
+            WriteOverlay(vec![3, 200, 1000]),
+            WriteOverlay((600..700).collect()),
+        ],
+        &lsmt_config_unsharded(),
+        &tempdir,
+    );
+    let storage_layout = ShardedTestStorageLayout {
+        dir_path: tempdir.path().to_path_buf(),
+        base: tempdir.path().join("vmemory_0.bin"),
+        overlay_suffix: "vmemory_0.overlay".to_owned(),
+    };
+    let expected_buffer = storage_as_buffer(&Storage::load(&storage_layout).unwrap());
+    let expected_num_pages = (&storage_layout as &dyn StorageLayout)
+        .memory_size_pages()
+        .unwrap();
+
+    let StorageFiles { base, overlays } = storage_files(tempdir.path());
+    let base = base.unwrap();
+    assert_eq!(overlays.len(), 2);
+    for path in std::iter::once(&base).chain(overlays.iter()) {
+        compress_in_place(path);
+        assert!(compression::is_compressed(path).unwrap());
+    }
+
+    let storage = Storage::load(&storage_layout).unwrap();
+    assert_eq!(storage.num_logical_pages(), expected_num_pages);
+    assert_eq!(
+        (&storage_layout as &dyn StorageLayout)
+            .memory_size_pages()
+            .unwrap(),
+        expected_num_pages
+    );
+    assert_eq!(storage_as_buffer(&storage), expected_buffer);
+}
+
 #[cfg(not(feature = "fuzzing_code"))]
 mod proptest_tests {
     use super::*;
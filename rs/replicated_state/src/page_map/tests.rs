@@ -901,3 +901,58 @@ fn restrict_to_range() {
 
     assert_eq!(memory_instructions, expected_memory_instructions);
 }
+
+#[test]
+fn maybe_differing_pages_skips_hardlinked_files() {
+    let tmp = tempfile::Builder::new()
+        .prefix("checkpoints")
+        .tempdir()
+        .unwrap();
+    let heap_file = tmp.path().join("heap");
+    let linked_heap_file = tmp.path().join("linked_heap");
+    let copied_heap_file = tmp.path().join("copied_heap");
+
+    let mut original_map = PageMap::new_for_testing();
+    let pages = [[1u8; PAGE_SIZE], [2u8; PAGE_SIZE], [3u8; PAGE_SIZE]];
+    original_map.update(
+        &pages
+            .iter()
+            .enumerate()
+            .map(|(index, page)| (PageIndex::new(index as u64), page))
+            .collect::<Vec<_>>(),
+    );
+    let metrics = StorageMetrics::new(&MetricsRegistry::new());
+    persist_delta_to_base(&original_map, heap_file.clone(), &metrics).unwrap();
+    std::fs::hard_link(&heap_file, &linked_heap_file).unwrap();
+    std::fs::copy(&heap_file, &copied_heap_file).unwrap();
+
+    let open = |path: &Path| {
+        PageMap::open(
+            &base_only_storage_layout(path.to_path_buf()),
+            Height::new(0),
+            Arc::new(TestPageAllocatorFileDescriptorImpl::new()),
+        )
+        .unwrap()
+    };
+    let page_map = open(&heap_file);
+    let mut linked_page_map = open(&linked_heap_file);
+    let copied_page_map = open(&copied_heap_file);
+
+    assert!(page_map.maybe_differing_pages(&linked_page_map).is_empty());
+    assert_eq!(
+        page_map
+            .maybe_differing_pages(&copied_page_map)
+            .into_iter()
+            .collect::<Vec<_>>(),
+        (0..3).map(PageIndex::new).collect::<Vec<_>>()
+    );
+
+    linked_page_map.update(&[(PageIndex::new(5), &[5u8; PAGE_SIZE])]);
+    assert_eq!(
+        page_map
+            .maybe_differing_pages(&linked_page_map)
+            .into_iter()
+            .collect::<Vec<_>>(),
+        vec![PageIndex::new(5)]
+    );
+}
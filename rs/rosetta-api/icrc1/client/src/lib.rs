@@ -3,6 +3,8 @@ use anyhow::Context;
 use candid::Nat;
 use ic_icrc_rosetta::common::types::ApproveMetadata;
 use ic_icrc_rosetta::common::types::Error;
+use ic_icrc_rosetta::common::types::FeeMetadata;
+use ic_icrc_rosetta::common::types::FeeSetter;
 use ic_icrc_rosetta::construction_api::types::ConstructionMetadataRequestOptions;
 use ic_icrc_rosetta::construction_api::types::ConstructionPayloadsRequestMetadata;
 use ic_rosetta_api::models::Amount;
@@ -414,6 +416,52 @@ impl RosettaClient {
         Ok(vec![approver_operation, spender_operation])
     }
 
+    /// Builds a FEE operation that pays the current fee of the ledger, as returned by
+    /// /construction/metadata, from the given account. Adding it to the operations of a
+    /// transaction sets the fee explicitly, so that the ledger rejects the transaction
+    /// instead of charging a different fee if the fee changes before the transaction
+    /// is submitted.
+    pub async fn build_fee_operation(
+        &self,
+        fee_payer: Account,
+        operation_index: u64,
+        network_identifier: NetworkIdentifier,
+    ) -> Result<Operation, Error> {
+        let suggested_fee = self
+            .fetch_transaction_metadata(network_identifier)
+            .await?
+            .suggested_fee
+            .and_then(|suggested_fee| suggested_fee.into_iter().next())
+            .ok_or_else(|| {
+                Error::parsing_unsuccessful(&"No suggested fee in the metadata response")
+            })?;
+        let currency = suggested_fee.currency.clone();
+        let fee = Nat::try_from(suggested_fee).map_err(|err| Error::parsing_unsuccessful(&err))?;
+
+        Ok(Operation {
+            operation_identifier: OperationIdentifier {
+                index: operation_index,
+                network_index: None,
+            },
+            related_operations: None,
+            type_: "FEE".to_string(),
+            status: None,
+            account: Some(fee_payer.into()),
+            amount: Some(Amount::new(
+                BigInt::from_biguint(num_bigint::Sign::Minus, fee.0),
+                currency,
+            )),
+            coin_change: None,
+            metadata: Some(
+                FeeMetadata {
+                    fee_set_by: FeeSetter::User,
+                }
+                .try_into()
+                .map_err(|err| Error::parsing_unsuccessful(&err))?,
+            ),
+        })
+    }
+
     async fn call_endpoint<T: Serialize + ?Sized, R: for<'a> Deserialize<'a>>(
         &self,
         path: &str,
@@ -6,7 +6,7 @@ use crate::{
         storage::storage_client::StorageClient,
         types::{ApproveMetadata, BlockMetadata, OperationType, TransactionMetadata},
     },
-    AppState,
+    AppState, MultiTokenAppState,
 };
 use anyhow::{bail, Context};
 use candid::Nat;
@@ -19,25 +19,34 @@ use rosetta_core::{
 };
 use serde_bytes::ByteBuf;
 use std::fmt::Write;
+use std::sync::Arc;
 use std::time::Duration;
 
-pub fn verify_network_id(
+/// Returns the state of the ledger identified by the given network identifier.
+pub fn get_state_from_network_id(
     network_identifier: &NetworkIdentifier,
-    state: &AppState,
-) -> anyhow::Result<()> {
-    let expected = &NetworkIdentifier::new(
+    state: &MultiTokenAppState,
+) -> anyhow::Result<Arc<AppState>> {
+    let expected = NetworkIdentifier::new(
         DEFAULT_BLOCKCHAIN.to_owned(),
-        state.icrc1_agent.ledger_canister_id.to_string(),
+        network_identifier.network.clone(),
     );
 
-    if network_identifier != expected {
-        bail!(
-            "Network Identifiers did not match: Expected {:?} | Actual {:?}",
-            expected,
+    match state.token_app_states.get(&network_identifier.network) {
+        Some(token_state) if network_identifier == &expected => Ok(token_state.clone()),
+        _ => bail!(
+            "Network Identifiers did not match: Expected one of {:?} | Actual {:?}",
+            state
+                .token_app_states
+                .keys()
+                .map(|ledger_id| NetworkIdentifier::new(
+                    DEFAULT_BLOCKCHAIN.to_owned(),
+                    ledger_id.clone()
+                ))
+                .collect::<Vec<_>>(),
             network_identifier
-        )
+        ),
     }
-    Ok(())
 }
 
 pub fn convert_timestamp_to_millis(timestamp_nanos: u64) -> anyhow::Result<u64> {
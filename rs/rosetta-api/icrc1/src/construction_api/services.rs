@@ -237,7 +237,7 @@ mod tests {
     use crate::common::utils::utils::icrc1_operation_to_rosetta_core_operations;
     use crate::construction_api::types::CanisterMethodName;
     use crate::construction_api::utils::build_icrc1_transaction_from_canister_method_args;
-    use candid::Encode;
+    use candid::{Encode, Nat};
     use ic_agent::agent::EnvelopeContent;
     use ic_agent::Identity;
     use ic_icrc1_test_utils::construction_payloads_request_metadata;
@@ -258,6 +258,7 @@ mod tests {
     use proptest::test_runner::TestRunner;
     use rosetta_core::models::RosettaSupportedKeyPair;
     use rosetta_core::models::{Ed25519KeyPair, Secp256k1KeyPair};
+    use rosetta_core::objects::ObjectMap;
 
     const NUM_TEST_CASES: u32 = 100;
     const NUM_BLOCKS: usize = 1;
@@ -634,4 +635,138 @@ mod tests {
             )
             .unwrap();
     }
+
+    // Runs the offline construction flow for the given operation, signed by the given key pair,
+    // and checks that both the unsigned and the signed transaction parse back to the operations
+    // the flow started with.
+    fn assert_construction_flow_round_trip<T: RosettaSupportedKeyPair>(
+        key_pair: &T,
+        operation: crate::common::storage::types::IcrcOperation,
+    ) {
+        let currency = Currency {
+            symbol: DEFAULT_TOKEN_SYMBOL.to_owned(),
+            decimals: DEFAULT_DECIMAL_PLACES as u32,
+            metadata: None,
+        };
+        let fee = match &operation {
+            crate::common::storage::types::IcrcOperation::Transfer { fee, .. } => fee.clone(),
+            crate::common::storage::types::IcrcOperation::Approve { fee, .. } => fee.clone(),
+            _ => panic!("Only transfer and approve operations can be constructed"),
+        };
+        let operations =
+            icrc1_operation_to_rosetta_core_operations(operation, currency.clone(), fee).unwrap();
+
+        let ConstructionPreprocessResponse {
+            required_public_keys,
+            ..
+        } = construction_preprocess(operations.clone()).unwrap();
+        let signer = Account::from(key_pair.generate_principal_id().unwrap().0);
+        assert_eq!(required_public_keys, Some(vec![signer.into()]));
+
+        let payloads_response = construction_payloads(
+            operations.clone(),
+            None,
+            &PrincipalId::new_anonymous().0,
+            vec![key_pair.into()],
+            SystemTime::now(),
+        )
+        .unwrap();
+
+        let parse_response = construction_parse(
+            payloads_response.unsigned_transaction.clone(),
+            false,
+            currency.clone(),
+        )
+        .unwrap();
+        assert_eq!(parse_response.operations, operations);
+        assert_eq!(parse_response.account_identifier_signers, None);
+
+        let signatures =
+            RosettaClient::sign_transaction(key_pair, payloads_response.clone()).unwrap();
+        let ConstructionCombineResponse { signed_transaction } =
+            construction_combine(payloads_response.unsigned_transaction, signatures).unwrap();
+
+        let parse_response = construction_parse(signed_transaction, true, currency).unwrap();
+        assert_eq!(parse_response.operations, operations);
+        assert_eq!(
+            parse_response.account_identifier_signers,
+            Some(vec![signer.into()])
+        );
+    }
+
+    #[test]
+    fn test_construction_flow_approve_and_transfer_from() {
+        let approver = Ed25519KeyPair::generate(0);
+        let spender = Secp256k1KeyPair::generate(1);
+        let approver_account = Account {
+            owner: approver.generate_principal_id().unwrap().0,
+            subaccount: Some([1; 32]),
+        };
+        let spender_account = Account {
+            owner: spender.generate_principal_id().unwrap().0,
+            subaccount: None,
+        };
+        let receiver_account = Account {
+            owner: PrincipalId::new_user_test_id(2).0,
+            subaccount: Some([2; 32]),
+        };
+
+        for (expected_allowance, expires_at, fee) in [
+            (None, None, None),
+            (
+                Some(Nat::from(0u64)),
+                None,
+                Some(Nat::from(DEFAULT_TRANSFER_FEE)),
+            ),
+            (
+                Some(Nat::from(1_000_000u64)),
+                Some(u64::MAX),
+                Some(Nat::from(DEFAULT_TRANSFER_FEE)),
+            ),
+        ] {
+            assert_construction_flow_round_trip(
+                &approver,
+                crate::common::storage::types::IcrcOperation::Approve {
+                    from: approver_account,
+                    spender: spender_account,
+                    amount: Nat::from(2_000_000u64),
+                    expected_allowance,
+                    expires_at,
+                    fee,
+                },
+            );
+        }
+
+        for fee in [None, Some(Nat::from(DEFAULT_TRANSFER_FEE))] {
+            assert_construction_flow_round_trip(
+                &spender,
+                crate::common::storage::types::IcrcOperation::Transfer {
+                    from: approver_account,
+                    to: receiver_account,
+                    spender: Some(spender_account),
+                    amount: Nat::from(1_000_000u64),
+                    fee,
+                },
+            );
+        }
+    }
+
+    #[test]
+    fn test_construction_metadata_request_options_default() {
+        assert!(
+            ConstructionMetadataRequestOptions::try_from(None)
+                .unwrap()
+                .suggested_fee
+        );
+        let options: ObjectMap = ConstructionMetadataRequestOptions {
+            suggested_fee: false,
+        }
+        .try_into()
+        .unwrap();
+        assert!(
+            !ConstructionMetadataRequestOptions::try_from(Some(options))
+                .unwrap()
+                .suggested_fee
+        );
+    }
 }
@@ -10,9 +10,17 @@ use std::str::FromStr;
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ConstructionMetadataRequestOptions {
+    /// Whether /construction/metadata should return the current fee of the ledger.
+    /// Defaults to true so that offline signers that do not call /construction/preprocess
+    /// still learn the fee they have to pay.
+    #[serde(default = "default_suggested_fee")]
     pub suggested_fee: bool,
 }
 
+fn default_suggested_fee() -> bool {
+    true
+}
+
 impl TryFrom<ConstructionMetadataRequestOptions> for ObjectMap {
     type Error = anyhow::Error;
     fn try_from(d: ConstructionMetadataRequestOptions) -> Result<ObjectMap, Self::Error> {
@@ -8,7 +8,7 @@ use icrc_ledger_types::icrc3::archive::ArchiveInfo;
 use num_traits::ToPrimitive;
 use rosetta_core::objects::Currency;
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     sync::{Arc, Mutex},
 };
 use tokio::sync::Mutex as AsyncMutex;
@@ -26,6 +26,13 @@ pub struct AppState {
     pub metadata: Metadata,
 }
 
+/// The state shared by the HTTP handlers. Every ledger served by this
+/// process has its own [AppState], keyed by the textual representation
+/// of its canister id which is also the network of its `NetworkIdentifier`.
+pub struct MultiTokenAppState {
+    pub token_app_states: BTreeMap<String, Arc<AppState>>,
+}
+
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub struct Metadata {
     pub symbol: String,
@@ -16,11 +16,13 @@ use ic_icrc_rosetta::{
     construction_api::endpoints::*,
     data_api::endpoints::*,
     ledger_blocks_synchronization::blocks_synchronizer::start_synching_blocks,
-    AppState, Metadata,
+    AppState, Metadata, MultiTokenAppState,
 };
 use ic_sys::fs::write_string_using_tmp_file;
 use icrc_ledger_agent::{CallMode, Icrc1Agent};
 use lazy_static::lazy_static;
+use std::collections::BTreeMap;
+use std::str::FromStr;
 use std::sync::{Arc, Mutex};
 use std::{path::PathBuf, process};
 use tokio::{net::TcpListener, sync::Mutex as AsyncMutex};
@@ -53,11 +55,54 @@ enum NetworkType {
     Testnet,
 }
 
+/// A ledger served by Rosetta together with the optional
+/// metadata of its token.
+#[derive(Clone, Debug)]
+struct TokenDef {
+    ledger_id: CanisterId,
+    icrc1_symbol: Option<String>,
+    icrc1_decimals: Option<u8>,
+}
+
+impl TokenDef {
+    fn are_metadata_args_set(&self) -> bool {
+        self.icrc1_symbol.is_some() && self.icrc1_decimals.is_some()
+    }
+}
+
+impl FromStr for TokenDef {
+    type Err = String;
+
+    /// Parses a token definition of the form `<ledger_id>[:<symbol>[:<decimals>]]`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(':');
+        let ledger_id = CanisterId::from_str(parts.next().unwrap_or_default())
+            .map_err(|err| format!("Invalid ledger id in {}: {}", s, err))?;
+        let icrc1_symbol = parts.next().map(str::to_string);
+        let icrc1_decimals = parts
+            .next()
+            .map(u8::from_str)
+            .transpose()
+            .map_err(|err| format!("Invalid decimals in {}: {}", s, err))?;
+        if parts.next().is_some() {
+            return Err(format!(
+                "Invalid token definition {}, expected <ledger_id>[:<symbol>[:<decimals>]]",
+                s
+            ));
+        }
+        Ok(Self {
+            ledger_id,
+            icrc1_symbol,
+            icrc1_decimals,
+        })
+    }
+}
+
 #[derive(Debug, Parser)]
 #[command(author, version, about, long_about = None)]
 struct Args {
-    #[arg(short, long)]
-    ledger_id: CanisterId,
+    #[arg(short, long, required_unless_present = "multi_tokens")]
+    ledger_id: Option<CanisterId>,
 
     /// The symbol of the ICRC-1 token.
     /// If set Rosetta will check the symbol against the ledger it connects to. If the symbol does not match, it will exit.
@@ -67,6 +112,18 @@ struct Args {
     #[arg(long)]
     icrc1_decimals: Option<u8>,
 
+    /// A comma separated list of ledgers to serve from this process instead of [ledger_id].
+    /// Every ledger is defined as `<ledger_id>[:<symbol>[:<decimals>]]` and is served
+    /// under its own network identifier, i.e., the network is the ledger id.
+    /// Symbol and decimals have the same meaning as [icrc1_symbol] and [icrc1_decimals].
+    #[arg(long, value_delimiter = ',', conflicts_with_all = ["ledger_id", "icrc1_symbol", "icrc1_decimals"])]
+    multi_tokens: Vec<TokenDef>,
+
+    /// The directory where the stores of the ledgers in [multi_tokens] are kept if [store_type] is file.
+    /// The store of every ledger is the file `<ledger_id>.sqlite` in this directory.
+    #[arg(long, default_value = "/data")]
+    multi_tokens_store_dir: PathBuf,
+
     /// The port to which Rosetta will bind.
     /// If not set then it will be 0.
     #[arg(short, long)]
@@ -137,8 +194,35 @@ impl Args {
         })
     }
 
-    fn are_metadata_args_set(&self) -> bool {
-        self.icrc1_symbol.is_some() && self.icrc1_decimals.is_some()
+    /// Return the ledgers that Rosetta should serve.
+    fn token_defs(&self) -> anyhow::Result<Vec<TokenDef>> {
+        if !self.multi_tokens.is_empty() {
+            return Ok(self.multi_tokens.clone());
+        }
+        Ok(vec![TokenDef {
+            ledger_id: self
+                .ledger_id
+                .context("Either the ledger id or multiple tokens must be provided.")?,
+            icrc1_symbol: self.icrc1_symbol.clone(),
+            icrc1_decimals: self.icrc1_decimals,
+        }])
+    }
+
+    /// Return the file to use for the store of the given ledger if [store_type] is file.
+    fn store_file(&self, ledger_id: &CanisterId) -> PathBuf {
+        if self.multi_tokens.is_empty() {
+            self.store_file.clone()
+        } else {
+            self.multi_tokens_store_dir
+                .join(format!("{}.sqlite", ledger_id))
+        }
+    }
+
+    fn store(&self, ledger_id: &CanisterId) -> anyhow::Result<StorageClient> {
+        match self.store_type {
+            StoreType::InMemory => StorageClient::new_in_memory(),
+            StoreType::File => StorageClient::new_persistent(&self.store_file(ledger_id)),
+        }
     }
 }
 
@@ -201,39 +285,40 @@ fn add_request_span() -> FnTraceLayer {
 }
 
 async fn load_metadata(
-    args: &Args,
+    token_def: &TokenDef,
+    offline: bool,
     icrc1_agent: &Icrc1Agent,
     storage: &StorageClient,
 ) -> anyhow::Result<Metadata> {
-    if args.offline {
+    if offline {
         let db_metadata_entries = storage.read_metadata()?;
         // If metadata is empty and the args are not set, bail out.
-        if db_metadata_entries.is_empty() && !args.are_metadata_args_set() {
+        if db_metadata_entries.is_empty() && !token_def.are_metadata_args_set() {
             bail!("Metadata must be initialized by starting Rosetta in online mode first or by providing ICRC-1 metadata arguments.");
         }
 
         // If metadata is set in args and not entries are found in the database,
         // return the metadata from the args.
-        if args.are_metadata_args_set() && db_metadata_entries.is_empty() {
+        if token_def.are_metadata_args_set() && db_metadata_entries.is_empty() {
             return Ok(Metadata::from_args(
-                args.icrc1_symbol.clone().unwrap(),
-                args.icrc1_decimals.unwrap(),
+                token_def.icrc1_symbol.clone().unwrap(),
+                token_def.icrc1_decimals.unwrap(),
             ));
         }
 
         // Populate a metadata object with the database entries.
         let db_metadata = Metadata::from_metadata_entries(&db_metadata_entries)?;
         // If the metadata args are not set, return using the db metadata.
-        if !args.are_metadata_args_set() {
+        if !token_def.are_metadata_args_set() {
             return Ok(db_metadata);
         }
 
         // Extract the symbol and decimals from the arguments.
-        let symbol = args
+        let symbol = token_def
             .icrc1_symbol
             .clone()
             .context("ICRC-1 symbol should be provided in offline mode.")?;
-        let decimals = args
+        let decimals = token_def
             .icrc1_decimals
             .context("ICRC-1 decimals should be provided in offline mode.")?;
 
@@ -284,10 +369,7 @@ async fn main() -> Result<()> {
 
     let _guard = init_logs(args.log_level, &args.log_file)?;
 
-    let storage = Arc::new(match args.store_type {
-        StoreType::InMemory => StorageClient::new_in_memory()?,
-        StoreType::File => StorageClient::new_persistent(&args.store_file)?,
-    });
+    let token_defs = args.token_defs()?;
 
     let network_url = args.effective_network_url();
 
@@ -312,52 +394,73 @@ async fn main() -> Result<()> {
         ic_agent.status().await?.replica_health_status
     );
 
-    let icrc1_agent = Arc::new(Icrc1Agent {
-        agent: ic_agent,
-        ledger_canister_id: args.ledger_id.into(),
-    });
+    let mut token_app_states = BTreeMap::new();
+    for token_def in token_defs {
+        let storage = Arc::new(args.store(&token_def.ledger_id)?);
+
+        let icrc1_agent = Arc::new(Icrc1Agent {
+            agent: ic_agent.clone(),
+            ledger_canister_id: token_def.ledger_id.into(),
+        });
+
+        let metadata = load_metadata(&token_def, args.offline, &icrc1_agent, &storage).await?;
+        if let Some(token_symbol) = token_def.icrc1_symbol.clone() {
+            if metadata.symbol != token_symbol {
+                bail!(
+                    "Provided symbol does not match symbol retrieved in online mode. Expected: {}, Got: {}",
+                    metadata.symbol, token_symbol
+                );
+            }
+        }
 
-    let metadata = load_metadata(&args, &icrc1_agent, &storage).await?;
-    if let Some(token_symbol) = args.icrc1_symbol.clone() {
-        if metadata.symbol != token_symbol {
+        info!(
+            "ICRC Rosetta is connected to the ICRC-1 ledger: {}",
+            token_def.ledger_id
+        );
+        info!(
+            "The token symbol of the ICRC-1 ledger is: {}",
+            metadata.symbol
+        );
+
+        if token_app_states.contains_key(&token_def.ledger_id.to_string()) {
             bail!(
-                "Provided symbol does not match symbol retrieved in online mode. Expected: {}, Got: {}",
-                metadata.symbol, token_symbol
+                "The ledger {} is specified more than once.",
+                token_def.ledger_id
             );
         }
+        token_app_states.insert(
+            token_def.ledger_id.to_string(),
+            Arc::new(AppState {
+                icrc1_agent,
+                ledger_id: token_def.ledger_id,
+                synched: Arc::new(Mutex::new(None)),
+                storage,
+                archive_canister_ids: Arc::new(AsyncMutex::new(vec![])),
+                metadata,
+            }),
+        );
     }
 
-    info!(
-        "ICRC Rosetta is connected to the ICRC-1 ledger: {}",
-        args.ledger_id
-    );
-    info!(
-        "The token symbol of the ICRC-1 ledger is: {}",
-        metadata.symbol
-    );
-
-    let shared_state = Arc::new(AppState {
-        icrc1_agent: icrc1_agent.clone(),
-        ledger_id: args.ledger_id,
-        synched: Arc::new(Mutex::new(None)),
-        storage: storage.clone(),
-        archive_canister_ids: Arc::new(AsyncMutex::new(vec![])),
-        metadata,
-    });
+    let shared_state = Arc::new(MultiTokenAppState { token_app_states });
 
     if args.exit_on_sync {
         if args.offline {
             bail!("'exit-on-sync' and 'offline' parameters cannot be specified at the same time.");
         }
 
-        info!("Starting to sync blocks");
-        start_synching_blocks(
-            icrc1_agent.clone(),
-            storage.clone(),
-            *MAXIMUM_BLOCKS_PER_REQUEST,
-            Arc::new(AsyncMutex::new(vec![])),
-        )
-        .await?;
+        for token_state in shared_state.token_app_states.values() {
+            info!(
+                "Starting to sync blocks of ledger {}",
+                token_state.ledger_id
+            );
+            start_synching_blocks(
+                token_state.icrc1_agent.clone(),
+                token_state.storage.clone(),
+                *MAXIMUM_BLOCKS_PER_REQUEST,
+                Arc::new(AsyncMutex::new(vec![])),
+            )
+            .await?;
+        }
 
         process::exit(0);
     }
@@ -395,7 +498,7 @@ async fn main() -> Result<()> {
     let rosetta_url = format!("0.0.0.0:{}", args.get_port());
     let tcp_listener = TcpListener::bind(rosetta_url.clone()).await?;
 
-    if let Some(port_file) = args.port_file {
+    if let Some(port_file) = &args.port_file {
         write_string_using_tmp_file(
             port_file,
             tcp_listener.local_addr()?.port().to_string().as_str(),
@@ -403,38 +506,46 @@ async fn main() -> Result<()> {
     }
 
     if !args.offline {
-        tokio::task::spawn_blocking(move || {
-            let mut sync_wait_secs = BLOCK_SYNC_WAIT_SECS;
-
+        // Every ledger is synchronized by its own loop so that a failing
+        // ledger does not delay the synchronization of the others.
+        for token_state in shared_state.token_app_states.values() {
+            let token_state = token_state.clone();
             let block_sync_storage = match args.store_type {
-                StoreType::InMemory => storage.clone(),
-                StoreType::File => {
-                    Arc::new(StorageClient::new_persistent(&args.store_file).unwrap())
-                }
+                StoreType::InMemory => token_state.storage.clone(),
+                StoreType::File => Arc::new(StorageClient::new_persistent(
+                    &args.store_file(&token_state.ledger_id),
+                )?),
             };
 
-            tokio::runtime::Handle::current().block_on(async {
-                loop {
-                    if let Err(e) = start_synching_blocks(
-                        icrc1_agent.clone(),
-                        block_sync_storage.clone(),
-                        *MAXIMUM_BLOCKS_PER_REQUEST,
-                        shared_state.clone().archive_canister_ids.clone(),
-                    )
-                    .await
-                    {
-                        error!("Error while syncing blocks: {}", e);
-                        sync_wait_secs =
-                            std::cmp::min(sync_wait_secs * 2, MAX_BLOCK_SYNC_WAIT_SECS);
-                        info!("Retrying in {} seconds.", sync_wait_secs);
-                    } else {
-                        sync_wait_secs = BLOCK_SYNC_WAIT_SECS;
+            tokio::task::spawn_blocking(move || {
+                let mut sync_wait_secs = BLOCK_SYNC_WAIT_SECS;
+
+                tokio::runtime::Handle::current().block_on(async {
+                    loop {
+                        if let Err(e) = start_synching_blocks(
+                            token_state.icrc1_agent.clone(),
+                            block_sync_storage.clone(),
+                            *MAXIMUM_BLOCKS_PER_REQUEST,
+                            token_state.archive_canister_ids.clone(),
+                        )
+                        .await
+                        {
+                            error!(
+                                "Error while syncing blocks of ledger {}: {}",
+                                token_state.ledger_id, e
+                            );
+                            sync_wait_secs =
+                                std::cmp::min(sync_wait_secs * 2, MAX_BLOCK_SYNC_WAIT_SECS);
+                            info!("Retrying in {} seconds.", sync_wait_secs);
+                        } else {
+                            sync_wait_secs = BLOCK_SYNC_WAIT_SECS;
+                        }
+
+                        tokio::time::sleep(std::time::Duration::from_secs(sync_wait_secs)).await;
                     }
-
-                    tokio::time::sleep(std::time::Duration::from_secs(sync_wait_secs)).await;
-                }
+                });
             });
-        });
+        }
     }
 
     info!("Starting Rosetta server");
@@ -100,3 +100,17 @@ pub fn create_and_install_icrc_ledger(pocket_ic: &PocketIc, init_args: InitArgs)
     pocket_ic.install_canister(canister_id, wasm_module, custom_encoded_init_args, None);
     canister_id
 }
+
+// Install an additional icrc ledger on the SNS subnet, e.g., to test multiple tokens
+pub fn create_and_install_additional_icrc_ledger(
+    pocket_ic: &PocketIc,
+    init_args: InitArgs,
+) -> Principal {
+    let wasm_module = local_replica::icrc_ledger_wasm();
+    let sns_subnet_id = pocket_ic.topology().get_sns().unwrap();
+    let canister_id = pocket_ic.create_canister_on_subnet(None, None, sns_subnet_id);
+    let custom_encoded_init_args = Encode!(&(LedgerArgument::Init(init_args.clone()))).unwrap();
+    pocket_ic.add_cycles(canister_id, STARTING_CYCLES_PER_CANISTER);
+    pocket_ic.install_canister(canister_id, wasm_module, custom_encoded_init_args, None);
+    canister_id
+}
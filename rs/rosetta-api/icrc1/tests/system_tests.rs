@@ -441,6 +441,93 @@ fn test_network_options() {
     });
 }
 
+#[test]
+fn test_multi_tokens() {
+    let account = Account {
+        owner: PrincipalId::new_user_test_id(1).0,
+        subaccount: None,
+    };
+    let rt = Runtime::new().unwrap();
+    let setup = Setup::builder()
+        .with_initial_balance(account, 1_000u64)
+        .build();
+    let second_ledger_id = local_replica::create_and_install_additional_icrc_ledger(
+        &setup.pocket_ic,
+        local_replica::icrc_ledger_default_args_builder()
+            .with_token_symbol("XTST2")
+            .with_minting_account(setup.minting_account)
+            .with_initial_balance(account, 2_000u64)
+            .build(),
+    );
+
+    rt.block_on(async {
+        let rosetta_context = start_rosetta(
+            &rosetta_bin(),
+            RosettaOptions {
+                network_url: Some(format!("http://localhost:{}", setup.port)),
+                offline: false,
+                multi_tokens: Some(vec![
+                    setup.icrc1_ledger_canister_id.to_string(),
+                    format!("{}:XTST2", second_ledger_id),
+                ]),
+                ..RosettaOptions::default()
+            },
+        )
+        .await;
+        let rosetta_client =
+            RosettaClient::from_str_url(&format!("http://0.0.0.0:{}", rosetta_context.port))
+                .expect("Unable to parse url");
+
+        let first_network_identifier = NetworkIdentifier::new(
+            DEFAULT_BLOCKCHAIN.to_owned(),
+            setup.icrc1_ledger_canister_id.to_string(),
+        );
+        let second_network_identifier =
+            NetworkIdentifier::new(DEFAULT_BLOCKCHAIN.to_owned(), second_ledger_id.to_string());
+
+        // Every ledger is served as its own network.
+        let network_list = rosetta_client
+            .network_list()
+            .await
+            .expect("Unable to call network_list")
+            .network_identifiers;
+        assert_eq!(network_list.len(), 2);
+        assert!(network_list.contains(&first_network_identifier));
+        assert!(network_list.contains(&second_network_identifier));
+
+        // Every ledger is synchronized into its own store and uses its own currency.
+        for (network_identifier, symbol, expected_balance) in [
+            (
+                first_network_identifier,
+                setup.icrc1_ledger_canister_init_args.token_symbol.clone(),
+                1_000u64,
+            ),
+            (second_network_identifier, "XTST2".to_string(), 2_000u64),
+        ] {
+            // The initial balances of the test identity and of the account are minted in blocks 0 and 1.
+            wait_for_rosetta_block(&rosetta_client, network_identifier.clone(), 1).await;
+            let balance = rosetta_client
+                .account_balance(1, account.into(), network_identifier)
+                .await
+                .expect("Unable to call account_balance")
+                .balances[0]
+                .clone();
+            assert_eq!(balance.value, expected_balance.to_string());
+            assert_eq!(balance.currency.symbol, symbol);
+        }
+
+        // Networks of ledgers that are not served are rejected.
+        let unknown_network_identifier = NetworkIdentifier::new(
+            DEFAULT_BLOCKCHAIN.to_owned(),
+            PrincipalId::new_user_test_id(2).to_string(),
+        );
+        assert!(rosetta_client
+            .network_status(unknown_network_identifier)
+            .await
+            .is_err());
+    });
+}
+
 proptest! {
     #![proptest_config(ProptestConfig::with_cases(*NUM_TEST_CASES))]
     #[test]
@@ -1430,6 +1517,218 @@ fn test_rosetta_transfer_from() {
     });
 }
 
+// Strips the operation identifiers so that operations can be compared regardless of their order.
+fn normalize_operations(operations: Vec<Operation>) -> Vec<String> {
+    let mut operations: Vec<String> = operations
+        .into_iter()
+        .map(|mut operation| {
+            operation.operation_identifier = OperationIdentifier {
+                index: 0,
+                network_index: None,
+            };
+            serde_json::to_string(&operation).unwrap()
+        })
+        .collect();
+    operations.sort();
+    operations
+}
+
+// Constructs, signs and submits a transaction through the construction API checking that the
+// unsigned and the signed transaction parse back to the given operations.
+async fn construct_and_submit_with_parse_round_trip<T: RosettaSupportedKeyPair>(
+    rosetta_client: &RosettaClient,
+    network_identifier: NetworkIdentifier,
+    signer_keypair: &T,
+    operations: Vec<Operation>,
+) {
+    let payloads_response = rosetta_client
+        .construction_payloads(
+            network_identifier.clone(),
+            operations.clone(),
+            Some(vec![signer_keypair.into()]),
+            None,
+        )
+        .await
+        .unwrap();
+
+    let parse_response = rosetta_client
+        .construction_parse(
+            network_identifier.clone(),
+            payloads_response.unsigned_transaction.clone(),
+            false,
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        normalize_operations(parse_response.operations),
+        normalize_operations(operations.clone())
+    );
+
+    let signatures =
+        RosettaClient::sign_transaction(signer_keypair, payloads_response.clone()).unwrap();
+    let signed_transaction = rosetta_client
+        .construction_combine(
+            network_identifier.clone(),
+            payloads_response.unsigned_transaction,
+            signatures,
+        )
+        .await
+        .unwrap()
+        .signed_transaction;
+
+    let parse_response = rosetta_client
+        .construction_parse(network_identifier.clone(), signed_transaction.clone(), true)
+        .await
+        .unwrap();
+    assert_eq!(
+        normalize_operations(parse_response.operations),
+        normalize_operations(operations)
+    );
+    let signer: Account = signer_keypair.generate_principal_id().unwrap().0.into();
+    assert_eq!(
+        parse_response.account_identifier_signers,
+        Some(vec![signer.into()])
+    );
+
+    let hash_response = rosetta_client
+        .construction_hash(network_identifier.clone(), signed_transaction.clone())
+        .await
+        .unwrap();
+    let submit_response = rosetta_client
+        .construction_submit(network_identifier, signed_transaction)
+        .await
+        .unwrap();
+    assert_eq!(
+        hash_response.transaction_identifier,
+        submit_response.transaction_identifier
+    );
+}
+
+#[test]
+fn test_construction_api_approve_and_transfer_from_flow() {
+    let approver_keypair = EdKeypair::generate(0);
+    let spender_keypair = Secp256k1KeyPair::generate(1);
+    let approver_account: Account = approver_keypair.generate_principal_id().unwrap().0.into();
+    let spender_account: Account = spender_keypair.generate_principal_id().unwrap().0.into();
+    let receiver_account = Account {
+        owner: PrincipalId::new_user_test_id(2).0,
+        subaccount: Some([2; 32]),
+    };
+    let rt = Runtime::new().unwrap();
+    let setup = Setup::builder()
+        .with_initial_balance(approver_account, 1_000_000_000_000u64)
+        .build();
+
+    rt.block_on(async {
+        let env = RosettaTestingEnvironmentBuilder::new(&setup).build().await;
+        wait_for_rosetta_block(&env.rosetta_client, env.network_identifier.clone(), 0).await;
+
+        // The metadata endpoint returns the current fee of the ledger.
+        let metadata_response = env
+            .rosetta_client
+            .construction_metadata(
+                ConstructionMetadataRequestOptions {
+                    suggested_fee: true,
+                },
+                env.network_identifier.clone(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            metadata_response.suggested_fee.unwrap()[0].value,
+            DEFAULT_TRANSFER_FEE.to_string()
+        );
+
+        // Approve with an expected allowance and an expiration, paying the suggested fee.
+        let allowance: Nat = 1_000_000_000u64.into();
+        let expires_at = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos() as u64
+            + Duration::from_secs(3600).as_nanos() as u64;
+        let mut operations = env
+            .rosetta_client
+            .build_approve_operations(
+                &approver_keypair,
+                None,
+                spender_account,
+                allowance.clone(),
+                Some(Nat::from(0u64)),
+                env.network_identifier.clone(),
+                Some(expires_at),
+            )
+            .await
+            .unwrap();
+        operations.push(
+            env.rosetta_client
+                .build_fee_operation(approver_account, 2, env.network_identifier.clone())
+                .await
+                .unwrap(),
+        );
+        construct_and_submit_with_parse_round_trip(
+            &env.rosetta_client,
+            env.network_identifier.clone(),
+            &approver_keypair,
+            operations,
+        )
+        .await;
+        wait_for_rosetta_block(&env.rosetta_client, env.network_identifier.clone(), 1).await;
+
+        let current_allowance = env
+            .icrc1_agent
+            .allowance(approver_account, spender_account, CallMode::Query)
+            .await
+            .unwrap();
+        assert_eq!(current_allowance.allowance, allowance);
+        assert_eq!(current_allowance.expires_at, Some(expires_at));
+
+        // Transfer part of the allowance as the spender.
+        let transfer_amount: Nat = 1_000_000u64.into();
+        let mut operations = env
+            .rosetta_client
+            .build_transfer_from_operations(
+                &spender_keypair,
+                None,
+                receiver_account,
+                approver_account,
+                transfer_amount.clone(),
+                env.network_identifier.clone(),
+            )
+            .await
+            .unwrap();
+        operations.push(
+            env.rosetta_client
+                .build_fee_operation(approver_account, 3, env.network_identifier.clone())
+                .await
+                .unwrap(),
+        );
+        construct_and_submit_with_parse_round_trip(
+            &env.rosetta_client,
+            env.network_identifier.clone(),
+            &spender_keypair,
+            operations,
+        )
+        .await;
+        wait_for_rosetta_block(&env.rosetta_client, env.network_identifier.clone(), 2).await;
+
+        assert_eq!(
+            env.icrc1_agent
+                .balance_of(receiver_account, CallMode::Query)
+                .await
+                .unwrap(),
+            transfer_amount
+        );
+        assert_eq!(
+            env.icrc1_agent
+                .allowance(approver_account, spender_account, CallMode::Query)
+                .await
+                .unwrap()
+                .allowance,
+            allowance - transfer_amount - Nat::from(DEFAULT_TRANSFER_FEE)
+        );
+    });
+}
+
 #[test]
 fn test_search_transactions() {
     let mut runner = TestRunner::new(TestRunnerConfig {
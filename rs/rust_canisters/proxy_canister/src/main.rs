@@ -166,6 +166,7 @@ mod proxy_canister_test {
                 name: "date".to_string(),
                 value: "Fri, 03 Jun 2022 16:23:43 GMT".to_string(),
             }],
+            is_replicated: None,
         };
         let sanitized = transform(TransformArgs {
             response: raw_response,
@@ -186,6 +187,7 @@ mod proxy_canister_test {
                 status: 200,
                 headers: vec![],
                 body: response.as_bytes().to_vec(),
+                is_replicated: None,
             },
             context: context.as_bytes().to_vec(),
         };
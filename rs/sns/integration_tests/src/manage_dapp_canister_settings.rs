@@ -107,6 +107,7 @@ fn test_manage_dapp_canister_settings_successful() {
             Some(1_000_000_000_000),
             ic_management_canister_types::LogVisibilityV2::Public,
             Some(1_000_000_000),
+            vec![],
         ),
     );
 
@@ -160,6 +161,7 @@ fn test_manage_dapp_canister_settings_successful() {
             Some(0),
             ic_management_canister_types::LogVisibilityV2::Controllers,
             Some(2_000_000_000),
+            vec![],
         ),
     );
 }
@@ -237,6 +239,7 @@ fn test_manage_dapp_canister_settings_failure() {
             Some(1_000_000_000_000),
             ic_management_canister_types::LogVisibilityV2::Public,
             Some(1_000_000_000),
+            vec![],
         ),
     );
 
@@ -310,6 +313,7 @@ fn test_manage_dapp_canister_settings_failure() {
             Some(1_000_000_000_000),
             ic_management_canister_types::LogVisibilityV2::Public,
             Some(1_000_000_000),
+            vec![],
         ),
     );
 
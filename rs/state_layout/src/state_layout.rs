@@ -12,6 +12,7 @@ use ic_protobuf::{
     },
 };
 use ic_replicated_state::{
+    canister_snapshots::SnapshotSource,
     canister_state::{
         execution_state::{NextScheduledMethod, WasmMetadata},
         system_state::{
@@ -19,7 +20,7 @@ use ic_replicated_state::{
             OnLowWasmMemoryHookStatus,
         },
     },
-    page_map::{Shard, StorageLayout, StorageResult},
+    page_map::{storage::compression, Shard, StorageLayout, StorageResult},
     CallContextManager, CanisterStatus, ExecutionTask, ExportedFunctions, Global, NumWasmPages,
 };
 use ic_sys::{fs::sync_path, mmap::ScopedMmap};
@@ -180,6 +181,7 @@ pub struct CanisterStateBits {
     pub next_snapshot_id: u64,
     pub snapshots_memory_usage: NumBytes,
     pub on_low_wasm_memory_hook_status: OnLowWasmMemoryHookStatus,
+    pub environment_variables: BTreeMap<String, String>,
 }
 
 /// This struct contains bits of the `CanisterSnapshot` that are not already
@@ -190,6 +192,8 @@ pub struct CanisterSnapshotBits {
     pub snapshot_id: SnapshotId,
     /// Identifies the canister to which this snapshot belongs.
     pub canister_id: CanisterId,
+    /// Whether the snapshot was taken from the canister or uploaded.
+    pub source: SnapshotSource,
     /// The timestamp indicating the moment the snapshot was captured.
     pub taken_at_timestamp: Time,
     /// The canister version at the time of taking the snapshot.
@@ -540,6 +544,193 @@ impl StateLayout {
         Ok(())
     }
 
+    /// Rewrites the `PageMap` files of the checkpoint in the compressed format, see
+    /// [`compression`]. The logical content of the files, and hence the manifest of the
+    /// checkpoint, doesn't change.
+    ///
+    /// Each file is replaced atomically, and only if compression makes it smaller. Files that are
+    /// already compressed are skipped, so existing checkpoints can be migrated by calling this
+    /// function repeatedly.
+    pub fn compress_checkpoint(
+        &self,
+        cp: &CheckpointLayout<ReadOnly>,
+        thread_pool: Option<&mut scoped_threadpool::Pool>,
+    ) -> Result<(), LayoutError> {
+        self.compress_checkpoints(std::slice::from_ref(cp), thread_pool)
+    }
+
+    /// Like [`Self::compress_checkpoint`], but for several checkpoints at once. Checkpoints share
+    /// unchanged files through hardlinks, so each file (inode) is compressed only once and the
+    /// compressed file is hardlinked into all checkpoints that shared the original one.
+    ///
+    /// Files that are also linked from outside of `cps`, e.g. from a newer checkpoint or the tip,
+    /// are left uncompressed, so that loading those doesn't require decompressing them.
+    pub fn compress_checkpoints(
+        &self,
+        cps: &[CheckpointLayout<ReadOnly>],
+        mut thread_pool: Option<&mut scoped_threadpool::Pool>,
+    ) -> Result<(), LayoutError> {
+        use std::os::unix::fs::MetadataExt;
+
+        let start = Instant::now();
+        // The paths of each file, grouped by inode in the order they are first seen.
+        let mut files: Vec<Vec<(&CheckpointLayout<ReadOnly>, PathBuf)>> = Vec::new();
+        // The number of hardlinks of each file.
+        let mut nlinks: Vec<u64> = Vec::new();
+        let mut file_by_inode = BTreeMap::new();
+        for cp in cps {
+            for page_map in cp.all_existing_pagemaps()? {
+                let base = page_map.base();
+                let paths = base
+                    .exists()
+                    .then_some(base)
+                    .into_iter()
+                    .chain(page_map.existing_overlays()?);
+                for path in paths {
+                    let metadata = path.metadata().map_err(|err| LayoutError::IoError {
+                        path: path.clone(),
+                        message: "Failed to get file metadata".to_string(),
+                        io_err: err,
+                    })?;
+                    let index = *file_by_inode
+                        .entry((metadata.dev(), metadata.ino()))
+                        .or_insert_with(|| {
+                            files.push(Vec::new());
+                            nlinks.push(metadata.nlink());
+                            files.len() - 1
+                        });
+                    files[index].push((cp, path));
+                }
+            }
+        }
+
+        let files: Vec<_> = files
+            .into_iter()
+            .zip(nlinks)
+            .filter(|(paths, nlink)| *nlink <= paths.len() as u64)
+            .map(|(paths, _)| paths)
+            .collect();
+
+        let results = maybe_parallel_map(&mut thread_pool, files.iter(), |paths| {
+            let (cp, path) = &paths[0];
+            let sizes = self.compress_checkpoint_file(cp, path)?;
+            if sizes.0 != sizes.1 {
+                for (other_cp, other_path) in &paths[1..] {
+                    self.relink_checkpoint_file(other_cp, path, other_path)?;
+                }
+            }
+            Ok(sizes)
+        });
+        let (mut size_before, mut size_after) = (0, 0);
+        let mut dirs_to_sync = BTreeSet::new();
+        for (paths, result) in files.iter().zip(results) {
+            let (before, after): (u64, u64) = result?;
+            size_before += before;
+            size_after += after;
+            if before != after {
+                for (_, path) in paths {
+                    dirs_to_sync.insert(path.parent().unwrap().to_path_buf());
+                }
+            }
+        }
+        for dir in dirs_to_sync {
+            sync_path(&dir).map_err(|err| LayoutError::IoError {
+                path: dir,
+                message: "Failed to sync directory of compressed files".to_string(),
+                io_err: err,
+            })?;
+        }
+
+        info!(
+            self.log,
+            "Compressed checkpoints {:?} from {} to {} bytes in {:?}",
+            cps.iter().map(|cp| cp.height()).collect::<Vec<_>>(),
+            size_before,
+            size_after,
+            start.elapsed()
+        );
+        Ok(())
+    }
+
+    /// Atomically replaces `path` in checkpoint `cp` with a hardlink to `target`.
+    fn relink_checkpoint_file(
+        &self,
+        cp: &CheckpointLayout<ReadOnly>,
+        target: &Path,
+        path: &Path,
+    ) -> Result<(), LayoutError> {
+        let io_err = |io_err, message: &str| LayoutError::IoError {
+            path: path.to_path_buf(),
+            message: message.to_string(),
+            io_err,
+        };
+
+        let relative_path = path.strip_prefix(cp.raw_path()).unwrap_or(path);
+        let tmp = self.fs_tmp().join(format!(
+            "relink_{}_{}",
+            cp.height(),
+            relative_path.to_string_lossy().replace('/', "_")
+        ));
+        if tmp.exists() {
+            std::fs::remove_file(&tmp).map_err(|err| io_err(err, "Failed to remove stale file"))?;
+        }
+        std::fs::hard_link(target, &tmp)
+            .map_err(|err| io_err(err, "Failed to hardlink compressed file"))?;
+        std::fs::rename(&tmp, path)
+            .map_err(|err| io_err(err, "Failed to replace file with compressed file"))
+    }
+
+    /// Compresses a single `PageMap` file of checkpoint `cp` and returns its size before and after.
+    fn compress_checkpoint_file(
+        &self,
+        cp: &CheckpointLayout<ReadOnly>,
+        path: &Path,
+    ) -> Result<(u64, u64), LayoutError> {
+        let io_err = |io_err, message: &str| LayoutError::IoError {
+            path: path.to_path_buf(),
+            message: message.to_string(),
+            io_err,
+        };
+
+        let size = path
+            .metadata()
+            .map_err(|err| io_err(err, "Failed to get file metadata"))?
+            .len();
+        if compression::is_compressed(path)
+            .map_err(|err| io_err(err, "Failed to read compression trailer"))?
+        {
+            return Ok((size, size));
+        }
+
+        // File names are not unique within a checkpoint, so the temporary file is named after the
+        // relative path of the file.
+        let relative_path = path.strip_prefix(cp.raw_path()).unwrap_or(path);
+        let tmp = self.fs_tmp().join(format!(
+            "compress_{}_{}",
+            cp.height(),
+            relative_path.to_string_lossy().replace('/', "_")
+        ));
+        if tmp.exists() {
+            std::fs::remove_file(&tmp).map_err(|err| io_err(err, "Failed to remove stale file"))?;
+        }
+        compression::compress_file(path, &tmp)
+            .map_err(|err| io_err(err, "Failed to compress file"))?;
+
+        let compressed_size = tmp
+            .metadata()
+            .map_err(|err| io_err(err, "Failed to get compressed file metadata"))?
+            .len();
+        if compressed_size >= size {
+            std::fs::remove_file(&tmp)
+                .map_err(|err| io_err(err, "Failed to remove compressed file"))?;
+            return Ok((size, size));
+        }
+        mark_readonly_if_file(&tmp).map_err(|err| io_err(err, "Failed to mark file readonly"))?;
+        std::fs::rename(&tmp, path)
+            .map_err(|err| io_err(err, "Failed to replace file with compressed file"))?;
+        Ok((size, compressed_size))
+    }
+
     /// Create tip handler. Could only be called once as TipHandler is an exclusive owner of the
     /// tip folder.
     pub fn capture_tip_handler(&self) -> TipHandler {
@@ -2349,6 +2540,16 @@ impl From<CanisterStateBits> for pb_canister_state_bits::CanisterStateBits {
                 )
                 .into(),
             ),
+            environment_variables: item
+                .environment_variables
+                .iter()
+                .map(
+                    |(name, value)| pb_canister_state_bits::EnvironmentVariable {
+                        name: name.clone(),
+                        value: value.clone(),
+                    },
+                )
+                .collect(),
         }
     }
 }
@@ -2508,6 +2709,11 @@ impl TryFrom<pb_canister_state_bits::CanisterStateBits> for CanisterStateBits {
                 "CanisterStateBits::on_low_wasm_memory_hook_status",
             )
             .unwrap_or_default(),
+            environment_variables: value
+                .environment_variables
+                .into_iter()
+                .map(|var| (var.name, var.value))
+                .collect(),
         })
     }
 }
@@ -2596,6 +2802,7 @@ impl From<CanisterSnapshotBits> for pb_canister_snapshot_bits::CanisterSnapshotB
                 .iter()
                 .map(|global| global.into())
                 .collect(),
+            source: pb_canister_snapshot_bits::SnapshotSource::from(item.source) as i32,
         }
     }
 }
@@ -2629,6 +2836,9 @@ impl TryFrom<pb_canister_snapshot_bits::CanisterSnapshotBits> for CanisterSnapsh
         Ok(Self {
             snapshot_id: SnapshotId::from((canister_id, item.snapshot_id)),
             canister_id,
+            source: pb_canister_snapshot_bits::SnapshotSource::try_from(item.source)
+                .unwrap_or_default()
+                .into(),
             taken_at_timestamp: Time::from_nanos_since_unix_epoch(item.taken_at_timestamp),
             canister_version: item.canister_version,
             binary_hash,
@@ -2857,7 +3067,12 @@ fn copy_file_and_set_permissions(
     if src.metadata()?.permissions().readonly() && dst_permissions == FilePermissions::ReadOnly {
         std::fs::hard_link(src, dst)?
     } else {
-        do_copy(log, src, dst)?;
+        // Files that are modified at the destination must be uncompressed.
+        if dst_permissions == FilePermissions::ReadWrite && compression::is_compressed(src)? {
+            compression::decompress_file(src, dst)?;
+        } else {
+            do_copy(log, src, dst)?;
+        }
         let dst_metadata = dst.metadata()?;
         // We don't want to change the readonly flag of any files that are hardlinked somewhere else
         #[cfg(target_os = "linux")]
@@ -4,11 +4,13 @@ use ic_management_canister_types::{
     CanisterChange, CanisterChangeDetails, CanisterChangeOrigin, CanisterInstallMode, IC_00,
 };
 use ic_replicated_state::{
+    canister_snapshots::SnapshotSource,
     canister_state::system_state::{CanisterHistory, OnLowWasmMemoryHookStatus},
     metadata_state::subnet_call_context_manager::InstallCodeCallId,
     page_map::Shard,
     NumWasmPages,
 };
+use ic_sys::PAGE_SIZE;
 use ic_test_utilities_logger::with_test_replica_logger;
 use ic_test_utilities_tmpdir::tmpdir;
 use ic_test_utilities_types::messages::{IngressBuilder, RequestBuilder, ResponseBuilder};
@@ -61,6 +63,7 @@ fn default_canister_state_bits() -> CanisterStateBits {
         next_snapshot_id: 0,
         snapshots_memory_usage: NumBytes::from(0),
         on_low_wasm_memory_hook_status: OnLowWasmMemoryHookStatus::default(),
+        environment_variables: BTreeMap::new(),
     }
 }
 
@@ -207,6 +210,7 @@ fn test_canister_snapshots_decode() {
     let canister_snapshot_bits = CanisterSnapshotBits {
         snapshot_id: SnapshotId::from((canister_id, 5)),
         canister_id,
+        source: SnapshotSource::MetadataUpload,
         taken_at_timestamp: UNIX_EPOCH,
         canister_version: 3,
         binary_hash: Some(WasmHash::from(&CanisterModule::new(vec![2, 3, 4]))),
@@ -569,6 +573,86 @@ fn test_all_existing_pagemaps() {
     );
 }
 
+#[test]
+fn test_compress_checkpoints_keeps_hardlinks() {
+    use std::os::unix::fs::MetadataExt;
+
+    with_test_replica_logger(|log| {
+        let tempdir = tmpdir("state_layout");
+        let metrics_registry = ic_metrics::MetricsRegistry::new();
+        let state_layout =
+            StateLayout::try_new(log, tempdir.path().to_path_buf(), &metrics_registry).unwrap();
+        let scratchpad_dir = tmpdir("scratchpad");
+        let content: Vec<u8> = (0..64 * PAGE_SIZE).map(|i| (i / PAGE_SIZE) as u8).collect();
+
+        let scratchpad = CheckpointLayout::<RwPolicy<()>>::new_untracked(
+            scratchpad_dir.path().join("1"),
+            Height::new(1),
+        )
+        .unwrap();
+        let vmemory = scratchpad
+            .canister(&canister_test_id(1))
+            .unwrap()
+            .vmemory_0();
+        std::fs::write(vmemory.base(), &content).unwrap();
+        let cp1 = state_layout
+            .scratchpad_to_checkpoint(scratchpad, Height::new(1), None)
+            .unwrap();
+
+        // The second checkpoint shares the unchanged file with the first one.
+        let scratchpad = CheckpointLayout::<RwPolicy<()>>::new_untracked(
+            scratchpad_dir.path().join("2"),
+            Height::new(2),
+        )
+        .unwrap();
+        let vmemory = scratchpad
+            .canister(&canister_test_id(1))
+            .unwrap()
+            .vmemory_0();
+        std::fs::hard_link(
+            cp1.canister(&canister_test_id(1))
+                .unwrap()
+                .vmemory_0()
+                .base(),
+            vmemory.base(),
+        )
+        .unwrap();
+        let cp2 = state_layout
+            .scratchpad_to_checkpoint(scratchpad, Height::new(2), None)
+            .unwrap();
+
+        let base1 = cp1
+            .canister(&canister_test_id(1))
+            .unwrap()
+            .vmemory_0()
+            .base();
+        let base2 = cp2
+            .canister(&canister_test_id(1))
+            .unwrap()
+            .vmemory_0()
+            .base();
+
+        // Files that are also linked from a checkpoint that is not compressed stay uncompressed.
+        state_layout
+            .compress_checkpoints(std::slice::from_ref(&cp1), None)
+            .unwrap();
+        assert!(!compression::is_compressed(&base1).unwrap());
+
+        state_layout
+            .compress_checkpoints(&[cp1.clone(), cp2.clone()], None)
+            .unwrap();
+
+        assert!(compression::is_compressed(&base1).unwrap());
+        assert_eq!(
+            compression::uncompressed_len(&base1).unwrap(),
+            content.len() as u64
+        );
+        let (metadata1, metadata2) = (base1.metadata().unwrap(), base2.metadata().unwrap());
+        assert_eq!(metadata1.ino(), metadata2.ino());
+        assert_eq!(metadata1.nlink(), 2);
+    });
+}
+
 proptest! {
 #[test]
 fn read_back_wasm_memory_overlay_file_names(heights in random_sorted_unique_heights(10)) {
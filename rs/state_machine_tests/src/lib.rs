@@ -118,7 +118,7 @@ use ic_types::{
         Batch, BatchMessages, BatchSummary, BlockmakerMetrics, ConsensusResponse,
         QueryStatsPayload, SelfValidatingPayload, TotalQueryStats, ValidationContext, XNetPayload,
     },
-    canister_http::{CanisterHttpResponse, CanisterHttpResponseContent},
+    canister_http::{CanisterHttpResponse, CanisterHttpResponseContent, Replication},
     consensus::{
         block_maker::SubnetRecords,
         certification::{Certification, CertificationContent},
@@ -2111,6 +2111,13 @@ impl StateMachine {
         contents: Vec<CanisterHttpResponseContent>,
     ) {
         assert_eq!(contents.len(), self.nodes.len());
+        let replication = self
+            .get_latest_state()
+            .metadata
+            .subnet_call_context_manager
+            .canister_http_request_contexts
+            .get(&CanisterHttpRequestId::from(request_id))
+            .map(|context| context.replication);
         for (node, content) in std::iter::zip(self.nodes.iter(), contents.into_iter()) {
             let registry_version = self.registry_client.get_latest_version();
             let response = CanisterHttpResponse {
@@ -2132,9 +2139,18 @@ impl StateMachine {
                 content: response_metadata,
                 signature,
             };
-            self.canister_http_pool.write().unwrap().apply(vec![
-                CanisterHttpChangeAction::AddToValidated(share.clone(), response.clone()),
-            ]);
+            // The designated node of a non-replicated request gossips its
+            // response along with its share.
+            let change_action = match replication {
+                Some(Replication::NonReplicated(node_id)) if node_id == node.node_id => {
+                    CanisterHttpChangeAction::AddToValidatedAndGossipResponse(share, response)
+                }
+                _ => CanisterHttpChangeAction::AddToValidated(share, response),
+            };
+            self.canister_http_pool
+                .write()
+                .unwrap()
+                .apply(vec![change_action]);
         }
     }
 
@@ -2428,6 +2444,11 @@ impl StateMachine {
         replicated_state.metadata.batch_time
     }
 
+    /// Returns the path to the state directory of this `StateMachine`.
+    pub fn state_dir_path(&self) -> PathBuf {
+        self.state_dir.path()
+    }
+
     /// Sets the time that the state machine will use for executing next
     /// messages.
     pub fn set_time(&self, time: SystemTime) {
@@ -2663,6 +2684,20 @@ impl StateMachine {
         self.set_checkpoint_interval_length(checkpoint_interval_length);
     }
 
+    /// Makes sure that the latest state is checkpointed without executing a
+    /// round: if there is no checkpoint at the latest height yet, the latest
+    /// state is committed unchanged at the next height as a checkpoint.
+    pub fn checkpoint_latest_state(&self) {
+        let latest_height = self.state_manager.latest_state_height();
+        if self.state_manager.checkpoint_heights().last() == Some(&latest_height) {
+            return;
+        }
+        let (h, state) = self.state_manager.take_tip();
+        self.state_manager
+            .commit_and_certify(state, h.increment(), CertificationScope::Full, None);
+        self.certify_latest_state();
+    }
+
     /// Replaces the canister state in this state machine with the canister
     /// state in given source replicated state.
     ///
@@ -449,6 +449,7 @@ pub fn load_canister_state(
         canister_state_bits.log_visibility,
         canister_state_bits.canister_log,
         canister_state_bits.wasm_memory_limit,
+        canister_state_bits.environment_variables,
         canister_state_bits.next_snapshot_id,
         canister_state_bits.snapshots_memory_usage,
         metrics,
@@ -566,6 +567,7 @@ pub fn load_snapshot<P: ReadPolicy>(
 
     let canister_snapshot = CanisterSnapshot::new(
         canister_snapshot_bits.canister_id,
+        canister_snapshot_bits.source,
         canister_snapshot_bits.taken_at_timestamp,
         canister_snapshot_bits.canister_version,
         canister_snapshot_bits.certified_data.clone(),
@@ -827,6 +827,7 @@ pub struct StateManagerImpl {
     malicious_flags: MaliciousFlags,
     latest_height_update_time: Arc<Mutex<Instant>>,
     lsmt_status: FlagStatus,
+    checkpoint_compression: FlagStatus,
 }
 
 #[cfg(debug_assertions)]
@@ -1652,6 +1653,30 @@ impl StateManagerImpl {
             state: Arc::new(initial_state(own_subnet_id, own_subnet_type).take()),
         };
 
+        // Checkpoint compression relies on LSMT never modifying checkpoint files in the tip.
+        let checkpoint_compression = match config.lsmt_config.lsmt_status {
+            FlagStatus::Enabled => config.checkpoint_compression,
+            FlagStatus::Disabled => FlagStatus::Disabled,
+        };
+        if checkpoint_compression == FlagStatus::Enabled {
+            // Migrate existing checkpoints except the latest one, which the tip is initialized
+            // from and which therefore stays uncompressed. All other checkpoints are compressed in
+            // a single request, so that files shared between them are compressed once and stay
+            // shared.
+            if let Some((_, older)) = snapshots_with_checkpoint_layouts.split_last() {
+                if !older.is_empty() {
+                    tip_channel
+                        .send(TipRequest::CompressCheckpoints {
+                            checkpoint_layouts: older
+                                .iter()
+                                .map(|(_, checkpoint_layout)| checkpoint_layout.clone())
+                                .collect(),
+                        })
+                        .expect("failed to send CompressCheckpoints request");
+                }
+            }
+        }
+
         let tip_height_and_state = match snapshots_with_checkpoint_layouts.last() {
             Some((snapshot, checkpoint_layout)) => {
                 // Set latest state height in metadata to be last checkpoint height
@@ -1750,6 +1775,7 @@ impl StateManagerImpl {
             malicious_flags,
             latest_height_update_time: Arc::new(Mutex::new(Instant::now())),
             lsmt_status: config.lsmt_config.lsmt_status,
+            checkpoint_compression,
         }
     }
     /// Returns the Page Allocator file descriptor factory. This will then be
@@ -2678,11 +2704,28 @@ impl StateManagerImpl {
             // With lsmt, we do not need the defrag.
             // Without lsmt, the ResetTipAndMerge happens earlier in make_checkpoint.
             let tip_requests = if self.lsmt_status == FlagStatus::Enabled {
-                vec![TipRequest::ResetTipAndMerge {
+                let mut tip_requests = vec![TipRequest::ResetTipAndMerge {
                     checkpoint_layout: cp_layout.clone(),
                     pagemaptypes: PageMapType::list_all_including_snapshots(state),
                     is_initializing_tip: false,
-                }]
+                }];
+                // The new checkpoint stays uncompressed, as the tip and the next execution rounds
+                // load its files. Older checkpoints are only read in chunks, e.g. by state sync,
+                // so compressing them doesn't require decompressing whole files. Compress after
+                // resetting the tip, so that the tip no longer links files of older checkpoints.
+                if self.checkpoint_compression == FlagStatus::Enabled {
+                    let checkpoint_layouts: Vec<_> = self
+                        .states
+                        .read()
+                        .states_metadata
+                        .range(..height)
+                        .filter_map(|(_, metadata)| metadata.checkpoint_layout.clone())
+                        .collect();
+                    if !checkpoint_layouts.is_empty() {
+                        tip_requests.push(TipRequest::CompressCheckpoints { checkpoint_layouts });
+                    }
+                }
+                tip_requests
             } else {
                 vec![TipRequest::DefragTip {
                     height,
@@ -2796,9 +2839,11 @@ fn flush_canister_snapshots_and_page_maps(
     let snapshot_operations = tip_state.canister_snapshots.take_unflushed_changes();
 
     for op in &snapshot_operations {
-        // Only CanisterSnapshots that are new since the last flush will have PageMaps that need to be flushed. They will
-        // have a corresponding Backup in the snapshot operations list.
-        if let SnapshotOperation::Backup(_canister_id, snapshot_id) = op {
+        // Only CanisterSnapshots that are new or were uploaded to since the last flush will have PageMaps that need to be
+        // flushed. They will have a corresponding Backup or Upload in the snapshot operations list.
+        if let SnapshotOperation::Backup(_, snapshot_id) | SnapshotOperation::Upload(snapshot_id) =
+            op
+        {
             // If we can't find the CanisterSnapshot they must have been already deleted again. Nothing to flush in this case.
             if let Some(canister_snapshot) = tip_state.canister_snapshots.get_mut(*snapshot_id) {
                 let new_snapshot = Arc::make_mut(canister_snapshot);
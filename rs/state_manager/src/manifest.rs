@@ -25,7 +25,7 @@ use ic_config::flag_status::FlagStatus;
 use ic_crypto_sha2::Sha256;
 use ic_logger::{error, fatal, replica_logger::no_op_logger, ReplicaLogger};
 use ic_metrics::MetricsRegistry;
-use ic_replicated_state::page_map::StorageLayout;
+use ic_replicated_state::page_map::{storage::compression, StorageLayout};
 use ic_replicated_state::PageIndex;
 use ic_state_layout::{CheckpointLayout, ReadOnly, CANISTER_FILE, UNVERIFIED_CHECKPOINT_MARKER};
 use ic_sys::{mmap::ScopedMmap, PAGE_SIZE};
@@ -346,7 +346,7 @@ fn build_chunk_table_parallel(
     // and close the corresponding file.
     // This way we keep the number of files opened at the same time
     // low (it doesn't exceed the number of the threads).
-    let file_cache: Arc<Mutex<HashMap<u32, Weak<ScopedMmap>>>> =
+    let file_cache: Arc<Mutex<HashMap<u32, Weak<CheckpointFile>>>> =
         Arc::new(Mutex::new(HashMap::new()));
 
     // Compute real chunk hashes in parallel.
@@ -360,33 +360,33 @@ fn build_chunk_table_parallel(
             let file_cache = Arc::clone(&file_cache);
             scope.execute(move || {
                 let recompute_chunk_hash = || {
-                    let mmap: Arc<ScopedMmap> = if file_size > max_chunk_size as u64 {
+                    let file: Arc<CheckpointFile> = if file_size > max_chunk_size as u64 {
                         // We only use the file cache if there is more than one chunk in the file,
                         // otherwise the synchronization cost is unnecessary.
                         let mut cache = file_cache.lock().unwrap();
                         match cache.get(&chunk_info.file_index).and_then(Weak::upgrade) {
-                            Some(mmap) => mmap,
+                            Some(file) => file,
                             None => {
-                                let mmap = Arc::new(
-                                    ScopedMmap::from_path(&file_path)
-                                        .unwrap_or_else(|e| fatal!(log, "failed to mmap file {}: {}", file_path.display(), e)),
+                                let file = Arc::new(
+                                    CheckpointFile::open(&file_path)
+                                        .unwrap_or_else(|e| fatal!(log, "failed to open file {}: {}", file_path.display(), e)),
                                 );
-                                cache.insert(chunk_info.file_index, Arc::downgrade(&mmap));
-                                mmap
+                                cache.insert(chunk_info.file_index, Arc::downgrade(&file));
+                                file
                             }
                         }
                     } else {
                         Arc::new(
-                            ScopedMmap::from_path(&file_path)
-                                .unwrap_or_else(|e| fatal!(log, "failed to mmap file {}: {}", file_path.display(), e))
+                            CheckpointFile::open(&file_path)
+                                .unwrap_or_else(|e| fatal!(log, "failed to open file {}: {}", file_path.display(), e))
                         )
                     };
-                    let data = mmap.as_slice();
 
                     let mut hasher = chunk_hasher();
                     let chunk_start = chunk_info.offset as usize;
                     let chunk_end = chunk_start + chunk_info.size_bytes as usize;
-                    hasher.write(&data[chunk_start..chunk_end]);
+                    file.with_range(chunk_start..chunk_end, |data| hasher.write(data))
+                        .unwrap_or_else(|e| fatal!(log, "failed to read file {}: {}", file_path.display(), e));
                     hasher.finish()
                 };
 
@@ -461,7 +461,7 @@ fn build_chunk_table_sequential(
 
         (num_chunks as u32).update_hash(&mut file_hash);
 
-        let compute_file_chunk_hashes = |data: &[u8]| {
+        let compute_file_chunk_hashes = |file: &CheckpointFile| {
             // It's OK to not have any chunks for 0-sized files (though it's unlikely that
             // we have any).
             while bytes_left > 0 {
@@ -470,7 +470,10 @@ fn build_chunk_table_sequential(
 
                 let recompute_chunk_hash = || {
                     let mut hasher = chunk_hasher();
-                    hasher.write(&data[offset as usize..(offset + chunk_size) as usize]);
+                    file.with_range(offset as usize..(offset + chunk_size) as usize, |data| {
+                        hasher.write(data)
+                    })
+                    .expect("failed to read file");
                     hasher.finish()
                 };
 
@@ -539,9 +542,8 @@ fn build_chunk_table_sequential(
             });
         };
 
-        let mmap = ScopedMmap::from_path(root.join(&relative_path)).expect("failed to open file");
-        let data = mmap.as_slice();
-        compute_file_chunk_hashes(data);
+        let file = CheckpointFile::open(&root.join(&relative_path)).expect("failed to open file");
+        compute_file_chunk_hashes(&file);
     }
 
     assert_eq!(chunk_table.len(), chunk_actions.len());
@@ -549,6 +551,38 @@ fn build_chunk_table_sequential(
     (file_table, chunk_table)
 }
 
+/// The content of a checkpoint file. Uncompressed files are memory mapped, while chunks of
+/// compressed `PageMap` files are decompressed on demand using their block index.
+enum CheckpointFile {
+    Mapped(ScopedMmap),
+    Compressed(compression::Reader),
+}
+
+impl CheckpointFile {
+    fn open(path: &Path) -> std::io::Result<Self> {
+        let reader = compression::Reader::open(path)?;
+        if reader.is_compressed() {
+            Ok(Self::Compressed(reader))
+        } else {
+            Ok(Self::Mapped(ScopedMmap::mmap_file_readonly(
+                std::fs::File::open(path)?,
+            )?))
+        }
+    }
+
+    /// Calls `f` with the (uncompressed) bytes of the file in `range`.
+    fn with_range<R>(&self, range: Range<usize>, f: impl FnOnce(&[u8]) -> R) -> std::io::Result<R> {
+        match self {
+            Self::Mapped(mmap) => Ok(f(&mmap.as_slice()[range])),
+            Self::Compressed(reader) => {
+                let mut buf = vec![0; range.len()];
+                reader.read_exact_at(&mut buf, range.start as u64)?;
+                Ok(f(&buf))
+            }
+        }
+    }
+}
+
 /// Traverses root recursively and populates the `files` vector with entries of
 /// the form `(relative_file_name, file_len)`.
 fn files_with_sizes(
@@ -566,7 +600,19 @@ fn files_with_sizes(
         })?;
 
     if metadata.is_file() {
-        files.push(FileWithSize(relative_path, metadata.len()))
+        // Compressed files are accounted with their uncompressed size.
+        let size_bytes = if compression::is_page_map_file(&absolute_path) {
+            compression::uncompressed_len(&absolute_path).map_err(|io_err| {
+                CheckpointError::IoError {
+                    path: absolute_path.clone(),
+                    message: "failed to get uncompressed length".to_string(),
+                    io_err: io_err.to_string(),
+                }
+            })?
+        } else {
+            metadata.len()
+        };
+        files.push(FileWithSize(relative_path, size_bytes))
     } else {
         assert!(
             metadata.is_dir(),
@@ -13,6 +13,7 @@ use crate::{
 };
 use ic_interfaces::p2p::state_sync::{AddChunkError, Chunk, ChunkId, Chunkable};
 use ic_logger::{debug, error, fatal, info, trace, warn, ReplicaLogger};
+use ic_replicated_state::page_map::storage::compression;
 use ic_state_layout::utils::do_copy_overwrite;
 use ic_state_layout::{error::LayoutError, CheckpointLayout, ReadOnly, RwPolicy, StateLayout};
 use ic_sys::mmap::ScopedMmap;
@@ -314,7 +315,7 @@ impl IncompleteState {
                         .permissions();
                     if validate_data || ALWAYS_VALIDATE {
 
-                        let src = std::fs::File::open(&src_path).unwrap_or_else(|err| {
+                        let src = compression::open_in(&src_path, root_new).unwrap_or_else(|err| {
                             fatal!(
                                 log,
                                 "Failed to open file {} for read: {}",
@@ -594,7 +595,7 @@ impl IncompleteState {
                     root_old.join(&manifest_old.file_table[*src_file_index].relative_path);
                 let corrupted_chunks = Arc::clone(&corrupted_chunks);
                 scope.execute(move || {
-                    let src = std::fs::File::open(&src_path).unwrap_or_else(|err| {
+                    let src = compression::open_in(&src_path, root_new).unwrap_or_else(|err| {
                         fatal!(
                             log,
                             "Failed to open file {} for read: {}",
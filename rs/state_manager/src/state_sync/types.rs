@@ -477,7 +477,7 @@ impl StateSyncMessage {
 
         #[cfg(target_family = "unix")]
         {
-            use std::os::unix::fs::FileExt;
+            use ic_replicated_state::page_map::storage::compression;
 
             let get_single_chunk = |chunk_index: usize| -> Option<Vec<u8>> {
                 let chunk = self.manifest.chunk_table.get(chunk_index).cloned()?;
@@ -485,7 +485,8 @@ impl StateSyncMessage {
                     .checkpoint_root
                     .join(&self.manifest.file_table[chunk.file_index as usize].relative_path);
                 let mut buf = vec![0; chunk.size_bytes as usize];
-                let f = std::fs::File::open(path).ok()?;
+                // PageMap files may be compressed, chunks refer to their uncompressed content.
+                let f = compression::Reader::open(&path).ok()?;
                 f.read_exact_at(&mut buf[..], chunk.offset).ok()?;
                 Some(buf)
             };
@@ -120,6 +120,12 @@ pub(crate) enum TipRequest {
         states: Arc<parking_lot::RwLock<SharedState>>,
         persist_metadata_guard: Arc<Mutex<()>>,
     },
+    /// Rewrite the PageMap files of the checkpoints in the compressed format, keeping the
+    /// hardlinks between them.
+    /// State: *
+    CompressCheckpoints {
+        checkpoint_layouts: Vec<CheckpointLayout<ReadOnly>>,
+    },
     /// Validate the checkpointed state is valid and identical to the execution state.
     /// Crash if diverges.
     #[cfg(debug_assertions)]
@@ -449,6 +455,25 @@ pub(crate) fn spawn_tip_thread(
                             have_latest_manifest = true;
                         }
 
+                        TipRequest::CompressCheckpoints { checkpoint_layouts } => {
+                            let _timer = request_timer(&metrics, "compress_checkpoints");
+                            // Compression is best effort, the checkpoints remain valid if some of
+                            // their files stay uncompressed.
+                            if let Err(err) = state_layout
+                                .compress_checkpoints(&checkpoint_layouts, Some(&mut thread_pool))
+                            {
+                                error!(
+                                    log,
+                                    "Failed to compress checkpoints {:?}: {}",
+                                    checkpoint_layouts
+                                        .iter()
+                                        .map(|cp| cp.height())
+                                        .collect::<Vec<_>>(),
+                                    err
+                                );
+                            }
+                        }
+
                         #[cfg(debug_assertions)]
                         TipRequest::ValidateReplicatedState {
                             checkpointed_state,
@@ -490,6 +515,11 @@ fn flush_snapshot_changes<T>(
             SnapshotOperation::Restore(canister_id, snapshot_id) => {
                 restore(log, layout, canister_id, snapshot_id)?;
             }
+            SnapshotOperation::Upload(snapshot_id) => {
+                // The uploaded contents are only stored in the `PageMap`s of the snapshot,
+                // which are flushed afterwards. We only make sure the directory exists.
+                layout.snapshot(&snapshot_id)?;
+            }
         }
     }
 
@@ -1075,6 +1105,7 @@ fn serialize_canister_to_tip(
                 .system_state
                 .task_queue
                 .peek_hook_status(),
+            environment_variables: canister_state.system_state.environment_variables.clone(),
         }
         .into(),
     )?;
@@ -1096,6 +1127,7 @@ fn serialize_snapshot_to_tip(
         CanisterSnapshotBits {
             snapshot_id: *snapshot_id,
             canister_id: canister_snapshot.canister_id(),
+            source: canister_snapshot.source(),
             taken_at_timestamp: *canister_snapshot.taken_at_timestamp(),
             canister_version: canister_snapshot.canister_version(),
             binary_hash: Some(canister_snapshot.canister_module().module_hash().into()),
@@ -1,7 +1,9 @@
 //! Computes diff of canonical trees between checkpoints.
 
 use ic_registry_subnet_type::SubnetType;
-use ic_replicated_state::page_map::TestPageAllocatorFileDescriptorImpl;
+use ic_replicated_state::{
+    page_map::TestPageAllocatorFileDescriptorImpl, CanisterState, PageMap, ReplicatedState,
+};
 use ic_state_layout::CompleteCheckpointLayout;
 use ic_state_manager::{
     checkpoint::load_checkpoint,
@@ -9,44 +11,379 @@ use ic_state_manager::{
     tree_hash::hash_state,
     CheckpointError, CheckpointMetrics,
 };
-use ic_types::Height;
+use ic_types::{CanisterId, Height};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
 use std::path::PathBuf;
 use std::sync::Arc;
 
-/// Loads the checkpoints at `path_a` and `path_b` and diffs them.
-fn diff_checkpoints(path_a: PathBuf, path_b: PathBuf) -> Result<Changes, CheckpointError> {
+/// How the output of `cdiff` is grouped and formatted.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DiffMode {
+    /// Raw diff of the canonical trees.
+    Tree,
+    /// Changes grouped by canister, as human-readable text.
+    PerCanister,
+    /// Changes grouped by canister, as JSON.
+    PerCanisterJson,
+}
+
+/// Loads the checkpoint at `path`.
+fn load_state(path: PathBuf) -> Result<ReplicatedState, CheckpointError> {
     let unused_height = Height::from(0);
     let own_subnet_type = SubnetType::Application;
     let dummy_metrics_registry = ic_metrics::MetricsRegistry::new();
     let dummy_metrics = CheckpointMetrics::new(&dummy_metrics_registry, crate::commands::logger());
-    let state_a = load_checkpoint(
-        &CompleteCheckpointLayout::new_untracked(path_a, unused_height)?,
-        own_subnet_type,
-        &dummy_metrics,
-        None,
-        Arc::new(TestPageAllocatorFileDescriptorImpl::new()),
-    )?;
-    let state_b = load_checkpoint(
-        &CompleteCheckpointLayout::new_untracked(path_b, unused_height)?,
+    load_checkpoint(
+        &CompleteCheckpointLayout::new_untracked(path, unused_height)?,
         own_subnet_type,
         &dummy_metrics,
         None,
         Arc::new(TestPageAllocatorFileDescriptorImpl::new()),
-    )?;
+    )
+}
+
+/// Loads the checkpoints at `path_a` and `path_b` and diffs them.
+fn diff_checkpoints(path_a: PathBuf, path_b: PathBuf) -> Result<Changes, CheckpointError> {
+    let state_a = load_state(path_a)?;
+    let state_b = load_state(path_b)?;
 
     let tree_a = hash_state(&state_a);
     let tree_b = hash_state(&state_b);
     Ok(diff(&tree_a, &tree_b))
 }
 
+/// A value that differs between the two checkpoints.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct Change<T> {
+    pub before: T,
+    pub after: T,
+}
+
+impl<T: PartialEq> Change<T> {
+    fn between(before: T, after: T) -> Option<Self> {
+        if before == after {
+            None
+        } else {
+            Some(Self { before, after })
+        }
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Change<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} -> {}", self.before, self.after)
+    }
+}
+
+/// Whether a canister exists in both checkpoints or only in one of them.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CanisterDiffKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// The changes of a single canister between two checkpoints. Fields that did
+/// not change are `None` (or zero, for page counts).
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct CanisterChanges {
+    pub kind: CanisterDiffKind,
+    pub heap_pages_changed: usize,
+    pub stable_memory_pages_changed: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub certified_data: Option<Change<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cycles_balance: Option<Change<u128>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub controllers: Option<Change<Vec<String>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ingress_queue_size: Option<Change<usize>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input_queues_size: Option<Change<usize>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_queues_size: Option<Change<usize>>,
+}
+
+impl CanisterChanges {
+    fn is_empty(&self) -> bool {
+        self.kind == CanisterDiffKind::Changed
+            && self.heap_pages_changed == 0
+            && self.stable_memory_pages_changed == 0
+            && self.certified_data.is_none()
+            && self.cycles_balance.is_none()
+            && self.controllers.is_none()
+            && self.ingress_queue_size.is_none()
+            && self.input_queues_size.is_none()
+            && self.output_queues_size.is_none()
+    }
+}
+
+/// Counts the host pages that differ between two page maps. A missing page
+/// map is treated as empty.
+fn count_changed_pages(a: Option<&PageMap>, b: Option<&PageMap>) -> usize {
+    let empty = PageMap::new(Arc::new(TestPageAllocatorFileDescriptorImpl::new()));
+    let a = a.unwrap_or(&empty);
+    let b = b.unwrap_or(&empty);
+    a.maybe_differing_pages(b)
+        .into_iter()
+        .filter(|index| a.get_page(*index) != b.get_page(*index))
+        .count()
+}
+
+fn heap(canister: &CanisterState) -> Option<&PageMap> {
+    canister
+        .execution_state
+        .as_ref()
+        .map(|execution_state| &execution_state.wasm_memory.page_map)
+}
+
+fn stable_memory(canister: &CanisterState) -> Option<&PageMap> {
+    canister
+        .execution_state
+        .as_ref()
+        .map(|execution_state| &execution_state.stable_memory.page_map)
+}
+
+fn certified_data(canister: &CanisterState) -> String {
+    hex::encode(&canister.system_state.certified_data)
+}
+
+fn cycles_balance(canister: &CanisterState) -> u128 {
+    canister.system_state.balance().get()
+}
+
+fn controllers(canister: &CanisterState) -> Vec<String> {
+    canister
+        .system_state
+        .controllers
+        .iter()
+        .map(|controller| controller.to_string())
+        .collect()
+}
+
+fn ingress_queue_size(canister: &CanisterState) -> usize {
+    canister.system_state.queues().ingress_queue_message_count()
+}
+
+fn input_queues_size(canister: &CanisterState) -> usize {
+    canister.system_state.queues().input_queues_message_count()
+}
+
+fn output_queues_size(canister: &CanisterState) -> usize {
+    canister.system_state.queues().output_queues_message_count()
+}
+
+/// Compares the canister with the same ID in two states. Either side may be
+/// missing, but not both.
+fn diff_canister(a: Option<&CanisterState>, b: Option<&CanisterState>) -> CanisterChanges {
+    let kind = match (a, b) {
+        (Some(_), Some(_)) => CanisterDiffKind::Changed,
+        (None, Some(_)) => CanisterDiffKind::Added,
+        (Some(_), None) => CanisterDiffKind::Removed,
+        (None, None) => unreachable!("canister must exist in at least one of the states"),
+    };
+    // A missing canister compares as if every field had its default value.
+    fn field<T: Default + PartialEq>(
+        a: Option<&CanisterState>,
+        b: Option<&CanisterState>,
+        f: fn(&CanisterState) -> T,
+    ) -> Option<Change<T>> {
+        Change::between(a.map(f).unwrap_or_default(), b.map(f).unwrap_or_default())
+    }
+
+    CanisterChanges {
+        kind,
+        heap_pages_changed: count_changed_pages(a.and_then(heap), b.and_then(heap)),
+        stable_memory_pages_changed: count_changed_pages(
+            a.and_then(stable_memory),
+            b.and_then(stable_memory),
+        ),
+        certified_data: field(a, b, certified_data),
+        cycles_balance: field(a, b, cycles_balance),
+        controllers: field(a, b, controllers),
+        ingress_queue_size: field(a, b, ingress_queue_size),
+        input_queues_size: field(a, b, input_queues_size),
+        output_queues_size: field(a, b, output_queues_size),
+    }
+}
+
+/// Groups the differences between two states by canister. Canisters without
+/// changes are omitted.
+pub fn diff_canisters(
+    state_a: &ReplicatedState,
+    state_b: &ReplicatedState,
+) -> BTreeMap<CanisterId, CanisterChanges> {
+    let canister_ids: BTreeSet<CanisterId> = state_a
+        .canister_states
+        .keys()
+        .chain(state_b.canister_states.keys())
+        .cloned()
+        .collect();
+    canister_ids
+        .into_iter()
+        .map(|canister_id| {
+            let changes = diff_canister(
+                state_a.canister_state(&canister_id),
+                state_b.canister_state(&canister_id),
+            );
+            (canister_id, changes)
+        })
+        .filter(|(_, changes)| !changes.is_empty())
+        .collect()
+}
+
+/// Prints per-canister changes in a human-readable form.
+fn print_canister_changes(changes: &BTreeMap<CanisterId, CanisterChanges>) {
+    for (canister_id, changes) in changes {
+        let kind = match changes.kind {
+            CanisterDiffKind::Added => "added",
+            CanisterDiffKind::Removed => "removed",
+            CanisterDiffKind::Changed => "changed",
+        };
+        println!("{} ({})", canister_id, kind);
+        if changes.heap_pages_changed > 0 {
+            println!("  heap pages changed: {}", changes.heap_pages_changed);
+        }
+        if changes.stable_memory_pages_changed > 0 {
+            println!(
+                "  stable memory pages changed: {}",
+                changes.stable_memory_pages_changed
+            );
+        }
+        if let Some(change) = &changes.certified_data {
+            println!("  certified data: {}", change);
+        }
+        if let Some(change) = &changes.cycles_balance {
+            println!("  cycles balance: {}", change);
+        }
+        if let Some(change) = &changes.controllers {
+            println!(
+                "  controllers: [{}] -> [{}]",
+                change.before.join(", "),
+                change.after.join(", ")
+            );
+        }
+        if let Some(change) = &changes.ingress_queue_size {
+            println!("  ingress queue size: {}", change);
+        }
+        if let Some(change) = &changes.input_queues_size {
+            println!("  input queues size: {}", change);
+        }
+        if let Some(change) = &changes.output_queues_size {
+            println!("  output queues size: {}", change);
+        }
+    }
+}
+
 /// `cdiff` command entry point.
-pub fn do_diff(path_a: PathBuf, path_b: PathBuf) -> Result<(), String> {
-    let d = diff_checkpoints(path_a, path_b).map_err(|err| format!("✗ Diff FAILED:\n\t{}", err))?;
-    if d.is_empty() {
-        println!("✓ Snapshots are identical");
+pub fn do_diff(path_a: PathBuf, path_b: PathBuf, mode: DiffMode) -> Result<(), String> {
+    if mode == DiffMode::Tree {
+        let d =
+            diff_checkpoints(path_a, path_b).map_err(|err| format!("✗ Diff FAILED:\n\t{}", err))?;
+        if d.is_empty() {
+            println!("✓ Snapshots are identical");
+        } else {
+            print!("{}", PrettyPrintedChanges(&d));
+        }
+        return Ok(());
+    }
+
+    let load = |path| load_state(path).map_err(|err| format!("✗ Diff FAILED:\n\t{}", err));
+    let changes = diff_canisters(&load(path_a)?, &load(path_b)?);
+    if mode == DiffMode::PerCanisterJson {
+        let changes: BTreeMap<String, CanisterChanges> = changes
+            .into_iter()
+            .map(|(canister_id, changes)| (canister_id.to_string(), changes))
+            .collect();
+        let json = serde_json::to_string_pretty(&changes)
+            .map_err(|err| format!("✗ Failed to serialize the diff:\n\t{}", err))?;
+        println!("{}", json);
+    } else if changes.is_empty() {
+        println!("✓ Canisters are identical");
     } else {
-        print!("{}", PrettyPrintedChanges(&d));
+        print_canister_changes(&changes);
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use ic_test_utilities_state::{CanisterStateBuilder, ReplicatedStateBuilder};
+    use ic_test_utilities_types::ids::{canister_test_id, user_test_id};
+    use ic_types::Cycles;
+
+    use super::{diff_canisters, CanisterDiffKind, Change};
+
+    #[test]
+    fn per_canister_diff_reports_changed_fields() {
+        let unchanged = CanisterStateBuilder::new()
+            .with_canister_id(canister_test_id(1))
+            .build();
+        let removed = CanisterStateBuilder::new()
+            .with_canister_id(canister_test_id(2))
+            .build();
+        let changed_before = CanisterStateBuilder::new()
+            .with_canister_id(canister_test_id(3))
+            .with_wasm(vec![])
+            .with_stable_memory(vec![1; 4096])
+            .with_cycles(Cycles::new(1_000))
+            .with_certified_data(vec![1, 2])
+            .build();
+        let changed_after = CanisterStateBuilder::new()
+            .with_canister_id(canister_test_id(3))
+            .with_controller(user_test_id(7).get())
+            .with_wasm(vec![])
+            .with_stable_memory(vec![2; 3 * 4096])
+            .with_cycles(Cycles::new(500))
+            .with_certified_data(vec![1, 2])
+            .build();
+        let added = CanisterStateBuilder::new()
+            .with_canister_id(canister_test_id(4))
+            .build();
+
+        let state_a = ReplicatedStateBuilder::new()
+            .with_canister(unchanged.clone())
+            .with_canister(removed)
+            .with_canister(changed_before)
+            .build();
+        let state_b = ReplicatedStateBuilder::new()
+            .with_canister(unchanged)
+            .with_canister(changed_after)
+            .with_canister(added)
+            .build();
+
+        let changes = diff_canisters(&state_a, &state_b);
+        assert_eq!(
+            changes.keys().cloned().collect::<Vec<_>>(),
+            vec![
+                canister_test_id(2),
+                canister_test_id(3),
+                canister_test_id(4)
+            ]
+        );
+        assert_eq!(
+            changes[&canister_test_id(2)].kind,
+            CanisterDiffKind::Removed
+        );
+        assert_eq!(changes[&canister_test_id(4)].kind, CanisterDiffKind::Added);
+
+        let changed = &changes[&canister_test_id(3)];
+        assert_eq!(changed.kind, CanisterDiffKind::Changed);
+        assert_eq!(changed.heap_pages_changed, 0);
+        assert_eq!(changed.stable_memory_pages_changed, 3);
+        assert_eq!(changed.certified_data, None);
+        assert_eq!(
+            changed.cycles_balance,
+            Some(Change {
+                before: 1_000,
+                after: 500
+            })
+        );
+        assert!(changed.controllers.is_some());
+        assert_eq!(changed.ingress_queue_size, None);
+    }
+}
@@ -0,0 +1,315 @@
+//! Executes a read-only query against a canister in a checkpoint.
+
+use candid::IDLArgs;
+use ic_config::{execution_environment::Config as HypervisorConfig, subnet_config::SubnetConfig};
+use ic_cycles_account_manager::CyclesAccountManager;
+use ic_execution_environment::{Hypervisor, InternalHttpQueryHandler};
+use ic_interfaces_state_manager::Labeled;
+use ic_logger::replica_logger::no_op_logger;
+use ic_metrics::MetricsRegistry;
+use ic_registry_subnet_type::SubnetType;
+use ic_replicated_state::page_map::TestPageAllocatorFileDescriptorImpl;
+use ic_state_layout::{CompleteCheckpointLayout, StateLayout, CHECKPOINTS_DIR};
+use ic_state_manager::{checkpoint::load_checkpoint, CheckpointMetrics};
+use ic_types::{
+    ingress::WasmResult,
+    messages::{Query, QuerySource},
+    CanisterId, Height, PrincipalId, UserId,
+};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Loads the checkpoint at `height` (or the latest checkpoint, if `height` is
+/// not specified) under the given state layout root and executes the query
+/// `method_name` of `canister_id` against it in non-replicated mode.
+///
+/// The argument is given and the reply is printed in the Candid textual
+/// format. The checkpoint is opened read-only and nothing under the state
+/// layout root is modified.
+pub fn do_query(
+    root: PathBuf,
+    height: Option<Height>,
+    subnet_type: SubnetType,
+    canister_id: CanisterId,
+    method_name: String,
+    arg: String,
+    sender: PrincipalId,
+) -> Result<(), String> {
+    let method_payload = candid_parser::parse_idl_args(&arg)
+        .map_err(|e| format!("failed to parse the Candid argument {}: {}", arg, e))?
+        .to_bytes()
+        .map_err(|e| format!("failed to encode the Candid argument {}: {}", arg, e))?;
+
+    let cp_layout = open_checkpoint(&root, height)?;
+    let reply = execute_query(
+        &cp_layout,
+        subnet_type,
+        canister_id,
+        method_name,
+        method_payload,
+        sender,
+        HypervisorConfig::default(),
+    )?;
+    match IDLArgs::from_bytes(&reply) {
+        Ok(args) => println!("{}", args),
+        // Not every canister replies with Candid.
+        Err(_) => println!("0x{}", hex::encode(reply)),
+    }
+    Ok(())
+}
+
+/// Opens the verified checkpoint at `height` (or the latest verified
+/// checkpoint) under the state layout root without constructing a
+/// `StateLayout`, which would clean up and create directories under the root.
+fn open_checkpoint(
+    root: &Path,
+    height: Option<Height>,
+) -> Result<CompleteCheckpointLayout, String> {
+    let checkpoints = root.join(CHECKPOINTS_DIR);
+    let open = |height: Height| {
+        CompleteCheckpointLayout::new_untracked(
+            checkpoints.join(StateLayout::checkpoint_name(height)),
+            height,
+        )
+        .map_err(|e| format!("failed to open the checkpoint at height {}: {}", height, e))
+    };
+
+    if let Some(height) = height {
+        let cp_layout = open(height)?;
+        if !cp_layout.is_checkpoint_verified() {
+            return Err(format!(
+                "the checkpoint at height {} is not verified",
+                height
+            ));
+        }
+        return Ok(cp_layout);
+    }
+
+    let mut heights = match std::fs::read_dir(&checkpoints) {
+        Ok(entries) => entries
+            .filter_map(|entry| {
+                let name = entry.ok()?.file_name();
+                u64::from_str_radix(name.to_str()?, 16)
+                    .ok()
+                    .map(Height::new)
+            })
+            .collect(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => vec![],
+        Err(e) => {
+            return Err(format!(
+                "failed to list the checkpoints under {}: {}",
+                checkpoints.display(),
+                e
+            ))
+        }
+    };
+    heights.sort_unstable();
+    for height in heights.into_iter().rev() {
+        let cp_layout = open(height)?;
+        if cp_layout.is_checkpoint_verified() {
+            return Ok(cp_layout);
+        }
+    }
+    Err(format!("no checkpoints found under {}", root.display()))
+}
+
+/// Loads the checkpoint and executes the query against it, returning the
+/// reply.
+fn execute_query(
+    cp_layout: &CompleteCheckpointLayout,
+    subnet_type: SubnetType,
+    canister_id: CanisterId,
+    method_name: String,
+    method_payload: Vec<u8>,
+    sender: PrincipalId,
+    config: HypervisorConfig,
+) -> Result<Vec<u8>, String> {
+    let height = cp_layout.height();
+    let metrics_registry = MetricsRegistry::new();
+    let fd_factory = Arc::new(TestPageAllocatorFileDescriptorImpl::new());
+    let checkpoint_metrics = CheckpointMetrics::new(&metrics_registry, no_op_logger());
+    let state = load_checkpoint(
+        cp_layout,
+        subnet_type,
+        &checkpoint_metrics,
+        None,
+        Arc::clone(&fd_factory) as Arc<_>,
+    )
+    .map_err(|e| format!("failed to load the checkpoint at height {}: {}", height, e))?;
+
+    if state.canister_state(&canister_id).is_none() {
+        return Err(format!(
+            "canister {} does not exist at height {}",
+            canister_id, height
+        ));
+    }
+
+    let own_subnet_id = state.metadata.own_subnet_id;
+    let subnet_config = SubnetConfig::new(subnet_type);
+    let cycles_account_manager = Arc::new(CyclesAccountManager::new(
+        subnet_config.scheduler_config.max_instructions_per_message,
+        subnet_type,
+        own_subnet_id,
+        subnet_config.cycles_account_manager_config,
+    ));
+    let hypervisor = Arc::new(Hypervisor::new(
+        config.clone(),
+        &metrics_registry,
+        own_subnet_id,
+        subnet_type,
+        no_op_logger(),
+        Arc::clone(&cycles_account_manager),
+        subnet_config.scheduler_config.dirty_page_overhead,
+        fd_factory,
+    ));
+    let (query_stats_collector, _) =
+        ic_query_stats::init_query_stats(no_op_logger(), &config, &metrics_registry);
+    let query_handler = InternalHttpQueryHandler::new(
+        no_op_logger(),
+        hypervisor,
+        subnet_type,
+        config,
+        &metrics_registry,
+        subnet_config
+            .scheduler_config
+            .max_instructions_per_message_without_dts,
+        cycles_account_manager,
+        query_stats_collector,
+    );
+
+    let query = Query {
+        source: QuerySource::User {
+            user_id: UserId::from(sender),
+            ingress_expiry: 0,
+            nonce: None,
+        },
+        receiver: canister_id,
+        method_name,
+        method_payload,
+    };
+
+    // No data certificate is available for historical states.
+    match query_handler.query(query, Labeled::new(height, Arc::new(state)), vec![]) {
+        Ok(WasmResult::Reply(reply)) => Ok(reply),
+        Ok(WasmResult::Reject(message)) => Err(format!("the query was rejected: {}", message)),
+        Err(e) => Err(format!("failed to execute the query: {}", e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{do_query, execute_query, open_checkpoint};
+    use ic_config::{execution_environment::Config as HypervisorConfig, flag_status::FlagStatus};
+    use ic_interfaces_state_manager::StateReader;
+    use ic_registry_subnet_type::SubnetType;
+    use ic_state_layout::{StateLayout, CHECKPOINTS_DIR};
+    use ic_state_machine_tests::StateMachine;
+    use ic_test_utilities_types::ids::canister_test_id;
+    use ic_types::PrincipalId;
+    use std::path::Path;
+
+    const QUERY_WAT: &str = r#"
+        (module
+            (import "ic0" "msg_reply" (func $msg_reply))
+            (import "ic0" "msg_reply_data_append"
+                (func $msg_reply_data_append (param i32 i32)))
+            (func $greet
+                (call $msg_reply_data_append (i32.const 0) (i32.const 5))
+                (call $msg_reply))
+            (memory 1)
+            (data (i32.const 0) "hello")
+            (export "canister_query greet" (func $greet)))"#;
+
+    fn copy_dir(src: &Path, dst: &Path) {
+        std::fs::create_dir_all(dst).unwrap();
+        for entry in std::fs::read_dir(src).unwrap() {
+            let entry = entry.unwrap();
+            let dst_path = dst.join(entry.file_name());
+            if entry.file_type().unwrap().is_dir() {
+                copy_dir(&entry.path(), &dst_path);
+            } else {
+                std::fs::copy(entry.path(), dst_path).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn query_runs_against_checkpoint_without_modifying_root() {
+        let env = StateMachine::new();
+        let canister_id = env.install_canister_wat(QUERY_WAT, vec![], None);
+        env.checkpointed_tick();
+        env.await_state_hash();
+        let height = env.state_manager.latest_state_height();
+        let checkpoint_name = StateLayout::checkpoint_name(height);
+
+        // Only the checkpoint is copied to the new root.
+        let root = tempfile::TempDir::new().unwrap();
+        copy_dir(
+            &env.state_dir_path()
+                .join(CHECKPOINTS_DIR)
+                .join(&checkpoint_name),
+            &root.path().join(CHECKPOINTS_DIR).join(&checkpoint_name),
+        );
+
+        let cp_layout = open_checkpoint(root.path(), None).unwrap();
+        assert_eq!(cp_layout.height(), height);
+        let config = HypervisorConfig {
+            canister_sandboxing_flag: FlagStatus::Disabled,
+            ..Default::default()
+        };
+        let reply = execute_query(
+            &cp_layout,
+            SubnetType::System,
+            canister_id,
+            "greet".to_string(),
+            vec![],
+            PrincipalId::new_anonymous(),
+            config,
+        )
+        .unwrap();
+        assert_eq!(reply, b"hello");
+
+        // No directories were created under the root.
+        let entries: Vec<_> = std::fs::read_dir(root.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(entries, vec![CHECKPOINTS_DIR]);
+    }
+
+    #[test]
+    fn query_fails_on_invalid_argument() {
+        let root = tempfile::TempDir::new().unwrap();
+        let err = do_query(
+            root.path().to_path_buf(),
+            None,
+            SubnetType::Application,
+            canister_test_id(1),
+            "greet".to_string(),
+            "(\"unterminated".to_string(),
+            PrincipalId::new_anonymous(),
+        )
+        .unwrap_err();
+        assert!(
+            err.contains("failed to parse the Candid argument"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn query_fails_without_checkpoints() {
+        let root = tempfile::TempDir::new().unwrap();
+        let err = do_query(
+            root.path().to_path_buf(),
+            None,
+            SubnetType::Application,
+            canister_test_id(1),
+            "greet".to_string(),
+            "(\"world\")".to_string(),
+            PrincipalId::new_anonymous(),
+        )
+        .unwrap_err();
+        assert!(err.contains("no checkpoints found"), "{}", err);
+    }
+}
@@ -2,13 +2,18 @@
 //!
 //! A command-line tool to manage Internet Computer replicated states (decode
 //! persisted state files, diff checkpoints, compute partial state hashes and
-//! checkpoint manifests, import state trees).
+//! checkpoint manifests, import state trees, query canisters in checkpoints).
 
 use clap::Parser;
+use ic_canister_sandbox_backend_lib::{
+    canister_sandbox_main, compiler_sandbox::compiler_sandbox_main,
+    launcher::sandbox_launcher_main, RUN_AS_CANISTER_SANDBOX_FLAG, RUN_AS_COMPILER_SANDBOX_FLAG,
+    RUN_AS_SANDBOX_LAUNCHER_FLAG,
+};
 use ic_registry_routing_table::CanisterIdRange;
 use ic_registry_subnet_type::SubnetType;
 use ic_state_tool::commands;
-use ic_types::{PrincipalId, Time};
+use ic_types::{CanisterId, Height, PrincipalId, Time};
 use std::path::PathBuf;
 
 /// Supported `state_tool` commands and their arguments.
@@ -17,7 +22,16 @@ use std::path::PathBuf;
 enum Opt {
     /// Computes diff of canonical trees between checkpoints.
     #[clap(name = "cdiff")]
-    CDiff { path_a: PathBuf, path_b: PathBuf },
+    CDiff {
+        path_a: PathBuf,
+        path_b: PathBuf,
+        /// Groups the changes by canister instead of printing the raw tree diff.
+        #[clap(long)]
+        per_canister: bool,
+        /// Prints the per-canister changes as JSON.
+        #[clap(long, requires = "per_canister")]
+        json: bool,
+    },
 
     /// Computes partial state hash that is used for certification.
     #[clap(name = "chash")]
@@ -51,6 +65,33 @@ enum Opt {
         path: PathBuf,
     },
 
+    /// Executes a read-only query call against a canister in a checkpoint
+    /// and prints the reply as Candid.
+    #[clap(name = "query")]
+    Query {
+        /// Path to the state layout.
+        #[clap(long, required = true)]
+        root: PathBuf,
+        /// Height of the checkpoint to query. Defaults to the latest checkpoint.
+        #[clap(long)]
+        height: Option<u64>,
+        /// Type of the subnet the state belongs to.
+        #[clap(long, default_value = "application")]
+        subnet_type: SubnetType,
+        /// ID of the canister to query.
+        #[clap(long, required = true)]
+        canister_id: CanisterId,
+        /// Name of the query method.
+        #[clap(long, required = true)]
+        method: String,
+        /// Argument of the query in Candid textual format.
+        #[clap(long, default_value = "()")]
+        arg: String,
+        /// Principal to send the query as.
+        #[clap(long, default_value_t = PrincipalId::new_anonymous())]
+        sender: PrincipalId,
+    },
+
     /// Verifies whether the textual representation
     /// of a manifest matches its root hash.
     #[clap(name = "verify_manifest")]
@@ -150,9 +191,36 @@ enum Opt {
 }
 
 fn main() {
+    // Check if `state-tool` is running in the canister sandbox mode where it
+    // waits for commands from the parent process. This check has to be
+    // performed before the arguments are parsed because the parent process
+    // does not pass all the normally required arguments of `state-tool`.
+    if std::env::args().any(|arg| arg == RUN_AS_CANISTER_SANDBOX_FLAG) {
+        canister_sandbox_main();
+        return;
+    } else if std::env::args().any(|arg| arg == RUN_AS_SANDBOX_LAUNCHER_FLAG) {
+        sandbox_launcher_main();
+        return;
+    } else if std::env::args().any(|arg| arg == RUN_AS_COMPILER_SANDBOX_FLAG) {
+        compiler_sandbox_main();
+        return;
+    }
+
     let opt = Parser::parse();
     let result = match opt {
-        Opt::CDiff { path_a, path_b } => commands::cdiff::do_diff(path_a, path_b),
+        Opt::CDiff {
+            path_a,
+            path_b,
+            per_canister,
+            json,
+        } => {
+            let mode = match (per_canister, json) {
+                (false, _) => commands::cdiff::DiffMode::Tree,
+                (true, false) => commands::cdiff::DiffMode::PerCanister,
+                (true, true) => commands::cdiff::DiffMode::PerCanisterJson,
+            };
+            commands::cdiff::do_diff(path_a, path_b, mode)
+        }
         Opt::CHash { path } => commands::chash::do_hash(path),
         Opt::ImportState {
             state,
@@ -160,6 +228,23 @@ fn main() {
             height,
         } => commands::import_state::do_import(state, config, height),
         Opt::Manifest { path } => commands::manifest::do_compute_manifest(path),
+        Opt::Query {
+            root,
+            height,
+            subnet_type,
+            canister_id,
+            method,
+            arg,
+            sender,
+        } => commands::query::do_query(
+            root,
+            height.map(Height::new),
+            subnet_type,
+            canister_id,
+            method,
+            arg,
+            sender,
+        ),
         Opt::VerifyManifest { file } => commands::verify_manifest::do_verify_manifest(&file),
         Opt::ListStates { config } => commands::list::do_list(config),
         Opt::Decode { file } => commands::decode::do_decode(file),
@@ -11,6 +11,9 @@ use ic_interfaces::execution_environment::{
     TrapCode::{self, CyclesAmountTooBigFor64Bit},
 };
 use ic_logger::{error, ReplicaLogger};
+use ic_management_canister_types::{
+    EcdsaCurve, EcdsaKeyId, MasterPublicKeyId, SchnorrAlgorithm, SchnorrKeyId,
+};
 use ic_registry_subnet_type::SubnetType;
 use ic_replicated_state::{
     canister_state::WASM_PAGE_SIZE_IN_BYTES, memory_required_to_push_request, Memory, NumWasmPages,
@@ -31,6 +34,7 @@ use sandbox_safe_system_state::{CanisterStatusView, SandboxSafeSystemState, Syst
 use serde::{Deserialize, Serialize};
 use stable_memory::StableMemory;
 use std::{
+    collections::BTreeMap,
     convert::{From, TryFrom},
     rc::Rc,
 };
@@ -43,7 +47,7 @@ mod stable_memory;
 
 pub const MULTIPLIER_MAX_SIZE_LOCAL_SUBNET: u64 = 5;
 const MAX_NON_REPLICATED_QUERY_REPLY_SIZE: NumBytes = NumBytes::new(3 << 20);
-const CERTIFIED_DATA_MAX_LENGTH: usize = 32;
+pub const CERTIFIED_DATA_MAX_LENGTH: usize = 32;
 
 // Enables tracing of system calls for local debugging.
 const TRACE_SYSCALLS: bool = false;
@@ -58,6 +62,11 @@ const MAX_32_BIT_STABLE_MEMORY_IN_PAGES: u64 = 64 * 1024; // 4GiB
 /// best-effort responses represented in seconds.
 pub const MAX_CALL_TIMEOUT_SECONDS: u32 = 300;
 
+/// Return codes of `ic0.cost_sign_with_ecdsa` and `ic0.cost_sign_with_schnorr`.
+const COST_SIGN_SUCCESS: u32 = 0;
+const COST_SIGN_INVALID_CURVE_OR_ALGORITHM: u32 = 1;
+const COST_SIGN_INVALID_KEY_NAME: u32 = 2;
+
 // This macro is used in system calls for tracing.
 macro_rules! trace_syscall {
     ($self:ident, $name:ident, $result:expr $( , $args:expr )*) => {{
@@ -1392,6 +1401,103 @@ impl SystemApiImpl {
         }
     }
 
+    /// Shared implementation of `ic0.cost_sign_with_ecdsa` and
+    /// `ic0.cost_sign_with_schnorr`. The `key_id` constructor is `None` if
+    /// the canister passed an unknown curve or algorithm.
+    fn ic0_cost_sign_with_threshold_key_helper(
+        &self,
+        method_name: &str,
+        src: usize,
+        size: usize,
+        key_id: Option<impl FnOnce(String) -> MasterPublicKeyId>,
+        dst: usize,
+        heap: &mut [u8],
+    ) -> HypervisorResult<u32> {
+        let key_name = valid_subslice(method_name, src, size, heap)?.to_vec();
+        let Some(key_id) = key_id else {
+            return Ok(COST_SIGN_INVALID_CURVE_OR_ALGORITHM);
+        };
+        let Ok(key_name) = String::from_utf8(key_name) else {
+            return Ok(COST_SIGN_INVALID_KEY_NAME);
+        };
+        match self
+            .sandbox_safe_system_state
+            .cost_sign_with_threshold_key(&key_id(key_name))
+        {
+            Some(cycles) => {
+                copy_cycles_to_heap(cycles, dst, heap, method_name)?;
+                Ok(COST_SIGN_SUCCESS)
+            }
+            None => Ok(COST_SIGN_INVALID_KEY_NAME),
+        }
+    }
+
+    /// Returns the environment variables of the canister. They are not
+    /// available in the `start` function.
+    fn environment_variables_helper(
+        &self,
+        method_name: &str,
+    ) -> HypervisorResult<&BTreeMap<String, String>> {
+        match &self.api_type {
+            ApiType::Start { .. } => Err(self.error_for(method_name)),
+            ApiType::Init { .. }
+            | ApiType::SystemTask { .. }
+            | ApiType::Update { .. }
+            | ApiType::Cleanup { .. }
+            | ApiType::NonReplicatedQuery { .. }
+            | ApiType::ReplicatedQuery { .. }
+            | ApiType::PreUpgrade { .. }
+            | ApiType::ReplyCallback { .. }
+            | ApiType::RejectCallback { .. }
+            | ApiType::InspectMessage { .. } => {
+                Ok(self.sandbox_safe_system_state.environment_variables())
+            }
+        }
+    }
+
+    /// Returns the name of the environment variable at `index`.
+    fn env_var_name_at_index(&self, method_name: &str, index: usize) -> HypervisorResult<&str> {
+        let environment_variables = self.environment_variables_helper(method_name)?;
+        environment_variables
+            .keys()
+            .nth(index)
+            .map(|name| name.as_str())
+            .ok_or_else(|| HypervisorError::UserContractViolation {
+                error: format!(
+                    "{} failed because the index {} is out of bounds. The canister has {} environment variables.",
+                    method_name,
+                    index,
+                    environment_variables.len()
+                ),
+                suggestion: "Use ic0.env_var_count to get the number of environment variables."
+                    .to_string(),
+                doc_link: "".to_string(),
+            })
+    }
+
+    /// Reads the environment variable name given by `name_src`/`name_size`
+    /// from the heap and returns the value of the variable, if it exists.
+    fn env_var_value_by_name(
+        &self,
+        method_name: &str,
+        name_src: usize,
+        name_size: usize,
+        heap: &[u8],
+    ) -> HypervisorResult<Option<&str>> {
+        let environment_variables = self.environment_variables_helper(method_name)?;
+        let name = valid_subslice(method_name, name_src, name_size, heap)?;
+        let name =
+            std::str::from_utf8(name).map_err(|_| HypervisorError::UserContractViolation {
+                error: format!(
+                    "{} failed because the name is not valid UTF-8.",
+                    method_name
+                ),
+                suggestion: "Environment variable names are UTF-8 strings.".to_string(),
+                doc_link: "".to_string(),
+            })?;
+        Ok(environment_variables.get(name).map(|value| value.as_str()))
+    }
+
     fn ic0_msg_cycles_available_helper(&self, method_name: &str) -> HypervisorResult<Cycles> {
         match &self.api_type {
             ApiType::Start { .. }
@@ -3414,6 +3520,248 @@ impl SystemApi for SystemApiImpl {
         trace_syscall!(self, CyclesBurn128, result, amount);
         result
     }
+
+    fn ic0_cost_call(
+        &self,
+        method_name_size: u64,
+        payload_size: u64,
+        dst: usize,
+        heap: &mut [u8],
+    ) -> HypervisorResult<()> {
+        let cycles = self
+            .sandbox_safe_system_state
+            .cost_call(method_name_size, payload_size);
+        let result = copy_cycles_to_heap(cycles, dst, heap, "ic0_cost_call");
+        trace_syscall!(
+            self,
+            CostCall,
+            result,
+            method_name_size,
+            payload_size,
+            cycles
+        );
+        result
+    }
+
+    fn ic0_cost_create_canister(&self, dst: usize, heap: &mut [u8]) -> HypervisorResult<()> {
+        let cycles = self.sandbox_safe_system_state.cost_create_canister();
+        let result = copy_cycles_to_heap(cycles, dst, heap, "ic0_cost_create_canister");
+        trace_syscall!(self, CostCreateCanister, result, cycles);
+        result
+    }
+
+    fn ic0_cost_http_request(
+        &self,
+        request_size: u64,
+        max_res_bytes: u64,
+        dst: usize,
+        heap: &mut [u8],
+    ) -> HypervisorResult<()> {
+        let cycles = self
+            .sandbox_safe_system_state
+            .cost_http_request(request_size, max_res_bytes);
+        let result = copy_cycles_to_heap(cycles, dst, heap, "ic0_cost_http_request");
+        trace_syscall!(
+            self,
+            CostHttpRequest,
+            result,
+            request_size,
+            max_res_bytes,
+            cycles
+        );
+        result
+    }
+
+    fn ic0_cost_sign_with_ecdsa(
+        &self,
+        src: usize,
+        size: usize,
+        curve: u32,
+        dst: usize,
+        heap: &mut [u8],
+    ) -> HypervisorResult<u32> {
+        let curve = match curve {
+            0 => Some(EcdsaCurve::Secp256k1),
+            _ => None,
+        };
+        let result = self.ic0_cost_sign_with_threshold_key_helper(
+            "ic0_cost_sign_with_ecdsa",
+            src,
+            size,
+            curve.map(|curve| move |name| MasterPublicKeyId::Ecdsa(EcdsaKeyId { curve, name })),
+            dst,
+            heap,
+        );
+        trace_syscall!(self, CostSignWithEcdsa, result, curve);
+        result
+    }
+
+    fn ic0_cost_sign_with_schnorr(
+        &self,
+        src: usize,
+        size: usize,
+        algorithm: u32,
+        dst: usize,
+        heap: &mut [u8],
+    ) -> HypervisorResult<u32> {
+        let algorithm = match algorithm {
+            0 => Some(SchnorrAlgorithm::Bip340Secp256k1),
+            1 => Some(SchnorrAlgorithm::Ed25519),
+            _ => None,
+        };
+        let result = self.ic0_cost_sign_with_threshold_key_helper(
+            "ic0_cost_sign_with_schnorr",
+            src,
+            size,
+            algorithm.map(|algorithm| {
+                move |name| MasterPublicKeyId::Schnorr(SchnorrKeyId { algorithm, name })
+            }),
+            dst,
+            heap,
+        );
+        trace_syscall!(self, CostSignWithSchnorr, result, algorithm);
+        result
+    }
+
+    fn ic0_env_var_count(&self) -> HypervisorResult<usize> {
+        let result = self
+            .environment_variables_helper("ic0_env_var_count")
+            .map(|environment_variables| environment_variables.len());
+        trace_syscall!(self, EnvVarCount, result);
+        result
+    }
+
+    fn ic0_env_var_name_size(&self, index: usize) -> HypervisorResult<usize> {
+        let result = self
+            .env_var_name_at_index("ic0_env_var_name_size", index)
+            .map(|name| name.len());
+        trace_syscall!(self, EnvVarNameSize, result, index);
+        result
+    }
+
+    fn ic0_env_var_name_copy(
+        &self,
+        index: usize,
+        dst: usize,
+        offset: usize,
+        size: usize,
+        heap: &mut [u8],
+    ) -> HypervisorResult<()> {
+        let result = match self.env_var_name_at_index("ic0_env_var_name_copy", index) {
+            Ok(name) => {
+                valid_subslice("ic0.env_var_name_copy heap", dst, size, heap)?;
+                let name_subslice =
+                    valid_subslice("ic0.env_var_name_copy name", offset, size, name.as_bytes())?;
+                deterministic_copy_from_slice(&mut heap[dst..dst + size], name_subslice);
+                Ok(())
+            }
+            Err(err) => Err(err),
+        };
+        trace_syscall!(
+            self,
+            EnvVarNameCopy,
+            result,
+            index,
+            dst,
+            offset,
+            size,
+            summarize(heap, dst, size)
+        );
+        result
+    }
+
+    fn ic0_env_var_name_exists(
+        &self,
+        name_src: usize,
+        name_size: usize,
+        heap: &[u8],
+    ) -> HypervisorResult<i32> {
+        let result = self
+            .env_var_value_by_name("ic0_env_var_name_exists", name_src, name_size, heap)
+            .map(|value| value.is_some() as i32);
+        trace_syscall!(
+            self,
+            EnvVarNameExists,
+            result,
+            summarize(heap, name_src, name_size)
+        );
+        result
+    }
+
+    fn ic0_env_var_value_size(
+        &self,
+        name_src: usize,
+        name_size: usize,
+        heap: &[u8],
+    ) -> HypervisorResult<usize> {
+        let method_name = "ic0_env_var_value_size";
+        let result = self
+            .env_var_value_by_name(method_name, name_src, name_size, heap)
+            .and_then(|value| {
+                value
+                    .map(|value| value.len())
+                    .ok_or_else(|| env_var_not_found_error(method_name))
+            });
+        trace_syscall!(
+            self,
+            EnvVarValueSize,
+            result,
+            summarize(heap, name_src, name_size)
+        );
+        result
+    }
+
+    fn ic0_env_var_value_copy(
+        &self,
+        name_src: usize,
+        name_size: usize,
+        dst: usize,
+        offset: usize,
+        size: usize,
+        heap: &mut [u8],
+    ) -> HypervisorResult<()> {
+        let method_name = "ic0_env_var_value_copy";
+        let result = match self
+            .env_var_value_by_name(method_name, name_src, name_size, heap)
+            .and_then(|value| value.ok_or_else(|| env_var_not_found_error(method_name)))
+        {
+            Ok(value) => {
+                valid_subslice("ic0.env_var_value_copy heap", dst, size, heap)?;
+                let value_subslice = valid_subslice(
+                    "ic0.env_var_value_copy value",
+                    offset,
+                    size,
+                    value.as_bytes(),
+                )?;
+                deterministic_copy_from_slice(&mut heap[dst..dst + size], value_subslice);
+                Ok(())
+            }
+            Err(err) => Err(err),
+        };
+        trace_syscall!(
+            self,
+            EnvVarValueCopy,
+            result,
+            name_src,
+            name_size,
+            dst,
+            offset,
+            size,
+            summarize(heap, dst, size)
+        );
+        result
+    }
+}
+
+fn env_var_not_found_error(method_name: &str) -> HypervisorError {
+    HypervisorError::UserContractViolation {
+        error: format!(
+            "{} failed because no environment variable with the given name exists.",
+            method_name
+        ),
+        suggestion: "Use ic0.env_var_name_exists to check whether a variable is set.".to_string(),
+        doc_link: "".to_string(),
+    }
 }
 
 /// The default implementation of the `OutOfInstructionHandler` trait.
@@ -11,7 +11,7 @@ use ic_limits::{LOG_CANISTER_OPERATION_CYCLES_THRESHOLD, SMALL_APP_SUBNET_MAX_SI
 use ic_logger::{info, ReplicaLogger};
 use ic_management_canister_types::{
     CanisterStatusType, CreateCanisterArgs, InstallChunkedCodeArgs, InstallCodeArgsV2,
-    LoadCanisterSnapshotArgs, Method as Ic00Method, Payload,
+    LoadCanisterSnapshotArgs, MasterPublicKeyId, Method as Ic00Method, Payload,
     ProvisionalCreateCanisterWithCyclesArgs, UninstallCodeArgs, UpdateSettingsArgs, IC_00,
 };
 use ic_nns_constants::CYCLES_MINTING_CANISTER_ID;
@@ -261,7 +261,11 @@ impl SystemStateChanges {
             | Ok(Ic00Method::ClearChunkStore)
             | Ok(Ic00Method::TakeCanisterSnapshot)
             | Ok(Ic00Method::ListCanisterSnapshots)
-            | Ok(Ic00Method::DeleteCanisterSnapshot) => Ok(None),
+            | Ok(Ic00Method::DeleteCanisterSnapshot)
+            | Ok(Ic00Method::ReadCanisterSnapshotMetadata)
+            | Ok(Ic00Method::ReadCanisterSnapshotData)
+            | Ok(Ic00Method::UploadCanisterSnapshotMetadata)
+            | Ok(Ic00Method::UploadCanisterSnapshotData) => Ok(None),
             Err(_) => Err(UserError::new(
                 ErrorCode::CanisterMethodNotFound,
                 format!("Management canister has no method '{}'", msg.method_name),
@@ -555,6 +559,9 @@ pub struct SandboxSafeSystemState {
     pub(super) status: CanisterStatusView,
     pub(super) subnet_type: SubnetType,
     pub(super) subnet_size: usize,
+    /// Size of the subnet that signs with each threshold key, as used for
+    /// scaling the signature fees.
+    threshold_key_subnet_sizes: BTreeMap<MasterPublicKeyId, usize>,
     dirty_page_overhead: NumInstructions,
     freeze_threshold: NumSeconds,
     memory_allocation: MemoryAllocation,
@@ -576,6 +583,7 @@ pub struct SandboxSafeSystemState {
     global_timer: CanisterTimer,
     canister_version: u64,
     controllers: BTreeSet<PrincipalId>,
+    environment_variables: BTreeMap<String, String>,
     pub(super) request_metadata: RequestMetadata,
     caller: Option<PrincipalId>,
 }
@@ -603,10 +611,12 @@ impl SandboxSafeSystemState {
         ic00_available_request_slots: usize,
         ic00_aliases: BTreeSet<CanisterId>,
         subnet_size: usize,
+        threshold_key_subnet_sizes: BTreeMap<MasterPublicKeyId, usize>,
         dirty_page_overhead: NumInstructions,
         global_timer: CanisterTimer,
         canister_version: u64,
         controllers: BTreeSet<PrincipalId>,
+        environment_variables: BTreeMap<String, String>,
         request_metadata: RequestMetadata,
         caller: Option<PrincipalId>,
         next_canister_log_record_idx: u64,
@@ -616,6 +626,7 @@ impl SandboxSafeSystemState {
             status,
             subnet_type: cycles_account_manager.subnet_type(),
             subnet_size,
+            threshold_key_subnet_sizes,
             dirty_page_overhead,
             freeze_threshold,
             memory_allocation,
@@ -641,6 +652,7 @@ impl SandboxSafeSystemState {
             global_timer,
             canister_version,
             controllers,
+            environment_variables,
             request_metadata,
             caller,
         }
@@ -698,6 +710,16 @@ impl SandboxSafeSystemState {
         let subnet_size = network_topology
             .get_subnet_size(&cycles_account_manager.get_subnet_id())
             .unwrap_or(SMALL_APP_SUBNET_MAX_SIZE);
+        // Signing requests are routed to the first subnet enabled to sign
+        // with the key, so its size determines the signature fee.
+        let threshold_key_subnet_sizes = network_topology
+            .idkg_signing_subnets
+            .iter()
+            .filter_map(|(key_id, subnets)| {
+                let subnet_size = network_topology.get_subnet_size(subnets.first()?)?;
+                Some((key_id.clone(), subnet_size))
+            })
+            .collect();
 
         Self::new_internal(
             system_state.canister_id,
@@ -720,10 +742,12 @@ impl SandboxSafeSystemState {
             ic00_available_request_slots,
             ic00_aliases,
             subnet_size,
+            threshold_key_subnet_sizes,
             dirty_page_overhead,
             system_state.global_timer,
             system_state.canister_version,
             system_state.controllers.clone(),
+            system_state.environment_variables.clone(),
             request_metadata,
             caller,
             system_state.canister_log.next_idx(),
@@ -742,6 +766,10 @@ impl SandboxSafeSystemState {
         self.canister_version
     }
 
+    pub fn environment_variables(&self) -> &BTreeMap<String, String> {
+        &self.environment_variables
+    }
+
     pub fn set_global_timer(&mut self, timer: CanisterTimer) {
         // Update both sandbox global timer and the changes.
         self.system_state_changes.new_global_timer = Some(timer);
@@ -873,6 +901,48 @@ impl SandboxSafeSystemState {
         burned_cycles
     }
 
+    /// Returns the cycles withdrawn when performing a call with the given
+    /// method name and payload sizes, excluding any cycles attached to it.
+    pub(super) fn cost_call(&self, method_name_size: u64, payload_size: u64) -> Cycles {
+        self.cycles_account_manager.xnet_call_total_fee(
+            NumBytes::from(method_name_size.saturating_add(payload_size)),
+            self.subnet_size,
+        )
+    }
+
+    /// Returns the fee for creating a canister on this subnet.
+    pub(super) fn cost_create_canister(&self) -> Cycles {
+        self.cycles_account_manager
+            .canister_creation_fee(self.subnet_size)
+    }
+
+    /// Returns the fee for an HTTPS outcall with the given request size and
+    /// response size limit.
+    pub(super) fn cost_http_request(&self, request_size: u64, max_res_bytes: u64) -> Cycles {
+        self.cycles_account_manager.http_request_fee(
+            NumBytes::from(request_size),
+            Some(NumBytes::from(max_res_bytes)),
+            self.subnet_size,
+        )
+    }
+
+    /// Returns the fee for signing with the given threshold key or `None` if
+    /// no subnet is enabled to sign with the key.
+    pub(super) fn cost_sign_with_threshold_key(
+        &self,
+        key_id: &MasterPublicKeyId,
+    ) -> Option<Cycles> {
+        let subnet_size = *self.threshold_key_subnet_sizes.get(key_id)?;
+        Some(match key_id {
+            MasterPublicKeyId::Ecdsa(_) => {
+                self.cycles_account_manager.ecdsa_signature_fee(subnet_size)
+            }
+            MasterPublicKeyId::Schnorr(_) => self
+                .cycles_account_manager
+                .schnorr_signature_fee(subnet_size),
+        })
+    }
+
     pub(super) fn refund_cycles(&mut self, cycles: Cycles) {
         let mut new_balance = self.cycles_balance();
         new_balance += cycles;
@@ -1432,10 +1502,12 @@ mod tests {
             0,
             BTreeSet::new(),
             SMALL_APP_SUBNET_MAX_SIZE,
+            BTreeMap::new(),
             SchedulerConfig::application_subnet().dirty_page_overhead,
             CanisterTimer::Inactive,
             0,
             BTreeSet::new(),
+            BTreeMap::new(),
             RequestMetadata::new(0, Time::from_nanos_since_unix_epoch(0)),
             None,
             0,
@@ -1479,10 +1551,12 @@ mod tests {
             0,
             BTreeSet::new(),
             SMALL_APP_SUBNET_MAX_SIZE,
+            BTreeMap::new(),
             SchedulerConfig::application_subnet().dirty_page_overhead,
             CanisterTimer::Inactive,
             0,
             BTreeSet::new(),
+            BTreeMap::new(),
             RequestMetadata::new(0, Time::from_nanos_since_unix_epoch(0)),
             None,
             0,
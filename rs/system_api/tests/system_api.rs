@@ -1,3 +1,4 @@
+use assert_matches::assert_matches;
 use ic_base_types::{NumBytes, NumSeconds, PrincipalIdBlobParseError};
 use ic_config::{
     embedders::Config as EmbeddersConfig, flag_status::FlagStatus, subnet_config::SchedulerConfig,
@@ -11,11 +12,14 @@ use ic_interfaces::execution_environment::{
 };
 use ic_limits::SMALL_APP_SUBNET_MAX_SIZE;
 use ic_logger::replica_logger::no_op_logger;
+use ic_management_canister_types::{
+    EcdsaCurve, EcdsaKeyId, MasterPublicKeyId, SchnorrAlgorithm, SchnorrKeyId,
+};
 use ic_registry_subnet_type::SubnetType;
 use ic_replicated_state::NumWasmPages;
 use ic_replicated_state::{
     canister_state::system_state::OnLowWasmMemoryHookStatus, testing::CanisterQueuesTesting,
-    CallOrigin, Memory, NetworkTopology, SystemState,
+    CallOrigin, Memory, NetworkTopology, SubnetTopology, SystemState,
 };
 use ic_system_api::{
     sandbox_safe_system_state::SandboxSafeSystemState, ApiType, DefaultOutOfInstructionsHandler,
@@ -24,7 +28,7 @@ use ic_system_api::{
 use ic_test_utilities::cycles_account_manager::CyclesAccountManagerBuilder;
 use ic_test_utilities_state::SystemStateBuilder;
 use ic_test_utilities_types::{
-    ids::{call_context_test_id, canister_test_id, subnet_test_id, user_test_id},
+    ids::{call_context_test_id, canister_test_id, node_test_id, subnet_test_id, user_test_id},
     messages::RequestBuilder,
 };
 use ic_types::{
@@ -258,12 +262,23 @@ fn is_supported(api_type: SystemApiCallId, context: &str) -> bool {
         SystemApiCallId::MsgCyclesAccept => vec!["U", "Rt", "Ry"],
         SystemApiCallId::MsgCyclesAccept128 => vec!["U", "Rt", "Ry"],
         SystemApiCallId::CyclesBurn128 => vec!["I", "G", "U", "Ry", "Rt", "C", "T"],
+        SystemApiCallId::CostCall => vec!["*", "s"],
+        SystemApiCallId::CostCreateCanister => vec!["*", "s"],
+        SystemApiCallId::CostHttpRequest => vec!["*", "s"],
+        SystemApiCallId::CostSignWithEcdsa => vec!["*", "s"],
+        SystemApiCallId::CostSignWithSchnorr => vec!["*", "s"],
         SystemApiCallId::CanisterSelfSize => vec!["*"],
         SystemApiCallId::CanisterSelfCopy => vec!["*"],
         SystemApiCallId::CanisterCycleBalance => vec!["*"],
         SystemApiCallId::CanisterCycleBalance128 => vec!["*"],
         SystemApiCallId::CanisterStatus => vec!["*"],
         SystemApiCallId::CanisterVersion => vec!["*"],
+        SystemApiCallId::EnvVarCount => vec!["*"],
+        SystemApiCallId::EnvVarNameSize => vec!["*"],
+        SystemApiCallId::EnvVarNameCopy => vec!["*"],
+        SystemApiCallId::EnvVarNameExists => vec!["*"],
+        SystemApiCallId::EnvVarValueSize => vec!["*"],
+        SystemApiCallId::EnvVarValueCopy => vec!["*"],
         SystemApiCallId::MsgMethodNameSize => vec!["F"],
         SystemApiCallId::MsgMethodNameCopy => vec!["F"],
         SystemApiCallId::AcceptMessage => vec!["F"],
@@ -590,6 +605,66 @@ fn api_availability_test(
                 context,
             );
         }
+        SystemApiCallId::EnvVarCount => {
+            assert_api_availability(
+                |api| api.ic0_env_var_count(),
+                api_type,
+                &system_state_with_environment_variables(),
+                cycles_account_manager,
+                api_type_enum,
+                context,
+            );
+        }
+        SystemApiCallId::EnvVarNameSize => {
+            assert_api_availability(
+                |api| api.ic0_env_var_name_size(0),
+                api_type,
+                &system_state_with_environment_variables(),
+                cycles_account_manager,
+                api_type_enum,
+                context,
+            );
+        }
+        SystemApiCallId::EnvVarNameCopy => {
+            assert_api_availability(
+                |api| api.ic0_env_var_name_copy(0, 0, 0, 1, &mut [42; 128]),
+                api_type,
+                &system_state_with_environment_variables(),
+                cycles_account_manager,
+                api_type_enum,
+                context,
+            );
+        }
+        SystemApiCallId::EnvVarNameExists => {
+            assert_api_availability(
+                |api| api.ic0_env_var_name_exists(0, 1, b"A"),
+                api_type,
+                &system_state_with_environment_variables(),
+                cycles_account_manager,
+                api_type_enum,
+                context,
+            );
+        }
+        SystemApiCallId::EnvVarValueSize => {
+            assert_api_availability(
+                |api| api.ic0_env_var_value_size(0, 1, b"A"),
+                api_type,
+                &system_state_with_environment_variables(),
+                cycles_account_manager,
+                api_type_enum,
+                context,
+            );
+        }
+        SystemApiCallId::EnvVarValueCopy => {
+            assert_api_availability(
+                |api| api.ic0_env_var_value_copy(0, 1, 1, 0, 1, &mut [b'A'; 128]),
+                api_type,
+                &system_state_with_environment_variables(),
+                cycles_account_manager,
+                api_type_enum,
+                context,
+            );
+        }
         SystemApiCallId::GlobalTimerSet => {
             assert_api_availability(
                 |mut api| api.ic0_global_timer_set(time::UNIX_EPOCH),
@@ -775,6 +850,56 @@ fn api_availability_test(
                 context,
             );
         }
+        SystemApiCallId::CostCall => {
+            assert_api_availability(
+                |api| api.ic0_cost_call(0, 0, 0, &mut [42; 128]),
+                api_type,
+                &system_state,
+                cycles_account_manager,
+                api_type_enum,
+                context,
+            );
+        }
+        SystemApiCallId::CostCreateCanister => {
+            assert_api_availability(
+                |api| api.ic0_cost_create_canister(0, &mut [42; 128]),
+                api_type,
+                &system_state,
+                cycles_account_manager,
+                api_type_enum,
+                context,
+            );
+        }
+        SystemApiCallId::CostHttpRequest => {
+            assert_api_availability(
+                |api| api.ic0_cost_http_request(0, 0, 0, &mut [42; 128]),
+                api_type,
+                &system_state,
+                cycles_account_manager,
+                api_type_enum,
+                context,
+            );
+        }
+        SystemApiCallId::CostSignWithEcdsa => {
+            assert_api_availability(
+                |api| api.ic0_cost_sign_with_ecdsa(0, 0, 0, 0, &mut [42; 128]),
+                api_type,
+                &system_state,
+                cycles_account_manager,
+                api_type_enum,
+                context,
+            );
+        }
+        SystemApiCallId::CostSignWithSchnorr => {
+            assert_api_availability(
+                |api| api.ic0_cost_sign_with_schnorr(0, 0, 0, 0, &mut [42; 128]),
+                api_type,
+                &system_state,
+                cycles_account_manager,
+                api_type_enum,
+                context,
+            );
+        }
         // stable API is tested separately
         SystemApiCallId::StableGrow
         | SystemApiCallId::StableRead
@@ -1943,6 +2068,246 @@ fn test_ic0_cycles_burn() {
     assert_eq!(Cycles::new(0), Cycles::from(&heap));
 }
 
+#[test]
+fn test_ic0_cost_call_create_canister_and_http_request() {
+    let cycles_account_manager = CyclesAccountManagerBuilder::new().build();
+    let api = get_system_api(
+        ApiTypeBuilder::build_update_api(),
+        &SystemStateBuilder::default().build(),
+        cycles_account_manager,
+    );
+    // The default network topology does not contain the own subnet.
+    let subnet_size = SMALL_APP_SUBNET_MAX_SIZE;
+
+    let mut heap = vec![0; 16];
+    api.ic0_cost_call(10, 100, 0, &mut heap).unwrap();
+    assert_eq!(
+        Cycles::from(&heap),
+        cycles_account_manager.xnet_call_total_fee(NumBytes::from(110), subnet_size)
+    );
+
+    let mut heap = vec![0; 16];
+    api.ic0_cost_create_canister(0, &mut heap).unwrap();
+    assert_eq!(
+        Cycles::from(&heap),
+        cycles_account_manager.canister_creation_fee(subnet_size)
+    );
+
+    let mut heap = vec![0; 16];
+    api.ic0_cost_http_request(100, 2_000, 0, &mut heap).unwrap();
+    assert_eq!(
+        Cycles::from(&heap),
+        cycles_account_manager.http_request_fee(
+            NumBytes::from(100),
+            Some(NumBytes::from(2_000)),
+            subnet_size
+        )
+    );
+
+    // The result does not fit into the heap.
+    assert!(api.ic0_cost_create_canister(8, &mut heap).is_err());
+}
+
+#[test]
+fn test_ic0_cost_sign_with_threshold_keys() {
+    let ecdsa_key = "ecdsa_key";
+    let schnorr_key = "schnorr_key";
+    let signing_subnet_id = subnet_test_id(2);
+    let signing_subnet_size = 34;
+    let network_topology = NetworkTopology {
+        idkg_signing_subnets: btreemap! {
+            MasterPublicKeyId::Ecdsa(EcdsaKeyId {
+                curve: EcdsaCurve::Secp256k1,
+                name: ecdsa_key.to_string(),
+            }) => vec![signing_subnet_id],
+            MasterPublicKeyId::Schnorr(SchnorrKeyId {
+                algorithm: SchnorrAlgorithm::Ed25519,
+                name: schnorr_key.to_string(),
+            }) => vec![signing_subnet_id],
+        },
+        subnets: btreemap! {
+            signing_subnet_id => SubnetTopology {
+                nodes: (0..signing_subnet_size).map(node_test_id).collect(),
+                ..SubnetTopology::default()
+            },
+        },
+        ..NetworkTopology::default()
+    };
+    let cycles_account_manager = CyclesAccountManagerBuilder::new()
+        .with_ecdsa_signature_fee(Cycles::new(1_000_000))
+        .with_schnorr_signature_fee(Cycles::new(2_000_000))
+        .build();
+    let api_type = ApiTypeBuilder::build_update_api();
+    let execution_mode = api_type.execution_mode();
+    let sandbox_safe_system_state = SandboxSafeSystemState::new(
+        &SystemStateBuilder::default().build(),
+        cycles_account_manager,
+        &network_topology,
+        SchedulerConfig::application_subnet().dirty_page_overhead,
+        execution_parameters(execution_mode.clone()).compute_allocation,
+        RequestMetadata::new(0, UNIX_EPOCH),
+        api_type.caller(),
+        api_type.call_context_id(),
+    );
+    let api = SystemApiImpl::new(
+        api_type,
+        sandbox_safe_system_state,
+        CANISTER_CURRENT_MEMORY_USAGE,
+        CANISTER_CURRENT_MESSAGE_MEMORY_USAGE,
+        execution_parameters(execution_mode),
+        SubnetAvailableMemory::new(i64::MAX / 2, i64::MAX / 2, i64::MAX / 2),
+        EmbeddersConfig::default()
+            .feature_flags
+            .wasm_native_stable_memory,
+        EmbeddersConfig::default().feature_flags.canister_backtrace,
+        EmbeddersConfig::default().max_sum_exported_function_name_lengths,
+        Memory::new_for_testing(),
+        NumWasmPages::from(0),
+        Rc::new(DefaultOutOfInstructionsHandler::default()),
+        no_op_logger(),
+    );
+
+    // The key name is stored after the 16 bytes reserved for the result.
+    let heap_with_key = |key_name: &str| [vec![0; 16], key_name.as_bytes().to_vec()].concat();
+
+    let mut heap = heap_with_key(ecdsa_key);
+    assert_eq!(
+        api.ic0_cost_sign_with_ecdsa(16, ecdsa_key.len(), 0, 0, &mut heap),
+        Ok(0)
+    );
+    assert_eq!(
+        Cycles::from(&heap[..16].to_vec()),
+        cycles_account_manager.ecdsa_signature_fee(signing_subnet_size as usize)
+    );
+
+    let mut heap = heap_with_key(schnorr_key);
+    assert_eq!(
+        api.ic0_cost_sign_with_schnorr(16, schnorr_key.len(), 1, 0, &mut heap),
+        Ok(0)
+    );
+    assert_eq!(
+        Cycles::from(&heap[..16].to_vec()),
+        cycles_account_manager.schnorr_signature_fee(signing_subnet_size as usize)
+    );
+
+    // Unknown curve or algorithm.
+    let mut heap = heap_with_key(ecdsa_key);
+    assert_eq!(
+        api.ic0_cost_sign_with_ecdsa(16, ecdsa_key.len(), 1, 0, &mut heap),
+        Ok(1)
+    );
+    let mut heap = heap_with_key(schnorr_key);
+    assert_eq!(
+        api.ic0_cost_sign_with_schnorr(16, schnorr_key.len(), 2, 0, &mut heap),
+        Ok(1)
+    );
+
+    // No subnet signs with the key.
+    let mut heap = heap_with_key(schnorr_key);
+    assert_eq!(
+        api.ic0_cost_sign_with_schnorr(16, schnorr_key.len(), 0, 0, &mut heap),
+        Ok(2)
+    );
+    let mut heap = heap_with_key(schnorr_key);
+    assert_eq!(
+        api.ic0_cost_sign_with_ecdsa(16, schnorr_key.len(), 0, 0, &mut heap),
+        Ok(2)
+    );
+    assert_eq!(heap[..16], [0; 16]);
+
+    // The key name is out of bounds.
+    let mut heap = heap_with_key(ecdsa_key);
+    assert!(api
+        .ic0_cost_sign_with_ecdsa(16, ecdsa_key.len() + 1, 0, 0, &mut heap)
+        .is_err());
+}
+
+fn system_state_with_environment_variables() -> SystemState {
+    let mut system_state = get_system_state();
+    system_state.environment_variables = btreemap! {
+        "A".to_string() => "1".to_string(),
+    };
+    system_state
+}
+
+#[test]
+fn test_ic0_env_var_apis() {
+    let system_state = SystemStateBuilder::default()
+        .environment_variables(btreemap! {
+            "NETWORK".to_string() => "staging".to_string(),
+            "API_URL".to_string() => "https://example.com".to_string(),
+        })
+        .build();
+    let api = get_system_api(
+        ApiTypeBuilder::build_update_api(),
+        &system_state,
+        CyclesAccountManagerBuilder::new().build(),
+    );
+
+    assert_eq!(api.ic0_env_var_count().unwrap(), 2);
+
+    // Variables are ordered by name.
+    assert_eq!(api.ic0_env_var_name_size(0).unwrap(), "API_URL".len());
+    assert_eq!(api.ic0_env_var_name_size(1).unwrap(), "NETWORK".len());
+    let mut heap = vec![0; 7];
+    api.ic0_env_var_name_copy(1, 0, 0, 7, &mut heap).unwrap();
+    assert_eq!(heap, b"NETWORK");
+    let mut heap = vec![0; 3];
+    api.ic0_env_var_name_copy(0, 0, 4, 3, &mut heap).unwrap();
+    assert_eq!(heap, b"URL");
+
+    let mut heap = b"NETWORK".to_vec();
+    assert_eq!(api.ic0_env_var_name_exists(0, 7, &heap).unwrap(), 1);
+    assert_eq!(api.ic0_env_var_name_exists(0, 3, &heap).unwrap(), 0);
+    assert_eq!(
+        api.ic0_env_var_value_size(0, 7, &heap).unwrap(),
+        "staging".len()
+    );
+
+    heap.extend_from_slice(&[0; 7]);
+    api.ic0_env_var_value_copy(0, 7, 7, 0, 7, &mut heap)
+        .unwrap();
+    assert_eq!(&heap[7..], b"staging");
+}
+
+#[test]
+fn test_ic0_env_var_apis_trap_on_invalid_input() {
+    let api = get_system_api(
+        ApiTypeBuilder::build_update_api(),
+        &system_state_with_environment_variables(),
+        CyclesAccountManagerBuilder::new().build(),
+    );
+
+    // Out of bounds index.
+    assert_matches!(
+        api.ic0_env_var_name_size(1),
+        Err(HypervisorError::UserContractViolation { .. })
+    );
+    assert_matches!(
+        api.ic0_env_var_name_copy(1, 0, 0, 1, &mut [0; 1]),
+        Err(HypervisorError::UserContractViolation { .. })
+    );
+    // Name that does not exist.
+    assert_matches!(
+        api.ic0_env_var_value_size(0, 1, b"B"),
+        Err(HypervisorError::UserContractViolation { .. })
+    );
+    assert_matches!(
+        api.ic0_env_var_value_copy(0, 1, 1, 0, 1, &mut [b'B'; 2]),
+        Err(HypervisorError::UserContractViolation { .. })
+    );
+    // Name that is not valid UTF-8.
+    assert_matches!(
+        api.ic0_env_var_name_exists(0, 1, &[0xff]),
+        Err(HypervisorError::UserContractViolation { .. })
+    );
+    // Copy past the end of the value.
+    assert_matches!(
+        api.ic0_env_var_value_copy(0, 1, 1, 0, 2, &mut [b'A'; 3]),
+        Err(HypervisorError::ToolchainContractViolation { .. })
+    );
+}
+
 #[test]
 fn test_save_log_message_adds_canister_log_records() {
     let messages: Vec<Vec<_>> = vec![
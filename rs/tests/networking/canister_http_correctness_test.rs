@@ -81,6 +81,8 @@ pub fn test(env: TestEnv) {
                             context: vec![0, 1, 2],
                         }),
                         max_response_bytes: None,
+                        response_range: None,
+                        is_replicated: None,
                     },
                     cycles: 500_000_000_000,
                 },
@@ -108,6 +110,8 @@ pub fn test(env: TestEnv) {
                             context: vec![0, 1, 2],
                         }),
                         max_response_bytes: None,
+                        response_range: None,
+                        is_replicated: None,
                     },
                     cycles: 500_000_000_000,
                 },
@@ -142,6 +146,8 @@ pub fn test(env: TestEnv) {
                             context: vec![0, 1, 2],
                         }),
                         max_response_bytes: None,
+                        response_range: None,
+                        is_replicated: None,
                     },
                     cycles: 500_000_000_000,
                 },
@@ -176,6 +182,8 @@ pub fn test(env: TestEnv) {
                             context: vec![0, 1, 2],
                         }),
                         max_response_bytes: None,
+                        response_range: None,
+                        is_replicated: None,
                     },
                     cycles: 0,
                 },
@@ -198,6 +206,8 @@ pub fn test(env: TestEnv) {
                 context: vec![0, 1, 2],
             }),
             max_response_bytes: None,
+            response_range: None,
+            is_replicated: None,
         };
         test_results.push(
             test_canister_http_property(
@@ -248,6 +258,8 @@ pub fn test(env: TestEnv) {
                 context: vec![0, 1, 2],
             }),
             max_response_bytes: Some(16384),
+            response_range: None,
+            is_replicated: None,
         };
         test_results.push(
             test_canister_http_property(
@@ -303,6 +315,8 @@ pub fn test(env: TestEnv) {
                             context: vec![0, 1, 2],
                         }),
                         max_response_bytes: Some(4 * 1024 * 1024),
+                        response_range: None,
+                        is_replicated: None,
                     },
                     cycles: 0,
                 },
@@ -330,6 +344,8 @@ pub fn test(env: TestEnv) {
                             context: vec![0, 1, 2],
                         }),
                         max_response_bytes: None,
+                        response_range: None,
+                        is_replicated: None,
                     },
                     cycles: 500_000_000_000,
                 },
@@ -357,6 +373,8 @@ pub fn test(env: TestEnv) {
                             context: vec![0, 1, 2],
                         }),
                         max_response_bytes: None,
+                        response_range: None,
+                        is_replicated: None,
                     },
                     cycles: 500_000_000_000,
                 },
@@ -387,6 +405,8 @@ pub fn test(env: TestEnv) {
                             context: vec![0, 1, 2],
                         }),
                         max_response_bytes: None,
+                        response_range: None,
+                        is_replicated: None,
                     },
                     cycles: 500_000_000_000,
                 },
@@ -414,6 +434,8 @@ pub fn test(env: TestEnv) {
                             context: vec![0, 1, 2],
                         }),
                         max_response_bytes: Some(8 * 1024),
+                        response_range: None,
+                        is_replicated: None,
                     },
                     cycles: 500_000_000_000,
                 },
@@ -442,6 +464,8 @@ pub fn test(env: TestEnv) {
                             context: vec![0, 1, 2],
                         }),
                         max_response_bytes: None,
+                        response_range: None,
+                        is_replicated: None,
                     },
                     cycles: 500_000_000_000,
                 },
@@ -469,6 +493,8 @@ pub fn test(env: TestEnv) {
                             context: vec![0, 1, 2],
                         }),
                         max_response_bytes: None,
+                        response_range: None,
+                        is_replicated: None,
                     },
                     cycles: 500_000_000_000,
                 },
@@ -496,6 +522,8 @@ pub fn test(env: TestEnv) {
                             context: vec![0, 1, 2],
                         }),
                         max_response_bytes: None,
+                        response_range: None,
+                        is_replicated: None,
                     },
                     cycles: 500_000_000_000,
                 },
@@ -168,6 +168,8 @@ pub fn test(env: TestEnv) {
                             }),
                             method: HttpMethod::GET,
                             max_response_bytes: None,
+                            response_range: None,
+                            is_replicated: None,
                         },
                         cycles: 500_000_000_000,
                     },
@@ -221,6 +223,8 @@ pub fn test(env: TestEnv) {
                             }),
                             method: HttpMethod::GET,
                             max_response_bytes: None,
+                            response_range: None,
+                            is_replicated: None,
                         },
                         cycles: 500_000_000_000,
                     },
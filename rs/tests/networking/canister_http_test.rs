@@ -94,6 +94,8 @@ async fn test_proxy_canister(proxy_canister: &Canister<'_>, url: String, logger:
                             }),
                             method: HttpMethod::GET,
                             max_response_bytes: None,
+                            response_range: None,
+                            is_replicated: None,
                         },
                         cycles: 500_000_000_000,
                     },
@@ -59,6 +59,17 @@ pub type BoundedHttpHeaders = BoundedVec<
     HttpHeader,
 >;
 
+/// Struct used for encoding/decoding
+/// `record {
+///     offset : nat64;
+///     length : nat64;
+/// }`
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, CandidType, Deserialize, Serialize)]
+pub struct HttpResponseRange {
+    pub offset: u64,
+    pub length: u64,
+}
+
 /// Struct used for encoding/decoding
 /// `(http_request : (record {
 //     url : text;
@@ -70,6 +81,8 @@ pub type BoundedHttpHeaders = BoundedVec<
 //       function : func (record {response : http_response; context : blob}) -> (http_response) query;
 //       context : blob;
 //     };
+//     response_range : opt record { offset : nat64; length : nat64 };
+//     is_replicated : opt bool;
 //   })`
 #[derive(Clone, PartialEq, Debug, CandidType, Deserialize)]
 pub struct CanisterHttpRequestArgs {
@@ -80,6 +93,14 @@ pub struct CanisterHttpRequestArgs {
     pub body: Option<Vec<u8>>,
     pub method: HttpMethod,
     pub transform: Option<TransformContext>,
+    /// If set, only the given byte range of the response body is returned.
+    /// The full body is downloaded to cut out the range, so the request is
+    /// charged for the ranged download limit instead of `max_response_bytes`.
+    pub response_range: Option<HttpResponseRange>,
+    /// If set to `false`, the request is made by a single node and its response
+    /// is returned without being attested by the other nodes of the subnet.
+    /// Defaults to `true`.
+    pub is_replicated: Option<bool>,
 }
 
 impl Payload<'_> for CanisterHttpRequestArgs {}
@@ -114,6 +135,8 @@ fn test_http_headers_max_number() {
             body: None,
             method: HttpMethod::GET,
             transform: None,
+            response_range: None,
+            is_replicated: None,
         };
 
         // Act.
@@ -167,6 +190,8 @@ fn test_http_headers_max_total_size() {
             body: None,
             method: HttpMethod::GET,
             transform: None,
+            response_range: None,
+            is_replicated: None,
         };
 
         // Act.
@@ -214,6 +239,8 @@ fn test_http_headers_max_element_size() {
             body: None,
             method: HttpMethod::GET,
             transform: None,
+            response_range: None,
+            is_replicated: None,
         };
 
         // Act.
@@ -298,6 +325,7 @@ pub enum HttpMethod {
 ///     status: nat;
 ///     headers: vec http_header;
 ///     body: blob;
+///     is_replicated: opt bool;
 /// })`;
 #[derive(Clone, Eq, PartialEq, Hash, Debug, CandidType, Deserialize, Serialize)]
 pub struct CanisterHttpResponsePayload {
@@ -305,6 +333,9 @@ pub struct CanisterHttpResponsePayload {
     pub headers: Vec<HttpHeader>,
     #[serde(with = "serde_bytes")]
     pub body: Vec<u8>,
+    /// Set to `false` in the responses to non-replicated requests, which are
+    /// made by a single node and not attested by the other nodes of the subnet.
+    pub is_replicated: Option<bool>,
 }
 
 impl Payload<'_> for CanisterHttpResponsePayload {}
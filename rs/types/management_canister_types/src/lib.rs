@@ -11,7 +11,7 @@ use candid::{CandidType, Decode, DecoderConfig, Deserialize, Encode};
 pub use data_size::*;
 pub use http::{
     BoundedHttpHeaders, CanisterHttpRequestArgs, CanisterHttpResponsePayload, HttpHeader,
-    HttpMethod, TransformArgs, TransformContext, TransformFunc,
+    HttpMethod, HttpResponseRange, TransformArgs, TransformContext, TransformFunc,
 };
 use ic_base_types::{
     CanisterId, NodeId, NumBytes, PrincipalId, RegistryVersion, SnapshotId, SubnetId,
@@ -116,6 +116,10 @@ pub enum Method {
     LoadCanisterSnapshot,
     ListCanisterSnapshots,
     DeleteCanisterSnapshot,
+    ReadCanisterSnapshotMetadata,
+    ReadCanisterSnapshotData,
+    UploadCanisterSnapshotMetadata,
+    UploadCanisterSnapshotData,
 }
 
 fn candid_error_to_user_error(err: candid::Error) -> UserError {
@@ -868,6 +872,7 @@ impl TryFrom<pb_canister_state_bits::LogVisibilityV2> for LogVisibilityV2 {
 ///     reserved_cycles_limit: nat;
 ///     log_visibility: log_visibility;
 ///     wasm_memory_limit: nat;
+///     environment_variables: vec environment_variable;
 /// })`
 #[derive(Clone, Eq, PartialEq, Debug, CandidType, Deserialize)]
 pub struct DefiniteCanisterSettingsArgs {
@@ -879,9 +884,11 @@ pub struct DefiniteCanisterSettingsArgs {
     reserved_cycles_limit: candid::Nat,
     log_visibility: LogVisibilityV2,
     wasm_memory_limit: candid::Nat,
+    environment_variables: Vec<EnvironmentVariable>,
 }
 
 impl DefiniteCanisterSettingsArgs {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         controller: PrincipalId,
         controllers: Vec<PrincipalId>,
@@ -891,6 +898,7 @@ impl DefiniteCanisterSettingsArgs {
         reserved_cycles_limit: Option<u128>,
         log_visibility: LogVisibilityV2,
         wasm_memory_limit: Option<u64>,
+        environment_variables: Vec<EnvironmentVariable>,
     ) -> Self {
         let memory_allocation = candid::Nat::from(memory_allocation.unwrap_or(0));
         let reserved_cycles_limit = candid::Nat::from(reserved_cycles_limit.unwrap_or(0));
@@ -904,6 +912,7 @@ impl DefiniteCanisterSettingsArgs {
             reserved_cycles_limit,
             log_visibility,
             wasm_memory_limit,
+            environment_variables,
         }
     }
 
@@ -922,6 +931,10 @@ impl DefiniteCanisterSettingsArgs {
     pub fn wasm_memory_limit(&self) -> candid::Nat {
         self.wasm_memory_limit.clone()
     }
+
+    pub fn environment_variables(&self) -> &[EnvironmentVariable] {
+        &self.environment_variables
+    }
 }
 
 impl Payload<'_> for DefiniteCanisterSettingsArgs {}
@@ -1045,6 +1058,7 @@ impl CanisterStatusResultV2 {
         query_ingress_payload_size: u128,
         query_egress_payload_size: u128,
         wasm_memory_limit: Option<u64>,
+        environment_variables: Vec<EnvironmentVariable>,
     ) -> Self {
         Self {
             status,
@@ -1064,6 +1078,7 @@ impl CanisterStatusResultV2 {
                 reserved_cycles_limit,
                 log_visibility,
                 wasm_memory_limit,
+                environment_variables,
             ),
             freezing_threshold: candid::Nat::from(freezing_threshold),
             idle_cycles_burned_per_day: candid::Nat::from(idle_cycles_burned_per_day),
@@ -1713,6 +1728,7 @@ impl DataSize for PrincipalId {
 ///     log_visibility : opt log_visibility;
 ///     wasm_memory_limit: opt nat;
 ///     wasm_memory_threshold: opt nat;
+///     environment_variables: opt vec environment_variable;
 /// })`
 #[derive(Clone, Eq, PartialEq, Debug, Default, CandidType, Deserialize)]
 pub struct CanisterSettingsArgs {
@@ -1724,6 +1740,7 @@ pub struct CanisterSettingsArgs {
     pub log_visibility: Option<LogVisibilityV2>,
     pub wasm_memory_limit: Option<candid::Nat>,
     pub wasm_memory_threshold: Option<candid::Nat>,
+    pub environment_variables: Option<Vec<EnvironmentVariable>>,
 }
 
 impl Payload<'_> for CanisterSettingsArgs {}
@@ -1741,6 +1758,7 @@ impl CanisterSettingsArgs {
             log_visibility: None,
             wasm_memory_limit: None,
             wasm_memory_threshold: None,
+            environment_variables: None,
         }
     }
 }
@@ -1755,6 +1773,7 @@ pub struct CanisterSettingsArgsBuilder {
     log_visibility: Option<LogVisibilityV2>,
     wasm_memory_limit: Option<candid::Nat>,
     wasm_memory_threshold: Option<candid::Nat>,
+    environment_variables: Option<Vec<EnvironmentVariable>>,
 }
 
 #[allow(dead_code)]
@@ -1773,6 +1792,7 @@ impl CanisterSettingsArgsBuilder {
             log_visibility: self.log_visibility,
             wasm_memory_limit: self.wasm_memory_limit,
             wasm_memory_threshold: self.wasm_memory_threshold,
+            environment_variables: self.environment_variables,
         }
     }
 
@@ -1857,6 +1877,36 @@ impl CanisterSettingsArgsBuilder {
             ..self
         }
     }
+
+    /// Sets the environment variables, replacing all existing ones.
+    pub fn with_environment_variables(
+        self,
+        environment_variables: Vec<EnvironmentVariable>,
+    ) -> Self {
+        Self {
+            environment_variables: Some(environment_variables),
+            ..self
+        }
+    }
+}
+
+/// Struct used for encoding/decoding
+/// `(record {
+///     name: text;
+///     value: text;
+/// })`
+#[derive(
+    Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, CandidType, Deserialize, Serialize,
+)]
+pub struct EnvironmentVariable {
+    pub name: String,
+    pub value: String,
+}
+
+impl EnvironmentVariable {
+    pub fn new(name: String, value: String) -> Self {
+        Self { name, value }
+    }
 }
 
 /// Struct used for encoding/decoding
@@ -2637,11 +2687,19 @@ impl Payload<'_> for NodeMetricsHistoryResponse {}
 /// ```text
 /// record {
 ///     canister_id: principal;
+///     filter: opt canister_log_filter;
+///     start_idx: opt nat64;
+///     max_records: opt nat64;
 /// }
 /// ```
 #[derive(Clone, Debug, Default, CandidType, Deserialize)]
 pub struct FetchCanisterLogsRequest {
     pub canister_id: PrincipalId,
+    pub filter: Option<CanisterLogFilter>,
+    /// Cursor: only records with an index at least `start_idx` are returned.
+    pub start_idx: Option<u64>,
+    /// Maximum number of records to return.
+    pub max_records: Option<u64>,
 }
 
 impl Payload<'_> for FetchCanisterLogsRequest {}
@@ -2650,20 +2708,67 @@ impl FetchCanisterLogsRequest {
     pub fn new(canister_id: CanisterId) -> Self {
         Self {
             canister_id: canister_id.into(),
+            filter: None,
+            start_idx: None,
+            max_records: None,
         }
     }
 
+    pub fn with_filter(mut self, filter: CanisterLogFilter) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    pub fn with_start_idx(mut self, start_idx: u64) -> Self {
+        self.start_idx = Some(start_idx);
+        self
+    }
+
+    pub fn with_max_records(mut self, max_records: u64) -> Self {
+        self.max_records = Some(max_records);
+        self
+    }
+
     pub fn get_canister_id(&self) -> CanisterId {
         CanisterId::unchecked_from_principal(self.canister_id)
     }
 }
 
+/// `CandidType` for `CanisterLogFilter`
+/// ```text
+/// record {
+///     start_timestamp_nanos: opt nat64;
+///     end_timestamp_nanos: opt nat64;
+///     start_idx: opt nat64;
+///     end_idx: opt nat64;
+///     content_substring: opt text;
+///     content_regex: opt text;
+///     traps_only: opt bool;
+/// }
+/// ```
+///
+/// All bounds are inclusive. A record is returned only if it matches every
+/// criterion that is set.
+#[derive(Clone, Eq, PartialEq, Debug, Default, CandidType, Deserialize, Serialize)]
+pub struct CanisterLogFilter {
+    pub start_timestamp_nanos: Option<u64>,
+    pub end_timestamp_nanos: Option<u64>,
+    pub start_idx: Option<u64>,
+    pub end_idx: Option<u64>,
+    pub content_substring: Option<String>,
+    pub content_regex: Option<String>,
+    pub traps_only: Option<bool>,
+}
+
+impl Payload<'_> for CanisterLogFilter {}
+
 /// `CandidType` for `CanisterLogRecord`
 /// ```text
 /// record {
 ///     idx: nat64;
 ///     timestamp_nanos: nat64;
 ///     content: blob;
+///     is_trap: opt bool;
 /// }
 /// ```
 #[derive(Clone, Eq, PartialEq, Debug, Default, CandidType, Deserialize, Serialize)]
@@ -2672,13 +2777,21 @@ pub struct CanisterLogRecord {
     pub timestamp_nanos: u64,
     #[serde(with = "serde_bytes")]
     pub content: Vec<u8>,
+    /// Whether the record was added by the replica because the execution
+    /// trapped, rather than by the canister itself.
+    pub is_trap: Option<bool>,
 }
 
 impl Payload<'_> for CanisterLogRecord {}
 
 impl DataSize for CanisterLogRecord {
     fn data_size(&self) -> usize {
-        std::mem::size_of::<Self>() + self.content.as_slice().data_size()
+        // The trap flag is not counted, so that it does not reduce the
+        // number of records that fit into the canister log.
+        self.idx.data_size()
+            + self.timestamp_nanos.data_size()
+            + std::mem::size_of::<Vec<u8>>()
+            + self.content.as_slice().data_size()
     }
 }
 
@@ -2688,6 +2801,7 @@ fn test_canister_log_record_data_size() {
         idx: 100,
         timestamp_nanos: 200,
         content: vec![1, 2, 3],
+        is_trap: None,
     };
     assert_eq!(record.data_size(), 8 + 8 + 24 + 3);
 }
@@ -2698,6 +2812,7 @@ impl From<&CanisterLogRecord> for pb_canister_state_bits::CanisterLogRecord {
             idx: item.idx,
             timestamp_nanos: item.timestamp_nanos,
             content: item.content.clone(),
+            is_trap: item.is_trap.unwrap_or_default(),
         }
     }
 }
@@ -2708,6 +2823,7 @@ impl From<pb_canister_state_bits::CanisterLogRecord> for CanisterLogRecord {
             idx: item.idx,
             timestamp_nanos: item.timestamp_nanos,
             content: item.content,
+            is_trap: Some(item.is_trap),
         }
     }
 }
@@ -2716,11 +2832,16 @@ impl From<pb_canister_state_bits::CanisterLogRecord> for CanisterLogRecord {
 /// ```text
 /// record {
 ///     canister_log_records: vec canister_log_record;
+///     next_idx: opt nat64;
 /// }
 /// ```
 #[derive(Clone, PartialEq, Debug, Default, CandidType, Deserialize)]
 pub struct FetchCanisterLogsResponse {
     pub canister_log_records: Vec<CanisterLogRecord>,
+    /// Index to pass as `start_idx` in the next request to continue tailing
+    /// the log. It points past the last record that was inspected, so that
+    /// records skipped by the filter are not inspected again.
+    pub next_idx: Option<u64>,
 }
 
 impl Payload<'_> for FetchCanisterLogsResponse {}
@@ -3173,6 +3294,343 @@ impl ListCanisterSnapshotArgs {
 
 impl Payload<'_> for ListCanisterSnapshotArgs {}
 
+/// The maximum size of a chunk of snapshot data that can be read or
+/// uploaded in a single call.
+pub const MAX_CANISTER_SNAPSHOT_DATA_CHUNK_SIZE: u64 = 2_000_000;
+
+fn decode_snapshot_id(snapshot_id: &[u8]) -> Result<SnapshotId, UserError> {
+    SnapshotId::try_from(&snapshot_id.to_vec()).map_err(|err| {
+        UserError::new(
+            ErrorCode::InvalidManagementPayload,
+            format!("Payload deserialization error: {err:?}"),
+        )
+    })
+}
+
+/// Struct used for encoding/decoding
+/// `(record {
+///     canister_id: principal;
+///     snapshot_id: blob;
+/// })`
+#[derive(Clone, Eq, PartialEq, Debug, Default, CandidType, Deserialize)]
+pub struct ReadCanisterSnapshotMetadataArgs {
+    canister_id: PrincipalId,
+    #[serde(with = "serde_bytes")]
+    snapshot_id: Vec<u8>,
+}
+
+impl ReadCanisterSnapshotMetadataArgs {
+    pub fn new(canister_id: CanisterId, snapshot_id: SnapshotId) -> Self {
+        Self {
+            canister_id: canister_id.get(),
+            snapshot_id: snapshot_id.to_vec(),
+        }
+    }
+
+    pub fn get_canister_id(&self) -> CanisterId {
+        CanisterId::unchecked_from_principal(self.canister_id)
+    }
+
+    pub fn get_snapshot_id(&self) -> SnapshotId {
+        SnapshotId::try_from(&self.snapshot_id).unwrap()
+    }
+}
+
+impl<'a> Payload<'a> for ReadCanisterSnapshotMetadataArgs {
+    fn decode(blob: &'a [u8]) -> Result<Self, UserError> {
+        let args = Decode!([decoder_config()]; blob, Self).map_err(candid_error_to_user_error)?;
+        // Verify that snapshot ID has the correct format.
+        decode_snapshot_id(&args.snapshot_id)?;
+        Ok(args)
+    }
+}
+
+/// An exported global variable of a canister snapshot.
+/// `variant {
+///     i32: int32;
+///     i64: int64;
+///     f32: float32;
+///     f64: float64;
+///     v128: nat;
+/// }`
+#[derive(Copy, Clone, PartialEq, Debug, CandidType, Deserialize)]
+pub enum SnapshotGlobal {
+    #[serde(rename = "i32")]
+    I32(i32),
+    #[serde(rename = "i64")]
+    I64(i64),
+    #[serde(rename = "f32")]
+    F32(f32),
+    #[serde(rename = "f64")]
+    F64(f64),
+    #[serde(rename = "v128")]
+    V128(u128),
+}
+
+/// Struct to be returned when reading the metadata of a canister snapshot.
+/// `(record {
+///     taken_at_timestamp: nat64;
+///     canister_version: nat64;
+///     wasm_module_size: nat64;
+///     exported_globals: vec global;
+///     wasm_memory_size: nat64;
+///     stable_memory_size: nat64;
+///     wasm_chunk_store: vec record { hash: blob };
+///     certified_data: blob;
+/// })`
+#[derive(Clone, PartialEq, Debug, CandidType, Deserialize)]
+pub struct ReadCanisterSnapshotMetadataResponse {
+    pub taken_at_timestamp: u64,
+    pub canister_version: u64,
+    pub wasm_module_size: u64,
+    pub exported_globals: Vec<SnapshotGlobal>,
+    pub wasm_memory_size: u64,
+    pub stable_memory_size: u64,
+    pub wasm_chunk_store: Vec<ChunkHash>,
+    #[serde(with = "serde_bytes")]
+    pub certified_data: Vec<u8>,
+}
+
+impl Payload<'_> for ReadCanisterSnapshotMetadataResponse {}
+
+/// The part of a canister snapshot to read.
+/// `variant {
+///     wasm_module: record { offset: nat64; size: nat64 };
+///     main_memory: record { offset: nat64; size: nat64 };
+///     stable_memory: record { offset: nat64; size: nat64 };
+///     wasm_chunk: record { hash: blob };
+/// }`
+#[derive(Clone, Eq, PartialEq, Debug, CandidType, Deserialize)]
+pub enum CanisterSnapshotDataKind {
+    #[serde(rename = "wasm_module")]
+    WasmModule { offset: u64, size: u64 },
+    #[serde(rename = "main_memory")]
+    MainMemory { offset: u64, size: u64 },
+    #[serde(rename = "stable_memory")]
+    StableMemory { offset: u64, size: u64 },
+    #[serde(rename = "wasm_chunk")]
+    WasmChunk {
+        #[serde(with = "serde_bytes")]
+        hash: Vec<u8>,
+    },
+}
+
+/// Struct used for encoding/decoding
+/// `(record {
+///     canister_id: principal;
+///     snapshot_id: blob;
+///     kind: snapshot_data_kind;
+/// })`
+#[derive(Clone, Eq, PartialEq, Debug, CandidType, Deserialize)]
+pub struct ReadCanisterSnapshotDataArgs {
+    canister_id: PrincipalId,
+    #[serde(with = "serde_bytes")]
+    snapshot_id: Vec<u8>,
+    pub kind: CanisterSnapshotDataKind,
+}
+
+impl ReadCanisterSnapshotDataArgs {
+    pub fn new(
+        canister_id: CanisterId,
+        snapshot_id: SnapshotId,
+        kind: CanisterSnapshotDataKind,
+    ) -> Self {
+        Self {
+            canister_id: canister_id.get(),
+            snapshot_id: snapshot_id.to_vec(),
+            kind,
+        }
+    }
+
+    pub fn get_canister_id(&self) -> CanisterId {
+        CanisterId::unchecked_from_principal(self.canister_id)
+    }
+
+    pub fn get_snapshot_id(&self) -> SnapshotId {
+        SnapshotId::try_from(&self.snapshot_id).unwrap()
+    }
+}
+
+impl<'a> Payload<'a> for ReadCanisterSnapshotDataArgs {
+    fn decode(blob: &'a [u8]) -> Result<Self, UserError> {
+        let args = Decode!([decoder_config()]; blob, Self).map_err(candid_error_to_user_error)?;
+        // Verify that snapshot ID has the correct format.
+        decode_snapshot_id(&args.snapshot_id)?;
+        Ok(args)
+    }
+}
+
+/// Struct to be returned when reading data of a canister snapshot.
+/// `(record {
+///     chunk: blob;
+/// })`
+#[derive(Clone, Eq, PartialEq, Debug, Default, CandidType, Deserialize)]
+pub struct ReadCanisterSnapshotDataResponse {
+    #[serde(with = "serde_bytes")]
+    pub chunk: Vec<u8>,
+}
+
+impl Payload<'_> for ReadCanisterSnapshotDataResponse {}
+
+/// Struct used for encoding/decoding
+/// `(record {
+///     canister_id: principal;
+///     replace_snapshot: opt blob;
+///     wasm_module_size: nat64;
+///     exported_globals: vec global;
+///     wasm_memory_size: nat64;
+///     stable_memory_size: nat64;
+///     certified_data: blob;
+/// })`
+#[derive(Clone, PartialEq, Debug, Default, CandidType, Deserialize)]
+pub struct UploadCanisterSnapshotMetadataArgs {
+    pub canister_id: PrincipalId,
+    pub replace_snapshot: Option<serde_bytes::ByteBuf>,
+    pub wasm_module_size: u64,
+    pub exported_globals: Vec<SnapshotGlobal>,
+    pub wasm_memory_size: u64,
+    pub stable_memory_size: u64,
+    #[serde(with = "serde_bytes")]
+    pub certified_data: Vec<u8>,
+}
+
+impl UploadCanisterSnapshotMetadataArgs {
+    pub fn new(
+        canister_id: CanisterId,
+        replace_snapshot: Option<SnapshotId>,
+        wasm_module_size: u64,
+        exported_globals: Vec<SnapshotGlobal>,
+        wasm_memory_size: u64,
+        stable_memory_size: u64,
+        certified_data: Vec<u8>,
+    ) -> Self {
+        Self {
+            canister_id: canister_id.get(),
+            replace_snapshot: replace_snapshot
+                .map(|snapshot_id| ByteBuf::from(snapshot_id.to_vec())),
+            wasm_module_size,
+            exported_globals,
+            wasm_memory_size,
+            stable_memory_size,
+            certified_data,
+        }
+    }
+
+    pub fn get_canister_id(&self) -> CanisterId {
+        CanisterId::unchecked_from_principal(self.canister_id)
+    }
+
+    pub fn replace_snapshot(&self) -> Option<SnapshotId> {
+        self.replace_snapshot
+            .as_ref()
+            .map(|bytes| SnapshotId::try_from(&bytes.clone().into_vec()).unwrap())
+    }
+}
+
+impl<'a> Payload<'a> for UploadCanisterSnapshotMetadataArgs {
+    fn decode(blob: &'a [u8]) -> Result<Self, UserError> {
+        let args = Decode!([decoder_config()]; blob, Self).map_err(candid_error_to_user_error)?;
+        if let Some(replace_snapshot) = &args.replace_snapshot {
+            // Verify that snapshot ID has the correct format.
+            decode_snapshot_id(replace_snapshot)?;
+        }
+        Ok(args)
+    }
+}
+
+/// Struct to be returned when uploading the metadata of a canister snapshot.
+/// `(record {
+///     snapshot_id: blob;
+/// })`
+#[derive(Clone, Eq, PartialEq, Debug, Default, CandidType, Deserialize)]
+pub struct UploadCanisterSnapshotMetadataResponse {
+    #[serde(with = "serde_bytes")]
+    pub snapshot_id: Vec<u8>,
+}
+
+impl Payload<'_> for UploadCanisterSnapshotMetadataResponse {}
+
+impl UploadCanisterSnapshotMetadataResponse {
+    pub fn new(snapshot_id: &SnapshotId) -> Self {
+        Self {
+            snapshot_id: snapshot_id.to_vec(),
+        }
+    }
+
+    pub fn get_snapshot_id(&self) -> SnapshotId {
+        SnapshotId::try_from(&self.snapshot_id).unwrap()
+    }
+}
+
+/// The part of a canister snapshot to write.
+/// `variant {
+///     wasm_module: record { offset: nat64 };
+///     main_memory: record { offset: nat64 };
+///     stable_memory: record { offset: nat64 };
+///     wasm_chunk;
+/// }`
+#[derive(Clone, Eq, PartialEq, Debug, CandidType, Deserialize)]
+pub enum CanisterSnapshotDataOffset {
+    #[serde(rename = "wasm_module")]
+    WasmModule { offset: u64 },
+    #[serde(rename = "main_memory")]
+    MainMemory { offset: u64 },
+    #[serde(rename = "stable_memory")]
+    StableMemory { offset: u64 },
+    #[serde(rename = "wasm_chunk")]
+    WasmChunk,
+}
+
+/// Struct used for encoding/decoding
+/// `(record {
+///     canister_id: principal;
+///     snapshot_id: blob;
+///     kind: snapshot_data_offset;
+///     chunk: blob;
+/// })`
+#[derive(Clone, Eq, PartialEq, Debug, CandidType, Deserialize)]
+pub struct UploadCanisterSnapshotDataArgs {
+    canister_id: PrincipalId,
+    #[serde(with = "serde_bytes")]
+    snapshot_id: Vec<u8>,
+    pub kind: CanisterSnapshotDataOffset,
+    #[serde(with = "serde_bytes")]
+    pub chunk: Vec<u8>,
+}
+
+impl UploadCanisterSnapshotDataArgs {
+    pub fn new(
+        canister_id: CanisterId,
+        snapshot_id: SnapshotId,
+        kind: CanisterSnapshotDataOffset,
+        chunk: Vec<u8>,
+    ) -> Self {
+        Self {
+            canister_id: canister_id.get(),
+            snapshot_id: snapshot_id.to_vec(),
+            kind,
+            chunk,
+        }
+    }
+
+    pub fn get_canister_id(&self) -> CanisterId {
+        CanisterId::unchecked_from_principal(self.canister_id)
+    }
+
+    pub fn get_snapshot_id(&self) -> SnapshotId {
+        SnapshotId::try_from(&self.snapshot_id).unwrap()
+    }
+}
+
+impl<'a> Payload<'a> for UploadCanisterSnapshotDataArgs {
+    fn decode(blob: &'a [u8]) -> Result<Self, UserError> {
+        let args = Decode!([decoder_config()]; blob, Self).map_err(candid_error_to_user_error)?;
+        // Verify that snapshot ID has the correct format.
+        decode_snapshot_id(&args.snapshot_id)?;
+        Ok(args)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
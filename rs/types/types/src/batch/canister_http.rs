@@ -1,8 +1,8 @@
 use crate::{
     canister_http::{
         CanisterHttpReject, CanisterHttpRequestId, CanisterHttpResponse,
-        CanisterHttpResponseContent, CanisterHttpResponseDivergence, CanisterHttpResponseMetadata,
-        CanisterHttpResponseShare, CanisterHttpResponseWithConsensus,
+        CanisterHttpResponseArtifact, CanisterHttpResponseContent, CanisterHttpResponseDivergence,
+        CanisterHttpResponseMetadata, CanisterHttpResponseShare, CanisterHttpResponseWithConsensus,
     },
     crypto::{BasicSig, BasicSigOf, CryptoHash, CryptoHashOf, Signed},
     messages::CallbackId,
@@ -48,17 +48,37 @@ impl CanisterHttpPayload {
     }
 }
 
+impl From<&CanisterHttpResponse> for pb::CanisterHttpResponse {
+    fn from(response: &CanisterHttpResponse) -> Self {
+        pb::CanisterHttpResponse {
+            id: response.id.get(),
+            timeout: response.timeout.as_nanos_since_unix_epoch(),
+            content: Some(pb::CanisterHttpResponseContent::from(&response.content)),
+            canister_id: Some(pb::CanisterId::from(response.canister_id)),
+        }
+    }
+}
+
+impl TryFrom<pb::CanisterHttpResponse> for CanisterHttpResponse {
+    type Error = ProxyDecodeError;
+
+    fn try_from(response: pb::CanisterHttpResponse) -> Result<Self, Self::Error> {
+        Ok(CanisterHttpResponse {
+            id: CanisterHttpRequestId::new(response.id),
+            timeout: Time::from_nanos_since_unix_epoch(response.timeout),
+            canister_id: try_from_option_field(
+                response.canister_id,
+                "CanisterHttpResponse::canister_id",
+            )?,
+            content: try_from_option_field(response.content, "CanisterHttpResponse::content")?,
+        })
+    }
+}
+
 impl From<&CanisterHttpResponseWithConsensus> for pb::CanisterHttpResponseWithConsensus {
     fn from(payload: &CanisterHttpResponseWithConsensus) -> Self {
         pb::CanisterHttpResponseWithConsensus {
-            response: Some(pb::CanisterHttpResponse {
-                id: payload.content.id.get(),
-                timeout: payload.content.timeout.as_nanos_since_unix_epoch(),
-                content: Some(pb::CanisterHttpResponseContent::from(
-                    &payload.content.content,
-                )),
-                canister_id: Some(pb::CanisterId::from(payload.content.canister_id)),
-            }),
+            response: Some(pb::CanisterHttpResponse::from(&payload.content)),
             hash: payload.proof.content.content_hash.clone().get().0,
             registry_version: payload.proof.content.registry_version.get(),
             signatures: payload
@@ -243,6 +263,28 @@ impl TryFrom<pb::CanisterHttpShare> for CanisterHttpResponseShare {
     }
 }
 
+impl From<CanisterHttpResponseArtifact> for pb::CanisterHttpArtifact {
+    fn from(artifact: CanisterHttpResponseArtifact) -> Self {
+        pb::CanisterHttpArtifact {
+            share: Some(artifact.share.into()),
+            response: artifact
+                .response
+                .as_ref()
+                .map(pb::CanisterHttpResponse::from),
+        }
+    }
+}
+
+impl TryFrom<pb::CanisterHttpArtifact> for CanisterHttpResponseArtifact {
+    type Error = ProxyDecodeError;
+    fn try_from(artifact: pb::CanisterHttpArtifact) -> Result<Self, Self::Error> {
+        Ok(CanisterHttpResponseArtifact {
+            share: try_from_option_field(artifact.share, "CanisterHttpArtifact::share")?,
+            response: artifact.response.map(TryFrom::try_from).transpose()?,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -265,6 +307,7 @@ mod tests {
                             value: "value1".to_string()
                         }],
                         body: b"Test data in body".to_vec(),
+                        is_replicated: None,
                     })
                     .unwrap(),
                 ),
@@ -317,4 +360,39 @@ mod tests {
         let new_payload = CanisterHttpResponseDivergence::try_from(pb_payload).unwrap();
         assert_eq!(payload, new_payload);
     }
+
+    /// Tests, whether a roundtrip of protobuf conversions generates the same
+    /// `CanisterHttpResponseArtifact`
+    #[test]
+    fn canister_http_response_artifact_conversion() {
+        let share = Signed {
+            content: CanisterHttpResponseMetadata {
+                id: CanisterHttpRequestId::new(1),
+                timeout: Time::from_nanos_since_unix_epoch(1234),
+                content_hash: CryptoHashOf::<CanisterHttpResponse>::new(CryptoHash(vec![
+                    0, 1, 2, 3,
+                ])),
+                registry_version: RegistryVersion::new(1),
+            },
+            signature: BasicSignature {
+                signer: NodeId::from(PrincipalId::new_node_test_id(1)),
+                signature: BasicSigOf::new(BasicSig(vec![0, 1, 2, 3])),
+            },
+        };
+        let response = CanisterHttpResponse {
+            id: CanisterHttpRequestId::new(1),
+            timeout: Time::from_nanos_since_unix_epoch(1234),
+            canister_id: crate::CanisterId::from(1),
+            content: CanisterHttpResponseContent::Success(b"Test data".to_vec()),
+        };
+        for response in [None, Some(response)] {
+            let artifact = CanisterHttpResponseArtifact {
+                share: share.clone(),
+                response,
+            };
+            let pb_artifact = pb::CanisterHttpArtifact::from(artifact.clone());
+            let new_artifact = CanisterHttpResponseArtifact::try_from(pb_artifact).unwrap();
+            assert_eq!(artifact, new_artifact);
+        }
+    }
 }
@@ -45,15 +45,18 @@ use crate::{
     artifact::{CanisterHttpResponseId, IdentifiableArtifact, PbArtifact},
     crypto::{CryptoHashOf, Signed},
     messages::{CallbackId, RejectContext, Request},
+    node_id_into_protobuf, node_id_try_from_option,
     signature::*,
-    CanisterId, CountBytes, RegistryVersion, Time,
+    CanisterId, CountBytes, NodeId, RegistryVersion, Time,
 };
+use candid::{Decode, Encode};
 use ic_base_types::{NumBytes, PrincipalId};
 use ic_error_types::{ErrorCode, RejectCode, UserError};
 #[cfg(test)]
 use ic_exhaustive_derive::ExhaustiveSet;
 use ic_management_canister_types::{
-    CanisterHttpRequestArgs, HttpHeader, HttpMethod, TransformContext,
+    CanisterHttpRequestArgs, CanisterHttpResponsePayload, HttpHeader, HttpMethod,
+    HttpResponseRange, TransformContext,
 };
 use ic_protobuf::{
     proxy::{try_from_option_field, ProxyDecodeError},
@@ -82,6 +85,12 @@ pub const MAX_CANISTER_HTTP_REQUEST_BYTES: u64 = 2_000_000;
 /// Maximum number of response bytes for a canister http request.
 pub const MAX_CANISTER_HTTP_RESPONSE_BYTES: u64 = 2_000_000;
 
+/// Number of response bytes a ranged canister http request is charged for in
+/// addition to the requested range. It bounds the charge for downloading the
+/// full body, which is cached by the adapter and shared by all ranges that are
+/// requested from the same response.
+pub const CANISTER_HTTP_RANGED_FETCH_CHARGED_BYTES: u64 = MAX_CANISTER_HTTP_RESPONSE_BYTES;
+
 /// Maximum number of bytes to represent URL for a canister http request.
 pub const MAX_CANISTER_HTTP_URL_SIZE: usize = 8192;
 
@@ -114,6 +123,17 @@ impl From<TransformContext> for Transform {
     }
 }
 
+/// Specifies which nodes make a canister http request.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Deserialize, Serialize)]
+pub enum Replication {
+    /// All nodes of the subnet make the request and the response only gets
+    /// delivered once enough nodes agree on it.
+    FullyReplicated,
+    /// Only the given node makes the request and its response gets delivered
+    /// without being attested by the other nodes.
+    NonReplicated(NodeId),
+}
+
 #[derive(Clone, Eq, PartialEq, Hash, Debug, Deserialize, Serialize)]
 pub struct CanisterHttpRequestContext {
     pub request: Request,
@@ -125,6 +145,10 @@ pub struct CanisterHttpRequestContext {
     pub http_method: CanisterHttpMethod,
     pub transform: Option<Transform>,
     pub time: Time,
+    /// The byte range of the response body that the adapter returns, if the
+    /// canister only asked for part of the response.
+    pub response_range: Option<HttpResponseRange>,
+    pub replication: Replication,
 }
 
 impl From<&CanisterHttpRequestContext> for pb_metadata::CanisterHttpRequestContext {
@@ -155,6 +179,16 @@ impl From<&CanisterHttpRequestContext> for pb_metadata::CanisterHttpRequestConte
                 .map(|transform| transform.context.clone()),
             http_method: pb_metadata::HttpMethod::from(&context.http_method).into(),
             time: context.time.as_nanos_since_unix_epoch(),
+            response_range: context
+                .response_range
+                .map(|range| pb_metadata::HttpResponseRange {
+                    offset: range.offset,
+                    length: range.length,
+                }),
+            non_replicated_node_id: match context.replication {
+                Replication::FullyReplicated => None,
+                Replication::NonReplicated(node_id) => Some(node_id_into_protobuf(node_id)),
+            },
         }
     }
 }
@@ -213,6 +247,14 @@ impl TryFrom<pb_metadata::CanisterHttpRequestContext> for CanisterHttpRequestCon
                 .try_into()?,
             transform,
             time: Time::from_nanos_since_unix_epoch(context.time),
+            response_range: context.response_range.map(|range| HttpResponseRange {
+                offset: range.offset,
+                length: range.length,
+            }),
+            replication: match context.non_replicated_node_id {
+                None => Replication::FullyReplicated,
+                node_id => Replication::NonReplicated(node_id_try_from_option(node_id)?),
+            },
         })
     }
 }
@@ -302,6 +344,14 @@ impl TryFrom<(Time, &Request, CanisterHttpRequestArgs)> for CanisterHttpRequestC
             None => Ok(None),
         }?;
 
+        if let Some(range) = args.response_range {
+            if range.length == 0 || range.length > MAX_CANISTER_HTTP_RESPONSE_BYTES {
+                return Err(CanisterHttpRequestContextError::ResponseRangeLength(
+                    range.length,
+                ));
+            }
+        }
+
         let url_len = args.url.len();
         if url_len > MAX_CANISTER_HTTP_URL_SIZE {
             return Err(CanisterHttpRequestContextError::UrlTooLong(url_len));
@@ -335,11 +385,29 @@ impl TryFrom<(Time, &Request, CanisterHttpRequestArgs)> for CanisterHttpRequestC
             },
             transform: args.transform.map(From::from),
             time,
+            response_range: args.response_range,
+            // Non-replicated requests are assigned to a node by execution.
+            replication: Replication::FullyReplicated,
         })
     }
 }
 
 impl CanisterHttpRequestContext {
+    /// Returns the number of response bytes the request is charged for. This
+    /// is the length of the requested range plus
+    /// `CANISTER_HTTP_RANGED_FETCH_CHARGED_BYTES` for ranged requests and
+    /// `max_response_bytes` otherwise.
+    pub fn charged_response_bytes(&self) -> Option<NumBytes> {
+        match &self.response_range {
+            Some(range) => Some(NumBytes::from(
+                range
+                    .length
+                    .saturating_add(CANISTER_HTTP_RANGED_FETCH_CHARGED_BYTES),
+            )),
+            None => self.max_response_bytes,
+        }
+    }
+
     /// Calculate the size of all unbounded struct elements.
     pub fn variable_parts_size(&self) -> NumBytes {
         let request_size = self.url.len()
@@ -378,6 +446,7 @@ pub struct InvalidTransformPrincipalId {
 #[derive(Debug)]
 pub enum CanisterHttpRequestContextError {
     MaxResponseBytes(InvalidMaxResponseBytes),
+    ResponseRangeLength(u64),
     TransformPrincipalId(InvalidTransformPrincipalId),
     UrlTooLong(usize),
     TooManyHeaders(usize),
@@ -397,6 +466,13 @@ impl From<CanisterHttpRequestContextError> for UserError {
                     err.min, err.max, err.given
                 ),
             ),
+            CanisterHttpRequestContextError::ResponseRangeLength(length) => UserError::new(
+                ErrorCode::CanisterRejectedMessage,
+                format!(
+                    "response_range length expected to be in the range [1..{}], got {}",
+                    MAX_CANISTER_HTTP_RESPONSE_BYTES, length
+                ),
+            ),
             CanisterHttpRequestContextError::TransformPrincipalId(err) => UserError::new(
                 ErrorCode::CanisterRejectedMessage,
                 format!(
@@ -486,6 +562,28 @@ pub enum CanisterHttpResponseContent {
     Reject(CanisterHttpReject),
 }
 
+impl CanisterHttpResponseContent {
+    /// Returns whether a successful response is marked as not attested by the
+    /// other nodes of the subnet, see [`mark_unattested`].
+    pub fn is_marked_unattested(&self) -> bool {
+        match self {
+            CanisterHttpResponseContent::Success(payload) => {
+                Decode!(payload, CanisterHttpResponsePayload)
+                    .is_ok_and(|response| response.is_replicated == Some(false))
+            }
+            CanisterHttpResponseContent::Reject(_) => false,
+        }
+    }
+}
+
+/// Marks the candid encoded [`CanisterHttpResponsePayload`] of a response to a
+/// non-replicated request as not attested by the other nodes of the subnet.
+pub fn mark_unattested(payload: &[u8]) -> Result<Vec<u8>, candid::Error> {
+    let mut response = Decode!(payload, CanisterHttpResponsePayload)?;
+    response.is_replicated = Some(false);
+    Encode!(&response)
+}
+
 impl CountBytes for CanisterHttpResponseContent {
     fn count_bytes(&self) -> usize {
         match self {
@@ -612,23 +710,33 @@ impl crate::crypto::SignedBytesWithoutDomainSeparator for CanisterHttpResponseMe
 }
 
 /// A signature share of of [`CanisterHttpResponseMetadata`].
-///
-/// This is the artifact that will actually be gossiped.
 pub type CanisterHttpResponseShare =
     Signed<CanisterHttpResponseMetadata, BasicSignature<CanisterHttpResponseMetadata>>;
 
-impl IdentifiableArtifact for CanisterHttpResponseShare {
+/// The artifact that will actually be gossiped.
+///
+/// It consists of a [`CanisterHttpResponseShare`] and, for non-replicated
+/// requests, of the response itself. Only the node that made a non-replicated
+/// request has its response, so it is gossiped along with the share to allow
+/// any block maker to include it.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Deserialize, Serialize)]
+pub struct CanisterHttpResponseArtifact {
+    pub share: CanisterHttpResponseShare,
+    pub response: Option<CanisterHttpResponse>,
+}
+
+impl IdentifiableArtifact for CanisterHttpResponseArtifact {
     const NAME: &'static str = "canisterhttp";
     type Id = CanisterHttpResponseId;
     fn id(&self) -> Self::Id {
-        self.clone()
+        self.share.clone()
     }
 }
 
-impl PbArtifact for CanisterHttpResponseShare {
+impl PbArtifact for CanisterHttpResponseArtifact {
     type PbId = ic_protobuf::types::v1::CanisterHttpShare;
     type PbIdError = ProxyDecodeError;
-    type PbMessage = ic_protobuf::types::v1::CanisterHttpShare;
+    type PbMessage = ic_protobuf::types::v1::CanisterHttpArtifact;
     type PbMessageError = ProxyDecodeError;
 }
 
@@ -676,6 +784,8 @@ mod tests {
                 deadline: NO_DEADLINE,
             },
             time: UNIX_EPOCH,
+            response_range: None,
+            replication: Replication::FullyReplicated,
         };
 
         let expected_size = context.url.len()
@@ -718,6 +828,8 @@ mod tests {
                 deadline: NO_DEADLINE,
             },
             time: UNIX_EPOCH,
+            response_range: None,
+            replication: Replication::FullyReplicated,
         };
 
         let expected_size = context.url.len()
@@ -730,6 +842,120 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_response_range_length_is_validated() {
+        let request = Request {
+            receiver: CanisterId::ic_00(),
+            sender: CanisterId::ic_00(),
+            sender_reply_callback: CallbackId::from(3),
+            payment: Cycles::new(10),
+            method_name: "http_request".to_string(),
+            method_payload: Vec::new(),
+            metadata: None,
+            deadline: NO_DEADLINE,
+        };
+        let args = |length| CanisterHttpRequestArgs {
+            url: "https://example.com".to_string(),
+            max_response_bytes: None,
+            headers: Default::default(),
+            body: None,
+            method: HttpMethod::GET,
+            transform: None,
+            response_range: Some(HttpResponseRange { offset: 0, length }),
+            is_replicated: None,
+        };
+
+        for length in [1, MAX_CANISTER_HTTP_RESPONSE_BYTES] {
+            let context =
+                CanisterHttpRequestContext::try_from((UNIX_EPOCH, &request, args(length))).unwrap();
+            assert_eq!(
+                context.response_range,
+                Some(HttpResponseRange { offset: 0, length })
+            );
+            assert_eq!(
+                context.charged_response_bytes(),
+                Some(NumBytes::from(
+                    length + CANISTER_HTTP_RANGED_FETCH_CHARGED_BYTES
+                ))
+            );
+
+            let round_trip = CanisterHttpRequestContext::try_from(
+                pb_metadata::CanisterHttpRequestContext::from(&context),
+            )
+            .unwrap();
+            assert_eq!(context, round_trip);
+        }
+
+        for length in [0, MAX_CANISTER_HTTP_RESPONSE_BYTES + 1] {
+            assert!(matches!(
+                CanisterHttpRequestContext::try_from((UNIX_EPOCH, &request, args(length))),
+                Err(CanisterHttpRequestContextError::ResponseRangeLength(given)) if given == length
+            ));
+        }
+    }
+
+    #[test]
+    fn test_replication_proto_round_trip() {
+        for replication in [
+            Replication::FullyReplicated,
+            Replication::NonReplicated(NodeId::from(PrincipalId::new_node_test_id(7))),
+        ] {
+            let context = CanisterHttpRequestContext {
+                url: "https://example.com".to_string(),
+                headers: vec![],
+                body: None,
+                max_response_bytes: None,
+                http_method: CanisterHttpMethod::POST,
+                transform: None,
+                request: Request {
+                    receiver: CanisterId::ic_00(),
+                    sender: CanisterId::ic_00(),
+                    sender_reply_callback: CallbackId::from(3),
+                    payment: Cycles::new(10),
+                    method_name: "http_request".to_string(),
+                    method_payload: Vec::new(),
+                    metadata: None,
+                    deadline: NO_DEADLINE,
+                },
+                time: UNIX_EPOCH,
+                response_range: None,
+                replication,
+            };
+
+            let round_trip = CanisterHttpRequestContext::try_from(
+                pb_metadata::CanisterHttpRequestContext::from(&context),
+            )
+            .unwrap();
+            assert_eq!(context, round_trip);
+        }
+    }
+
+    #[test]
+    fn test_mark_unattested() {
+        let payload = Encode!(&CanisterHttpResponsePayload {
+            status: 200,
+            headers: vec![],
+            body: b"body".to_vec(),
+            is_replicated: None,
+        })
+        .unwrap();
+        assert!(!CanisterHttpResponseContent::Success(payload.clone()).is_marked_unattested());
+
+        let marked = mark_unattested(&payload).unwrap();
+        assert!(CanisterHttpResponseContent::Success(marked.clone()).is_marked_unattested());
+        assert_eq!(
+            Decode!(&marked, CanisterHttpResponsePayload).unwrap().body,
+            b"body".to_vec()
+        );
+
+        assert!(mark_unattested(b"not candid").is_err());
+        assert!(!CanisterHttpResponseContent::Reject(CanisterHttpReject {
+            reject_code: RejectCode::SysFatal,
+            message: String::new(),
+        })
+        .is_marked_unattested());
+    }
+
     #[test]
     fn canister_http_method_proto_round_trip() {
         for initial in CanisterHttpMethod::iter() {
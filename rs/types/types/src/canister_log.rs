@@ -10,7 +10,7 @@ pub const MAX_ALLOWED_CANISTER_LOG_BUFFER_SIZE: usize = 4 * 1024;
 
 fn truncate_content(mut record: CanisterLogRecord) -> CanisterLogRecord {
     let max_content_size =
-        MAX_ALLOWED_CANISTER_LOG_BUFFER_SIZE - std::mem::size_of::<CanisterLogRecord>();
+        MAX_ALLOWED_CANISTER_LOG_BUFFER_SIZE - CanisterLogRecord::default().data_size();
     record.content.truncate(max_content_size);
     record
 }
@@ -156,11 +156,21 @@ impl CanisterLog {
 
     /// Adds a new log record.
     pub fn add_record(&mut self, timestamp_nanos: u64, content: Vec<u8>) {
+        self.push_record(timestamp_nanos, content, false);
+    }
+
+    /// Adds a new log record describing a trap of the execution.
+    pub fn add_trap_record(&mut self, timestamp_nanos: u64, content: Vec<u8>) {
+        self.push_record(timestamp_nanos, content, true);
+    }
+
+    fn push_record(&mut self, timestamp_nanos: u64, content: Vec<u8>, is_trap: bool) {
         // Add record and update the next index.
         self.records.push_back(truncate_content(CanisterLogRecord {
             idx: self.next_idx,
             timestamp_nanos,
             content,
+            is_trap: Some(is_trap),
         }));
         self.next_idx += 1;
     }
@@ -190,6 +200,7 @@ mod tests {
                 idx,
                 timestamp_nanos,
                 content: content.to_vec(),
+                is_trap: Some(false),
             })
             .collect()
     }
@@ -114,6 +114,25 @@ impl CanisterModule {
     pub fn module_hash(&self) -> [u8; WASM_HASH_LENGTH] {
         self.module_hash
     }
+
+    /// Overwrites the bytes of this module starting at `offset`. The module
+    /// is copied on write: a file-backed module is first copied to memory,
+    /// so the backing file, which may be hardlinked into a checkpoint, is
+    /// never modified, and an in-memory module shared with another
+    /// `CanisterModule` is copied before it is modified.
+    ///
+    /// Panics if the bytes do not fit in the module.
+    pub fn write(&mut self, offset: usize, bytes: &[u8]) {
+        if let ModuleStorage::File(_, _) = self.module {
+            self.module = ModuleStorage::Memory(Arc::new(self.as_slice().to_vec()));
+        }
+        let ModuleStorage::Memory(shared) = &mut self.module else {
+            unreachable!("The module was copied to memory above.");
+        };
+        let module = Arc::make_mut(shared);
+        module[offset..offset + bytes.len()].copy_from_slice(bytes);
+        self.module_hash = ic_crypto_sha2::Sha256::hash(module);
+    }
 }
 
 impl fmt::Debug for CanisterModule {